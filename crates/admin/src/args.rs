@@ -0,0 +1,321 @@
+//! Command-line parsing
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+pub const USAGE: &str = "\
+Usage: paperforge-admin <command> [options]
+
+Commands:
+  create-tenant <name> [--scopes read,write] [--rate-limit RPS]
+  issue-key <tenant-id> [--scopes read,write] [--expires-in-days N]
+  revoke-key <tenant-id>
+  signing-secret <tenant-id> [--disable]
+  dlq list [--queue ingestion|embedding]
+  dlq redrive [--queue ingestion|embedding] [--max N]
+  reembed <tenant-id> [--paper ID] [--chunk-size N] [--chunk-overlap N]
+  reindex [--tenant ID] [--no-text-search] [--vector-indexes]
+  export <tenant-id> [--include-embeddings] [--output FILE]
+  usage-report [--tenant ID] [--since RFC3339]";
+
+/// Requests per second for new tenants, as in the `tenants` table default
+const DEFAULT_RATE_LIMIT_RPS: i32 = 100;
+
+/// DLQ messages moved per redrive unless `--max` says otherwise
+const DEFAULT_REDRIVE_MAX: usize = 100;
+
+/// Queue whose dead letters a `dlq` command works on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueName {
+    Ingestion,
+    Embedding,
+}
+
+impl FromStr for QueueName {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "ingestion" => Ok(QueueName::Ingestion),
+            "embedding" => Ok(QueueName::Embedding),
+            _ => bail!("Unknown queue '{}', expected ingestion or embedding", s),
+        }
+    }
+}
+
+/// An admin command with its options
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    CreateTenant {
+        name: String,
+        scopes: Option<Vec<String>>,
+        rate_limit_rps: i32,
+    },
+    IssueKey {
+        tenant_id: Uuid,
+        scopes: Option<Vec<String>>,
+        expires_in_days: Option<u32>,
+    },
+    RevokeKey {
+        tenant_id: Uuid,
+    },
+    /// Issue a new request signing secret, or disable signing
+    SigningSecret {
+        tenant_id: Uuid,
+        disable: bool,
+    },
+    DlqList {
+        queue: QueueName,
+    },
+    DlqRedrive {
+        queue: QueueName,
+        max_messages: usize,
+    },
+    Reembed {
+        tenant_id: Uuid,
+        paper_id: Option<Uuid>,
+        chunk_size: Option<usize>,
+        chunk_overlap: Option<usize>,
+    },
+    Reindex {
+        tenant_id: Option<Uuid>,
+        text_search: bool,
+        vector_indexes: bool,
+    },
+    Export {
+        tenant_id: Uuid,
+        include_embeddings: bool,
+        output: Option<String>,
+    },
+    UsageReport {
+        tenant_id: Option<Uuid>,
+        since: Option<DateTime<Utc>>,
+    },
+}
+
+impl Command {
+    /// Parse the arguments after the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut args = args.into_iter();
+        let command = args.next().context("No command given")?;
+
+        let command = match command.as_str() {
+            "create-tenant" => {
+                let mut opts = Options::parse(args, &["--scopes", "--rate-limit"], &[])?;
+                Command::CreateTenant {
+                    name: opts.positional("tenant name")?,
+                    scopes: opts.take("--scopes").map(|s| scope_list(&s)),
+                    rate_limit_rps: opts.parsed("--rate-limit")?.unwrap_or(DEFAULT_RATE_LIMIT_RPS),
+                }
+            }
+            "issue-key" => {
+                let mut opts = Options::parse(args, &["--scopes", "--expires-in-days"], &[])?;
+                Command::IssueKey {
+                    tenant_id: opts.positional("tenant ID")?.parse().context("Invalid tenant ID")?,
+                    scopes: opts.take("--scopes").map(|s| scope_list(&s)),
+                    expires_in_days: opts.parsed("--expires-in-days")?,
+                }
+            }
+            "revoke-key" => {
+                let mut opts = Options::parse(args, &[], &[])?;
+                Command::RevokeKey {
+                    tenant_id: opts.positional("tenant ID")?.parse().context("Invalid tenant ID")?,
+                }
+            }
+            "signing-secret" => {
+                let mut opts = Options::parse(args, &[], &["--disable"])?;
+                Command::SigningSecret {
+                    tenant_id: opts.positional("tenant ID")?.parse().context("Invalid tenant ID")?,
+                    disable: opts.flag("--disable"),
+                }
+            }
+            "dlq" => {
+                let action = args.next().context("dlq needs an action: list or redrive")?;
+                let mut opts = Options::parse(args, &["--queue", "--max"], &[])?;
+                let queue = opts.parsed("--queue")?.unwrap_or(QueueName::Ingestion);
+                match action.as_str() {
+                    "list" => Command::DlqList { queue },
+                    "redrive" => Command::DlqRedrive {
+                        queue,
+                        max_messages: opts.parsed("--max")?.unwrap_or(DEFAULT_REDRIVE_MAX),
+                    },
+                    _ => bail!("Unknown dlq action '{}', expected list or redrive", action),
+                }
+            }
+            "reembed" => {
+                let mut opts = Options::parse(args, &["--paper", "--chunk-size", "--chunk-overlap"], &[])?;
+                Command::Reembed {
+                    tenant_id: opts.positional("tenant ID")?.parse().context("Invalid tenant ID")?,
+                    paper_id: opts.parsed("--paper")?,
+                    chunk_size: opts.parsed("--chunk-size")?,
+                    chunk_overlap: opts.parsed("--chunk-overlap")?,
+                }
+            }
+            "reindex" => {
+                let mut opts = Options::parse(args, &["--tenant"], &["--no-text-search", "--vector-indexes"])?;
+                let text_search = !opts.flag("--no-text-search");
+                let vector_indexes = opts.flag("--vector-indexes");
+                if !text_search && !vector_indexes {
+                    bail!("Nothing to rebuild; drop --no-text-search or pass --vector-indexes");
+                }
+                Command::Reindex {
+                    tenant_id: opts.parsed("--tenant")?,
+                    text_search,
+                    vector_indexes,
+                }
+            }
+            "export" => {
+                let mut opts = Options::parse(args, &["--output"], &["--include-embeddings"])?;
+                Command::Export {
+                    tenant_id: opts.positional("tenant ID")?.parse().context("Invalid tenant ID")?,
+                    include_embeddings: opts.flag("--include-embeddings"),
+                    output: opts.take("--output"),
+                }
+            }
+            "usage-report" => {
+                let mut opts = Options::parse(args, &["--tenant", "--since"], &[])?;
+                Command::UsageReport {
+                    tenant_id: opts.parsed("--tenant")?,
+                    since: opts.parsed("--since")?,
+                }
+            }
+            _ => bail!("Unknown command '{}'", command),
+        };
+        Ok(command)
+    }
+}
+
+/// Split a comma-separated scope list
+fn scope_list(scopes: &str) -> Vec<String> {
+    scopes
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// A command's options and positional arguments
+struct Options {
+    positional: Vec<String>,
+    values: HashMap<&'static str, String>,
+    flags: Vec<&'static str>,
+}
+
+impl Options {
+    /// Split `args` into the options taking `values`, the `flags`, and
+    /// positional arguments
+    fn parse(
+        mut args: impl Iterator<Item = String>,
+        values: &[&'static str],
+        flags: &[&'static str],
+    ) -> anyhow::Result<Self> {
+        let mut parsed = Options {
+            positional: Vec::new(),
+            values: HashMap::new(),
+            flags: Vec::new(),
+        };
+
+        while let Some(arg) = args.next() {
+            if let Some(name) = values.iter().find(|name| **name == arg) {
+                let value = args.next().with_context(|| format!("{} needs a value", name))?;
+                parsed.values.insert(name, value);
+            } else if let Some(name) = flags.iter().find(|name| **name == arg) {
+                parsed.flags.push(name);
+            } else if arg.starts_with("--") {
+                bail!("Unknown option: {}", arg);
+            } else {
+                parsed.positional.push(arg);
+            }
+        }
+
+        parsed.positional.reverse();
+        Ok(parsed)
+    }
+
+    /// The next positional argument, which must be present
+    fn positional(&mut self, what: &str) -> anyhow::Result<String> {
+        let value = self.positional.pop().with_context(|| format!("Missing {}", what))?;
+        if let Some(extra) = self.positional.last() {
+            bail!("Unexpected argument: {}", extra);
+        }
+        Ok(value)
+    }
+
+    fn take(&mut self, name: &str) -> Option<String> {
+        self.values.remove(name)
+    }
+
+    fn parsed<T>(&mut self, name: &str) -> anyhow::Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.take(name)
+            .map(|value| value.parse().map_err(|e| anyhow::anyhow!("Invalid {} '{}': {}", name, value, e)))
+            .transpose()
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Command> {
+        Command::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_create_tenant() {
+        assert_eq!(
+            parse(&["create-tenant", "acme", "--scopes", "read, write,admin"]).unwrap(),
+            Command::CreateTenant {
+                name: "acme".to_string(),
+                scopes: Some(vec!["read".to_string(), "write".to_string(), "admin".to_string()]),
+                rate_limit_rps: DEFAULT_RATE_LIMIT_RPS,
+            }
+        );
+        assert!(parse(&["create-tenant"]).is_err());
+        assert!(parse(&["create-tenant", "acme", "extra"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_dlq() {
+        assert_eq!(
+            parse(&["dlq", "redrive", "--queue", "embedding"]).unwrap(),
+            Command::DlqRedrive { queue: QueueName::Embedding, max_messages: DEFAULT_REDRIVE_MAX }
+        );
+        assert_eq!(parse(&["dlq", "list"]).unwrap(), Command::DlqList { queue: QueueName::Ingestion });
+        assert!(parse(&["dlq", "purge"]).is_err());
+        assert!(parse(&["dlq", "list", "--queue", "search"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_signing_secret() {
+        let tenant_id = Uuid::new_v4();
+        assert_eq!(
+            parse(&["signing-secret", &tenant_id.to_string()]).unwrap(),
+            Command::SigningSecret { tenant_id, disable: false }
+        );
+        assert_eq!(
+            parse(&["signing-secret", &tenant_id.to_string(), "--disable"]).unwrap(),
+            Command::SigningSecret { tenant_id, disable: true }
+        );
+        assert!(parse(&["signing-secret"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_options() {
+        assert!(parse(&["revoke-key", "not-a-uuid"]).is_err());
+        assert!(parse(&["export", &Uuid::nil().to_string(), "--format", "csv"]).is_err());
+        assert!(parse(&["reindex", "--no-text-search"]).is_err());
+        assert!(parse(&["usage-report", "--since"]).is_err());
+        assert!(parse(&[]).is_err());
+    }
+}
@@ -0,0 +1,443 @@
+//! Command implementations
+//!
+//! Results go to stdout (tab-separated where tabular, so they pipe into
+//! `cut` and `sort`); progress and warnings go to stderr.
+
+use anyhow::{bail, Context};
+use chrono::Utc;
+use serde::Serialize;
+use std::io::Write;
+use uuid::Uuid;
+
+use crate::args::{Command, QueueName};
+use paperforge_common::{
+    audit::{AuditEvent, AuditLogger},
+    auth::{api_key_lookup_prefix, generate_api_key, generate_signing_secret, hash_api_key, API_KEY_SCOPES},
+    db::{models::Tenant, DbPool, ExportChunk, Repository},
+    outbox::{OutboxPayload, INGESTION_QUEUE},
+    queue::{Queue, QueueConfig, ReprocessPaperMessage},
+    reindex::{ReindexWorker, ReindexWorkerConfig},
+    usage::month_start,
+    AppConfig,
+};
+
+/// Papers fetched per page when re-embedding a whole tenant
+const REEMBED_PAGE_SIZE: u64 = 100;
+
+/// Chunks fetched per page while exporting
+const EXPORT_PAGE_SIZE: u64 = 500;
+
+/// One line of an export, as in `GET /v2/export/chunks` minus the cursor
+#[derive(Serialize)]
+struct ExportLine {
+    chunk_id: Uuid,
+    paper_id: Uuid,
+    paper_title: String,
+    chunk_index: i32,
+    chunk_type: String,
+    content: String,
+    metadata: serde_json::Value,
+    token_count: i32,
+    embedding_model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embedding: Option<Vec<f32>>,
+    created_at: String,
+}
+
+impl From<ExportChunk> for ExportLine {
+    fn from(chunk: ExportChunk) -> Self {
+        Self {
+            embedding: chunk.parse_embedding(),
+            chunk_id: chunk.id,
+            paper_id: chunk.paper_id,
+            paper_title: chunk.paper_title,
+            chunk_index: chunk.chunk_index,
+            chunk_type: chunk.chunk_type,
+            content: chunk.content,
+            metadata: chunk.metadata,
+            token_count: chunk.token_count,
+            embedding_model: chunk.embedding_model,
+            created_at: chunk.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Runs admin commands against the configured database and queues
+pub struct Admin {
+    config: AppConfig,
+    repo: Repository,
+    audit: AuditLogger,
+    /// Recorded as the audit log actor
+    actor: String,
+}
+
+impl Admin {
+    pub fn new(config: AppConfig, db: DbPool) -> Self {
+        let repo = Repository::new(db);
+        let user = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+        Self {
+            config,
+            audit: AuditLogger::new(repo.clone()),
+            repo,
+            actor: format!("admin-cli:{}", user),
+        }
+    }
+
+    pub async fn run(&self, command: Command) -> anyhow::Result<()> {
+        match command {
+            Command::CreateTenant { name, scopes, rate_limit_rps } => {
+                self.create_tenant(&name, scopes, rate_limit_rps).await
+            }
+            Command::IssueKey { tenant_id, scopes, expires_in_days } => {
+                self.issue_key(tenant_id, scopes, expires_in_days).await
+            }
+            Command::RevokeKey { tenant_id } => self.revoke_key(tenant_id).await,
+            Command::SigningSecret { tenant_id, disable } => self.signing_secret(tenant_id, disable).await,
+            Command::DlqList { queue } => self.dlq_list(queue).await,
+            Command::DlqRedrive { queue, max_messages } => self.dlq_redrive(queue, max_messages).await,
+            Command::Reembed { tenant_id, paper_id, chunk_size, chunk_overlap } => {
+                self.reembed(tenant_id, paper_id, chunk_size, chunk_overlap).await
+            }
+            Command::Reindex { tenant_id, text_search, vector_indexes } => {
+                self.reindex(tenant_id, text_search, vector_indexes).await
+            }
+            Command::Export { tenant_id, include_embeddings, output } => {
+                self.export(tenant_id, include_embeddings, output.as_deref()).await
+            }
+            Command::UsageReport { tenant_id, since } => self.usage_report(tenant_id, since).await,
+        }
+    }
+
+    async fn tenant(&self, tenant_id: Uuid) -> anyhow::Result<Tenant> {
+        self.repo
+            .find_tenant_by_id(tenant_id)
+            .await?
+            .with_context(|| format!("Tenant {} not found", tenant_id))
+    }
+
+    async fn create_tenant(&self, name: &str, scopes: Option<Vec<String>>, rate_limit_rps: i32) -> anyhow::Result<()> {
+        let scopes = scopes.unwrap_or_else(|| API_KEY_SCOPES.iter().map(|s| s.to_string()).collect());
+        let api_key = generate_api_key();
+        let hash = hash_api_key(&api_key)?;
+
+        let tenant = self
+            .repo
+            .create_tenant(name, &hash, api_key_lookup_prefix(&api_key), &scopes, rate_limit_rps)
+            .await?;
+
+        self.audit.record(
+            AuditEvent::new("tenant.create", "tenant")
+                .by_operator(&self.actor, Some(tenant.id))
+                .resource_id(tenant.id)
+                .after(&tenant),
+        ).await;
+
+        println!("tenant_id\t{}", tenant.id);
+        println!("api_key\t{}", api_key);
+        eprintln!("Store the API key now; it can't be shown again");
+        Ok(())
+    }
+
+    /// Issue a new key, replacing the tenant's current one
+    async fn issue_key(
+        &self,
+        tenant_id: Uuid,
+        scopes: Option<Vec<String>>,
+        expires_in_days: Option<u32>,
+    ) -> anyhow::Result<()> {
+        let tenant = self.tenant(tenant_id).await?;
+        let scopes = match scopes {
+            Some(scopes) => scopes,
+            None if tenant.api_key_scopes.is_empty() => {
+                API_KEY_SCOPES.iter().map(|s| s.to_string()).collect()
+            }
+            None => tenant.api_key_scope_list(),
+        };
+        let expires_at = expires_in_days.map(|days| Utc::now() + chrono::Duration::days(days as i64));
+
+        let api_key = generate_api_key();
+        let hash = hash_api_key(&api_key)?;
+        self.repo
+            .replace_tenant_api_key(tenant.id, &hash, api_key_lookup_prefix(&api_key), &scopes, expires_at)
+            .await?;
+
+        self.audit.record(
+            AuditEvent::new("api_key.issue", "tenant")
+                .by_operator(&self.actor, Some(tenant.id))
+                .resource_id(tenant.id)
+                .before(&tenant)
+                .after(&serde_json::json!({ "scopes": scopes, "expires_at": expires_at })),
+        ).await;
+
+        println!("api_key\t{}", api_key);
+        eprintln!("The previous key stops working once cached verifications expire (about a minute)");
+        Ok(())
+    }
+
+    async fn revoke_key(&self, tenant_id: Uuid) -> anyhow::Result<()> {
+        if !self.repo.revoke_tenant_api_key(tenant_id).await? {
+            bail!("Tenant {} not found", tenant_id);
+        }
+
+        self.audit.record(
+            AuditEvent::new("api_key.revoke", "tenant")
+                .by_operator(&self.actor, Some(tenant_id))
+                .resource_id(tenant_id),
+        ).await;
+
+        eprintln!("Key revoked; it stops working once cached verifications expire (about a minute)");
+        Ok(())
+    }
+
+    /// Replace the tenant's request signing secret, or clear it
+    async fn signing_secret(&self, tenant_id: Uuid, disable: bool) -> anyhow::Result<()> {
+        let secret = (!disable).then(generate_signing_secret);
+        if !self.repo.set_tenant_signing_secret(tenant_id, secret.as_deref()).await? {
+            bail!("Tenant {} not found", tenant_id);
+        }
+
+        let action = if disable { "signing_secret.disable" } else { "signing_secret.issue" };
+        self.audit.record(
+            AuditEvent::new(action, "tenant")
+                .by_operator(&self.actor, Some(tenant_id))
+                .resource_id(tenant_id),
+        ).await;
+
+        match secret {
+            Some(secret) => {
+                println!("signing_secret\t{}", secret);
+                eprintln!("Store the secret now; it can't be shown again. Any previous secret no longer works");
+            }
+            None => eprintln!("Request signing disabled"),
+        }
+        Ok(())
+    }
+
+    async fn queue(&self, name: QueueName) -> anyhow::Result<Queue> {
+        let url = match name {
+            QueueName::Ingestion => self.config.queue.ingestion_queue_url.clone()
+                .context("APP__QUEUE__INGESTION_QUEUE_URL is not set")?,
+            QueueName::Embedding => self.config.queue.embedding_queue_url.clone()
+                .context("APP__QUEUE__EMBEDDING_QUEUE_URL is not set")?,
+        };
+        let config = QueueConfig {
+            url,
+            dlq_url: Some(self.config.queue.dlq_url.clone().context("APP__QUEUE__DLQ_URL is not set")?),
+            ..Default::default()
+        };
+        Ok(Queue::new(config).await?)
+    }
+
+    /// Print a sample of dead letters: message ID and body per line
+    async fn dlq_list(&self, name: QueueName) -> anyhow::Result<()> {
+        let queue = self.queue(name).await?;
+        let count = queue.get_dlq_count().await?;
+        let messages = queue.receive_from_dlq().await?;
+
+        for message in &messages {
+            println!("{}\t{}", message.message_id().unwrap_or("-"), message.body().unwrap_or_default());
+        }
+        eprintln!(
+            "Showing {} of about {} messages; they stay hidden from other readers for 30 seconds",
+            messages.len(),
+            count
+        );
+        Ok(())
+    }
+
+    async fn dlq_redrive(&self, name: QueueName, max_messages: usize) -> anyhow::Result<()> {
+        let queue = self.queue(name).await?;
+        let redriven = queue.redrive_all(max_messages).await?;
+
+        let queue_name = match name {
+            QueueName::Ingestion => "ingestion",
+            QueueName::Embedding => "embedding",
+        };
+        self.audit.record(
+            AuditEvent::new("dlq.redrive", "queue")
+                .by_operator(&self.actor, None)
+                .resource_id(queue_name)
+                .after(&serde_json::json!({ "redriven": redriven })),
+        ).await;
+
+        println!("redriven\t{}", redriven);
+        Ok(())
+    }
+
+    /// Queue reprocessing jobs that re-chunk and re-embed papers
+    ///
+    /// Jobs go through the outbox like `POST /v2/papers/{id}/reprocess`, so
+    /// they're published once a relay (gateway or ingestion) picks them up.
+    async fn reembed(
+        &self,
+        tenant_id: Uuid,
+        paper_id: Option<Uuid>,
+        chunk_size: Option<usize>,
+        chunk_overlap: Option<usize>,
+    ) -> anyhow::Result<()> {
+        self.tenant(tenant_id).await?;
+
+        let paper_ids = match paper_id {
+            Some(paper_id) => {
+                let paper = self.repo
+                    .find_paper_by_id(paper_id)
+                    .await?
+                    .filter(|p| !p.is_deleted() && p.tenant_id == tenant_id)
+                    .with_context(|| format!("Paper {} not found for tenant {}", paper_id, tenant_id))?;
+                vec![paper.id]
+            }
+            None => {
+                let mut ids = Vec::new();
+                loop {
+                    let (papers, total) = self.repo
+                        .list_papers(tenant_id, ids.len() as u64, REEMBED_PAGE_SIZE)
+                        .await?;
+                    ids.extend(papers.iter().map(|p| p.id));
+                    if papers.is_empty() || ids.len() as u64 >= total {
+                        break;
+                    }
+                }
+                ids
+            }
+        };
+
+        for paper_id in &paper_ids {
+            let paper_id = *paper_id;
+            let job = self.repo.create_job_with_outbox(
+                tenant_id,
+                Some(paper_id),
+                None,
+                INGESTION_QUEUE,
+                |job| OutboxPayload::new(&ReprocessPaperMessage {
+                    job_id: job.id,
+                    tenant_id,
+                    paper_id,
+                    chunk_size,
+                    chunk_overlap,
+                }),
+            ).await?;
+
+            self.audit.record(
+                AuditEvent::new("paper.reprocess", "paper")
+                    .by_operator(&self.actor, Some(tenant_id))
+                    .resource_id(paper_id)
+                    .after(&job),
+            ).await;
+
+            println!("{}\t{}", paper_id, job.id);
+        }
+
+        eprintln!("Queued {} reprocessing jobs", paper_ids.len());
+        Ok(())
+    }
+
+    /// Run a re-indexing job in this process, like `POST /v2/admin/reindex`
+    /// does on a gateway
+    async fn reindex(&self, tenant_id: Option<Uuid>, text_search: bool, vector_indexes: bool) -> anyhow::Result<()> {
+        if let Some(tenant_id) = tenant_id {
+            self.tenant(tenant_id).await?;
+        }
+        if let Some(active) = self.repo.find_active_reindex_job().await? {
+            bail!("Re-indexing job {} is already running", active.id);
+        }
+
+        let job = self.repo
+            .create_reindex_job(tenant_id, text_search, vector_indexes, &self.actor)
+            .await?;
+
+        self.audit.record(
+            AuditEvent::new("index.rebuild", "reindex_job")
+                .by_operator(&self.actor, None)
+                .resource_id(job.id)
+                .after(&serde_json::json!({
+                    "tenant_id": tenant_id,
+                    "text_search": text_search,
+                    "vector_indexes": vector_indexes,
+                })),
+        ).await;
+
+        // Holding the lease keeps the gateways' workers off the job
+        let Some(job) = self.repo.claim_reindex_job(Some(job.id)).await? else {
+            bail!("Re-indexing job {} was picked up by a gateway; follow it with GET /v2/admin/reindex/{}", job.id, job.id);
+        };
+        eprintln!("Re-indexing job {} started", job.id);
+        ReindexWorker::new(self.repo.clone(), ReindexWorkerConfig::default()).run(&job).await?;
+
+        let job = self.repo.find_reindex_job(job.id).await?.unwrap_or(job);
+        println!("job_id\t{}", job.id);
+        println!("chunks\t{}/{}", job.chunks_done, job.chunks_total);
+        println!("indexes\t{}/{}", job.indexes_done, job.indexes_total);
+        Ok(())
+    }
+
+    /// Write a tenant's chunks as NDJSON to `output`, or stdout
+    async fn export(&self, tenant_id: Uuid, include_embeddings: bool, output: Option<&str>) -> anyhow::Result<()> {
+        self.tenant(tenant_id).await?;
+
+        let writer: Box<dyn Write> = match output {
+            Some(path) => Box::new(
+                std::fs::File::create(path).with_context(|| format!("Failed to create {}", path))?,
+            ),
+            None => Box::new(std::io::stdout().lock()),
+        };
+        let mut writer = std::io::BufWriter::new(writer);
+
+        let mut after = None;
+        let mut exported = 0u64;
+        loop {
+            let page = self.repo
+                .export_chunks(tenant_id, after, include_embeddings, EXPORT_PAGE_SIZE)
+                .await?;
+            let full = page.len() as u64 == EXPORT_PAGE_SIZE;
+            after = page.last().map(|chunk| (chunk.created_at, chunk.id));
+
+            for chunk in page {
+                serde_json::to_writer(&mut writer, &ExportLine::from(chunk))?;
+                writer.write_all(b"\n")?;
+                exported += 1;
+            }
+            if !full {
+                break;
+            }
+        }
+        writer.flush()?;
+
+        eprintln!("Exported {} chunks", exported);
+        Ok(())
+    }
+
+    /// Usage per tenant and model since `since` (default: this month)
+    ///
+    /// Tenants without usage are left out unless asked for by ID.
+    async fn usage_report(
+        &self,
+        tenant_id: Option<Uuid>,
+        since: Option<chrono::DateTime<Utc>>,
+    ) -> anyhow::Result<()> {
+        let since = since.unwrap_or_else(|| month_start(Utc::now()));
+        let tenants = match tenant_id {
+            Some(tenant_id) => vec![self.tenant(tenant_id).await?],
+            None => self.repo.list_tenants().await?,
+        };
+
+        println!("tenant_id\ttenant\tkind\tmodel\tcalls\tprompt_tokens\tcompletion_tokens\tcost_usd");
+        for tenant in tenants {
+            for totals in self.repo.usage_by_model(tenant.id, since).await? {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.6}",
+                    tenant.id,
+                    tenant.name,
+                    totals.kind,
+                    totals.model,
+                    totals.calls,
+                    totals.prompt_tokens,
+                    totals.completion_tokens,
+                    totals.cost_micros as f64 / 1_000_000.0,
+                );
+            }
+        }
+
+        eprintln!("Usage since {}", since.to_rfc3339());
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! PaperForge admin CLI
+//!
+//! Operator tasks that go through the same repository and queue code as the
+//! services, instead of ad-hoc psql and aws-cli scripts:
+//!
+//! ```text
+//! paperforge-admin create-tenant acme --scopes read,write
+//! paperforge-admin dlq redrive --queue embedding --max 500
+//! paperforge-admin usage-report --since 2026-01-01T00:00:00Z
+//! ```
+//!
+//! Configuration loads like the services' (`APP__DATABASE__URL`, ...).
+//! Changes are written to the audit log with the actor `admin-cli:$USER`.
+
+mod args;
+mod commands;
+
+use crate::args::{Command, USAGE};
+use crate::commands::Admin;
+use paperforge_common::{
+    config::{AppConfig, Service},
+    db::DbPool,
+};
+use tracing::Level;
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+
+    tracing_subscriber::fmt()
+        .with_max_level(Level::WARN)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let command = match Command::parse(args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(command).await {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(command: Command) -> anyhow::Result<()> {
+    let config = AppConfig::load_for(Service::Admin).await?;
+    let db = DbPool::new(&config.database).await?;
+    Admin::new(config, db).run(command).await
+}
@@ -0,0 +1,231 @@
+//! Command-line parsing
+
+use anyhow::{bail, Context};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: bench <command> [options]
+
+Commands:
+  seed [--tenants N] [--papers N] [--topics N] [--seed N] [--queries FILE]
+  run --tenants FILE --queries FILE [--target gateway|grpc] [--url URL]
+      [--rps N] [--duration SECS] [--mode vector|bm25|hybrid] [--limit N]
+      [--max-in-flight N] [--baseline FILE] [--tolerance FRACTION] [--json]";
+
+const DEFAULT_TENANTS: usize = 3;
+const DEFAULT_PAPERS: usize = 200;
+const DEFAULT_RPS: u32 = 50;
+const DEFAULT_DURATION_SECS: u64 = 60;
+const DEFAULT_LIMIT: usize = 10;
+const DEFAULT_MAX_IN_FLIGHT: usize = 256;
+
+/// P99 increase over the baseline tolerated before a run fails
+const DEFAULT_TOLERANCE: f64 = 0.10;
+
+const DEFAULT_GATEWAY_URL: &str = "http://localhost:8080";
+const DEFAULT_GRPC_URL: &str = "http://localhost:50051";
+
+/// Service a workload is replayed against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// `POST /v2/search` on the gateway, authenticated per tenant
+    Gateway,
+    /// `SearchService/Search` on the search service, with the service token
+    Grpc,
+}
+
+impl FromStr for Target {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "gateway" => Ok(Target::Gateway),
+            "grpc" => Ok(Target::Grpc),
+            _ => bail!("Unknown target '{}', expected gateway or grpc", s),
+        }
+    }
+}
+
+/// Options of `bench run`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunArgs {
+    pub target: Target,
+    pub url: String,
+    /// `tenant_id<TAB>api_key` lines, as written by `bench seed`
+    pub tenants_file: String,
+    /// One query per line
+    pub queries_file: String,
+    pub rps: u32,
+    pub duration: Duration,
+    pub mode: String,
+    pub limit: usize,
+    /// Requests allowed in flight before new ones are skipped
+    pub max_in_flight: usize,
+    /// Earlier `--json` report to compare P99 against
+    pub baseline: Option<String>,
+    pub tolerance: f64,
+    pub json: bool,
+}
+
+/// A bench command with its options
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Seed {
+        tenants: usize,
+        papers: usize,
+        topics: Option<usize>,
+        seed: Option<u64>,
+        queries: Option<String>,
+    },
+    Run(RunArgs),
+}
+
+impl Command {
+    /// Parse the arguments after the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut args = args.into_iter();
+        let command = args.next().context("No command given")?;
+
+        let command = match command.as_str() {
+            "seed" => {
+                let mut opts = Options::parse(args, &["--tenants", "--papers", "--topics", "--seed", "--queries"], &[])?;
+                let tenants = opts.parsed("--tenants")?.unwrap_or(DEFAULT_TENANTS);
+                if tenants == 0 {
+                    bail!("--tenants must be at least 1");
+                }
+                Command::Seed {
+                    tenants,
+                    papers: opts.parsed("--papers")?.unwrap_or(DEFAULT_PAPERS),
+                    topics: opts.parsed("--topics")?,
+                    seed: opts.parsed("--seed")?,
+                    queries: opts.take("--queries"),
+                }
+            }
+            "run" => {
+                let mut opts = Options::parse(
+                    args,
+                    &[
+                        "--tenants", "--queries", "--target", "--url", "--rps", "--duration",
+                        "--mode", "--limit", "--max-in-flight", "--baseline", "--tolerance",
+                    ],
+                    &["--json"],
+                )?;
+                let target = opts.parsed("--target")?.unwrap_or(Target::Gateway);
+                let rps = opts.parsed("--rps")?.unwrap_or(DEFAULT_RPS);
+                if rps == 0 {
+                    bail!("--rps must be at least 1");
+                }
+                let mode = opts.take("--mode").unwrap_or_else(|| "hybrid".to_string());
+                if !matches!(mode.as_str(), "vector" | "bm25" | "hybrid") {
+                    bail!("Unknown mode '{}', expected vector, bm25 or hybrid", mode);
+                }
+                Command::Run(RunArgs {
+                    target,
+                    url: opts.take("--url").unwrap_or_else(|| match target {
+                        Target::Gateway => DEFAULT_GATEWAY_URL.to_string(),
+                        Target::Grpc => DEFAULT_GRPC_URL.to_string(),
+                    }),
+                    tenants_file: opts.take("--tenants").context("--tenants is required")?,
+                    queries_file: opts.take("--queries").context("--queries is required")?,
+                    rps,
+                    duration: Duration::from_secs(opts.parsed("--duration")?.unwrap_or(DEFAULT_DURATION_SECS)),
+                    mode,
+                    limit: opts.parsed("--limit")?.unwrap_or(DEFAULT_LIMIT),
+                    max_in_flight: opts.parsed("--max-in-flight")?.unwrap_or(DEFAULT_MAX_IN_FLIGHT).max(1),
+                    baseline: opts.take("--baseline"),
+                    tolerance: opts.parsed("--tolerance")?.unwrap_or(DEFAULT_TOLERANCE),
+                    json: opts.flag("--json"),
+                })
+            }
+            _ => bail!("Unknown command '{}'", command),
+        };
+        Ok(command)
+    }
+}
+
+/// A command's options
+struct Options {
+    values: HashMap<&'static str, String>,
+    flags: Vec<&'static str>,
+}
+
+impl Options {
+    /// Split `args` into the options taking `values` and the `flags`; bench
+    /// commands take no positional arguments
+    fn parse(
+        mut args: impl Iterator<Item = String>,
+        values: &[&'static str],
+        flags: &[&'static str],
+    ) -> anyhow::Result<Self> {
+        let mut parsed = Options {
+            values: HashMap::new(),
+            flags: Vec::new(),
+        };
+
+        while let Some(arg) = args.next() {
+            if let Some(name) = values.iter().find(|name| **name == arg) {
+                let value = args.next().with_context(|| format!("{} needs a value", name))?;
+                parsed.values.insert(name, value);
+            } else if let Some(name) = flags.iter().find(|name| **name == arg) {
+                parsed.flags.push(name);
+            } else if arg.starts_with("--") {
+                bail!("Unknown option: {}", arg);
+            } else {
+                bail!("Unexpected argument: {}", arg);
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn take(&mut self, name: &str) -> Option<String> {
+        self.values.remove(name)
+    }
+
+    fn parsed<T>(&mut self, name: &str) -> anyhow::Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.take(name)
+            .map(|value| value.parse().map_err(|e| anyhow::anyhow!("Invalid {} '{}': {}", name, value, e)))
+            .transpose()
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Command> {
+        Command::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_run_defaults() {
+        let Command::Run(run) = parse(&["run", "--tenants", "t.tsv", "--queries", "q.txt", "--target", "grpc"]).unwrap()
+        else {
+            panic!("expected run");
+        };
+        assert_eq!(run.target, Target::Grpc);
+        assert_eq!(run.url, DEFAULT_GRPC_URL);
+        assert_eq!(run.rps, DEFAULT_RPS);
+        assert_eq!(run.mode, "hybrid");
+        assert!(!run.json);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_options() {
+        assert!(parse(&["run", "--queries", "q.txt"]).is_err());
+        assert!(parse(&["run", "--tenants", "t.tsv", "--queries", "q.txt", "--rps", "0"]).is_err());
+        assert!(parse(&["run", "--tenants", "t.tsv", "--queries", "q.txt", "--mode", "fuzzy"]).is_err());
+        assert!(parse(&["seed", "--tenants", "0"]).is_err());
+        assert!(parse(&["seed", "extra"]).is_err());
+        assert!(parse(&[]).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! PaperForge load generator
+//!
+//! Seeds synthetic tenants and papers, then replays a query workload at a
+//! fixed request rate against the gateway or the search service and checks
+//! the latency percentiles against the SLOs in
+//! [`paperforge_common::metrics`]:
+//!
+//! ```text
+//! bench seed --tenants 3 --papers 500 --queries queries.txt > tenants.tsv
+//! bench run --tenants tenants.tsv --queries queries.txt --rps 100 --duration 120 --json > baseline.json
+//! bench run --tenants tenants.tsv --queries queries.txt --rps 100 --baseline baseline.json
+//! ```
+//!
+//! `run` exits with status 1 when a check fails, so it can gate CI. Seeding
+//! loads configuration like the ingestion service (`APP__DATABASE__URL`,
+//! ...); gRPC runs authenticate with `PAPERFORGE_SERVICE_TOKEN`.
+
+mod args;
+mod report;
+mod seed;
+mod workload;
+
+use anyhow::Context;
+use tracing::Level;
+
+use crate::args::{Command, RunArgs, USAGE};
+use crate::report::{check, Report};
+use paperforge_common::{
+    config::{AppConfig, Service},
+    db::DbPool,
+};
+use paperforge_ingestion::corpus_gen::CorpusGenConfig;
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+
+    tracing_subscriber::fmt()
+        .with_max_level(Level::WARN)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let command = match Command::parse(args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match run(command).await {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Run `command`; `false` when a workload missed its targets
+async fn run(command: Command) -> anyhow::Result<bool> {
+    match command {
+        Command::Seed { tenants, papers, topics, seed, queries } => {
+            let config = AppConfig::load_for(Service::Ingestion).await?;
+            let db = DbPool::new(&config.database).await?;
+            let defaults = CorpusGenConfig::default();
+            let gen_config = CorpusGenConfig {
+                papers,
+                topics: topics.unwrap_or(defaults.topics),
+                seed: seed.unwrap_or(defaults.seed),
+                ..defaults
+            };
+            seed::seed(&config, db, tenants, &gen_config, queries.as_deref()).await?;
+            Ok(true)
+        }
+        Command::Run(args) => run_workload(&args).await,
+    }
+}
+
+async fn run_workload(args: &RunArgs) -> anyhow::Result<bool> {
+    let tenants = std::fs::read_to_string(&args.tenants_file)
+        .with_context(|| format!("Failed to read {}", args.tenants_file))?;
+    let tenants = workload::parse_tenants(&tenants)?;
+    let queries: Vec<String> = std::fs::read_to_string(&args.queries_file)
+        .with_context(|| format!("Failed to read {}", args.queries_file))?
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let baseline: Option<Report> = args
+        .baseline
+        .as_ref()
+        .map(|path| -> anyhow::Result<Report> {
+            let input = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+            serde_json::from_str(&input).with_context(|| format!("{} is not a bench report", path))
+        })
+        .transpose()?;
+
+    eprintln!(
+        "Replaying {} queries as {} tenants at {} rps for {}s against {}",
+        queries.len(),
+        tenants.len(),
+        args.rps,
+        args.duration.as_secs(),
+        args.url
+    );
+    let report = workload::run(args, tenants, queries).await?;
+    let checks = check(&report, baseline.as_ref(), args.tolerance);
+    let passed = checks.iter().all(|c| c.passed);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "{} requests ({} errors, {} skipped) in {:.1}s, {:.1} rps",
+            report.requests, report.errors, report.skipped, report.duration_secs, report.achieved_rps
+        );
+        println!(
+            "p50 {:.1}ms  p90 {:.1}ms  p99 {:.1}ms  max {:.1}ms",
+            report.p50_ms, report.p90_ms, report.p99_ms, report.max_ms
+        );
+    }
+    // Checks go to stderr so `--json` output stays usable as a baseline
+    for c in &checks {
+        eprintln!("{:<16} {:<4} {}", c.name, if c.passed { "ok" } else { "FAIL" }, c.detail);
+    }
+    if report.skipped > 0 {
+        eprintln!("Warning: {} requests skipped; raise --max-in-flight or lower --rps", report.skipped);
+    }
+    Ok(passed)
+}
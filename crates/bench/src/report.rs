@@ -0,0 +1,167 @@
+//! Latency percentiles and SLO checks
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use paperforge_common::{
+    metrics::{SLO_P50_SECS, SLO_P99_SECS},
+    slo::AVAILABILITY_TARGET,
+};
+
+/// Outcome of one workload run; `--json` output, and `--baseline` input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub target: String,
+    pub mode: String,
+    pub requested_rps: u32,
+    pub achieved_rps: f64,
+    pub duration_secs: f64,
+    /// Requests that completed, successfully or not
+    pub requests: usize,
+    pub errors: usize,
+    /// Requests not sent because `max_in_flight` were outstanding
+    pub skipped: usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl Report {
+    /// Summarize the latencies of successful requests
+    pub fn new(
+        target: &str,
+        mode: &str,
+        requested_rps: u32,
+        elapsed: Duration,
+        mut latencies: Vec<Duration>,
+        errors: usize,
+        skipped: usize,
+    ) -> Self {
+        latencies.sort();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let requests = latencies.len() + errors;
+
+        Self {
+            target: target.to_string(),
+            mode: mode.to_string(),
+            requested_rps,
+            achieved_rps: requests as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            duration_secs: elapsed.as_secs_f64(),
+            requests,
+            errors,
+            skipped,
+            p50_ms: ms(percentile(&latencies, 0.50)),
+            p90_ms: ms(percentile(&latencies, 0.90)),
+            p99_ms: ms(percentile(&latencies, 0.99)),
+            max_ms: ms(latencies.last().copied().unwrap_or_default()),
+        }
+    }
+
+    /// Share of sent requests that succeeded
+    pub fn success_ratio(&self) -> f64 {
+        let sent = self.requests + self.skipped;
+        if sent == 0 {
+            return 0.0;
+        }
+        (self.requests - self.errors) as f64 / sent as f64
+    }
+}
+
+/// Nearest-rank percentile of sorted samples; zero without samples
+pub fn percentile(sorted: &[Duration], quantile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (quantile.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// One pass/fail criterion of a run
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Compare a run with the SLOs and, when given, a baseline run
+///
+/// P99 may exceed the baseline's by `tolerance` (a fraction) before it
+/// counts as a regression.
+pub fn check(report: &Report, baseline: Option<&Report>, tolerance: f64) -> Vec<Check> {
+    let p50_target = SLO_P50_SECS * 1000.0;
+    let p99_target = SLO_P99_SECS * 1000.0;
+    let success = report.success_ratio();
+
+    let mut checks = vec![
+        Check {
+            name: "p50",
+            passed: report.p50_ms <= p50_target,
+            detail: format!("{:.1}ms (target {:.0}ms)", report.p50_ms, p50_target),
+        },
+        Check {
+            name: "p99",
+            passed: report.p99_ms <= p99_target,
+            detail: format!("{:.1}ms (target {:.0}ms)", report.p99_ms, p99_target),
+        },
+        Check {
+            name: "availability",
+            passed: success >= AVAILABILITY_TARGET,
+            detail: format!("{:.3}% (target {:.1}%)", success * 100.0, AVAILABILITY_TARGET * 100.0),
+        },
+    ];
+
+    if let Some(baseline) = baseline {
+        let limit = baseline.p99_ms * (1.0 + tolerance);
+        checks.push(Check {
+            name: "p99 vs baseline",
+            passed: report.p99_ms <= limit,
+            detail: format!(
+                "{:.1}ms (baseline {:.1}ms, limit {:.1}ms)",
+                report.p99_ms, baseline.p99_ms, limit
+            ),
+        });
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&ms| Duration::from_millis(ms)).collect()
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let samples = millis(&(1..=100).collect::<Vec<_>>());
+        assert_eq!(percentile(&samples, 0.50), Duration::from_millis(50));
+        assert_eq!(percentile(&samples, 0.99), Duration::from_millis(99));
+        assert_eq!(percentile(&samples, 1.0), Duration::from_millis(100));
+        assert_eq!(percentile(&millis(&[7]), 0.99), Duration::from_millis(7));
+        assert_eq!(percentile(&[], 0.5), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_check_against_slo_and_baseline() {
+        let fast = Report::new("gateway", "hybrid", 10, Duration::from_secs(10), millis(&[10; 100]), 0, 0);
+        assert!(check(&fast, None, 0.1).iter().all(|c| c.passed));
+
+        let slow = Report::new("gateway", "hybrid", 10, Duration::from_secs(10), millis(&[12; 100]), 0, 0);
+        let checks = check(&slow, Some(&fast), 0.1);
+        let regression = checks.iter().find(|c| c.name == "p99 vs baseline").unwrap();
+        assert!(!regression.passed);
+        assert!(check(&slow, Some(&fast), 0.5).iter().all(|c| c.passed));
+    }
+
+    #[test]
+    fn test_errors_and_skips_count_against_availability() {
+        let report = Report::new("grpc", "bm25", 10, Duration::from_secs(10), millis(&[10; 98]), 1, 1);
+        assert_eq!(report.requests, 99);
+        assert!((report.success_ratio() - 0.98).abs() < 1e-9);
+        let availability = check(&report, None, 0.1).into_iter().find(|c| c.name == "availability").unwrap();
+        assert!(!availability.passed);
+    }
+}
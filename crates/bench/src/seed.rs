@@ -0,0 +1,71 @@
+//! Synthetic tenants and papers to benchmark against
+
+use anyhow::{anyhow, Context};
+use std::io::Write;
+
+use paperforge_common::{
+    auth::{api_key_lookup_prefix, generate_api_key, hash_api_key, API_KEY_SCOPES},
+    db::{DbPool, Repository},
+    AppConfig,
+};
+use paperforge_ingestion::corpus_gen::{self, CorpusGenConfig, SyntheticCorpus};
+
+/// Requests per second for bench tenants, high enough that the gateway's
+/// per-tenant limit doesn't shape the workload
+const BENCH_RATE_LIMIT_RPS: i32 = 100_000;
+
+/// Create `tenants` tenants, each loaded with the same synthetic corpus
+///
+/// Writes `tenant_id<TAB>api_key` lines to stdout and, when `queries` is
+/// given, one query per paper title to that file.
+pub async fn seed(
+    config: &AppConfig,
+    db: DbPool,
+    tenants: usize,
+    gen_config: &CorpusGenConfig,
+    queries: Option<&str>,
+) -> anyhow::Result<()> {
+    // No embedding queue, so chunks are embedded inline with the hashing model
+    let processor = paperforge_ingestion::build_processor(config, db.clone(), None, None)
+        .map_err(|e| anyhow!("Failed to build ingestion processor: {}", e))?;
+    let repo = Repository::new(db);
+    let corpus = corpus_gen::generate(gen_config);
+    let scopes: Vec<String> = API_KEY_SCOPES.iter().map(|s| s.to_string()).collect();
+
+    for i in 0..tenants {
+        let api_key = generate_api_key();
+        let hash = hash_api_key(&api_key)?;
+        let tenant = repo
+            .create_tenant(
+                &format!("bench-{}-{}", gen_config.seed, i),
+                &hash,
+                api_key_lookup_prefix(&api_key),
+                &scopes,
+                BENCH_RATE_LIMIT_RPS,
+            )
+            .await?;
+
+        eprintln!("Loading {} papers for tenant {}", corpus.papers.len(), tenant.id);
+        corpus_gen::load(&processor, &corpus, tenant.id, gen_config.seed)
+            .await
+            .with_context(|| format!("Failed to load the corpus for tenant {}", tenant.id))?;
+        println!("{}\t{}", tenant.id, api_key);
+    }
+
+    if let Some(path) = queries {
+        let mut file = std::fs::File::create(path).with_context(|| format!("Failed to create {}", path))?;
+        for query in corpus_queries(&corpus) {
+            writeln!(file, "{}", query)?;
+        }
+        eprintln!("Wrote queries to {}", path);
+    }
+    Ok(())
+}
+
+/// One query per distinct paper title, lowercased like typed queries
+fn corpus_queries(corpus: &SyntheticCorpus) -> Vec<String> {
+    let mut queries: Vec<String> = corpus.papers.iter().map(|p| p.title.to_lowercase()).collect();
+    queries.sort();
+    queries.dedup();
+    queries
+}
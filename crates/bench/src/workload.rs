@@ -0,0 +1,191 @@
+//! Open-loop query replay
+//!
+//! Requests are started on a fixed schedule whether or not earlier ones have
+//! finished, so a slow server shows up as latency rather than as a lower
+//! request rate.
+
+use anyhow::{bail, Context};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Semaphore};
+use tonic::transport::Channel;
+use uuid::Uuid;
+
+use crate::args::{RunArgs, Target};
+use crate::report::Report;
+use paperforge_common::{
+    auth::ServiceTokenInterceptor,
+    proto::search::{
+        search_service_client::SearchServiceClient, SearchMode, SearchOptions as ProtoSearchOptions,
+        SearchRequest as ProtoSearchRequest,
+    },
+};
+
+/// A tenant to send queries as
+#[derive(Debug, Clone)]
+pub struct BenchTenant {
+    pub id: Uuid,
+    pub api_key: String,
+}
+
+/// Parse `tenant_id<TAB>api_key` lines, as written by `bench seed`
+pub fn parse_tenants(input: &str) -> anyhow::Result<Vec<BenchTenant>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let (id, api_key) = line
+                .split_once('\t')
+                .with_context(|| format!("Line {}: expected tenant_id<TAB>api_key", i + 1))?;
+            Ok(BenchTenant {
+                id: id.trim().parse().with_context(|| format!("Line {}: invalid tenant ID", i + 1))?,
+                api_key: api_key.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Client for the service under test
+#[derive(Clone)]
+enum Client {
+    Gateway(reqwest::Client),
+    Grpc(SearchServiceClient<tonic::service::interceptor::InterceptedService<Channel, ServiceTokenInterceptor>>),
+}
+
+impl Client {
+    fn connect(args: &RunArgs) -> anyhow::Result<Self> {
+        Ok(match args.target {
+            Target::Gateway => Client::Gateway(reqwest::Client::new()),
+            Target::Grpc => {
+                let token = std::env::var("PAPERFORGE_SERVICE_TOKEN").ok();
+                let channel = Channel::from_shared(args.url.clone())?.connect_lazy();
+                let interceptor = ServiceTokenInterceptor::new(token.as_deref())?;
+                Client::Grpc(SearchServiceClient::with_interceptor(channel, interceptor))
+            }
+        })
+    }
+
+    async fn search(&self, args: &RunArgs, tenant: &BenchTenant, query: &str) -> anyhow::Result<()> {
+        match self {
+            Client::Gateway(client) => {
+                client
+                    .post(format!("{}/v2/search", args.url.trim_end_matches('/')))
+                    .bearer_auth(&tenant.api_key)
+                    .json(&serde_json::json!({
+                        "query": query,
+                        "options": { "mode": args.mode, "limit": args.limit },
+                    }))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            Client::Grpc(client) => {
+                let mode = match args.mode.as_str() {
+                    "vector" => SearchMode::Vector,
+                    "bm25" => SearchMode::Bm25,
+                    _ => SearchMode::Hybrid,
+                };
+                let mut request = tonic::Request::new(ProtoSearchRequest {
+                    query: query.to_string(),
+                    tenant_id: tenant.id.to_string(),
+                    query_embedding: Vec::new(),
+                    options: Some(ProtoSearchOptions {
+                        mode: mode as i32,
+                        limit: args.limit as i32,
+                        ..Default::default()
+                    }),
+                });
+                request.metadata_mut().insert("x-tenant-id", tenant.id.to_string().parse()?);
+                client.clone().search(request).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replay `queries` at `args.rps` for `args.duration`, round-robin over `tenants`
+pub async fn run(args: &RunArgs, tenants: Vec<BenchTenant>, queries: Vec<String>) -> anyhow::Result<Report> {
+    if tenants.is_empty() {
+        bail!("No tenants to send queries as");
+    }
+    if queries.is_empty() {
+        bail!("No queries to replay");
+    }
+
+    let client = Client::connect(args)?;
+    let args = Arc::new(args.clone());
+    let tenants = Arc::new(tenants);
+    let queries = Arc::new(queries);
+    let in_flight = Arc::new(Semaphore::new(args.max_in_flight));
+    let (results, mut samples) = mpsc::unbounded_channel::<Option<Duration>>();
+
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / args.rps as f64));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Burst);
+    let start = Instant::now();
+    let mut skipped = 0;
+
+    for i in 0.. {
+        ticker.tick().await;
+        if start.elapsed() >= args.duration {
+            break;
+        }
+        let Ok(permit) = in_flight.clone().try_acquire_owned() else {
+            skipped += 1;
+            continue;
+        };
+
+        let (client, args, tenants, queries, results) =
+            (client.clone(), args.clone(), tenants.clone(), queries.clone(), results.clone());
+        tokio::spawn(async move {
+            let tenant = &tenants[i % tenants.len()];
+            let query = &queries[i % queries.len()];
+            let sent = Instant::now();
+            let outcome = match client.search(&args, tenant, query).await {
+                Ok(()) => Some(sent.elapsed()),
+                Err(e) => {
+                    tracing::debug!(error = %e, query = %query, "Search failed");
+                    None
+                }
+            };
+            let _ = results.send(outcome);
+            drop(permit);
+        });
+    }
+
+    let elapsed = start.elapsed();
+
+    // Wait for outstanding requests before summarizing
+    drop(results);
+    let mut latencies = Vec::new();
+    let mut errors = 0;
+    while let Some(outcome) = samples.recv().await {
+        match outcome {
+            Some(latency) => latencies.push(latency),
+            None => errors += 1,
+        }
+    }
+
+    let target = match args.target {
+        Target::Gateway => "gateway",
+        Target::Grpc => "grpc",
+    };
+    Ok(Report::new(target, &args.mode, args.rps, elapsed, latencies, errors, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tenants() {
+        let id = Uuid::new_v4();
+        let tenants = parse_tenants(&format!("{}\tpf_key\n\n", id)).unwrap();
+        assert_eq!(tenants.len(), 1);
+        assert_eq!(tenants[0].id, id);
+        assert_eq!(tenants[0].api_key, "pf_key");
+
+        assert!(parse_tenants("not-a-uuid\tpf_key").is_err());
+        assert!(parse_tenants(&id.to_string()).is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! Saved search alerts
+//!
+//! [`AlertRunner`] runs every saved search that has a webhook or email
+//! address against the chunks indexed since its last run. Papers whose best
+//! new chunk reaches the search's `min_score` are reported:
+//! - to the webhook as a signed `saved_search.matched` event
+//! - by email, when an SMTP server is configured
+//!
+//! A search's `last_run_at` only moves forward once its alert is delivered,
+//! so failed deliveries are retried on the next run.
+//!
+//! Webhook URLs come from API callers, so they are held to the
+//! [`FetcherConfig`] policy on delivery as well as when saved: the host is
+//! resolved and pinned to public addresses, and redirects aren't followed.
+
+use crate::calibration::ScoreCalibrator;
+use crate::config::{AlertsConfig, FetcherConfig};
+use crate::db::{models::SavedSearch, ChunkResult, Repository};
+use crate::errors::{AppError, Result};
+use crate::fetcher::{check_url, pinned_client};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use lettre::{message::Mailbox, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying the webhook body's HMAC-SHA256 signature
+pub const SIGNATURE_HEADER: &str = "X-PaperForge-Signature";
+
+/// Chunks fetched per reported paper, so one long paper can't crowd out others
+const CHUNKS_PER_PAPER: usize = 5;
+
+/// Longest snippet included in an alert, in characters
+const SNIPPET_CHARS: usize = 300;
+
+/// A newly indexed paper matching a saved search
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertPaper {
+    pub paper_id: Uuid,
+    pub paper_title: String,
+    /// Calibrated score of the paper's best new chunk
+    pub score: f64,
+    pub snippet: String,
+}
+
+/// Webhook event body
+#[derive(Debug, Serialize)]
+pub struct AlertEvent<'a> {
+    pub event: &'static str,
+    pub timestamp: DateTime<Utc>,
+    pub data: AlertData<'a>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AlertData<'a> {
+    pub saved_search_id: Uuid,
+    pub name: &'a str,
+    pub query: &'a str,
+    pub papers: &'a [AlertPaper],
+}
+
+/// Best new chunk of each paper scoring at least `min_score`, best first
+///
+/// `calibrate` maps the results' raw scores to 0-1.
+pub fn matching_papers(
+    results: Vec<ChunkResult>,
+    calibrate: impl Fn(f64) -> f64,
+    min_score: f64,
+    max_papers: usize,
+) -> Vec<AlertPaper> {
+    let mut results = results;
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    
+    let mut seen = HashSet::new();
+    let mut papers = Vec::new();
+    for result in results {
+        let score = calibrate(result.score);
+        if score < min_score || !seen.insert(result.paper_id) {
+            continue;
+        }
+        papers.push(AlertPaper {
+            paper_id: result.paper_id,
+            paper_title: result.paper_title,
+            score,
+            snippet: result.content.chars().take(SNIPPET_CHARS).collect(),
+        });
+        if papers.len() == max_papers {
+            break;
+        }
+    }
+    papers
+}
+
+/// `sha256=<hex>` signature of a webhook body
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Delivers alerts to webhooks and email
+pub struct AlertNotifier {
+    webhook_timeout: Duration,
+    fetcher: FetcherConfig,
+    mailer: Option<AsyncSmtpTransport<Tokio1Executor>>,
+    from: Mailbox,
+}
+
+impl AlertNotifier {
+    /// Create a notifier; email needs `smtp_url`
+    ///
+    /// Webhooks are only delivered to URLs that `fetcher` allows.
+    pub fn new(config: &AlertsConfig, fetcher: &FetcherConfig) -> Result<Self> {
+        let mailer = config.smtp_url.as_deref()
+            .map(|url| AsyncSmtpTransport::<Tokio1Executor>::from_url(url).map(|builder| builder.build()))
+            .transpose()
+            .map_err(|e| AppError::Configuration {
+                message: format!("Invalid alerts.smtp_url: {}", e),
+            })?;
+        let from = config.email_from.parse().map_err(|e| AppError::Configuration {
+            message: format!("Invalid alerts.email_from: {}", e),
+        })?;
+        
+        Ok(Self {
+            webhook_timeout: Duration::from_secs(config.webhook_timeout_secs),
+            fetcher: fetcher.clone(),
+            mailer,
+            from,
+        })
+    }
+    
+    /// Send an alert for `papers` to the search's webhook and email address
+    pub async fn notify(&self, search: &SavedSearch, papers: &[AlertPaper]) -> Result<()> {
+        if let Some(url) = &search.webhook_url {
+            self.post_webhook(search, url, papers).await?;
+        }
+        if let Some(email) = &search.email {
+            match &self.mailer {
+                Some(mailer) => self.send_email(mailer, search, email, papers).await?,
+                None => warn!(saved_search_id = %search.id, "No SMTP server configured; skipping email alert"),
+            }
+        }
+        Ok(())
+    }
+    
+    async fn post_webhook(&self, search: &SavedSearch, url: &str, papers: &[AlertPaper]) -> Result<()> {
+        let body = serde_json::to_vec(&AlertEvent {
+            event: "saved_search.matched",
+            timestamp: Utc::now(),
+            data: AlertData {
+                saved_search_id: search.id,
+                name: &search.name,
+                query: &search.query,
+                papers,
+            },
+        })?;
+        
+        // Checked again in case the policy changed since the search was saved
+        let url = check_url(url, &self.fetcher)?;
+        let builder = reqwest::Client::builder().timeout(self.webhook_timeout);
+        let mut request = pinned_client(&url, builder, &self.fetcher)
+            .await?
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &search.webhook_secret {
+            request = request.header(SIGNATURE_HEADER, sign_payload(secret, &body));
+        }
+        request.body(body).send().await?.error_for_status()?;
+        Ok(())
+    }
+    
+    async fn send_email(
+        &self,
+        mailer: &AsyncSmtpTransport<Tokio1Executor>,
+        search: &SavedSearch,
+        to: &str,
+        papers: &[AlertPaper],
+    ) -> Result<()> {
+        fn email_error(e: impl std::fmt::Display) -> AppError {
+            AppError::Internal {
+                message: format!("Email alert failed: {}", e),
+            }
+        }
+        
+        let mut text = format!(
+            "{} new paper{} matched your saved search \"{}\":\n",
+            papers.len(),
+            if papers.len() == 1 { "" } else { "s" },
+            search.query
+        );
+        for paper in papers {
+            text.push_str(&format!("\n{} (score {:.2})\n{}\n", paper.paper_title, paper.score, paper.snippet));
+        }
+        
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to.parse().map_err(email_error)?)
+            .subject(format!("New papers for \"{}\"", search.name))
+            .body(text)
+            .map_err(email_error)?;
+        mailer.send(message).await.map_err(email_error)?;
+        Ok(())
+    }
+}
+
+/// Runs saved searches against newly indexed chunks
+pub struct AlertRunner {
+    repo: Repository,
+    notifier: AlertNotifier,
+    // Alert scores use the calibration priors; alert queries aren't sampled
+    calibrator: ScoreCalibrator,
+    max_papers: usize,
+}
+
+impl AlertRunner {
+    /// Create a runner
+    pub fn new(repo: Repository, config: &AlertsConfig, fetcher: &FetcherConfig) -> Result<Self> {
+        Ok(Self {
+            repo,
+            notifier: AlertNotifier::new(config, fetcher)?,
+            calibrator: ScoreCalibrator::default(),
+            max_papers: config.max_papers.max(1),
+        })
+    }
+    
+    /// Run every alerting saved search once; returns the alerts sent
+    pub async fn run_once(&self) -> Result<usize> {
+        let mut sent = 0;
+        for search in self.repo.list_alerting_saved_searches().await? {
+            match self.run_search(&search).await {
+                Ok(true) => sent += 1,
+                Ok(false) => {}
+                Err(e) => warn!(saved_search_id = %search.id, error = %e, "Saved search alert failed"),
+            }
+        }
+        Ok(sent)
+    }
+    
+    /// Run one search; `true` if an alert was sent
+    async fn run_search(&self, search: &SavedSearch) -> Result<bool> {
+        let now = Utc::now().fixed_offset();
+        let results = self.repo.bm25_search_new_chunks(
+            search.tenant_id,
+            &search.query,
+            search.last_run_at,
+            now,
+            self.max_papers * CHUNKS_PER_PAPER,
+        ).await?;
+        
+        let papers = matching_papers(
+            results,
+            |score| self.calibrator.calibrate("bm25", score),
+            search.min_score,
+            self.max_papers,
+        );
+        if !papers.is_empty() {
+            self.notifier.notify(search, &papers).await?;
+            info!(
+                saved_search_id = %search.id,
+                tenant_id = %search.tenant_id,
+                papers = papers.len(),
+                "Saved search alert sent"
+            );
+        }
+        
+        self.repo.mark_saved_search_run(search.id, now).await?;
+        Ok(!papers.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn result(paper: u128, score: f64) -> ChunkResult {
+        ChunkResult {
+            chunk_id: Uuid::new_v4(),
+            paper_id: Uuid::from_u128(paper),
+            paper_title: format!("Paper {}", paper),
+            content: "x".repeat(500),
+            chunk_index: 0,
+            score,
+            embedding_model: String::new(),
+            chunk_type: "text".to_string(),
+            provenance: None,
+        }
+    }
+    
+    #[test]
+    fn test_matching_papers() {
+        let results = vec![result(1, 0.7), result(2, 0.8), result(1, 0.9), result(3, 0.2), result(4, 0.6)];
+        
+        let papers = matching_papers(results, |score| score, 0.5, 2);
+        assert_eq!(papers.len(), 2);
+        assert_eq!(papers[0].paper_id, Uuid::from_u128(1));
+        assert_eq!(papers[0].score, 0.9);
+        assert_eq!(papers[1].paper_id, Uuid::from_u128(2));
+        assert_eq!(papers[0].snippet.chars().count(), SNIPPET_CHARS);
+    }
+    
+    #[test]
+    fn test_sign_payload() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_private_webhooks_are_refused() {
+        let notifier = AlertNotifier::new(&AlertsConfig::default(), &FetcherConfig::default()).unwrap();
+        let now = Utc::now().into();
+        let mut search = SavedSearch {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "Internal".to_string(),
+            query: "metadata".to_string(),
+            min_score: 0.5,
+            webhook_url: None,
+            webhook_secret: None,
+            email: None,
+            last_run_at: now,
+            created_at: now,
+        };
+        
+        for url in ["http://169.254.169.254/latest/meta-data", "http://localhost:6379/", "file:///etc/passwd"] {
+            search.webhook_url = Some(url.to_string());
+            let result = notifier.notify(&search, &[]).await;
+            assert!(matches!(result, Err(AppError::Validation { .. })), "{} was not refused", url);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Session and query analytics
+//!
+//! Every search is logged to `query_logs` with its normalized query, mode,
+//! result count and latency; other session activity (result clicks, paper
+//! views, synthesis requests) goes to `session_events`. Writes happen off
+//! the request path and a failed write is logged, never surfaced: analytics
+//! must not slow down or fail a search.
+
+use crate::db::Repository;
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Event recorded when a user clicks a search result
+pub const EVENT_CLICK: &str = "click";
+
+/// Event recorded when a user asks for a synthesized answer
+pub const EVENT_SYNTHESIS: &str = "synthesis";
+
+/// A completed search
+#[derive(Debug, Clone)]
+pub struct SearchRecord {
+    pub tenant_id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub query: String,
+    /// `vector`, `bm25`, `hybrid` or `intelligent`
+    pub mode: String,
+    pub result_count: usize,
+    pub latency_ms: u64,
+}
+
+/// Writes searches and session events in the background
+#[derive(Clone)]
+pub struct Analytics {
+    repository: Repository,
+}
+
+impl Analytics {
+    pub fn new(repository: Repository) -> Self {
+        Self { repository }
+    }
+    
+    /// Log a search
+    pub fn record_search(&self, search: SearchRecord) {
+        let repository = self.repository.clone();
+        tokio::spawn(async move {
+            if let Err(e) = repository
+                .record_query_log(
+                    search.tenant_id,
+                    search.session_id,
+                    &search.query,
+                    &search.mode,
+                    search.result_count,
+                    search.latency_ms,
+                )
+                .await
+            {
+                tracing::warn!(tenant_id = %search.tenant_id, error = %e, "Failed to log search");
+            }
+        });
+    }
+    
+    /// Record an event in a session
+    pub fn record_event(&self, tenant_id: Uuid, session_id: Option<Uuid>, event_type: &str, data: Value) {
+        let repository = self.repository.clone();
+        let event_type = event_type.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = repository
+                .record_session_event(tenant_id, session_id, &event_type, data)
+                .await
+            {
+                tracing::warn!(%tenant_id, event = %event_type, error = %e, "Failed to record session event");
+            }
+        });
+    }
+}
+
+/// Share of searches that returned nothing; 0 when there were none
+pub fn zero_result_rate(searches: i64, zero_result_searches: i64) -> f64 {
+    if searches == 0 {
+        0.0
+    } else {
+        zero_result_searches as f64 / searches as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_zero_result_rate() {
+        assert_eq!(zero_result_rate(0, 0), 0.0);
+        assert_eq!(zero_result_rate(4, 1), 0.25);
+        assert_eq!(zero_result_rate(3, 3), 1.0);
+    }
+}
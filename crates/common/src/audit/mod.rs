@@ -0,0 +1,165 @@
+//! Audit logging for mutating operations
+//!
+//! Handlers describe each change as an [`AuditEvent`] (who, what, and the
+//! resource state before and after) and hand it to [`AuditLogger`], which
+//! appends it to the `audit_logs` table.
+
+use crate::auth::AuthContext;
+use crate::db::models::AuditLogActiveModel;
+use crate::db::Repository;
+use sea_orm::Set;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Snapshot fields that are never written to the audit log
+const REDACTED_FIELDS: &[&str] = &["api_key_hash", "signing_secret", "token_hash"];
+
+/// Actor recorded for operations not triggered by a request
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// A single audited operation
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub tenant_id: Option<Uuid>,
+    pub actor: String,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub request_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl AuditEvent {
+    /// Start an event performed by the system; use [`by`](Self::by) for callers
+    pub fn new(action: &str, resource_type: &str) -> Self {
+        Self {
+            tenant_id: None,
+            actor: SYSTEM_ACTOR.to_string(),
+            action: action.to_string(),
+            resource_type: resource_type.to_string(),
+            resource_id: None,
+            request_id: None,
+            before: None,
+            after: None,
+        }
+    }
+    
+    /// Attribute the event to an authenticated caller and their tenant
+    pub fn by(mut self, auth: &AuthContext) -> Self {
+        self.tenant_id = Some(auth.tenant_id);
+        self.actor = auth.actor();
+        self.request_id = Some(auth.request_id.clone());
+        self
+    }
+    
+    /// Attribute the event to an operator acting outside the API, optionally
+    /// on behalf of a tenant
+    pub fn by_operator(mut self, actor: &str, tenant_id: Option<Uuid>) -> Self {
+        self.tenant_id = tenant_id;
+        self.actor = actor.to_string();
+        self
+    }
+    
+    pub fn resource_id(mut self, id: impl ToString) -> Self {
+        self.resource_id = Some(id.to_string());
+        self
+    }
+    
+    /// Record the resource state before the operation
+    pub fn before<T: Serialize>(mut self, state: &T) -> Self {
+        self.before = snapshot(state);
+        self
+    }
+    
+    /// Record the resource state after the operation
+    pub fn after<T: Serialize>(mut self, state: &T) -> Self {
+        self.after = snapshot(state);
+        self
+    }
+}
+
+/// Serialize a resource for the log, dropping secrets
+fn snapshot<T: Serialize>(state: &T) -> Option<Value> {
+    let mut value = serde_json::to_value(state).ok()?;
+    if let Value::Object(map) = &mut value {
+        for field in REDACTED_FIELDS {
+            map.remove(*field);
+        }
+    }
+    Some(value)
+}
+
+/// Writes audit events to the database
+#[derive(Clone)]
+pub struct AuditLogger {
+    repository: Repository,
+}
+
+impl AuditLogger {
+    pub fn new(repository: Repository) -> Self {
+        Self { repository }
+    }
+    
+    /// Append an event to the audit log
+    ///
+    /// Runs after the audited change has committed, so a failure here is
+    /// logged rather than failing the request.
+    pub async fn record(&self, event: AuditEvent) {
+        let entry = AuditLogActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(event.tenant_id),
+            actor: Set(event.actor.clone()),
+            action: Set(event.action.clone()),
+            resource_type: Set(event.resource_type.clone()),
+            resource_id: Set(event.resource_id.clone()),
+            request_id: Set(event.request_id.clone()),
+            before: Set(event.before),
+            after: Set(event.after),
+            created_at: Set(chrono::Utc::now().into()),
+        };
+        
+        if let Err(e) = self.repository.create_audit_log(entry).await {
+            tracing::error!(
+                action = %event.action,
+                actor = %event.actor,
+                resource_type = %event.resource_type,
+                resource_id = ?event.resource_id,
+                error = %e,
+                "Failed to write audit log"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_event_from_auth_context() {
+        let auth = AuthContext {
+            tenant_id: Uuid::new_v4(),
+            api_key: Some("pk_0123456789abcdef".to_string()),
+            user_id: None,
+            scopes: vec![],
+            request_id: "req-1".to_string(),
+        };
+        
+        let event = AuditEvent::new("paper.delete", "paper")
+            .by(&auth)
+            .resource_id(42)
+            .before(&json!({ "title": "A", "api_key_hash": "secret" }));
+        
+        assert_eq!(event.tenant_id, Some(auth.tenant_id));
+        assert_eq!(event.actor, "api_key:pk_01234567");
+        assert_eq!(event.request_id.as_deref(), Some("req-1"));
+        assert_eq!(event.resource_id.as_deref(), Some("42"));
+        assert_eq!(event.before, Some(json!({ "title": "A" })));
+        assert!(event.after.is_none());
+        
+        assert_eq!(AuditEvent::new("dlq.redrive", "queue").actor, SYSTEM_ACTOR);
+    }
+}
@@ -0,0 +1,167 @@
+//! gRPC authentication
+//!
+//! [`GrpcAuthLayer`] authenticates every incoming call from its metadata
+//! and stores the resulting [`AuthContext`] and the call's
+//! [`RequestContext`] in the request extensions.
+//! External callers authenticate with an API key or JWT, as over HTTP;
+//! internal callers present the shared service token and forward the
+//! caller's tenant with [`RequestContext::propagate`]. Health checks are let through
+//! unauthenticated, so probes don't need credentials.
+
+use super::{AuthContext, AuthState};
+use crate::deadline;
+use crate::errors::{AppError, Result};
+use crate::request_context::RequestContext;
+use axum::http;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tonic::body::BoxBody;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use tower::{Layer, Service};
+
+/// Path prefix of the standard gRPC health service
+const HEALTH_SERVICE_PATH: &str = "/grpc.health.v1.Health/";
+
+/// Tower layer authenticating incoming gRPC calls
+#[derive(Clone)]
+pub struct GrpcAuthLayer {
+    state: AuthState,
+}
+
+impl GrpcAuthLayer {
+    /// Create a layer backed by the given auth state
+    pub fn new(state: AuthState) -> Self {
+        Self { state }
+    }
+}
+
+impl<S> Layer<S> for GrpcAuthLayer {
+    type Service = GrpcAuthService<S>;
+    
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcAuthService {
+            inner,
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Service produced by [`GrpcAuthLayer`]
+#[derive(Clone)]
+pub struct GrpcAuthService<S> {
+    inner: S,
+    state: AuthState,
+}
+
+impl<S, B> Service<http::Request<B>> for GrpcAuthService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = std::result::Result<Self::Response, Self::Error>> + Send>>;
+    
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+    
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        // Use the service that was polled ready and leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let state = self.state.clone();
+        
+        Box::pin(async move {
+            if request.uri().path().starts_with(HEALTH_SERVICE_PATH) {
+                return inner.call(request).await;
+            }
+            match state.authenticate_headers(request.headers(), true).await {
+                Ok(context) => {
+                    let request_context = RequestContext::from_headers(request.headers())
+                        .with_auth(&context)
+                        .with_timeout(deadline::grpc_timeout(request.headers()));
+                    request.extensions_mut().insert(request_context);
+                    request.extensions_mut().insert(context);
+                    inner.call(request).await
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, path = %request.uri().path(), "gRPC call rejected");
+                    Ok(Status::from(e).into_http())
+                }
+            }
+        })
+    }
+}
+
+/// Get the authenticated caller of a gRPC request
+pub fn grpc_auth_context<T>(request: &Request<T>) -> std::result::Result<AuthContext, Status> {
+    request
+        .extensions()
+        .get::<AuthContext>()
+        .cloned()
+        .ok_or_else(|| Status::unauthenticated("Request was not authenticated"))
+}
+
+/// Client interceptor attaching the service token to outgoing calls
+#[derive(Clone)]
+pub struct ServiceTokenInterceptor {
+    authorization: Option<MetadataValue<Ascii>>,
+}
+
+impl ServiceTokenInterceptor {
+    /// Create an interceptor; with no token, calls are sent unauthenticated
+    pub fn new(service_token: Option<&str>) -> Result<Self> {
+        let authorization = service_token
+            .map(|token| {
+                format!("Bearer {}", token).parse().map_err(|_| AppError::Configuration {
+                    message: "Service token is not valid gRPC metadata".to_string(),
+                })
+            })
+            .transpose()?;
+        
+        Ok(Self { authorization })
+    }
+}
+
+impl Interceptor for ServiceTokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> std::result::Result<Request<()>, Status> {
+        if let Some(authorization) = &self.authorization {
+            request.metadata_mut().insert("authorization", authorization.clone());
+        }
+        Ok(request)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+    
+    #[test]
+    fn test_outgoing_metadata() {
+        let auth = AuthContext {
+            tenant_id: Uuid::new_v4(),
+            api_key: None,
+            user_id: None,
+            scopes: vec![],
+            request_id: "req-1".to_string(),
+        };
+        
+        let mut interceptor = ServiceTokenInterceptor::new(Some("secret")).unwrap();
+        let mut request = interceptor.call(Request::new(())).unwrap();
+        RequestContext::from_headers(&http::HeaderMap::new())
+            .with_auth(&auth)
+            .propagate(&mut request);
+        
+        let metadata = request.metadata();
+        assert_eq!(metadata.get("authorization").unwrap(), "Bearer secret");
+        assert_eq!(metadata.get("x-tenant-id").unwrap().to_str().unwrap(), auth.tenant_id.to_string());
+        assert_eq!(metadata.get("x-request-id").unwrap(), "req-1");
+    }
+}
@@ -3,21 +3,59 @@
 //! Provides:
 //! - API key validation
 //! - JWT token generation and validation
+//! - Refresh token generation
 //! - Tenant context extraction
+//! - gRPC authentication and tenant propagation
+//! - HMAC request signing
+//! - OIDC (JWKS) token validation
 
+mod grpc;
+mod oidc;
+mod signing;
+
+pub use grpc::{grpc_auth_context, GrpcAuthLayer, GrpcAuthService, ServiceTokenInterceptor};
+pub use oidc::OidcValidator;
+pub use signing::{
+    generate_signing_secret, sign_request, signature_middleware, verify_signature, NonceCache,
+    NONCE_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+
+use crate::cache::Cache;
+use crate::config::AuthConfig;
+use crate::db::models::Tenant;
+use crate::db::{DbPool, Repository};
 use crate::errors::{AppError, Result};
 use axum::{
-    extract::{FromRequestParts, Request},
-    http::request::Parts,
+    extract::{FromRef, FromRequestParts, Request},
+    http::{request::Parts, HeaderMap},
     middleware::Next,
     response::Response,
 };
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
 use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
 use uuid::Uuid;
 
+/// Prefix of every API key
+pub const API_KEY_PREFIX: &str = "pk_";
+
+/// Prefix of every refresh token
+pub const REFRESH_TOKEN_PREFIX: &str = "rt_";
+
+/// Scopes granted to new API keys unless others are requested
+pub const API_KEY_SCOPES: &[&str] = &["read", "write"];
+
+/// Scope that implies every other; granted only by operators
+pub const ADMIN_SCOPE: &str = "admin";
+
+/// Key characters (after the prefix) stored in clear for lookup
+const API_KEY_LOOKUP_CHARS: usize = 8;
+
 /// Extracted authentication context available to handlers
 #[derive(Debug, Clone)]
 pub struct AuthContext {
@@ -40,7 +78,18 @@ pub struct AuthContext {
 impl AuthContext {
     /// Check if the context has a specific scope
     pub fn has_scope(&self, scope: &str) -> bool {
-        self.scopes.contains(&scope.to_string()) || self.scopes.contains(&"admin".to_string())
+        self.scopes.iter().any(|s| s == scope || s == ADMIN_SCOPE)
+    }
+    
+    /// Identify the caller for audit records
+    ///
+    /// API keys are identified by their lookup prefix, never the full key.
+    pub fn actor(&self) -> String {
+        match (&self.user_id, &self.api_key) {
+            (Some(user_id), _) => format!("user:{}", user_id),
+            (None, Some(api_key)) => format!("api_key:{}", api_key_lookup_prefix(api_key)),
+            (None, None) => format!("tenant:{}", self.tenant_id),
+        }
     }
     
     /// Require a specific scope, returning error if not present
@@ -116,6 +165,11 @@ impl JwtManager {
             })
     }
     
+    /// Lifetime of issued tokens in seconds
+    pub fn expiration_secs(&self) -> i64 {
+        self.expiration_secs
+    }
+    
     /// Validate and decode a JWT token
     pub fn validate_token(&self, token: &str) -> Result<JwtClaims> {
         decode::<JwtClaims>(token, &self.decoding_key, &Validation::default())
@@ -131,22 +185,62 @@ impl JwtManager {
     }
 }
 
-/// Hash an API key for storage
-pub fn hash_api_key(api_key: &str) -> String {
+/// Hash a high-entropy token (refresh or service token) for storage
+///
+/// Unsalted SHA-256 is only appropriate for random tokens that cannot be
+/// brute-forced; API keys use [`hash_api_key`].
+pub fn hash_token(token: &str) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(api_key.as_bytes());
+    hasher.update(token.as_bytes());
     hex::encode(hasher.finalize())
 }
 
+/// Hash an API key for storage (argon2id with a random salt)
+pub fn hash_api_key(api_key: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(api_key.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::Internal {
+            message: format!("Failed to hash API key: {}", e),
+        })
+}
+
 /// Validate an API key against a stored hash
+///
+/// Accepts argon2 PHC strings and legacy SHA-256 hex hashes; both
+/// comparisons are constant-time.
 pub fn validate_api_key(api_key: &str, stored_hash: &str) -> bool {
-    hash_api_key(api_key) == stored_hash
+    if is_legacy_api_key_hash(stored_hash) {
+        return hash_token(api_key).as_bytes().ct_eq(stored_hash.as_bytes()).into();
+    }
+    
+    PasswordHash::new(stored_hash)
+        .and_then(|hash| Argon2::default().verify_password(api_key.as_bytes(), &hash))
+        .is_ok()
+}
+
+/// Check whether a stored hash predates argon2 (unsalted SHA-256 hex)
+pub fn is_legacy_api_key_hash(stored_hash: &str) -> bool {
+    !stored_hash.starts_with("$argon2")
+}
+
+/// Leading characters of an API key used to look up candidate tenants
+pub fn api_key_lookup_prefix(api_key: &str) -> &str {
+    let len = (API_KEY_PREFIX.len() + API_KEY_LOOKUP_CHARS).min(api_key.len());
+    api_key.get(..len).unwrap_or(api_key)
 }
 
 /// Generate a new API key
 pub fn generate_api_key() -> String {
     let random_bytes: [u8; 32] = rand::random();
-    format!("pk_{}", hex::encode(random_bytes))
+    format!("{}{}", API_KEY_PREFIX, hex::encode(random_bytes))
+}
+
+/// Generate a new refresh token
+pub fn generate_refresh_token() -> String {
+    let random_bytes: [u8; 32] = rand::random();
+    format!("{}{}", REFRESH_TOKEN_PREFIX, hex::encode(random_bytes))
 }
 
 /// Generate an idempotency key from content
@@ -158,6 +252,12 @@ pub fn generate_idempotency_key(title: &str, abstract_text: &str) -> String {
     hex::encode(hasher.finalize())
 }
 
+/// Check whether a JWT is signed with a shared secret (PaperForge-minted)
+fn is_hmac_token(token: &str) -> bool {
+    jsonwebtoken::decode_header(token)
+        .is_ok_and(|h| matches!(h.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512))
+}
+
 /// Extract API key from Authorization header
 pub fn extract_api_key(auth_header: &str) -> Option<&str> {
     if auth_header.starts_with("Bearer ") {
@@ -167,59 +267,306 @@ pub fn extract_api_key(auth_header: &str) -> Option<&str> {
     }
 }
 
-/// Axum extractor for AuthContext
-impl<S> FromRequestParts<S> for AuthContext
-where
-    S: Send + Sync,
-{
-    type Rejection = AppError;
+/// How long a verified API key skips argon2 verification
+const VERIFIED_KEY_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Upper bound on cached verified keys
+const VERIFIED_KEY_CAPACITY: usize = 10_000;
+
+/// Recently verified API keys, keyed by SHA-256 of the key
+///
+/// argon2 verification costs tens of milliseconds, too much to pay on every
+/// request. Entries expire quickly so revoked keys stop working promptly.
+#[derive(Default)]
+struct VerifiedKeyCache {
+    entries: std::sync::Mutex<std::collections::HashMap<String, (Tenant, std::time::Instant)>>,
+}
+
+impl VerifiedKeyCache {
+    fn get(&self, key: &str) -> Option<Tenant> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(key)
+            .filter(|(_, verified_at)| verified_at.elapsed() < VERIFIED_KEY_TTL)
+            .map(|(tenant, _)| tenant.clone())
+    }
+    
+    fn insert(&self, key: String, tenant: Tenant) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= VERIFIED_KEY_CAPACITY {
+            entries.retain(|_, (_, verified_at)| verified_at.elapsed() < VERIFIED_KEY_TTL);
+        }
+        entries.insert(key, (tenant, std::time::Instant::now()));
+    }
+    
+    fn remove_tenant(&self, tenant_id: Uuid) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (tenant, _)| tenant.id != tenant_id);
+    }
+}
+
+/// State the [`AuthContext`] extractor needs
+///
+/// Services expose it from their router state with `FromRef`.
+#[derive(Clone)]
+pub struct AuthState {
+    jwt: Option<Arc<JwtManager>>,
+    oidc: Option<Arc<OidcValidator>>,
+    service_token_hash: Option<String>,
+    signature_window_secs: i64,
+    nonces: Arc<NonceCache>,
+    /// Shared nonce store; without it nonces are only seen by this process
+    cache: Option<Arc<Cache>>,
+    verified_keys: Arc<VerifiedKeyCache>,
+    repository: Repository,
+}
+
+impl AuthState {
+    /// Create auth state; JWTs are disabled unless a secret is configured
+    pub fn new(config: &AuthConfig, db: DbPool) -> Self {
+        let jwt = config
+            .jwt_secret
+            .as_deref()
+            .map(|secret| Arc::new(JwtManager::new(secret, config.jwt_expiration_secs)));
+        
+        Self {
+            jwt,
+            oidc: config.oidc.clone().map(|oidc| Arc::new(OidcValidator::new(oidc))),
+            service_token_hash: config.service_token.as_deref().map(hash_token),
+            signature_window_secs: config.signature_window_secs as i64,
+            nonces: Arc::new(NonceCache::default()),
+            cache: None,
+            verified_keys: Arc::new(VerifiedKeyCache::default()),
+            repository: Repository::new(db),
+        }
+    }
+    
+    /// Record signed requests' nonces in Redis, so a request accepted by
+    /// one instance can't be replayed against another
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+    
+    /// Get the JWT manager, failing if no JWT secret is configured
+    pub fn jwt(&self) -> Result<&JwtManager> {
+        self.jwt.as_deref().ok_or_else(|| AppError::ServiceUnavailable {
+            message: "JWT authentication is not configured".to_string(),
+        })
+    }
+    
+    /// Resolve an API key to its (active, unexpired) tenant
+    ///
+    /// Keys are verified with argon2id against the tenants sharing the key's
+    /// prefix. Keys still stored as legacy SHA-256 are accepted once more
+    /// and rehashed with argon2id.
+    pub async fn authenticate_api_key(&self, api_key: &str) -> Result<Tenant> {
+        let tenant = self.verify_api_key(api_key).await?;
+        
+        if tenant.is_api_key_expired() {
+            return Err(AppError::Unauthorized {
+                message: "API key has expired".to_string(),
+            });
+        }
+        
+        Ok(tenant)
+    }
+    
+    async fn verify_api_key(&self, api_key: &str) -> Result<Tenant> {
+        if !api_key.starts_with(API_KEY_PREFIX) {
+            return Err(AppError::InvalidApiKey);
+        }
+        
+        let cache_key = hash_token(api_key);
+        if let Some(tenant) = self.verified_keys.get(&cache_key) {
+            return Ok(tenant);
+        }
+        
+        let prefix = api_key_lookup_prefix(api_key);
+        let candidates = self.repository.find_tenants_by_api_key_prefix(prefix).await?;
+        
+        for tenant in candidates {
+            let key = api_key.to_string();
+            let stored_hash = tenant.api_key_hash.clone();
+            
+            // argon2 is deliberately slow; keep it off the async workers
+            let valid = tokio::task::spawn_blocking(move || validate_api_key(&key, &stored_hash))
+                .await
+                .map_err(|e| AppError::Internal {
+                    message: format!("API key verification failed: {}", e),
+                })?;
+            
+            if valid {
+                self.record_api_key_use(tenant.id);
+                self.verified_keys.insert(cache_key, tenant.clone());
+                return Ok(tenant);
+            }
+        }
+        
+        let tenant = self
+            .repository
+            .find_tenant_by_api_key_hash(&cache_key)
+            .await?
+            .ok_or(AppError::InvalidApiKey)?;
+        
+        let key = api_key.to_string();
+        let upgraded = tokio::task::spawn_blocking(move || hash_api_key(&key))
+            .await
+            .map_err(|e| AppError::Internal {
+                message: format!("API key hashing failed: {}", e),
+            })??;
+        self.repository
+            .set_tenant_api_key(tenant.id, &upgraded, prefix)
+            .await?;
+        tracing::info!(tenant_id = %tenant.id, "Upgraded legacy API key hash to argon2id");
+        
+        self.record_api_key_use(tenant.id);
+        self.verified_keys.insert(cache_key, tenant.clone());
+        Ok(tenant)
+    }
+    
+    /// Stop accepting a tenant's cached API key verifications
+    ///
+    /// Called after the key is replaced, so this instance rejects the old
+    /// key at once; other instances do once their cached verification
+    /// expires.
+    pub fn forget_api_key(&self, tenant_id: Uuid) {
+        self.verified_keys.remove_tenant(tenant_id);
+    }
     
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+    /// Update the key's last-used time in the background
+    ///
+    /// Called only when a key is verified (not on cache hits), so each key
+    /// is written at most about once per cache TTL.
+    fn record_api_key_use(&self, tenant_id: Uuid) {
+        let repository = self.repository.clone();
+        tokio::spawn(async move {
+            if let Err(e) = repository.touch_tenant_api_key(tenant_id).await {
+                tracing::warn!(tenant_id = %tenant_id, error = %e, "Failed to record API key use");
+            }
+        });
+    }
+    
+    /// Check a credential against the internal service token
+    fn is_service_token(&self, credential: &str) -> bool {
+        self.service_token_hash
+            .as_deref()
+            .is_some_and(|hash| hash_token(credential).as_bytes().ct_eq(hash.as_bytes()).into())
+    }
+    
+    /// Authenticate a request from its headers (or gRPC metadata)
+    ///
+    /// The bearer credential is an API key or a JWT access token.
+    /// `X-Tenant-ID` is optional but must match the credential's tenant.
+    /// With `allow_service_token`, internal callers may instead present the
+    /// service token and must then name the tenant they act for.
+    pub async fn authenticate_headers(
+        &self,
+        headers: &HeaderMap,
+        allow_service_token: bool,
+    ) -> Result<AuthContext> {
         // Extract request ID
-        let request_id = parts
-            .headers
+        let request_id = headers
             .get("x-request-id")
             .and_then(|v| v.to_str().ok())
             .map(String::from)
             .unwrap_or_else(|| Uuid::new_v4().to_string());
         
-        // Extract tenant ID
-        let tenant_id = parts
-            .headers
+        // Extract tenant ID (optional, cross-checked below)
+        let header_tenant_id = headers
             .get("x-tenant-id")
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| Uuid::parse_str(s).ok())
-            .ok_or_else(|| AppError::Unauthorized {
-                message: "Missing or invalid X-Tenant-ID header".to_string(),
-            })?;
+            .map(|v| {
+                v.to_str()
+                    .ok()
+                    .and_then(|s| Uuid::parse_str(s).ok())
+                    .ok_or_else(|| AppError::Unauthorized {
+                        message: "Invalid X-Tenant-ID header".to_string(),
+                    })
+            })
+            .transpose()?;
         
         // Extract API key or JWT
-        let auth_header = parts
-            .headers
+        let auth_header = headers
             .get("authorization")
             .and_then(|v| v.to_str().ok())
             .ok_or_else(|| AppError::Unauthorized {
                 message: "Missing Authorization header".to_string(),
             })?;
         
-        let api_key = extract_api_key(auth_header)
-            .map(String::from);
+        let credential = extract_api_key(auth_header).ok_or_else(|| AppError::Unauthorized {
+            message: "Authorization header must use the Bearer scheme".to_string(),
+        })?;
         
-        // For now, accept any API key starting with "pk_"
-        // In production, this would validate against the database
-        if let Some(ref key) = api_key {
-            if !key.starts_with("pk_") {
-                return Err(AppError::InvalidApiKey);
+        if allow_service_token && self.is_service_token(credential) {
+            let tenant_id = header_tenant_id.ok_or_else(|| AppError::Unauthorized {
+                message: "Service calls must set X-Tenant-ID".to_string(),
+            })?;
+            
+            return Ok(AuthContext {
+                tenant_id,
+                api_key: None,
+                user_id: None,
+                scopes: API_KEY_SCOPES.iter().map(|s| s.to_string()).collect(),
+                request_id,
+            });
+        }
+        
+        let context = if credential.starts_with(API_KEY_PREFIX) {
+            let tenant = self.authenticate_api_key(credential).await?;
+            
+            AuthContext {
+                tenant_id: tenant.id,
+                api_key: Some(credential.to_string()),
+                user_id: None,
+                scopes: tenant.api_key_scope_list(),
+                request_id,
             }
+        } else if let Some(oidc) = self.oidc.as_deref().filter(|_| !is_hmac_token(credential)) {
+            oidc.validate(credential, request_id).await?
+        } else {
+            let jwt = self.jwt.as_deref().ok_or(AppError::InvalidApiKey)?;
+            let claims = jwt.validate_token(credential)?;
+            let tenant_id = Uuid::parse_str(&claims.tenant_id)
+                .map_err(|_| AppError::InvalidApiKey)?;
+            
+            AuthContext {
+                tenant_id,
+                api_key: None,
+                // Tokens exchanged for an API key have the tenant as subject
+                user_id: Uuid::parse_str(&claims.sub).ok().filter(|id| *id != tenant_id),
+                scopes: claims.scopes,
+                request_id,
+            }
+        };
+        
+        if header_tenant_id.is_some_and(|id| id != context.tenant_id) {
+            return Err(AppError::TenantMismatch);
         }
         
-        Ok(AuthContext {
-            tenant_id,
-            api_key,
-            user_id: None,
-            scopes: vec!["read".to_string(), "write".to_string()],
-            request_id,
-        })
+        Ok(context)
+    }
+}
+
+/// Axum extractor for AuthContext
+impl<S> FromRequestParts<S> for AuthContext
+where
+    S: Send + Sync,
+    AuthState: FromRef<S>,
+{
+    type Rejection = AppError;
+    
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        // Already authenticated by middleware (signed requests) or an
+        // earlier extractor
+        if let Some(context) = parts.extensions.get::<AuthContext>() {
+            return Ok(context.clone());
+        }
+        
+        let context = AuthState::from_ref(state)
+            .authenticate_headers(&parts.headers, false)
+            .await?;
+        parts.extensions.insert(context.clone());
+        Ok(context)
     }
 }
 
@@ -240,18 +587,6 @@ pub async fn auth_middleware(
         });
     }
     
-    // Check for Tenant ID header
-    let tenant_header = request
-        .headers()
-        .get("x-tenant-id")
-        .and_then(|v| v.to_str().ok());
-    
-    if tenant_header.is_none() {
-        return Err(AppError::Unauthorized {
-            message: "Missing X-Tenant-ID header".to_string(),
-        });
-    }
-    
     Ok(next.run(request).await)
 }
 
@@ -262,9 +597,29 @@ mod tests {
     #[test]
     fn test_hash_api_key() {
         let key = "pk_test_12345";
-        let hash = hash_api_key(key);
+        let hash = hash_api_key(key).unwrap();
+        assert!(hash.starts_with("$argon2id"));
+        assert!(!is_legacy_api_key_hash(&hash));
         assert!(validate_api_key(key, &hash));
         assert!(!validate_api_key("wrong_key", &hash));
+        
+        // Salted: the same key hashes differently each time
+        assert_ne!(hash, hash_api_key(key).unwrap());
+    }
+    
+    #[test]
+    fn test_legacy_api_key_hash() {
+        let key = "pk_test_12345";
+        let legacy = hash_token(key);
+        assert!(is_legacy_api_key_hash(&legacy));
+        assert!(validate_api_key(key, &legacy));
+        assert!(!validate_api_key("wrong_key", &legacy));
+    }
+    
+    #[test]
+    fn test_api_key_lookup_prefix() {
+        assert_eq!(api_key_lookup_prefix("pk_0123456789abcdef"), "pk_01234567");
+        assert_eq!(api_key_lookup_prefix("pk_01"), "pk_01");
     }
     
     #[test]
@@ -291,6 +646,14 @@ mod tests {
         assert_eq!(extract_api_key("Basic abc"), None);
     }
     
+    #[test]
+    fn test_generate_refresh_token() {
+        let token = generate_refresh_token();
+        assert!(token.starts_with(REFRESH_TOKEN_PREFIX));
+        assert_ne!(token, generate_refresh_token());
+        assert_eq!(hash_token(&token).len(), 64);
+    }
+    
     #[test]
     fn test_jwt_roundtrip() {
         let manager = JwtManager::new("test_secret", 3600);
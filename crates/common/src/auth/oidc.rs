@@ -0,0 +1,208 @@
+//! OIDC token validation
+//!
+//! Validates RS256 access tokens issued by an external identity provider
+//! (Auth0, Keycloak, Cognito, ...) against its JWKS endpoint. Keys are
+//! cached and refetched when they expire or when a token names an unknown
+//! `kid`, which covers provider key rotation.
+
+use super::{AuthContext, ADMIN_SCOPE};
+use crate::config::OidcConfig;
+use crate::errors::{AppError, Result};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Minimum delay between JWKS fetches triggered by unknown key IDs
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+struct CachedKeys {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+/// Validates identity provider tokens
+pub struct OidcValidator {
+    config: OidcConfig,
+    jwks_url: String,
+    http: reqwest::Client,
+    cache: RwLock<Option<CachedKeys>>,
+}
+
+impl OidcValidator {
+    /// Create a validator; the JWKS is fetched on first use
+    pub fn new(config: OidcConfig) -> Self {
+        let jwks_url = config.jwks_url.clone().unwrap_or_else(|| {
+            format!("{}/.well-known/jwks.json", config.issuer.trim_end_matches('/'))
+        });
+        
+        Self {
+            config,
+            jwks_url,
+            http: reqwest::Client::new(),
+            cache: RwLock::new(None),
+        }
+    }
+    
+    /// Validate a token and build the caller's auth context
+    pub async fn validate(&self, token: &str, request_id: String) -> Result<AuthContext> {
+        let header = decode_header(token).map_err(|_| AppError::InvalidApiKey)?;
+        if header.alg != Algorithm::RS256 {
+            return Err(AppError::Unauthorized {
+                message: format!("Unsupported token algorithm: {:?}", header.alg),
+            });
+        }
+        let kid = header.kid.ok_or_else(|| AppError::Unauthorized {
+            message: "Token has no key ID".to_string(),
+        })?;
+        
+        let key = self.decoding_key(&kid).await?;
+        
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&[&self.config.issuer]);
+        match &self.config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        
+        let claims = decode::<Value>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => AppError::ExpiredToken,
+                _ => AppError::InvalidApiKey,
+            })?;
+        
+        context_from_claims(&claims, &self.config.tenant_claim, request_id)
+    }
+    
+    /// Find the key for `kid`, refreshing the JWKS if needed
+    async fn decoding_key(&self, kid: &str) -> Result<DecodingKey> {
+        let ttl = Duration::from_secs(self.config.jwks_cache_secs);
+        
+        {
+            let cache = self.cache.read().await;
+            if let Some(cached) = cache.as_ref() {
+                let fresh = cached.fetched_at.elapsed() < ttl;
+                if let Some(jwk) = cached.keys.find(kid).filter(|_| fresh) {
+                    return DecodingKey::from_jwk(jwk).map_err(|_| AppError::InvalidApiKey);
+                }
+                // Unknown kid with a recent fetch: don't let bad tokens hammer the IdP
+                if fresh && cached.fetched_at.elapsed() < MIN_REFRESH_INTERVAL {
+                    return Err(AppError::InvalidApiKey);
+                }
+            }
+        }
+        
+        let mut cache = self.cache.write().await;
+        
+        // Another request may have refreshed while we waited for the lock
+        let refreshed = cache
+            .as_ref()
+            .is_some_and(|c| c.fetched_at.elapsed() < MIN_REFRESH_INTERVAL);
+        if !refreshed {
+            let keys = self.fetch_keys().await?;
+            tracing::info!(keys = keys.keys.len(), url = %self.jwks_url, "Fetched JWKS");
+            *cache = Some(CachedKeys {
+                keys,
+                fetched_at: Instant::now(),
+            });
+        }
+        
+        let jwk = cache
+            .as_ref()
+            .and_then(|c| c.keys.find(kid))
+            .ok_or(AppError::InvalidApiKey)?;
+        DecodingKey::from_jwk(jwk).map_err(|_| AppError::InvalidApiKey)
+    }
+    
+    async fn fetch_keys(&self) -> Result<JwkSet> {
+        let response = self
+            .http
+            .get(&self.jwks_url)
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::ServiceUnavailable {
+                message: format!("Failed to fetch JWKS: {}", e),
+            })?;
+        
+        response.json().await.map_err(|e| AppError::ServiceUnavailable {
+            message: format!("Invalid JWKS response: {}", e),
+        })
+    }
+}
+
+/// Map identity provider claims to an auth context
+///
+/// The tenant comes from `tenant_claim` (a UUID string). Scopes are read
+/// from the standard `scope` string or the `scp` array some providers use.
+/// `admin` is dropped: it is only granted with API keys issued by an
+/// operator, never by the identity provider.
+fn context_from_claims(claims: &Value, tenant_claim: &str, request_id: String) -> Result<AuthContext> {
+    let tenant_id = claims
+        .get(tenant_claim)
+        .and_then(Value::as_str)
+        .and_then(|s| Uuid::parse_str(s).ok())
+        .ok_or_else(|| AppError::Unauthorized {
+            message: format!("Token is missing a valid '{}' claim", tenant_claim),
+        })?;
+    
+    let scopes = match (claims.get("scope"), claims.get("scp")) {
+        (Some(Value::String(scope)), _) => scope.split_whitespace().map(String::from).collect(),
+        (_, Some(Value::Array(scp))) => scp
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let scopes = scopes.into_iter().filter(|scope| scope != ADMIN_SCOPE).collect();
+    
+    let user_id = claims
+        .get("sub")
+        .and_then(Value::as_str)
+        .and_then(|s| Uuid::parse_str(s).ok());
+    
+    Ok(AuthContext {
+        tenant_id,
+        api_key: None,
+        user_id,
+        scopes,
+        request_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_context_from_claims() {
+        let tenant_id = Uuid::new_v4();
+        
+        let claims = json!({
+            "sub": "auth0|abc",
+            "org_id": tenant_id.to_string(),
+            "scope": "read write",
+        });
+        let context = context_from_claims(&claims, "org_id", "req".into()).unwrap();
+        assert_eq!(context.tenant_id, tenant_id);
+        assert_eq!(context.scopes, vec!["read", "write"]);
+        assert!(context.user_id.is_none());
+        
+        let claims = json!({ "org_id": tenant_id.to_string(), "scp": ["read"] });
+        let context = context_from_claims(&claims, "org_id", "req".into()).unwrap();
+        assert_eq!(context.scopes, vec!["read"]);
+        
+        let claims = json!({ "org_id": tenant_id.to_string(), "scope": "read admin" });
+        let context = context_from_claims(&claims, "org_id", "req".into()).unwrap();
+        assert_eq!(context.scopes, vec!["read"]);
+        assert!(!context.has_scope("admin"));
+        
+        assert!(context_from_claims(&json!({ "sub": "x" }), "org_id", "req".into()).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+//! HMAC request signing
+//!
+//! Machine-to-machine callers can sign requests with a per-tenant shared
+//! secret instead of sending a bearer key:
+//!
+//! ```text
+//! X-Tenant-ID: <tenant_uuid>
+//! X-Timestamp: <unix seconds>
+//! X-Nonce:     <unique per request>
+//! X-Signature: hex(hmac-sha256(secret, timestamp \n nonce \n METHOD \n path \n body))
+//! ```
+//!
+//! Requests outside the timestamp window are rejected, and each nonce is
+//! accepted once per window. Nonces are recorded in Redis when the service
+//! has it, so every replica sees them; otherwise only in the process.
+//!
+//! Signed requests get the scopes of the tenant's API key. Secrets are
+//! provisioned and rotated with `paperforge-admin signing-secret`.
+
+use super::{AuthContext, AuthState, API_KEY_SCOPES};
+use crate::errors::{AppError, Result};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Header carrying the request signature
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// Header carrying the signing timestamp (Unix seconds)
+pub const TIMESTAMP_HEADER: &str = "x-timestamp";
+
+/// Header carrying the request nonce
+pub const NONCE_HEADER: &str = "x-nonce";
+
+/// Largest body buffered for signature verification
+const MAX_SIGNED_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Longest accepted nonce
+const MAX_NONCE_LEN: usize = 128;
+
+/// Prune expired nonces once the cache grows past this size
+const NONCE_PRUNE_THRESHOLD: usize = 10_000;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Generate a new request signing secret
+pub fn generate_signing_secret() -> String {
+    let random_bytes: [u8; 32] = rand::random();
+    hex::encode(random_bytes)
+}
+
+/// Compute the signature of a request
+pub fn sign_request(
+    secret: &str,
+    timestamp: i64,
+    nonce: &str,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> String {
+    hex::encode(signing_mac(secret, timestamp, nonce, method, path, body).finalize().into_bytes())
+}
+
+/// Verify a hex signature in constant time
+pub fn verify_signature(
+    secret: &str,
+    timestamp: i64,
+    nonce: &str,
+    method: &str,
+    path: &str,
+    body: &[u8],
+    signature: &str,
+) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    
+    signing_mac(secret, timestamp, nonce, method, path, body)
+        .verify_slice(&signature)
+        .is_ok()
+}
+
+fn signing_mac(
+    secret: &str,
+    timestamp: i64,
+    nonce: &str,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}\n{}\n{}\n{}\n", timestamp, nonce, method.to_uppercase(), path).as_bytes());
+    mac.update(body);
+    mac
+}
+
+/// Nonces seen within the replay window by this process
+#[derive(Default)]
+pub struct NonceCache {
+    seen: Mutex<HashMap<String, i64>>,
+}
+
+impl NonceCache {
+    /// Record a nonce until `expires_at`; returns `false` if it was already seen
+    pub fn insert(&self, key: String, expires_at: i64, now: i64) -> bool {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        
+        if seen.len() >= NONCE_PRUNE_THRESHOLD {
+            seen.retain(|_, expiry| *expiry > now);
+        }
+        
+        match seen.get(&key) {
+            Some(&expiry) if expiry > now => false,
+            _ => {
+                seen.insert(key, expires_at);
+                true
+            }
+        }
+    }
+}
+
+impl AuthState {
+    /// Authenticate a signed request
+    pub async fn authenticate_signature(&self, parts: &Parts, body: &[u8]) -> Result<AuthContext> {
+        let headers = &parts.headers;
+        
+        let request_id = headers
+            .get("x-request-id")
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        
+        let tenant_id = header(headers, "x-tenant-id")
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .ok_or_else(|| AppError::Unauthorized {
+                message: "Signed requests must set a valid X-Tenant-ID".to_string(),
+            })?;
+        
+        let timestamp: i64 = header(headers, TIMESTAMP_HEADER)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| AppError::Unauthorized {
+                message: "Missing or invalid X-Timestamp header".to_string(),
+            })?;
+        
+        let nonce = header(headers, NONCE_HEADER)
+            .filter(|n| !n.is_empty() && n.len() <= MAX_NONCE_LEN)
+            .ok_or_else(|| AppError::Unauthorized {
+                message: "Missing or invalid X-Nonce header".to_string(),
+            })?;
+        
+        let signature = header(headers, SIGNATURE_HEADER).unwrap_or_default();
+        
+        let now = chrono::Utc::now().timestamp();
+        let window = self.signature_window_secs;
+        if (now - timestamp).abs() > window {
+            return Err(AppError::Unauthorized {
+                message: "Request timestamp outside the allowed window".to_string(),
+            });
+        }
+        
+        let tenant = self
+            .repository
+            .find_tenant_by_id(tenant_id)
+            .await?
+            .filter(|t| t.is_active && t.signing_secret.is_some())
+            .ok_or_else(|| AppError::Unauthorized {
+                message: "Request signing is not enabled for this tenant".to_string(),
+            })?;
+        let secret = tenant.signing_secret.as_deref().unwrap_or_default();
+        
+        let path = parts
+            .uri
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or("/");
+        
+        if !verify_signature(secret, timestamp, nonce, parts.method.as_str(), path, body, signature) {
+            return Err(AppError::Unauthorized {
+                message: "Invalid request signature".to_string(),
+            });
+        }
+        
+        // Record the nonce only after the signature checks out, so unsigned
+        // requests cannot fill the cache. It is kept until the timestamp
+        // leaves the window.
+        let nonce_key = format!("{}:{}", tenant_id, nonce);
+        let fresh = match &self.cache {
+            Some(cache) => {
+                let ttl = Duration::from_secs((timestamp + window - now).max(1) as u64);
+                cache.claim_once(&format!("nonce:{}", nonce_key), ttl).await?
+            }
+            None => self.nonces.insert(nonce_key, timestamp + window, now),
+        };
+        if !fresh {
+            return Err(AppError::Unauthorized {
+                message: "Replayed request".to_string(),
+            });
+        }
+        
+        Ok(AuthContext {
+            tenant_id,
+            api_key: None,
+            user_id: None,
+            scopes: if tenant.api_key_scopes.is_empty() {
+                API_KEY_SCOPES.iter().map(|s| s.to_string()).collect()
+            } else {
+                tenant.api_key_scope_list()
+            },
+            request_id,
+        })
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Middleware verifying HMAC-signed requests
+///
+/// Requests without `X-Signature` pass through untouched. Signed requests
+/// are verified and the resulting [`AuthContext`] is stored in the request
+/// extensions, where the extractor picks it up. Apply this outside any
+/// `nest` so the signed path includes the API prefix.
+pub async fn signature_middleware(
+    State(state): State<AuthState>,
+    request: Request,
+    next: Next,
+) -> std::result::Result<Response, AppError> {
+    if !request.headers().contains_key(SIGNATURE_HEADER) {
+        return Ok(next.run(request).await);
+    }
+    
+    let (mut parts, body) = request.into_parts();
+    
+    let content_length = header(&parts.headers, "content-length")
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_SIGNED_BODY_BYTES {
+        return Err(AppError::PayloadTooLarge {
+            size: content_length,
+            limit: MAX_SIGNED_BODY_BYTES,
+        });
+    }
+    
+    let body = axum::body::to_bytes(body, MAX_SIGNED_BODY_BYTES)
+        .await
+        .map_err(|e| AppError::InvalidFormat {
+            message: format!("Failed to read request body: {}", e),
+        })?;
+    
+    let context = state.authenticate_signature(&parts, &body).await?;
+    parts.extensions.insert(context);
+    
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_signature_roundtrip() {
+        let signature = sign_request("secret", 1_700_000_000, "n1", "post", "/v2/search", b"{}");
+        
+        assert!(verify_signature("secret", 1_700_000_000, "n1", "POST", "/v2/search", b"{}", &signature));
+        assert!(!verify_signature("other", 1_700_000_000, "n1", "POST", "/v2/search", b"{}", &signature));
+        assert!(!verify_signature("secret", 1_700_000_001, "n1", "POST", "/v2/search", b"{}", &signature));
+        assert!(!verify_signature("secret", 1_700_000_000, "n1", "POST", "/v2/search", b"{ }", &signature));
+        assert!(!verify_signature("secret", 1_700_000_000, "n1", "POST", "/v2/search", b"{}", "zz"));
+    }
+    
+    #[test]
+    fn test_nonce_replay() {
+        let cache = NonceCache::default();
+        
+        assert!(cache.insert("t:n1".to_string(), 100, 0));
+        assert!(!cache.insert("t:n1".to_string(), 100, 50));
+        assert!(cache.insert("t:n2".to_string(), 100, 50));
+        // Expired entries can be reused
+        assert!(cache.insert("t:n1".to_string(), 300, 150));
+    }
+}
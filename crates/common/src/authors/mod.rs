@@ -0,0 +1,176 @@
+//! Author names, ORCID iDs and the `author:` search filter
+//!
+//! Paper metadata lists authors under `authors`, either as plain names or
+//! as objects with `name` (or `given`/`family`) and an optional `orcid`.
+//! Crossref backfills plain names, with ORCID iDs alongside in
+//! `author_orcids`. Authors with an ORCID are identified by it; the rest by
+//! their normalized name.
+
+use regex_lite::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// An author as listed on a paper
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorName {
+    pub name: String,
+    /// ORCID iD, e.g. `0000-0002-1825-0097`
+    pub orcid: Option<String>,
+}
+
+impl AuthorName {
+    /// Lowercased name without punctuation, for matching
+    pub fn normalized_name(&self) -> String {
+        normalize_name(&self.name)
+    }
+}
+
+fn orcid_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\b(\d{4})-?(\d{4})-?(\d{4})-?(\d{3}[\dXx])\b").unwrap())
+}
+
+fn author_filter() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r#"(?i)\bauthor:(?:"([^"]*)"|(\S+))"#).unwrap())
+}
+
+/// Lowercase a name, drop punctuation and collapse whitespace
+///
+/// "LeCun, Yann" and "Yann LeCun" stay distinct; names are matched in the
+/// order given.
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Normalize an ORCID iD given bare or as an orcid.org URL
+///
+/// Returns `None` when the input isn't an ORCID iD or its check digit
+/// doesn't match (ISO 7064 MOD 11-2).
+pub fn normalize_orcid(input: &str) -> Option<String> {
+    let caps = orcid_pattern().captures(input.trim())?;
+    let orcid = format!("{}-{}-{}-{}", &caps[1], &caps[2], &caps[3], &caps[4]).to_uppercase();
+    
+    let digits: Vec<u32> = orcid.chars().filter_map(|c| c.to_digit(10)).collect();
+    let total = digits[..15].iter().fold(0, |total, d| (total + d) * 2);
+    let check = (12 - total % 11) % 11;
+    let expected = if check == 10 { 'X' } else { char::from_digit(check, 10)? };
+    
+    orcid.ends_with(expected).then_some(orcid)
+}
+
+/// Authors listed in paper metadata, in order
+pub fn metadata_authors(metadata: &serde_json::Value) -> Vec<AuthorName> {
+    let Some(authors) = metadata.get("authors").and_then(|a| a.as_array()) else {
+        return Vec::new();
+    };
+    let orcids = metadata.get("author_orcids").and_then(|o| o.as_array());
+    
+    authors.iter()
+        .enumerate()
+        .filter_map(|(i, author)| {
+            let (name, orcid) = match author {
+                serde_json::Value::String(name) => (name.clone(), None),
+                serde_json::Value::Object(fields) => {
+                    let text = |key: &str| fields.get(key).and_then(|v| v.as_str());
+                    let name = match (text("name"), text("given"), text("family")) {
+                        (Some(name), _, _) => name.to_string(),
+                        (None, Some(given), Some(family)) => format!("{} {}", given, family),
+                        (None, None, Some(family)) => family.to_string(),
+                        _ => return None,
+                    };
+                    (name, text("orcid").and_then(normalize_orcid))
+                }
+                _ => return None,
+            };
+            let orcid = orcid.or_else(|| {
+                orcids?.get(i)?.as_str().and_then(normalize_orcid)
+            });
+            
+            let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+            (!normalize_name(&name).is_empty()).then_some(AuthorName { name, orcid })
+        })
+        .collect()
+}
+
+/// Split an `author:` filter off a search query
+///
+/// `author:lecun`, `author:"Yann LeCun"` and `author:0000-0002-1825-0097`
+/// are recognized; the first one filters and every one is removed from the
+/// returned query. The filter comes back as an ORCID iD or a normalized name
+/// fragment, see [`normalize_author_filter`].
+pub fn split_author_filter(query: &str) -> (String, Option<String>) {
+    let filter = author_filter()
+        .captures(query)
+        .and_then(|caps| caps.get(1).or(caps.get(2)).map(|m| m.as_str().to_string()))
+        .and_then(|value| normalize_author_filter(&value));
+    
+    let rest = author_filter().replace_all(query, " ");
+    (rest.split_whitespace().collect::<Vec<_>>().join(" "), filter)
+}
+
+/// An author filter value as an ORCID iD, or else a normalized name
+/// fragment; `None` when nothing is left to match
+pub fn normalize_author_filter(value: &str) -> Option<String> {
+    normalize_orcid(value).or_else(|| {
+        Some(normalize_name(value)).filter(|name| !name.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_normalize_orcid() {
+        assert_eq!(
+            normalize_orcid("https://orcid.org/0000-0002-1825-0097").as_deref(),
+            Some("0000-0002-1825-0097")
+        );
+        assert_eq!(normalize_orcid("0000-0002-1694-233x").as_deref(), Some("0000-0002-1694-233X"));
+        // Bad check digit
+        assert_eq!(normalize_orcid("0000-0002-1825-0098"), None);
+        assert_eq!(normalize_orcid("LeCun"), None);
+    }
+    
+    #[test]
+    fn test_metadata_authors() {
+        let metadata = serde_json::json!({
+            "authors": [
+                "Yann  LeCun",
+                {"given": "Yoshua", "family": "Bengio", "orcid": "http://orcid.org/0000-0002-1825-0097"},
+                {"name": "Geoffrey Hinton"},
+                {"affiliation": "Nowhere"},
+            ],
+            "author_orcids": ["0000-0001-5109-3700", null, "not an orcid"],
+        });
+        
+        let authors = metadata_authors(&metadata);
+        assert_eq!(authors.len(), 3);
+        assert_eq!(authors[0].name, "Yann LeCun");
+        assert_eq!(authors[0].orcid.as_deref(), Some("0000-0001-5109-3700"));
+        assert_eq!(authors[1].name, "Yoshua Bengio");
+        assert_eq!(authors[1].orcid.as_deref(), Some("0000-0002-1825-0097"));
+        assert_eq!(authors[2].orcid, None);
+        assert_eq!(authors[2].normalized_name(), "geoffrey hinton");
+    }
+    
+    #[test]
+    fn test_split_author_filter() {
+        assert_eq!(
+            split_author_filter(r#"convolutional networks author:"Yann LeCun""#),
+            ("convolutional networks".to_string(), Some("yann lecun".to_string()))
+        );
+        assert_eq!(
+            split_author_filter("Author:0000-0002-1825-0097 deep learning"),
+            ("deep learning".to_string(), Some("0000-0002-1825-0097".to_string()))
+        );
+        assert_eq!(split_author_filter("transformers"), ("transformers".to_string(), None));
+    }
+}
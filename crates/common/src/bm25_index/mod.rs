@@ -0,0 +1,393 @@
+//! Per-tenant BM25 indexes
+//!
+//! An alternative to Postgres full-text search for large corpora: each
+//! tenant's chunks are indexed with tantivy, in a directory of its own.
+//! - The ingestion worker keeps the indexes up to date from the `chunks`
+//!   table ([`Bm25Index::sync_tenant`])
+//! - The search service queries them ([`Bm25Index::search`]) and loads the
+//!   hits from Postgres, which drops chunks of papers deleted since the last
+//!   sync
+//!
+//! Syncs are incremental: each commit records the last chunk indexed, in
+//! `(created_at, id)` order, and the number of chunks. New chunks are
+//! appended; when the tenant's chunk count no longer adds up (papers were
+//! deleted or reprocessed) the index is rebuilt.
+//!
+//! With `use_storage`, every commit is published to document storage and
+//! the search service fetches indexes from there instead of sharing the
+//! directory.
+
+use crate::config::Bm25IndexConfig;
+use crate::db::Repository;
+use crate::errors::{AppError, Result};
+use crate::storage::ObjectStore;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::{
+    Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, STORED, STRING,
+};
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+/// Memory budget of an index writer
+const WRITER_HEAP_BYTES: usize = 64 * 1024 * 1024;
+
+/// Chunks read from Postgres per page while indexing
+const SYNC_PAGE_SIZE: u64 = 1000;
+
+/// Object listing the files of a published index
+const MANIFEST_OBJECT: &str = "manifest.json";
+
+/// Index files rewritten on every commit; segment files never change
+const MUTABLE_FILES: &[&str] = &["meta.json", ".managed.json"];
+
+/// Progress recorded in each commit's payload
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct SyncState {
+    /// Last chunk indexed, in `(created_at, id)` order
+    after: Option<(DateTimeWithTimeZone, Uuid)>,
+    /// Chunks in the index
+    chunks: i64,
+}
+
+/// Files of a published index
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    files: Vec<String>,
+}
+
+#[derive(Clone, Copy)]
+struct Fields {
+    chunk_id: Field,
+    content: Field,
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let chunk_id = builder.add_text_field("chunk_id", STRING | STORED);
+    let content = builder.add_text_field(
+        "content",
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("en_stem")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        ),
+    );
+    (builder.build(), Fields { chunk_id, content })
+}
+
+fn index_error(err: impl std::fmt::Display) -> AppError {
+    AppError::Internal {
+        message: format!("BM25 index error: {}", err),
+    }
+}
+
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+    tokio::task::spawn_blocking(f).await.map_err(index_error)?
+}
+
+fn open_or_create(dir: &Path) -> Result<Index> {
+    std::fs::create_dir_all(dir)?;
+    let directory = tantivy::directory::MmapDirectory::open(dir).map_err(index_error)?;
+    Index::open_or_create(directory, schema().0).map_err(index_error)
+}
+
+fn sync_state(index: &Index) -> SyncState {
+    index.load_metas()
+        .ok()
+        .and_then(|metas| metas.payload)
+        .and_then(|payload| serde_json::from_str(&payload).ok())
+        .unwrap_or_default()
+}
+
+/// Commit the writer with `state` as its payload
+fn commit(mut writer: IndexWriter, state: &SyncState) -> Result<()> {
+    let mut prepared = writer.prepare_commit().map_err(index_error)?;
+    prepared.set_payload(&serde_json::to_string(state)?);
+    prepared.commit().map_err(index_error)?;
+    writer.wait_merging_threads().map_err(index_error)
+}
+
+/// A tenant's index, open for searching
+struct TenantReader {
+    reader: IndexReader,
+    parser: QueryParser,
+    chunk_id: Field,
+    refreshed_at: Mutex<Instant>,
+}
+
+impl TenantReader {
+    fn open(dir: &Path) -> Result<Option<Self>> {
+        if !dir.join("meta.json").exists() {
+            return Ok(None);
+        }
+        
+        let index = Index::open_in_dir(dir).map_err(index_error)?;
+        let (_, fields) = schema();
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(index_error)?;
+        Ok(Some(Self {
+            reader,
+            parser: QueryParser::for_index(&index, vec![fields.content]),
+            chunk_id: fields.chunk_id,
+            refreshed_at: Mutex::new(Instant::now()),
+        }))
+    }
+    
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<(Uuid, f32)>> {
+        let searcher = self.reader.searcher();
+        // Free text from users: ignore query syntax the parser can't use
+        let (query, _) = self.parser.parse_query_lenient(query);
+        let top = searcher.search(&query, &TopDocs::with_limit(limit)).map_err(index_error)?;
+        
+        let mut hits = Vec::with_capacity(top.len());
+        for (score, address) in top {
+            let doc: TantivyDocument = searcher.doc(address).map_err(index_error)?;
+            if let Some(id) = doc
+                .get_first(self.chunk_id)
+                .and_then(|value| value.as_str())
+                .and_then(|id| Uuid::parse_str(id).ok())
+            {
+                hits.push((id, score));
+            }
+        }
+        Ok(hits)
+    }
+}
+
+/// Per-tenant tantivy indexes under one directory
+pub struct Bm25Index {
+    config: Bm25IndexConfig,
+    storage: Option<Arc<dyn ObjectStore>>,
+    readers: Mutex<HashMap<Uuid, Arc<TenantReader>>>,
+}
+
+impl Bm25Index {
+    /// Create a handle on the configured indexes
+    ///
+    /// `storage` is required when the config sets `use_storage`.
+    pub fn new(config: Bm25IndexConfig, storage: Option<Arc<dyn ObjectStore>>) -> Result<Self> {
+        if config.use_storage && storage.is_none() {
+            return Err(AppError::Configuration {
+                message: "bm25_index.use_storage needs document storage to be enabled".to_string(),
+            });
+        }
+        
+        let storage = storage.filter(|_| config.use_storage);
+        Ok(Self {
+            config,
+            storage,
+            readers: Mutex::new(HashMap::new()),
+        })
+    }
+    
+    fn tenant_dir(&self, tenant_id: Uuid) -> PathBuf {
+        Path::new(&self.config.dir).join(tenant_id.to_string())
+    }
+    
+    fn object_key(tenant_id: Uuid, file: &str) -> String {
+        format!("bm25/{}/{}", tenant_id, file)
+    }
+    
+    /// Bring a tenant's index up to date with its chunks
+    ///
+    /// Returns the number of chunks indexed.
+    pub async fn sync_tenant(&self, repo: &Repository, tenant_id: Uuid) -> Result<usize> {
+        let dir = self.tenant_dir(tenant_id);
+        let index = {
+            let dir = dir.clone();
+            blocking(move || open_or_create(&dir)).await?
+        };
+        
+        let mut state = sync_state(&index);
+        let (total, newer) = repo.count_index_chunks(tenant_id, state.after).await?;
+        let rebuild = total != state.chunks + newer;
+        if !rebuild && newer == 0 {
+            return Ok(0);
+        }
+        
+        let (_, fields) = schema();
+        let writer: IndexWriter = index.writer(WRITER_HEAP_BYTES).map_err(index_error)?;
+        if rebuild {
+            debug!(tenant_id = %tenant_id, indexed = state.chunks, total = total, "Rebuilding BM25 index");
+            writer.delete_all_documents().map_err(index_error)?;
+            state = SyncState::default();
+        }
+        
+        let mut indexed = 0;
+        loop {
+            let page = repo.list_index_chunks(tenant_id, state.after, SYNC_PAGE_SIZE).await?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            state.after = Some((last.created_at, last.id));
+            
+            for chunk in &page {
+                writer
+                    .add_document(doc!(
+                        fields.chunk_id => chunk.id.to_string(),
+                        fields.content => chunk.content.as_str(),
+                    ))
+                    .map_err(index_error)?;
+            }
+            indexed += page.len();
+        }
+        state.chunks += indexed as i64;
+        
+        let committed = state.clone();
+        blocking(move || commit(writer, &committed)).await?;
+        info!(tenant_id = %tenant_id, indexed = indexed, chunks = state.chunks, rebuild = rebuild, "BM25 index updated");
+        
+        if let Some(storage) = &self.storage {
+            self.publish(storage.as_ref(), tenant_id, &dir).await?;
+        }
+        Ok(indexed)
+    }
+    
+    /// Upload the tenant's index files, skipping segments already uploaded
+    async fn publish(&self, storage: &dyn ObjectStore, tenant_id: Uuid, dir: &Path) -> Result<()> {
+        let uploaded: HashSet<String> = match storage.get(&Self::object_key(tenant_id, MANIFEST_OBJECT)).await? {
+            Some(body) => serde_json::from_slice::<Manifest>(&body)?.files.into_iter().collect(),
+            None => HashSet::new(),
+        };
+        
+        let mut manifest = Manifest::default();
+        for entry in std::fs::read_dir(dir)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".lock") {
+                continue;
+            }
+            if MUTABLE_FILES.contains(&name.as_str()) || !uploaded.contains(&name) {
+                let body = tokio::fs::read(dir.join(&name)).await?;
+                storage.put(&Self::object_key(tenant_id, &name), body, "application/octet-stream").await?;
+            }
+            manifest.files.push(name);
+        }
+        
+        // The manifest goes last, so readers never see files missing
+        storage.put(
+            &Self::object_key(tenant_id, MANIFEST_OBJECT),
+            serde_json::to_vec(&manifest)?,
+            "application/json",
+        ).await
+    }
+    
+    /// Download the tenant's published index; false when there is none
+    async fn fetch(&self, storage: &dyn ObjectStore, tenant_id: Uuid, dir: &Path) -> Result<bool> {
+        let Some(body) = storage.get(&Self::object_key(tenant_id, MANIFEST_OBJECT)).await? else {
+            return Ok(false);
+        };
+        let manifest: Manifest = serde_json::from_slice(&body)?;
+        tokio::fs::create_dir_all(dir).await?;
+        
+        // Segments first and `meta.json` last, so a reader opening the
+        // directory meanwhile sees either commit whole
+        let mut files = manifest.files;
+        files.sort_by_key(|name| name == "meta.json");
+        for name in files {
+            let path = dir.join(&name);
+            if !MUTABLE_FILES.contains(&name.as_str()) && path.exists() {
+                continue;
+            }
+            let Some(body) = storage.get(&Self::object_key(tenant_id, &name)).await? else {
+                return Err(index_error(format!("published file {} is missing", name)));
+            };
+            let partial = dir.join(format!("{}.part", name));
+            tokio::fs::write(&partial, body).await?;
+            tokio::fs::rename(&partial, &path).await?;
+        }
+        Ok(true)
+    }
+    
+    /// The tenant's reader, reloaded when it is older than `refresh_secs`
+    async fn reader(&self, tenant_id: Uuid) -> Result<Option<Arc<TenantReader>>> {
+        let refresh = Duration::from_secs(self.config.refresh_secs);
+        let cached = self.readers.lock().unwrap().get(&tenant_id).cloned();
+        let dir = self.tenant_dir(tenant_id);
+        
+        if let Some(reader) = &cached {
+            if reader.refreshed_at.lock().unwrap().elapsed() < refresh {
+                return Ok(cached);
+            }
+        }
+        
+        if let Some(storage) = &self.storage {
+            self.fetch(storage.as_ref(), tenant_id, &dir).await?;
+        }
+        
+        if let Some(reader) = cached {
+            reader.reader.reload().map_err(index_error)?;
+            *reader.refreshed_at.lock().unwrap() = Instant::now();
+            return Ok(Some(reader));
+        }
+        
+        let Some(reader) = blocking(move || TenantReader::open(&dir)).await? else {
+            return Ok(None);
+        };
+        let reader = Arc::new(reader);
+        self.readers.lock().unwrap().insert(tenant_id, reader.clone());
+        Ok(Some(reader))
+    }
+    
+    /// Best-matching chunk ids for `query` in the tenant's index, with BM25
+    /// scores, best first
+    ///
+    /// Tenants without an index yet have no matches.
+    pub async fn search(&self, tenant_id: Uuid, query: &str, limit: usize) -> Result<Vec<(Uuid, f32)>> {
+        let Some(reader) = self.reader(tenant_id).await? else {
+            return Ok(Vec::new());
+        };
+        
+        let query = query.to_string();
+        blocking(move || reader.search(&query, limit)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_index_round_trip() {
+        let dir = std::env::temp_dir().join(format!("paperforge-bm25-{}", Uuid::new_v4()));
+        let index = open_or_create(&dir).unwrap();
+        let (_, fields) = schema();
+        
+        let attention = Uuid::new_v4();
+        let graphs = Uuid::new_v4();
+        let writer: IndexWriter = index.writer(WRITER_HEAP_BYTES).unwrap();
+        writer.add_document(doc!(
+            fields.chunk_id => attention.to_string(),
+            fields.content => "Transformers rely on attention mechanisms over token sequences",
+        )).unwrap();
+        writer.add_document(doc!(
+            fields.chunk_id => graphs.to_string(),
+            fields.content => "Graph neural networks aggregate neighbour features",
+        )).unwrap();
+        
+        let state = SyncState {
+            after: Some((chrono::Utc::now().into(), graphs)),
+            chunks: 2,
+        };
+        commit(writer, &state).unwrap();
+        assert_eq!(sync_state(&open_or_create(&dir).unwrap()), state);
+        
+        // Stemming matches "attention mechanism" against "mechanisms"
+        let reader = TenantReader::open(&dir).unwrap().unwrap();
+        let hits = reader.search("attention mechanism (", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, attention);
+        
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
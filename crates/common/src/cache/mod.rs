@@ -3,17 +3,30 @@
 //! Provides:
 //! - Connection pool management
 //! - Generic get/set operations with TTL
+//! - Pipelined multi-key get/set, one round trip per batch
+//! - Optional in-process tier in front of Redis for hot keys
+//! - Optional compression of large values
+//! - Distributed locks for singleton background tasks
 //! - Query result caching
 //! - Session storage
 
+use crate::config::{CacheCompression, RedisConfig};
 use crate::errors::{AppError, Result};
+use crate::metrics;
 use redis::{AsyncCommands, Client, aio::MultiplexedConnection};
 use serde::{de::DeserializeOwned, Serialize};
+use moka::Expiry;
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::future::Future;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, warn};
 
+/// Key prefix of values every service must agree on, whatever prefix its
+/// own cache uses
+pub const SHARED_KEY_PREFIX: &str = "paperforge";
+
 /// Redis cache configuration
 #[derive(Debug, Clone)]
 pub struct CacheConfig {
@@ -25,6 +38,14 @@ pub struct CacheConfig {
     pub pool_size: usize,
     /// Key prefix for namespacing
     pub key_prefix: String,
+    /// Size of the in-process tier in bytes; 0 disables it
+    pub local_max_bytes: u64,
+    /// Longest an entry is served from the in-process tier
+    pub local_ttl: Duration,
+    /// Compression for values of at least `compress_above_bytes`
+    pub compression: CacheCompression,
+    /// Smallest serialized value that is compressed
+    pub compress_above_bytes: usize,
 }
 
 impl Default for CacheConfig {
@@ -33,15 +54,97 @@ impl Default for CacheConfig {
             url: "redis://localhost:6379".to_string(),
             default_ttl_secs: 300,
             pool_size: 10,
-            key_prefix: "paperforge".to_string(),
+            key_prefix: SHARED_KEY_PREFIX.to_string(),
+            local_max_bytes: 0,
+            local_ttl: Duration::from_secs(10),
+            compression: CacheCompression::None,
+            compress_above_bytes: 16 * 1024,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Cache settings from the service configuration
+    pub fn from_redis(redis: &RedisConfig) -> Self {
+        Self {
+            url: redis.url.clone(),
+            default_ttl_secs: redis.default_ttl_secs,
+            pool_size: redis.pool_size as usize,
+            local_max_bytes: redis.local_cache_max_bytes,
+            local_ttl: Duration::from_secs(redis.local_cache_ttl_secs),
+            compression: redis.compression,
+            compress_above_bytes: redis.compress_above_bytes,
+            ..Default::default()
         }
     }
 }
 
+/// Takes a lock and, only if it was free, bumps its fencing counter
+const LOCK_SCRIPT: &str = r#"
+if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+    return redis.call('INCR', KEYS[2])
+end
+return 0
+"#;
+
+/// Deletes a lock only while it still belongs to the caller
+const UNLOCK_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// A distributed lock held by this process, see [`Cache::lock`]
+#[derive(Debug)]
+pub struct Lock {
+    key: String,
+    owner: String,
+    /// Increases with every acquisition of the lock, so work done under a
+    /// lock that has since expired and been taken over can be told apart
+    pub fencing_token: u64,
+}
+
+/// Serialized value held in the in-process tier, with its own lifetime
+#[derive(Clone)]
+struct LocalEntry {
+    json: Arc<str>,
+    ttl: Duration,
+}
+
+/// Expires local entries after their TTL, which is at most the Redis TTL
+struct LocalExpiry;
+
+impl Expiry<String, LocalEntry> for LocalExpiry {
+    fn expire_after_create(&self, _key: &String, entry: &LocalEntry, _created_at: Instant) -> Option<Duration> {
+        Some(entry.ttl)
+    }
+    
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        entry: &LocalEntry,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        Some(entry.ttl)
+    }
+}
+
+type LocalCache = moka::future::Cache<String, LocalEntry>;
+
 /// Redis cache client
+///
+/// With `local_max_bytes` set, values are also kept in process for up to
+/// `local_ttl`: reads try the local tier before Redis, and writes and deletes
+/// go through both. Other instances don't see local invalidations, so a
+/// changed value can be served stale there for up to `local_ttl`.
 pub struct Cache {
     client: Client,
     connection: RwLock<MultiplexedConnection>,
+    local: Option<LocalCache>,
+    /// TTL used by [`Cache::set`], changeable at runtime
+    default_ttl_secs: AtomicU64,
     config: CacheConfig,
 }
 
@@ -63,6 +166,8 @@ impl Cache {
         Ok(Self {
             client,
             connection: RwLock::new(connection),
+            local: local_cache(&config),
+            default_ttl_secs: AtomicU64::new(config.default_ttl_secs),
             config,
         })
     }
@@ -75,12 +180,10 @@ impl Cache {
     /// Get a value from cache
     pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
         let full_key = self.key(key);
-        let mut conn = self.connection.write().await;
-        
-        let value: Option<String> = conn.get(&full_key).await
-            .map_err(|e| AppError::CacheError {
-                message: format!("Failed to get key '{}': {}", full_key, e),
-            })?;
+        let value = match self.local_get(&full_key).await {
+            Some(json) => Some(json),
+            None => self.redis_get(&full_key).await?,
+        };
         
         match value {
             Some(json) => {
@@ -98,9 +201,167 @@ impl Cache {
         }
     }
     
+    /// Bytes stored in Redis for a serialized value
+    fn encode(&self, json: &str) -> Vec<u8> {
+        if json.len() < self.config.compress_above_bytes {
+            return json.as_bytes().to_vec();
+        }
+        encode(json, self.config.compression)
+    }
+    
+    /// Read from the in-process tier
+    async fn local_get(&self, full_key: &str) -> Option<Arc<str>> {
+        let entry = self.local.as_ref()?.get(full_key).await?;
+        debug!(key = %full_key, "Local cache hit");
+        Some(entry.json)
+    }
+    
+    /// Read from Redis, keeping a hit in the in-process tier
+    async fn redis_get(&self, full_key: &str) -> Result<Option<Arc<str>>> {
+        let mut conn = self.connection.write().await;
+        let stored: Option<Vec<u8>> = conn.get(full_key).await
+            .map_err(|e| AppError::CacheError {
+                message: format!("Failed to get key '{}': {}", full_key, e),
+            })?;
+        let Some(stored) = stored else {
+            return Ok(None);
+        };
+        let json: Arc<str> = decode(stored)?.into();
+        
+        if self.local.is_some() {
+            let ttl: i64 = conn.ttl(full_key).await.unwrap_or(-1);
+            self.local_insert(full_key, json.clone(), ttl).await;
+        }
+        Ok(Some(json))
+    }
+    
+    /// Keep a value read from Redis in the in-process tier
+    ///
+    /// `redis_ttl` is the key's remaining TTL in seconds as Redis reports it;
+    /// the local copy mustn't outlive the Redis one.
+    async fn local_insert(&self, full_key: &str, json: Arc<str>, redis_ttl: i64) {
+        let Some(local) = &self.local else {
+            return;
+        };
+        let ttl = match u64::try_from(redis_ttl) {
+            Ok(secs) => self.config.local_ttl.min(Duration::from_secs(secs)),
+            Err(_) => self.config.local_ttl,
+        };
+        local.insert(full_key.to_string(), LocalEntry { json, ttl }).await;
+    }
+    
+    /// Get several values in one round trip
+    ///
+    /// Returns a value or `None` for each key, in order. Keys in the
+    /// in-process tier aren't sent to Redis. A cached value that fails to
+    /// parse counts as a miss.
+    pub async fn get_many<T: DeserializeOwned>(&self, keys: &[String]) -> Result<Vec<Option<T>>> {
+        let full_keys: Vec<String> = keys.iter().map(|key| self.key(key)).collect();
+        let mut values = Vec::with_capacity(keys.len());
+        for full_key in &full_keys {
+            values.push(self.local_get(full_key).await);
+        }
+        
+        let missing: Vec<usize> = (0..values.len()).filter(|&i| values[i].is_none()).collect();
+        if !missing.is_empty() {
+            let mut pipe = redis::pipe();
+            for &i in &missing {
+                pipe.get(&full_keys[i]);
+                if self.local.is_some() {
+                    pipe.ttl(&full_keys[i]);
+                }
+            }
+            
+            let mut conn = self.connection.write().await;
+            let replies: Vec<redis::Value> = pipe.query_async(&mut *conn).await
+                .map_err(|e| AppError::CacheError {
+                    message: format!("Failed to get {} keys: {}", missing.len(), e),
+                })?;
+            drop(conn);
+            
+            let stride = if self.local.is_some() { 2 } else { 1 };
+            for (&i, reply) in missing.iter().zip(replies.chunks(stride)) {
+                let stored: Option<Vec<u8>> = redis::from_redis_value(&reply[0]).unwrap_or(None);
+                let Some(stored) = stored else {
+                    continue;
+                };
+                let json: Arc<str> = match decode(stored) {
+                    Ok(json) => json.into(),
+                    Err(e) => {
+                        warn!(key = %full_keys[i], error = %e, "Failed to decode cached value");
+                        continue;
+                    }
+                };
+                if let Some(ttl) = reply.get(1) {
+                    let ttl = redis::from_redis_value(ttl).unwrap_or(-1);
+                    self.local_insert(&full_keys[i], json.clone(), ttl).await;
+                }
+                values[i] = Some(json);
+            }
+        }
+        
+        let hits = values.iter().filter(|v| v.is_some()).count();
+        debug!(keys = keys.len(), hits, "Cache multi-get");
+        Ok(values
+            .into_iter()
+            .zip(&full_keys)
+            .map(|(json, full_key)| {
+                serde_json::from_str(&json?)
+                    .map_err(|e| warn!(key = %full_key, error = %e, "Failed to parse cached value"))
+                    .ok()
+            })
+            .collect())
+    }
+    
+    /// Set several values with the same TTL in one round trip
+    pub async fn set_many<T: Serialize>(&self, entries: &[(String, T)], ttl_secs: u64) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        
+        let mut serialized = Vec::with_capacity(entries.len());
+        let mut pipe = redis::pipe();
+        for (key, value) in entries {
+            let full_key = self.key(key);
+            let json = serde_json::to_string(value)
+                .map_err(|e| AppError::CacheError {
+                    message: format!("Failed to serialize value: {}", e),
+                })?;
+            pipe.set_ex(&full_key, self.encode(&json), ttl_secs).ignore();
+            serialized.push((full_key, json));
+        }
+        
+        let mut conn = self.connection.write().await;
+        pipe.query_async::<()>(&mut *conn).await
+            .map_err(|e| AppError::CacheError {
+                message: format!("Failed to set {} keys: {}", entries.len(), e),
+            })?;
+        drop(conn);
+        
+        if let Some(local) = &self.local {
+            let ttl = self.config.local_ttl.min(Duration::from_secs(ttl_secs));
+            for (full_key, json) in serialized {
+                local.insert(full_key, LocalEntry { json: json.into(), ttl }).await;
+            }
+        }
+        
+        debug!(keys = entries.len(), ttl_secs, "Cache multi-set");
+        Ok(())
+    }
+    
     /// Set a value in cache with default TTL
     pub async fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
-        self.set_with_ttl(key, value, self.config.default_ttl_secs).await
+        self.set_with_ttl(key, value, self.default_ttl()).await
+    }
+    
+    /// TTL used by [`Cache::set`], in seconds
+    pub fn default_ttl(&self) -> u64 {
+        self.default_ttl_secs.load(Ordering::Relaxed)
+    }
+    
+    /// Change the TTL of values set from now on, e.g. after a config reload
+    pub fn set_default_ttl(&self, ttl_secs: u64) {
+        self.default_ttl_secs.store(ttl_secs, Ordering::Relaxed);
     }
     
     /// Set a value in cache with custom TTL
@@ -112,12 +373,17 @@ impl Cache {
             })?;
         
         let mut conn = self.connection.write().await;
-        conn.set_ex(&full_key, &json, ttl_secs)
+        conn.set_ex(&full_key, self.encode(&json), ttl_secs)
             .await
             .map_err(|e| AppError::CacheError {
                 message: format!("Failed to set key '{}': {}", full_key, e),
             })?;
         
+        if let Some(local) = &self.local {
+            let ttl = self.config.local_ttl.min(Duration::from_secs(ttl_secs));
+            local.insert(full_key.clone(), LocalEntry { json: json.into(), ttl }).await;
+        }
+        
         debug!(key = %full_key, ttl_secs, "Cache set");
         Ok(())
     }
@@ -125,6 +391,9 @@ impl Cache {
     /// Delete a key from cache
     pub async fn delete(&self, key: &str) -> Result<bool> {
         let full_key = self.key(key);
+        if let Some(local) = &self.local {
+            local.invalidate(&full_key).await;
+        }
         let mut conn = self.connection.write().await;
         
         let deleted: i32 = conn.del(&full_key).await
@@ -177,6 +446,127 @@ impl Cache {
         Ok(value)
     }
     
+    /// Current generation of a tenant's cached search results
+    ///
+    /// Part of every search result key, so bumping it with
+    /// [`invalidate_search`](Self::invalidate_search) retires all of the
+    /// tenant's cached results at once. Always read from Redis, never from
+    /// the in-process tier. The gateway, ingestion and embedding worker bump
+    /// it while the search service reads it, so it lives under
+    /// [`SHARED_KEY_PREFIX`] rather than this cache's prefix.
+    pub async fn search_generation(&self, tenant_id: uuid::Uuid) -> Result<u64> {
+        let full_key = search_generation_key(tenant_id);
+        let mut conn = self.connection.write().await;
+        let generation: Option<u64> = conn.get(&full_key).await
+            .map_err(|e| AppError::CacheError {
+                message: format!("Failed to get key '{}': {}", full_key, e),
+            })?;
+        Ok(generation.unwrap_or(0))
+    }
+    
+    /// Retire a tenant's cached search results after its papers change
+    ///
+    /// The old entries aren't deleted; no key refers to them any more and they
+    /// expire with their TTL.
+    pub async fn invalidate_search(&self, tenant_id: uuid::Uuid) -> Result<u64> {
+        let full_key = search_generation_key(tenant_id);
+        let mut conn = self.connection.write().await;
+        let generation: u64 = conn.incr(&full_key, 1).await
+            .map_err(|e| AppError::CacheError {
+                message: format!("Failed to bump key '{}': {}", full_key, e),
+            })?;
+        debug!(tenant_id = %tenant_id, generation, "Search cache invalidated");
+        Ok(generation)
+    }
+    
+    /// Take the distributed lock `name` for at most `ttl`
+    ///
+    /// Returns `None` when another holder has it. The lock expires after
+    /// `ttl` even if it isn't released, so a crashed holder can't keep it.
+    pub async fn lock(&self, name: &str, ttl: Duration) -> Result<Option<Lock>> {
+        let (key, fence_key) = lock_keys(&self.config.key_prefix, name);
+        let owner = uuid::Uuid::new_v4().to_string();
+        
+        let mut conn = self.connection.write().await;
+        let token: u64 = redis::Script::new(LOCK_SCRIPT)
+            .key(&key)
+            .key(&fence_key)
+            .arg(&owner)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| AppError::CacheError {
+                message: format!("Failed to take lock '{}': {}", key, e),
+            })?;
+        
+        if token == 0 {
+            debug!(key = %key, "Lock held elsewhere");
+            return Ok(None);
+        }
+        debug!(key = %key, fencing_token = token, "Lock taken");
+        Ok(Some(Lock { key, owner, fencing_token: token }))
+    }
+    
+    /// Release a lock taken with [`lock`](Self::lock)
+    ///
+    /// Returns `false` when the lock had already expired; it may then be held
+    /// by someone else, whose lock is left alone.
+    pub async fn unlock(&self, lock: Lock) -> Result<bool> {
+        let mut conn = self.connection.write().await;
+        let released: i32 = redis::Script::new(UNLOCK_SCRIPT)
+            .key(&lock.key)
+            .arg(&lock.owner)
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| AppError::CacheError {
+                message: format!("Failed to release lock '{}': {}", lock.key, e),
+            })?;
+        Ok(released > 0)
+    }
+    
+    /// Claim `name` for `ttl`; returns `false` if it is already claimed
+    ///
+    /// A `SET NX EX`, so a name is claimed once across every instance
+    /// sharing the Redis until it expires, e.g. a signed request's nonce.
+    pub async fn claim_once(&self, name: &str, ttl: Duration) -> Result<bool> {
+        let key = format!("{}:once:{}", self.config.key_prefix, name);
+        
+        let mut conn = self.connection.write().await;
+        let claimed: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| AppError::CacheError {
+                message: format!("Failed to claim '{}': {}", key, e),
+            })?;
+        Ok(claimed.is_some())
+    }
+    
+    /// Run `task` under the lock `name`, unless another holder has it
+    ///
+    /// Returns `None` without running `task` when the lock is taken. `ttl`
+    /// should exceed the task's longest run; a task outliving it is warned
+    /// about, as another instance may have started the same work.
+    pub async fn with_lock<T>(&self, name: &str, ttl: Duration, task: impl Future<Output = T>) -> Result<Option<T>> {
+        let Some(lock) = self.lock(name, ttl).await? else {
+            return Ok(None);
+        };
+        let fencing_token = lock.fencing_token;
+        let output = task.await;
+        
+        match self.unlock(lock).await {
+            Ok(true) => {}
+            Ok(false) => warn!(lock = %name, fencing_token, "Lock expired before the task finished"),
+            // It expires on its own
+            Err(e) => warn!(lock = %name, error = %e, "Failed to release lock"),
+        }
+        Ok(Some(output))
+    }
+    
     /// Ping Redis to check connectivity
     pub async fn ping(&self) -> Result<()> {
         let mut conn = self.connection.write().await;
@@ -190,13 +580,105 @@ impl Cache {
     }
 }
 
+/// Redis keys of a lock and its fencing counter
+///
+/// The hash tag keeps both in one cluster slot, as the lock script needs.
+fn lock_keys(prefix: &str, name: &str) -> (String, String) {
+    let key = format!("{}:lock:{{{}}}", prefix, name);
+    let fence_key = format!("{}:fence", key);
+    (key, fence_key)
+}
+
+/// First byte of a zstd-compressed value
+const FORMAT_ZSTD: u8 = 0x01;
+
+/// First byte of an lz4-compressed value
+const FORMAT_LZ4: u8 = 0x02;
+
+/// Compress a serialized value, prefixed with its format byte
+///
+/// Uncompressed values are stored as plain JSON, which never starts with a
+/// format byte, so values written before compression was enabled still
+/// read. A value that doesn't shrink is stored uncompressed.
+fn encode(json: &str, compression: CacheCompression) -> Vec<u8> {
+    let (format, algorithm, compressed) = match compression {
+        CacheCompression::None => return json.as_bytes().to_vec(),
+        CacheCompression::Zstd => match zstd::bulk::compress(json.as_bytes(), 0) {
+            Ok(compressed) => (FORMAT_ZSTD, "zstd", compressed),
+            Err(e) => {
+                warn!(error = %e, "Failed to compress cache value, storing it uncompressed");
+                return json.as_bytes().to_vec();
+            }
+        },
+        CacheCompression::Lz4 => (FORMAT_LZ4, "lz4", lz4_flex::compress_prepend_size(json.as_bytes())),
+    };
+    
+    metrics::record_cache_compression(algorithm, json.len(), compressed.len() + 1);
+    if compressed.len() + 1 >= json.len() {
+        return json.as_bytes().to_vec();
+    }
+    let mut stored = Vec::with_capacity(compressed.len() + 1);
+    stored.push(format);
+    stored.extend_from_slice(&compressed);
+    stored
+}
+
+/// Serialized value from bytes stored in Redis, whatever the compression
+/// setting was when it was written
+fn decode(stored: Vec<u8>) -> Result<String> {
+    let decompressed = match stored.first() {
+        Some(&FORMAT_ZSTD) => zstd::stream::decode_all(&stored[1..])
+            .map_err(|e| e.to_string()),
+        Some(&FORMAT_LZ4) => lz4_flex::decompress_size_prepended(&stored[1..])
+            .map_err(|e| e.to_string()),
+        _ => Ok(stored),
+    };
+    decompressed
+        .and_then(|bytes| String::from_utf8(bytes).map_err(|e| e.to_string()))
+        .map_err(|e| AppError::CacheError {
+            message: format!("Failed to decode cached value: {}", e),
+        })
+}
+
+/// Redis key of a tenant's search result generation, the same for every cache
+fn search_generation_key(tenant_id: uuid::Uuid) -> String {
+    format!("{}:{}", SHARED_KEY_PREFIX, keys::search_generation(tenant_id))
+}
+
+/// In-process tier for `config`, if enabled
+///
+/// Entries are weighed by their serialized size so the tier stays within
+/// `local_max_bytes`.
+fn local_cache(config: &CacheConfig) -> Option<LocalCache> {
+    if config.local_max_bytes == 0 {
+        return None;
+    }
+    
+    let cache = moka::future::Cache::builder()
+        .max_capacity(config.local_max_bytes)
+        .weigher(|key: &String, entry: &LocalEntry| {
+            u32::try_from(key.len() + entry.json.len()).unwrap_or(u32::MAX)
+        })
+        .expire_after(LocalExpiry)
+        .build();
+    Some(cache)
+}
+
 /// Cache key builder helpers
 pub mod keys {
     use uuid::Uuid;
     
     /// Build a search query cache key
-    pub fn search_query(tenant_id: Uuid, query_hash: &str, mode: &str) -> String {
-        format!("search:{}:{}:{}", tenant_id, mode, query_hash)
+    ///
+    /// `generation` is the tenant's current
+    /// [`search_generation`](super::Cache::search_generation).
+    pub fn search_query(tenant_id: Uuid, generation: u64, query_hash: &str, mode: &str) -> String {
+        format!("search:{}:{}:{}:{}", tenant_id, generation, mode, query_hash)
+    }
+    
+    /// Build the key of a tenant's search result generation
+    pub fn search_generation(tenant_id: Uuid) -> String {
+        format!("search_generation:{}", tenant_id)
     }
     
     /// Build a session cache key
@@ -218,6 +700,17 @@ pub mod keys {
     pub fn rate_limit(tenant_id: Uuid, endpoint: &str) -> String {
         format!("ratelimit:{}:{}", tenant_id, endpoint)
     }
+    
+    /// Build a query understanding cache key
+    pub fn query_understanding(query_hash: &str) -> String {
+        format!("query:{}", query_hash)
+    }
+    
+    /// Hex SHA-256 of free text, for the hash part of a key
+    pub fn digest(text: &str) -> String {
+        use sha2::{Digest, Sha256};
+        hex::encode(Sha256::digest(text.as_bytes()))
+    }
 }
 
 #[cfg(test)]
@@ -229,8 +722,67 @@ mod tests {
         let tenant_id = uuid::Uuid::new_v4();
         let session_id = uuid::Uuid::new_v4();
         
-        assert!(keys::search_query(tenant_id, "abc123", "hybrid").contains("search:"));
+        assert!(keys::search_query(tenant_id, 0, "abc123", "hybrid").contains("search:"));
+        assert_ne!(
+            keys::search_query(tenant_id, 0, "abc123", "hybrid"),
+            keys::search_query(tenant_id, 1, "abc123", "hybrid")
+        );
         assert!(keys::session(session_id).contains("session:"));
         assert!(keys::embedding("hash", "ada-002").contains("embedding:"));
     }
+    
+    #[test]
+    fn test_search_generation_key_ignores_cache_prefix() {
+        let tenant_id = uuid::Uuid::new_v4();
+        assert_eq!(
+            search_generation_key(tenant_id),
+            format!("paperforge:search_generation:{}", tenant_id)
+        );
+    }
+    
+    #[test]
+    fn test_lock_keys_share_a_slot() {
+        let (key, fence_key) = lock_keys("paperforge", "alerts");
+        assert_eq!(key, "paperforge:lock:{alerts}");
+        assert_eq!(fence_key, "paperforge:lock:{alerts}:fence");
+    }
+    
+    #[test]
+    fn test_compressed_values_round_trip() {
+        let json = serde_json::to_string(&vec!["graph neural networks"; 200]).unwrap();
+        
+        for compression in [CacheCompression::Zstd, CacheCompression::Lz4] {
+            let stored = encode(&json, compression);
+            assert!(stored.len() < json.len() / 4);
+            assert!(matches!(stored[0], FORMAT_ZSTD | FORMAT_LZ4));
+            assert_eq!(decode(stored).unwrap(), json);
+        }
+        
+        // Plain JSON, including values cached before compression was enabled
+        assert_eq!(encode("[1,2]", CacheCompression::Zstd), b"[1,2]");
+        assert_eq!(decode(b"{\"a\":1}".to_vec()).unwrap(), "{\"a\":1}");
+        assert!(decode(vec![FORMAT_ZSTD, 0xff]).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_local_tier_respects_size_and_ttl() {
+        assert!(local_cache(&CacheConfig::default()).is_none());
+        
+        let config = CacheConfig {
+            local_max_bytes: 64,
+            local_ttl: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let local = local_cache(&config).unwrap();
+        let entry = |json: &str, ttl| LocalEntry { json: json.into(), ttl };
+        
+        local.insert("a".to_string(), entry("\"value\"", config.local_ttl)).await;
+        local.insert("big".to_string(), entry(&"x".repeat(100), config.local_ttl)).await;
+        local.run_pending_tasks().await;
+        assert_eq!(local.get("a").await.unwrap().json.as_ref(), "\"value\"");
+        assert!(local.get("big").await.is_none());
+        
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(local.get("a").await.is_none());
+    }
 }
@@ -0,0 +1,178 @@
+//! Search score calibration
+//!
+//! Raw scores are on different scales per retrieval mode: cosine
+//! similarity for vector search, `ts_rank_cd` for BM25 and summed
+//! reciprocal ranks for hybrid search. [`ScoreCalibrator`] maps each mode's
+//! raw scores onto 0-1 with a min-max mapping between low and high
+//! quantiles of the scores it has seen, so `score` and `min_score` mean the
+//! same thing in every mode.
+//!
+//! Until a mode has seen enough scores, fixed priors for its typical range
+//! are used. The mapping is monotonic, so rankings never change.
+
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Raw score ranges used before enough scores have been sampled
+const PRIORS: &[(&str, f64, f64)] = &[
+    ("vector", 0.2, 0.9),
+    ("bm25", 0.0, 0.5),
+    // Reciprocal rank fusion: a first place in both lists with k = 60
+    ("hybrid", 0.0, 2.0 / 61.0),
+];
+
+/// Scores sampled before the mapping is fitted to them
+const MIN_SAMPLES: usize = 200;
+
+/// Queries between refits of a mode's mapping
+const REFIT_EVERY: u32 = 50;
+
+/// Quantiles mapped to 0 and 1
+const LOW_QUANTILE: f64 = 0.05;
+const HIGH_QUANTILE: f64 = 0.99;
+
+/// Calibration key of a search mode; unknown modes run as hybrid
+pub fn calibration_mode(mode: &str) -> &'static str {
+    match mode {
+        "vector" => "vector",
+        "bm25" => "bm25",
+        _ => "hybrid",
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ModeState {
+    /// Reservoir sample of raw scores
+    samples: Vec<f64>,
+    /// Scores offered to the reservoir
+    seen: u64,
+    /// Queries since the last fit
+    pending: u32,
+    fitted: bool,
+    low: f64,
+    high: f64,
+}
+
+impl ModeState {
+    fn new(mode: &str) -> Self {
+        let (low, high) = PRIORS
+            .iter()
+            .find(|(name, _, _)| *name == mode)
+            .map_or((0.0, 1.0), |(_, low, high)| (*low, *high));
+        Self {
+            samples: Vec::new(),
+            seen: 0,
+            pending: 0,
+            fitted: false,
+            low,
+            high,
+        }
+    }
+    
+    fn refit(&mut self) {
+        let mut sorted = self.samples.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let quantile = |q: f64| sorted[((sorted.len() - 1) as f64 * q).round() as usize];
+        
+        let (low, high) = (quantile(LOW_QUANTILE), quantile(HIGH_QUANTILE));
+        // A degenerate sample (every score equal) says nothing about the range
+        if high > low {
+            self.low = low;
+            self.high = high;
+        }
+        self.pending = 0;
+        self.fitted = true;
+    }
+}
+
+/// Maps raw search scores to 0-1 relevance estimates, per mode
+pub struct ScoreCalibrator {
+    sample_size: usize,
+    modes: Mutex<HashMap<&'static str, ModeState>>,
+}
+
+impl ScoreCalibrator {
+    /// Create a calibrator keeping up to `sample_size` scores per mode
+    pub fn new(sample_size: usize) -> Self {
+        Self {
+            sample_size: sample_size.max(MIN_SAMPLES),
+            modes: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    /// Sample the raw scores of one query's results
+    pub fn observe(&self, mode: &str, scores: &[f64]) {
+        let mode = calibration_mode(mode);
+        let mut modes = self.modes.lock().unwrap();
+        let state = modes.entry(mode).or_insert_with(|| ModeState::new(mode));
+        
+        let mut rng = rand::thread_rng();
+        for &score in scores.iter().filter(|s| s.is_finite()) {
+            state.seen += 1;
+            if state.samples.len() < self.sample_size {
+                state.samples.push(score);
+            } else {
+                let slot = rng.gen_range(0..state.seen);
+                if let Some(sample) = state.samples.get_mut(slot as usize) {
+                    *sample = score;
+                }
+            }
+        }
+        
+        state.pending += 1;
+        if state.samples.len() >= MIN_SAMPLES && (!state.fitted || state.pending >= REFIT_EVERY) {
+            state.refit();
+        }
+    }
+    
+    /// Calibrated score of a raw `score`, between 0 and 1
+    pub fn calibrate(&self, mode: &str, score: f64) -> f64 {
+        let mode = calibration_mode(mode);
+        let (low, high) = match self.modes.lock().unwrap().get(mode) {
+            Some(state) => (state.low, state.high),
+            None => {
+                let state = ModeState::new(mode);
+                (state.low, state.high)
+            }
+        };
+        
+        ((score - low) / (high - low)).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for ScoreCalibrator {
+    fn default() -> Self {
+        Self::new(5000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_priors_before_samples() {
+        let calibrator = ScoreCalibrator::default();
+        
+        assert_eq!(calibrator.calibrate("vector", 0.9), 1.0);
+        assert!((calibrator.calibrate("vector", 0.55) - 0.5).abs() < 1e-9);
+        assert_eq!(calibrator.calibrate("bm25", -1.0), 0.0);
+        // RRF scores would all look irrelevant on a 0-1 scale
+        assert!(calibrator.calibrate("rrf", 1.0 / 61.0) > 0.4);
+    }
+    
+    #[test]
+    fn test_fits_sampled_scores() {
+        let calibrator = ScoreCalibrator::new(1000);
+        for query in 0..50 {
+            let scores: Vec<f64> = (0..10).map(|i| 10.0 + i as f64 + (query % 10) as f64 / 10.0).collect();
+            calibrator.observe("bm25", &scores);
+        }
+        
+        // Raw scores span 10-20 once sampled; the prior range is long gone
+        assert_eq!(calibrator.calibrate("bm25", 0.4), 0.0);
+        assert!((calibrator.calibrate("bm25", 15.0) - 0.5).abs() < 0.05);
+        assert!(calibrator.calibrate("bm25", 12.0) < calibrator.calibrate("bm25", 13.0));
+    }
+}
@@ -0,0 +1,412 @@
+//! Text chunking
+//!
+//! Splits paper text into chunks for embedding. Shared by the ingestion
+//! service and the gateway's chunking preview, so a preview shows exactly
+//! the chunks ingestion would produce. Topic-aware splitting lives in
+//! [`semantic`].
+
+mod sections;
+pub mod semantic;
+
+pub use sections::{detect_sections, Section};
+
+use crate::db::models::ChunkType;
+use crate::embeddings::Embedder;
+use crate::errors::Result;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::str::FromStr;
+use text_splitter::{ChunkConfig, TextSplitter};
+use tracing::debug;
+
+/// How text is split into chunks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStrategy {
+    /// Split on paragraph, sentence and word boundaries up to the target size
+    #[default]
+    Recursive,
+    /// Cut where sentence embeddings show a topic shift
+    Semantic,
+}
+
+impl FromStr for ChunkStrategy {
+    type Err = String;
+    
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "recursive" => Ok(ChunkStrategy::Recursive),
+            "semantic" => Ok(ChunkStrategy::Semantic),
+            other => Err(format!("Unknown chunk strategy: {}", other)),
+        }
+    }
+}
+
+/// Configuration for text chunking
+#[derive(Debug, Clone)]
+pub struct ChunkingConfig {
+    pub strategy: ChunkStrategy,
+    /// Target chunk size in characters (the upper bound for semantic chunks)
+    pub chunk_size: usize,
+    /// Overlap between chunks in characters
+    pub chunk_overlap: usize,
+    /// Minimum chunk size (smaller chunks are merged)
+    pub min_chunk_size: usize,
+    /// Semantic chunking cuts at sentence distances above this percentile
+    pub breakpoint_percentile: f64,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: ChunkStrategy::Recursive,
+            chunk_size: 1000,
+            chunk_overlap: 200,
+            min_chunk_size: 100,
+            breakpoint_percentile: 90.0,
+        }
+    }
+}
+
+/// A text chunk with metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextChunk {
+    /// The chunk content
+    pub content: String,
+    /// Index of this chunk in the document
+    pub index: i32,
+    /// Approximate token count
+    pub token_count: i32,
+    /// Start character position in original text
+    pub start_pos: usize,
+    /// End character position in original text
+    pub end_pos: usize,
+    /// Body text, table or figure caption
+    pub chunk_type: ChunkType,
+    /// Where the chunk is in its source PDF (see
+    /// [`Provenance`](crate::db::models::Provenance))
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl TextChunk {
+    /// Byte range of a body text chunk in the text it was cut from; `None`
+    /// for tables, captions and equations, which come from the PDF layout
+    pub fn text_range(&self) -> Option<Range<usize>> {
+        (self.chunk_type == ChunkType::Text).then_some(self.start_pos..self.end_pos)
+    }
+}
+
+/// Split text into chunks for embedding
+pub fn chunk_text(text: &str, config: &ChunkingConfig) -> Vec<TextChunk> {
+    let splitter = TextSplitter::new(ChunkConfig::new(config.chunk_size));
+    
+    let chunks: Vec<&str> = splitter.chunks(text).collect();
+    
+    debug!(
+        input_len = text.len(),
+        chunk_count = chunks.len(),
+        chunk_size = config.chunk_size,
+        "Text chunked"
+    );
+    
+    let mut result = Vec::with_capacity(chunks.len());
+    let mut pos = 0;
+    
+    for (index, chunk_text) in chunks.into_iter().enumerate() {
+        // Find the actual position in the original text
+        let start_pos = text[pos..].find(chunk_text).map(|p| pos + p).unwrap_or(pos);
+        let end_pos = start_pos + chunk_text.len();
+        
+        // Skip chunks that are too small
+        if chunk_text.len() < config.min_chunk_size {
+            continue;
+        }
+        
+        // Estimate token count (rough approximation: ~4 chars per token)
+        let token_count = (chunk_text.len() / 4) as i32;
+        
+        result.push(TextChunk {
+            content: chunk_text.to_string(),
+            index: index as i32,
+            token_count,
+            start_pos,
+            end_pos,
+            chunk_type: ChunkType::Text,
+            metadata: None,
+        });
+        
+        pos = end_pos;
+    }
+    
+    // Re-index after filtering
+    for (i, chunk) in result.iter_mut().enumerate() {
+        chunk.index = i as i32;
+    }
+    
+    result
+}
+
+/// Split text with the strategy `config` selects
+///
+/// `boundary_embedder` is only used by [`ChunkStrategy::Semantic`].
+pub async fn chunk(
+    text: &str,
+    config: &ChunkingConfig,
+    boundary_embedder: &dyn Embedder,
+) -> Result<Vec<TextChunk>> {
+    match config.strategy {
+        ChunkStrategy::Recursive => Ok(chunk_text(text, config)),
+        ChunkStrategy::Semantic => semantic::chunk_semantic(text, config, boundary_embedder).await,
+    }
+}
+
+/// Split text as [`chunk`] does, leaving out the byte range `skip`
+///
+/// Text on either side of `skip` is chunked on its own, so no chunk spans
+/// it; positions stay relative to `text`.
+pub async fn chunk_around(
+    text: &str,
+    skip: Range<usize>,
+    config: &ChunkingConfig,
+    boundary_embedder: &dyn Embedder,
+) -> Result<Vec<TextChunk>> {
+    let mut chunks = chunk(&text[..skip.start], config, boundary_embedder).await?;
+    let mut tail = chunk(&text[skip.end..], config, boundary_embedder).await?;
+    for chunk in &mut tail {
+        chunk.start_pos += skip.end;
+        chunk.end_pos += skip.end;
+    }
+    chunks.append(&mut tail);
+    
+    for (i, chunk) in chunks.iter_mut().enumerate() {
+        chunk.index = i as i32;
+    }
+    Ok(chunks)
+}
+
+/// Rebuild the text body text chunks were cut from, given in index order
+///
+/// When every chunk has its byte range, chunks are laid back at their
+/// offsets: text shared by overlapping chunks is kept once, and gaps (text
+/// left out of chunks) become whitespace of the same length, so offsets into
+/// the result match the original. Chunks stored without ranges are joined
+/// with spaces.
+pub fn reassemble(chunks: &[(&str, Option<Range<usize>>)]) -> String {
+    let ranged: Option<Vec<(&str, Range<usize>)>> = chunks
+        .iter()
+        .map(|(content, range)| {
+            let range = range.clone().filter(|r| r.len() == content.len())?;
+            Some((*content, range))
+        })
+        .collect();
+    let Some(ranged) = ranged else {
+        return chunks.iter().map(|(content, _)| *content).collect::<Vec<_>>().join(" ");
+    };
+    
+    let mut text = String::new();
+    for (content, range) in ranged {
+        if range.start >= text.len() {
+            let gap = range.start - text.len();
+            text.push_str(&if gap == 1 { " ".to_string() } else { "\n".repeat(gap) });
+            text.push_str(content);
+        } else if range.end > text.len() {
+            // Only the part past what earlier chunks covered is new
+            match content.get(text.len() - range.start..) {
+                Some(rest) => text.push_str(rest),
+                None => {
+                    text.push(' ');
+                    text.push_str(content);
+                }
+            }
+        }
+    }
+    text
+}
+
+/// Chunk text with overlap (sliding window)
+pub fn chunk_text_with_overlap(text: &str, config: &ChunkingConfig) -> Vec<TextChunk> {
+    let mut chunks = Vec::new();
+    let chars: Vec<char> = text.chars().collect();
+    let total_len = chars.len();
+    
+    if total_len == 0 {
+        return chunks;
+    }
+    
+    let mut start = 0;
+    let mut index = 0;
+    
+    while start < total_len {
+        let end = (start + config.chunk_size).min(total_len);
+        let chunk_chars: String = chars[start..end].iter().collect();
+        
+        // Try to break at sentence boundary
+        let chunk_text = if end < total_len {
+            find_sentence_boundary(&chunk_chars)
+        } else {
+            chunk_chars
+        };
+        
+        if chunk_text.len() >= config.min_chunk_size {
+            let token_count = (chunk_text.len() / 4) as i32;
+            
+            chunks.push(TextChunk {
+                content: chunk_text.clone(),
+                index,
+                token_count,
+                start_pos: start,
+                end_pos: start + chunk_text.len(),
+                chunk_type: ChunkType::Text,
+                metadata: None,
+            });
+            
+            index += 1;
+        }
+        
+        // Move forward with overlap
+        let advance = if config.chunk_overlap < config.chunk_size {
+            config.chunk_size - config.chunk_overlap
+        } else {
+            config.chunk_size / 2
+        };
+        
+        start += advance.max(1);
+    }
+    
+    chunks
+}
+
+/// Find a good sentence boundary to break at
+fn find_sentence_boundary(text: &str) -> String {
+    // Look for sentence-ending punctuation near the end
+    let sentence_endings = [". ", "! ", "? ", ".\n", "!\n", "?\n"];
+    
+    // Search in the last 20% of the text for a good break point
+    let search_start = (text.len() as f64 * 0.8) as usize;
+    let search_region = &text[search_start..];
+    
+    for ending in sentence_endings.iter() {
+        if let Some(pos) = search_region.rfind(ending) {
+            let break_pos = search_start + pos + ending.len();
+            return text[..break_pos].to_string();
+        }
+    }
+    
+    // No good break found, return as-is
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_basic_chunking() {
+        let text = "This is a test. ".repeat(100);
+        let config = ChunkingConfig {
+            chunk_size: 200,
+            chunk_overlap: 50,
+            min_chunk_size: 50,
+            ..Default::default()
+        };
+        
+        let chunks = chunk_text(&text, &config);
+        assert!(!chunks.is_empty());
+        
+        for chunk in &chunks {
+            assert!(chunk.content.len() >= config.min_chunk_size);
+        }
+    }
+    
+    #[test]
+    fn test_overlap_chunking() {
+        let text = "Sentence one. Sentence two. Sentence three. Sentence four. Sentence five.";
+        let config = ChunkingConfig {
+            chunk_size: 30,
+            chunk_overlap: 10,
+            min_chunk_size: 10,
+            ..Default::default()
+        };
+        
+        let chunks = chunk_text_with_overlap(&text, &config);
+        assert!(chunks.len() >= 2);
+    }
+    
+    fn ranges(chunks: &[TextChunk]) -> Vec<(&str, Option<Range<usize>>)> {
+        chunks.iter().map(|c| (c.content.as_str(), c.text_range())).collect()
+    }
+    
+    #[test]
+    fn test_reassemble_keeps_overlap_once() {
+        let text = "Graph networks pass messages. Attention weighs them.";
+        let chunks = [
+            ("Graph networks pass messages.", Some(0..29)),
+            ("messages. Attention", Some(20..39)),
+            ("Attention weighs them.", Some(30..52)),
+        ];
+        assert_eq!(reassemble(&chunks), text);
+        
+        // A gap keeps its length so later offsets still match
+        let chunks = [("Graph", Some(0..5)), ("pass", Some(15..19))];
+        assert_eq!(reassemble(&chunks), format!("Graph{}pass", "\n".repeat(10)));
+        
+        // Without ranges the chunks are joined as they are
+        let chunks = [("Graph networks", None), ("pass messages.", Some(15..29))];
+        assert_eq!(reassemble(&chunks), "Graph networks pass messages.");
+    }
+    
+    #[test]
+    fn test_rechunking_reassembled_text_is_stable() {
+        let text = [
+            "Message passing networks aggregate features from neighbours.",
+            "Attention layers weigh each neighbour by a learned score.",
+        ]
+        .repeat(20)
+        .join(" ");
+        let config = ChunkingConfig {
+            chunk_size: 300,
+            min_chunk_size: 50,
+            ..Default::default()
+        };
+        
+        // Reprocessing rebuilds the text from the stored chunks each time
+        let original = chunk_text(&text, &config);
+        let first_text = reassemble(&ranges(&original));
+        let first = chunk_text(&first_text, &config);
+        let second_text = reassemble(&ranges(&first));
+        let second = chunk_text(&second_text, &config);
+        
+        assert_eq!(first_text, second_text);
+        let contents = |chunks: &[TextChunk]| chunks.iter().map(|c| c.content.clone()).collect::<Vec<_>>();
+        assert_eq!(contents(&first), contents(&original));
+        assert_eq!(contents(&second), contents(&first));
+    }
+    
+    #[tokio::test]
+    async fn test_chunk_around_skips_range() {
+        let head = "Body text before the list. ".repeat(4);
+        let skipped = "[1] A. Author. A paper.\n";
+        let tail = "Appendix text after the list. ".repeat(4);
+        let text = format!("{}{}{}", head, skipped, tail);
+        let skip = head.len()..head.len() + skipped.len();
+        let config = ChunkingConfig {
+            chunk_size: 200,
+            min_chunk_size: 10,
+            ..Default::default()
+        };
+        
+        let embedder = crate::embeddings::HashEmbedder::new(16);
+        let chunks = chunk_around(&text, skip.clone(), &config, &embedder).await.unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|c| !c.content.contains("[1]")));
+        assert_eq!(chunks[1].index, 1);
+        assert_eq!(&text[chunks[1].start_pos..chunks[1].end_pos], chunks[1].content);
+        assert!(chunks[1].start_pos >= skip.end);
+    }
+    
+    #[test]
+    fn test_empty_text() {
+        let chunks = chunk_text("", &ChunkingConfig::default());
+        assert!(chunks.is_empty());
+    }
+}
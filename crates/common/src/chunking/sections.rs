@@ -0,0 +1,131 @@
+//! Section heading detection
+//!
+//! Finds the section structure of extracted paper text from its headings:
+//! numbered headings ("3.2 Training Setup", "IV. RESULTS") and the standard
+//! unnumbered ones ("Abstract", "References"). Detection is line based and
+//! deliberately conservative; a body line is only taken as a heading when
+//! it is short and doesn't read like a sentence.
+
+use regex_lite::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Longest line considered as a heading, in characters
+const MAX_HEADING_CHARS: usize = 80;
+
+/// Most words a heading may have
+const MAX_HEADING_WORDS: usize = 10;
+
+/// A section of the text, from its heading to the next heading
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    /// Heading text, without numbering
+    pub title: String,
+    /// Nesting depth from the numbering; 1 for unnumbered headings
+    pub level: usize,
+    /// Byte offset of the heading
+    pub start_pos: usize,
+    /// Byte offset where the next section starts
+    pub end_pos: usize,
+}
+
+impl Section {
+    /// The section containing byte offset `pos`
+    pub fn at(sections: &[Section], pos: usize) -> Option<&Section> {
+        sections.iter().find(|s| (s.start_pos..s.end_pos).contains(&pos))
+    }
+}
+
+fn numbered_heading() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"^(\d+(?:\.\d+)*\.?|[IVX]+\.)\s+([A-Z][^.!?]*)$").unwrap()
+    })
+}
+
+fn named_heading() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?i)^(abstract|introduction|related work|background|preliminaries|methods?|methodology|approach|experiments?|experimental setup|evaluation|results|discussion|conclusions?|limitations|future work|references|bibliography|acknowledge?ments|appendix(?: [a-z])?):?$",
+        )
+        .unwrap()
+    })
+}
+
+/// Parse a line as a heading, returning its title and level
+fn parse_heading(line: &str) -> Option<(String, usize)> {
+    if line.len() > MAX_HEADING_CHARS || line.split_whitespace().count() > MAX_HEADING_WORDS {
+        return None;
+    }
+    
+    if let Some(caps) = numbered_heading().captures(line) {
+        let level = caps[1].trim_end_matches('.').split('.').count();
+        return Some((caps[2].trim().to_string(), level));
+    }
+    
+    named_heading()
+        .is_match(line)
+        .then(|| (line.trim_end_matches(':').to_string(), 1))
+}
+
+/// Detect the sections of `text`
+///
+/// Text before the first heading belongs to no section.
+pub fn detect_sections(text: &str) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut offset = 0;
+    
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if let Some((title, level)) = parse_heading(trimmed) {
+            let start_pos = offset + (line.len() - line.trim_start().len());
+            if let Some(previous) = sections.last_mut() {
+                previous.end_pos = start_pos;
+            }
+            sections.push(Section {
+                title,
+                level,
+                start_pos,
+                end_pos: text.len(),
+            });
+        }
+        offset += line.len();
+    }
+    
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_detect_sections() {
+        let text = "A Study of Things\n\nAbstract\nWe study things.\n\n1 Introduction\n\
+                    Things matter. 2 reasons are given below.\n\
+                    2.1 Training Setup\nWe train for 3 epochs.\n\
+                    IV. RESULTS\nIt works.\nReferences\n[1] Someone. A paper.";
+        let sections = detect_sections(text);
+        let titles: Vec<(&str, usize)> =
+            sections.iter().map(|s| (s.title.as_str(), s.level)).collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("Abstract", 1),
+                ("Introduction", 1),
+                ("Training Setup", 2),
+                ("RESULTS", 1),
+                ("References", 1),
+            ]
+        );
+        
+        assert_eq!(&text[sections[0].start_pos..sections[0].start_pos + 8], "Abstract");
+        assert_eq!(sections[0].end_pos, sections[1].start_pos);
+        assert_eq!(sections[4].end_pos, text.len());
+        
+        let pos = text.find("We train").unwrap();
+        assert_eq!(Section::at(&sections, pos).unwrap().title, "Training Setup");
+        assert!(Section::at(&sections, 0).is_none());
+    }
+}
@@ -0,0 +1,303 @@
+//! Semantic chunking
+//!
+//! Cuts chunks where the topic shifts rather than at fixed sizes, so a
+//! definition stays in the same chunk as its explanation. Each sentence is
+//! embedded together with its neighbours, and the cosine distance between
+//! consecutive sentences is measured; distances in the top percentile mark
+//! topic boundaries.
+//!
+//! Chunk size stays within the configured bounds: a boundary is only taken
+//! once the chunk reaches `min_chunk_size`, and a chunk is always cut before
+//! it would grow past `chunk_size`. Sentences longer than `chunk_size` are
+//! first split with the recursive splitter. Overlap is not applied.
+
+use super::{ChunkingConfig, TextChunk};
+use crate::db::models::ChunkType;
+use crate::embeddings::Embedder;
+use crate::errors::Result;
+use std::ops::Range;
+use text_splitter::{ChunkConfig, TextSplitter};
+use tracing::debug;
+
+/// Sentence windows embedded per request, so long documents stay within
+/// the provider's batch limits
+const EMBED_BATCH_SIZE: usize = 64;
+
+/// Split text into chunks at topical shift points
+pub async fn chunk_semantic(
+    text: &str,
+    config: &ChunkingConfig,
+    embedder: &dyn Embedder,
+) -> Result<Vec<TextChunk>> {
+    let sentences = bound_sentences(text, split_sentences(text), config.chunk_size);
+    if sentences.is_empty() {
+        return Ok(Vec::new());
+    }
+    
+    // Embed each sentence with one neighbour either side to smooth out
+    // short sentences
+    let windows: Vec<String> = (0..sentences.len())
+        .map(|i| {
+            let first = &sentences[i.saturating_sub(1)];
+            let last = &sentences[(i + 1).min(sentences.len() - 1)];
+            text[first.start..last.end].to_string()
+        })
+        .collect();
+    let mut embeddings = Vec::with_capacity(windows.len());
+    for batch in windows.chunks(EMBED_BATCH_SIZE) {
+        embeddings.extend(embedder.embed_batch(batch).await?);
+    }
+    
+    let distances: Vec<f32> = embeddings
+        .windows(2)
+        .map(|pair| cosine_distance(&pair[0], &pair[1]))
+        .collect();
+    let threshold = percentile(&distances, config.breakpoint_percentile);
+    let breaks: Vec<bool> = distances.iter().map(|d| *d >= threshold && *d > 0.0).collect();
+    
+    let chunks = assemble(text, &sentences, &breaks, config);
+    
+    debug!(
+        input_len = text.len(),
+        sentence_count = sentences.len(),
+        chunk_count = chunks.len(),
+        threshold = threshold,
+        "Text chunked semantically"
+    );
+    
+    Ok(chunks)
+}
+
+/// Byte ranges of the sentences in `text`, trimmed of surrounding whitespace
+///
+/// A sentence ends at `.`, `!` or `?` followed by whitespace, or at a blank
+/// line.
+fn split_sentences(text: &str) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, n)| *n);
+        let end = match (c, next) {
+            ('.' | '!' | '?', Some(n)) if n.is_whitespace() => Some(i + c.len_utf8()),
+            ('\n', Some('\n')) => Some(i),
+            _ => None,
+        };
+        if let Some(end) = end {
+            push_trimmed(text, start..end, &mut sentences);
+            start = end;
+        }
+    }
+    push_trimmed(text, start..text.len(), &mut sentences);
+    
+    sentences
+}
+
+/// Split sentences longer than `max_len` with the recursive splitter, so no
+/// chunk or embedded window grows past the bound because of one sentence
+fn bound_sentences(text: &str, sentences: Vec<Range<usize>>, max_len: usize) -> Vec<Range<usize>> {
+    let splitter = TextSplitter::new(ChunkConfig::new(max_len));
+    let mut bounded = Vec::with_capacity(sentences.len());
+    for sentence in sentences {
+        if sentence.len() <= max_len {
+            bounded.push(sentence);
+            continue;
+        }
+        for (offset, piece) in splitter.chunk_indices(&text[sentence.clone()]) {
+            let start = sentence.start + offset;
+            bounded.push(start..start + piece.len());
+        }
+    }
+    bounded
+}
+
+fn push_trimmed(text: &str, range: Range<usize>, sentences: &mut Vec<Range<usize>>) {
+    let slice = &text[range.clone()];
+    let leading = slice.len() - slice.trim_start().len();
+    let trimmed = slice.trim();
+    if !trimmed.is_empty() {
+        let start = range.start + leading;
+        sentences.push(start..start + trimmed.len());
+    }
+}
+
+/// Cosine distance; zero vectors (no tokens) count as identical
+fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    1.0 - dot / (norm_a * norm_b)
+}
+
+/// Value at percentile `p` (0-100) of `values`, nearest rank
+fn percentile(values: &[f32], p: f64) -> f32 {
+    if values.is_empty() {
+        return f32::MAX;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let rank = (p.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank]
+}
+
+/// Group sentences into chunks, cutting after sentence `i` when `breaks[i]`
+/// is set and the size bounds allow
+fn assemble(
+    text: &str,
+    sentences: &[Range<usize>],
+    breaks: &[bool],
+    config: &ChunkingConfig,
+) -> Vec<TextChunk> {
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+    
+    for (i, sentence) in sentences.iter().enumerate() {
+        let span = match current.take() {
+            Some(span) if sentence.end - span.start > config.chunk_size => {
+                spans.push(span);
+                sentence.clone()
+            }
+            Some(span) => span.start..sentence.end,
+            None => sentence.clone(),
+        };
+        
+        if breaks.get(i).copied().unwrap_or(false) && span.len() >= config.min_chunk_size {
+            spans.push(span);
+        } else {
+            current = Some(span);
+        }
+    }
+    if let Some(span) = current {
+        // A short tail joins the previous chunk rather than standing alone
+        match spans.last_mut() {
+            Some(last) if span.len() < config.min_chunk_size => last.end = span.end,
+            _ => spans.push(span),
+        }
+    }
+    
+    spans
+        .into_iter()
+        .enumerate()
+        .map(|(index, span)| TextChunk {
+            content: text[span.clone()].to_string(),
+            index: index as i32,
+            token_count: (span.len() / 4) as i32,
+            start_pos: span.start,
+            end_pos: span.end,
+            chunk_type: ChunkType::Text,
+            metadata: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::ChunkStrategy;
+    use crate::embeddings::HashEmbedder;
+    
+    #[test]
+    fn test_split_sentences() {
+        let text = "First sentence. Second one?  Third!\n\nHeading\nBody text";
+        let sentences: Vec<&str> = split_sentences(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(
+            sentences,
+            vec!["First sentence.", "Second one?", "Third!", "Heading\nBody text"]
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_cuts_at_topic_shift() {
+        let cats = "Cats are small domestic felines. Cats purr when content. \
+                    Domestic cats hunt mice and birds. ";
+        let rust = "The Rust compiler checks ownership. Rust borrow checking prevents \
+                    data races. The compiler rejects dangling references.";
+        let text = format!("{}{}", cats, rust);
+        let config = ChunkingConfig {
+            strategy: ChunkStrategy::Semantic,
+            chunk_size: 1000,
+            min_chunk_size: 50,
+            breakpoint_percentile: 80.0,
+            ..Default::default()
+        };
+        
+        let chunks = chunk_semantic(&text, &config, &HashEmbedder::new(256)).await.unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].content, cats.trim());
+        assert!(chunks[1].content.starts_with("The Rust compiler"));
+        assert_eq!(chunks[1].index, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_respects_max_size() {
+        let text = "Short sentence about cats. ".repeat(40);
+        let config = ChunkingConfig {
+            strategy: ChunkStrategy::Semantic,
+            chunk_size: 200,
+            min_chunk_size: 20,
+            ..Default::default()
+        };
+        
+        let chunks = chunk_semantic(&text, &config, &HashEmbedder::new(256)).await.unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.content.len() <= 200));
+    }
+    
+    #[tokio::test]
+    async fn test_splits_sentences_over_max_size() {
+        let run_on = "a sentence that never ends ".repeat(40);
+        let text = format!("Short opening sentence. {}. Short closing sentence.", run_on.trim());
+        let config = ChunkingConfig {
+            strategy: ChunkStrategy::Semantic,
+            chunk_size: 200,
+            min_chunk_size: 20,
+            ..Default::default()
+        };
+        
+        let chunks = chunk_semantic(&text, &config, &HashEmbedder::new(256)).await.unwrap();
+        assert!(chunks.len() >= run_on.len() / 200);
+        assert!(chunks.iter().all(|c| c.content.len() <= 200));
+        assert!(chunks.iter().all(|c| text[c.start_pos..c.end_pos] == c.content));
+    }
+    
+    /// Records the largest batch it was asked to embed
+    struct BatchRecorder {
+        inner: HashEmbedder,
+        largest: std::sync::atomic::AtomicUsize,
+    }
+    
+    #[async_trait::async_trait]
+    impl Embedder for BatchRecorder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.inner.embed(text).await
+        }
+        
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.largest.fetch_max(texts.len(), std::sync::atomic::Ordering::SeqCst);
+            self.inner.embed_batch(texts).await
+        }
+        
+        fn model_name(&self) -> &str {
+            self.inner.model_name()
+        }
+        
+        fn dimension(&self) -> usize {
+            self.inner.dimension()
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_embeds_in_bounded_batches() {
+        let text = (0..200).map(|i| format!("Sentence number {} is here.", i)).collect::<Vec<_>>().join(" ");
+        let embedder = BatchRecorder { inner: HashEmbedder::new(64), largest: Default::default() };
+        let config = ChunkingConfig { strategy: ChunkStrategy::Semantic, ..Default::default() };
+        
+        let chunks = chunk_semantic(&text, &config, &embedder).await.unwrap();
+        assert!(!chunks.is_empty());
+        assert_eq!(embedder.largest.into_inner(), EMBED_BATCH_SIZE);
+    }
+}
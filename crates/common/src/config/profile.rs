@@ -0,0 +1,178 @@
+//! Per-service configuration profiles
+//!
+//! Each service deserializes only the sections it reads, so a malformed
+//! setting for one service (say, an ingestion queue batch size) can't stop
+//! another from booting. Sections a service doesn't load keep their
+//! defaults. Services also get their own config files under
+//! `config/<service>/`, layered over the shared ones.
+
+use config::{builder::DefaultState, Config, ConfigBuilder, ConfigError, Source};
+
+/// Sections every profile loads
+const COMMON_SECTIONS: &[&str] = &["observability", "secrets"];
+
+/// Sections that must be present for a service to start
+const REQUIRED_SECTIONS: &[&str] = &["database"];
+
+/// A PaperForge service with its own configuration profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    Gateway,
+    Search,
+    Ingestion,
+    EmbeddingWorker,
+    /// The `paperforge-admin` operator CLI
+    Admin,
+}
+
+impl Service {
+    /// Name used for profile directories and the default tracing service name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Service::Gateway => "gateway",
+            Service::Search => "search",
+            Service::Ingestion => "ingestion",
+            Service::EmbeddingWorker => "embedding-worker",
+            Service::Admin => "admin",
+        }
+    }
+    
+    /// Top-level sections the service reads, besides the common ones
+    pub fn sections(&self) -> &'static [&'static str] {
+        match self {
+            Service::Gateway => &[
+                "server", "database", "redis", "embedding", "queue", "auth",
+                "rate_limit", "ingestion", "search", "gateway", "storage", "context",
+                "fetcher", "sessions",
+            ],
+            Service::Search => &["database", "redis", "auth", "search", "storage", "bm25_index", "pagerank"],
+            Service::Ingestion => &[
+                "database", "redis", "queue", "embedding", "retention", "ingestion", "crossref",
+                "fetcher", "storage", "bm25_index", "alerts", "job_retry",
+            ],
+            Service::EmbeddingWorker => &["database", "redis", "queue", "embedding", "embedding_worker"],
+            Service::Admin => &["database", "queue"],
+        }
+    }
+    
+    /// Whether the service loads the top-level section `key`
+    pub fn loads(&self, key: &str) -> bool {
+        COMMON_SECTIONS.contains(&key) || self.sections().contains(&key)
+    }
+    
+    /// Defaults that differ from the shared ones
+    pub(crate) fn defaults(
+        &self,
+        builder: ConfigBuilder<DefaultState>,
+    ) -> Result<ConfigBuilder<DefaultState>, ConfigError> {
+        builder.set_default("observability.service_name", format!("paperforge-{}", self.name()))
+    }
+}
+
+/// Drop the sections `service` doesn't read
+///
+/// With no service every section is kept. Either way the required sections
+/// must be present.
+pub(crate) fn retain_sections(config: Config, service: Option<Service>) -> Result<Config, ConfigError> {
+    let mut sections = config.collect()?;
+    for key in REQUIRED_SECTIONS {
+        if !sections.contains_key(*key) {
+            return Err(ConfigError::NotFound(key.to_string()));
+        }
+    }
+    
+    let Some(service) = service else {
+        return Ok(config);
+    };
+    sections.retain(|key, _| service.loads(key));
+    
+    let mut builder = Config::builder();
+    for (key, value) in sections {
+        builder = builder.set_override(key, value)?;
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AppConfig, CacheCompression};
+    use config::{File, FileFormat};
+    
+    const MALFORMED_QUEUE: &str = r#"
+        [database]
+        url = "postgres://db/paperforge"
+        
+        [search]
+        grpc_port = 50100
+        
+        [queue]
+        batch_size = "lots"
+    "#;
+    
+    fn parse(source: &str) -> Config {
+        Config::builder()
+            .add_source(File::from_str(source, FileFormat::Toml))
+            .build()
+            .unwrap()
+    }
+    
+    #[test]
+    fn test_unrelated_section_is_ignored() {
+        assert!(parse(MALFORMED_QUEUE).try_deserialize::<AppConfig>().is_err());
+        
+        let config: AppConfig = retain_sections(parse(MALFORMED_QUEUE), Some(Service::Search))
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(config.search.grpc_port, 50100);
+        assert_eq!(config.database.url, "postgres://db/paperforge");
+        
+        assert!(retain_sections(parse(MALFORMED_QUEUE), Some(Service::Ingestion))
+            .unwrap()
+            .try_deserialize::<AppConfig>()
+            .is_err());
+    }
+    
+    #[test]
+    fn test_gateway_loads_sessions() {
+        let config: AppConfig = retain_sections(
+            parse("[database]\nurl = \"postgres://db/paperforge\"\n[sessions]\nttl_minutes = 60"),
+            Some(Service::Gateway),
+        )
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+        assert_eq!(config.sessions.ttl_minutes, 60);
+    }
+    
+    #[test]
+    fn test_search_loads_redis() {
+        let config: AppConfig = retain_sections(
+            parse("[database]\nurl = \"postgres://db/paperforge\"\n[redis]\ncompression = \"zstd\""),
+            Some(Service::Search),
+        )
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+        assert_eq!(config.redis.compression, CacheCompression::Zstd);
+    }
+    
+    #[test]
+    fn test_ingestion_loads_redis() {
+        let config: AppConfig = retain_sections(
+            parse("[database]\nurl = \"postgres://db/paperforge\"\n[redis]\nurl = \"redis://cache:6379\""),
+            Some(Service::Ingestion),
+        )
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+        assert_eq!(config.redis.url, "redis://cache:6379");
+    }
+    
+    #[test]
+    fn test_database_is_required() {
+        let config = parse("[search]\ngrpc_port = 1");
+        assert!(retain_sections(config, Some(Service::Search)).is_err());
+    }
+}
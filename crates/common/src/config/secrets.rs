@@ -0,0 +1,351 @@
+//! Secret references in configuration
+//!
+//! Any string config value of the form `secret://<backend>/<path>[#field]`
+//! is replaced at load time with the secret it names:
+//!
+//! - `secret://aws-sm/paperforge/openai-key` - AWS Secrets Manager secret
+//!   `paperforge/openai-key` (credentials and region from the default AWS chain)
+//! - `secret://vault/kv/data/paperforge#jwt_secret` - field `jwt_secret` of
+//!   the Vault secret at `kv/data/paperforge` (`VAULT_ADDR` / `VAULT_TOKEN`)
+//!
+//! `#field` selects a key from a JSON secret. Fetched values are cached for
+//! `secrets.cache_ttl_secs` so periodic refreshes don't hammer the backends.
+
+use crate::errors::{AppError, Result};
+use aws_config::{BehaviorVersion, SdkConfig};
+use aws_credential_types::provider::ProvideCredentials;
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_sigv4::sign::v4;
+use config::{Value, ValueKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::OnceCell;
+
+/// Prefix marking a config value as a secret reference
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// Secret backend settings (read before secrets are resolved)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SecretsConfig {
+    /// Vault address; defaults to `VAULT_ADDR`
+    pub vault_addr: Option<String>,
+    
+    /// Vault token; defaults to `VAULT_TOKEN`
+    pub vault_token: Option<String>,
+    
+    /// How long fetched secrets are reused
+    #[serde(default = "default_cache_ttl")]
+    pub cache_ttl_secs: u64,
+    
+    /// Re-resolve secrets this often (seconds); `None` resolves only at startup.
+    /// Services shut down gracefully when a secret changed, to be restarted
+    /// with it. Keep `cache_ttl_secs` below this or refreshes see cached values.
+    pub refresh_secs: Option<u64>,
+}
+
+fn default_cache_ttl() -> u64 { 300 }
+
+/// A parsed `secret://` reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretRef {
+    AwsSecretsManager { secret_id: String, field: Option<String> },
+    Vault { path: String, field: Option<String> },
+}
+
+impl SecretRef {
+    /// Parse a config value; `Ok(None)` if it is not a secret reference
+    pub fn parse(value: &str) -> Result<Option<Self>> {
+        let Some(rest) = value.strip_prefix(SECRET_SCHEME) else {
+            return Ok(None);
+        };
+        
+        let (location, field) = match rest.split_once('#') {
+            Some((location, field)) => (location, Some(field.to_string())),
+            None => (rest, None),
+        };
+        let invalid = || AppError::Configuration {
+            message: format!("Invalid secret reference: {}", value),
+        };
+        let (backend, path) = location.split_once('/').ok_or_else(invalid)?;
+        if path.is_empty() {
+            return Err(invalid());
+        }
+        
+        match backend {
+            "aws-sm" => Ok(Some(Self::AwsSecretsManager { secret_id: path.to_string(), field })),
+            "vault" => Ok(Some(Self::Vault { path: path.to_string(), field })),
+            _ => Err(AppError::Configuration {
+                message: format!("Unknown secret backend '{}' in {}", backend, value),
+            }),
+        }
+    }
+}
+
+/// Fetches secrets from AWS Secrets Manager and Vault
+pub struct SecretResolver {
+    config: SecretsConfig,
+    http: reqwest::Client,
+    aws: OnceCell<SdkConfig>,
+    cache: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl SecretResolver {
+    pub fn new(config: SecretsConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            aws: OnceCell::new(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    /// Resolve one `secret://` reference
+    pub async fn resolve(&self, reference: &str) -> Result<String> {
+        let ttl = Duration::from_secs(self.config.cache_ttl_secs);
+        {
+            let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some((value, fetched_at)) = cache.get(reference) {
+                if fetched_at.elapsed() < ttl {
+                    return Ok(value.clone());
+                }
+            }
+        }
+        
+        let secret = SecretRef::parse(reference)?.ok_or_else(|| AppError::Configuration {
+            message: format!("Not a secret reference: {}", reference),
+        })?;
+        
+        let (raw, field) = match secret {
+            SecretRef::AwsSecretsManager { secret_id, field } => {
+                (self.fetch_aws(&secret_id).await?, field)
+            }
+            SecretRef::Vault { path, field } => {
+                (self.fetch_vault(&path).await?, field)
+            }
+        };
+        let value = select_field(raw, field.as_deref(), reference)?;
+        
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.insert(reference.to_string(), (value.clone(), Instant::now()));
+        Ok(value)
+    }
+    
+    /// Replace every secret reference in a config tree
+    ///
+    /// Returns the dotted keys and resolved values, ready for
+    /// `ConfigBuilder::set_override`.
+    pub async fn resolve_tree(&self, root: &HashMap<String, Value>) -> Result<Vec<(String, String)>> {
+        let mut references = Vec::new();
+        for (key, value) in root {
+            collect_references(key.clone(), value, &mut references);
+        }
+        
+        let mut resolved = Vec::with_capacity(references.len());
+        for (key, reference) in references {
+            let value = self.resolve(&reference).await.map_err(|e| AppError::Configuration {
+                message: format!("Failed to resolve secret for '{}': {}", key, e),
+            })?;
+            tracing::info!(key = %key, "Resolved config secret");
+            resolved.push((key, value));
+        }
+        
+        Ok(resolved)
+    }
+    
+    async fn fetch_aws(&self, secret_id: &str) -> Result<serde_json::Value> {
+        let aws = self
+            .aws
+            .get_or_init(|| aws_config::load_defaults(BehaviorVersion::latest()))
+            .await;
+        
+        let region = aws.region().ok_or_else(|| AppError::Configuration {
+            message: "AWS region is not configured".to_string(),
+        })?;
+        let credentials = aws
+            .credentials_provider()
+            .ok_or_else(|| AppError::Configuration {
+                message: "AWS credentials are not configured".to_string(),
+            })?
+            .provide_credentials()
+            .await
+            .map_err(|e| AppError::Configuration {
+                message: format!("Failed to load AWS credentials: {}", e),
+            })?;
+        
+        let url = aws
+            .endpoint_url()
+            .map(String::from)
+            .unwrap_or_else(|| format!("https://secretsmanager.{}.amazonaws.com/", region));
+        let body = serde_json::to_vec(&serde_json::json!({ "SecretId": secret_id }))?;
+        let headers = [
+            ("content-type", "application/x-amz-json-1.1"),
+            ("x-amz-target", "secretsmanager.GetSecretValue"),
+        ];
+        
+        // Sign the request with SigV4
+        let identity = credentials.into();
+        let signing_params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(region.as_ref())
+            .name("secretsmanager")
+            .time(SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()
+            .map_err(|e| AppError::Internal {
+                message: format!("Failed to build signing params: {}", e),
+            })?
+            .into();
+        let signable = SignableRequest::new(
+            "POST",
+            url.as_str(),
+            headers.iter().copied(),
+            SignableBody::Bytes(&body),
+        )
+        .and_then(|request| sign(request, &signing_params))
+        .map_err(|e| AppError::Internal {
+            message: format!("Failed to sign Secrets Manager request: {}", e),
+        })?;
+        let (instructions, _signature) = signable.into_parts();
+        
+        let mut request = self.http.post(&url).body(body.clone());
+        for (name, value) in headers.iter().copied().chain(instructions.headers()) {
+            request = request.header(name, value);
+        }
+        
+        let response: serde_json::Value = send_json(request, "Secrets Manager").await?;
+        let secret = response
+            .get("SecretString")
+            .and_then(|s| s.as_str())
+            .ok_or_else(|| AppError::Configuration {
+                message: format!("Secret '{}' has no SecretString", secret_id),
+            })?;
+        
+        // JSON secrets allow `#field`; anything else is used as-is
+        Ok(serde_json::from_str(secret).unwrap_or_else(|_| serde_json::Value::String(secret.to_string())))
+    }
+    
+    async fn fetch_vault(&self, path: &str) -> Result<serde_json::Value> {
+        let addr = self
+            .config
+            .vault_addr
+            .clone()
+            .or_else(|| std::env::var("VAULT_ADDR").ok())
+            .ok_or_else(|| AppError::Configuration {
+                message: "Vault address is not configured (VAULT_ADDR)".to_string(),
+            })?;
+        let token = self
+            .config
+            .vault_token
+            .clone()
+            .or_else(|| std::env::var("VAULT_TOKEN").ok())
+            .ok_or_else(|| AppError::Configuration {
+                message: "Vault token is not configured (VAULT_TOKEN)".to_string(),
+            })?;
+        
+        let url = format!("{}/v1/{}", addr.trim_end_matches('/'), path.trim_start_matches('/'));
+        let request = self.http.get(&url).header("X-Vault-Token", token);
+        let response: serde_json::Value = send_json(request, "Vault").await?;
+        
+        // KV v2 nests the secret under data.data; KV v1 under data
+        let data = response.get("data").cloned().unwrap_or_default();
+        Ok(match data.get("data") {
+            Some(inner) if data.get("metadata").is_some() => inner.clone(),
+            _ => data,
+        })
+    }
+}
+
+/// Send a request and decode a JSON response
+async fn send_json(request: reqwest::RequestBuilder, backend: &str) -> Result<serde_json::Value> {
+    request
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::Configuration {
+            message: format!("{} request failed: {}", backend, e),
+        })?
+        .json()
+        .await
+        .map_err(|e| AppError::Configuration {
+            message: format!("Invalid {} response: {}", backend, e),
+        })
+}
+
+/// Pick the requested field out of a fetched secret
+///
+/// Without `#field`, string secrets are used as-is and objects must have
+/// exactly one key.
+fn select_field(secret: serde_json::Value, field: Option<&str>, reference: &str) -> Result<String> {
+    let value = match (field, secret) {
+        (Some(field), serde_json::Value::Object(mut map)) => map.remove(field),
+        (Some(_), _) => None,
+        (None, serde_json::Value::Object(map)) if map.len() == 1 => map.into_iter().next().map(|(_, v)| v),
+        (None, serde_json::Value::Object(_)) => None,
+        (None, value) => Some(value),
+    };
+    
+    match value {
+        Some(serde_json::Value::String(s)) => Ok(s),
+        Some(serde_json::Value::Null) | None => Err(AppError::Configuration {
+            message: format!("Secret {} has no such field (add #field for multi-key secrets)", reference),
+        }),
+        Some(other) => Ok(other.to_string()),
+    }
+}
+
+/// Collect `(dotted.key, reference)` pairs for every secret reference
+fn collect_references(key: String, value: &Value, out: &mut Vec<(String, String)>) {
+    match &value.kind {
+        ValueKind::String(s) if s.starts_with(SECRET_SCHEME) => out.push((key, s.clone())),
+        ValueKind::Table(table) => {
+            for (child, value) in table {
+                collect_references(format!("{}.{}", key, child), value, out);
+            }
+        }
+        ValueKind::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                collect_references(format!("{}[{}]", key, i), value, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_parse_secret_ref() {
+        assert_eq!(SecretRef::parse("plain-value").unwrap(), None);
+        assert_eq!(
+            SecretRef::parse("secret://aws-sm/paperforge/openai-key").unwrap(),
+            Some(SecretRef::AwsSecretsManager {
+                secret_id: "paperforge/openai-key".to_string(),
+                field: None,
+            })
+        );
+        assert_eq!(
+            SecretRef::parse("secret://vault/kv/data/paperforge#jwt_secret").unwrap(),
+            Some(SecretRef::Vault {
+                path: "kv/data/paperforge".to_string(),
+                field: Some("jwt_secret".to_string()),
+            })
+        );
+        assert!(SecretRef::parse("secret://gcp/x").is_err());
+        assert!(SecretRef::parse("secret://vault").is_err());
+    }
+    
+    #[test]
+    fn test_select_field() {
+        let secret = json!({ "username": "pf", "password": "hunter2" });
+        assert_eq!(select_field(secret.clone(), Some("password"), "r").unwrap(), "hunter2");
+        assert!(select_field(secret, None, "r").is_err());
+        assert_eq!(select_field(json!({ "value": "x" }), None, "r").unwrap(), "x");
+        assert_eq!(select_field(json!("raw"), None, "r").unwrap(), "raw");
+    }
+}
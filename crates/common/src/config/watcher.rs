@@ -0,0 +1,170 @@
+//! Hot reload of runtime-tunable settings
+//!
+//! [`ConfigWatcher`] reloads configuration when a config file changes or the
+//! process receives SIGHUP, and atomically swaps in the reload-safe settings
+//! (see [`AppConfig::with_reloadable`]). Readers take cheap snapshots from
+//! the shared [`ArcSwap`]; components that must react to a change (log
+//! filters, rate limiters, cache TTLs) subscribe to change notifications.
+
+use super::{AppConfig, Service};
+use arc_swap::ArcSwap;
+use config::ConfigError;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Extensions the config crate recognises for config files
+const CONFIG_EXTENSIONS: &[&str] = &["toml", "json", "yaml", "yml", "ini", "ron", "json5"];
+
+/// Current configuration, shared between handlers and the watcher
+pub type SharedConfig = Arc<ArcSwap<AppConfig>>;
+
+/// Watches config sources and swaps in reloaded settings
+pub struct ConfigWatcher {
+    current: SharedConfig,
+    changes: watch::Sender<Arc<AppConfig>>,
+    service: Option<Service>,
+}
+
+impl ConfigWatcher {
+    pub fn new(config: AppConfig) -> Self {
+        let config = Arc::new(config);
+        let (changes, _) = watch::channel(config.clone());
+        
+        Self {
+            current: Arc::new(ArcSwap::new(config)),
+            changes,
+            service: None,
+        }
+    }
+    
+    /// Reload with `service`'s profile (see [`AppConfig::load_for`])
+    pub fn for_service(mut self, service: Service) -> Self {
+        self.service = Some(service);
+        self
+    }
+    
+    /// Handle for reading the current configuration
+    pub fn shared(&self) -> SharedConfig {
+        self.current.clone()
+    }
+    
+    /// Receive every configuration that is swapped in
+    pub fn subscribe(&self) -> watch::Receiver<Arc<AppConfig>> {
+        self.changes.subscribe()
+    }
+    
+    /// Reload configuration and apply reload-safe changes
+    ///
+    /// Returns whether anything changed. Changes to settings that need a
+    /// restart are logged and ignored.
+    pub async fn reload(&self) -> Result<bool, ConfigError> {
+        let reloaded = AppConfig::load_profile(self.service).await?;
+        let current = self.current.load_full();
+        let next = current.with_reloadable(&reloaded);
+        
+        if !same(&next, &reloaded) {
+            warn!("Configuration has changes that only take effect after a restart");
+        }
+        if same(&next, &current) {
+            return Ok(false);
+        }
+        
+        let next = Arc::new(next);
+        self.current.store(next.clone());
+        self.changes.send_replace(next);
+        info!("Configuration reloaded");
+        
+        Ok(true)
+    }
+    
+    /// Reload on SIGHUP and whenever a config file changes
+    ///
+    /// Files are checked every `poll_interval`.
+    pub fn spawn(self: Arc<Self>, poll_interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut files = file_fingerprint(self.service);
+            let mut ticker = tokio::time::interval(poll_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            
+            #[cfg(unix)]
+            let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+                .map_err(|e| warn!(error = %e, "Failed to install SIGHUP handler"))
+                .ok();
+            
+            loop {
+                #[cfg(unix)]
+                let hangup_received = async {
+                    match hangup.as_mut() {
+                        Some(signal) => signal.recv().await,
+                        None => std::future::pending().await,
+                    }
+                };
+                #[cfg(not(unix))]
+                let hangup_received = std::future::pending::<Option<()>>();
+                
+                let trigger = tokio::select! {
+                    _ = ticker.tick() => {
+                        let latest = file_fingerprint(self.service);
+                        if latest == files {
+                            continue;
+                        }
+                        files = latest;
+                        "file change"
+                    }
+                    _ = hangup_received => "SIGHUP",
+                };
+                
+                info!(trigger, "Reloading configuration");
+                if let Err(e) = self.reload().await {
+                    warn!(error = %e, "Failed to reload configuration, keeping current settings");
+                }
+            }
+        })
+    }
+}
+
+/// Compare configurations by their serialized form
+fn same(a: &AppConfig, b: &AppConfig) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Existing config files and their modification times
+fn file_fingerprint(service: Option<Service>) -> Vec<(PathBuf, Option<SystemTime>)> {
+    AppConfig::file_names(service)
+        .into_iter()
+        .flat_map(|name| {
+            CONFIG_EXTENSIONS
+                .iter()
+                .map(move |ext| PathBuf::from(format!("{}.{}", name, ext)))
+        })
+        .filter_map(|path| {
+            let metadata = std::fs::metadata(&path).ok()?;
+            Some((path, metadata.modified().ok()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_with_reloadable() {
+        let current = AppConfig::default();
+        let mut reloaded = AppConfig::default();
+        reloaded.rate_limit.requests_per_second = 7;
+        reloaded.search.vector_weight = 0.9;
+        reloaded.server.port = 9999;
+        
+        let next = current.with_reloadable(&reloaded);
+        assert_eq!(next.rate_limit.requests_per_second, 7);
+        assert_eq!(next.search.vector_weight, 0.9);
+        assert_eq!(next.server.port, current.server.port);
+        assert!(!same(&next, &reloaded));
+        assert!(!same(&next, &current));
+    }
+}
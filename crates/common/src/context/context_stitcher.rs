@@ -6,6 +6,7 @@
 //! - Cross-reference detection
 //! - Token budget management
 
+use super::token_budget::{estimate_tokens, truncate_to_tokens, TokenBudget};
 use crate::errors::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -65,9 +66,6 @@ pub enum ReferenceType {
 /// Context stitcher configuration
 #[derive(Debug, Clone)]
 pub struct ContextStitcherConfig {
-    /// Maximum token budget
-    pub max_tokens: usize,
-    
     /// Maximum windows to create
     pub max_windows: usize,
     
@@ -81,7 +79,6 @@ pub struct ContextStitcherConfig {
 impl Default for ContextStitcherConfig {
     fn default() -> Self {
         Self {
-            max_tokens: 4000,
             max_windows: 5,
             stitch_overlap: 100,
             min_chunk_score: 0.3,
@@ -112,7 +109,14 @@ impl ContextStitcher {
     }
     
     /// Stitch chunks into context windows
-    pub fn stitch(&self, chunks: Vec<ChunkInput>) -> Result<(Vec<ContextWindow>, Vec<CrossReference>)> {
+    ///
+    /// The most relevant windows are kept within `budget`'s context share;
+    /// the window crossing the limit is trimmed and the rest are dropped.
+    pub fn stitch(
+        &self,
+        chunks: Vec<ChunkInput>,
+        budget: &TokenBudget,
+    ) -> Result<(Vec<ContextWindow>, Vec<CrossReference>)> {
         // Filter by minimum score
         let mut chunks: Vec<ChunkInput> = chunks
             .into_iter()
@@ -131,41 +135,33 @@ impl ContextStitcher {
             paper_groups.entry(chunk.paper_id).or_default().push(chunk);
         }
         
-        // Create windows for each paper
-        let mut windows = Vec::new();
-        let mut total_tokens = 0;
+        // Create windows for each paper, most relevant first
+        let mut windows: Vec<ContextWindow> = paper_groups
+            .into_iter()
+            .map(|(paper_id, paper_chunks)| self.create_window(paper_id, paper_chunks))
+            .collect();
+        windows.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        windows.truncate(self.config.max_windows);
         
-        for (paper_id, paper_chunks) in paper_groups {
-            if windows.len() >= self.config.max_windows {
-                break;
-            }
-            
-            let window = self.create_window(paper_id, paper_chunks);
-            
-            // Check token budget
-            if total_tokens + window.token_count > self.config.max_tokens {
-                // Try to fit a smaller version
-                let remaining = self.config.max_tokens.saturating_sub(total_tokens);
-                if remaining > 500 {
-                    let trimmed = self.trim_window(window, remaining);
-                    total_tokens += trimmed.token_count;
-                    windows.push(trimmed);
+        // Fit the windows to the token budget
+        let sizes: Vec<(f32, usize)> = windows.iter().map(|w| (w.relevance_score, w.token_count)).collect();
+        let granted = budget.allocate(&sizes);
+        let windows: Vec<ContextWindow> = windows
+            .into_iter()
+            .zip(granted)
+            .filter(|(_, tokens)| *tokens > 0)
+            .map(|(window, tokens)| {
+                if tokens < window.token_count {
+                    self.trim_window(window, tokens)
+                } else {
+                    window
                 }
-                break;
-            }
-            
-            total_tokens += window.token_count;
-            windows.push(window);
-        }
+            })
+            .collect();
         
         // Detect cross-references
         let cross_refs = self.detect_cross_references(&windows);
         
-        // Sort windows by relevance
-        windows.sort_by(|a, b| {
-            b.relevance_score.partial_cmp(&a.relevance_score).unwrap()
-        });
-        
         Ok((windows, cross_refs))
     }
     
@@ -191,7 +187,7 @@ impl ContextStitcher {
         
         // Stitch content with overlap handling
         let content = self.stitch_chunks(&chunks);
-        let token_count = self.estimate_tokens(&content);
+        let token_count = estimate_tokens(&content);
         
         ContextWindow {
             paper_id,
@@ -252,15 +248,8 @@ impl ContextStitcher {
     
     /// Trim window to fit token budget
     fn trim_window(&self, window: ContextWindow, max_tokens: usize) -> ContextWindow {
-        let estimated_chars = max_tokens * 4; // ~4 chars per token
-        
-        let content = if window.content.len() > estimated_chars {
-            window.content.chars().take(estimated_chars).collect()
-        } else {
-            window.content
-        };
-        
-        let token_count = self.estimate_tokens(&content);
+        let content = truncate_to_tokens(&window.content, max_tokens);
+        let token_count = estimate_tokens(&content);
         
         ContextWindow {
             content,
@@ -319,12 +308,6 @@ impl ContextStitcher {
         
         intersection as f32 / union as f32
     }
-    
-    /// Estimate token count (simple approximation)
-    fn estimate_tokens(&self, text: &str) -> usize {
-        // Rough estimate: 1 token ~= 4 characters
-        text.len() / 4
-    }
 }
 
 #[cfg(test)]
@@ -354,16 +337,33 @@ mod tests {
             },
         ];
         
-        let (windows, _refs) = stitcher.stitch(chunks).unwrap();
+        let (windows, _refs) = stitcher.stitch(chunks, &TokenBudget::new(4000)).unwrap();
         
         assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].paper_title, "Test Paper");
     }
     
     #[test]
-    fn test_token_estimation() {
+    fn test_stitching_within_budget() {
         let stitcher = ContextStitcher::new(ContextStitcherConfig::default());
         
-        let tokens = stitcher.estimate_tokens("This is a test string.");
-        assert!(tokens > 0);
+        let chunk = |title: &str, score: f32| ChunkInput {
+            chunk_id: Uuid::new_v4(),
+            paper_id: Uuid::new_v4(),
+            paper_title: title.to_string(),
+            content: "word ".repeat(400),
+            chunk_index: 0,
+            score,
+        };
+        let budget = TokenBudget::new(1000).with_output(200);
+        
+        let (windows, _refs) = stitcher
+            .stitch(vec![chunk("Low", 0.4), chunk("High", 0.9), chunk("Mid", 0.6)], &budget)
+            .unwrap();
+        
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].paper_title, "High");
+        assert_eq!(windows[0].token_count, 500);
+        assert!(windows[1].token_count <= 300);
     }
 }
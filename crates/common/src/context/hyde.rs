@@ -0,0 +1,162 @@
+//! HyDE - Hypothetical document embeddings
+//!
+//! Short queries embed poorly next to paper passages. HyDE has the LLM write
+//! a passage that would answer the query and searches with the passage's
+//! embedding instead, optionally fused with the query's own embedding.
+
+use super::llm::{Completion, LlmClient};
+use crate::embeddings::{Embedder, InputKind, Preprocessor};
+use crate::errors::Result;
+use std::sync::Arc;
+
+const HYDE_SYSTEM_PROMPT: &str = "You are a research assistant. Write a short passage in the style \
+    of a scientific paper that answers the question. Reply with the passage only.";
+
+/// HyDE configuration
+#[derive(Debug, Clone)]
+pub struct HydeConfig {
+    /// Maximum tokens of the hypothetical passage
+    pub max_tokens: usize,
+    
+    /// Generation temperature
+    pub temperature: f32,
+}
+
+impl Default for HydeConfig {
+    fn default() -> Self {
+        Self {
+            max_tokens: 200,
+            temperature: 0.3,
+        }
+    }
+}
+
+/// A query embedding produced through a hypothetical passage
+#[derive(Debug, Clone)]
+pub struct HydeEmbedding {
+    /// The generated passage
+    pub passage: String,
+    
+    /// Embedding to search with
+    pub embedding: Vec<f32>,
+}
+
+/// Generates and embeds hypothetical answer passages
+pub struct HydeExpander {
+    llm: Arc<LlmClient>,
+    embedder: Arc<dyn Embedder>,
+    config: HydeConfig,
+    preprocessor: Arc<Preprocessor>,
+}
+
+impl HydeExpander {
+    /// Create a new expander
+    pub fn new(llm: Arc<LlmClient>, embedder: Arc<dyn Embedder>, config: HydeConfig) -> Self {
+        Self {
+            llm,
+            embedder,
+            config,
+            preprocessor: Arc::new(Preprocessor::default()),
+        }
+    }
+    
+    /// Prepare passages with `preprocessor` before embedding them, as
+    /// stored chunks were
+    pub fn with_preprocessor(mut self, preprocessor: Arc<Preprocessor>) -> Self {
+        self.preprocessor = preprocessor;
+        self
+    }
+    
+    /// Write a passage that would answer `query`
+    pub async fn hypothetical_document(&self, query: &str) -> Result<String> {
+        let prompt = format!("Question: {}\n\nPassage:", query);
+        let passage = self.llm.complete(&Completion {
+            system: HYDE_SYSTEM_PROMPT,
+            prompt: &prompt,
+            max_tokens: self.config.max_tokens,
+            temperature: self.config.temperature,
+        }).await?;
+        
+        Ok(passage.trim().to_string())
+    }
+    
+    /// Embed a hypothetical passage for `query`
+    ///
+    /// With a query embedding, the result is the two embeddings fused with
+    /// `query_weight` given to the query (0.0 uses the passage alone).
+    pub async fn embed(
+        &self,
+        query: &str,
+        query_embedding: Option<&[f32]>,
+        query_weight: f32,
+    ) -> Result<HydeEmbedding> {
+        let passage = self.hypothetical_document(query).await?;
+        let passage_embedding = self
+            .embedder
+            .embed(&self.preprocessor.apply(&passage, InputKind::Passage))
+            .await?;
+        
+        let embedding = match query_embedding {
+            Some(query_embedding) if query_weight > 0.0 => {
+                fuse_embeddings(query_embedding, &passage_embedding, query_weight)
+            }
+            _ => passage_embedding,
+        };
+        
+        Ok(HydeEmbedding { passage, embedding })
+    }
+}
+
+/// Weighted sum of two L2-normalized embeddings, normalized again
+///
+/// `weight` is the share of `a`, clamped to `0.0..=1.0`.
+pub fn fuse_embeddings(a: &[f32], b: &[f32], weight: f32) -> Vec<f32> {
+    let weight = weight.clamp(0.0, 1.0);
+    let a = normalize(a);
+    let b = normalize(b);
+    let fused: Vec<f32> = a
+        .iter()
+        .zip(&b)
+        .map(|(x, y)| weight * x + (1.0 - weight) * y)
+        .collect();
+    normalize(&fused)
+}
+
+fn normalize(v: &[f32]) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return v.to_vec();
+    }
+    v.iter().map(|x| x / norm).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_fuse_embeddings() {
+        let query = [2.0, 0.0];
+        let passage = [0.0, 1.0];
+        
+        let fused = fuse_embeddings(&query, &passage, 0.5);
+        assert!((fused[0] - fused[1]).abs() < 1e-6);
+        assert!((fused.iter().map(|x| x * x).sum::<f32>() - 1.0).abs() < 1e-6);
+        
+        assert_eq!(fuse_embeddings(&query, &passage, 0.0), vec![0.0, 1.0]);
+        assert_eq!(fuse_embeddings(&query, &passage, 2.0), vec![1.0, 0.0]);
+    }
+    
+    #[tokio::test]
+    async fn test_offline_expansion() {
+        let hyde = HydeExpander::new(
+            Arc::new(LlmClient::offline("gpt-4o-mini")),
+            Arc::new(crate::embeddings::MockEmbedder::new(64)),
+            HydeConfig::default(),
+        );
+        
+        let expanded = hyde.embed("What is attention?", None, 0.0).await.unwrap();
+        assert_eq!(expanded.passage, "What is attention? [offline completion]");
+        assert_eq!(expanded.embedding, hyde.embed("What is attention?", None, 0.0).await.unwrap().embedding);
+    }
+}
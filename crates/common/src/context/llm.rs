@@ -0,0 +1,301 @@
+//! LLM client - Chat completions for the context engine
+//!
+//! A thin client for OpenAI-compatible chat completion APIs, shared by the
+//! synthesizer and the query parser's fallback.
+//!
+//! A client without an API key, or one configured offline, never calls the
+//! API: free-text completions come back as deterministic canned text and
+//! callers that need structured output use their own mock paths (see
+//! [`LlmClient::is_configured`]).
+
+use super::token_budget::{context_window_for_model, estimate_tokens, truncate_to_tokens, TokenBudget};
+use crate::errors::{AppError, Result};
+use crate::usage::{UsageKind, UsageMeter};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::Arc;
+
+/// LLM client configuration
+#[derive(Debug, Clone)]
+pub struct LLMConfig {
+    /// API endpoint
+    pub endpoint: String,
+    
+    /// API key
+    pub api_key: String,
+    
+    /// Model name
+    pub model: String,
+    
+    /// Timeout in seconds
+    pub timeout_secs: u64,
+    
+    /// Context window in tokens; looked up from the model name when unset
+    pub context_window: Option<usize>,
+    
+    /// Answer with deterministic canned output instead of calling the API
+    pub offline: bool,
+}
+
+impl Default for LLMConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://api.openai.com/v1/chat/completions".to_string(),
+            api_key: String::new(),
+            model: "gpt-4o-mini".to_string(),
+            timeout_secs: 30,
+            context_window: None,
+            offline: false,
+        }
+    }
+}
+
+/// A single chat completion request
+#[derive(Debug, Clone)]
+pub struct Completion<'a> {
+    /// System prompt
+    pub system: &'a str,
+    
+    /// User prompt
+    pub prompt: &'a str,
+    
+    /// Maximum output tokens
+    pub max_tokens: usize,
+    
+    /// Temperature (0.0 - 1.0)
+    pub temperature: f32,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    format_type: &'static str,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+    max_tokens: usize,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessageResponse,
+}
+
+#[derive(Deserialize)]
+struct ChatMessageResponse {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+/// Client for an OpenAI-compatible chat completions API
+pub struct LlmClient {
+    config: LLMConfig,
+    client: reqwest::Client,
+    meter: Option<Arc<UsageMeter>>,
+}
+
+impl LlmClient {
+    /// Create a new client
+    pub fn new(config: LLMConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| AppError::Internal {
+                message: format!("Failed to create HTTP client: {}", e),
+            })?;
+        
+        Ok(Self { config, client, meter: None })
+    }
+    
+    /// Check budgets and record usage of every completion with `meter`
+    pub fn with_meter(mut self, meter: Arc<UsageMeter>) -> Self {
+        self.meter = Some(meter);
+        self
+    }
+    
+    /// Client that never calls the API
+    pub fn offline(model: &str) -> Self {
+        Self {
+            config: LLMConfig {
+                model: model.to_string(),
+                offline: true,
+                ..Default::default()
+            },
+            client: reqwest::Client::new(),
+            meter: None,
+        }
+    }
+    
+    /// Whether completions are sent to the API
+    ///
+    /// When they aren't (offline, or no API key), callers should fall back
+    /// to their mock output.
+    pub fn is_configured(&self) -> bool {
+        !self.config.offline && !self.config.api_key.is_empty()
+    }
+    
+    /// Model requests are sent to
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+    
+    /// Context window of the configured model, in tokens
+    pub fn context_window(&self) -> usize {
+        self.config
+            .context_window
+            .unwrap_or_else(|| context_window_for_model(&self.config.model))
+    }
+    
+    /// An empty token budget for the configured model
+    pub fn token_budget(&self) -> TokenBudget {
+        TokenBudget::new(self.context_window())
+    }
+    
+    /// Generate a free-text completion
+    ///
+    /// Offline, this is deterministic canned text derived from the prompt.
+    pub async fn complete(&self, completion: &Completion<'_>) -> Result<String> {
+        if !self.is_configured() {
+            return Ok(offline_completion(completion));
+        }
+        self.chat(completion, false).await
+    }
+    
+    /// Generate a completion in JSON mode and deserialize it
+    ///
+    /// The prompt must describe the expected JSON shape; JSON mode only
+    /// guarantees that the output parses.
+    pub async fn complete_json<T: DeserializeOwned>(&self, completion: &Completion<'_>) -> Result<T> {
+        if !self.is_configured() {
+            return Err(AppError::ServiceUnavailable {
+                message: "LLM is offline".to_string(),
+            });
+        }
+        let content = self.chat(completion, true).await?;
+        parse_json(&content)
+    }
+    
+    async fn chat(&self, completion: &Completion<'_>, json: bool) -> Result<String> {
+        if let Some(meter) = &self.meter {
+            meter.check_budget().await?;
+        }
+        
+        let request = ChatRequest {
+            model: &self.config.model,
+            messages: vec![
+                ChatMessage {
+                    role: "system",
+                    content: completion.system,
+                },
+                ChatMessage {
+                    role: "user",
+                    content: completion.prompt,
+                },
+            ],
+            max_tokens: completion.max_tokens,
+            temperature: completion.temperature,
+            response_format: json.then_some(ResponseFormat {
+                format_type: "json_object",
+            }),
+        };
+        
+        let response = self.client
+            .post(&self.config.endpoint)
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal {
+                message: format!("LLM API request failed: {}", e),
+            })?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::Internal {
+                message: format!("LLM API error {}: {}", status, body),
+            });
+        }
+        
+        let chat_response: ChatResponse = response.json().await
+            .map_err(|e| AppError::Internal {
+                message: format!("Failed to parse LLM response: {}", e),
+            })?;
+        
+        let content = chat_response.choices.into_iter().next()
+            .map(|c| c.message.content);
+        
+        if let Some(meter) = &self.meter {
+            // Estimate when the API doesn't report usage
+            let (prompt_tokens, completion_tokens) = match chat_response.usage {
+                Some(usage) => (usage.prompt_tokens, usage.completion_tokens),
+                None => (
+                    estimate_tokens(completion.system) as u64 + estimate_tokens(completion.prompt) as u64,
+                    content.as_deref().map(estimate_tokens).unwrap_or(0) as u64,
+                ),
+            };
+            meter.record(UsageKind::Llm, &self.config.model, prompt_tokens, completion_tokens).await;
+        }
+        
+        content
+            .ok_or_else(|| AppError::Internal {
+                message: "Empty response from LLM".to_string(),
+            })
+    }
+}
+
+/// Canned completion for offline clients
+///
+/// Echoes the first line of the prompt (without a leading `Label:`), so the
+/// output is stable and still about the request, e.g. when it is embedded.
+fn offline_completion(completion: &Completion<'_>) -> String {
+    let line = completion
+        .prompt
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let line = match line.split_once(':') {
+        Some((label, rest)) if !label.contains(' ') && !rest.trim().is_empty() => rest.trim(),
+        _ => line,
+    };
+    
+    truncate_to_tokens(&format!("{} [offline completion]", line), completion.max_tokens)
+}
+
+/// Deserialize JSON model output, tolerating a surrounding code fence
+pub(crate) fn parse_json<T: DeserializeOwned>(content: &str) -> Result<T> {
+    let content = content.trim();
+    let content = content
+        .strip_prefix("```json")
+        .or_else(|| content.strip_prefix("```"))
+        .and_then(|c| c.strip_suffix("```"))
+        .unwrap_or(content);
+    
+    serde_json::from_str(content.trim()).map_err(|e| AppError::Internal {
+        message: format!("LLM returned invalid JSON: {}", e),
+    })
+}
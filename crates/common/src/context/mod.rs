@@ -6,13 +6,37 @@
 //! - Context stitching
 //! - Multi-hop reasoning
 //! - LLM synthesis
+//! - Literature reviews
 
+mod hyde;
+mod llm;
 mod query_parser;
 mod context_stitcher;
 mod reasoner;
+mod review;
 mod synthesizer;
+mod token_budget;
+mod verification;
 
-pub use query_parser::{QueryParser, QueryUnderstanding, Entity};
-pub use context_stitcher::{ContextStitcher, ContextWindow, CrossReference};
-pub use reasoner::{Reasoner, ReasoningChain, ReasoningHop};
-pub use synthesizer::{Synthesizer, SynthesisOptions, SynthesizedAnswer, Citation};
+pub use hyde::{fuse_embeddings, HydeConfig, HydeEmbedding, HydeExpander};
+pub use llm::{Completion, LLMConfig, LlmClient};
+pub use query_parser::{
+    ComparisonQuery, Entity, EntityType, QueryDictionaries, QueryIntent, QueryParser,
+    QueryParserConfig, QueryUnderstanding,
+};
+pub use context_stitcher::{
+    ChunkInput, ContextStitcher, ContextStitcherConfig, ContextWindow, CrossReference,
+};
+pub use reasoner::{Reasoner, ReasonerConfig, ReasonerContext, ReasoningChain, ReasoningHop};
+pub use review::{
+    bibliography, BibliographyEntry, LiteratureReview, ReviewConfig, ReviewPaper, ReviewSection,
+    ReviewWriter,
+};
+pub use synthesizer::{
+    Citation, ComparisonDimension, ComparisonSubject, StructuredComparison, SubjectFinding,
+    Synthesizer, SynthesisContext, SynthesisOptions, SynthesizedAnswer,
+};
+pub use token_budget::{
+    context_window_for_model, estimate_tokens, truncate_to_tokens, TokenAllocation, TokenBudget,
+};
+pub use verification::{UnsupportedClaim, UnsupportedClaimAction, VerificationMethod, UNSUPPORTED_MARKER};
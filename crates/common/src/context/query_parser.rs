@@ -4,10 +4,29 @@
 //! - Intent classification
 //! - Entity extraction (concepts, authors, methods)
 //! - Query expansion with synonyms
+//!
+//! Dictionaries are loaded once and shared between parsers. Parsed queries
+//! can be cached in Redis, keyed by the normalized query text. When the
+//! heuristics aren't confident, an LLM classifies the query instead.
 
-use crate::errors::Result;
+use super::llm::{Completion, LlmClient};
+use crate::cache::{keys, Cache};
+use crate::errors::{AppError, Result};
+use crate::metrics;
+use regex_lite::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use tracing::{debug, warn};
+
+/// Confidence given to entities the LLM extracts
+const LLM_ENTITY_CONFIDENCE: f32 = 0.8;
+
+const LLM_SYSTEM_PROMPT: &str = "You analyze search queries over a corpus of research papers. \
+    Reply with a single JSON object and nothing else.";
 
 /// Query understanding result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +47,16 @@ pub struct QueryUnderstanding {
     pub confidence: f32,
 }
 
+/// The entities a comparison query compares
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonQuery {
+    /// Compared entities, in query order
+    pub subjects: Vec<String>,
+    
+    /// What they are compared on, e.g. "text classification"
+    pub aspect: Option<String>,
+}
+
 /// Query intent classification
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum QueryIntent {
@@ -45,6 +74,36 @@ pub enum QueryIntent {
     General,
 }
 
+impl QueryIntent {
+    /// Label used in metrics and API responses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryIntent::Factual => "factual",
+            QueryIntent::Comparison => "comparison",
+            QueryIntent::Exploratory => "exploratory",
+            QueryIntent::Procedural => "procedural",
+            QueryIntent::Survey => "survey",
+            QueryIntent::General => "general",
+        }
+    }
+}
+
+impl FromStr for QueryIntent {
+    type Err = String;
+    
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "factual" => Ok(QueryIntent::Factual),
+            "comparison" => Ok(QueryIntent::Comparison),
+            "exploratory" => Ok(QueryIntent::Exploratory),
+            "procedural" => Ok(QueryIntent::Procedural),
+            "survey" => Ok(QueryIntent::Survey),
+            "general" => Ok(QueryIntent::General),
+            other => Err(format!("Unknown query intent: {}", other)),
+        }
+    }
+}
+
 /// Extracted entity from query
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entity {
@@ -80,6 +139,156 @@ pub enum EntityType {
     Term,
 }
 
+impl EntityType {
+    /// Label used in API responses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityType::Concept => "concept",
+            EntityType::Author => "author",
+            EntityType::Method => "method",
+            EntityType::Dataset => "dataset",
+            EntityType::Venue => "venue",
+            EntityType::Temporal => "temporal",
+            EntityType::Term => "term",
+        }
+    }
+}
+
+impl FromStr for EntityType {
+    type Err = String;
+    
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "concept" => Ok(EntityType::Concept),
+            "author" => Ok(EntityType::Author),
+            "method" => Ok(EntityType::Method),
+            "dataset" => Ok(EntityType::Dataset),
+            "venue" => Ok(EntityType::Venue),
+            "temporal" => Ok(EntityType::Temporal),
+            "term" => Ok(EntityType::Term),
+            other => Err(format!("Unknown entity type: {}", other)),
+        }
+    }
+}
+
+/// Query classification returned by the LLM fallback
+#[derive(Debug, Deserialize)]
+struct LlmUnderstanding {
+    intent: String,
+    #[serde(default)]
+    entities: Vec<LlmEntity>,
+    confidence: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlmEntity {
+    text: String,
+    #[serde(rename = "type")]
+    entity_type: String,
+}
+
+/// Word lists the parser matches queries against
+///
+/// All entries are lowercase. In a dictionary file every list is optional;
+/// a missing list keeps its built-in entries.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QueryDictionaries {
+    /// Abbreviations and their expansions
+    pub synonyms: HashMap<String, Vec<String>>,
+    
+    /// Words ignored during entity extraction
+    pub stop_words: HashSet<String>,
+    
+    /// Single words naming a method or model family
+    pub methods: HashSet<String>,
+    
+    /// Two-word concepts, e.g. "machine learning"
+    pub concepts: HashSet<String>,
+    
+    /// Words referring to time, besides years
+    pub temporal_terms: HashSet<String>,
+}
+
+impl QueryDictionaries {
+    /// Load dictionaries from a JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path).map_err(|e| AppError::Configuration {
+            message: format!("Failed to read query dictionaries {}: {}", path.display(), e),
+        })?;
+        let dictionaries: Self = serde_json::from_str(&data).map_err(|e| AppError::Configuration {
+            message: format!("Invalid query dictionaries {}: {}", path.display(), e),
+        })?;
+        Ok(dictionaries.normalized())
+    }
+    
+    /// The built-in dictionaries, shared by every parser that uses them
+    pub fn builtin() -> Arc<Self> {
+        static BUILTIN: OnceLock<Arc<QueryDictionaries>> = OnceLock::new();
+        BUILTIN.get_or_init(|| Arc::new(Self::default())).clone()
+    }
+    
+    fn normalized(self) -> Self {
+        let lower = |set: HashSet<String>| set.into_iter().map(|w| w.to_lowercase()).collect();
+        Self {
+            synonyms: self
+                .synonyms
+                .into_iter()
+                .map(|(word, syns)| (word.to_lowercase(), syns))
+                .collect(),
+            stop_words: lower(self.stop_words),
+            methods: lower(self.methods),
+            concepts: lower(self.concepts),
+            temporal_terms: lower(self.temporal_terms),
+        }
+    }
+}
+
+impl Default for QueryDictionaries {
+    fn default() -> Self {
+        let set = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        
+        // Common ML/research abbreviations
+        let synonyms = [
+            ("ml", "machine learning"),
+            ("nlp", "natural language processing"),
+            ("cv", "computer vision"),
+            ("dl", "deep learning"),
+            ("llm", "large language model"),
+            ("rl", "reinforcement learning"),
+            ("gan", "generative adversarial network"),
+            ("vae", "variational autoencoder"),
+        ]
+        .into_iter()
+        .map(|(word, syn)| (word.to_string(), vec![syn.to_string()]))
+        .collect();
+        
+        Self {
+            synonyms,
+            stop_words: set(&[
+                "a", "an", "the", "is", "are", "was", "were", "be", "been",
+                "in", "on", "at", "to", "for", "of", "with", "by", "from",
+                "and", "or", "but", "not", "this", "that", "these", "those",
+                "it", "its", "as", "do", "does", "did", "has", "have", "had",
+                "can", "could", "will", "would", "should", "may", "might",
+            ]),
+            methods: set(&[
+                "algorithm", "model", "network", "transformer", "cnn", "rnn",
+                "lstm", "bert", "gpt", "attention", "embedding", "classifier",
+                "regression", "clustering", "detection", "segmentation",
+            ]),
+            concepts: set(&[
+                "machine learning", "deep learning", "neural network",
+                "natural language", "computer vision", "reinforcement learning",
+                "transfer learning", "attention mechanism", "language model",
+                "knowledge graph", "graph neural", "generative model",
+            ]),
+            temporal_terms: set(&["recent", "latest", "new", "early", "current"]),
+        }
+    }
+}
+
 /// Query parser configuration
 #[derive(Debug, Clone)]
 pub struct QueryParserConfig {
@@ -94,6 +303,9 @@ pub struct QueryParserConfig {
     
     /// Use LLM for complex queries
     pub use_llm_fallback: bool,
+    
+    /// Heuristic confidence below which the LLM is consulted
+    pub llm_fallback_threshold: f32,
 }
 
 impl Default for QueryParserConfig {
@@ -103,6 +315,7 @@ impl Default for QueryParserConfig {
             max_expansions: 5,
             min_entity_confidence: 0.6,
             use_llm_fallback: true,
+            llm_fallback_threshold: 0.6,
         }
     }
 }
@@ -111,30 +324,201 @@ impl Default for QueryParserConfig {
 pub struct QueryParser {
     config: QueryParserConfig,
     
-    /// Synonym dictionary for expansion
-    synonyms: HashMap<String, Vec<String>>,
+    /// Synonym, stop word and entity dictionaries
+    dictionaries: Arc<QueryDictionaries>,
+    
+    /// Cache of parsed queries and its TTL in seconds
+    cache: Option<(Arc<Cache>, u64)>,
     
-    /// Stop words to filter
-    stop_words: Vec<String>,
+    /// LLM for queries the heuristics can't make sense of
+    llm: Option<Arc<LlmClient>>,
 }
 
 impl QueryParser {
-    /// Create a new query parser
+    /// Create a new query parser with the built-in dictionaries
     pub fn new(config: QueryParserConfig) -> Self {
-        let synonyms = Self::load_default_synonyms();
-        let stop_words = Self::load_stop_words();
-        
+        Self::with_dictionaries(config, QueryDictionaries::builtin())
+    }
+    
+    /// Create a query parser with shared dictionaries
+    pub fn with_dictionaries(config: QueryParserConfig, dictionaries: Arc<QueryDictionaries>) -> Self {
         Self {
             config,
-            synonyms,
-            stop_words,
+            dictionaries,
+            cache: None,
+            llm: None,
         }
     }
     
+    /// Classify low-confidence queries with `llm` when
+    /// [`use_llm_fallback`](QueryParserConfig::use_llm_fallback) is set
+    pub fn with_llm(mut self, llm: Arc<LlmClient>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+    
+    /// Cache parsed queries for `ttl_secs`
+    ///
+    /// Entries are keyed by the normalized query only, so parsers sharing a
+    /// cache should share their configuration and dictionaries too.
+    pub fn with_cache(mut self, cache: Arc<Cache>, ttl_secs: u64) -> Self {
+        self.cache = Some((cache, ttl_secs));
+        self
+    }
+    
+    /// Normalize a query for parsing and caching: trimmed, lowercase and
+    /// with single spaces between words
+    pub fn normalize(query: &str) -> String {
+        query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+    }
+    
+    /// Split a comparison query into the entities it compares
+    ///
+    /// Recognizes "x vs y", "x versus y", "compare x and/with/to y",
+    /// "difference between x and y" and "how does x compare to y", with any
+    /// number of comma-separated entities and an optional trailing aspect
+    /// ("... for text classification"). Returns `None` unless at least two
+    /// entities are found.
+    pub fn comparison(query: &str) -> Option<ComparisonQuery> {
+        static COMPARED_TO: OnceLock<Regex> = OnceLock::new();
+        static LEADING: OnceLock<Regex> = OnceLock::new();
+        static VERSUS: OnceLock<Regex> = OnceLock::new();
+        static SEPARATOR: OnceLock<Regex> = OnceLock::new();
+        static FILLER: OnceLock<Regex> = OnceLock::new();
+        static ASPECT: OnceLock<Regex> = OnceLock::new();
+        
+        let compared_to = COMPARED_TO.get_or_init(|| {
+            Regex::new(r"^(?:how (?:does|do|did) )?(.+?) compared? (?:to|with|against) (.+)$").unwrap()
+        });
+        let leading = LEADING.get_or_init(|| {
+            Regex::new(r"^(?:(?:what (?:is|are) )?(?:the )?(?:differences?|comparison) (?:between|of)|compare|comparing|contrast|contrasting) (.+)$").unwrap()
+        });
+        let versus = VERSUS.get_or_init(|| Regex::new(r" (?:vs\.?|versus) ").unwrap());
+        let separator = SEPARATOR.get_or_init(|| {
+            Regex::new(r" *, *(?:and |or )?| (?:vs\.?|versus|and|with|to|or|against) ").unwrap()
+        });
+        let filler = FILLER.get_or_init(|| {
+            Regex::new(r"^(?:how (?:does|do|did)|what (?:is|are)|which is better,?|is|are|does|do|should i use) ").unwrap()
+        });
+        let aspect = ASPECT.get_or_init(|| {
+            Regex::new(r" (?:for|in terms of|on|when|regarding|across|in) (.+)$").unwrap()
+        });
+        
+        let query = Self::normalize(query);
+        let query = query.trim_end_matches(['?', '.', '!']);
+        let span = if let Some(caps) = compared_to.captures(query) {
+            format!("{} vs {}", &caps[1], &caps[2])
+        } else if let Some(caps) = leading.captures(query) {
+            caps[1].to_string()
+        } else if versus.is_match(query) {
+            query.to_string()
+        } else {
+            return None;
+        };
+        
+        let mut subjects: Vec<String> = separator.split(&span).map(str::to_string).collect();
+        let mut query_aspect = None;
+        if let Some(last) = subjects.last_mut() {
+            if let Some(caps) = aspect.captures(last) {
+                query_aspect = Some(caps[1].to_string());
+                let end = caps.get(0).map(|m| m.start()).unwrap_or(last.len());
+                last.truncate(end);
+            }
+        }
+        if let Some(first) = subjects.first_mut() {
+            *first = filler.replace(first, "").into_owned();
+        }
+        
+        let mut seen = HashSet::new();
+        subjects.retain(|s| !s.trim().is_empty() && seen.insert(s.trim().to_string()));
+        let subjects: Vec<String> = subjects.into_iter().map(|s| s.trim().to_string()).collect();
+        
+        (subjects.len() >= 2).then_some(ComparisonQuery {
+            subjects,
+            aspect: query_aspect,
+        })
+    }
+    
     /// Parse a query and extract understanding
     pub async fn parse(&self, query: &str) -> Result<QueryUnderstanding> {
-        let query = query.trim().to_lowercase();
+        let query = Self::normalize(query);
+        let Some((cache, ttl_secs)) = &self.cache else {
+            let understanding = self.understand(query).await;
+            metrics::record_query_intent(understanding.intent.as_str());
+            return Ok(understanding);
+        };
+        
+        let key = keys::query_understanding(&hex::encode(Sha256::digest(query.as_bytes())));
+        let cached = cache.get::<QueryUnderstanding>(&key).await.unwrap_or_else(|e| {
+            warn!(error = %e, "Query understanding cache read failed");
+            None
+        });
+        metrics::record_cache(cached.is_some(), "query_understanding");
+        
+        let understanding = match cached {
+            Some(understanding) => understanding,
+            None => {
+                let understanding = self.understand(query).await;
+                if let Err(e) = cache.set_with_ttl(&key, &understanding, *ttl_secs).await {
+                    warn!(error = %e, "Failed to cache query understanding");
+                }
+                understanding
+            }
+        };
+        metrics::record_query_intent(understanding.intent.as_str());
+        
+        Ok(understanding)
+    }
+    
+    /// Understand a normalized query, consulting the LLM if needed
+    async fn understand(&self, query: String) -> QueryUnderstanding {
+        let mut understanding = self.understand_heuristically(query);
+        
+        let llm = match &self.llm {
+            Some(llm) if self.config.use_llm_fallback
+                && understanding.confidence < self.config.llm_fallback_threshold => llm,
+            _ => return understanding,
+        };
         
+        match self.classify_with_llm(llm, &understanding.original_query).await {
+            Ok(classified) => {
+                debug!(
+                    query = %understanding.original_query,
+                    heuristic_confidence = understanding.confidence,
+                    intent = %classified.intent,
+                    "Query classified by LLM"
+                );
+                merge_llm_understanding(&mut understanding, classified);
+            }
+            Err(e) => warn!(error = %e, "LLM query classification failed, using heuristics"),
+        }
+        
+        understanding
+    }
+    
+    /// Ask the LLM for the query's intent and entities
+    async fn classify_with_llm(&self, llm: &LlmClient, query: &str) -> Result<LlmUnderstanding> {
+        let prompt = format!(
+            "Classify the intent of the search query and extract the entities it mentions.\n\n\
+            Intents: factual, comparison, exploratory, procedural, survey, general.\n\
+            Entity types: concept, author, method, dataset, venue, temporal, term.\n\n\
+            Reply with JSON of the form \
+            {{\"intent\": \"...\", \"entities\": [{{\"text\": \"...\", \"type\": \"...\"}}], \"confidence\": 0.0}}, \
+            where confidence (0 to 1) is how sure you are of the intent.\n\n\
+            Query: {}",
+            query
+        );
+        
+        llm.complete_json(&Completion {
+            system: LLM_SYSTEM_PROMPT,
+            prompt: &prompt,
+            max_tokens: 300,
+            temperature: 0.0,
+        }).await
+    }
+    
+    /// Understand a normalized query with the built-in heuristics
+    fn understand_heuristically(&self, query: String) -> QueryUnderstanding {
         // Detect intent
         let intent = self.detect_intent(&query);
         
@@ -151,13 +535,13 @@ impl QueryParser {
         // Calculate confidence based on extraction quality
         let confidence = self.calculate_confidence(&intent, &entities);
         
-        Ok(QueryUnderstanding {
+        QueryUnderstanding {
             original_query: query,
             intent,
             entities,
             expanded_terms,
             confidence,
-        })
+        }
     }
     
     /// Detect query intent using heuristics
@@ -287,7 +671,7 @@ impl QueryParser {
         let mut expansions = Vec::new();
         
         for word in query.split_whitespace() {
-            if let Some(syns) = self.synonyms.get(&word.to_lowercase()) {
+            if let Some(syns) = self.dictionaries.synonyms.get(&word.to_lowercase()) {
                 for syn in syns.iter().take(self.config.max_expansions) {
                     if !expansions.contains(syn) {
                         expansions.push(syn.clone());
@@ -317,16 +701,11 @@ impl QueryParser {
     }
     
     fn is_stop_word(&self, word: &str) -> bool {
-        self.stop_words.contains(&word.to_lowercase())
+        self.dictionaries.stop_words.contains(&word.to_lowercase())
     }
     
     fn is_method_keyword(&self, word: &str) -> bool {
-        let methods = [
-            "algorithm", "model", "network", "transformer", "cnn", "rnn", 
-            "lstm", "bert", "gpt", "attention", "embedding", "classifier",
-            "regression", "clustering", "detection", "segmentation",
-        ];
-        methods.contains(&word.to_lowercase().as_str())
+        self.dictionaries.methods.contains(&word.to_lowercase())
     }
     
     fn is_temporal(&self, word: &str) -> bool {
@@ -334,44 +713,54 @@ impl QueryParser {
         if let Ok(year) = word.parse::<i32>() {
             return (1900..=2100).contains(&year);
         }
-        let temporal_terms = ["recent", "latest", "new", "early", "current"];
-        temporal_terms.contains(&word.to_lowercase().as_str())
+        self.dictionaries.temporal_terms.contains(&word.to_lowercase())
     }
     
     fn is_known_concept(&self, bigram: &str) -> bool {
-        let known = [
-            "machine learning", "deep learning", "neural network", 
-            "natural language", "computer vision", "reinforcement learning",
-            "transfer learning", "attention mechanism", "language model",
-            "knowledge graph", "graph neural", "generative model",
-        ];
-        known.contains(&bigram.to_lowercase().as_str())
-    }
-    
-    fn load_default_synonyms() -> HashMap<String, Vec<String>> {
-        let mut synonyms = HashMap::new();
-        
-        // Add some common ML/research synonyms
-        synonyms.insert("ml".to_string(), vec!["machine learning".to_string()]);
-        synonyms.insert("nlp".to_string(), vec!["natural language processing".to_string()]);
-        synonyms.insert("cv".to_string(), vec!["computer vision".to_string()]);
-        synonyms.insert("dl".to_string(), vec!["deep learning".to_string()]);
-        synonyms.insert("llm".to_string(), vec!["large language model".to_string()]);
-        synonyms.insert("rl".to_string(), vec!["reinforcement learning".to_string()]);
-        synonyms.insert("gan".to_string(), vec!["generative adversarial network".to_string()]);
-        synonyms.insert("vae".to_string(), vec!["variational autoencoder".to_string()]);
-        
-        synonyms
-    }
-    
-    fn load_stop_words() -> Vec<String> {
-        vec![
-            "a", "an", "the", "is", "are", "was", "were", "be", "been",
-            "in", "on", "at", "to", "for", "of", "with", "by", "from",
-            "and", "or", "but", "not", "this", "that", "these", "those",
-            "it", "its", "as", "do", "does", "did", "has", "have", "had",
-            "can", "could", "will", "would", "should", "may", "might",
-        ].into_iter().map(|s| s.to_string()).collect()
+        self.dictionaries.concepts.contains(&bigram.to_lowercase())
+    }
+}
+
+/// Fold an LLM classification into a heuristic understanding
+///
+/// The LLM's intent replaces the heuristic one; its entities are added to
+/// the heuristic entities, and refine the type of plain terms.
+fn merge_llm_understanding(understanding: &mut QueryUnderstanding, classified: LlmUnderstanding) {
+    if let Ok(intent) = classified.intent.parse() {
+        understanding.intent = intent;
+    }
+    
+    for entity in classified.entities {
+        let text = QueryParser::normalize(&entity.text);
+        if text.is_empty() {
+            continue;
+        }
+        let entity_type = entity.entity_type.parse().unwrap_or(EntityType::Term);
+        
+        match understanding.entities.iter_mut().find(|e| e.text == text) {
+            Some(existing) => {
+                if existing.entity_type == EntityType::Term {
+                    existing.entity_type = entity_type;
+                    existing.confidence = existing.confidence.max(LLM_ENTITY_CONFIDENCE);
+                }
+            }
+            None => {
+                let span = understanding
+                    .original_query
+                    .find(&text)
+                    .map(|start| (start, start + text.len()));
+                understanding.entities.push(Entity {
+                    text,
+                    entity_type,
+                    confidence: LLM_ENTITY_CONFIDENCE,
+                    span,
+                });
+            }
+        }
+    }
+    
+    if let Some(confidence) = classified.confidence {
+        understanding.confidence = understanding.confidence.max(confidence.clamp(0.0, 1.0));
     }
 }
 
@@ -398,6 +787,21 @@ mod tests {
         assert_eq!(result.intent, QueryIntent::Comparison);
     }
     
+    #[test]
+    fn test_comparison_subjects() {
+        let subjects = |q: &str| QueryParser::comparison(q).map(|c| c.subjects);
+        
+        let comparison = QueryParser::comparison("Compare BERT vs GPT for text classification").unwrap();
+        assert_eq!(comparison.subjects, vec!["bert", "gpt"]);
+        assert_eq!(comparison.aspect.as_deref(), Some("text classification"));
+        
+        assert_eq!(subjects("What is the difference between CNNs and RNNs?").unwrap(), vec!["cnns", "rnns"]);
+        assert_eq!(subjects("How does attention compare to LSTM gating?").unwrap(), vec!["attention", "lstm gating"]);
+        assert_eq!(subjects("transformers vs. rnns, cnns").unwrap(), vec!["transformers", "rnns", "cnns"]);
+        assert_eq!(subjects("transfer learning in vision"), None);
+        assert_eq!(subjects("compare dropout"), None);
+    }
+    
     #[tokio::test]
     async fn test_procedural_intent() {
         let parser = QueryParser::new(QueryParserConfig::default());
@@ -406,4 +810,43 @@ mod tests {
         
         assert_eq!(result.intent, QueryIntent::Procedural);
     }
+    
+    #[tokio::test]
+    async fn test_dictionaries_from_file() {
+        let path = std::env::temp_dir().join(format!("query-dictionaries-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{"methods": ["XGBoost"], "synonyms": {"GBM": ["gradient boosting"]}}"#).unwrap();
+        let dictionaries = QueryDictionaries::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        // Lists left out of the file keep their built-in entries
+        assert!(dictionaries.stop_words.contains("the"));
+        assert!(!dictionaries.methods.contains("bert"));
+        
+        let parser = QueryParser::with_dictionaries(QueryParserConfig::default(), Arc::new(dictionaries));
+        let result = parser.parse("  XGBoost   and GBM ").await.unwrap();
+        
+        assert_eq!(result.original_query, "xgboost and gbm");
+        assert!(result.entities.iter().any(|e| e.text == "xgboost" && e.entity_type == EntityType::Method));
+        assert_eq!(result.expanded_terms, vec!["gradient boosting".to_string()]);
+    }
+    
+    #[test]
+    fn test_merge_llm_understanding() {
+        let parser = QueryParser::new(QueryParserConfig::default());
+        let mut understanding = parser.understand_heuristically(QueryParser::normalize("RLHF reward hacking in GPT-4"));
+        assert_eq!(understanding.intent, QueryIntent::General);
+        
+        let classified: LlmUnderstanding = crate::context::llm::parse_json(
+            "```json\n{\"intent\": \"exploratory\", \"confidence\": 0.9, \"entities\": [\
+            {\"text\": \"RLHF\", \"type\": \"method\"}, {\"text\": \"reward hacking\", \"type\": \"concept\"}]}\n```",
+        ).unwrap();
+        merge_llm_understanding(&mut understanding, classified);
+        
+        assert_eq!(understanding.intent, QueryIntent::Exploratory);
+        assert_eq!(understanding.confidence, 0.9);
+        let concept = understanding.entities.iter().find(|e| e.text == "reward hacking").unwrap();
+        assert_eq!(concept.entity_type, EntityType::Concept);
+        assert_eq!(concept.span, Some((5, 19)));
+        assert!(understanding.entities.iter().any(|e| e.text == "rlhf" && e.entity_type == EntityType::Method));
+    }
 }
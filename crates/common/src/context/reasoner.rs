@@ -9,6 +9,7 @@
 use crate::errors::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::time::{Duration, Instant};
 
 /// Reasoning chain result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +53,14 @@ pub struct ReasoningHop {
     
     /// Confidence in this hop
     pub confidence: f32,
+    
+    /// Time spent on this hop's search, in milliseconds
+    #[serde(default)]
+    pub latency_ms: u64,
+    
+    /// Whether the search ran out of its latency budget
+    #[serde(default)]
+    pub timed_out: bool,
 }
 
 /// Reasoner configuration
@@ -68,6 +77,10 @@ pub struct ReasonerConfig {
     
     /// Enable LLM-based fact extraction
     pub use_llm: bool,
+    
+    /// Latency budget of each hop's search; reasoning stops at the first
+    /// hop that exceeds it
+    pub hop_timeout: Option<Duration>,
 }
 
 impl Default for ReasonerConfig {
@@ -77,6 +90,7 @@ impl Default for ReasonerConfig {
             min_confidence: 0.5,
             max_facts_per_hop: 5,
             use_llm: true,
+            hop_timeout: Some(Duration::from_secs(2)),
         }
     }
 }
@@ -116,8 +130,31 @@ impl Reasoner {
         let mut seen_facts: HashSet<String> = HashSet::new();
         
         for hop_num in 1..=self.config.max_hops {
-            // Execute search for current query
-            let contexts = search_fn(current_query.clone()).await?;
+            // Execute search for current query, within the hop's budget
+            let started = Instant::now();
+            let search = search_fn(current_query.clone());
+            let contexts = match self.config.hop_timeout {
+                Some(budget) => match tokio::time::timeout(budget, search).await {
+                    Ok(contexts) => contexts?,
+                    Err(_) => {
+                        tracing::warn!(hop = hop_num, query = %current_query, "Reasoning hop timed out");
+                        hops.push(ReasoningHop {
+                            query: current_query.clone(),
+                            hop_number: hop_num,
+                            facts: Vec::new(),
+                            docs_considered: 0,
+                            next_query: None,
+                            rationale: None,
+                            confidence: 0.0,
+                            latency_ms: started.elapsed().as_millis() as u64,
+                            timed_out: true,
+                        });
+                        break;
+                    }
+                },
+                None => search.await?,
+            };
+            let latency_ms = started.elapsed().as_millis() as u64;
             
             if contexts.is_empty() {
                 break;
@@ -151,6 +188,8 @@ impl Reasoner {
                 next_query: next_query.clone(),
                 rationale,
                 confidence,
+                latency_ms,
+                timed_out: false,
             };
             
             all_facts.extend(new_facts);
@@ -167,11 +206,12 @@ impl Reasoner {
             }
         }
         
-        // Calculate overall confidence
-        let overall_confidence = if hops.is_empty() {
+        // Calculate overall confidence over the hops that completed
+        let completed: Vec<&ReasoningHop> = hops.iter().filter(|h| !h.timed_out).collect();
+        let overall_confidence = if completed.is_empty() {
             0.0
         } else {
-            hops.iter().map(|h| h.confidence).sum::<f32>() / hops.len() as f32
+            completed.iter().map(|h| h.confidence).sum::<f32>() / completed.len() as f32
         };
         
         Ok(ReasoningChain {
@@ -354,4 +394,23 @@ mod tests {
         
         assert_eq!(sentences.len(), 3);
     }
+    
+    #[tokio::test]
+    async fn test_hop_timeout() {
+        let reasoner = Reasoner::new(ReasonerConfig {
+            hop_timeout: Some(Duration::from_millis(10)),
+            ..Default::default()
+        });
+        
+        let slow_search = |_query: String| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(Vec::new())
+        };
+        
+        let chain = reasoner.reason("attention in transformers", slow_search).await.unwrap();
+        
+        assert_eq!(chain.hop_count, 1);
+        assert!(chain.hops[0].timed_out);
+        assert_eq!(chain.confidence, 0.0);
+    }
 }
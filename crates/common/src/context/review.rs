@@ -0,0 +1,325 @@
+//! Literature review generation
+//!
+//! Groups the papers retrieved for a topic into sub-topics by embedding
+//! similarity and writes one review section per group, citing papers by
+//! their bibliography number.
+
+use super::llm::{Completion, LlmClient};
+use super::verification::cosine_similarity;
+use crate::embeddings::Embedder;
+use crate::errors::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const REVIEW_SYSTEM_PROMPT: &str = "You are a research assistant writing one section of a \
+    literature review. Use ONLY the provided papers and reply with JSON.";
+
+/// k-means iterations when clustering papers
+const CLUSTER_ITERATIONS: usize = 10;
+
+/// Review configuration
+#[derive(Debug, Clone)]
+pub struct ReviewConfig {
+    /// Most sections (sub-topics) in a review
+    pub max_sections: usize,
+    
+    /// Maximum output tokens per section
+    pub max_tokens_per_section: usize,
+    
+    /// Generation temperature
+    pub temperature: f32,
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            max_sections: 5,
+            max_tokens_per_section: 600,
+            temperature: 0.4,
+        }
+    }
+}
+
+/// A paper retrieved for the review, with its best passages
+#[derive(Debug, Clone)]
+pub struct ReviewPaper {
+    pub paper_id: Uuid,
+    pub title: String,
+    pub passages: Vec<String>,
+    pub score: f32,
+}
+
+/// Generated literature review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiteratureReview {
+    pub topic: String,
+    pub sections: Vec<ReviewSection>,
+    pub bibliography: Vec<BibliographyEntry>,
+}
+
+/// One sub-topic section of a review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewSection {
+    pub title: String,
+    pub content: String,
+    
+    /// Bibliography numbers cited in the section
+    pub citations: Vec<usize>,
+}
+
+/// Bibliography entry; `index` is the number papers are cited by
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BibliographyEntry {
+    pub index: usize,
+    pub paper_id: Uuid,
+    pub title: String,
+}
+
+/// Section as returned by the LLM
+#[derive(Debug, Deserialize)]
+struct SectionResponse {
+    title: String,
+    content: String,
+}
+
+/// Writes literature reviews from retrieved papers
+pub struct ReviewWriter {
+    llm: Arc<LlmClient>,
+    embedder: Arc<dyn Embedder>,
+    config: ReviewConfig,
+}
+
+impl ReviewWriter {
+    /// Create a new writer
+    pub fn new(llm: Arc<LlmClient>, embedder: Arc<dyn Embedder>, config: ReviewConfig) -> Self {
+        Self { llm, embedder, config }
+    }
+    
+    /// Group papers into sub-topics
+    ///
+    /// Returns indices into `papers`, largest group first. Papers should be
+    /// ordered by relevance; the most relevant one seeds the first group.
+    pub async fn cluster(&self, papers: &[ReviewPaper]) -> Result<Vec<Vec<usize>>> {
+        if papers.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let texts: Vec<String> = papers
+            .iter()
+            .map(|p| format!("{}\n{}", p.title, p.passages.first().map(String::as_str).unwrap_or("")))
+            .collect();
+        let embeddings = self.embedder.embed_batch(&texts).await?;
+        
+        // Roughly two papers per section, at least one section
+        let k = ((papers.len() as f64 / 2.0).sqrt().ceil() as usize).clamp(1, self.config.max_sections.max(1));
+        Ok(kmeans(&embeddings, k))
+    }
+    
+    /// Write the section for one group of papers
+    ///
+    /// `group` holds indices into `papers`; papers are cited by their
+    /// position in `papers`, counting from 1.
+    pub async fn write_section(
+        &self,
+        topic: &str,
+        papers: &[ReviewPaper],
+        group: &[usize],
+    ) -> Result<ReviewSection> {
+        let response = if self.llm.is_configured() {
+            let prompt = build_section_prompt(topic, papers, group);
+            self.llm.complete_json::<SectionResponse>(&Completion {
+                system: REVIEW_SYSTEM_PROMPT,
+                prompt: &prompt,
+                max_tokens: self.config.max_tokens_per_section,
+                temperature: self.config.temperature,
+            }).await?
+        } else {
+            // Mock section for development/testing
+            mock_section(papers, group)
+        };
+        
+        let citations = section_citations(&response.content, group);
+        Ok(ReviewSection {
+            title: response.title,
+            content: response.content,
+            citations,
+        })
+    }
+}
+
+/// Bibliography of every retrieved paper, numbered from 1
+pub fn bibliography(papers: &[ReviewPaper]) -> Vec<BibliographyEntry> {
+    papers
+        .iter()
+        .enumerate()
+        .map(|(i, p)| BibliographyEntry {
+            index: i + 1,
+            paper_id: p.paper_id,
+            title: p.title.clone(),
+        })
+        .collect()
+}
+
+fn build_section_prompt(topic: &str, papers: &[ReviewPaper], group: &[usize]) -> String {
+    let mut prompt = format!(
+        "The review is about: {}\n\n\
+        The papers below cover one sub-topic of it. Name the sub-topic and write a section of 2-4 \
+        paragraphs that synthesizes the papers: their approaches, findings and where they agree \
+        or disagree. Cite papers inline by number, e.g. [3].\n\n\
+        Reply with JSON of the form {{\"title\": string, \"content\": string}}.\n\nPapers:\n",
+        topic
+    );
+    
+    for &i in group {
+        let paper = &papers[i];
+        prompt.push_str(&format!("\n[{}] {}\n", i + 1, paper.title));
+        for passage in &paper.passages {
+            prompt.push_str(passage);
+            prompt.push('\n');
+        }
+    }
+    
+    prompt
+}
+
+fn mock_section(papers: &[ReviewPaper], group: &[usize]) -> SectionResponse {
+    let mut term_counts: HashMap<String, usize> = HashMap::new();
+    for &i in group {
+        for word in papers[i].title.split_whitespace() {
+            let word: String = word.chars().filter(|c| c.is_alphanumeric()).collect();
+            if word.len() > 4 {
+                *term_counts.entry(word.to_lowercase()).or_default() += 1;
+            }
+        }
+    }
+    let title = term_counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(term, _)| term)
+        .unwrap_or_else(|| "Related work".to_string());
+    
+    let content = group
+        .iter()
+        .map(|&i| format!("{} [{}].", papers[i].title, i + 1))
+        .collect::<Vec<_>>()
+        .join(" ");
+    
+    SectionResponse {
+        title,
+        content: format!("{}\n\n[Mock section - LLM offline]", content),
+    }
+}
+
+/// Citation numbers in `content` that belong to the section's group
+fn section_citations(content: &str, group: &[usize]) -> Vec<usize> {
+    let pattern = regex_lite::Regex::new(r"\[(\d+)\]").unwrap();
+    let mut citations: Vec<usize> = pattern
+        .captures_iter(content)
+        .filter_map(|cap| cap[1].parse::<usize>().ok())
+        .filter(|&n| n > 0 && group.contains(&(n - 1)))
+        .collect();
+    citations.sort_unstable();
+    citations.dedup();
+    citations
+}
+
+/// Cluster embeddings into at most `k` groups by cosine similarity
+///
+/// Centroids are seeded farthest-first starting from the first embedding,
+/// so results are deterministic. Empty groups are dropped and the rest are
+/// returned largest first.
+pub(crate) fn kmeans(embeddings: &[Vec<f32>], k: usize) -> Vec<Vec<usize>> {
+    if embeddings.is_empty() || k == 0 {
+        return Vec::new();
+    }
+    
+    let mut centroids = vec![embeddings[0].clone()];
+    while centroids.len() < k.min(embeddings.len()) {
+        let farthest = (0..embeddings.len())
+            .map(|i| {
+                let nearest = centroids
+                    .iter()
+                    .map(|c| cosine_similarity(&embeddings[i], c))
+                    .fold(f32::MIN, f32::max);
+                (i, nearest)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        centroids.push(embeddings[farthest].clone());
+    }
+    
+    let mut assignment = vec![0; embeddings.len()];
+    for _ in 0..CLUSTER_ITERATIONS {
+        let mut changed = false;
+        for (i, embedding) in embeddings.iter().enumerate() {
+            let best = (0..centroids.len())
+                .max_by(|&a, &b| {
+                    cosine_similarity(embedding, &centroids[a])
+                        .total_cmp(&cosine_similarity(embedding, &centroids[b]))
+                })
+                .unwrap_or(0);
+            if assignment[i] != best {
+                assignment[i] = best;
+                changed = true;
+            }
+        }
+        
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<&Vec<f32>> = embeddings
+                .iter()
+                .zip(&assignment)
+                .filter(|(_, &a)| a == c)
+                .map(|(e, _)| e)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            for (d, value) in centroid.iter_mut().enumerate() {
+                *value = members.iter().map(|m| m[d]).sum::<f32>() / members.len() as f32;
+            }
+        }
+        
+        if !changed {
+            break;
+        }
+    }
+    
+    let mut groups: Vec<Vec<usize>> = vec![Vec::new(); centroids.len()];
+    for (i, &c) in assignment.iter().enumerate() {
+        groups[c].push(i);
+    }
+    groups.retain(|g| !g.is_empty());
+    groups.sort_by_key(|g| std::cmp::Reverse(g.len()));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_kmeans_separates_topics() {
+        let embeddings = vec![
+            vec![1.0, 0.1, 0.0],
+            vec![0.0, 0.1, 1.0],
+            vec![0.9, 0.0, 0.1],
+            vec![0.1, 0.0, 0.9],
+            vec![1.0, 0.0, 0.0],
+        ];
+        
+        let groups = kmeans(&embeddings, 2);
+        assert_eq!(groups, vec![vec![0, 2, 4], vec![1, 3]]);
+        
+        assert_eq!(kmeans(&embeddings, 10).len(), 5);
+        assert!(kmeans(&[], 3).is_empty());
+    }
+    
+    #[test]
+    fn test_section_citations_stay_in_group() {
+        let citations = section_citations("Both [3] and [1] agree [3], unlike [2] or [9].", &[0, 2]);
+        assert_eq!(citations, vec![1, 3]);
+    }
+}
@@ -5,10 +5,19 @@
 //! - Citation extraction
 //! - Confidence scoring
 //! - Hallucination detection
+//! - Structured comparisons
 
-use crate::errors::{AppError, Result};
+use super::llm::{Completion, LLMConfig, LlmClient};
+use super::token_budget::{estimate_tokens, truncate_to_tokens};
+use super::verification::{
+    self, UnsupportedClaim, UnsupportedClaimAction, Verification, VerificationMethod,
+};
+use crate::embeddings::Embedder;
+use crate::errors::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
 use uuid::Uuid;
 
 /// Synthesized answer
@@ -28,6 +37,10 @@ pub struct SynthesizedAnswer {
     
     /// Key facts extracted
     pub key_facts: Vec<String>,
+    
+    /// Sentences the contexts don't support
+    #[serde(default)]
+    pub unsupported_claims: Vec<UnsupportedClaim>,
 }
 
 /// Citation in synthesized answer
@@ -66,6 +79,15 @@ pub struct SynthesisOptions {
     
     /// System prompt override
     pub system_prompt: Option<String>,
+    
+    /// How answer sentences are checked against the contexts
+    pub verification: VerificationMethod,
+    
+    /// What to do with sentences the contexts don't support
+    pub unsupported_claims: UnsupportedClaimAction,
+    
+    /// Minimum embedding similarity for a sentence to count as supported
+    pub support_threshold: f32,
 }
 
 /// Synthesis style
@@ -87,6 +109,9 @@ impl Default for SynthesisOptions {
             include_citations: true,
             style: SynthesisStyle::Detailed,
             system_prompt: None,
+            verification: VerificationMethod::Embedding,
+            unsupported_claims: UnsupportedClaimAction::Flag,
+            support_threshold: 0.6,
         }
     }
 }
@@ -100,50 +125,97 @@ pub struct SynthesisContext {
     pub relevance_score: f32,
 }
 
-/// LLM client configuration
+/// Contexts retrieved for one side of a comparison
 #[derive(Debug, Clone)]
-pub struct LLMConfig {
-    /// API endpoint
-    pub endpoint: String,
+pub struct ComparisonSubject {
+    /// Name of the compared entity, e.g. a method or model
+    pub name: String,
+    
+    /// Contexts retrieved for this entity
+    pub contexts: Vec<SynthesisContext>,
+}
+
+/// Structured comparison of two or more entities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuredComparison {
+    /// Compared entities, in query order
+    pub subjects: Vec<String>,
     
-    /// API key
-    pub api_key: String,
+    /// Dimensions the entities are compared along
+    pub dimensions: Vec<ComparisonDimension>,
     
-    /// Model name
-    pub model: String,
+    /// Overall summary of the comparison
+    pub summary: String,
     
-    /// Timeout in seconds
-    pub timeout_secs: u64,
+    /// Contexts cited anywhere in the comparison
+    pub citations: Vec<Citation>,
 }
 
-impl Default for LLMConfig {
-    fn default() -> Self {
-        Self {
-            endpoint: "https://api.openai.com/v1/chat/completions".to_string(),
-            api_key: String::new(),
-            model: "gpt-4o-mini".to_string(),
-            timeout_secs: 30,
-        }
-    }
+/// One dimension of a comparison, e.g. accuracy or training cost
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonDimension {
+    /// Dimension name
+    pub name: String,
+    
+    /// What the contexts say about each entity along this dimension
+    pub findings: Vec<SubjectFinding>,
+    
+    /// How the entities differ along this dimension, with citations
+    #[serde(default)]
+    pub difference: Option<String>,
+}
+
+/// Finding about one entity along a comparison dimension
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubjectFinding {
+    /// Entity the finding is about
+    pub subject: String,
+    
+    /// The finding itself
+    pub finding: String,
+    
+    /// Indices (1-based) of the contexts supporting the finding
+    #[serde(default)]
+    pub citations: Vec<usize>,
+}
+
+/// Comparison as returned by the LLM, before citations are resolved
+#[derive(Debug, Deserialize)]
+struct ComparisonResponse {
+    #[serde(default)]
+    dimensions: Vec<ComparisonDimension>,
+    #[serde(default)]
+    summary: String,
 }
 
+const DEFAULT_SYSTEM_PROMPT: &str = "You are a helpful research assistant.";
+
+const COMPARISON_SYSTEM_PROMPT: &str = "You are a research assistant comparing methods from the \
+    scientific literature. Use ONLY the provided context and reply with JSON.";
+
 /// Synthesizer for generating answers
 pub struct Synthesizer {
-    config: LLMConfig,
-    client: reqwest::Client,
+    llm: Arc<LlmClient>,
+    
+    /// Embedder for embedding-based verification
+    embedder: Option<Arc<dyn Embedder>>,
 }
 
 impl Synthesizer {
     /// Create a new synthesizer
     pub fn new(config: LLMConfig) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(config.timeout_secs))
-            .build()
-            .map_err(|e| AppError::Internal { 
-                message: format!("Failed to create HTTP client: {}", e) 
-            })?;
-        
-        Ok(Self { config, client })
+        Ok(Self::from_client(Arc::new(LlmClient::new(config)?)))
+    }
+    
+    /// Create a synthesizer sharing an existing LLM client
+    pub fn from_client(llm: Arc<LlmClient>) -> Self {
+        Self { llm, embedder: None }
+    }
+    
+    /// Use `embedder` for [`VerificationMethod::Embedding`]
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = Some(embedder);
+        self
     }
     
     /// Synthesize an answer from context
@@ -153,17 +225,24 @@ impl Synthesizer {
         contexts: &[SynthesisContext],
         options: &SynthesisOptions,
     ) -> Result<SynthesizedAnswer> {
+        // Fit the contexts into the model's context window
+        let contexts = &self.fit_contexts(question, contexts, options);
+        
         // Build prompt
         let prompt = self.build_prompt(question, contexts, options);
         
         // Call LLM
         let response = self.call_llm(&prompt, options).await?;
         
+        // Check the answer's claims against the contexts
+        let verification = self.verify(&response, contexts, options).await;
+        let response = verification::apply_action(&response, &verification, options.unsupported_claims);
+        
         // Extract citations
         let citations = self.extract_citations(&response, contexts);
         
-        // Calculate confidence based on context coverage
-        let confidence = self.calculate_confidence(&response, contexts);
+        // Calculate confidence based on context coverage, less unsupported claims
+        let confidence = self.calculate_confidence(&response, contexts) * verification.supported_ratio();
         
         // Extract key facts
         let key_facts = self.extract_key_facts(&response);
@@ -177,6 +256,207 @@ impl Synthesizer {
             confidence,
             token_count,
             key_facts,
+            unsupported_claims: verification.unsupported.into_iter().map(|(_, claim)| claim).collect(),
+        })
+    }
+    
+    /// Compare two or more entities from their own retrieved contexts
+    ///
+    /// Contexts are numbered across subjects in order, so citation `[n]` in
+    /// the result refers to the n-th context of the flattened list.
+    pub async fn compare(
+        &self,
+        question: &str,
+        subjects: &[ComparisonSubject],
+        options: &SynthesisOptions,
+    ) -> Result<StructuredComparison> {
+        let contexts: Vec<SynthesisContext> = subjects
+            .iter()
+            .flat_map(|s| s.contexts.iter().cloned())
+            .collect();
+        
+        let response = if self.llm.is_configured() {
+            let prompt = self.build_comparison_prompt(question, subjects);
+            self.llm.complete_json::<ComparisonResponse>(&Completion {
+                system: COMPARISON_SYSTEM_PROMPT,
+                prompt: &prompt,
+                max_tokens: options.max_tokens,
+                temperature: options.temperature,
+            }).await?
+        } else {
+            // Mock comparison for development/testing
+            self.generate_mock_comparison(subjects)
+        };
+        
+        Ok(self.resolve_comparison(subjects, response, &contexts))
+    }
+    
+    /// Build the comparison prompt, with contexts grouped by subject
+    fn build_comparison_prompt(&self, question: &str, subjects: &[ComparisonSubject]) -> String {
+        let names: Vec<&str> = subjects.iter().map(|s| s.name.as_str()).collect();
+        let mut prompt = format!(
+            "Compare {} to answer the question below. Pick the dimensions the context actually \
+            covers (for example accuracy, efficiency, data requirements). For each dimension give \
+            one finding per entity and, where the context supports it, the key difference. \
+            Cite sources by number. If the context says nothing about an entity along a \
+            dimension, say so in its finding.\n\n\
+            Reply with JSON of the form {{\"dimensions\": [{{\"name\": string, \"findings\": \
+            [{{\"subject\": string, \"finding\": string, \"citations\": [number]}}], \
+            \"difference\": string or null}}], \"summary\": string}}. Use [n] citations inside \
+            \"difference\" and \"summary\".\n\n\
+            Question: {}\n",
+            names.join(", "),
+            question
+        );
+        
+        let mut index = 0;
+        for subject in subjects {
+            prompt.push_str(&format!("\nContext for {}:\n", subject.name));
+            for ctx in &subject.contexts {
+                index += 1;
+                prompt.push_str(&format!("\n[{}] {}\n{}\n", index, ctx.paper_title, ctx.content));
+            }
+        }
+        
+        prompt
+    }
+    
+    /// Generate a mock comparison for testing
+    fn generate_mock_comparison(&self, subjects: &[ComparisonSubject]) -> ComparisonResponse {
+        let mut index = 0;
+        let findings = subjects
+            .iter()
+            .map(|subject| {
+                let first = index + 1;
+                index += subject.contexts.len();
+                match subject.contexts.first() {
+                    Some(ctx) => SubjectFinding {
+                        subject: subject.name.clone(),
+                        finding: ctx.content.chars().take(200).collect(),
+                        citations: vec![first],
+                    },
+                    None => SubjectFinding {
+                        subject: subject.name.clone(),
+                        finding: "No relevant context found.".to_string(),
+                        citations: Vec::new(),
+                    },
+                }
+            })
+            .collect();
+        
+        ComparisonResponse {
+            dimensions: vec![ComparisonDimension {
+                name: "Overview".to_string(),
+                findings,
+                difference: None,
+            }],
+            summary: "[Mock comparison - LLM offline]".to_string(),
+        }
+    }
+    
+    /// Drop unknown citation indices and collect the cited contexts
+    fn resolve_comparison(
+        &self,
+        subjects: &[ComparisonSubject],
+        mut response: ComparisonResponse,
+        contexts: &[SynthesisContext],
+    ) -> StructuredComparison {
+        let mut cited_text = response.summary.clone();
+        for dimension in &mut response.dimensions {
+            for finding in &mut dimension.findings {
+                finding.citations.retain(|&i| i > 0 && i <= contexts.len());
+                finding.citations.dedup();
+                for i in &finding.citations {
+                    cited_text.push_str(&format!(" [{}]", i));
+                }
+            }
+            if let Some(difference) = &dimension.difference {
+                cited_text.push(' ');
+                cited_text.push_str(difference);
+            }
+        }
+        
+        let mut citations = self.extract_citations(&cited_text, contexts);
+        // Positions refer to the scratch text, not to anything the caller sees
+        for citation in &mut citations {
+            citation.position = None;
+        }
+        
+        StructuredComparison {
+            subjects: subjects.iter().map(|s| s.name.clone()).collect(),
+            dimensions: response.dimensions,
+            summary: response.summary,
+            citations,
+        }
+    }
+    
+    /// Keep the most relevant contexts that fit the model's context window
+    /// next to the prompt and the expected output
+    ///
+    /// Context order is kept, so citation numbers follow the returned list.
+    fn fit_contexts(
+        &self,
+        question: &str,
+        contexts: &[SynthesisContext],
+        options: &SynthesisOptions,
+    ) -> Vec<SynthesisContext> {
+        let budget = self.llm.token_budget()
+            .with_system_prompt(options.system_prompt.as_deref().unwrap_or(DEFAULT_SYSTEM_PROMPT))
+            .with_system_prompt(&self.build_prompt(question, &[], options))
+            .with_output(options.max_tokens);
+        
+        let header_tokens = |ctx: &SynthesisContext| estimate_tokens(&ctx.paper_title) + 8;
+        let sizes: Vec<(f32, usize)> = contexts
+            .iter()
+            .map(|ctx| (ctx.relevance_score, header_tokens(ctx) + estimate_tokens(&ctx.content)))
+            .collect();
+        let granted = budget.allocate(&sizes);
+        
+        contexts
+            .iter()
+            .zip(granted)
+            .filter(|(_, tokens)| *tokens > 0)
+            .map(|(ctx, tokens)| SynthesisContext {
+                content: truncate_to_tokens(&ctx.content, tokens.saturating_sub(header_tokens(ctx))),
+                ..ctx.clone()
+            })
+            .collect()
+    }
+    
+    /// Run the configured verification pass
+    ///
+    /// Verification is best effort: when it can't run, every claim is
+    /// treated as supported.
+    async fn verify(
+        &self,
+        answer: &str,
+        contexts: &[SynthesisContext],
+        options: &SynthesisOptions,
+    ) -> Verification {
+        let claims = verification::extract_claims(answer, contexts.len());
+        let result = match options.verification {
+            VerificationMethod::None => return Verification::default(),
+            VerificationMethod::Embedding => match &self.embedder {
+                Some(embedder) => {
+                    verification::verify_with_embeddings(
+                        embedder.as_ref(),
+                        answer,
+                        claims,
+                        contexts,
+                        options.support_threshold,
+                    ).await
+                }
+                None => return Verification::default(),
+            },
+            VerificationMethod::LlmJudge if self.llm.is_configured() => {
+                verification::verify_with_llm(&self.llm, answer, claims, contexts).await
+            }
+            VerificationMethod::LlmJudge => return Verification::default(),
+        };
+        
+        result.unwrap_or_else(|e| {
+            warn!(error = %e, "Answer verification failed");
+            Verification::default()
         })
     }
     
@@ -224,89 +504,22 @@ impl Synthesizer {
     
     /// Call the LLM API
     async fn call_llm(&self, prompt: &str, options: &SynthesisOptions) -> Result<String> {
-        // In a real implementation, this would call OpenAI or another LLM
-        // For now, return a mock response for testing
-        
-        if self.config.api_key.is_empty() {
+        if !self.llm.is_configured() {
             // Mock response for development/testing
             return Ok(self.generate_mock_response(prompt));
         }
         
-        #[derive(Serialize)]
-        struct ChatMessage {
-            role: String,
-            content: String,
-        }
+        let system = options
+            .system_prompt
+            .as_deref()
+            .unwrap_or(DEFAULT_SYSTEM_PROMPT);
         
-        #[derive(Serialize)]
-        struct ChatRequest {
-            model: String,
-            messages: Vec<ChatMessage>,
-            max_tokens: usize,
-            temperature: f32,
-        }
-        
-        #[derive(Deserialize)]
-        struct ChatChoice {
-            message: ChatMessageResponse,
-        }
-        
-        #[derive(Deserialize)]
-        struct ChatMessageResponse {
-            content: String,
-        }
-        
-        #[derive(Deserialize)]
-        struct ChatResponse {
-            choices: Vec<ChatChoice>,
-        }
-        
-        let request = ChatRequest {
-            model: self.config.model.clone(),
-            messages: vec![
-                ChatMessage {
-                    role: "system".to_string(),
-                    content: options.system_prompt.clone().unwrap_or_else(|| {
-                        "You are a helpful research assistant.".to_string()
-                    }),
-                },
-                ChatMessage {
-                    role: "user".to_string(),
-                    content: prompt.to_string(),
-                },
-            ],
+        self.llm.complete(&Completion {
+            system,
+            prompt,
             max_tokens: options.max_tokens,
             temperature: options.temperature,
-        };
-        
-        let response = self.client
-            .post(&self.config.endpoint)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::Internal {
-                message: format!("LLM API request failed: {}", e),
-            })?;
-        
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(AppError::Internal {
-                message: format!("LLM API error {}: {}", status, body),
-            });
-        }
-        
-        let chat_response: ChatResponse = response.json().await
-            .map_err(|e| AppError::Internal {
-                message: format!("Failed to parse LLM response: {}", e),
-            })?;
-        
-        chat_response.choices.first()
-            .map(|c| c.message.content.clone())
-            .ok_or_else(|| AppError::Internal {
-                message: "Empty response from LLM".to_string(),
-            })
+        }).await
     }
     
     /// Generate mock response for testing
@@ -321,14 +534,14 @@ impl Synthesizer {
                     The research literature indicates several key findings [1]. \
                     Further analysis suggests important implications for this area [2]. \
                     However, more research is needed to fully understand the mechanisms involved.\n\n\
-                    [Mock response - LLM API key not configured]",
+                    [Mock response - LLM offline]",
                     question
                 );
             }
         }
         
         "Based on the provided context, the answer requires further investigation. \
-        [Mock response - LLM API key not configured]".to_string()
+        [Mock response - LLM offline]".to_string()
     }
     
     /// Extract citations from response
@@ -465,4 +678,94 @@ mod tests {
         assert!(confidence > 0.5);
         assert!(confidence <= 1.0);
     }
+    
+    #[test]
+    fn test_contexts_fit_context_window() {
+        let synthesizer = Synthesizer::new(LLMConfig {
+            context_window: Some(2000),
+            ..Default::default()
+        }).unwrap();
+        
+        let context = |title: &str, relevance_score: f32| SynthesisContext {
+            paper_id: Uuid::new_v4(),
+            paper_title: title.to_string(),
+            content: "word ".repeat(800),
+            relevance_score,
+        };
+        let contexts = vec![context("Low", 0.3), context("High", 0.9)];
+        let options = SynthesisOptions {
+            max_tokens: 500,
+            ..Default::default()
+        };
+        
+        let fitted = synthesizer.fit_contexts("What is attention?", &contexts, &options);
+        
+        assert_eq!(fitted.len(), 2);
+        assert_eq!(fitted[1].content, contexts[1].content);
+        assert!(fitted[0].content.len() < contexts[0].content.len());
+    }
+    
+    #[tokio::test]
+    async fn test_comparison_citations() {
+        let synthesizer = Synthesizer::new(LLMConfig::default()).unwrap();
+        
+        let context = |title: &str| SynthesisContext {
+            paper_id: Uuid::new_v4(),
+            paper_title: title.to_string(),
+            content: format!("{} content", title),
+            relevance_score: 0.8,
+        };
+        let subjects = vec![
+            ComparisonSubject {
+                name: "bert".to_string(),
+                contexts: vec![context("BERT"), context("RoBERTa")],
+            },
+            ComparisonSubject {
+                name: "gpt".to_string(),
+                contexts: vec![context("GPT")],
+            },
+        ];
+        
+        let response: ComparisonResponse = crate::context::llm::parse_json(r#"{
+            "dimensions": [{
+                "name": "Pretraining objective",
+                "findings": [
+                    {"subject": "bert", "finding": "Masked language modelling", "citations": [1, 9]},
+                    {"subject": "gpt", "finding": "Next-token prediction", "citations": [3]}
+                ],
+                "difference": "Bidirectional [2] versus left-to-right context"
+            }],
+            "summary": "They differ in objective."
+        }"#).unwrap();
+        let contexts: Vec<_> = subjects.iter().flat_map(|s| s.contexts.clone()).collect();
+        let comparison = synthesizer.resolve_comparison(&subjects, response, &contexts);
+        
+        assert_eq!(comparison.subjects, vec!["bert", "gpt"]);
+        assert_eq!(comparison.dimensions[0].findings[0].citations, vec![1]);
+        let cited: Vec<usize> = comparison.citations.iter().map(|c| c.index).collect();
+        assert_eq!(cited, vec![1, 2, 3]);
+        
+        // Without an API key the mock cites each subject's first context
+        let mock = synthesizer.compare("bert vs gpt", &subjects, &SynthesisOptions::default()).await.unwrap();
+        assert_eq!(mock.dimensions[0].findings[1].citations, vec![3]);
+    }
+    
+    #[tokio::test]
+    async fn test_offline_synthesis_is_stable() {
+        let synthesizer = Synthesizer::from_client(Arc::new(LlmClient::offline("gpt-4o-mini")));
+        let contexts = vec![SynthesisContext {
+            paper_id: Uuid::from_u128(1),
+            paper_title: "Attention Is All You Need".to_string(),
+            content: "The Transformer relies entirely on self-attention.".to_string(),
+            relevance_score: 0.9,
+        }];
+        
+        let options = SynthesisOptions::default();
+        let synthesize = || synthesizer.synthesize("What is attention?", &contexts, &options);
+        let first = serde_json::to_string(&synthesize().await.unwrap()).unwrap();
+        let second = serde_json::to_string(&synthesize().await.unwrap()).unwrap();
+        
+        assert_eq!(first, second);
+        assert!(first.contains("[Mock response - LLM offline]"));
+    }
 }
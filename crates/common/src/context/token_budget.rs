@@ -0,0 +1,193 @@
+//! Token budget - Allocates an LLM's context window
+//!
+//! The model's context window is split between the system prompt,
+//! conversation history, the expected output and retrieved context. The
+//! context share goes to windows in order of relevance; the window that
+//! crosses the limit is trimmed and the ones that don't fit are dropped.
+
+/// Context window assumed for models not in the table
+const DEFAULT_CONTEXT_WINDOW: usize = 8192;
+
+/// Smallest share worth trimming a window down to; below this a window is
+/// dropped instead
+const MIN_TRIMMED_TOKENS: usize = 100;
+
+/// Known context windows by model name prefix; the longest matching prefix wins
+const MODEL_CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4o-mini", 128_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4.1", 1_000_000),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4", 8192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("claude", 200_000),
+    ("mistral-large", 128_000),
+    ("llama-3", 128_000),
+];
+
+/// Context window of `model`, in tokens
+pub fn context_window_for_model(model: &str) -> usize {
+    let model = model.to_lowercase();
+    MODEL_CONTEXT_WINDOWS
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, window)| *window)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
+/// Estimate the token count of `text` (~4 characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Cut `text` down to about `max_tokens`, at a word boundary when possible
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> String {
+    let max_chars = max_tokens * 4;
+    if text.len() <= max_chars {
+        return text.to_string();
+    }
+    
+    let mut end = max_chars;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let cut = &text[..end];
+    match cut.rfind(char::is_whitespace) {
+        Some(space) if space > end / 2 => cut[..space].trim_end().to_string(),
+        _ => cut.to_string(),
+    }
+}
+
+/// How a context window's tokens are split
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenAllocation {
+    pub system: usize,
+    pub history: usize,
+    pub context: usize,
+    pub output: usize,
+}
+
+/// Budget of one LLM call
+#[derive(Debug, Clone)]
+pub struct TokenBudget {
+    context_window: usize,
+    system: usize,
+    history: usize,
+    output: usize,
+}
+
+impl TokenBudget {
+    /// Budget for a model with a `context_window`-token window
+    pub fn new(context_window: usize) -> Self {
+        Self {
+            context_window,
+            system: 0,
+            history: 0,
+            output: 0,
+        }
+    }
+    
+    /// Budget for a named model
+    pub fn for_model(model: &str) -> Self {
+        Self::new(context_window_for_model(model))
+    }
+    
+    /// Reserve room for the system prompt and any fixed prompt text
+    pub fn with_system_prompt(mut self, prompt: &str) -> Self {
+        self.system += estimate_tokens(prompt);
+        self
+    }
+    
+    /// Reserve room for conversation history
+    pub fn with_history(mut self, tokens: usize) -> Self {
+        self.history += tokens;
+        self
+    }
+    
+    /// Reserve room for the model's output
+    pub fn with_output(mut self, max_tokens: usize) -> Self {
+        self.output = max_tokens;
+        self
+    }
+    
+    /// Tokens left for retrieved context
+    pub fn context_tokens(&self) -> usize {
+        self.context_window
+            .saturating_sub(self.system)
+            .saturating_sub(self.history)
+            .saturating_sub(self.output)
+    }
+    
+    /// The full split of the context window
+    pub fn allocation(&self) -> TokenAllocation {
+        TokenAllocation {
+            system: self.system,
+            history: self.history,
+            context: self.context_tokens(),
+            output: self.output,
+        }
+    }
+    
+    /// Share out the context tokens among windows by relevance
+    ///
+    /// `windows` holds each window's `(relevance, tokens)`. Returns the
+    /// tokens granted to each, in the same order: the full size for windows
+    /// that fit, less for the one trimmed to fill the budget, 0 for dropped
+    /// windows.
+    pub fn allocate(&self, windows: &[(f32, usize)]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..windows.len()).collect();
+        order.sort_by(|&a, &b| windows[b].0.total_cmp(&windows[a].0));
+        
+        let mut granted = vec![0; windows.len()];
+        let mut remaining = self.context_tokens();
+        for i in order {
+            let tokens = windows[i].1;
+            if tokens <= remaining {
+                granted[i] = tokens;
+                remaining -= tokens;
+            } else if remaining >= MIN_TRIMMED_TOKENS {
+                granted[i] = remaining;
+                remaining = 0;
+            }
+        }
+        
+        granted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_model_context_windows() {
+        assert_eq!(context_window_for_model("gpt-4o-mini"), 128_000);
+        assert_eq!(context_window_for_model("gpt-4-0613"), 8192);
+        assert_eq!(context_window_for_model("gpt-4-32k-0613"), 32_768);
+        assert_eq!(context_window_for_model("my-local-model"), DEFAULT_CONTEXT_WINDOW);
+        assert!(estimate_tokens("This is a test string.") > 0);
+    }
+    
+    #[test]
+    fn test_allocate_by_relevance() {
+        let budget = TokenBudget::new(1000)
+            .with_system_prompt(&"x".repeat(400))
+            .with_history(100)
+            .with_output(300);
+        assert_eq!(budget.context_tokens(), 500);
+        
+        // The most relevant window fits, the next is trimmed to what's left,
+        // and the least relevant is dropped
+        let granted = budget.allocate(&[(0.2, 100), (0.9, 300), (0.5, 400)]);
+        assert_eq!(granted, vec![0, 300, 200]);
+        
+        // Too little left to be worth trimming into
+        assert_eq!(budget.allocate(&[(0.9, 450), (0.5, 400)]), vec![450, 0]);
+        
+        assert_eq!(truncate_to_tokens("one two three four", 3), "one two");
+    }
+}
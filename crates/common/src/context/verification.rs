@@ -0,0 +1,375 @@
+//! Answer verification - Checks synthesized claims against their sources
+//!
+//! Splits an answer into sentences and checks each one against the contexts
+//! it cites (or all contexts when it cites none), either by embedding
+//! similarity or by asking the LLM to judge support. Unsupported sentences
+//! can be flagged, annotated in place or stripped from the answer.
+
+use super::llm::{Completion, LlmClient};
+use super::synthesizer::SynthesisContext;
+use crate::embeddings::Embedder;
+use crate::errors::Result;
+use regex_lite::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Sentences with fewer words aren't treated as claims
+const MIN_CLAIM_WORDS: usize = 4;
+
+/// Marker appended to unsupported sentences by [`UnsupportedClaimAction::Annotate`]
+pub const UNSUPPORTED_MARKER: &str = "[unsupported]";
+
+const JUDGE_SYSTEM_PROMPT: &str = "You check whether claims are supported by source passages. \
+    Reply with a single JSON object and nothing else.";
+
+/// How answer sentences are checked against the contexts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerificationMethod {
+    /// Skip verification
+    None,
+    /// Cosine similarity between sentence and context embeddings
+    Embedding,
+    /// Ask the LLM whether the contexts support each sentence
+    LlmJudge,
+}
+
+/// What happens to sentences the contexts don't support
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnsupportedClaimAction {
+    /// Report them in `unsupported_claims` only
+    Flag,
+    /// Also mark them in the answer text
+    Annotate,
+    /// Also remove them from the answer text
+    Strip,
+}
+
+/// A sentence of the answer its contexts don't support
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsupportedClaim {
+    /// The sentence
+    pub text: String,
+    
+    /// Support score (0.0 - 1.0); similarity, or 0.0 for a judge's rejection
+    pub support: f32,
+    
+    /// Context (1-based) that came closest to supporting it
+    pub closest_context: Option<usize>,
+}
+
+/// A sentence of the answer checked as a claim
+#[derive(Debug, Clone)]
+pub(crate) struct Claim {
+    /// Byte range in the answer
+    pub start: usize,
+    pub end: usize,
+    /// Contexts (0-based) the sentence cites
+    pub cited: Vec<usize>,
+}
+
+/// Outcome of a verification pass
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Verification {
+    /// Number of sentences checked
+    pub checked: usize,
+    /// Unsupported claims with their byte ranges in the answer
+    pub unsupported: Vec<(Claim, UnsupportedClaim)>,
+}
+
+impl Verification {
+    /// Share of checked claims that are supported
+    pub fn supported_ratio(&self) -> f32 {
+        if self.checked == 0 {
+            return 1.0;
+        }
+        1.0 - self.unsupported.len() as f32 / self.checked as f32
+    }
+}
+
+fn citation_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\[(\d+)\]").unwrap())
+}
+
+/// Byte ranges of the sentences in `text`
+///
+/// A sentence ends at `.`, `!` or `?` followed by whitespace, or at a line
+/// break. Ranges exclude surrounding whitespace.
+pub(crate) fn sentence_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    
+    while let Some((i, c)) = chars.next() {
+        let end = match c {
+            '\n' => Some(i),
+            '.' | '!' | '?' => match chars.peek() {
+                None => Some(i + 1),
+                Some((_, next)) if next.is_whitespace() => Some(i + 1),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(end) = end {
+            push_span(text, start, end, &mut spans);
+            start = end;
+        }
+    }
+    push_span(text, start, text.len(), &mut spans);
+    
+    spans
+}
+
+fn push_span(text: &str, start: usize, end: usize, spans: &mut Vec<(usize, usize)>) {
+    let slice = &text[start..end];
+    let trimmed = slice.trim();
+    if !trimmed.is_empty() {
+        let offset = start + (slice.len() - slice.trim_start().len());
+        spans.push((offset, offset + trimmed.len()));
+    }
+}
+
+/// The sentences of `answer` worth checking
+///
+/// Short sentences and reference list lines ("[1] Some Title") are skipped.
+pub(crate) fn extract_claims(answer: &str, context_count: usize) -> Vec<Claim> {
+    sentence_spans(answer)
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let sentence = &answer[start..end];
+            let stripped = citation_pattern().replace_all(sentence, "");
+            if sentence.starts_with('[') || stripped.split_whitespace().count() < MIN_CLAIM_WORDS {
+                return None;
+            }
+            
+            let mut cited: Vec<usize> = citation_pattern()
+                .captures_iter(sentence)
+                .filter_map(|cap| cap[1].parse::<usize>().ok())
+                .filter(|&n| n > 0 && n <= context_count)
+                .map(|n| n - 1)
+                .collect();
+            cited.dedup();
+            
+            Some(Claim { start, end, cited })
+        })
+        .collect()
+}
+
+/// Check claims by embedding similarity against the contexts
+pub(crate) async fn verify_with_embeddings(
+    embedder: &dyn Embedder,
+    answer: &str,
+    claims: Vec<Claim>,
+    contexts: &[SynthesisContext],
+    threshold: f32,
+) -> Result<Verification> {
+    if claims.is_empty() || contexts.is_empty() {
+        return Ok(Verification::default());
+    }
+    
+    let sentences: Vec<String> = claims
+        .iter()
+        .map(|c| citation_pattern().replace_all(&answer[c.start..c.end], "").trim().to_string())
+        .collect();
+    let context_texts: Vec<String> = contexts.iter().map(|c| c.content.clone()).collect();
+    let sentence_embeddings = embedder.embed_batch(&sentences).await?;
+    let context_embeddings = embedder.embed_batch(&context_texts).await?;
+    
+    let mut verification = Verification {
+        checked: claims.len(),
+        unsupported: Vec::new(),
+    };
+    for ((claim, sentence), embedding) in claims.into_iter().zip(sentences).zip(&sentence_embeddings) {
+        let candidates: Vec<usize> = if claim.cited.is_empty() {
+            (0..contexts.len()).collect()
+        } else {
+            claim.cited.clone()
+        };
+        let (closest, support) = candidates
+            .into_iter()
+            .map(|i| (i, cosine_similarity(embedding, &context_embeddings[i])))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0));
+        
+        if support < threshold {
+            verification.unsupported.push((
+                claim,
+                UnsupportedClaim {
+                    text: sentence,
+                    support: support.max(0.0),
+                    closest_context: Some(closest + 1),
+                },
+            ));
+        }
+    }
+    
+    Ok(verification)
+}
+
+#[derive(Debug, Deserialize)]
+struct JudgeVerdicts {
+    claims: Vec<JudgeVerdict>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JudgeVerdict {
+    index: usize,
+    supported: bool,
+}
+
+/// Check claims by asking the LLM whether the contexts support them
+pub(crate) async fn verify_with_llm(
+    llm: &LlmClient,
+    answer: &str,
+    claims: Vec<Claim>,
+    contexts: &[SynthesisContext],
+) -> Result<Verification> {
+    if claims.is_empty() || contexts.is_empty() {
+        return Ok(Verification::default());
+    }
+    
+    let mut prompt = String::from("Sources:\n");
+    for (i, ctx) in contexts.iter().enumerate() {
+        prompt.push_str(&format!("\n[{}] {}\n{}\n", i + 1, ctx.paper_title, ctx.content));
+    }
+    prompt.push_str("\nClaims:\n");
+    for (i, claim) in claims.iter().enumerate() {
+        prompt.push_str(&format!("{}. {}\n", i + 1, &answer[claim.start..claim.end]));
+    }
+    prompt.push_str(
+        "\nFor each claim, decide whether the sources support it. A claim citing [n] must be \
+        supported by source [n]. Reply with JSON of the form \
+        {\"claims\": [{\"index\": 1, \"supported\": true}]}.",
+    );
+    
+    let verdicts: JudgeVerdicts = llm.complete_json(&Completion {
+        system: JUDGE_SYSTEM_PROMPT,
+        prompt: &prompt,
+        max_tokens: 20 * claims.len() + 50,
+        temperature: 0.0,
+    }).await?;
+    
+    let mut verification = Verification {
+        checked: claims.len(),
+        unsupported: Vec::new(),
+    };
+    for (i, claim) in claims.into_iter().enumerate() {
+        // Claims the judge skipped count as supported
+        let rejected = verdicts.claims.iter().any(|v| v.index == i + 1 && !v.supported);
+        if rejected {
+            let text = answer[claim.start..claim.end].to_string();
+            let closest_context = claim.cited.first().map(|i| i + 1);
+            verification.unsupported.push((
+                claim,
+                UnsupportedClaim {
+                    text,
+                    support: 0.0,
+                    closest_context,
+                },
+            ));
+        }
+    }
+    
+    Ok(verification)
+}
+
+/// Rewrite the answer according to `action`
+pub(crate) fn apply_action(answer: &str, verification: &Verification, action: UnsupportedClaimAction) -> String {
+    if action == UnsupportedClaimAction::Flag || verification.unsupported.is_empty() {
+        return answer.to_string();
+    }
+    
+    let mut ranges: Vec<(usize, usize)> = verification
+        .unsupported
+        .iter()
+        .map(|(claim, _)| (claim.start, claim.end))
+        .collect();
+    ranges.sort_unstable();
+    
+    let mut result = String::with_capacity(answer.len());
+    let mut last = 0;
+    for (start, end) in ranges {
+        match action {
+            UnsupportedClaimAction::Annotate => {
+                result.push_str(&answer[last..end]);
+                result.push(' ');
+                result.push_str(UNSUPPORTED_MARKER);
+            }
+            _ => {
+                result.push_str(answer[last..start].trim_end_matches([' ', '\t']));
+            }
+        }
+        last = end;
+    }
+    result.push_str(&answer[last..]);
+    
+    if action == UnsupportedClaimAction::Strip {
+        return result
+            .lines()
+            .map(|line| line.trim())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string();
+    }
+    result
+}
+
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::HashEmbedder;
+    use uuid::Uuid;
+    
+    fn context(content: &str) -> SynthesisContext {
+        SynthesisContext {
+            paper_id: Uuid::new_v4(),
+            paper_title: "Paper".to_string(),
+            content: content.to_string(),
+            relevance_score: 0.9,
+        }
+    }
+    
+    #[test]
+    fn test_extract_claims() {
+        let answer = "Attention replaces recurrence entirely [2]. It works.\n\n[1] Attention Is All You Need";
+        let claims = extract_claims(answer, 2);
+        
+        assert_eq!(claims.len(), 1);
+        assert_eq!(&answer[claims[0].start..claims[0].end], "Attention replaces recurrence entirely [2].");
+        assert_eq!(claims[0].cited, vec![1]);
+    }
+    
+    #[tokio::test]
+    async fn test_embedding_verification() {
+        let contexts = vec![context("The transformer relies entirely on self-attention layers.")];
+        let answer = "The transformer relies entirely on self-attention layers [1]. \
+                      Bananas ripen quickly in warm tropical climates.";
+        let claims = extract_claims(answer, contexts.len());
+        
+        let verification = verify_with_embeddings(&HashEmbedder::new(256), answer, claims, &contexts, 0.5)
+            .await
+            .unwrap();
+        assert_eq!(verification.checked, 2);
+        assert_eq!(verification.unsupported.len(), 1);
+        assert!(verification.unsupported[0].1.text.starts_with("Bananas"));
+        assert_eq!(verification.supported_ratio(), 0.5);
+        
+        assert_eq!(
+            apply_action(answer, &verification, UnsupportedClaimAction::Strip),
+            "The transformer relies entirely on self-attention layers [1]."
+        );
+        assert!(apply_action(answer, &verification, UnsupportedClaimAction::Annotate)
+            .ends_with("climates. [unsupported]"));
+    }
+}
@@ -0,0 +1,316 @@
+//! Crossref metadata resolution
+//!
+//! Looks up DOIs in the Crossref REST API to backfill canonical paper
+//! metadata (title, authors, venue, publication date). Requests are
+//! throttled client-side so a burst of ingestion jobs stays within
+//! Crossref's rate limits.
+
+use crate::authors::normalize_orcid;
+use crate::config::CrossrefConfig;
+use crate::errors::{AppError, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use governor::{
+    clock::DefaultClock,
+    state::{InMemoryState, NotKeyed},
+    Quota, RateLimiter,
+};
+use regex_lite::Regex;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::debug;
+
+/// Canonical metadata for a DOI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkMetadata {
+    pub doi: String,
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    /// ORCID iD of each author, where Crossref has one
+    pub author_orcids: Vec<Option<String>>,
+    /// Journal or proceedings title
+    pub venue: Option<String>,
+    pub published: Option<NaiveDate>,
+}
+
+impl WorkMetadata {
+    /// Publication date as a UTC timestamp at midnight
+    pub fn published_at(&self) -> Option<DateTime<Utc>> {
+        self.published
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|at| at.and_utc())
+    }
+    
+    /// Merge into paper metadata, keeping keys the caller already set
+    pub fn merge_into(&self, metadata: &mut serde_json::Value) {
+        if !metadata.is_object() {
+            *metadata = serde_json::json!({});
+        }
+        let Some(map) = metadata.as_object_mut() else {
+            return;
+        };
+        
+        map.entry("doi").or_insert_with(|| self.doi.clone().into());
+        if !self.authors.is_empty() && !map.contains_key("authors") {
+            map.insert("authors".to_string(), self.authors.clone().into());
+            if self.author_orcids.iter().any(Option::is_some) {
+                map.insert("author_orcids".to_string(), self.author_orcids.clone().into());
+            }
+        }
+        if let Some(venue) = &self.venue {
+            map.entry("venue").or_insert_with(|| venue.clone().into());
+        }
+        map.insert("metadata_source".to_string(), "crossref".into());
+    }
+}
+
+/// Rate-limited Crossref API client
+pub struct CrossrefClient {
+    client: reqwest::Client,
+    limiter: RateLimiter<NotKeyed, InMemoryState, DefaultClock>,
+    api_url: reqwest::Url,
+}
+
+impl CrossrefClient {
+    /// Create a client from config
+    ///
+    /// When `mailto` is set it is sent in the User-Agent, which routes
+    /// requests to Crossref's "polite" pool.
+    pub fn new(config: &CrossrefConfig) -> Result<Self> {
+        let user_agent = match &config.mailto {
+            Some(mailto) => format!("paperforge/{} (mailto:{})", crate::VERSION, mailto),
+            None => format!("paperforge/{}", crate::VERSION),
+        };
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent(user_agent)
+            .build()?;
+        
+        let api_url = reqwest::Url::parse(&config.api_url)
+            .ok()
+            .filter(|url| !url.cannot_be_a_base())
+            .ok_or_else(|| AppError::Configuration {
+                message: format!("Invalid crossref.api_url: {}", config.api_url),
+            })?;
+        
+        let rps = NonZeroU32::new(config.requests_per_second).unwrap_or(NonZeroU32::MIN);
+        
+        Ok(Self {
+            client,
+            limiter: RateLimiter::direct(Quota::per_second(rps)),
+            api_url,
+        })
+    }
+    
+    /// Look up a DOI; `None` when Crossref doesn't know it
+    pub async fn resolve(&self, doi: &str) -> Result<Option<WorkMetadata>> {
+        self.limiter.until_ready().await;
+        
+        let response = self.client.get(work_url(&self.api_url, doi)).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            debug!(doi = %doi, "DOI not found in Crossref");
+            return Ok(None);
+        }
+        
+        let body: WorkResponse = response.error_for_status()?.json().await?;
+        Ok(Some(body.message.into_metadata(doi)))
+    }
+}
+
+/// URL of a DOI's work record
+///
+/// DOIs may contain `/`, `?`, `#` and `%`, so the DOI is percent-encoded as
+/// a single path segment rather than pasted into the path.
+fn work_url(api_url: &reqwest::Url, doi: &str) -> reqwest::Url {
+    let mut url = api_url.clone();
+    url.path_segments_mut()
+        .expect("API URL checked to be a base when the client was created")
+        .pop_if_empty()
+        .extend(["works", doi]);
+    url
+}
+
+#[derive(Deserialize)]
+struct WorkResponse {
+    message: Work,
+}
+
+#[derive(Deserialize)]
+struct Work {
+    #[serde(rename = "DOI")]
+    doi: Option<String>,
+    #[serde(default)]
+    title: Vec<String>,
+    #[serde(default)]
+    author: Vec<Author>,
+    #[serde(rename = "container-title", default)]
+    container_title: Vec<String>,
+    #[serde(rename = "published-print")]
+    published_print: Option<PartialDate>,
+    #[serde(rename = "published-online")]
+    published_online: Option<PartialDate>,
+    issued: Option<PartialDate>,
+}
+
+#[derive(Deserialize)]
+struct Author {
+    given: Option<String>,
+    family: Option<String>,
+    /// Organisational authors only have a name
+    name: Option<String>,
+    #[serde(rename = "ORCID")]
+    orcid: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PartialDate {
+    #[serde(rename = "date-parts", default)]
+    date_parts: Vec<Vec<Option<i32>>>,
+}
+
+impl PartialDate {
+    /// Missing month or day default to the first
+    fn to_date(&self) -> Option<NaiveDate> {
+        let parts = self.date_parts.first()?;
+        let year = (*parts.first()?)?;
+        let month = parts.get(1).copied().flatten().unwrap_or(1);
+        let day = parts.get(2).copied().flatten().unwrap_or(1);
+        NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+    }
+}
+
+impl Work {
+    fn into_metadata(self, requested: &str) -> WorkMetadata {
+        let published = [&self.published_print, &self.published_online, &self.issued]
+            .into_iter()
+            .flatten()
+            .find_map(PartialDate::to_date);
+        
+        let (authors, author_orcids) = self
+            .author
+            .into_iter()
+            .filter_map(|a| {
+                let name = match (a.given, a.family, a.name) {
+                    (Some(given), Some(family), _) => format!("{} {}", given, family),
+                    (None, Some(family), _) => family,
+                    (_, None, name) => name?,
+                };
+                Some((name, a.orcid.as_deref().and_then(normalize_orcid)))
+            })
+            .unzip();
+        
+        WorkMetadata {
+            doi: self.doi.map(|doi| doi.to_lowercase()).unwrap_or_else(|| requested.to_string()),
+            title: self
+                .title
+                .into_iter()
+                .next()
+                .map(|t| t.split_whitespace().collect::<Vec<_>>().join(" ")),
+            authors,
+            author_orcids,
+            venue: self.container_title.into_iter().next(),
+            published,
+        }
+    }
+}
+
+fn doi_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r#"10\.\d{4,9}/[^\s"<>]+"#).unwrap())
+}
+
+/// Normalize a DOI given as a bare DOI, `doi:` prefix, or doi.org URL
+///
+/// Returns `None` when the input isn't a DOI.
+pub fn normalize_doi(input: &str) -> Option<String> {
+    let found = doi_pattern().find(input.trim())?;
+    let doi = found.as_str().trim_end_matches(['.', ',', ';', ':', ')', ']']);
+    Some(doi.to_lowercase())
+}
+
+/// Find the first DOI mentioned in extracted text
+///
+/// Only the start of the text is scanned; a paper's own DOI appears on its
+/// first page, while later DOIs usually belong to references.
+pub fn find_doi(text: &str) -> Option<String> {
+    const SCAN_CHARS: usize = 5000;
+    let end = text
+        .char_indices()
+        .nth(SCAN_CHARS)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+    normalize_doi(&text[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_doi_detection() {
+        assert_eq!(
+            normalize_doi("https://doi.org/10.1145/3292500.3330701").as_deref(),
+            Some("10.1145/3292500.3330701")
+        );
+        assert_eq!(
+            normalize_doi("doi:10.48550/arXiv.1706.03762.").as_deref(),
+            Some("10.48550/arxiv.1706.03762")
+        );
+        assert_eq!(normalize_doi("arXiv:1706.03762"), None);
+        
+        let text = "Attention Is All You Need\nPublished as (DOI 10.5555/3295222.3295349), NeurIPS";
+        assert_eq!(find_doi(text).as_deref(), Some("10.5555/3295222.3295349"));
+    }
+    
+    #[test]
+    fn test_work_url_encodes_doi() {
+        let api_url = reqwest::Url::parse("https://api.crossref.org/").unwrap();
+        assert_eq!(
+            work_url(&api_url, "10.1000/a#b?c%d").as_str(),
+            "https://api.crossref.org/works/10.1000%2Fa%23b%3Fc%25d"
+        );
+        assert_eq!(
+            work_url(&api_url, "10.1002/(SICI)1097-4636<413::AID-JBM3>3.0.CO;2-N").as_str(),
+            "https://api.crossref.org/works/10.1002%2F(SICI)1097-4636%3C413::AID-JBM3%3E3.0.CO;2-N"
+        );
+        
+        let proxied = reqwest::Url::parse("http://localhost:8080/crossref").unwrap();
+        assert_eq!(
+            work_url(&proxied, "10.1038/nature14539").as_str(),
+            "http://localhost:8080/crossref/works/10.1038%2Fnature14539"
+        );
+    }
+    
+    #[test]
+    fn test_work_metadata() {
+        let body = r#"{"message": {
+            "DOI": "10.1038/NATURE14539",
+            "title": ["Deep   learning"],
+            "author": [
+                {"given": "Yann", "family": "LeCun", "ORCID": "http://orcid.org/0000-0002-1825-0097"},
+                {"family": "Bengio"},
+                {"name": "Deep Learning Consortium"}
+            ],
+            "container-title": ["Nature"],
+            "issued": {"date-parts": [[2015, 5]]}
+        }}"#;
+        let work: WorkResponse = serde_json::from_str(body).unwrap();
+        let metadata = work.message.into_metadata("10.1038/nature14539");
+        
+        assert_eq!(metadata.doi, "10.1038/nature14539");
+        assert_eq!(metadata.title.as_deref(), Some("Deep learning"));
+        assert_eq!(metadata.authors, vec!["Yann LeCun", "Bengio", "Deep Learning Consortium"]);
+        assert_eq!(metadata.author_orcids[0].as_deref(), Some("0000-0002-1825-0097"));
+        assert_eq!(metadata.author_orcids[1], None);
+        assert_eq!(metadata.venue.as_deref(), Some("Nature"));
+        assert_eq!(metadata.published, NaiveDate::from_ymd_opt(2015, 5, 1));
+        
+        let mut paper = serde_json::json!({"venue": "Nature (preprint)"});
+        metadata.merge_into(&mut paper);
+        assert_eq!(paper["venue"], "Nature (preprint)");
+        assert_eq!(paper["authors"][0], "Yann LeCun");
+        assert_eq!(paper["author_orcids"][0], "0000-0002-1825-0097");
+        assert_eq!(paper["doi"], "10.1038/nature14539");
+    }
+}
@@ -0,0 +1,345 @@
+//! In-memory repository fake
+//!
+//! [`MemoryRepository`] implements the narrow repository traits over plain
+//! collections, for unit tests of code that would otherwise need Postgres.
+//! Enabled by the `test-util` feature.
+
+use super::models::{IngestionJob, JobCheckpoint, JobStatus, Session};
+use super::stores::{EmbeddingRepository, JobRepository, PageRankRepository, SessionRepository};
+use super::{NewChunk, TenantCitations};
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+/// A paper as far as the citation graph is concerned
+#[derive(Debug, Clone)]
+struct MemoryPaper {
+    tenant_id: Uuid,
+    title: String,
+    pagerank: Option<f64>,
+    pagerank_updated_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Default)]
+struct State {
+    jobs: HashMap<Uuid, IngestionJob>,
+    checkpoints: Vec<JobCheckpoint>,
+    /// Stored chunks by paper, in index order
+    chunks: HashMap<Uuid, Vec<NewChunk>>,
+    sessions: HashMap<Uuid, Session>,
+    /// Papers in insertion order
+    papers: Vec<(Uuid, MemoryPaper)>,
+    /// `(citing, cited, created_at)`
+    citations: Vec<(Uuid, Uuid, DateTimeWithTimeZone)>,
+}
+
+/// Repository backed by in-memory collections
+#[derive(Default)]
+pub struct MemoryRepository {
+    state: Mutex<State>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panicking test must not take the others down with it
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn insert_job(&self, job: IngestionJob) {
+        self.state().jobs.insert(job.id, job);
+    }
+
+    /// Add a checkpoint, replacing the job's previous one for the same stage
+    pub fn insert_checkpoint(&self, checkpoint: JobCheckpoint) {
+        let mut guard = self.state();
+        guard
+            .checkpoints
+            .retain(|c| !(c.job_id == checkpoint.job_id && c.stage == checkpoint.stage));
+        guard.checkpoints.push(checkpoint);
+    }
+
+    pub fn insert_session(&self, session: Session) {
+        self.state().sessions.insert(session.id, session);
+    }
+
+    /// Add a processed paper to `tenant_id`'s citation graph
+    pub fn insert_paper(&self, tenant_id: Uuid, paper_id: Uuid, title: &str) {
+        self.state().papers.push((
+            paper_id,
+            MemoryPaper {
+                tenant_id,
+                title: title.to_string(),
+                pagerank: None,
+                pagerank_updated_at: None,
+            },
+        ));
+    }
+
+    /// Record that `citing` cites `cited`, as of now
+    pub fn insert_citation(&self, citing: Uuid, cited: Uuid) {
+        self.state().citations.push((citing, cited, Utc::now().fixed_offset()));
+    }
+
+    /// A paper's persisted PageRank
+    pub fn pagerank(&self, paper_id: Uuid) -> Option<f64> {
+        self.state()
+            .papers
+            .iter()
+            .find(|(id, _)| *id == paper_id)
+            .and_then(|(_, paper)| paper.pagerank)
+    }
+
+    /// A paper's stored chunks, in index order
+    pub fn chunks(&self, paper_id: Uuid) -> Vec<NewChunk> {
+        self.state().chunks.get(&paper_id).cloned().unwrap_or_default()
+    }
+
+    pub fn session_count(&self) -> usize {
+        self.state().sessions.len()
+    }
+}
+
+#[async_trait]
+impl JobRepository for MemoryRepository {
+    async fn find_job_by_id(&self, id: Uuid) -> Result<Option<IngestionJob>> {
+        Ok(self.state().jobs.get(&id).cloned())
+    }
+
+    async fn find_checkpoints(&self, job_id: Uuid) -> Result<Vec<JobCheckpoint>> {
+        let mut checkpoints: Vec<JobCheckpoint> = self
+            .state()
+            .checkpoints
+            .iter()
+            .filter(|c| c.job_id == job_id)
+            .cloned()
+            .collect();
+        checkpoints.sort_by_key(|c| c.created_at);
+        Ok(checkpoints)
+    }
+}
+
+#[async_trait]
+impl EmbeddingRepository for MemoryRepository {
+    async fn stored_chunk_indexes(&self, paper_id: Uuid) -> Result<HashSet<i32>> {
+        Ok(self.chunks(paper_id).iter().map(|c| c.index).collect())
+    }
+
+    async fn upsert_chunks(&self, paper_id: Uuid, chunks: Vec<NewChunk>, _embedding_version: i32) -> Result<Vec<Uuid>> {
+        let mut guard = self.state();
+        let stored = guard.chunks.entry(paper_id).or_default();
+        let ids = chunks.iter().map(|_| Uuid::new_v4()).collect();
+        for chunk in chunks {
+            stored.retain(|c| c.index != chunk.index);
+            stored.push(chunk);
+        }
+        stored.sort_by_key(|c| c.index);
+        Ok(ids)
+    }
+
+    async fn update_job_progress(&self, job_id: Uuid, chunks_processed: i32) -> Result<()> {
+        if let Some(job) = self.state().jobs.get_mut(&job_id) {
+            job.chunks_processed = chunks_processed;
+        }
+        Ok(())
+    }
+
+    async fn set_failed_chunks(&self, job_id: Uuid, chunk_indexes: &[i32]) -> Result<()> {
+        if let Some(job) = self.state().jobs.get_mut(&job_id) {
+            job.failed_chunks = serde_json::json!(chunk_indexes);
+        }
+        Ok(())
+    }
+
+    async fn update_job_status(
+        &self,
+        job_id: Uuid,
+        status: JobStatus,
+        paper_id: Option<Uuid>,
+        chunks_total: Option<i32>,
+        error_message: Option<String>,
+    ) -> Result<IngestionJob> {
+        let mut guard = self.state();
+        let job = guard
+            .jobs
+            .get_mut(&job_id)
+            .ok_or_else(|| AppError::JobNotFound { id: job_id.to_string() })?;
+
+        let terminal = matches!(status, JobStatus::Completed | JobStatus::Failed | JobStatus::Dead);
+        if !terminal {
+            job.stage = String::from(status.clone());
+        }
+        job.status = String::from(status);
+        job.paper_id = paper_id.or(job.paper_id);
+        job.chunks_total = chunks_total.unwrap_or(job.chunks_total);
+        job.error_message = error_message.or(job.error_message.take());
+        if terminal {
+            job.completed_at = Some(Utc::now().fixed_offset());
+        }
+        Ok(job.clone())
+    }
+
+    async fn delete_checkpoints(&self, job_id: Uuid) -> Result<u64> {
+        let mut guard = self.state();
+        let before = guard.checkpoints.len();
+        guard.checkpoints.retain(|c| c.job_id != job_id);
+        Ok((before - guard.checkpoints.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl SessionRepository for MemoryRepository {
+    async fn find_session(&self, session_id: Uuid) -> Result<Option<Session>> {
+        Ok(self.state().sessions.get(&session_id).cloned())
+    }
+
+    async fn upsert_session(
+        &self,
+        tenant_id: Uuid,
+        session_id: Uuid,
+        state: serde_json::Value,
+        ttl_minutes: i64,
+    ) -> Result<Session> {
+        let now = Utc::now().fixed_offset();
+        let expires_at = now + chrono::Duration::minutes(ttl_minutes);
+        let mut guard = self.state();
+
+        let session = match guard.sessions.get(&session_id) {
+            Some(existing) if existing.tenant_id != tenant_id => return Err(AppError::TenantMismatch),
+            Some(existing) => Session {
+                state,
+                last_active_at: now,
+                expires_at,
+                ..existing.clone()
+            },
+            None => Session {
+                id: session_id,
+                tenant_id,
+                state,
+                created_at: now,
+                last_active_at: now,
+                expires_at,
+            },
+        };
+        guard.sessions.insert(session_id, session.clone());
+        Ok(session)
+    }
+
+    async fn delete_expired_sessions(&self, _batch_size: u64) -> Result<u64> {
+        let mut guard = self.state();
+        let before = guard.sessions.len();
+        guard.sessions.retain(|_, session| !session.is_expired());
+        Ok((before - guard.sessions.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl PageRankRepository for MemoryRepository {
+    async fn pagerank_pending_papers(&self) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        let guard = self.state();
+        let mut pending: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (id, paper) in &guard.papers {
+            let stale = guard.citations.iter().any(|(citing, _, created_at)| {
+                citing == id && paper.pagerank_updated_at.map_or(true, |updated| *created_at > updated)
+            });
+            if stale {
+                pending.entry(paper.tenant_id).or_default().push(*id);
+            }
+        }
+        Ok(pending)
+    }
+
+    async fn tenants_with_papers(&self) -> Result<Vec<Uuid>> {
+        let mut tenants: Vec<Uuid> = self.state().papers.iter().map(|(_, p)| p.tenant_id).collect();
+        tenants.sort();
+        tenants.dedup();
+        Ok(tenants)
+    }
+
+    async fn citation_graph(&self, tenant_id: Uuid) -> Result<TenantCitations> {
+        let guard = self.state();
+        let papers: Vec<(Uuid, String)> = guard
+            .papers
+            .iter()
+            .filter(|(_, p)| p.tenant_id == tenant_id)
+            .map(|(id, p)| (*id, p.title.clone()))
+            .collect();
+        let edges = guard
+            .citations
+            .iter()
+            .filter(|(citing, cited, _)| {
+                papers.iter().any(|(id, _)| id == citing) && guard.papers.iter().any(|(id, _)| id == cited)
+            })
+            .map(|(citing, cited, _)| (*citing, *cited))
+            .collect();
+        Ok(TenantCitations { papers, edges })
+    }
+
+    async fn paper_pageranks(&self, tenant_id: Uuid) -> Result<HashMap<Uuid, f64>> {
+        Ok(self
+            .state()
+            .papers
+            .iter()
+            .filter(|(_, p)| p.tenant_id == tenant_id)
+            .filter_map(|(id, p)| p.pagerank.map(|score| (*id, score)))
+            .collect())
+    }
+
+    async fn save_pageranks(&self, scores: &[(Uuid, f64)], computed_at: DateTimeWithTimeZone) -> Result<()> {
+        let mut guard = self.state();
+        for &(paper_id, score) in scores {
+            if let Some((_, paper)) = guard.papers.iter_mut().find(|(id, _)| *id == paper_id) {
+                paper.pagerank = Some(score);
+                paper.pagerank_updated_at = Some(computed_at);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sessions_stay_with_their_tenant() {
+        let repo = MemoryRepository::new();
+        let (tenant_id, session_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let created = repo.upsert_session(tenant_id, session_id, serde_json::json!({"n": 1}), 30).await.unwrap();
+        let updated = repo.upsert_session(tenant_id, session_id, serde_json::json!({"n": 2}), 30).await.unwrap();
+        assert_eq!(updated.created_at, created.created_at);
+        assert_eq!(updated.state["n"], 2);
+
+        let taken = repo.upsert_session(Uuid::new_v4(), session_id, serde_json::json!({}), 30).await;
+        assert!(matches!(taken, Err(AppError::TenantMismatch)));
+
+        repo.upsert_session(tenant_id, Uuid::new_v4(), serde_json::json!({}), -1).await.unwrap();
+        assert_eq!(repo.delete_expired_sessions(100).await.unwrap(), 1);
+        assert_eq!(repo.session_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pagerank_pending_until_saved() {
+        let repo = MemoryRepository::new();
+        let tenant_id = Uuid::new_v4();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        repo.insert_paper(tenant_id, a, "A");
+        repo.insert_paper(tenant_id, b, "B");
+        repo.insert_citation(a, b);
+
+        assert_eq!(repo.pagerank_pending_papers().await.unwrap()[&tenant_id], vec![a]);
+        assert_eq!(repo.citation_graph(tenant_id).await.unwrap().edges, vec![(a, b)]);
+
+        repo.save_pageranks(&[(a, 0.4), (b, 0.6)], Utc::now().fixed_offset()).await.unwrap();
+        assert!(repo.pagerank_pending_papers().await.unwrap().is_empty());
+        assert_eq!(repo.pagerank(b), Some(0.6));
+    }
+}
@@ -3,19 +3,35 @@
 //! Provides:
 //! - SeaORM entity models
 //! - Repository pattern for data access
-//! - Connection pool management
+//! - Connection pool management with read routing
 //! - Query helpers
 
+#[cfg(any(test, feature = "test-util"))]
+pub mod memory;
 pub mod models;
+mod query_log;
 mod repository;
+mod routing;
+mod stores;
 
-pub use repository::{ChunkResult, Repository};
+pub use repository::{
+    AuditLogFilter, ChunkResult, CitationEdge, CitationRelation, CollectionSummary, ExportChunk, IndexChunk,
+    NewChunk, NewSavedSearch, PaperUpdate, QueryTotals, RelatedPaper, Repository, SearchScope, SimilarPaper,
+    SimilarityBasis, TenantCitations, TopQuery, UnitOfWork, UsageTotals, TRANSACTION_MAX_ATTEMPTS,
+};
+pub use stores::{EmbeddingRepository, JobRepository, PageRankRepository, SessionRepository};
+pub use routing::{consistent, PrimaryReason, ReadRoute, ReplicaStatus};
 
-use crate::config::DatabaseConfig;
+use crate::config::{DatabaseConfig, VectorStorage};
 use crate::errors::{AppError, Result};
-use sea_orm::{ConnectOptions, Database, DatabaseConnection};
-use std::time::Duration;
-use tracing::info;
+use crate::metrics;
+use query_log::QueryLimits;
+use routing::{Replica, ReplicaSet};
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, QueryResult, Statement};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use tracing::{info, warn};
 
 /// Database connection pool wrapper
 #[derive(Clone)]
@@ -23,8 +39,21 @@ pub struct DbPool {
     /// Primary connection (for writes)
     pub primary: DatabaseConnection,
     
-    /// Read replica connection (optional)
-    pub replica: Option<DatabaseConnection>,
+    /// Read replicas with their health, lag and latency, consulted when
+    /// routing reads
+    replicas: Arc<ReplicaSet>,
+    
+    /// Statement timeout and slow-query threshold for search queries
+    query_limits: QueryLimits,
+    
+    /// Precision vector search scans at
+    vector_storage: VectorStorage,
+    
+    /// Dimension of the `chunks.embedding` column
+    embedding_dimension: usize,
+    
+    /// Dimension of `chunks.embedding` in the database schema, read once
+    column_dimension: Arc<OnceCell<Option<usize>>>,
 }
 
 impl DbPool {
@@ -46,48 +75,190 @@ impl DbPool {
                 message: format!("Failed to connect to primary: {}", e) 
             })?;
         
-        // Connect to replica if configured
-        let replica = if let Some(ref read_url) = config.read_url {
-            info!("Connecting to read replica...");
+        // Replicas connect lazily, so one that is unreachable (say, in a
+        // region that is down) is marked down by its health check instead
+        // of failing startup
+        let mut replicas = Vec::new();
+        for replica in config.read_replicas() {
+            let name = replica.name.unwrap_or_default();
+            info!(replica = %name, weight = replica.weight, "Connecting to read replica...");
             
-            let mut replica_opts = ConnectOptions::new(read_url);
+            let mut replica_opts = ConnectOptions::new(&replica.url);
             replica_opts
                 .max_connections(config.max_connections)
                 .min_connections(config.min_connections)
                 .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
                 .idle_timeout(Duration::from_secs(config.idle_timeout_secs))
+                .connect_lazy(true)
                 .sqlx_logging(true);
             
-            let replica_conn = Database::connect(replica_opts)
+            let connection = Database::connect(replica_opts)
                 .await
                 .map_err(|e| AppError::DatabaseConnection { 
-                    message: format!("Failed to connect to replica: {}", e) 
+                    message: format!("Failed to connect to replica {}: {}", name, e) 
                 })?;
+            let status = Arc::new(ReplicaStatus::new(&name, replica.weight, config.max_replica_lag_bytes));
             
-            Some(replica_conn)
-        } else {
-            None
-        };
+            if config.replica_check_interval_secs > 0 {
+                tokio::spawn(routing::monitor(
+                    primary.clone(),
+                    connection.clone(),
+                    status.clone(),
+                    Duration::from_secs(config.replica_check_interval_secs),
+                ));
+            }
+            replicas.push(Replica { connection, status });
+        }
         
         info!("Database connections established");
         
-        Ok(Self { primary, replica })
+        Ok(Self {
+            primary,
+            replicas: Arc::new(ReplicaSet::new(
+                replicas,
+                Duration::from_millis(config.replica_latency_window_ms),
+            )),
+            query_limits: QueryLimits::new(config),
+            vector_storage: VectorStorage::default(),
+            embedding_dimension: crate::DEFAULT_EMBEDDING_DIMENSION,
+            column_dimension: Arc::new(OnceCell::new()),
+        })
+    }
+    
+    /// Search embeddings at `storage` precision, see
+    /// [`EmbeddingConfig::vector_storage`](crate::config::EmbeddingConfig::vector_storage)
+    pub fn with_vector_storage(mut self, storage: VectorStorage) -> Self {
+        self.vector_storage = storage;
+        self
+    }
+    
+    /// Precision vector search scans at
+    pub fn vector_storage(&self) -> VectorStorage {
+        self.vector_storage
+    }
+    
+    /// Embeddings stored in `chunks.embedding` have `dimension` values, see
+    /// [`EmbeddingConfig::dimension`](crate::config::EmbeddingConfig::dimension)
+    pub fn with_embedding_dimension(mut self, dimension: usize) -> Self {
+        self.embedding_dimension = dimension;
+        self
     }
     
-    /// Get the connection for reads (replica if available, otherwise primary)
+    /// Dimension of the `chunks.embedding` column
+    pub fn embedding_dimension(&self) -> usize {
+        self.embedding_dimension
+    }
+    
+    /// Dimension declared for `chunks.embedding` in the database
+    ///
+    /// `None` when the column is an unconstrained `vector`. Read from the
+    /// schema on first use and cached.
+    pub async fn column_dimension(&self) -> Result<Option<usize>> {
+        self.column_dimension
+            .get_or_try_init(|| async {
+                // pgvector keeps the dimension in the column's type modifier,
+                // which information_schema doesn't expose
+                let stmt = Statement::from_string(
+                    DbBackend::Postgres,
+                    r#"
+                    SELECT a.atttypmod AS dimension
+                    FROM information_schema.columns c
+                    JOIN pg_attribute a
+                        ON a.attrelid = format('%I.%I', c.table_schema, c.table_name)::regclass
+                        AND a.attname = c.column_name
+                    WHERE c.table_schema = current_schema()
+                        AND c.table_name = 'chunks'
+                        AND c.column_name = 'embedding'
+                        AND c.udt_name = 'vector'
+                    "#,
+                );
+                let row = self.primary.query_one(stmt).await?.ok_or_else(|| AppError::Configuration {
+                    message: "chunks.embedding vector column not found; have the migrations been applied?".to_string(),
+                })?;
+                let dimension: i32 = row.try_get("", "dimension")?;
+                Ok(usize::try_from(dimension).ok().filter(|d| *d > 0))
+            })
+            .await
+            .copied()
+    }
+    
+    /// Fail fast when `model` produces vectors the `chunks.embedding` column
+    /// can't store
+    ///
+    /// Services that embed call this at startup, so a misconfigured model is
+    /// reported before the first write rather than as a Postgres error.
+    pub async fn verify_embedding_dimension(&self, model: &str, dimension: usize) -> Result<()> {
+        match self.column_dimension().await? {
+            Some(column) if column != dimension => Err(dimension_mismatch(model, dimension, column)),
+            _ => Ok(()),
+        }
+    }
+    
+    /// Get the connection for reads
+    ///
+    /// A replica when one can serve, unless this unit of work has written;
+    /// see [`ReadRoute`] and the routing rules in `routing`.
     pub fn read(&self) -> &DatabaseConnection {
-        self.replica.as_ref().unwrap_or(&self.primary)
+        let route = self.replicas.route();
+        metrics::record_db_read(route.as_str(), route.reason());
+        
+        match route {
+            ReadRoute::Replica(index) => &self.replicas.replicas[index].connection,
+            ReadRoute::Primary(_) => &self.primary,
+        }
     }
     
     /// Get the connection for writes (always primary)
+    ///
+    /// Inside [`consistent`], later reads in the same unit of work go to the
+    /// primary as well.
     pub fn write(&self) -> &DatabaseConnection {
+        routing::mark_write();
         &self.primary
     }
     
+    /// Last known health, replication lag and latency of each replica
+    pub fn replica_statuses(&self) -> impl Iterator<Item = &ReplicaStatus> {
+        self.replicas.replicas.iter().map(|replica| replica.status.as_ref())
+    }
+    
+    /// Run a search query on the read connection
+    ///
+    /// The query is cancelled after `search_statement_timeout_ms`, or at
+    /// the request deadline if that comes first ([`AppError::QueryTimeout`]),
+    /// and its duration is recorded under
+    /// `label`; slow ones are logged.
+    pub async fn search_query(&self, label: &'static str, stmt: Statement) -> Result<Vec<QueryResult>> {
+        self.query_limits.query_all(self.read(), label, stmt).await
+    }
+    
+    /// Record connection pool usage and acquire latency every `interval`
+    ///
+    /// Each service starts this once after connecting; a zero interval
+    /// disables it.
+    pub fn spawn_metrics_collector(&self, interval: Duration) {
+        if interval.is_zero() {
+            return;
+        }
+        
+        let mut pools = vec![("primary".to_string(), self.primary.clone())];
+        for replica in &self.replicas.replicas {
+            pools.push((replica.status.name().to_string(), replica.connection.clone()));
+        }
+        
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                for (name, conn) in &pools {
+                    collect_pool_metrics(name, conn).await;
+                }
+            }
+        });
+    }
+    
     /// Ping the database to check connectivity
     pub async fn ping(&self) -> Result<()> {
-        use sea_orm::ConnectionTrait;
-        
         self.primary
             .execute_unprepared("SELECT 1")
             .await
@@ -95,15 +266,43 @@ impl DbPool {
                 message: format!("Primary ping failed: {}", e),
             })?;
         
-        if let Some(ref replica) = self.replica {
-            replica
-                .execute_unprepared("SELECT 1")
-                .await
-                .map_err(|e| AppError::DatabaseConnection {
-                    message: format!("Replica ping failed: {}", e),
-                })?;
+        // Reads fall back to the primary, so a down replica isn't an error
+        for replica in &self.replicas.replicas {
+            if let Err(e) = replica.connection.execute_unprepared("SELECT 1").await {
+                warn!(replica = %replica.status.name(), error = %e, "Replica ping failed");
+                replica.status.mark_down();
+            }
         }
         
         Ok(())
     }
 }
+
+/// Error for `model` producing `dimension`-dimensional vectors when the
+/// column holds `column`
+pub(crate) fn dimension_mismatch(model: &str, dimension: usize, column: usize) -> AppError {
+    AppError::Configuration {
+        message: format!(
+            "Embedding model {} produces {}-dimensional vectors but chunks.embedding is vector({}); \
+             configure a matching model or dimensions, or migrate the column",
+            model, dimension, column
+        ),
+    }
+}
+
+/// Record a pool's in-use and idle connections, then time acquiring one
+async fn collect_pool_metrics(name: &str, conn: &DatabaseConnection) {
+    let pool = conn.get_postgres_connection_pool();
+    let idle = pool.num_idle() as u32;
+    metrics::record_db_pool(name, pool.size().saturating_sub(idle), idle);
+    
+    let start = Instant::now();
+    match pool.acquire().await {
+        Ok(_conn) => metrics::record_db_acquire(name, start.elapsed().as_secs_f64(), false),
+        Err(sqlx::Error::PoolTimedOut) => {
+            warn!(pool = name, "Timed out acquiring a database connection");
+            metrics::record_db_acquire(name, start.elapsed().as_secs_f64(), true);
+        }
+        Err(e) => warn!(pool = name, error = %e, "Failed to acquire a database connection"),
+    }
+}
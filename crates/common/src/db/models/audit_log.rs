@@ -0,0 +1,46 @@
+//! Audit log entity for mutating operations
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "audit_logs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// Tenant the operation applied to (`None` for system-wide operations)
+    pub tenant_id: Option<Uuid>,
+    
+    /// Who performed the operation, e.g. `user:<id>` or `api_key:<prefix>`
+    #[sea_orm(column_type = "Text")]
+    pub actor: String,
+    
+    /// Dotted action name, e.g. `paper.delete`
+    #[sea_orm(column_type = "Text")]
+    pub action: String,
+    
+    #[sea_orm(column_type = "Text")]
+    pub resource_type: String,
+    
+    #[sea_orm(column_type = "Text", nullable)]
+    pub resource_id: Option<String>,
+    
+    #[sea_orm(column_type = "Text", nullable)]
+    pub request_id: Option<String>,
+    
+    /// Resource state before the operation
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub before: Option<serde_json::Value>,
+    
+    /// Resource state after the operation
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub after: Option<serde_json::Value>,
+    
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
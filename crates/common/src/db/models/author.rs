@@ -0,0 +1,54 @@
+//! Author entity
+//!
+//! One per ORCID iD within a tenant; authors without an ORCID are one per
+//! normalized name. Papers are linked through `paper_authors`.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "authors")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    pub tenant_id: Uuid,
+    
+    /// Display name as first seen
+    pub name: String,
+    
+    /// Lowercased name without punctuation
+    pub normalized_name: String,
+    
+    #[sea_orm(nullable)]
+    pub orcid: Option<String>,
+    
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+    
+    #[sea_orm(has_many = "super::paper_author::Entity")]
+    Papers,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl Related<super::paper_author::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Papers.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
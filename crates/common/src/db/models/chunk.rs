@@ -1,8 +1,102 @@
 //! Chunk entity with embedding versioning
 
+use crate::proto::search::ChunkProvenance as ProtoProvenance;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Kind of content a chunk holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkType {
+    /// Body text
+    #[default]
+    Text,
+    /// Table extracted from a PDF, cells separated by ` | `
+    Table,
+    /// Figure caption extracted from a PDF
+    FigureCaption,
+    /// Display equation extracted from a PDF
+    Equation,
+}
+
+impl From<String> for ChunkType {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "table" => ChunkType::Table,
+            "figure_caption" => ChunkType::FigureCaption,
+            "equation" => ChunkType::Equation,
+            _ => ChunkType::Text,
+        }
+    }
+}
+
+impl From<ChunkType> for String {
+    fn from(chunk_type: ChunkType) -> Self {
+        match chunk_type {
+            ChunkType::Text => "text".to_string(),
+            ChunkType::Table => "table".to_string(),
+            ChunkType::FigureCaption => "figure_caption".to_string(),
+            ChunkType::Equation => "equation".to_string(),
+        }
+    }
+}
+
+/// Where a chunk is in its source PDF, kept in the chunk's `metadata`
+///
+/// Character offsets count Unicode scalar values in the page's extracted
+/// text, whitespace collapsed, so a viewer can find the passage on the page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// 1-based page the chunk starts on
+    pub page: u32,
+    /// Page the chunk ends on, when it runs past `page`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_end: Option<u32>,
+    /// Offset of the chunk's start in `page`'s text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub char_start: Option<u32>,
+    /// Offset of the chunk's end in the text of `page_end` (or `page`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub char_end: Option<u32>,
+    /// Approximate bounding box `[x0, y0, x1, y1]` in PDF points, for
+    /// table and caption chunks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<[f32; 4]>,
+}
+
+impl Provenance {
+    /// Provenance in chunk metadata; `None` for chunks not from a PDF, or
+    /// from one ingested before pages were recorded
+    pub fn from_metadata(metadata: &serde_json::Value) -> Option<Self> {
+        metadata.get("page")?;
+        serde_json::from_value(metadata.clone()).ok()
+    }
+}
+
+impl From<Provenance> for ProtoProvenance {
+    fn from(p: Provenance) -> Self {
+        Self {
+            page: p.page,
+            page_end: p.page_end,
+            char_start: p.char_start,
+            char_end: p.char_end,
+            bbox: p.bbox.map(Vec::from).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<ProtoProvenance> for Provenance {
+    fn from(p: ProtoProvenance) -> Self {
+        Self {
+            page: p.page,
+            page_end: p.page_end,
+            char_start: p.char_start,
+            char_end: p.char_end,
+            bbox: p.bbox.try_into().ok(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "chunks")]
 pub struct Model {
@@ -37,6 +131,14 @@ pub struct Model {
     /// Character offset end in source document
     pub char_offset_end: Option<i32>,
     
+    /// Chunk kind (see [`ChunkType`])
+    #[sea_orm(column_type = "Text")]
+    pub chunk_type: String,
+    
+    /// Source location in the PDF (see [`Provenance`])
+    #[sea_orm(column_type = "JsonBinary")]
+    pub metadata: Json,
+    
     pub created_at: DateTimeWithTimeZone,
 }
 
@@ -60,15 +162,35 @@ impl Related<super::paper::Entity> for Entity {
 impl ActiveModelBehavior for ActiveModel {}
 
 impl Model {
+    /// Get chunk type as enum
+    pub fn chunk_type(&self) -> ChunkType {
+        ChunkType::from(self.chunk_type.clone())
+    }
+    
+    /// Where the chunk is in its source PDF
+    pub fn provenance(&self) -> Option<Provenance> {
+        Provenance::from_metadata(&self.metadata)
+    }
+    
+    /// Byte range of the chunk in the text it was cut from, when recorded
+    /// and consistent with the content
+    pub fn char_range(&self) -> Option<std::ops::Range<usize>> {
+        let start = usize::try_from(self.char_offset_start?).ok()?;
+        let end = usize::try_from(self.char_offset_end?).ok()?;
+        (end >= start && end - start == self.content.len()).then_some(start..end)
+    }
+    
     /// Parse embedding from stored text format to Vec<f32>
     pub fn parse_embedding(&self) -> Option<Vec<f32>> {
-        self.embedding.as_ref().and_then(|s| {
-            // Format: "[1.0,2.0,3.0,...]"
-            let inner = s.trim_start_matches('[').trim_end_matches(']');
-            inner
-                .split(',')
-                .map(|v| v.trim().parse::<f32>().ok())
-                .collect()
-        })
+        self.embedding.as_deref().and_then(parse_vector)
     }
 }
+
+/// Parse pgvector's text format, `"[1.0,2.0,3.0,...]"`
+pub(crate) fn parse_vector(s: &str) -> Option<Vec<f32>> {
+    let inner = s.trim_start_matches('[').trim_end_matches(']');
+    inner
+        .split(',')
+        .map(|v| v.trim().parse::<f32>().ok())
+        .collect()
+}
@@ -0,0 +1,53 @@
+//! Collection entity
+//!
+//! A named grouping of a tenant's papers. Membership lives in
+//! `collection_papers`.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "collections")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    pub tenant_id: Uuid,
+    
+    /// Unique per tenant
+    pub name: String,
+    
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+    
+    pub created_at: DateTimeWithTimeZone,
+    
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+    
+    #[sea_orm(has_many = "super::collection_paper::Entity")]
+    Papers,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl Related<super::collection_paper::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Papers.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,41 @@
+//! Collection membership entity
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "collection_papers")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub collection_id: Uuid,
+    
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub paper_id: Uuid,
+    
+    pub added_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::collection::Entity",
+        from = "Column::CollectionId",
+        to = "super::collection::Column::Id"
+    )]
+    Collection,
+    
+    #[sea_orm(
+        belongs_to = "super::paper::Entity",
+        from = "Column::PaperId",
+        to = "super::paper::Column::Id"
+    )]
+    Paper,
+}
+
+impl Related<super::collection::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Collection.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,70 @@
+//! Ingestion batch entity
+//!
+//! An uploaded archive of documents, ingested as one job per document.
+//! Progress is read from the batch's jobs.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// An archive entry that was not ingested
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedEntry {
+    /// Path of the entry in the archive
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "ingestion_batches")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    pub tenant_id: Uuid,
+    
+    /// File name of the uploaded archive
+    #[sea_orm(column_type = "Text", nullable)]
+    pub archive_name: Option<String>,
+    
+    /// Documents taken from the archive, one job each
+    pub document_count: i32,
+    
+    /// Entries left out, as a JSON array of [`SkippedEntry`]
+    pub skipped: Json,
+    
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl Model {
+    /// Entries of the archive that were not ingested
+    pub fn skipped_entries(&self) -> Vec<SkippedEntry> {
+        serde_json::from_value(self.skipped.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+    
+    #[sea_orm(has_many = "super::ingestion_job::Entity")]
+    IngestionJob,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl Related<super::ingestion_job::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::IngestionJob.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
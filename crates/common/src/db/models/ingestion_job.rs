@@ -12,7 +12,10 @@ pub enum JobStatus {
     Embedding,
     Indexing,
     Completed,
+    /// Failed; retried at `next_retry_at` when set
     Failed,
+    /// Failed for good: the error can't be retried or attempts ran out
+    Dead,
 }
 
 impl From<String> for JobStatus {
@@ -24,6 +27,7 @@ impl From<String> for JobStatus {
             "indexing" => JobStatus::Indexing,
             "completed" => JobStatus::Completed,
             "failed" => JobStatus::Failed,
+            "dead" => JobStatus::Dead,
             _ => JobStatus::Pending,
         }
     }
@@ -38,6 +42,7 @@ impl From<JobStatus> for String {
             JobStatus::Indexing => "indexing".to_string(),
             JobStatus::Completed => "completed".to_string(),
             JobStatus::Failed => "failed".to_string(),
+            JobStatus::Dead => "dead".to_string(),
         }
     }
 }
@@ -52,9 +57,16 @@ pub struct Model {
     
     pub paper_id: Option<Uuid>,
     
+    /// Upload batch the job's document came in with
+    pub batch_id: Option<Uuid>,
+    
     #[sea_orm(column_type = "Text")]
     pub status: String,
     
+    /// Last non-terminal status reached; kept when the job fails
+    #[sea_orm(column_type = "Text")]
+    pub stage: String,
+    
     pub chunks_total: i32,
     
     pub chunks_processed: i32,
@@ -62,6 +74,9 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub error_message: Option<String>,
     
+    /// Indexes of chunks that could not be embedded, as a JSON array
+    pub failed_chunks: Json,
+    
     #[sea_orm(column_type = "Text", nullable)]
     pub idempotency_key: Option<String>,
     
@@ -69,6 +84,12 @@ pub struct Model {
     
     pub next_retry_at: Option<DateTimeWithTimeZone>,
     
+    /// Queue message that started the job, re-sent when it is retried
+    pub queue_message: Option<Json>,
+    
+    /// Envelope the queue message was sent with
+    pub queue_attributes: Option<Json>,
+    
     pub created_at: DateTimeWithTimeZone,
     
     pub started_at: Option<DateTimeWithTimeZone>,
@@ -83,8 +104,19 @@ impl Model {
     }
     
     /// Check if the job is in a terminal state
+    ///
+    /// A failed job with a retry scheduled isn't.
     pub fn is_terminal(&self) -> bool {
-        matches!(self.job_status(), JobStatus::Completed | JobStatus::Failed)
+        match self.job_status() {
+            JobStatus::Completed | JobStatus::Dead => true,
+            JobStatus::Failed => self.next_retry_at.is_none(),
+            _ => false,
+        }
+    }
+    
+    /// Indexes of chunks that could not be embedded
+    pub fn failed_chunk_indexes(&self) -> Vec<i32> {
+        serde_json::from_value(self.failed_chunks.clone()).unwrap_or_default()
     }
     
     /// Calculate progress percentage
@@ -112,6 +144,13 @@ pub enum Relation {
         to = "super::paper::Column::Id"
     )]
     Paper,
+    
+    #[sea_orm(
+        belongs_to = "super::ingestion_batch::Entity",
+        from = "Column::BatchId",
+        to = "super::ingestion_batch::Column::Id"
+    )]
+    Batch,
 }
 
 impl Related<super::tenant::Entity> for Entity {
@@ -126,4 +165,10 @@ impl Related<super::paper::Entity> for Entity {
     }
 }
 
+impl Related<super::ingestion_batch::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Batch.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,60 @@
+//! Ingestion job checkpoint entity
+//!
+//! Output of a completed pipeline stage, kept so a retried job can resume
+//! from it.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Pipeline stage a checkpoint was saved after
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointStage {
+    /// Text and elements extracted from the source document
+    Extracted,
+    /// Chunk list, saved before chunks are sent for embedding
+    Chunked,
+}
+
+impl CheckpointStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckpointStage::Extracted => "extracted",
+            CheckpointStage::Chunked => "chunked",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "job_checkpoints")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub job_id: Uuid,
+    
+    #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
+    pub stage: String,
+    
+    /// Stage output as JSONB
+    #[sea_orm(column_type = "JsonBinary")]
+    pub data: serde_json::Value,
+    
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::ingestion_job::Entity",
+        from = "Column::JobId",
+        to = "super::ingestion_job::Column::Id"
+    )]
+    IngestionJob,
+}
+
+impl Related<super::ingestion_job::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::IngestionJob.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
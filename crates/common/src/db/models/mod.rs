@@ -5,9 +5,26 @@
 mod paper;
 mod chunk;
 mod tenant;
+mod tenant_settings;
 mod ingestion_job;
+mod ingestion_batch;
+mod job_checkpoint;
+mod review_job;
+mod reindex_job;
 mod citation;
 mod session;
+mod outbox;
+mod refresh_token;
+mod audit_log;
+mod usage_record;
+mod search_feedback;
+mod query_log;
+mod session_event;
+mod saved_search;
+mod collection;
+mod collection_paper;
+mod author;
+mod paper_author;
 
 pub use paper::{
     Entity as PaperEntity,
@@ -21,7 +38,10 @@ pub use chunk::{
     Model as Chunk,
     ActiveModel as ChunkActiveModel,
     Column as ChunkColumn,
+    ChunkType,
+    Provenance,
 };
+pub(crate) use chunk::parse_vector;
 
 pub use tenant::{
     Entity as TenantEntity,
@@ -30,6 +50,13 @@ pub use tenant::{
     Column as TenantColumn,
 };
 
+pub use tenant_settings::{
+    Entity as TenantSettingsEntity,
+    Model as TenantSettings,
+    ActiveModel as TenantSettingsActiveModel,
+    Column as TenantSettingsColumn,
+};
+
 pub use ingestion_job::{
     Entity as IngestionJobEntity,
     Model as IngestionJob,
@@ -38,6 +65,38 @@ pub use ingestion_job::{
     JobStatus,
 };
 
+pub use ingestion_batch::{
+    Entity as IngestionBatchEntity,
+    Model as IngestionBatch,
+    ActiveModel as IngestionBatchActiveModel,
+    Column as IngestionBatchColumn,
+    SkippedEntry,
+};
+
+pub use job_checkpoint::{
+    Entity as JobCheckpointEntity,
+    Model as JobCheckpoint,
+    ActiveModel as JobCheckpointActiveModel,
+    Column as JobCheckpointColumn,
+    CheckpointStage,
+};
+
+pub use review_job::{
+    Entity as ReviewJobEntity,
+    Model as ReviewJob,
+    ActiveModel as ReviewJobActiveModel,
+    Column as ReviewJobColumn,
+    ReviewStatus,
+};
+
+pub use reindex_job::{
+    Entity as ReindexJobEntity,
+    Model as ReindexJob,
+    ActiveModel as ReindexJobActiveModel,
+    Column as ReindexJobColumn,
+    ReindexStatus,
+};
+
 pub use citation::{
     Entity as CitationEntity,
     Model as Citation,
@@ -51,3 +110,87 @@ pub use session::{
     ActiveModel as SessionActiveModel,
     Column as SessionColumn,
 };
+
+pub use outbox::{
+    Entity as OutboxEntity,
+    Model as OutboxMessage,
+    ActiveModel as OutboxActiveModel,
+    Column as OutboxColumn,
+};
+
+pub use refresh_token::{
+    Entity as RefreshTokenEntity,
+    Model as RefreshToken,
+    ActiveModel as RefreshTokenActiveModel,
+    Column as RefreshTokenColumn,
+};
+
+pub use audit_log::{
+    Entity as AuditLogEntity,
+    Model as AuditLog,
+    ActiveModel as AuditLogActiveModel,
+    Column as AuditLogColumn,
+};
+
+pub use usage_record::{
+    Entity as UsageRecordEntity,
+    Model as UsageRecord,
+    ActiveModel as UsageRecordActiveModel,
+    Column as UsageRecordColumn,
+};
+
+pub use search_feedback::{
+    Entity as SearchFeedbackEntity,
+    Model as SearchFeedback,
+    ActiveModel as SearchFeedbackActiveModel,
+    Column as SearchFeedbackColumn,
+};
+
+pub use query_log::{
+    Entity as QueryLogEntity,
+    Model as QueryLog,
+    ActiveModel as QueryLogActiveModel,
+    Column as QueryLogColumn,
+};
+
+pub use session_event::{
+    Entity as SessionEventEntity,
+    Model as SessionEvent,
+    ActiveModel as SessionEventActiveModel,
+    Column as SessionEventColumn,
+};
+
+pub use saved_search::{
+    Entity as SavedSearchEntity,
+    Model as SavedSearch,
+    ActiveModel as SavedSearchActiveModel,
+    Column as SavedSearchColumn,
+};
+
+pub use collection::{
+    Entity as CollectionEntity,
+    Model as Collection,
+    ActiveModel as CollectionActiveModel,
+    Column as CollectionColumn,
+};
+
+pub use collection_paper::{
+    Entity as CollectionPaperEntity,
+    Model as CollectionPaper,
+    ActiveModel as CollectionPaperActiveModel,
+    Column as CollectionPaperColumn,
+};
+
+pub use author::{
+    Entity as AuthorEntity,
+    Model as Author,
+    ActiveModel as AuthorActiveModel,
+    Column as AuthorColumn,
+};
+
+pub use paper_author::{
+    Entity as PaperAuthorEntity,
+    Model as PaperAuthor,
+    ActiveModel as PaperAuthorActiveModel,
+    Column as PaperAuthorColumn,
+};
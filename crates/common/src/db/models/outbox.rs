@@ -0,0 +1,53 @@
+//! Outbox entity for transactional queue publishing
+
+use crate::queue::Envelope;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "outbox")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// Logical queue name the payload is published to
+    #[sea_orm(column_type = "Text")]
+    pub queue: String,
+    
+    /// Message body as JSONB
+    #[sea_orm(column_type = "JsonBinary")]
+    pub payload: serde_json::Value,
+    
+    /// Message envelope, sent as message attributes; `None` for rows
+    /// written before envelopes
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub attributes: Option<serde_json::Value>,
+    
+    /// Publish attempts so far
+    pub attempts: i32,
+    
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
+    
+    /// Lease held by a relay while publishing
+    pub locked_until: Option<DateTimeWithTimeZone>,
+    
+    pub created_at: DateTimeWithTimeZone,
+    
+    pub published_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Envelope to publish the message with
+    pub fn envelope(&self) -> Envelope {
+        self.attributes
+            .clone()
+            .and_then(|a| serde_json::from_value(a).ok())
+            .unwrap_or_default()
+    }
+}
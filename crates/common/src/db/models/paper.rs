@@ -3,7 +3,7 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "papers")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -36,6 +36,24 @@ pub struct Model {
     pub created_at: DateTimeWithTimeZone,
     
     pub updated_at: DateTimeWithTimeZone,
+    
+    /// Soft-delete marker; deleted papers are hidden from reads and search
+    /// until restored or purged
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    
+    /// Storage key of the original document
+    #[sea_orm(column_type = "Text", nullable)]
+    pub source_key: Option<String>,
+    
+    /// Storage key of the extracted text
+    #[sea_orm(column_type = "Text", nullable)]
+    pub text_key: Option<String>,
+    
+    /// Citation PageRank, about 1 for an average paper; `None` until computed
+    pub pagerank: Option<f64>,
+    
+    /// When `pagerank` was computed; newer citations make it stale
+    pub pagerank_updated_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -66,4 +84,11 @@ impl Related<super::chunk::Entity> for Entity {
     }
 }
 
+impl Model {
+    /// Check if the paper has been soft-deleted
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,42 @@
+//! Paper authorship entity
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "paper_authors")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub paper_id: Uuid,
+    
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub author_id: Uuid,
+    
+    /// Order in the paper's author list, from 1
+    pub position: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::author::Entity",
+        from = "Column::AuthorId",
+        to = "super::author::Column::Id"
+    )]
+    Author,
+    
+    #[sea_orm(
+        belongs_to = "super::paper::Entity",
+        from = "Column::PaperId",
+        to = "super::paper::Column::Id"
+    )]
+    Paper,
+}
+
+impl Related<super::author::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Author.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,57 @@
+//! Query log entity
+//!
+//! One search, with its normalized query, mode, result count and latency,
+//! for query analytics.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "query_logs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    pub tenant_id: Uuid,
+    
+    pub session_id: Option<Uuid>,
+    
+    /// Normalized query text (trimmed, lowercase)
+    #[sea_orm(column_type = "Text")]
+    pub query_text: String,
+    
+    /// SHA-256 of the normalized query
+    #[sea_orm(column_type = "Text")]
+    pub query_hash: String,
+    
+    /// `vector`, `bm25`, `hybrid` or `intelligent`
+    #[sea_orm(column_type = "Text")]
+    pub search_mode: String,
+    
+    pub result_count: i32,
+    
+    pub latency_ms: i32,
+    
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub clicked_results: Option<Json>,
+    
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
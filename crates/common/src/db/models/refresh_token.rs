@@ -0,0 +1,63 @@
+//! Refresh token entity for JWT rotation
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "refresh_tokens")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    pub tenant_id: Uuid,
+    
+    /// Shared by every token in a rotation chain
+    pub family_id: Uuid,
+    
+    /// SHA-256 of the token
+    #[sea_orm(column_type = "Text", unique)]
+    pub token_hash: String,
+    
+    /// Space-separated scopes
+    #[sea_orm(column_type = "Text")]
+    pub scopes: String,
+    
+    pub expires_at: DateTimeWithTimeZone,
+    
+    pub revoked_at: Option<DateTimeWithTimeZone>,
+    
+    /// Token issued when this one was rotated
+    pub replaced_by: Option<Uuid>,
+    
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl Model {
+    /// Check if the token is expired
+    pub fn is_expired(&self) -> bool {
+        self.expires_at < DateTimeWithTimeZone::from(chrono::Utc::now())
+    }
+    
+    /// Scopes as a list
+    pub fn scope_list(&self) -> Vec<String> {
+        self.scopes.split_whitespace().map(String::from).collect()
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
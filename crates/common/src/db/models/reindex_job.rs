@@ -0,0 +1,120 @@
+//! Re-indexing job entity
+//!
+//! Tracks an admin-requested rebuild of chunk text-search vectors and/or
+//! vector indexes.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Re-indexing job status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReindexStatus {
+    Pending,
+    /// Recomputing text-search vectors; `chunks_done` counts progress
+    TextSearch,
+    /// Rebuilding vector indexes; `indexes_done` counts progress
+    VectorIndexes,
+    Completed,
+    Failed,
+}
+
+impl ReindexStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReindexStatus::Pending => "pending",
+            ReindexStatus::TextSearch => "text_search",
+            ReindexStatus::VectorIndexes => "vector_indexes",
+            ReindexStatus::Completed => "completed",
+            ReindexStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "reindex_jobs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// Tenant whose chunks are rewritten; `None` for all tenants
+    pub tenant_id: Option<Uuid>,
+    
+    /// Recompute `text_search_vector` on chunks
+    pub text_search: bool,
+    
+    /// Rebuild the vector indexes on chunks
+    pub vector_indexes: bool,
+    
+    #[sea_orm(column_type = "Text")]
+    pub status: String,
+    
+    pub chunks_total: i64,
+    
+    pub chunks_done: i64,
+    
+    pub indexes_total: i32,
+    
+    pub indexes_done: i32,
+    
+    #[sea_orm(column_type = "Text")]
+    pub requested_by: String,
+    
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error_message: Option<String>,
+    
+    pub created_at: DateTimeWithTimeZone,
+    
+    pub started_at: Option<DateTimeWithTimeZone>,
+    
+    pub completed_at: Option<DateTimeWithTimeZone>,
+    
+    /// Last lease renewal by the worker running the job
+    pub heartbeat_at: Option<DateTimeWithTimeZone>,
+}
+
+impl Model {
+    /// Rough completion percentage
+    ///
+    /// When both phases run, rewriting chunks counts for the first 80% and
+    /// rebuilding indexes for the rest.
+    pub fn progress_percent(&self) -> f64 {
+        let fraction = |done: f64, total: f64| if total > 0.0 { (done / total).min(1.0) } else { 0.0 };
+        let text_share = match (self.text_search, self.vector_indexes) {
+            (true, true) => 80.0,
+            (true, false) => 100.0,
+            _ => 0.0,
+        };
+        
+        match self.status.as_str() {
+            "pending" => 0.0,
+            "text_search" => {
+                text_share * fraction(self.chunks_done as f64, self.chunks_total as f64)
+            }
+            "vector_indexes" => {
+                text_share
+                    + (100.0 - text_share)
+                        * fraction(self.indexes_done as f64, self.indexes_total as f64)
+            }
+            _ => 100.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
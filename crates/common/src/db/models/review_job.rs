@@ -0,0 +1,104 @@
+//! Literature review job entity
+//!
+//! Tracks a review generated in the background and holds the finished
+//! review.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Review job status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Pending,
+    /// Retrieving papers on the topic
+    Retrieving,
+    /// Grouping papers into sub-topics
+    Clustering,
+    /// Writing sections; `sections_written` counts progress
+    Writing,
+    Completed,
+    Failed,
+}
+
+impl ReviewStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStatus::Pending => "pending",
+            ReviewStatus::Retrieving => "retrieving",
+            ReviewStatus::Clustering => "clustering",
+            ReviewStatus::Writing => "writing",
+            ReviewStatus::Completed => "completed",
+            ReviewStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "review_jobs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    pub tenant_id: Uuid,
+    
+    #[sea_orm(column_type = "Text")]
+    pub topic: String,
+    
+    #[sea_orm(column_type = "Text")]
+    pub status: String,
+    
+    pub sections_total: i32,
+    
+    pub sections_written: i32,
+    
+    /// Finished review as JSONB
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub review: Option<serde_json::Value>,
+    
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error_message: Option<String>,
+    
+    pub created_at: DateTimeWithTimeZone,
+    
+    pub started_at: Option<DateTimeWithTimeZone>,
+    
+    pub completed_at: Option<DateTimeWithTimeZone>,
+}
+
+impl Model {
+    /// Rough completion percentage
+    ///
+    /// Retrieval and clustering count for the first 20%; writing sections
+    /// makes up the rest.
+    pub fn progress_percent(&self) -> f64 {
+        match self.status.as_str() {
+            "pending" => 0.0,
+            "retrieving" => 5.0,
+            "clustering" => 15.0,
+            "writing" if self.sections_total > 0 => {
+                20.0 + 80.0 * self.sections_written as f64 / self.sections_total as f64
+            }
+            "writing" => 20.0,
+            _ => 100.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
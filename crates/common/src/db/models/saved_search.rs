@@ -0,0 +1,55 @@
+//! Saved search entity
+//!
+//! A query a tenant follows. New papers matching it are sent to the
+//! webhook and/or email address.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "saved_searches")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    pub tenant_id: Uuid,
+    
+    pub name: String,
+    
+    #[sea_orm(column_type = "Text")]
+    pub query: String,
+    
+    /// Calibrated score a new paper must reach to alert
+    pub min_score: f64,
+    
+    pub webhook_url: Option<String>,
+    
+    /// Key for the webhook's HMAC-SHA256 signature
+    #[serde(skip_serializing)]
+    pub webhook_secret: Option<String>,
+    
+    pub email: Option<String>,
+    
+    /// Chunks indexed after this are new to the search
+    pub last_run_at: DateTimeWithTimeZone,
+    
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
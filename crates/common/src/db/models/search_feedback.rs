@@ -0,0 +1,47 @@
+//! Search feedback entity
+//!
+//! A thumbs up or down on one search result, tied to the query and, when
+//! the client tracks one, the session.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "search_feedback")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    pub tenant_id: Uuid,
+    
+    pub session_id: Option<Uuid>,
+    
+    /// Normalized query text (trimmed, lowercase)
+    #[sea_orm(column_type = "Text")]
+    pub query: String,
+    
+    pub chunk_id: Uuid,
+    
+    /// 1 for thumbs up, -1 for thumbs down
+    pub rating: i16,
+    
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
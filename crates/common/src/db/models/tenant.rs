@@ -12,11 +12,34 @@ pub struct Model {
     #[sea_orm(column_type = "Text", unique)]
     pub name: String,
     
+    /// argon2id hash of the API key (legacy rows: unsalted SHA-256 hex)
     #[sea_orm(column_type = "Text")]
     pub api_key_hash: String,
     
+    /// Leading characters of the API key, used to find candidate rows
+    #[sea_orm(column_type = "Text", nullable)]
+    pub api_key_prefix: Option<String>,
+    
+    /// Space-separated scopes granted to the API key
+    #[sea_orm(column_type = "Text")]
+    pub api_key_scopes: String,
+    
+    pub api_key_created_at: DateTimeWithTimeZone,
+    
+    /// `None` means the key never expires
+    pub api_key_expires_at: Option<DateTimeWithTimeZone>,
+    
+    pub api_key_last_used_at: Option<DateTimeWithTimeZone>,
+    
+    /// Shared secret for HMAC request signing; `None` disables signed requests
+    #[sea_orm(column_type = "Text", nullable)]
+    pub signing_secret: Option<String>,
+    
     pub rate_limit_rps: i32,
     
+    /// Backfill paper metadata from Crossref when a DOI is found
+    pub resolve_metadata: bool,
+    
     pub is_active: bool,
     
     pub created_at: DateTimeWithTimeZone,
@@ -24,6 +47,19 @@ pub struct Model {
     pub updated_at: DateTimeWithTimeZone,
 }
 
+impl Model {
+    /// Check if the API key is expired
+    pub fn is_api_key_expired(&self) -> bool {
+        self.api_key_expires_at
+            .is_some_and(|at| at < DateTimeWithTimeZone::from(chrono::Utc::now()))
+    }
+    
+    /// API key scopes as a list
+    pub fn api_key_scope_list(&self) -> Vec<String> {
+        self.api_key_scopes.split_whitespace().map(String::from).collect()
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::paper::Entity")]
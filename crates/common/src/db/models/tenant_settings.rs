@@ -0,0 +1,52 @@
+//! Tenant settings entity
+//!
+//! Default ingestion options and the usage budget for a tenant. Unset
+//! fields fall back to the service configuration.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "tenant_settings")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub tenant_id: Uuid,
+    
+    /// `recursive` or `semantic`
+    #[sea_orm(column_type = "Text", nullable)]
+    pub chunk_strategy: Option<String>,
+    
+    pub chunk_size: Option<i32>,
+    
+    pub chunk_overlap: Option<i32>,
+    
+    #[sea_orm(column_type = "Text", nullable)]
+    pub embedding_model: Option<String>,
+    
+    /// Monthly LLM and embedding spend cap in micro-dollars; uncapped when
+    /// unset
+    pub monthly_budget_micros: Option<i64>,
+    
+    /// Leave the reference list out of chunks
+    pub exclude_references: Option<bool>,
+    
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,50 @@
+//! Usage ledger entity
+//!
+//! One LLM completion or embedding call, with its token counts and
+//! estimated cost, attributed to a tenant.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "usage_ledger")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    pub tenant_id: Uuid,
+    
+    /// `llm` or `embedding`
+    #[sea_orm(column_type = "Text")]
+    pub kind: String,
+    
+    #[sea_orm(column_type = "Text")]
+    pub model: String,
+    
+    pub prompt_tokens: i32,
+    
+    pub completion_tokens: i32,
+    
+    /// Estimated cost in micro-dollars (1e-6 USD)
+    pub cost_micros: i64,
+    
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::tenant::Entity",
+        from = "Column::TenantId",
+        to = "super::tenant::Column::Id"
+    )]
+    Tenant,
+}
+
+impl Related<super::tenant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tenant.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,166 @@
+//! Statement timeouts and slow-query logging for search queries
+//!
+//! Search queries run in a short read transaction with `SET LOCAL
+//! statement_timeout`, so a pathological scan is cancelled by Postgres
+//! instead of holding a pooled connection. Within a request deadline the
+//! timeout is cut to the time left, so a query isn't left running for a
+//! client that has given up. Every timed query is recorded in
+//! `db_query_duration_seconds`; those over the slow-query threshold are
+//! also logged, with string literals and bind parameters left out.
+
+use crate::config::DatabaseConfig;
+use crate::deadline;
+use crate::errors::{AppError, Result};
+use crate::metrics;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbErr, QueryResult, RuntimeErr, Statement, TransactionTrait,
+};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Statement timeout and slow-query threshold for timed queries
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct QueryLimits {
+    statement_timeout_ms: u64,
+    slow_query_threshold: Duration,
+}
+
+impl QueryLimits {
+    pub(crate) fn new(config: &DatabaseConfig) -> Self {
+        Self {
+            statement_timeout_ms: config.search_statement_timeout_ms,
+            slow_query_threshold: Duration::from_millis(config.slow_query_threshold_ms),
+        }
+    }
+    
+    /// Run `stmt` under the statement timeout, recording how long it took
+    pub(crate) async fn query_all(
+        &self,
+        conn: &DatabaseConnection,
+        label: &'static str,
+        stmt: Statement,
+    ) -> Result<Vec<QueryResult>> {
+        let timeout_ms = statement_timeout(self.statement_timeout_ms, deadline::remaining());
+        if timeout_ms == Some(0) {
+            return Err(AppError::QueryTimeout { timeout_ms: 0 });
+        }
+        
+        let start = Instant::now();
+        let sql = stmt.sql.clone();
+        let params = stmt.values.as_ref().map_or(0, |values| values.0.len());
+        
+        let result = match timeout_ms {
+            Some(timeout_ms) => Self::query_all_with_timeout(conn, stmt, timeout_ms).await,
+            None => conn.query_all(stmt).await,
+        };
+        
+        let elapsed = start.elapsed();
+        metrics::record_db_query(label, elapsed.as_secs_f64());
+        if elapsed >= self.slow_query_threshold {
+            warn!(
+                query = label,
+                duration_ms = elapsed.as_millis() as u64,
+                sql = %redact_sql(&sql),
+                redacted_params = params,
+                "Slow query"
+            );
+        }
+        
+        result.map_err(|e| {
+            if is_query_canceled(&e) {
+                AppError::QueryTimeout { timeout_ms: timeout_ms.unwrap_or_default() }
+            } else {
+                e.into()
+            }
+        })
+    }
+    
+    async fn query_all_with_timeout(
+        conn: &DatabaseConnection,
+        stmt: Statement,
+        timeout_ms: u64,
+    ) -> std::result::Result<Vec<QueryResult>, DbErr> {
+        let txn = conn.begin().await?;
+        txn.execute_unprepared(&format!("SET LOCAL statement_timeout = {}", timeout_ms))
+            .await?;
+        
+        match txn.query_all(stmt).await {
+            Ok(rows) => {
+                txn.commit().await?;
+                Ok(rows)
+            }
+            Err(e) => {
+                txn.rollback().await?;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Statement timeout in milliseconds: the configured one (`0` for none), cut
+/// to the time left before the request deadline
+///
+/// `Some(0)` means the deadline has passed; Postgres would read a zero
+/// `statement_timeout` as no timeout at all.
+fn statement_timeout(configured_ms: u64, remaining: Option<Duration>) -> Option<u64> {
+    let configured = (configured_ms > 0).then_some(configured_ms);
+    let remaining = remaining.map(|r| r.as_millis().min(u64::MAX as u128) as u64);
+    match (configured, remaining) {
+        (Some(configured), Some(remaining)) => Some(configured.min(remaining)),
+        (configured, remaining) => configured.or(remaining),
+    }
+}
+
+/// Whether Postgres cancelled the statement (SQLSTATE 57014), which is how
+/// `statement_timeout` surfaces
+fn is_query_canceled(err: &DbErr) -> bool {
+    match err {
+        DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(err)))
+        | DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(err))) => {
+            err.code().as_deref() == Some("57014")
+        }
+        _ => false,
+    }
+}
+
+/// SQL on one line with string literals (such as inlined embeddings)
+/// replaced by `'?'`
+fn redact_sql(sql: &str) -> String {
+    let mut redacted = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            redacted.push(c);
+            continue;
+        }
+        
+        // Skip to the closing quote; a doubled quote is an escaped one
+        redacted.push_str("'?'");
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+        }
+    }
+    
+    redacted.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_statement_timeout_is_cut_to_deadline() {
+        assert_eq!(statement_timeout(0, None), None);
+        assert_eq!(statement_timeout(5000, None), Some(5000));
+        assert_eq!(statement_timeout(0, Some(Duration::from_millis(1200))), Some(1200));
+        assert_eq!(statement_timeout(5000, Some(Duration::from_millis(1200))), Some(1200));
+        assert_eq!(statement_timeout(500, Some(Duration::from_secs(30))), Some(500));
+        assert_eq!(statement_timeout(5000, Some(Duration::ZERO)), Some(0));
+    }
+}
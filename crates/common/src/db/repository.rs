@@ -3,16 +3,63 @@
 //! Provides a clean interface for all data access operations
 //! with proper error handling and transaction support.
 
+use crate::authors::AuthorName;
+use crate::config::VectorStorage;
 use crate::errors::{AppError, Result};
-use crate::db::DbPool;
+use crate::db::{dimension_mismatch, DbPool};
 use crate::db::models::*;
+use crate::outbox::OutboxPayload;
+use crate::storage::ObjectStore;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, 
-    PaginatorTrait, QueryFilter, QueryOrder, Set, Statement,
+    prelude::{DateTimeWithTimeZone, Expr},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DatabaseTransaction,
+    DbBackend, DbErr, EntityTrait, FromQueryResult, IsolationLevel, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, RuntimeErr, Set, Statement, TransactionTrait,
 };
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Most relevance feedback moves a hybrid search score, up or down; about a
+/// third of a first-place RRF contribution
+pub const FEEDBACK_MAX_BOOST: f64 = 0.005;
+
+/// Net votes at which feedback has half its maximum effect
+const FEEDBACK_HALF_VOTES: f64 = 3.0;
+
+/// Weight of votes cast on the same query, relative to other queries' votes
+const SAME_QUERY_VOTE_WEIGHT: f64 = 2.0;
+
+/// Papers updated per PageRank `UPDATE` (two bind parameters each)
+const PAGERANK_BATCH_SIZE: usize = 1000;
+
+/// References resolved or citations written per statement (three bind
+/// parameters each)
+const REFERENCE_BATCH_SIZE: usize = 500;
+
+/// Soft-deleted papers removed per purge transaction
+const PURGE_BATCH_SIZE: u64 = 100;
+
+/// Chunks written per upsert statement (ten bind parameters each)
+const CHUNK_UPSERT_BATCH_SIZE: usize = 500;
+
+/// Chunks rewritten per statement while recomputing text-search vectors
+const REINDEX_BATCH_SIZE: u64 = 1000;
+
+/// Candidates per requested result fetched from a quantized vector index
+/// before rescoring on full-precision vectors
+pub const QUANTIZED_CANDIDATES: usize = 4;
+
+/// Times [`Repository::transaction`] runs its work before giving up on
+/// deadlocks and serialization failures
+pub const TRANSACTION_MAX_ATTEMPTS: u32 = 3;
+
+/// Pause before re-running a conflicted transaction, multiplied by the attempt
+const TRANSACTION_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(20);
+
 /// Result from search operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChunkResult {
@@ -23,6 +70,271 @@ pub struct ChunkResult {
     pub chunk_index: i32,
     pub score: f64,
     pub embedding_model: String,
+    /// `text`, `table`, `figure_caption` or `equation`
+    pub chunk_type: String,
+    /// Where the chunk is in its source PDF
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+}
+
+/// A chunk and its embedding, ready to store
+#[derive(Debug, Clone)]
+pub struct NewChunk {
+    pub index: i32,
+    pub content: String,
+    pub embedding: Vec<f32>,
+    pub token_count: i32,
+    pub chunk_type: ChunkType,
+    /// Where the chunk is in its source PDF (see [`Provenance`])
+    pub metadata: serde_json::Value,
+    /// Model that produced `embedding`; a paper's chunks can differ when a
+    /// fallback provider embedded some of them
+    pub embedding_model: String,
+    /// Byte range of a text chunk in the text it was cut from
+    pub char_range: Option<std::ops::Range<usize>>,
+}
+
+/// Audit log query; `None` fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+    pub tenant_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Exclusive upper bound; pass the oldest `created_at` seen to page back
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Usage of one model over a period
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+pub struct UsageTotals {
+    pub kind: String,
+    pub model: String,
+    pub calls: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub cost_micros: i64,
+}
+
+/// A collection and how many live papers it holds
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+pub struct CollectionSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub paper_count: i64,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// How two papers are related through the citation graph
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CitationRelation {
+    /// Cited together by the same papers
+    #[default]
+    Cocitation,
+    /// Citing the same papers (bibliographic coupling)
+    Coupling,
+}
+
+/// What a paper's embedding is built from when finding similar papers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SimilarityBasis {
+    /// Mean of all the paper's text chunk embeddings
+    #[default]
+    Paper,
+    /// The first text chunk, which holds the title and abstract
+    Abstract,
+}
+
+/// A paper close to another in embedding space
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+pub struct SimilarPaper {
+    pub paper_id: Uuid,
+    pub title: String,
+    /// Cosine similarity of its closest chunk to the anchor embedding
+    pub similarity: f64,
+}
+
+/// A paper related to another through the citation graph
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+pub struct RelatedPaper {
+    pub paper_id: Uuid,
+    pub title: String,
+    /// Papers citing both (co-citation) or cited by both (coupling)
+    pub shared: i64,
+    /// `shared` over the geometric mean of the two papers' citation
+    /// (co-citation) or reference (coupling) counts; 0-1
+    pub score: f64,
+}
+
+/// A citation between two live papers
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+pub struct CitationEdge {
+    pub citing_paper_id: Uuid,
+    pub cited_paper_id: Uuid,
+    /// The sentence containing the citation
+    pub citation_context: Option<String>,
+}
+
+/// A tenant's processed papers and the citations between its live papers
+#[derive(Debug, Clone, Default)]
+pub struct TenantCitations {
+    /// Paper IDs with their titles
+    pub papers: Vec<(Uuid, String)>,
+    /// `(citing, cited)` paper IDs
+    pub edges: Vec<(Uuid, Uuid)>,
+}
+
+/// A normalized query and how its searches went
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+pub struct TopQuery {
+    pub query: String,
+    pub searches: i64,
+    /// Searches that returned nothing
+    pub zero_result_searches: i64,
+    pub avg_latency_ms: f64,
+    pub last_searched_at: DateTimeWithTimeZone,
+}
+
+/// Search counts over a period
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromQueryResult)]
+pub struct QueryTotals {
+    pub searches: i64,
+    pub zero_result_searches: i64,
+}
+
+/// A chunk's text for the lexical index
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct IndexChunk {
+    pub id: Uuid,
+    pub content: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// A chunk as exported to its tenant
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct ExportChunk {
+    pub id: Uuid,
+    pub paper_id: Uuid,
+    pub paper_title: String,
+    pub chunk_index: i32,
+    pub chunk_type: String,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub token_count: i32,
+    pub embedding_model: String,
+    /// pgvector text form; only selected when embeddings are requested
+    pub embedding: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl ExportChunk {
+    /// Parse the embedding, when it was selected
+    pub fn parse_embedding(&self) -> Option<Vec<f32>> {
+        self.embedding.as_deref().and_then(parse_vector)
+    }
+}
+
+/// Papers a search covers
+///
+/// Converts from a bare `Option<Uuid>` tenant, so callers that only scope
+/// by tenant pass `Some(tenant_id)` as before.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchScope {
+    /// Only this tenant's papers; `None` searches every tenant
+    pub tenant_id: Option<Uuid>,
+    /// Only papers in this collection
+    pub collection_id: Option<Uuid>,
+    /// Only papers by an author with this ORCID iD or normalized name
+    /// fragment (see `authors::normalize_author_filter`)
+    pub author: Option<String>,
+    /// Vector search only: chunks embedded by this model, the one that
+    /// embedded the query
+    pub embedding_model: Option<String>,
+}
+
+impl SearchScope {
+    /// One tenant's papers
+    pub fn tenant(tenant_id: Uuid) -> Self {
+        Self { tenant_id: Some(tenant_id), ..Default::default() }
+    }
+    
+    /// Narrow the scope to a collection, if one is given
+    pub fn in_collection(mut self, collection_id: Option<Uuid>) -> Self {
+        self.collection_id = collection_id;
+        self
+    }
+    
+    /// Narrow the scope to an author's papers, if a filter is given
+    pub fn by_author(mut self, author: Option<String>) -> Self {
+        self.author = author;
+        self
+    }
+    
+    /// Compare query vectors only with chunks embedded by the same model
+    pub fn with_model(mut self, embedding_model: impl Into<String>) -> Self {
+        self.embedding_model = Some(embedding_model.into());
+        self
+    }
+    
+    /// `AND ...` conditions on `p` (papers) and `c` (chunks), binding their
+    /// values after those already in `values`
+    fn sql_filter(&self, values: &mut Vec<sea_orm::Value>) -> String {
+        let mut filter = String::new();
+        if let Some(tenant_id) = self.tenant_id {
+            values.push(tenant_id.into());
+            filter.push_str(&format!(" AND p.tenant_id = ${}", values.len()));
+        }
+        if let Some(collection_id) = self.collection_id {
+            values.push(collection_id.into());
+            filter.push_str(&format!(
+                " AND c.paper_id IN (SELECT paper_id FROM collection_papers WHERE collection_id = ${})",
+                values.len()
+            ));
+        }
+        if let Some(author) = &self.author {
+            values.push(author.clone().into());
+            filter.push_str(&format!(
+                " AND c.paper_id IN (SELECT pa.paper_id FROM paper_authors pa JOIN authors a ON a.id = pa.author_id \
+                 WHERE a.orcid = ${n} OR strpos(a.normalized_name, ${n}) > 0)",
+                n = values.len()
+            ));
+        }
+        filter
+    }
+}
+
+impl From<Option<Uuid>> for SearchScope {
+    fn from(tenant_id: Option<Uuid>) -> Self {
+        Self { tenant_id, ..Default::default() }
+    }
+}
+
+/// A search to save
+#[derive(Debug, Clone)]
+pub struct NewSavedSearch {
+    pub name: String,
+    pub query: String,
+    pub min_score: f64,
+    pub webhook_url: Option<String>,
+    pub webhook_secret: Option<String>,
+    pub email: Option<String>,
+}
+
+/// Field changes for a paper; `None` leaves the field unchanged
+#[derive(Debug, Clone, Default)]
+pub struct PaperUpdate {
+    pub title: Option<String>,
+    pub abstract_text: Option<String>,
+    pub source: Option<String>,
+    pub external_id: Option<String>,
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub metadata: Option<serde_json::Value>,
 }
 
 /// Repository for data access operations
@@ -56,10 +368,133 @@ impl Repository {
         self.pool.ping().await
     }
     
+    // ========================================================================
+    // Transactions
+    // ========================================================================
+    
+    /// Run `work` atomically on the write connection
+    ///
+    /// Everything done through the [`UnitOfWork`] commits together, or not at
+    /// all when `work` returns an error. Deadlocks roll back and re-run
+    /// `work`, up to [`TRANSACTION_MAX_ATTEMPTS`] times, so it must be safe
+    /// to repeat:
+    ///
+    /// ```ignore
+    /// let job = repo.transaction(|uow| Box::pin(async move {
+    ///     let paper = uow.create_paper(tenant_id, title.clone(), ...).await?;
+    ///     uow.create_job(tenant_id, Some(paper.id), None).await
+    /// })).await?;
+    /// ```
+    ///
+    /// The transaction runs at Postgres' default read committed isolation,
+    /// where concurrent writers wait for each other rather than failing, so
+    /// deadlocks are in practice the only conflicts retried. Work that reads
+    /// rows and writes based on them, and must not race another writer,
+    /// should use [`transaction_with`](Self::transaction_with) at a stricter
+    /// level.
+    ///
+    /// The unit of work must not outlive the future `work` returns.
+    pub async fn transaction<'a, T, F>(&self, work: F) -> Result<T>
+    where
+        F: Fn(Arc<UnitOfWork>) -> BoxFuture<'a, Result<T>>,
+    {
+        self.transaction_with(None, work).await
+    }
+    
+    /// Run `work` as [`transaction`](Self::transaction) does, at `isolation`
+    ///
+    /// Under repeatable read or serializable isolation a conflicting
+    /// transaction fails with a serialization error; those are retried like
+    /// deadlocks. `None` keeps the server's default level.
+    pub async fn transaction_with<'a, T, F>(&self, isolation: Option<IsolationLevel>, work: F) -> Result<T>
+    where
+        F: Fn(Arc<UnitOfWork>) -> BoxFuture<'a, Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let txn = self.write_conn().begin_with_config(isolation, None).await?;
+            let uow = Arc::new(UnitOfWork { txn });
+            
+            let result = work(uow.clone()).await;
+            let txn = Arc::try_unwrap(uow)
+                .map_err(|_| AppError::Internal {
+                    message: "Unit of work still in use after its transaction".to_string(),
+                })?
+                .txn;
+            
+            let outcome = match result {
+                Ok(value) => txn.commit().await.map(|_| value).map_err(AppError::from),
+                Err(e) => {
+                    txn.rollback().await?;
+                    Err(e)
+                }
+            };
+            
+            match outcome {
+                Err(e) if attempt < TRANSACTION_MAX_ATTEMPTS && is_serialization_failure(&e) => {
+                    tracing::warn!(attempt, error = %e, "Transaction conflicted, retrying");
+                    tokio::time::sleep(TRANSACTION_RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+                outcome => return outcome,
+            }
+        }
+    }
+    
     // ========================================================================
     // Tenant Operations
     // ========================================================================
     
+    /// Create an active tenant holding the API key `hash`
+    pub async fn create_tenant(
+        &self,
+        name: &str,
+        hash: &str,
+        prefix: &str,
+        scopes: &[String],
+        rate_limit_rps: i32,
+    ) -> Result<Tenant> {
+        if TenantEntity::find()
+            .filter(TenantColumn::Name.eq(name))
+            .one(self.write_conn())
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Duplicate {
+                message: format!("Tenant '{}' already exists", name),
+            });
+        }
+        
+        let now = DateTimeWithTimeZone::from(chrono::Utc::now());
+        let tenant = TenantActiveModel {
+            id: Set(Uuid::new_v4()),
+            name: Set(name.to_string()),
+            api_key_hash: Set(hash.to_string()),
+            api_key_prefix: Set(Some(prefix.to_string())),
+            api_key_scopes: Set(scopes.join(" ")),
+            api_key_created_at: Set(now),
+            api_key_expires_at: Set(None),
+            api_key_last_used_at: Set(None),
+            signing_secret: Set(None),
+            rate_limit_rps: Set(rate_limit_rps),
+            resolve_metadata: Set(true),
+            is_active: Set(true),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+        
+        tenant.insert(self.write_conn()).await.map_err(Into::into)
+    }
+    
+    /// All tenants, by name
+    pub async fn list_tenants(&self) -> Result<Vec<Tenant>> {
+        TenantEntity::find()
+            .order_by_asc(TenantColumn::Name)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
     /// Find tenant by ID
     pub async fn find_tenant_by_id(&self, id: Uuid) -> Result<Option<Tenant>> {
         TenantEntity::find_by_id(id)
@@ -68,7 +503,140 @@ impl Repository {
             .map_err(Into::into)
     }
     
-    /// Find tenant by API key hash
+    /// Find active tenants whose API key starts with the given prefix
+    pub async fn find_tenants_by_api_key_prefix(&self, prefix: &str) -> Result<Vec<Tenant>> {
+        TenantEntity::find()
+            .filter(TenantColumn::ApiKeyPrefix.eq(prefix))
+            .filter(TenantColumn::IsActive.eq(true))
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Replace a tenant's stored API key hash and lookup prefix
+    pub async fn set_tenant_api_key(&self, id: Uuid, hash: &str, prefix: &str) -> Result<()> {
+        TenantEntity::update_many()
+            .col_expr(TenantColumn::ApiKeyHash, Expr::value(hash.to_string()))
+            .col_expr(TenantColumn::ApiKeyPrefix, Expr::value(prefix.to_string()))
+            .col_expr(TenantColumn::UpdatedAt, Expr::current_timestamp().into())
+            .filter(TenantColumn::Id.eq(id))
+            .exec(self.write_conn())
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Issue a new API key for a tenant, replacing the current one
+    ///
+    /// Refresh tokens obtained with the old key are revoked in the same
+    /// transaction.
+    pub async fn replace_tenant_api_key(
+        &self,
+        id: Uuid,
+        hash: &str,
+        prefix: &str,
+        scopes: &[String],
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<()> {
+        let txn = self.write_conn().begin().await?;
+        
+        TenantEntity::update_many()
+            .col_expr(TenantColumn::ApiKeyHash, Expr::value(hash.to_string()))
+            .col_expr(TenantColumn::ApiKeyPrefix, Expr::value(prefix.to_string()))
+            .col_expr(TenantColumn::ApiKeyScopes, Expr::value(scopes.join(" ")))
+            .col_expr(TenantColumn::ApiKeyCreatedAt, Expr::current_timestamp().into())
+            .col_expr(
+                TenantColumn::ApiKeyExpiresAt,
+                Expr::value(expires_at.map(DateTimeWithTimeZone::from)),
+            )
+            .col_expr(
+                TenantColumn::ApiKeyLastUsedAt,
+                Expr::value(Option::<DateTimeWithTimeZone>::None),
+            )
+            .col_expr(TenantColumn::UpdatedAt, Expr::current_timestamp().into())
+            .filter(TenantColumn::Id.eq(id))
+            .exec(&txn)
+            .await?;
+        
+        let revoked = Self::revoke_refresh_tokens(&txn, id).await?;
+        txn.commit().await?;
+        
+        tracing::info!(tenant_id = %id, revoked, "Revoked refresh tokens of the replaced API key");
+        Ok(())
+    }
+    
+    /// Expire a tenant's API key now
+    ///
+    /// Returns whether the tenant exists.
+    pub async fn revoke_tenant_api_key(&self, id: Uuid) -> Result<bool> {
+        let result = TenantEntity::update_many()
+            .col_expr(TenantColumn::ApiKeyExpiresAt, Expr::current_timestamp().into())
+            .col_expr(TenantColumn::UpdatedAt, Expr::current_timestamp().into())
+            .filter(TenantColumn::Id.eq(id))
+            .exec(self.write_conn())
+            .await?;
+        
+        Ok(result.rows_affected > 0)
+    }
+    
+    /// Set or clear a tenant's request signing secret
+    ///
+    /// Returns whether the tenant exists. The previous secret stops working
+    /// at once; signed requests look the secret up every time.
+    pub async fn set_tenant_signing_secret(&self, id: Uuid, secret: Option<&str>) -> Result<bool> {
+        let result = TenantEntity::update_many()
+            .col_expr(TenantColumn::SigningSecret, Expr::value(secret.map(str::to_string)))
+            .col_expr(TenantColumn::UpdatedAt, Expr::current_timestamp().into())
+            .filter(TenantColumn::Id.eq(id))
+            .exec(self.write_conn())
+            .await?;
+        
+        Ok(result.rows_affected > 0)
+    }
+    
+    /// Record that a tenant's API key was just used
+    pub async fn touch_tenant_api_key(&self, id: Uuid) -> Result<()> {
+        TenantEntity::update_many()
+            .col_expr(TenantColumn::ApiKeyLastUsedAt, Expr::current_timestamp().into())
+            .filter(TenantColumn::Id.eq(id))
+            .exec(self.write_conn())
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Find `tenant_id`'s API keys that have not been used since `cutoff`
+    ///
+    /// Only active tenants' keys are returned. Keys that were never used
+    /// count from their creation. Least recently used keys come first.
+    pub async fn find_stale_api_keys(
+        &self,
+        tenant_id: Uuid,
+        cutoff: chrono::DateTime<chrono::Utc>,
+        limit: u64,
+    ) -> Result<Vec<Tenant>> {
+        let cutoff = DateTimeWithTimeZone::from(cutoff);
+        
+        TenantEntity::find()
+            .filter(TenantColumn::Id.eq(tenant_id))
+            .filter(TenantColumn::IsActive.eq(true))
+            .filter(
+                Condition::any()
+                    .add(TenantColumn::ApiKeyLastUsedAt.lt(cutoff))
+                    .add(
+                        Condition::all()
+                            .add(TenantColumn::ApiKeyLastUsedAt.is_null())
+                            .add(TenantColumn::ApiKeyCreatedAt.lt(cutoff)),
+                    ),
+            )
+            .order_by_asc(Expr::cust("COALESCE(api_key_last_used_at, api_key_created_at)"))
+            .limit(limit)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Find tenant by legacy (unsalted SHA-256) API key hash
     pub async fn find_tenant_by_api_key_hash(&self, hash: &str) -> Result<Option<Tenant>> {
         TenantEntity::find()
             .filter(TenantColumn::ApiKeyHash.eq(hash))
@@ -78,6 +646,183 @@ impl Repository {
             .map_err(Into::into)
     }
     
+    /// Find a tenant's default ingestion settings
+    pub async fn find_tenant_settings(&self, tenant_id: Uuid) -> Result<Option<TenantSettings>> {
+        TenantSettingsEntity::find_by_id(tenant_id)
+            .one(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Store a tenant's default ingestion settings, replacing existing ones
+    pub async fn save_tenant_settings(&self, settings: TenantSettings) -> Result<TenantSettings> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO tenant_settings
+                (tenant_id, chunk_strategy, chunk_size, chunk_overlap, embedding_model,
+                 monthly_budget_micros, exclude_references, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET chunk_strategy = EXCLUDED.chunk_strategy,
+                chunk_size = EXCLUDED.chunk_size,
+                chunk_overlap = EXCLUDED.chunk_overlap,
+                embedding_model = EXCLUDED.embedding_model,
+                monthly_budget_micros = EXCLUDED.monthly_budget_micros,
+                exclude_references = EXCLUDED.exclude_references,
+                updated_at = EXCLUDED.updated_at
+            RETURNING *
+            "#,
+            vec![
+                settings.tenant_id.into(),
+                settings.chunk_strategy.into(),
+                settings.chunk_size.into(),
+                settings.chunk_overlap.into(),
+                settings.embedding_model.into(),
+                settings.monthly_budget_micros.into(),
+                settings.exclude_references.into(),
+            ],
+        );
+        
+        TenantSettingsEntity::find()
+            .from_raw_sql(stmt)
+            .one(self.write_conn())
+            .await?
+            .ok_or_else(|| AppError::Internal {
+                message: "Upsert returned no tenant settings row".to_string(),
+            })
+    }
+    
+    // ========================================================================
+    // Refresh Token Operations
+    // ========================================================================
+    
+    async fn insert_refresh_token<C: ConnectionTrait>(
+        conn: &C,
+        tenant_id: Uuid,
+        family_id: Uuid,
+        token_hash: &str,
+        scopes: String,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<RefreshToken> {
+        let token = RefreshTokenActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            family_id: Set(family_id),
+            token_hash: Set(token_hash.to_string()),
+            scopes: Set(scopes),
+            expires_at: Set(expires_at.into()),
+            revoked_at: Set(None),
+            replaced_by: Set(None),
+            created_at: Set(chrono::Utc::now().into()),
+        };
+        
+        token.insert(conn).await.map_err(Into::into)
+    }
+    
+    /// Store a new refresh token, starting a new rotation family
+    pub async fn create_refresh_token(
+        &self,
+        tenant_id: Uuid,
+        token_hash: &str,
+        scopes: &[String],
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<RefreshToken> {
+        Self::insert_refresh_token(
+            self.write_conn(),
+            tenant_id,
+            Uuid::new_v4(),
+            token_hash,
+            scopes.join(" "),
+            expires_at,
+        )
+        .await
+    }
+    
+    /// Find a refresh token by the hash of its value
+    pub async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        RefreshTokenEntity::find()
+            .filter(RefreshTokenColumn::TokenHash.eq(token_hash))
+            .one(self.write_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Revoke every live refresh token of a tenant, returning how many
+    async fn revoke_refresh_tokens<C: ConnectionTrait>(conn: &C, tenant_id: Uuid) -> Result<u64> {
+        let result = RefreshTokenEntity::update_many()
+            .col_expr(RefreshTokenColumn::RevokedAt, Expr::current_timestamp().into())
+            .filter(RefreshTokenColumn::TenantId.eq(tenant_id))
+            .filter(RefreshTokenColumn::RevokedAt.is_null())
+            .exec(conn)
+            .await?;
+        Ok(result.rows_affected)
+    }
+    
+    /// Rotate a refresh token
+    ///
+    /// The presented token is revoked and replaced by a new token in the
+    /// same family. Returns `None` if the token is unknown, expired or was
+    /// already rotated. Presenting a rotated token means it leaked, so the
+    /// whole family is revoked.
+    pub async fn rotate_refresh_token(
+        &self,
+        token_hash: &str,
+        new_token_hash: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<RefreshToken>> {
+        let txn = self.write_conn().begin().await?;
+        
+        let current = RefreshTokenEntity::find()
+            .filter(RefreshTokenColumn::TokenHash.eq(token_hash))
+            .lock_exclusive()
+            .one(&txn)
+            .await?;
+        
+        let Some(current) = current else {
+            return Ok(None);
+        };
+        
+        if current.revoked_at.is_some() {
+            RefreshTokenEntity::update_many()
+                .col_expr(RefreshTokenColumn::RevokedAt, Expr::current_timestamp().into())
+                .filter(RefreshTokenColumn::FamilyId.eq(current.family_id))
+                .filter(RefreshTokenColumn::RevokedAt.is_null())
+                .exec(&txn)
+                .await?;
+            txn.commit().await?;
+            
+            tracing::warn!(
+                family_id = %current.family_id,
+                tenant_id = %current.tenant_id,
+                "Refresh token reuse detected, revoked token family"
+            );
+            return Ok(None);
+        }
+        
+        if current.is_expired() {
+            return Ok(None);
+        }
+        
+        let next = Self::insert_refresh_token(
+            &txn,
+            current.tenant_id,
+            current.family_id,
+            new_token_hash,
+            current.scopes.clone(),
+            expires_at,
+        )
+        .await?;
+        
+        let mut revoked: RefreshTokenActiveModel = current.into();
+        revoked.revoked_at = Set(Some(chrono::Utc::now().into()));
+        revoked.replaced_by = Set(Some(next.id));
+        revoked.update(&txn).await?;
+        
+        txn.commit().await?;
+        Ok(Some(next))
+    }
+    
     // ========================================================================
     // Paper Operations
     // ========================================================================
@@ -93,11 +838,25 @@ impl Repository {
         metadata: serde_json::Value,
         idempotency_key: Option<String>,
     ) -> Result<Paper> {
-        let paper_id = Uuid::new_v4();
+        Self::new_paper(tenant_id, title, abstract_text, source, external_id, metadata, idempotency_key)
+            .insert(self.write_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    fn new_paper(
+        tenant_id: Uuid,
+        title: String,
+        abstract_text: String,
+        source: Option<String>,
+        external_id: Option<String>,
+        metadata: serde_json::Value,
+        idempotency_key: Option<String>,
+    ) -> PaperActiveModel {
         let now = chrono::Utc::now();
         
-        let paper = PaperActiveModel {
-            id: Set(paper_id),
+        PaperActiveModel {
+            id: Set(Uuid::new_v4()),
             tenant_id: Set(tenant_id),
             external_id: Set(external_id),
             title: Set(title),
@@ -108,9 +867,39 @@ impl Repository {
             idempotency_key: Set(idempotency_key),
             created_at: Set(now.into()),
             updated_at: Set(now.into()),
-        };
+            deleted_at: Set(None),
+            source_key: Set(None),
+            text_key: Set(None),
+            pagerank: Set(None),
+            pagerank_updated_at: Set(None),
+        }
+    }
+    
+    /// Record where a paper's original document and extracted text are stored
+    pub async fn set_paper_storage_keys(
+        &self,
+        id: Uuid,
+        source_key: Option<String>,
+        text_key: Option<String>,
+    ) -> Result<()> {
+        Self::apply_paper_storage_keys(self.write_conn(), id, source_key, text_key).await
+    }
+    
+    async fn apply_paper_storage_keys<C: ConnectionTrait>(
+        conn: &C,
+        id: Uuid,
+        source_key: Option<String>,
+        text_key: Option<String>,
+    ) -> Result<()> {
+        PaperEntity::update_many()
+            .col_expr(PaperColumn::SourceKey, Expr::value(source_key))
+            .col_expr(PaperColumn::TextKey, Expr::value(text_key))
+            .col_expr(PaperColumn::UpdatedAt, Expr::current_timestamp().into())
+            .filter(PaperColumn::Id.eq(id))
+            .exec(conn)
+            .await?;
         
-        paper.insert(self.write_conn()).await.map_err(Into::into)
+        Ok(())
     }
     
     /// Find paper by ID
@@ -121,6 +910,21 @@ impl Repository {
             .map_err(Into::into)
     }
     
+    /// Live papers of the tenant among `ids`, in no particular order
+    pub async fn find_papers_by_ids(&self, tenant_id: Uuid, ids: &[Uuid]) -> Result<Vec<Paper>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        PaperEntity::find()
+            .filter(PaperColumn::TenantId.eq(tenant_id))
+            .filter(PaperColumn::Id.is_in(ids.iter().copied()))
+            .filter(PaperColumn::DeletedAt.is_null())
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
     /// Find paper by idempotency key within tenant
     pub async fn find_paper_by_idempotency_key(
         &self,
@@ -144,6 +948,7 @@ impl Repository {
     ) -> Result<(Vec<Paper>, u64)> {
         let paginator = PaperEntity::find()
             .filter(PaperColumn::TenantId.eq(tenant_id))
+            .filter(PaperColumn::DeletedAt.is_null())
             .order_by_desc(PaperColumn::CreatedAt)
             .paginate(self.read_conn(), limit);
         
@@ -153,15 +958,116 @@ impl Repository {
         Ok((papers, total))
     }
     
-    /// Delete paper by ID
+    /// Apply field changes to a paper, bumping `updated_at`
+    pub async fn update_paper(&self, paper: Paper, update: PaperUpdate) -> Result<Paper> {
+        Self::paper_update(paper, update)
+            .update(self.write_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    fn paper_update(paper: Paper, update: PaperUpdate) -> PaperActiveModel {
+        let mut active: PaperActiveModel = paper.into();
+        
+        if let Some(title) = update.title {
+            active.title = Set(title);
+        }
+        if let Some(abstract_text) = update.abstract_text {
+            active.abstract_text = Set(abstract_text);
+        }
+        if let Some(source) = update.source {
+            active.source = Set(Some(source));
+        }
+        if let Some(external_id) = update.external_id {
+            active.external_id = Set(Some(external_id));
+        }
+        if let Some(published_at) = update.published_at {
+            active.published_at = Set(Some(published_at.into()));
+        }
+        if let Some(metadata) = update.metadata {
+            active.metadata = Set(metadata);
+        }
+        active.updated_at = Set(chrono::Utc::now().into());
+        active
+    }
+    
+    /// Soft-delete paper by ID
+    ///
+    /// The paper and its chunks stay in place but are excluded from listings
+    /// and search until restored or purged.
     pub async fn delete_paper(&self, id: Uuid) -> Result<bool> {
-        let result = PaperEntity::delete_by_id(id)
+        let result = PaperEntity::update_many()
+            .col_expr(PaperColumn::DeletedAt, Expr::current_timestamp().into())
+            .col_expr(PaperColumn::UpdatedAt, Expr::current_timestamp().into())
+            .filter(PaperColumn::Id.eq(id))
+            .filter(PaperColumn::DeletedAt.is_null())
             .exec(self.write_conn())
             .await?;
         
         Ok(result.rows_affected > 0)
     }
     
+    /// Restore a soft-deleted paper
+    pub async fn restore_paper(&self, id: Uuid) -> Result<bool> {
+        let result = PaperEntity::update_many()
+            .col_expr(PaperColumn::DeletedAt, Expr::value(Option::<DateTimeWithTimeZone>::None))
+            .col_expr(PaperColumn::UpdatedAt, Expr::current_timestamp().into())
+            .filter(PaperColumn::Id.eq(id))
+            .filter(PaperColumn::DeletedAt.is_not_null())
+            .exec(self.write_conn())
+            .await?;
+        
+        Ok(result.rows_affected > 0)
+    }
+    
+    /// Physically remove papers soft-deleted before `older_than`
+    ///
+    /// Chunks (and their vectors) and citations are removed via `ON DELETE CASCADE`.
+    /// The papers' stored source and text objects are deleted from `storage`
+    /// first, while the rows are locked against a restore; if that fails the
+    /// batch is left in place and purged on a later run.
+    pub async fn purge_deleted_papers(
+        &self,
+        older_than: chrono::DateTime<chrono::Utc>,
+        storage: Option<&dyn ObjectStore>,
+    ) -> Result<u64> {
+        let mut purged = 0;
+        
+        loop {
+            let txn = self.write_conn().begin().await?;
+            
+            let papers = PaperEntity::find()
+                .filter(PaperColumn::DeletedAt.is_not_null())
+                .filter(PaperColumn::DeletedAt.lt(older_than))
+                .order_by_asc(PaperColumn::DeletedAt)
+                .limit(PURGE_BATCH_SIZE)
+                .lock_exclusive()
+                .all(&txn)
+                .await?;
+            if papers.is_empty() {
+                return Ok(purged);
+            }
+            
+            if let Some(storage) = storage {
+                let keys = papers.iter().flat_map(|paper| [&paper.source_key, &paper.text_key]).flatten();
+                for key in keys {
+                    storage.delete(key).await?;
+                }
+            }
+            
+            let result = PaperEntity::delete_many()
+                .filter(PaperColumn::Id.is_in(papers.iter().map(|paper| paper.id)))
+                .exec(&txn)
+                .await?;
+            txn.commit().await?;
+            
+            purged += result.rows_affected;
+            if (papers.len() as u64) < PURGE_BATCH_SIZE {
+                return Ok(purged);
+            }
+        }
+    }
+    
     // ========================================================================
     // Chunk Operations
     // ========================================================================
@@ -170,19 +1076,19 @@ impl Repository {
     pub async fn create_chunks(
         &self,
         paper_id: Uuid,
-        chunks: Vec<(i32, String, Vec<f32>, i32)>,  // (index, content, embedding, token_count)
-        embedding_model: &str,
+        chunks: Vec<NewChunk>,
         embedding_version: i32,
     ) -> Result<Vec<Uuid>> {
+        self.check_embedding_dimensions(&chunks).await?;
         let mut chunk_ids = Vec::with_capacity(chunks.len());
         
-        for (index, content, embedding, token_count) in chunks {
+        for chunk in chunks {
             let chunk_id = Uuid::new_v4();
             
             // Convert Vec<f32> to pgvector string format "[1.0, 2.0, ...]"
             let embedding_str = format!(
                 "[{}]",
-                embedding.iter()
+                chunk.embedding.iter()
                     .map(|f| f.to_string())
                     .collect::<Vec<_>>()
                     .join(",")
@@ -194,19 +1100,24 @@ impl Repository {
                 r#"
                 INSERT INTO chunks (
                     id, paper_id, chunk_index, content, embedding, 
-                    embedding_model, embedding_version, token_count, created_at
+                    embedding_model, embedding_version, token_count,
+                    chunk_type, metadata, char_offset_start, char_offset_end, created_at
                 )
-                VALUES ($1, $2, $3, $4, $5::vector, $6, $7, $8, NOW())
+                VALUES ($1, $2, $3, $4, $5::vector, $6, $7, $8, $9, $10, $11, $12, NOW())
                 "#,
                 vec![
                     chunk_id.into(),
                     paper_id.into(),
-                    index.into(),
-                    content.into(),
+                    chunk.index.into(),
+                    chunk.content.into(),
                     embedding_str.into(),
-                    embedding_model.into(),
+                    chunk.embedding_model.into(),
                     embedding_version.into(),
-                    token_count.into(),
+                    chunk.token_count.into(),
+                    String::from(chunk.chunk_type).into(),
+                    chunk.metadata.into(),
+                    chunk.char_range.as_ref().map(|r| r.start as i32).into(),
+                    chunk.char_range.as_ref().map(|r| r.end as i32).into(),
                 ],
             );
             
@@ -217,6 +1128,124 @@ impl Repository {
         Ok(chunk_ids)
     }
     
+    /// Write embedded chunks, tolerating re-delivered embedding jobs
+    ///
+    /// A chunk already stored with the same embedding model is left as it is;
+    /// one stored with another model has its embedding replaced. Either way
+    /// the chunk keeps its id. Ids are returned in the order of `chunks`,
+    /// which are written with one statement per batch.
+    pub async fn upsert_chunks(
+        &self,
+        paper_id: Uuid,
+        chunks: Vec<NewChunk>,
+        embedding_version: i32,
+    ) -> Result<Vec<Uuid>> {
+        self.check_embedding_dimensions(&chunks).await?;
+        let mut chunk_ids = Vec::with_capacity(chunks.len());
+        
+        for batch in chunks.chunks(CHUNK_UPSERT_BATCH_SIZE) {
+            let indexes: Vec<i32> = batch.iter().map(|chunk| chunk.index).collect();
+            let mut values: Vec<sea_orm::Value> = vec![
+                paper_id.into(),
+                embedding_version.into(),
+                indexes.clone().into(),
+            ];
+            let rows: Vec<String> = batch.iter().map(|chunk| {
+                let embedding_str = format!(
+                    "[{}]",
+                    chunk.embedding.iter()
+                        .map(|f| f.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                );
+                let first = values.len() + 1;
+                values.extend([
+                    Uuid::new_v4().into(),
+                    chunk.index.into(),
+                    chunk.content.clone().into(),
+                    embedding_str.into(),
+                    chunk.embedding_model.clone().into(),
+                    chunk.token_count.into(),
+                    String::from(chunk.chunk_type).into(),
+                    chunk.metadata.clone().into(),
+                    chunk.char_range.as_ref().map(|r| r.start as i32).into(),
+                    chunk.char_range.as_ref().map(|r| r.end as i32).into(),
+                ]);
+                format!(
+                    "(${}, $1, ${}, ${}, ${}::vector, ${}, $2, ${}, ${}, ${}, ${}, ${}, NOW())",
+                    first, first + 1, first + 2, first + 3, first + 4,
+                    first + 5, first + 6, first + 7, first + 8, first + 9
+                )
+            }).collect();
+            
+            // Chunks left as they are aren't returned by the insert, so their
+            // ids come from the existing rows; the outer query sees the table
+            // as it was before the insert
+            let stmt = Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!(
+                    r#"
+                    WITH upserted AS (
+                        INSERT INTO chunks (
+                            id, paper_id, chunk_index, content, embedding,
+                            embedding_model, embedding_version, token_count,
+                            chunk_type, metadata, char_offset_start, char_offset_end, created_at
+                        )
+                        VALUES {}
+                        ON CONFLICT ON CONSTRAINT chunks_paper_index_unique DO UPDATE
+                        SET content = EXCLUDED.content,
+                            embedding = EXCLUDED.embedding,
+                            embedding_model = EXCLUDED.embedding_model,
+                            embedding_version = EXCLUDED.embedding_version,
+                            token_count = EXCLUDED.token_count,
+                            chunk_type = EXCLUDED.chunk_type,
+                            metadata = EXCLUDED.metadata,
+                            char_offset_start = EXCLUDED.char_offset_start,
+                            char_offset_end = EXCLUDED.char_offset_end
+                        WHERE chunks.embedding_model <> EXCLUDED.embedding_model
+                            OR chunks.embedding IS NULL
+                        RETURNING chunk_index, id
+                    )
+                    SELECT chunk_index, id FROM upserted
+                    UNION ALL
+                    SELECT chunk_index, id FROM chunks
+                    WHERE paper_id = $1
+                        AND chunk_index = ANY($3)
+                        AND chunk_index NOT IN (SELECT chunk_index FROM upserted)
+                    "#,
+                    rows.join(", ")
+                ),
+                values,
+            );
+            
+            let ids: HashMap<i32, Uuid> = self.write_conn()
+                .query_all(stmt)
+                .await?
+                .iter()
+                .map(|row| Ok((row.try_get::<i32>("", "chunk_index")?, row.try_get::<Uuid>("", "id")?)))
+                .collect::<Result<_>>()?;
+            for index in indexes {
+                let id = ids.get(&index).ok_or_else(|| AppError::Internal {
+                    message: format!("Chunk {} of paper {} was not written", index, paper_id),
+                })?;
+                chunk_ids.push(*id);
+            }
+        }
+        
+        Ok(chunk_ids)
+    }
+    
+    /// Reject chunks whose embeddings don't fit the `chunks.embedding` column
+    async fn check_embedding_dimensions(&self, chunks: &[NewChunk]) -> Result<()> {
+        let Some(column) = self.pool.column_dimension().await? else {
+            return Ok(());
+        };
+        match chunks.iter().find(|c| c.embedding.len() != column) {
+            Some(chunk) => Err(dimension_mismatch(&chunk.embedding_model, chunk.embedding.len(), column)),
+            None => Ok(()),
+        }
+    }
+    
     /// Get chunks for a paper
     pub async fn get_chunks_by_paper(&self, paper_id: Uuid) -> Result<Vec<Chunk>> {
         ChunkEntity::find()
@@ -227,12 +1256,55 @@ impl Repository {
             .map_err(Into::into)
     }
     
+    /// Indexes of the chunks stored for a paper
+    pub async fn stored_chunk_indexes(&self, paper_id: Uuid) -> Result<HashSet<i32>> {
+        let indexes: Vec<i32> = ChunkEntity::find()
+            .select_only()
+            .column(ChunkColumn::ChunkIndex)
+            .filter(ChunkColumn::PaperId.eq(paper_id))
+            .into_tuple()
+            .all(self.write_conn())
+            .await?;
+        
+        Ok(indexes.into_iter().collect())
+    }
+    
+    /// Get a paper's chunks with index in `first..=last`, in order
+    pub async fn get_chunks_by_paper_range(
+        &self,
+        paper_id: Uuid,
+        first: i32,
+        last: i32,
+    ) -> Result<Vec<Chunk>> {
+        ChunkEntity::find()
+            .filter(ChunkColumn::PaperId.eq(paper_id))
+            .filter(ChunkColumn::ChunkIndex.between(first, last))
+            .order_by_asc(ChunkColumn::ChunkIndex)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Delete all chunks (and their vectors) for a paper
+    pub async fn delete_chunks_by_paper(&self, paper_id: Uuid) -> Result<u64> {
+        let result = ChunkEntity::delete_many()
+            .filter(ChunkColumn::PaperId.eq(paper_id))
+            .exec(self.write_conn())
+            .await?;
+        
+        Ok(result.rows_affected)
+    }
+    
     /// Vector similarity search
+    ///
+    /// With a quantized [`VectorStorage`] the nearest
+    /// [`QUANTIZED_CANDIDATES`] times `limit` chunks are found in the
+    /// quantized index, then reranked and scored on full-precision vectors.
     pub async fn vector_search(
         &self,
         embedding: &[f32],
         limit: usize,
-        tenant_id: Option<Uuid>,
+        scope: impl Into<SearchScope>,
     ) -> Result<Vec<ChunkResult>> {
         let embedding_str = format!(
             "[{}]",
@@ -242,43 +1314,73 @@ impl Repository {
                 .join(",")
         );
         
-        let tenant_filter = tenant_id
-            .map(|_| "AND p.tenant_id = $3")
-            .unwrap_or("");
-        
-        let sql = format!(
-            r#"
-            SELECT 
-                c.id as chunk_id,
-                c.paper_id,
-                p.title as paper_title,
-                c.content,
-                c.chunk_index,
-                c.embedding_model,
-                1 - (c.embedding <=> $1::vector) as score
-            FROM chunks c
-            JOIN papers p ON c.paper_id = p.id
-            WHERE c.embedding IS NOT NULL
-            {}
-            ORDER BY c.embedding <=> $1::vector
-            LIMIT $2
-            "#,
-            tenant_filter
-        );
+        let storage = self.pool.vector_storage();
+        let candidates = match storage {
+            VectorStorage::Vector => limit,
+            VectorStorage::Halfvec | VectorStorage::Binary => limit * QUANTIZED_CANDIDATES,
+        };
         
         let mut values: Vec<sea_orm::Value> = vec![
             embedding_str.into(),
             (limit as i32).into(),
+            (candidates as i32).into(),
         ];
-        
-        if let Some(tid) = tenant_id {
-            values.push(tid.into());
+        let scope = scope.into();
+        let mut scope_filter = scope.sql_filter(&mut values);
+        if let Some(model) = &scope.embedding_model {
+            values.push(model.clone().into());
+            scope_filter.push_str(&format!(" AND c.embedding_model = ${}", values.len()));
         }
         
+        // Must match the expression indexes from migration 022, which are
+        // built at the column's dimension
+        let dimension = self.pool.embedding_dimension();
+        let candidate_order = match storage {
+            VectorStorage::Vector => "c.embedding <=> $1::vector".to_string(),
+            VectorStorage::Halfvec => format!(
+                "c.embedding::halfvec({0}) <=> $1::halfvec({0})",
+                dimension
+            ),
+            VectorStorage::Binary => format!(
+                "binary_quantize(c.embedding)::bit({0}) <~> binary_quantize($1::vector)::bit({0})",
+                dimension
+            ),
+        };
+        
+        let sql = format!(
+            r#"
+            SELECT chunk_id, paper_id, paper_title, content, chunk_index, embedding_model,
+                   1 - distance as score, chunk_type, metadata
+            FROM (
+                SELECT 
+                    c.id as chunk_id,
+                    c.paper_id,
+                    p.title as paper_title,
+                    c.content,
+                    c.chunk_index,
+                    c.embedding_model,
+                    c.embedding <=> $1::vector as distance,
+                    c.chunk_type,
+                    c.metadata
+                FROM chunks c
+                JOIN papers p ON c.paper_id = p.id
+                WHERE c.embedding IS NOT NULL
+                AND p.deleted_at IS NULL
+                {}
+                ORDER BY {}
+                LIMIT $3
+            ) candidates
+            ORDER BY distance
+            LIMIT $2
+            "#,
+            scope_filter,
+            candidate_order
+        );
+        
         let stmt = Statement::from_sql_and_values(DbBackend::Postgres, &sql, values);
         
-        let results = self.read_conn()
-            .query_all(stmt)
+        let results = self.pool
+            .search_query("vector_search", stmt)
             .await?
             .into_iter()
             .filter_map(|row| {
@@ -291,6 +1393,11 @@ impl Repository {
                     chunk_index: row.try_get_by_index::<i32>(4).ok()?,
                     embedding_model: row.try_get_by_index::<String>(5).ok()?,
                     score: row.try_get_by_index::<f64>(6).ok()?,
+                    chunk_type: row.try_get_by_index::<String>(7).ok()?,
+                    provenance: row
+                        .try_get_by_index::<serde_json::Value>(8)
+                        .ok()
+                        .and_then(|metadata| Provenance::from_metadata(&metadata)),
                 })
             })
             .collect();
@@ -298,16 +1405,90 @@ impl Repository {
         Ok(results)
     }
     
+    /// The tenant's live papers closest to a paper in embedding space
+    ///
+    /// The paper is represented by the mean of its text chunk embeddings
+    /// (or just the first chunk's for `SimilarityBasis::Abstract`), and
+    /// other papers by their closest chunk. Papers without embeddings have
+    /// no similar papers.
+    pub async fn similar_papers(
+        &self,
+        tenant_id: Uuid,
+        paper_id: Uuid,
+        basis: SimilarityBasis,
+        limit: u64,
+    ) -> Result<Vec<SimilarPaper>> {
+        // Chunks fetched per returned paper, as a paper often has several
+        // chunks near the anchor
+        const CHUNKS_PER_PAPER: u64 = 10;
+        
+        let anchor_chunks: Option<i64> = match basis {
+            SimilarityBasis::Paper => None,
+            SimilarityBasis::Abstract => Some(1),
+        };
+        
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            WITH anchor AS (
+                SELECT AVG(embedding) AS embedding
+                FROM (
+                    SELECT embedding
+                    FROM chunks
+                    WHERE paper_id = $1 AND embedding IS NOT NULL AND chunk_type = 'text'
+                    ORDER BY chunk_index
+                    LIMIT $5
+                ) first_chunks
+            ),
+            nearest AS (
+                SELECT c.paper_id, 1 - (c.embedding <=> anchor.embedding) AS similarity
+                FROM chunks c
+                CROSS JOIN anchor
+                JOIN papers p ON p.id = c.paper_id
+                WHERE anchor.embedding IS NOT NULL
+                  AND c.embedding IS NOT NULL
+                  AND c.paper_id <> $1
+                  AND p.tenant_id = $2
+                  AND p.deleted_at IS NULL
+                ORDER BY c.embedding <=> anchor.embedding
+                LIMIT $4
+            )
+            SELECT p.id AS paper_id, p.title, MAX(n.similarity) AS similarity
+            FROM nearest n
+            JOIN papers p ON p.id = n.paper_id
+            GROUP BY p.id, p.title
+            ORDER BY similarity DESC, p.id
+            LIMIT $3
+            "#,
+            vec![
+                paper_id.into(),
+                tenant_id.into(),
+                (limit as i64).into(),
+                ((limit * CHUNKS_PER_PAPER) as i64).into(),
+                anchor_chunks.into(),
+            ],
+        );
+        
+        self.pool
+            .search_query("similar_papers", stmt)
+            .await?
+            .iter()
+            .map(|row| SimilarPaper::from_query_result(row, "").map_err(Into::into))
+            .collect()
+    }
+    
     /// BM25 text search
     pub async fn bm25_search(
         &self,
         query: &str,
         limit: usize,
-        tenant_id: Option<Uuid>,
+        scope: impl Into<SearchScope>,
     ) -> Result<Vec<ChunkResult>> {
-        let tenant_filter = tenant_id
-            .map(|_| "AND p.tenant_id = $3")
-            .unwrap_or("");
+        let mut values: Vec<sea_orm::Value> = vec![
+            query.into(),
+            (limit as i32).into(),
+        ];
+        let scope_filter = scope.into().sql_filter(&mut values);
         
         let sql = format!(
             r#"
@@ -318,30 +1499,24 @@ impl Repository {
                 c.content,
                 c.chunk_index,
                 c.embedding_model,
-                ts_rank_cd(c.text_search_vector, plainto_tsquery('english', $1)) as score
+                ts_rank_cd(c.text_search_vector, plainto_tsquery('english', $1)) as score,
+                c.chunk_type,
+                c.metadata
             FROM chunks c
             JOIN papers p ON c.paper_id = p.id
             WHERE c.text_search_vector @@ plainto_tsquery('english', $1)
+            AND p.deleted_at IS NULL
             {}
             ORDER BY score DESC
             LIMIT $2
             "#,
-            tenant_filter
+            scope_filter
         );
         
-        let mut values: Vec<sea_orm::Value> = vec![
-            query.into(),
-            (limit as i32).into(),
-        ];
-        
-        if let Some(tid) = tenant_id {
-            values.push(tid.into());
-        }
-        
         let stmt = Statement::from_sql_and_values(DbBackend::Postgres, &sql, values);
         
-        let results = self.read_conn()
-            .query_all(stmt)
+        let results = self.pool
+            .search_query("bm25_search", stmt)
             .await?
             .into_iter()
             .filter_map(|row| {
@@ -354,6 +1529,11 @@ impl Repository {
                     chunk_index: row.try_get_by_index::<i32>(4).ok()?,
                     embedding_model: row.try_get_by_index::<String>(5).ok()?,
                     score: row.try_get_by_index::<f64>(6).ok()?,
+                    chunk_type: row.try_get_by_index::<String>(7).ok()?,
+                    provenance: row
+                        .try_get_by_index::<serde_json::Value>(8)
+                        .ok()
+                        .and_then(|metadata| Provenance::from_metadata(&metadata)),
                 })
             })
             .collect();
@@ -367,21 +1547,33 @@ impl Repository {
         query: &str,
         embedding: &[f32],
         limit: usize,
-        tenant_id: Option<Uuid>,
+        scope: impl Into<SearchScope>,
+    ) -> Result<Vec<ChunkResult>> {
+        self.hybrid_search_weighted(query, embedding, limit, scope, 1.0, 1.0).await
+    }
+    
+    /// Hybrid search with weighted Reciprocal Rank Fusion
+    pub async fn hybrid_search_weighted(
+        &self,
+        query: &str,
+        embedding: &[f32],
+        limit: usize,
+        scope: impl Into<SearchScope>,
+        vector_weight: f64,
+        bm25_weight: f64,
     ) -> Result<Vec<ChunkResult>> {
-        use std::collections::HashMap;
-        
         const K: f64 = 60.0;  // RRF constant
+        let scope = scope.into();
         
         // Run both searches in parallel
-        let vector_results = self.vector_search(embedding, limit * 2, tenant_id).await?;
-        let bm25_results = self.bm25_search(query, limit * 2, tenant_id).await?;
+        let vector_results = self.vector_search(embedding, limit * 2, scope.clone()).await?;
+        let bm25_results = self.bm25_search(query, limit * 2, scope.clone()).await?;
         
         // Compute RRF scores
         let mut rrf_scores: HashMap<Uuid, (ChunkResult, f64)> = HashMap::new();
         
         for (rank, result) in vector_results.into_iter().enumerate() {
-            let rrf = 1.0 / (K + (rank + 1) as f64);
+            let rrf = vector_weight / (K + (rank + 1) as f64);
             rrf_scores
                 .entry(result.chunk_id)
                 .and_modify(|(_, score)| *score += rrf)
@@ -389,7 +1581,7 @@ impl Repository {
         }
         
         for (rank, result) in bm25_results.into_iter().enumerate() {
-            let rrf = 1.0 / (K + (rank + 1) as f64);
+            let rrf = bm25_weight / (K + (rank + 1) as f64);
             rrf_scores
                 .entry(result.chunk_id)
                 .and_modify(|(_, score)| *score += rrf)
@@ -404,12 +1596,46 @@ impl Repository {
             })
             .collect();
         
+        // Nudge results by the tenant's relevance feedback
+        if let Some(tenant_id) = scope.tenant_id {
+            if let Err(e) = self.apply_feedback(tenant_id, query, &mut results).await {
+                tracing::warn!(error = %e, "Failed to apply search feedback");
+            }
+        }
+        
         results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
         results.truncate(limit);
         
         Ok(results)
     }
     
+    /// Add each result's feedback boost to its score
+    ///
+    /// Votes count towards a chunk's net score, double when cast on the same
+    /// query; the boost grows with net votes but stays within
+    /// [`FEEDBACK_MAX_BOOST`] either way.
+    async fn apply_feedback(&self, tenant_id: Uuid, query: &str, results: &mut [ChunkResult]) -> Result<()> {
+        let chunk_ids: Vec<Uuid> = results.iter().map(|r| r.chunk_id).collect();
+        let votes = self.feedback_for_chunks(tenant_id, &chunk_ids).await?;
+        if votes.is_empty() {
+            return Ok(());
+        }
+        
+        let query = normalize_query(query);
+        let mut net_votes: HashMap<Uuid, f64> = HashMap::new();
+        for vote in votes {
+            let weight = if vote.query == query { SAME_QUERY_VOTE_WEIGHT } else { 1.0 };
+            *net_votes.entry(vote.chunk_id).or_default() += weight * vote.rating as f64;
+        }
+        
+        for result in results.iter_mut() {
+            if let Some(&net) = net_votes.get(&result.chunk_id) {
+                result.score += FEEDBACK_MAX_BOOST * net / (net.abs() + FEEDBACK_HALF_VOTES);
+            }
+        }
+        Ok(())
+    }
+    
     // ========================================================================
     // Job Operations
     // ========================================================================
@@ -419,6 +1645,98 @@ impl Repository {
         &self,
         tenant_id: Uuid,
         idempotency_key: Option<String>,
+    ) -> Result<IngestionJob> {
+        Self::insert_job(self.write_conn(), tenant_id, None, idempotency_key).await
+    }
+    
+    /// Create an ingestion job and its queue message atomically
+    ///
+    /// The message is written to the outbox in the same transaction and
+    /// published by an [`OutboxRelay`](crate::outbox::OutboxRelay).
+    pub async fn create_job_with_outbox<F>(
+        &self,
+        tenant_id: Uuid,
+        paper_id: Option<Uuid>,
+        idempotency_key: Option<String>,
+        queue: &str,
+        build_message: F,
+    ) -> Result<IngestionJob>
+    where
+        F: Fn(&IngestionJob) -> OutboxPayload + Sync,
+    {
+        let build_message = &build_message;
+        self.transaction(|uow| {
+            let idempotency_key = idempotency_key.clone();
+            Box::pin(async move {
+                let job = uow.create_job(tenant_id, paper_id, idempotency_key).await?;
+                let message = build_message(&job);
+                uow.write_outbox(queue, message.clone()).await?;
+                // Kept on the job so a retry can send it again
+                uow.set_job_queue_message(job, message).await
+            })
+        })
+        .await
+    }
+    
+    /// Create an upload batch with one ingestion job per document, and the
+    /// jobs' queue messages, atomically
+    ///
+    /// `build_message` gets each document's position and its job. The
+    /// batch is only visible once all of its jobs are.
+    pub async fn create_batch_with_jobs<F>(
+        &self,
+        batch: IngestionBatch,
+        queue: &str,
+        build_message: F,
+    ) -> Result<(IngestionBatch, Vec<IngestionJob>)>
+    where
+        F: Fn(usize, &IngestionJob) -> OutboxPayload + Sync,
+    {
+        let build_message = &build_message;
+        self.transaction(|uow| {
+            let batch = batch.clone();
+            Box::pin(async move {
+                let documents = batch.document_count as usize;
+                let batch = uow.create_batch(batch).await?;
+                let mut jobs = Vec::with_capacity(documents);
+                for position in 0..documents {
+                    let job = uow.create_job(batch.tenant_id, None, None).await?;
+                    let message = build_message(position, &job);
+                    uow.write_outbox(queue, message.clone()).await?;
+                    let mut job: IngestionJobActiveModel = uow.set_job_queue_message(job, message).await?.into();
+                    job.batch_id = Set(Some(batch.id));
+                    jobs.push(job.update(&uow.txn).await?);
+                }
+                Ok((batch, jobs))
+            })
+        })
+        .await
+    }
+    
+    /// Find an upload batch by ID
+    pub async fn find_batch(&self, id: Uuid) -> Result<Option<IngestionBatch>> {
+        IngestionBatchEntity::find_by_id(id)
+            .one(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Jobs of an upload batch, in document order
+    pub async fn find_batch_jobs(&self, batch_id: Uuid) -> Result<Vec<IngestionJob>> {
+        IngestionJobEntity::find()
+            .filter(IngestionJobColumn::BatchId.eq(batch_id))
+            .order_by_asc(IngestionJobColumn::CreatedAt)
+            .order_by_asc(IngestionJobColumn::Id)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    async fn insert_job<C: ConnectionTrait>(
+        conn: &C,
+        tenant_id: Uuid,
+        paper_id: Option<Uuid>,
+        idempotency_key: Option<String>,
     ) -> Result<IngestionJob> {
         let job_id = Uuid::new_v4();
         let now = chrono::Utc::now();
@@ -426,20 +1744,25 @@ impl Repository {
         let job = IngestionJobActiveModel {
             id: Set(job_id),
             tenant_id: Set(tenant_id),
-            paper_id: Set(None),
+            paper_id: Set(paper_id),
+            batch_id: Set(None),
             status: Set("pending".to_string()),
+            stage: Set("pending".to_string()),
             chunks_total: Set(0),
             chunks_processed: Set(0),
             error_message: Set(None),
+            failed_chunks: Set(serde_json::json!([])),
             idempotency_key: Set(idempotency_key),
             attempt_count: Set(0),
             next_retry_at: Set(None),
+            queue_message: Set(None),
+            queue_attributes: Set(None),
             created_at: Set(now.into()),
             started_at: Set(None),
             completed_at: Set(None),
         };
         
-        job.insert(self.write_conn()).await.map_err(Into::into)
+        job.insert(conn).await.map_err(Into::into)
     }
     
     /// Find job by ID
@@ -472,16 +1795,51 @@ impl Repository {
         paper_id: Option<Uuid>,
         chunks_total: Option<i32>,
         error_message: Option<String>,
+    ) -> Result<IngestionJob> {
+        Self::apply_job_status(self.write_conn(), job_id, status, paper_id, chunks_total, error_message)
+            .await
+    }
+    
+    /// Update job status and write a queue message to the outbox atomically
+    pub async fn update_job_status_with_outbox(
+        &self,
+        job_id: Uuid,
+        status: JobStatus,
+        chunks_total: Option<i32>,
+        queue: &str,
+        message: OutboxPayload,
+    ) -> Result<IngestionJob> {
+        self.transaction(|uow| {
+            let (status, message) = (status.clone(), message.clone());
+            Box::pin(async move {
+                let job = uow.update_job_status(job_id, status, None, chunks_total, None).await?;
+                uow.write_outbox(queue, message).await?;
+                Ok(job)
+            })
+        })
+        .await
+    }
+    
+    async fn apply_job_status<C: ConnectionTrait>(
+        conn: &C,
+        job_id: Uuid,
+        status: JobStatus,
+        paper_id: Option<Uuid>,
+        chunks_total: Option<i32>,
+        error_message: Option<String>,
     ) -> Result<IngestionJob> {
         let now = chrono::Utc::now();
         
         let mut job: IngestionJobActiveModel = IngestionJobEntity::find_by_id(job_id)
-            .one(self.write_conn())
+            .one(conn)
             .await?
             .ok_or_else(|| AppError::JobNotFound { id: job_id.to_string() })?
             .into();
         
         job.status = Set(String::from(status.clone()));
+        if !matches!(status, JobStatus::Completed | JobStatus::Failed | JobStatus::Dead) {
+            job.stage = Set(String::from(status.clone()));
+        }
         
         if let Some(pid) = paper_id {
             job.paper_id = Set(Some(pid));
@@ -501,13 +1859,13 @@ impl Repository {
                     job.started_at = Set(Some(now.into()));
                 }
             }
-            JobStatus::Completed | JobStatus::Failed => {
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Dead => {
                 job.completed_at = Set(Some(now.into()));
             }
             _ => {}
         }
         
-        job.update(self.write_conn()).await.map_err(Into::into)
+        job.update(conn).await.map_err(Into::into)
     }
     
     /// Update job progress
@@ -526,83 +1884,2209 @@ impl Repository {
         Ok(())
     }
     
-    // ========================================================================
-    // Citation Operations
-    // ========================================================================
-    
-    /// Get citations for a paper (both directions)
-    pub async fn get_citations(
-        &self,
-        paper_id: Uuid,
-    ) -> Result<(Vec<Citation>, Vec<Citation>)> {
-        let outgoing = CitationEntity::find()
-            .filter(CitationColumn::CitingPaperId.eq(paper_id))
-            .all(self.read_conn())
-            .await?;
-        
-        let incoming = CitationEntity::find()
-            .filter(CitationColumn::CitedPaperId.eq(paper_id))
-            .all(self.read_conn())
-            .await?;
+    /// Record which of a job's chunks could not be embedded; empty clears
+    /// the list
+    pub async fn set_failed_chunks(&self, job_id: Uuid, chunk_indexes: &[i32]) -> Result<()> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE ingestion_jobs SET failed_chunks = $1 WHERE id = $2",
+            vec![serde_json::json!(chunk_indexes).into(), job_id.into()],
+        );
         
-        Ok((outgoing, incoming))
+        self.write_conn().execute(stmt).await?;
+        Ok(())
     }
     
-    // ========================================================================
-    // Session Operations
-    // ========================================================================
+    /// Count another processing attempt and clear the previous error
+    pub async fn record_job_attempt(&self, job_id: Uuid) -> Result<()> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE ingestion_jobs SET attempt_count = attempt_count + 1, error_message = NULL WHERE id = $1",
+            vec![job_id.into()],
+        );
+        
+        self.write_conn().execute(stmt).await?;
+        Ok(())
+    }
     
-    /// Create or update session
-    pub async fn upsert_session(
+    /// Record a failed attempt
+    ///
+    /// With `retry_at` the job is `failed` and re-sent once that time
+    /// passes (see [`Self::requeue_due_jobs`]); without it the job is `dead`.
+    pub async fn fail_job_attempt(
         &self,
-        tenant_id: Uuid,
-        session_id: Uuid,
-        state: serde_json::Value,
-        ttl_minutes: i64,
-    ) -> Result<Session> {
-        let now = chrono::Utc::now();
-        let expires = now + chrono::Duration::minutes(ttl_minutes);
-        
-        let session = SessionActiveModel {
-            id: Set(session_id),
-            tenant_id: Set(tenant_id),
-            state: Set(state),
-            created_at: Set(now.into()),
-            last_active_at: Set(now.into()),
-            expires_at: Set(expires.into()),
-        };
-        
-        // Use upsert
+        job_id: Uuid,
+        error_message: &str,
+        retry_at: Option<DateTimeWithTimeZone>,
+    ) -> Result<IngestionJob> {
+        let status = if retry_at.is_some() { JobStatus::Failed } else { JobStatus::Dead };
         let stmt = Statement::from_sql_and_values(
             DbBackend::Postgres,
             r#"
-            INSERT INTO sessions (id, tenant_id, state, created_at, last_active_at, expires_at)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            ON CONFLICT (id) DO UPDATE SET
-                state = EXCLUDED.state,
-                last_active_at = EXCLUDED.last_active_at,
-                expires_at = EXCLUDED.expires_at
+            UPDATE ingestion_jobs
+            SET status = $2, error_message = $3, next_retry_at = $4, completed_at = NOW()
+            WHERE id = $1
             RETURNING *
             "#,
             vec![
-                session_id.into(),
-                tenant_id.into(),
-                session.state.clone().into_value().unwrap(),
-                now.into(),
-                now.into(),
-                expires.into(),
+                job_id.into(),
+                String::from(status).into(),
+                error_message.into(),
+                retry_at.into(),
             ],
         );
         
-        // For simplicity, just insert and ignore conflicts
-        session.insert(self.write_conn()).await.map_err(Into::into)
+        IngestionJobEntity::find()
+            .from_raw_sql(stmt)
+            .one(self.write_conn())
+            .await?
+            .ok_or_else(|| AppError::JobNotFound { id: job_id.to_string() })
     }
     
-    /// Find session by ID
-    pub async fn find_session(&self, session_id: Uuid) -> Result<Option<Session>> {
+    /// Re-send up to `limit` failed jobs whose retry is due
+    ///
+    /// Each job goes back to `pending` and its stored queue message is
+    /// written to the outbox for `queue`, in one statement, so a job is
+    /// never requeued twice. Jobs without a stored message (created before
+    /// messages were kept) are left alone.
+    ///
+    /// The message's envelope carries the job's attempt count, which also
+    /// gives each retry its own FIFO deduplication ID; a byte-identical
+    /// re-send would be dropped if it fell within the five-minute window.
+    pub async fn requeue_due_jobs(&self, limit: u64, queue: &str) -> Result<Vec<IngestionJob>> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            WITH due AS (
+                SELECT id FROM ingestion_jobs
+                WHERE status = 'failed'
+                  AND next_retry_at <= NOW()
+                  AND queue_message IS NOT NULL
+                ORDER BY next_retry_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            ),
+            requeued AS (
+                UPDATE ingestion_jobs j
+                SET status = 'pending', next_retry_at = NULL, completed_at = NULL
+                FROM due
+                WHERE j.id = due.id
+                RETURNING j.*
+            ),
+            outbox AS (
+                INSERT INTO outbox (id, queue, payload, attributes)
+                SELECT
+                    gen_random_uuid(), $2, queue_message,
+                    jsonb_set(COALESCE(queue_attributes, '{}'::jsonb), '{attempts}', to_jsonb(attempt_count))
+                FROM requeued
+            )
+            SELECT * FROM requeued
+            "#,
+            vec![(limit as i64).into(), queue.into()],
+        );
+        
+        IngestionJobEntity::find()
+            .from_raw_sql(stmt)
+            .all(self.write_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    // ========================================================================
+    // Job Checkpoint Operations
+    // ========================================================================
+    
+    /// Save a stage's output, replacing any earlier checkpoint for the stage
+    pub async fn save_checkpoint(
+        &self,
+        job_id: Uuid,
+        stage: CheckpointStage,
+        data: serde_json::Value,
+    ) -> Result<()> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO job_checkpoints (job_id, stage, data, created_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (job_id, stage) DO UPDATE
+            SET data = EXCLUDED.data, created_at = EXCLUDED.created_at
+            "#,
+            vec![job_id.into(), stage.as_str().into(), data.into()],
+        );
+        
+        self.write_conn().execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Find the checkpoint saved after `stage`
+    pub async fn find_checkpoint(
+        &self,
+        job_id: Uuid,
+        stage: CheckpointStage,
+    ) -> Result<Option<JobCheckpoint>> {
+        JobCheckpointEntity::find_by_id((job_id, stage.as_str().to_string()))
+            .one(self.write_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// All checkpoints for a job, oldest first
+    pub async fn find_checkpoints(&self, job_id: Uuid) -> Result<Vec<JobCheckpoint>> {
+        JobCheckpointEntity::find()
+            .filter(JobCheckpointColumn::JobId.eq(job_id))
+            .order_by_asc(JobCheckpointColumn::CreatedAt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Drop a job's checkpoints once they're no longer needed
+    pub async fn delete_checkpoints(&self, job_id: Uuid) -> Result<u64> {
+        let result = JobCheckpointEntity::delete_many()
+            .filter(JobCheckpointColumn::JobId.eq(job_id))
+            .exec(self.write_conn())
+            .await?;
+        Ok(result.rows_affected)
+    }
+    
+    // ========================================================================
+    // Outbox Operations
+    // ========================================================================
+    
+    async fn insert_outbox<C: ConnectionTrait>(
+        conn: &C,
+        queue: &str,
+        message: OutboxPayload,
+    ) -> Result<OutboxMessage> {
+        let message = OutboxActiveModel {
+            id: Set(Uuid::new_v4()),
+            queue: Set(queue.to_string()),
+            payload: Set(message.payload),
+            attributes: Set(Some(serde_json::json!(message.envelope))),
+            attempts: Set(0),
+            last_error: Set(None),
+            locked_until: Set(None),
+            created_at: Set(chrono::Utc::now().into()),
+            published_at: Set(None),
+        };
+        
+        message.insert(conn).await.map_err(Into::into)
+    }
+    
+    /// Lease a batch of unpublished outbox messages
+    ///
+    /// Rows are locked with `SKIP LOCKED` so several relays can run
+    /// concurrently; a lease that expires without the row being marked
+    /// published makes it eligible again (at-least-once delivery). Only rows
+    /// for `queues` are leased, so relays that publish to different queues
+    /// leave each other's messages alone.
+    pub async fn claim_outbox_batch(
+        &self,
+        queues: &[String],
+        limit: u64,
+        lease_secs: u64,
+    ) -> Result<Vec<OutboxMessage>> {
+        if queues.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE outbox
+            SET locked_until = NOW() + ($2 || ' seconds')::INTERVAL,
+                attempts = attempts + 1
+            WHERE id IN (
+                SELECT id FROM outbox
+                WHERE published_at IS NULL
+                  AND (locked_until IS NULL OR locked_until < NOW())
+                  AND queue = ANY($3)
+                ORDER BY created_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+            vec![
+                (limit as i64).into(),
+                (lease_secs as i64).to_string().into(),
+                queues.to_vec().into(),
+            ],
+        );
+        
+        let mut messages = OutboxEntity::find()
+            .from_raw_sql(stmt)
+            .all(self.write_conn())
+            .await?;
+        
+        messages.sort_by_key(|m| m.created_at);
+        Ok(messages)
+    }
+    
+    /// Mark outbox messages as published
+    pub async fn mark_outbox_published(&self, ids: &[Uuid]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        
+        OutboxEntity::update_many()
+            .col_expr(OutboxColumn::PublishedAt, Expr::current_timestamp().into())
+            .col_expr(OutboxColumn::LockedUntil, Expr::value(Option::<DateTimeWithTimeZone>::None))
+            .filter(OutboxColumn::Id.is_in(ids.iter().copied()))
+            .exec(self.write_conn())
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Record a publish failure; the message is retried once its lease expires
+    pub async fn mark_outbox_failed(&self, id: Uuid, error: &str) -> Result<()> {
+        OutboxEntity::update_many()
+            .col_expr(OutboxColumn::LastError, Expr::value(error.to_string()))
+            .filter(OutboxColumn::Id.eq(id))
+            .exec(self.write_conn())
+            .await?;
+        
+        Ok(())
+    }
+    
+    // ========================================================================
+    // Citation Operations
+    // ========================================================================
+    
+    /// Record a citation edge between two papers
+    pub async fn create_citation(
+        &self,
+        citing_paper_id: Uuid,
+        cited_paper_id: Uuid,
+        citation_context: Option<String>,
+        position_in_paper: Option<i32>,
+    ) -> Result<Citation> {
+        let citation = CitationActiveModel {
+            id: Set(Uuid::new_v4()),
+            citing_paper_id: Set(citing_paper_id),
+            cited_paper_id: Set(cited_paper_id),
+            citation_context: Set(citation_context),
+            position_in_paper: Set(position_in_paper),
+            created_at: Set(chrono::Utc::now().into()),
+        };
+        
+        citation.insert(self.write_conn()).await.map_err(Into::into)
+    }
+    
+    /// Resolve reference list entries to the tenant's live papers
+    ///
+    /// `references` holds each entry's normalized DOI and text. An entry
+    /// matches a paper by DOI, or else when the entry text contains the
+    /// paper's title (titles under 20 characters are too ambiguous). Returns
+    /// one match per entry, in order.
+    pub async fn resolve_references(
+        &self,
+        tenant_id: Uuid,
+        citing_paper_id: Uuid,
+        references: &[(Option<String>, String)],
+    ) -> Result<Vec<Option<Uuid>>> {
+        #[derive(FromQueryResult)]
+        struct Match {
+            idx: i32,
+            paper_id: Uuid,
+        }
+        
+        let mut resolved = vec![None; references.len()];
+        for (batch_no, batch) in references.chunks(REFERENCE_BATCH_SIZE).enumerate() {
+            let mut values: Vec<sea_orm::Value> = vec![tenant_id.into(), citing_paper_id.into()];
+            let rows: Vec<String> = batch.iter().enumerate().map(|(i, (doi, text))| {
+                values.push(((batch_no * REFERENCE_BATCH_SIZE + i) as i32).into());
+                values.push(doi.clone().into());
+                values.push(text.to_lowercase().into());
+                format!("(${}::int, ${}::text, ${}::text)", values.len() - 2, values.len() - 1, values.len())
+            }).collect();
+            
+            let stmt = Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!(
+                    r#"
+                    SELECT DISTINCT ON (r.idx) r.idx, p.id AS paper_id
+                    FROM (VALUES {}) AS r(idx, doi, entry)
+                    JOIN papers p ON p.tenant_id = $1
+                        AND p.id <> $2
+                        AND p.deleted_at IS NULL
+                        AND (
+                            r.doi IN (lower(p.metadata->>'doi'), lower(p.external_id))
+                            OR (length(p.title) >= 20 AND strpos(r.entry, lower(p.title)) > 0)
+                        )
+                    ORDER BY r.idx,
+                        (r.doi IN (lower(p.metadata->>'doi'), lower(p.external_id))) IS TRUE DESC,
+                        length(p.title) DESC
+                    "#,
+                    rows.join(", ")
+                ),
+                values,
+            );
+            
+            for m in Match::find_by_statement(stmt).all(self.read_conn()).await? {
+                if let Some(slot) = resolved.get_mut(m.idx as usize) {
+                    *slot = Some(m.paper_id);
+                }
+            }
+        }
+        Ok(resolved)
+    }
+    
+    /// Record a paper's outgoing citations
+    ///
+    /// `citations` holds each cited paper with the citing sentences and
+    /// position in the paper. Existing edges get the new context and
+    /// position, so re-parsing a paper is idempotent.
+    pub async fn upsert_citations(
+        &self,
+        citing_paper_id: Uuid,
+        citations: &[(Uuid, Option<String>, i32)],
+    ) -> Result<u64> {
+        let mut upserted = 0;
+        for batch in citations.chunks(REFERENCE_BATCH_SIZE) {
+            let mut values: Vec<sea_orm::Value> = vec![citing_paper_id.into()];
+            let rows: Vec<String> = batch.iter().map(|(cited_paper_id, context, position)| {
+                values.push((*cited_paper_id).into());
+                values.push(context.clone().into());
+                values.push((*position).into());
+                format!("($1, ${}::uuid, ${}::text, ${}::int)", values.len() - 2, values.len() - 1, values.len())
+            }).collect();
+            
+            let stmt = Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!(
+                    r#"
+                    INSERT INTO citations (citing_paper_id, cited_paper_id, citation_context, position_in_paper)
+                    VALUES {}
+                    ON CONFLICT ON CONSTRAINT citations_unique DO UPDATE
+                    SET citation_context = EXCLUDED.citation_context,
+                        position_in_paper = EXCLUDED.position_in_paper
+                    "#,
+                    rows.join(", ")
+                ),
+                values,
+            );
+            upserted += self.write_conn().execute(stmt).await?.rows_affected();
+        }
+        Ok(upserted)
+    }
+    
+    /// Get citations for a paper (both directions)
+    pub async fn get_citations(
+        &self,
+        paper_id: Uuid,
+    ) -> Result<(Vec<Citation>, Vec<Citation>)> {
+        let outgoing = CitationEntity::find()
+            .filter(CitationColumn::CitingPaperId.eq(paper_id))
+            .all(self.read_conn())
+            .await?;
+        
+        let incoming = CitationEntity::find()
+            .filter(CitationColumn::CitedPaperId.eq(paper_id))
+            .all(self.read_conn())
+            .await?;
+        
+        Ok((outgoing, incoming))
+    }
+    
+    /// Citations between the tenant's live papers that cite or are cited by
+    /// any of `paper_ids`
+    pub async fn citation_edges(&self, tenant_id: Uuid, paper_ids: &[Uuid]) -> Result<Vec<CitationEdge>> {
+        if paper_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let mut values: Vec<sea_orm::Value> = vec![tenant_id.into()];
+        values.extend(paper_ids.iter().map(|&id| id.into()));
+        let placeholders = (2..=values.len())
+            .map(|i| format!("${}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                SELECT c.citing_paper_id, c.cited_paper_id, c.citation_context
+                FROM citations c
+                JOIN papers citing ON citing.id = c.citing_paper_id
+                JOIN papers cited ON cited.id = c.cited_paper_id
+                WHERE citing.tenant_id = $1
+                  AND cited.tenant_id = $1
+                  AND citing.deleted_at IS NULL
+                  AND cited.deleted_at IS NULL
+                  AND (c.citing_paper_id IN ({ids}) OR c.cited_paper_id IN ({ids}))
+                ORDER BY c.citing_paper_id, c.position_in_paper NULLS LAST, c.cited_paper_id
+                "#,
+                ids = placeholders,
+            ),
+            values,
+        );
+        
+        CitationEdge::find_by_statement(stmt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Papers most related to `paper_id` by co-citation or bibliographic coupling
+    ///
+    /// Only citations between the tenant's live papers count.
+    pub async fn related_papers(
+        &self,
+        tenant_id: Uuid,
+        paper_id: Uuid,
+        relation: CitationRelation,
+        limit: u64,
+    ) -> Result<Vec<RelatedPaper>> {
+        // Co-cited papers share citing papers; coupled papers share cited ones
+        let (anchor, shared) = match relation {
+            CitationRelation::Cocitation => ("cited_paper_id", "citing_paper_id"),
+            CitationRelation::Coupling => ("citing_paper_id", "cited_paper_id"),
+        };
+        
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                WITH live AS (
+                    SELECT c.citing_paper_id, c.cited_paper_id
+                    FROM citations c
+                    JOIN papers citing ON citing.id = c.citing_paper_id
+                    JOIN papers cited ON cited.id = c.cited_paper_id
+                    WHERE citing.tenant_id = $2
+                      AND citing.deleted_at IS NULL
+                      AND cited.deleted_at IS NULL
+                ),
+                pairs AS (
+                    SELECT b.{anchor} AS paper_id, COUNT(*) AS shared
+                    FROM live a
+                    JOIN live b ON b.{shared} = a.{shared}
+                    WHERE a.{anchor} = $1 AND b.{anchor} <> $1
+                    GROUP BY b.{anchor}
+                ),
+                degree AS (
+                    SELECT {anchor} AS paper_id, COUNT(*) AS n
+                    FROM live
+                    WHERE {anchor} = $1 OR {anchor} IN (SELECT paper_id FROM pairs)
+                    GROUP BY {anchor}
+                )
+                SELECT
+                    p.id AS paper_id,
+                    p.title,
+                    pairs.shared,
+                    pairs.shared / sqrt(d.n::float8 * own.n) AS score
+                FROM pairs
+                JOIN papers p ON p.id = pairs.paper_id
+                JOIN degree d ON d.paper_id = pairs.paper_id
+                JOIN degree own ON own.paper_id = $1
+                WHERE p.tenant_id = $2
+                ORDER BY score DESC, pairs.shared DESC, p.id
+                LIMIT $3
+                "#,
+                anchor = anchor,
+                shared = shared,
+            ),
+            vec![paper_id.into(), tenant_id.into(), (limit as i64).into()],
+        );
+        
+        RelatedPaper::find_by_statement(stmt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Papers whose citations are newer than their PageRank, by tenant
+    ///
+    /// Rank flows from citing to cited papers, so only the papers downstream of
+    /// these need new scores.
+    pub async fn pagerank_pending_papers(&self) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        let stmt = Statement::from_string(
+            DbBackend::Postgres,
+            r#"
+            SELECT DISTINCT p.tenant_id, p.id
+            FROM citations c
+            JOIN papers p ON p.id = c.citing_paper_id
+            WHERE p.deleted_at IS NULL
+              AND (p.pagerank_updated_at IS NULL OR c.created_at > p.pagerank_updated_at)
+            "#,
+        );
+        
+        let mut pending: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for row in self.read_conn().query_all(stmt).await? {
+            let tenant_id: Uuid = row.try_get("", "tenant_id")?;
+            pending.entry(tenant_id).or_default().push(row.try_get("", "id")?);
+        }
+        Ok(pending)
+    }
+    
+    /// A tenant's citation graph, for PageRank
+    pub async fn citation_graph(&self, tenant_id: Uuid) -> Result<TenantCitations> {
+        let papers = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT id, title
+            FROM papers
+            WHERE tenant_id = $1 AND status = 'processed' AND deleted_at IS NULL
+            "#,
+            vec![tenant_id.into()],
+        );
+        let edges = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT c.citing_paper_id, c.cited_paper_id
+            FROM citations c
+            INNER JOIN papers p1 ON c.citing_paper_id = p1.id
+            INNER JOIN papers p2 ON c.cited_paper_id = p2.id
+            WHERE p1.tenant_id = $1
+              AND p1.deleted_at IS NULL
+              AND p2.deleted_at IS NULL
+            "#,
+            vec![tenant_id.into()],
+        );
+        
+        let conn = self.read_conn();
+        let papers = conn.query_all(papers).await?
+            .iter()
+            .map(|row| Ok((row.try_get("", "id")?, row.try_get("", "title")?)))
+            .collect::<Result<Vec<(Uuid, String)>>>()?;
+        let edges = conn.query_all(edges).await?
+            .iter()
+            .map(|row| Ok((row.try_get("", "citing_paper_id")?, row.try_get("", "cited_paper_id")?)))
+            .collect::<Result<Vec<(Uuid, Uuid)>>>()?;
+        Ok(TenantCitations { papers, edges })
+    }
+    
+    /// Persisted PageRank of a tenant's scored papers
+    pub async fn paper_pageranks(&self, tenant_id: Uuid) -> Result<HashMap<Uuid, f64>> {
+        let scores = PaperEntity::find()
+            .select_only()
+            .column(PaperColumn::Id)
+            .column(PaperColumn::Pagerank)
+            .filter(PaperColumn::TenantId.eq(tenant_id))
+            .filter(PaperColumn::DeletedAt.is_null())
+            .filter(PaperColumn::Pagerank.is_not_null())
+            .into_tuple::<(Uuid, f64)>()
+            .all(self.read_conn())
+            .await?;
+        Ok(scores.into_iter().collect())
+    }
+    
+    /// Persist PageRank scores computed from the graph as of `computed_at`
+    pub async fn save_pageranks(
+        &self,
+        scores: &[(Uuid, f64)],
+        computed_at: DateTimeWithTimeZone,
+    ) -> Result<()> {
+        for batch in scores.chunks(PAGERANK_BATCH_SIZE) {
+            let mut values: Vec<sea_orm::Value> = vec![computed_at.into()];
+            let rows: Vec<String> = batch.iter().map(|&(paper_id, score)| {
+                values.push(paper_id.into());
+                values.push(score.into());
+                format!("(${}::uuid, ${}::float8)", values.len() - 1, values.len())
+            }).collect();
+            
+            let stmt = Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!(
+                    r#"
+                    UPDATE papers AS p
+                    SET pagerank = v.score, pagerank_updated_at = $1
+                    FROM (VALUES {}) AS v(id, score)
+                    WHERE p.id = v.id
+                    "#,
+                    rows.join(", ")
+                ),
+                values,
+            );
+            self.write_conn().execute(stmt).await?;
+        }
+        Ok(())
+    }
+    
+    // ========================================================================
+    // Review Job Operations
+    // ========================================================================
+    
+    /// Create a literature review job
+    pub async fn create_review_job(&self, tenant_id: Uuid, topic: &str) -> Result<ReviewJob> {
+        let job = ReviewJobActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            topic: Set(topic.to_string()),
+            status: Set(ReviewStatus::Pending.as_str().to_string()),
+            sections_total: Set(0),
+            sections_written: Set(0),
+            review: Set(None),
+            error_message: Set(None),
+            created_at: Set(chrono::Utc::now().into()),
+            started_at: Set(None),
+            completed_at: Set(None),
+        };
+        
+        job.insert(self.write_conn()).await.map_err(Into::into)
+    }
+    
+    /// Find review job by ID
+    pub async fn find_review_job(&self, id: Uuid) -> Result<Option<ReviewJob>> {
+        ReviewJobEntity::find_by_id(id)
+            .one(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Move a review job to `status`
+    ///
+    /// `started_at` is set on the first non-pending status; terminal
+    /// statuses set `completed_at`.
+    pub async fn update_review_status(
+        &self,
+        job_id: Uuid,
+        status: ReviewStatus,
+        error_message: Option<String>,
+    ) -> Result<()> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE review_jobs SET
+                status = $1,
+                error_message = COALESCE($2, error_message),
+                started_at = COALESCE(started_at, NOW()),
+                completed_at = CASE WHEN $1 IN ('completed', 'failed') THEN NOW() ELSE completed_at END
+            WHERE id = $3 AND status NOT IN ('completed', 'failed')
+            "#,
+            vec![status.as_str().into(), error_message.into(), job_id.into()],
+        );
+        
+        self.write_conn().execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Record how many review sections are written out of how many
+    pub async fn update_review_progress(
+        &self,
+        job_id: Uuid,
+        sections_written: i32,
+        sections_total: i32,
+    ) -> Result<()> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE review_jobs SET sections_written = $1, sections_total = $2 WHERE id = $3",
+            vec![sections_written.into(), sections_total.into(), job_id.into()],
+        );
+        
+        self.write_conn().execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Store the finished review and complete the job
+    pub async fn complete_review_job(&self, job_id: Uuid, review: serde_json::Value) -> Result<()> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE review_jobs SET
+                status = 'completed',
+                review = $1,
+                sections_written = sections_total,
+                completed_at = NOW()
+            WHERE id = $2
+            "#,
+            vec![review.into(), job_id.into()],
+        );
+        
+        self.write_conn().execute(stmt).await?;
+        Ok(())
+    }
+    
+    // ========================================================================
+    // Re-index Job Operations
+    // ========================================================================
+    
+    /// Create a re-indexing job; `tenant_id` of `None` covers all tenants
+    ///
+    /// Fails with [`AppError::Duplicate`] while another job is active.
+    pub async fn create_reindex_job(
+        &self,
+        tenant_id: Option<Uuid>,
+        text_search: bool,
+        vector_indexes: bool,
+        requested_by: &str,
+    ) -> Result<ReindexJob> {
+        let job = ReindexJobActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            text_search: Set(text_search),
+            vector_indexes: Set(vector_indexes),
+            status: Set(ReindexStatus::Pending.as_str().to_string()),
+            chunks_total: Set(0),
+            chunks_done: Set(0),
+            indexes_total: Set(0),
+            indexes_done: Set(0),
+            requested_by: Set(requested_by.to_string()),
+            error_message: Set(None),
+            created_at: Set(chrono::Utc::now().into()),
+            started_at: Set(None),
+            completed_at: Set(None),
+            heartbeat_at: Set(None),
+        };
+        
+        // A unique index over active jobs turns away concurrent requests
+        job.insert(self.write_conn()).await.map_err(|e| match e.sql_err() {
+            Some(sea_orm::SqlErr::UniqueConstraintViolation(_)) => AppError::Duplicate {
+                message: "A re-indexing job is already running".to_string(),
+            },
+            _ => e.into(),
+        })
+    }
+    
+    /// Find re-indexing job by ID
+    pub async fn find_reindex_job(&self, id: Uuid) -> Result<Option<ReindexJob>> {
+        ReindexJobEntity::find_by_id(id)
+            .one(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// A re-indexing job that is still pending or running, if any
+    pub async fn find_active_reindex_job(&self) -> Result<Option<ReindexJob>> {
+        ReindexJobEntity::find()
+            .filter(ReindexJobColumn::Status.is_not_in([
+                ReindexStatus::Completed.as_str(),
+                ReindexStatus::Failed.as_str(),
+            ]))
+            .order_by_desc(ReindexJobColumn::CreatedAt)
+            .one(self.write_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Move a re-indexing job to `status`
+    ///
+    /// `started_at` is set on the first non-pending status; terminal
+    /// statuses set `completed_at` and are final.
+    pub async fn update_reindex_status(
+        &self,
+        job_id: Uuid,
+        status: ReindexStatus,
+        error_message: Option<String>,
+    ) -> Result<()> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE reindex_jobs SET
+                status = $1,
+                error_message = COALESCE($2, error_message),
+                started_at = COALESCE(started_at, NOW()),
+                completed_at = CASE WHEN $1 IN ('completed', 'failed') THEN NOW() ELSE completed_at END
+            WHERE id = $3
+            "#,
+            vec![status.as_str().into(), error_message.into(), job_id.into()],
+        );
+        
+        self.write_conn().execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Lease the oldest pending re-indexing job no worker has claimed, or
+    /// the job `job_id` if it is still unclaimed
+    pub async fn claim_reindex_job(&self, job_id: Option<Uuid>) -> Result<Option<ReindexJob>> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE reindex_jobs SET heartbeat_at = NOW()
+            WHERE id = (
+                SELECT id FROM reindex_jobs
+                WHERE status = 'pending' AND heartbeat_at IS NULL
+                AND ($1::uuid IS NULL OR id = $1)
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+            vec![job_id.into()],
+        );
+        
+        ReindexJobEntity::find()
+            .from_raw_sql(stmt)
+            .one(self.write_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Renew the lease on a claimed re-indexing job
+    ///
+    /// Returns false once the job is no longer active, e.g. because it was
+    /// failed as stale while its worker couldn't reach the database.
+    pub async fn heartbeat_reindex_job(&self, job_id: Uuid) -> Result<bool> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE reindex_jobs SET heartbeat_at = NOW() WHERE id = $1 AND status NOT IN ('completed', 'failed')",
+            vec![job_id.into()],
+        );
+        
+        let result = self.write_conn().execute(stmt).await?;
+        Ok(result.rows_affected() == 1)
+    }
+    
+    /// Fail active re-indexing jobs whose lease wasn't renewed for
+    /// `lease_secs`, because their worker stopped; returns their IDs
+    pub async fn fail_stale_reindex_jobs(&self, lease_secs: u64) -> Result<Vec<Uuid>> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE reindex_jobs SET
+                status = 'failed',
+                error_message = 'The worker running the job stopped before it finished',
+                completed_at = NOW()
+            WHERE status NOT IN ('completed', 'failed')
+            AND COALESCE(heartbeat_at, created_at) < NOW() - ($1 || ' seconds')::INTERVAL
+            RETURNING id
+            "#,
+            vec![(lease_secs as i64).to_string().into()],
+        );
+        
+        let rows = self.write_conn().query_all(stmt).await?;
+        rows.iter()
+            .map(|row| row.try_get("", "id").map_err(Into::into))
+            .collect()
+    }
+    
+    /// Run a re-indexing job to completion
+    ///
+    /// Rewrites chunks in batches, then rebuilds vector indexes one at a time,
+    /// recording progress on the job. On error the job is marked failed.
+    pub async fn run_reindex_job(&self, job: &ReindexJob) -> Result<()> {
+        let result = self.reindex(job).await;
+        if let Err(e) = &result {
+            tracing::error!(job_id = %job.id, error = %e, "Re-indexing failed");
+            if let Err(e) = self.update_reindex_status(job.id, ReindexStatus::Failed, Some(e.to_string())).await {
+                tracing::error!(job_id = %job.id, error = %e, "Failed to record re-indexing failure");
+            }
+        }
+        result
+    }
+    
+    async fn reindex(&self, job: &ReindexJob) -> Result<()> {
+        if job.text_search {
+            self.update_reindex_status(job.id, ReindexStatus::TextSearch, None).await?;
+            let total = self.count_tenant_chunks(job.tenant_id).await?;
+            self.update_reindex_chunks(job.id, 0, total).await?;
+            
+            let mut done = 0i64;
+            let mut after = None;
+            loop {
+                let (rewritten, last) = self
+                    .rebuild_text_search_batch(job.tenant_id, after, REINDEX_BATCH_SIZE)
+                    .await?;
+                done += rewritten as i64;
+                // Chunks ingested meanwhile can push `done` past the count
+                self.update_reindex_chunks(job.id, done, total.max(done)).await?;
+                
+                match last {
+                    Some(id) if rewritten == REINDEX_BATCH_SIZE => after = Some(id),
+                    _ => break,
+                }
+            }
+        }
+        
+        if job.vector_indexes {
+            self.update_reindex_status(job.id, ReindexStatus::VectorIndexes, None).await?;
+            let indexes = self.chunk_vector_indexes().await?;
+            let total = indexes.len() as i32;
+            self.update_reindex_indexes(job.id, 0, total).await?;
+            
+            for (i, index) in indexes.iter().enumerate() {
+                tracing::info!(job_id = %job.id, index = %index, "Rebuilding vector index");
+                self.reindex_concurrently(index).await?;
+                self.update_reindex_indexes(job.id, i as i32 + 1, total).await?;
+            }
+        }
+        
+        self.update_reindex_status(job.id, ReindexStatus::Completed, None).await?;
+        tracing::info!(job_id = %job.id, "Re-indexing completed");
+        Ok(())
+    }
+    
+    /// Record how many chunks are rewritten out of how many
+    pub async fn update_reindex_chunks(&self, job_id: Uuid, done: i64, total: i64) -> Result<()> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE reindex_jobs SET chunks_done = $1, chunks_total = $2 WHERE id = $3",
+            vec![done.into(), total.into(), job_id.into()],
+        );
+        
+        self.write_conn().execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Record how many vector indexes are rebuilt out of how many
+    pub async fn update_reindex_indexes(&self, job_id: Uuid, done: i32, total: i32) -> Result<()> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE reindex_jobs SET indexes_done = $1, indexes_total = $2 WHERE id = $3",
+            vec![done.into(), total.into(), job_id.into()],
+        );
+        
+        self.write_conn().execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Chunks belonging to a tenant, or all chunks for `None`
+    pub async fn count_tenant_chunks(&self, tenant_id: Option<Uuid>) -> Result<i64> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COUNT(*) AS total FROM chunks WHERE $1::uuid IS NULL OR tenant_id = $1",
+            vec![tenant_id.into()],
+        );
+        
+        let row = self.read_conn().query_one(stmt).await?;
+        Ok(match row {
+            Some(row) => row.try_get("", "total")?,
+            None => 0,
+        })
+    }
+    
+    /// Rewrite the next `limit` chunks after `after`, ordered by ID, so
+    /// their generated `text_search_vector` is recomputed
+    ///
+    /// Returns how many chunks were rewritten and the last ID, to pass as
+    /// `after` for the next batch; `None` once every chunk is done.
+    pub async fn rebuild_text_search_batch(
+        &self,
+        tenant_id: Option<Uuid>,
+        after: Option<Uuid>,
+        limit: u64,
+    ) -> Result<(u64, Option<Uuid>)> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            WITH batch AS (
+                SELECT id, paper_id FROM chunks
+                WHERE ($1::uuid IS NULL OR tenant_id = $1)
+                AND ($2::uuid IS NULL OR id > $2)
+                ORDER BY id
+                LIMIT $3
+            )
+            UPDATE chunks c SET content = c.content
+            FROM batch
+            WHERE c.id = batch.id AND c.paper_id = batch.paper_id
+            RETURNING c.id
+            "#,
+            vec![tenant_id.into(), after.into(), (limit as i64).into()],
+        );
+        
+        let rows = self.write_conn().query_all(stmt).await?;
+        let ids = rows.iter()
+            .map(|row| row.try_get::<Uuid>("", "id"))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok((ids.len() as u64, ids.into_iter().max()))
+    }
+    
+    /// Names of the HNSW and IVFFlat indexes on chunks and its partitions
+    pub async fn chunk_vector_indexes(&self) -> Result<Vec<String>> {
+        let stmt = Statement::from_string(
+            DbBackend::Postgres,
+            r#"
+            SELECT indexname FROM pg_indexes
+            WHERE schemaname = current_schema()
+            AND tablename LIKE 'chunks%'
+            AND (indexdef ILIKE '%USING hnsw%' OR indexdef ILIKE '%USING ivfflat%')
+            ORDER BY indexname
+            "#,
+        );
+        
+        let rows = self.read_conn().query_all(stmt).await?;
+        rows.iter()
+            .map(|row| row.try_get("", "indexname").map_err(Into::into))
+            .collect()
+    }
+    
+    /// Rebuild an index without blocking writes to its table
+    ///
+    /// `REINDEX CONCURRENTLY` can't run inside a transaction, so this goes
+    /// straight to the primary connection.
+    pub async fn reindex_concurrently(&self, index_name: &str) -> Result<()> {
+        let sql = format!(
+            "REINDEX INDEX CONCURRENTLY \"{}\"",
+            index_name.replace('"', "\"\"")
+        );
+        self.write_conn()
+            .execute(Statement::from_string(DbBackend::Postgres, sql))
+            .await?;
+        Ok(())
+    }
+    
+    // ========================================================================
+    // Session Operations
+    // ========================================================================
+    
+    /// Create or update a session, pushing its expiry `ttl_minutes` out
+    ///
+    /// Fails with `TenantMismatch` when the ID belongs to another tenant's
+    /// session, which is left untouched.
+    pub async fn upsert_session(
+        &self,
+        tenant_id: Uuid,
+        session_id: Uuid,
+        state: serde_json::Value,
+        ttl_minutes: i64,
+    ) -> Result<Session> {
+        let now = chrono::Utc::now();
+        let expires = now + chrono::Duration::minutes(ttl_minutes);
+        
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO sessions (id, tenant_id, state, created_at, last_active_at, expires_at)
+            VALUES ($1, $2, $3, $4, $4, $5)
+            ON CONFLICT (id) DO UPDATE SET
+                state = EXCLUDED.state,
+                last_active_at = EXCLUDED.last_active_at,
+                expires_at = EXCLUDED.expires_at
+            WHERE sessions.tenant_id = EXCLUDED.tenant_id
+            RETURNING *
+            "#,
+            vec![
+                session_id.into(),
+                tenant_id.into(),
+                state.into(),
+                DateTimeWithTimeZone::from(now).into(),
+                DateTimeWithTimeZone::from(expires).into(),
+            ],
+        );
+        
+        SessionEntity::find()
+            .from_raw_sql(stmt)
+            .one(self.write_conn())
+            .await?
+            .ok_or(AppError::TenantMismatch)
+    }
+    
+    /// Delete sessions that expired before `now`, `batch_size` rows per
+    /// statement so a large backlog doesn't hold long locks
+    pub async fn delete_expired_sessions(&self, batch_size: u64) -> Result<u64> {
+        let mut deleted = 0;
+        loop {
+            let stmt = Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                DELETE FROM sessions
+                WHERE id IN (
+                    SELECT id FROM sessions
+                    WHERE expires_at < NOW()
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                "#,
+                vec![(batch_size as i64).into()],
+            );
+            let rows = self.write_conn().execute(stmt).await?.rows_affected();
+            deleted += rows;
+            if rows < batch_size {
+                return Ok(deleted);
+            }
+        }
+    }
+    
+    /// Find session by ID
+    pub async fn find_session(&self, session_id: Uuid) -> Result<Option<Session>> {
         SessionEntity::find_by_id(session_id)
             .one(self.read_conn())
             .await
             .map_err(Into::into)
     }
+    
+    // ========================================================================
+    // Feedback Operations
+    // ========================================================================
+    
+    /// Record a thumbs up (`rating` 1) or down (-1) on a search result
+    pub async fn record_feedback(
+        &self,
+        tenant_id: Uuid,
+        session_id: Option<Uuid>,
+        query: &str,
+        chunk_id: Uuid,
+        rating: i16,
+    ) -> Result<SearchFeedback> {
+        SearchFeedbackActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            session_id: Set(session_id),
+            query: Set(normalize_query(query)),
+            chunk_id: Set(chunk_id),
+            rating: Set(rating),
+            created_at: Set(chrono::Utc::now().into()),
+        }
+        .insert(self.write_conn())
+        .await
+        .map_err(Into::into)
+    }
+    
+    /// A tenant's feedback on any of `chunk_ids`
+    pub async fn feedback_for_chunks(&self, tenant_id: Uuid, chunk_ids: &[Uuid]) -> Result<Vec<SearchFeedback>> {
+        if chunk_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        SearchFeedbackEntity::find()
+            .filter(SearchFeedbackColumn::TenantId.eq(tenant_id))
+            .filter(SearchFeedbackColumn::ChunkId.is_in(chunk_ids.iter().copied()))
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Whether a chunk exists in one of the tenant's papers
+    pub async fn chunk_belongs_to_tenant(&self, chunk_id: Uuid, tenant_id: Uuid) -> Result<bool> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT 1
+            FROM chunks c
+            JOIN papers p ON c.paper_id = p.id
+            WHERE c.id = $1 AND p.tenant_id = $2 AND p.deleted_at IS NULL
+            "#,
+            vec![chunk_id.into(), tenant_id.into()],
+        );
+        
+        Ok(self.read_conn().query_one(stmt).await?.is_some())
+    }
+    
+    // ========================================================================
+    // Query Analytics Operations
+    // ========================================================================
+    
+    /// Log a search for query analytics
+    pub async fn record_query_log(
+        &self,
+        tenant_id: Uuid,
+        session_id: Option<Uuid>,
+        query: &str,
+        search_mode: &str,
+        result_count: usize,
+        latency_ms: u64,
+    ) -> Result<QueryLog> {
+        let query_text = normalize_query(query);
+        QueryLogActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            session_id: Set(session_id),
+            query_hash: Set(hex::encode(Sha256::digest(query_text.as_bytes()))),
+            query_text: Set(query_text),
+            search_mode: Set(search_mode.to_string()),
+            result_count: Set(result_count.min(i32::MAX as usize) as i32),
+            latency_ms: Set(latency_ms.min(i32::MAX as u64) as i32),
+            clicked_results: Set(Some(serde_json::json!([]))),
+            created_at: Set(chrono::Utc::now().into()),
+        }
+        .insert(self.write_conn())
+        .await
+        .map_err(Into::into)
+    }
+    
+    /// Record a non-search event in a session
+    pub async fn record_session_event(
+        &self,
+        tenant_id: Uuid,
+        session_id: Option<Uuid>,
+        event_type: &str,
+        data: serde_json::Value,
+    ) -> Result<SessionEvent> {
+        SessionEventActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            session_id: Set(session_id),
+            event_type: Set(event_type.to_string()),
+            data: Set(data),
+            created_at: Set(chrono::Utc::now().into()),
+        }
+        .insert(self.write_conn())
+        .await
+        .map_err(Into::into)
+    }
+    
+    /// A tenant's most frequent queries since `since`, most searched first
+    ///
+    /// With `zero_results_only`, only searches that returned nothing count.
+    pub async fn top_queries(
+        &self,
+        tenant_id: Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+        zero_results_only: bool,
+        limit: u64,
+    ) -> Result<Vec<TopQuery>> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT
+                MIN(query_text) AS query,
+                COUNT(*) AS searches,
+                COUNT(*) FILTER (WHERE result_count = 0) AS zero_result_searches,
+                AVG(latency_ms)::float8 AS avg_latency_ms,
+                MAX(created_at) AS last_searched_at
+            FROM query_logs
+            WHERE tenant_id = $1
+              AND created_at >= $2
+              AND (NOT $3 OR result_count = 0)
+            GROUP BY query_hash
+            ORDER BY searches DESC, last_searched_at DESC
+            LIMIT $4
+            "#,
+            vec![tenant_id.into(), since.into(), zero_results_only.into(), (limit as i64).into()],
+        );
+        
+        TopQuery::find_by_statement(stmt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// A tenant's search and zero-result search counts since `since`
+    pub async fn query_totals(
+        &self,
+        tenant_id: Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<QueryTotals> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT
+                COUNT(*) AS searches,
+                COUNT(*) FILTER (WHERE result_count = 0) AS zero_result_searches
+            FROM query_logs
+            WHERE tenant_id = $1 AND created_at >= $2
+            "#,
+            vec![tenant_id.into(), since.into()],
+        );
+        
+        Ok(QueryTotals::find_by_statement(stmt)
+            .one(self.read_conn())
+            .await?
+            .unwrap_or_default())
+    }
+    
+    // ========================================================================
+    // Lexical Index Operations
+    // ========================================================================
+    
+    /// Tenants with at least one live paper
+    pub async fn tenants_with_papers(&self) -> Result<Vec<Uuid>> {
+        let stmt = Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT DISTINCT tenant_id FROM papers WHERE deleted_at IS NULL",
+        );
+        
+        let rows = self.read_conn().query_all(stmt).await?;
+        rows.iter()
+            .map(|row| row.try_get("", "tenant_id").map_err(Into::into))
+            .collect()
+    }
+    
+    /// A tenant's live chunks, and how many of them sort after `after`
+    ///
+    /// Chunks are ordered by `(created_at, id)`.
+    pub async fn count_index_chunks(
+        &self,
+        tenant_id: Uuid,
+        after: Option<(DateTimeWithTimeZone, Uuid)>,
+    ) -> Result<(i64, i64)> {
+        let (after_time, after_id) = after.unzip();
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT
+                COUNT(*) AS total,
+                COUNT(*) FILTER (
+                    WHERE $2::timestamptz IS NULL OR (c.created_at, c.id) > ($2, $3)
+                ) AS newer
+            FROM chunks c
+            JOIN papers p ON c.paper_id = p.id
+            WHERE p.tenant_id = $1 AND p.deleted_at IS NULL
+            "#,
+            vec![tenant_id.into(), after_time.into(), after_id.into()],
+        );
+        
+        let row = self.read_conn().query_one(stmt).await?;
+        Ok(match row {
+            Some(row) => (row.try_get("", "total")?, row.try_get("", "newer")?),
+            None => (0, 0),
+        })
+    }
+    
+    /// Next page of a tenant's live chunks after `after`, ordered by
+    /// `(created_at, id)`
+    pub async fn list_index_chunks(
+        &self,
+        tenant_id: Uuid,
+        after: Option<(DateTimeWithTimeZone, Uuid)>,
+        limit: u64,
+    ) -> Result<Vec<IndexChunk>> {
+        let (after_time, after_id) = after.unzip();
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT c.id, c.content, c.created_at
+            FROM chunks c
+            JOIN papers p ON c.paper_id = p.id
+            WHERE p.tenant_id = $1 AND p.deleted_at IS NULL
+            AND ($2::timestamptz IS NULL OR (c.created_at, c.id) > ($2, $3))
+            ORDER BY c.created_at, c.id
+            LIMIT $4
+            "#,
+            vec![tenant_id.into(), after_time.into(), after_id.into(), (limit as i64).into()],
+        );
+        
+        IndexChunk::find_by_statement(stmt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Next page of a tenant's live chunks for export, in the same
+    /// `(created_at, id)` order as [`Repository::list_index_chunks`]
+    pub async fn export_chunks(
+        &self,
+        tenant_id: Uuid,
+        after: Option<(DateTimeWithTimeZone, Uuid)>,
+        include_embeddings: bool,
+        limit: u64,
+    ) -> Result<Vec<ExportChunk>> {
+        let (after_time, after_id) = after.unzip();
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT
+                c.id,
+                c.paper_id,
+                p.title as paper_title,
+                c.chunk_index,
+                c.chunk_type,
+                c.content,
+                c.metadata,
+                c.token_count,
+                c.embedding_model,
+                CASE WHEN $4 THEN c.embedding::text END as embedding,
+                c.created_at
+            FROM chunks c
+            JOIN papers p ON c.paper_id = p.id
+            WHERE p.tenant_id = $1 AND p.deleted_at IS NULL
+            AND ($2::timestamptz IS NULL OR (c.created_at, c.id) > ($2, $3))
+            ORDER BY c.created_at, c.id
+            LIMIT $5
+            "#,
+            vec![
+                tenant_id.into(),
+                after_time.into(),
+                after_id.into(),
+                include_embeddings.into(),
+                (limit as i64).into(),
+            ],
+        );
+        
+        ExportChunk::find_by_statement(stmt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Search results for a tenant's live chunks among `chunk_ids`
+    ///
+    /// Results come back unordered with a score of 0; ids of deleted or
+    /// foreign chunks are skipped.
+    pub async fn chunk_results_by_ids(&self, tenant_id: Uuid, chunk_ids: &[Uuid]) -> Result<Vec<ChunkResult>> {
+        if chunk_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let placeholders = (0..chunk_ids.len())
+            .map(|i| format!("${}", i + 2))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            r#"
+            SELECT 
+                c.id as chunk_id,
+                c.paper_id,
+                p.title as paper_title,
+                c.content,
+                c.chunk_index,
+                c.embedding_model,
+                c.chunk_type,
+                c.metadata
+            FROM chunks c
+            JOIN papers p ON c.paper_id = p.id
+            WHERE p.tenant_id = $1
+            AND c.id IN ({})
+            AND p.deleted_at IS NULL
+            "#,
+            placeholders
+        );
+        
+        let mut values: Vec<sea_orm::Value> = vec![tenant_id.into()];
+        values.extend(chunk_ids.iter().map(|id| (*id).into()));
+        let stmt = Statement::from_sql_and_values(DbBackend::Postgres, &sql, values);
+        
+        let results = self.read_conn()
+            .query_all(stmt)
+            .await?
+            .into_iter()
+            .filter_map(|row| {
+                Some(ChunkResult {
+                    chunk_id: row.try_get("", "chunk_id").ok()?,
+                    paper_id: row.try_get("", "paper_id").ok()?,
+                    paper_title: row.try_get("", "paper_title").ok()?,
+                    content: row.try_get("", "content").ok()?,
+                    chunk_index: row.try_get("", "chunk_index").ok()?,
+                    score: 0.0,
+                    embedding_model: row.try_get("", "embedding_model").ok()?,
+                    chunk_type: row.try_get("", "chunk_type").ok()?,
+                    provenance: row
+                        .try_get::<serde_json::Value>("", "metadata")
+                        .ok()
+                        .and_then(|metadata| Provenance::from_metadata(&metadata)),
+                })
+            })
+            .collect();
+        
+        Ok(results)
+    }
+    
+    // ========================================================================
+    // Saved Search Operations
+    // ========================================================================
+    
+    /// Save a search; only chunks indexed from now on are new to it
+    pub async fn create_saved_search(&self, tenant_id: Uuid, search: NewSavedSearch) -> Result<SavedSearch> {
+        let now = chrono::Utc::now();
+        SavedSearchActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            name: Set(search.name),
+            query: Set(search.query),
+            min_score: Set(search.min_score),
+            webhook_url: Set(search.webhook_url),
+            webhook_secret: Set(search.webhook_secret),
+            email: Set(search.email),
+            last_run_at: Set(now.into()),
+            created_at: Set(now.into()),
+        }
+        .insert(self.write_conn())
+        .await
+        .map_err(Into::into)
+    }
+    
+    /// A tenant's saved searches, oldest first
+    pub async fn list_saved_searches(&self, tenant_id: Uuid) -> Result<Vec<SavedSearch>> {
+        SavedSearchEntity::find()
+            .filter(SavedSearchColumn::TenantId.eq(tenant_id))
+            .order_by_asc(SavedSearchColumn::CreatedAt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Delete one of a tenant's saved searches; `false` if there was none
+    pub async fn delete_saved_search(&self, tenant_id: Uuid, id: Uuid) -> Result<bool> {
+        let result = SavedSearchEntity::delete_many()
+            .filter(SavedSearchColumn::Id.eq(id))
+            .filter(SavedSearchColumn::TenantId.eq(tenant_id))
+            .exec(self.write_conn())
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+    
+    /// Saved searches with a webhook or email address to alert
+    pub async fn list_alerting_saved_searches(&self) -> Result<Vec<SavedSearch>> {
+        SavedSearchEntity::find()
+            .filter(
+                Condition::any()
+                    .add(SavedSearchColumn::WebhookUrl.is_not_null())
+                    .add(SavedSearchColumn::Email.is_not_null()),
+            )
+            .order_by_asc(SavedSearchColumn::LastRunAt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Record that a saved search has seen every chunk indexed up to `at`
+    pub async fn mark_saved_search_run(&self, id: Uuid, at: DateTimeWithTimeZone) -> Result<()> {
+        SavedSearchEntity::update_many()
+            .col_expr(SavedSearchColumn::LastRunAt, Expr::value(at))
+            .filter(SavedSearchColumn::Id.eq(id))
+            .exec(self.write_conn())
+            .await?;
+        Ok(())
+    }
+    
+    /// BM25 search over a tenant's live chunks indexed in `(since, until]`
+    pub async fn bm25_search_new_chunks(
+        &self,
+        tenant_id: Uuid,
+        query: &str,
+        since: DateTimeWithTimeZone,
+        until: DateTimeWithTimeZone,
+        limit: usize,
+    ) -> Result<Vec<ChunkResult>> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT 
+                c.id as chunk_id,
+                c.paper_id,
+                p.title as paper_title,
+                c.content,
+                c.chunk_index,
+                c.embedding_model,
+                ts_rank_cd(c.text_search_vector, plainto_tsquery('english', $2))::float8 as score,
+                c.chunk_type,
+                c.metadata
+            FROM chunks c
+            JOIN papers p ON c.paper_id = p.id
+            WHERE p.tenant_id = $1
+            AND c.text_search_vector @@ plainto_tsquery('english', $2)
+            AND c.created_at > $3 AND c.created_at <= $4
+            AND p.deleted_at IS NULL
+            ORDER BY score DESC
+            LIMIT $5
+            "#,
+            vec![tenant_id.into(), query.into(), since.into(), until.into(), (limit as i32).into()],
+        );
+        
+        let results = self.pool
+            .search_query("bm25_search_new_chunks", stmt)
+            .await?
+            .into_iter()
+            .filter_map(|row| {
+                Some(ChunkResult {
+                    chunk_id: row.try_get("", "chunk_id").ok()?,
+                    paper_id: row.try_get("", "paper_id").ok()?,
+                    paper_title: row.try_get("", "paper_title").ok()?,
+                    content: row.try_get("", "content").ok()?,
+                    chunk_index: row.try_get("", "chunk_index").ok()?,
+                    score: row.try_get("", "score").ok()?,
+                    embedding_model: row.try_get("", "embedding_model").ok()?,
+                    chunk_type: row.try_get("", "chunk_type").ok()?,
+                    provenance: row
+                        .try_get::<serde_json::Value>("", "metadata")
+                        .ok()
+                        .and_then(|metadata| Provenance::from_metadata(&metadata)),
+                })
+            })
+            .collect();
+        
+        Ok(results)
+    }
+    
+    // ========================================================================
+    // Collection Operations
+    // ========================================================================
+    
+    /// Create a collection
+    pub async fn create_collection(
+        &self,
+        tenant_id: Uuid,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<Collection> {
+        let now = chrono::Utc::now();
+        CollectionActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            name: Set(name.to_string()),
+            description: Set(description),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+        }
+        .insert(self.write_conn())
+        .await
+        .map_err(Into::into)
+    }
+    
+    /// One of a tenant's collections
+    pub async fn find_collection(&self, tenant_id: Uuid, id: Uuid) -> Result<Option<Collection>> {
+        CollectionEntity::find_by_id(id)
+            .filter(CollectionColumn::TenantId.eq(tenant_id))
+            .one(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// A tenant's collection by name
+    pub async fn find_collection_by_name(&self, tenant_id: Uuid, name: &str) -> Result<Option<Collection>> {
+        CollectionEntity::find()
+            .filter(CollectionColumn::TenantId.eq(tenant_id))
+            .filter(CollectionColumn::Name.eq(name))
+            .one(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// A tenant's collections with their paper counts, by name
+    pub async fn list_collections(&self, tenant_id: Uuid) -> Result<Vec<CollectionSummary>> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT
+                col.id,
+                col.name,
+                col.description,
+                COUNT(p.id) AS paper_count,
+                col.created_at,
+                col.updated_at
+            FROM collections col
+            LEFT JOIN collection_papers cp ON cp.collection_id = col.id
+            LEFT JOIN papers p ON p.id = cp.paper_id AND p.deleted_at IS NULL
+            WHERE col.tenant_id = $1
+            GROUP BY col.id
+            ORDER BY col.name
+            "#,
+            vec![tenant_id.into()],
+        );
+        
+        CollectionSummary::find_by_statement(stmt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Rename or re-describe a collection; `None` leaves a field unchanged
+    pub async fn update_collection(
+        &self,
+        collection: Collection,
+        name: Option<String>,
+        description: Option<Option<String>>,
+    ) -> Result<Collection> {
+        let mut active: CollectionActiveModel = collection.into();
+        if let Some(name) = name {
+            active.name = Set(name);
+        }
+        if let Some(description) = description {
+            active.description = Set(description);
+        }
+        active.updated_at = Set(chrono::Utc::now().into());
+        
+        active.update(self.write_conn()).await.map_err(Into::into)
+    }
+    
+    /// Delete one of a tenant's collections (not its papers); `false` if
+    /// there was none
+    pub async fn delete_collection(&self, tenant_id: Uuid, id: Uuid) -> Result<bool> {
+        let result = CollectionEntity::delete_many()
+            .filter(CollectionColumn::Id.eq(id))
+            .filter(CollectionColumn::TenantId.eq(tenant_id))
+            .exec(self.write_conn())
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+    
+    /// Number of live papers in a collection
+    pub async fn count_collection_papers(&self, collection_id: Uuid) -> Result<i64> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT COUNT(*) AS paper_count
+            FROM collection_papers cp
+            JOIN papers p ON p.id = cp.paper_id
+            WHERE cp.collection_id = $1 AND p.deleted_at IS NULL
+            "#,
+            vec![collection_id.into()],
+        );
+        
+        let row = self.read_conn().query_one(stmt).await?;
+        Ok(match row {
+            Some(row) => row.try_get("", "paper_count")?,
+            None => 0,
+        })
+    }
+    
+    /// Live papers of the collection, most recently added first
+    pub async fn collection_paper_ids(&self, collection_id: Uuid, limit: u64, offset: u64) -> Result<Vec<Uuid>> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT cp.paper_id
+            FROM collection_papers cp
+            JOIN papers p ON p.id = cp.paper_id
+            WHERE cp.collection_id = $1 AND p.deleted_at IS NULL
+            ORDER BY cp.added_at DESC, cp.paper_id
+            LIMIT $2 OFFSET $3
+            "#,
+            vec![collection_id.into(), (limit as i64).into(), (offset as i64).into()],
+        );
+        
+        let rows = self.read_conn().query_all(stmt).await?;
+        rows.iter()
+            .map(|row| row.try_get("", "paper_id").map_err(Into::into))
+            .collect()
+    }
+    
+    /// Which of `paper_ids` are live papers of the tenant
+    pub async fn tenant_paper_ids(&self, tenant_id: Uuid, paper_ids: &[Uuid]) -> Result<Vec<Uuid>> {
+        if paper_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let papers = PaperEntity::find()
+            .select_only()
+            .column(PaperColumn::Id)
+            .filter(PaperColumn::TenantId.eq(tenant_id))
+            .filter(PaperColumn::Id.is_in(paper_ids.iter().copied()))
+            .filter(PaperColumn::DeletedAt.is_null())
+            .into_tuple::<Uuid>()
+            .all(self.read_conn())
+            .await?;
+        Ok(papers)
+    }
+    
+    /// Add papers to a collection; returns how many weren't in it already
+    pub async fn add_collection_papers(&self, collection_id: Uuid, paper_ids: &[Uuid]) -> Result<u64> {
+        if paper_ids.is_empty() {
+            return Ok(0);
+        }
+        
+        let rows = (0..paper_ids.len())
+            .map(|i| format!("($1, ${}, NOW())", i + 2))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            r#"
+            INSERT INTO collection_papers (collection_id, paper_id, added_at)
+            VALUES {}
+            ON CONFLICT (collection_id, paper_id) DO NOTHING
+            "#,
+            rows
+        );
+        
+        let mut values: Vec<sea_orm::Value> = vec![collection_id.into()];
+        values.extend(paper_ids.iter().map(|id| (*id).into()));
+        let result = self.write_conn()
+            .execute(Statement::from_sql_and_values(DbBackend::Postgres, &sql, values))
+            .await?;
+        
+        CollectionEntity::update_many()
+            .col_expr(CollectionColumn::UpdatedAt, Expr::value(DateTimeWithTimeZone::from(chrono::Utc::now())))
+            .filter(CollectionColumn::Id.eq(collection_id))
+            .exec(self.write_conn())
+            .await?;
+        Ok(result.rows_affected())
+    }
+    
+    /// Remove a paper from a collection; `false` if it wasn't in it
+    pub async fn remove_collection_paper(&self, collection_id: Uuid, paper_id: Uuid) -> Result<bool> {
+        let result = CollectionPaperEntity::delete_many()
+            .filter(CollectionPaperColumn::CollectionId.eq(collection_id))
+            .filter(CollectionPaperColumn::PaperId.eq(paper_id))
+            .exec(self.write_conn())
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+    
+    // ========================================================================
+    // Author Operations
+    // ========================================================================
+    
+    /// Replace a paper's authors
+    ///
+    /// Authors with an ORCID iD resolve to the tenant's author with that
+    /// ORCID, the rest to the author with the same normalized name and no
+    /// ORCID; missing authors are created. Returns the author IDs in order.
+    pub async fn link_paper_authors(
+        &self,
+        tenant_id: Uuid,
+        paper_id: Uuid,
+        authors: &[AuthorName],
+    ) -> Result<Vec<Uuid>> {
+        let txn = self.write_conn().begin().await?;
+        
+        PaperAuthorEntity::delete_many()
+            .filter(PaperAuthorColumn::PaperId.eq(paper_id))
+            .exec(&txn)
+            .await?;
+        
+        let mut author_ids = Vec::with_capacity(authors.len());
+        for (position, author) in authors.iter().enumerate() {
+            // The no-op update makes RETURNING yield existing rows too
+            let conflict = match author.orcid {
+                Some(_) => "(tenant_id, orcid) WHERE orcid IS NOT NULL",
+                None => "(tenant_id, normalized_name) WHERE orcid IS NULL",
+            };
+            let stmt = Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!(
+                    r#"
+                    INSERT INTO authors (id, tenant_id, name, normalized_name, orcid)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT {} DO UPDATE SET tenant_id = EXCLUDED.tenant_id
+                    RETURNING id
+                    "#,
+                    conflict
+                ),
+                vec![
+                    Uuid::new_v4().into(),
+                    tenant_id.into(),
+                    author.name.clone().into(),
+                    author.normalized_name().into(),
+                    author.orcid.clone().into(),
+                ],
+            );
+            let row = txn.query_one(stmt).await?.ok_or_else(|| AppError::Internal {
+                message: "Author upsert returned no row".to_string(),
+            })?;
+            let author_id: Uuid = row.try_get("", "id")?;
+            
+            // The same author listed twice keeps their first position
+            let stmt = Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                INSERT INTO paper_authors (paper_id, author_id, position)
+                VALUES ($1, $2, $3)
+                ON CONFLICT DO NOTHING
+                "#,
+                vec![paper_id.into(), author_id.into(), (position as i32 + 1).into()],
+            );
+            txn.execute(stmt).await?;
+            author_ids.push(author_id);
+        }
+        
+        txn.commit().await?;
+        Ok(author_ids)
+    }
+    
+    /// Get one of the tenant's authors
+    pub async fn find_author(&self, tenant_id: Uuid, author_id: Uuid) -> Result<Option<Author>> {
+        AuthorEntity::find_by_id(author_id)
+            .filter(AuthorColumn::TenantId.eq(tenant_id))
+            .one(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// An author's live papers, most recently published first
+    pub async fn author_papers(&self, author_id: Uuid, limit: u64, offset: u64) -> Result<Vec<Paper>> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT p.*
+            FROM paper_authors pa
+            JOIN papers p ON p.id = pa.paper_id
+            WHERE pa.author_id = $1 AND p.deleted_at IS NULL
+            ORDER BY p.published_at DESC NULLS LAST, p.created_at DESC, p.id
+            LIMIT $2 OFFSET $3
+            "#,
+            vec![author_id.into(), (limit as i64).into(), (offset as i64).into()],
+        );
+        
+        PaperEntity::find()
+            .from_raw_sql(stmt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Number of an author's live papers
+    pub async fn count_author_papers(&self, author_id: Uuid) -> Result<i64> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT COUNT(*) AS paper_count
+            FROM paper_authors pa
+            JOIN papers p ON p.id = pa.paper_id
+            WHERE pa.author_id = $1 AND p.deleted_at IS NULL
+            "#,
+            vec![author_id.into()],
+        );
+        
+        let row = self.read_conn().query_one(stmt).await?;
+        Ok(match row {
+            Some(row) => row.try_get("", "paper_count")?,
+            None => 0,
+        })
+    }
+    
+    // ========================================================================
+    // Usage Operations
+    // ========================================================================
+    
+    /// Append a usage ledger entry
+    pub async fn record_usage(&self, entry: UsageRecordActiveModel) -> Result<UsageRecord> {
+        entry.insert(self.write_conn()).await.map_err(Into::into)
+    }
+    
+    /// A tenant's usage since `since`, per model
+    pub async fn usage_by_model(
+        &self,
+        tenant_id: Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<UsageTotals>> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT kind, model,
+                COUNT(*) AS calls,
+                COALESCE(SUM(prompt_tokens), 0)::BIGINT AS prompt_tokens,
+                COALESCE(SUM(completion_tokens), 0)::BIGINT AS completion_tokens,
+                COALESCE(SUM(cost_micros), 0)::BIGINT AS cost_micros
+            FROM usage_ledger
+            WHERE tenant_id = $1 AND created_at >= $2
+            GROUP BY kind, model
+            ORDER BY cost_micros DESC, kind, model
+            "#,
+            vec![tenant_id.into(), since.into()],
+        );
+        
+        UsageTotals::find_by_statement(stmt)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// A tenant's total estimated spend since `since`, in micro-dollars
+    pub async fn tenant_spend_since(
+        &self,
+        tenant_id: Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64> {
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT COALESCE(SUM(cost_micros), 0)::BIGINT AS spend FROM usage_ledger WHERE tenant_id = $1 AND created_at >= $2",
+            vec![tenant_id.into(), since.into()],
+        );
+        
+        let row = self.read_conn().query_one(stmt).await?;
+        Ok(match row {
+            Some(row) => row.try_get("", "spend")?,
+            None => 0,
+        })
+    }
+    
+    // ========================================================================
+    // Audit Log Operations
+    // ========================================================================
+    
+    /// Append an audit log entry
+    pub async fn create_audit_log(&self, entry: AuditLogActiveModel) -> Result<AuditLog> {
+        entry.insert(self.write_conn()).await.map_err(Into::into)
+    }
+    
+    /// List audit log entries matching a filter, newest first
+    pub async fn list_audit_logs(&self, filter: &AuditLogFilter, limit: u64) -> Result<Vec<AuditLog>> {
+        let mut query = AuditLogEntity::find();
+        
+        if let Some(tenant_id) = filter.tenant_id {
+            query = query.filter(AuditLogColumn::TenantId.eq(tenant_id));
+        }
+        if let Some(actor) = &filter.actor {
+            query = query.filter(AuditLogColumn::Actor.eq(actor.as_str()));
+        }
+        if let Some(action) = &filter.action {
+            query = query.filter(AuditLogColumn::Action.eq(action.as_str()));
+        }
+        if let Some(resource_type) = &filter.resource_type {
+            query = query.filter(AuditLogColumn::ResourceType.eq(resource_type.as_str()));
+        }
+        if let Some(resource_id) = &filter.resource_id {
+            query = query.filter(AuditLogColumn::ResourceId.eq(resource_id.as_str()));
+        }
+        if let Some(since) = filter.since {
+            query = query.filter(AuditLogColumn::CreatedAt.gte(DateTimeWithTimeZone::from(since)));
+        }
+        if let Some(until) = filter.until {
+            query = query.filter(AuditLogColumn::CreatedAt.lt(DateTimeWithTimeZone::from(until)));
+        }
+        
+        query
+            .order_by_desc(AuditLogColumn::CreatedAt)
+            .limit(limit)
+            .all(self.read_conn())
+            .await
+            .map_err(Into::into)
+    }
+}
+
+/// Feedback and logged queries are compared case- and whitespace-insensitively
+fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+// ============================================================================
+// Unit of Work
+// ============================================================================
+
+/// Writes that commit together, handed to [`Repository::transaction`]'s work
+pub struct UnitOfWork {
+    txn: DatabaseTransaction,
+}
+
+impl UnitOfWork {
+    /// Create a new paper
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_paper(
+        &self,
+        tenant_id: Uuid,
+        title: String,
+        abstract_text: String,
+        source: Option<String>,
+        external_id: Option<String>,
+        metadata: serde_json::Value,
+        idempotency_key: Option<String>,
+    ) -> Result<Paper> {
+        Repository::new_paper(tenant_id, title, abstract_text, source, external_id, metadata, idempotency_key)
+            .insert(&self.txn)
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Apply field changes to a paper, bumping `updated_at`
+    pub async fn update_paper(&self, paper: Paper, update: PaperUpdate) -> Result<Paper> {
+        Repository::paper_update(paper, update)
+            .update(&self.txn)
+            .await
+            .map_err(Into::into)
+    }
+    
+    /// Record where a paper's original document and extracted text are stored
+    pub async fn set_paper_storage_keys(
+        &self,
+        id: Uuid,
+        source_key: Option<String>,
+        text_key: Option<String>,
+    ) -> Result<()> {
+        Repository::apply_paper_storage_keys(&self.txn, id, source_key, text_key).await
+    }
+    
+    /// Create an ingestion job
+    pub async fn create_job(
+        &self,
+        tenant_id: Uuid,
+        paper_id: Option<Uuid>,
+        idempotency_key: Option<String>,
+    ) -> Result<IngestionJob> {
+        Repository::insert_job(&self.txn, tenant_id, paper_id, idempotency_key).await
+    }
+    
+    /// Update job status
+    pub async fn update_job_status(
+        &self,
+        job_id: Uuid,
+        status: JobStatus,
+        paper_id: Option<Uuid>,
+        chunks_total: Option<i32>,
+        error_message: Option<String>,
+    ) -> Result<IngestionJob> {
+        Repository::apply_job_status(&self.txn, job_id, status, paper_id, chunks_total, error_message).await
+    }
+    
+    /// Keep the queue message that started a job, for retries
+    pub async fn set_job_queue_message(
+        &self,
+        job: IngestionJob,
+        message: OutboxPayload,
+    ) -> Result<IngestionJob> {
+        let mut job: IngestionJobActiveModel = job.into();
+        job.queue_message = Set(Some(message.payload));
+        job.queue_attributes = Set(Some(serde_json::json!(message.envelope)));
+        job.update(&self.txn).await.map_err(Into::into)
+    }
+    
+    /// Create an upload batch
+    pub async fn create_batch(&self, batch: IngestionBatch) -> Result<IngestionBatch> {
+        IngestionBatchActiveModel {
+            id: Set(batch.id),
+            tenant_id: Set(batch.tenant_id),
+            archive_name: Set(batch.archive_name),
+            document_count: Set(batch.document_count),
+            skipped: Set(batch.skipped),
+            created_at: Set(batch.created_at),
+        }
+        .insert(&self.txn)
+        .await
+        .map_err(Into::into)
+    }
+    
+    /// Write a queue message to the outbox, published once the transaction
+    /// commits
+    pub async fn write_outbox(&self, queue: &str, message: OutboxPayload) -> Result<OutboxMessage> {
+        Repository::insert_outbox(&self.txn, queue, message).await
+    }
+}
+
+/// Whether a transaction failed on a serialization conflict or deadlock and
+/// can be re-run as is
+fn is_serialization_failure(err: &AppError) -> bool {
+    let AppError::Database(DbErr::Exec(err) | DbErr::Query(err) | DbErr::Conn(err)) = err else {
+        return false;
+    };
+    match err {
+        RuntimeErr::SqlxError(sqlx::Error::Database(err)) => {
+            matches!(err.code().as_deref(), Some("40001" | "40P01"))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+    
+    /// A Postgres error carrying only its SQLSTATE
+    #[derive(Debug)]
+    struct PgError(&'static str);
+    
+    impl std::fmt::Display for PgError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+    
+    impl std::error::Error for PgError {}
+    
+    impl sqlx::error::DatabaseError for PgError {
+        fn message(&self) -> &str {
+            "could not serialize access"
+        }
+        
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+        
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+        
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+        
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+        
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+    
+    fn database_error(code: &'static str, wrap: fn(RuntimeErr) -> DbErr) -> AppError {
+        AppError::Database(wrap(RuntimeErr::SqlxError(sqlx::Error::Database(Box::new(PgError(code))))))
+    }
+    
+    #[test]
+    fn test_is_serialization_failure() {
+        assert!(is_serialization_failure(&database_error("40001", DbErr::Exec)));
+        assert!(is_serialization_failure(&database_error("40P01", DbErr::Query)));
+        assert!(is_serialization_failure(&database_error("40001", DbErr::Conn)));
+        
+        // A unique violation fails the same way when re-run
+        assert!(!is_serialization_failure(&database_error("23505", DbErr::Exec)));
+        assert!(!is_serialization_failure(&AppError::Database(DbErr::Exec(RuntimeErr::Internal(
+            "deadlock detected".to_string()
+        )))));
+        assert!(!is_serialization_failure(&AppError::Database(DbErr::RecordNotFound("chunk".to_string()))));
+        assert!(!is_serialization_failure(&AppError::Internal { message: "40001".to_string() }));
+    }
 }
@@ -0,0 +1,329 @@
+//! Read routing between the primary and the read replicas
+//!
+//! Reads go to a replica unless one of these sends them to the primary:
+//! - the current request has already written (read-your-writes, see [`consistent`])
+//! - every replica failed its last health check
+//! - every healthy replica's replay LSN trails the primary's WAL by more
+//!   than `max_replica_lag_bytes`
+//!
+//! Among the replicas that can serve, those whose latency is within
+//! `replica_latency_window_ms` of the fastest one share the reads in
+//! proportion to their weights, so reads stay in the nearest region while
+//! it has a replica to serve them. Replica health, lag and latency are
+//! refreshed in the background by [`monitor`].
+
+use crate::metrics;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use std::cell::Cell;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+tokio::task_local! {
+    static WROTE: Cell<bool>;
+}
+
+/// Run `future` as one unit of read-your-writes consistency
+///
+/// Once anything in it takes the write connection, its later reads go to
+/// the primary too.
+pub async fn consistent<F: Future>(future: F) -> F::Output {
+    WROTE.scope(Cell::new(false), future).await
+}
+
+/// Record that the current unit of work wrote to the primary
+pub(crate) fn mark_write() {
+    let _ = WROTE.try_with(|wrote| wrote.set(true));
+}
+
+fn wrote() -> bool {
+    WROTE.try_with(|wrote| wrote.get()).unwrap_or(false)
+}
+
+/// Where a read was sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadRoute {
+    /// The replica at this index
+    Replica(usize),
+    Primary(PrimaryReason),
+}
+
+/// Why a read went to the primary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimaryReason {
+    NoReplica,
+    ReadYourWrites,
+    ReplicaDown,
+    ReplicaLagging,
+}
+
+impl ReadRoute {
+    /// Route label for metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadRoute::Replica(_) => "replica",
+            ReadRoute::Primary(_) => "primary",
+        }
+    }
+    
+    /// Reason label for metrics
+    pub fn reason(&self) -> &'static str {
+        match self {
+            ReadRoute::Replica(_) => "healthy",
+            ReadRoute::Primary(PrimaryReason::NoReplica) => "no_replica",
+            ReadRoute::Primary(PrimaryReason::ReadYourWrites) => "read_your_writes",
+            ReadRoute::Primary(PrimaryReason::ReplicaDown) => "replica_down",
+            ReadRoute::Primary(PrimaryReason::ReplicaLagging) => "replica_lagging",
+        }
+    }
+}
+
+/// Last known health, replication lag and latency of one replica
+#[derive(Debug)]
+pub struct ReplicaStatus {
+    name: String,
+    weight: u32,
+    up: AtomicBool,
+    lag_bytes: AtomicU64,
+    /// Smoothed health check round trip, 0 until the first check
+    latency_micros: AtomicU64,
+    max_lag_bytes: u64,
+}
+
+impl ReplicaStatus {
+    pub(crate) fn new(name: &str, weight: u32, max_lag_bytes: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            weight,
+            up: AtomicBool::new(true),
+            lag_bytes: AtomicU64::new(0),
+            latency_micros: AtomicU64::new(0),
+            max_lag_bytes,
+        }
+    }
+    
+    /// Name the replica is configured, logged and labelled with
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    
+    /// Share of reads relative to the other replicas
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+    
+    /// Whether the replica answered its last health check
+    pub fn is_up(&self) -> bool {
+        self.up.load(Ordering::Relaxed)
+    }
+    
+    /// Bytes of WAL the replica had yet to replay at the last check
+    pub fn lag_bytes(&self) -> u64 {
+        self.lag_bytes.load(Ordering::Relaxed)
+    }
+    
+    /// Smoothed round trip of the health checks; zero before the first
+    pub fn latency(&self) -> Duration {
+        Duration::from_micros(self.latency_micros.load(Ordering::Relaxed))
+    }
+    
+    /// Whether reads may go to the replica
+    pub fn is_usable(&self) -> bool {
+        self.is_up() && self.lag_bytes() <= self.max_lag_bytes && self.weight > 0
+    }
+    
+    pub(crate) fn mark_down(&self) {
+        if self.up.swap(false, Ordering::Relaxed) {
+            warn!(replica = %self.name, "Read replica is down, routing its reads elsewhere");
+        }
+        metrics::record_replica_status(&self.name, false, self.lag_bytes(), self.latency().as_secs_f64());
+    }
+    
+    fn mark_up(&self, lag_bytes: u64, latency: Duration) {
+        if !self.up.swap(true, Ordering::Relaxed) {
+            info!(replica = %self.name, "Read replica is back up");
+        }
+        self.lag_bytes.store(lag_bytes, Ordering::Relaxed);
+        
+        // Smooth out single slow checks so routing doesn't flap
+        let sample = latency.as_micros().min(u64::MAX as u128) as u64;
+        let latency = match self.latency_micros.load(Ordering::Relaxed) {
+            0 => sample,
+            previous => (previous * 3 + sample) / 4,
+        };
+        self.latency_micros.store(latency, Ordering::Relaxed);
+        metrics::record_replica_status(&self.name, true, lag_bytes, latency as f64 / 1_000_000.0);
+    }
+}
+
+/// A replica's connection and status
+pub(crate) struct Replica {
+    pub(crate) connection: DatabaseConnection,
+    pub(crate) status: Arc<ReplicaStatus>,
+}
+
+/// Read replicas and the state for spreading reads over them
+pub(crate) struct ReplicaSet {
+    pub(crate) replicas: Vec<Replica>,
+    latency_window: Duration,
+    next: AtomicU64,
+}
+
+impl ReplicaSet {
+    pub(crate) fn new(replicas: Vec<Replica>, latency_window: Duration) -> Self {
+        Self {
+            replicas,
+            latency_window,
+            next: AtomicU64::new(0),
+        }
+    }
+    
+    /// Pick where a read goes
+    pub(crate) fn route(&self) -> ReadRoute {
+        if self.replicas.is_empty() {
+            return ReadRoute::Primary(PrimaryReason::NoReplica);
+        }
+        if wrote() {
+            return ReadRoute::Primary(PrimaryReason::ReadYourWrites);
+        }
+        
+        let statuses: Vec<&ReplicaStatus> = self.replicas.iter().map(|r| r.status.as_ref()).collect();
+        let tick = self.next.fetch_add(1, Ordering::Relaxed);
+        match select(&statuses, self.latency_window, tick) {
+            Some(index) => ReadRoute::Replica(index),
+            None if statuses.iter().any(|s| s.is_up()) => ReadRoute::Primary(PrimaryReason::ReplicaLagging),
+            None => ReadRoute::Primary(PrimaryReason::ReplicaDown),
+        }
+    }
+}
+
+/// Index of the replica the `tick`th read goes to, `None` when none can
+/// serve it
+///
+/// Usable replicas within `latency_window` of the fastest take turns in
+/// proportion to their weights.
+fn select(replicas: &[&ReplicaStatus], latency_window: Duration, tick: u64) -> Option<usize> {
+    let fastest = replicas.iter().filter(|r| r.is_usable()).map(|r| r.latency()).min()?;
+    let candidates: Vec<usize> = (0..replicas.len())
+        .filter(|&i| replicas[i].is_usable() && replicas[i].latency() <= fastest + latency_window)
+        .collect();
+    
+    let total: u64 = candidates.iter().map(|&i| replicas[i].weight() as u64).sum();
+    let mut position = tick % total;
+    for index in candidates {
+        let weight = replicas[index].weight() as u64;
+        if position < weight {
+            return Some(index);
+        }
+        position -= weight;
+    }
+    None
+}
+
+/// Check a replica every `interval`, updating `status`
+pub(crate) async fn monitor(
+    primary: DatabaseConnection,
+    replica: DatabaseConnection,
+    status: Arc<ReplicaStatus>,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match replica_lag(&primary, &replica).await {
+            Ok((lag_bytes, latency)) => {
+                if lag_bytes > status.max_lag_bytes && status.lag_bytes() <= status.max_lag_bytes {
+                    warn!(replica = %status.name, lag_bytes, "Read replica is lagging, routing its reads elsewhere");
+                }
+                status.mark_up(lag_bytes, latency);
+            }
+            Err(ReplicaCheckError::Replica(e)) => {
+                warn!(replica = %status.name, error = %e, "Read replica health check failed");
+                status.mark_down();
+            }
+            Err(ReplicaCheckError::Primary(e)) => {
+                warn!(error = %e, "Could not read primary WAL position");
+            }
+        }
+    }
+}
+
+enum ReplicaCheckError {
+    Primary(sea_orm::DbErr),
+    Replica(sea_orm::DbErr),
+}
+
+/// Bytes between the primary's current WAL position and the replica's
+/// replay position, 0 when the replica isn't in recovery, and the round
+/// trip of the replica query
+async fn replica_lag(
+    primary: &DatabaseConnection,
+    replica: &DatabaseConnection,
+) -> std::result::Result<(u64, Duration), ReplicaCheckError> {
+    let stmt = Statement::from_string(
+        DbBackend::Postgres,
+        "SELECT pg_last_wal_replay_lsn()::text AS lsn",
+    );
+    let start = Instant::now();
+    let replay_lsn: Option<String> = match replica.query_one(stmt).await {
+        Ok(Some(row)) => row.try_get("", "lsn").map_err(ReplicaCheckError::Replica)?,
+        Ok(None) => None,
+        Err(e) => return Err(ReplicaCheckError::Replica(e)),
+    };
+    let latency = start.elapsed();
+    let Some(replay_lsn) = replay_lsn else {
+        return Ok((0, latency));
+    };
+    
+    let stmt = Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_wal_lsn_diff(pg_current_wal_lsn(), $1::pg_lsn)::bigint AS lag",
+        [replay_lsn.into()],
+    );
+    let lag: i64 = match primary.query_one(stmt).await {
+        Ok(Some(row)) => row.try_get("", "lag").map_err(ReplicaCheckError::Primary)?,
+        Ok(None) => 0,
+        Err(e) => return Err(ReplicaCheckError::Primary(e)),
+    };
+    
+    Ok((lag.max(0) as u64, latency))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn replica(weight: u32, latency_ms: u64) -> ReplicaStatus {
+        let status = ReplicaStatus::new("replica", weight, 1024);
+        status.latency_micros.store(latency_ms * 1000, Ordering::Relaxed);
+        status
+    }
+    
+    fn picks(replicas: &[&ReplicaStatus], reads: u64) -> Vec<usize> {
+        let mut counts = vec![0; replicas.len()];
+        for tick in 0..reads {
+            counts[select(replicas, Duration::from_millis(10), tick).unwrap()] += 1;
+        }
+        counts
+    }
+    
+    #[test]
+    fn test_reads_follow_weights() {
+        let (a, b) = (replica(1, 2), replica(3, 5));
+        assert_eq!(picks(&[&a, &b], 400), vec![100, 300]);
+    }
+    
+    #[test]
+    fn test_distant_replicas_only_serve_when_near_ones_cannot() {
+        let (near, far) = (replica(1, 2), replica(1, 80));
+        assert_eq!(picks(&[&near, &far], 10), vec![10, 0]);
+        
+        near.up.store(false, Ordering::Relaxed);
+        assert_eq!(picks(&[&near, &far], 10), vec![0, 10]);
+        
+        far.lag_bytes.store(4096, Ordering::Relaxed);
+        assert_eq!(select(&[&near, &far], Duration::from_millis(10), 0), None);
+    }
+}
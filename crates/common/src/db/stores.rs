@@ -0,0 +1,172 @@
+//! Narrow repository traits
+//!
+//! Each trait covers the queries of one consumer, so code written against
+//! it can be unit-tested with [`MemoryRepository`](super::memory::MemoryRepository)
+//! instead of Postgres. [`Repository`] implements them all by delegating to
+//! its inherent methods.
+
+use super::models::{IngestionJob, JobCheckpoint, JobStatus, Session};
+use super::{NewChunk, Repository, TenantCitations};
+use crate::errors::Result;
+use async_trait::async_trait;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Ingestion job lookups, for job status
+#[async_trait]
+pub trait JobRepository: Send + Sync {
+    async fn find_job_by_id(&self, id: Uuid) -> Result<Option<IngestionJob>>;
+
+    /// A job's checkpoints, oldest first
+    async fn find_checkpoints(&self, job_id: Uuid) -> Result<Vec<JobCheckpoint>>;
+}
+
+/// Chunk storage and job bookkeeping, for the embedding worker
+#[async_trait]
+pub trait EmbeddingRepository: JobRepository {
+    /// Indexes of a paper's stored chunks
+    async fn stored_chunk_indexes(&self, paper_id: Uuid) -> Result<HashSet<i32>>;
+
+    /// Store chunks, replacing any stored under the same index
+    async fn upsert_chunks(&self, paper_id: Uuid, chunks: Vec<NewChunk>, embedding_version: i32) -> Result<Vec<Uuid>>;
+
+    async fn update_job_progress(&self, job_id: Uuid, chunks_processed: i32) -> Result<()>;
+
+    async fn set_failed_chunks(&self, job_id: Uuid, chunk_indexes: &[i32]) -> Result<()>;
+
+    async fn update_job_status(
+        &self,
+        job_id: Uuid,
+        status: JobStatus,
+        paper_id: Option<Uuid>,
+        chunks_total: Option<i32>,
+        error_message: Option<String>,
+    ) -> Result<IngestionJob>;
+
+    async fn delete_checkpoints(&self, job_id: Uuid) -> Result<u64>;
+}
+
+/// Context engine session storage
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn find_session(&self, session_id: Uuid) -> Result<Option<Session>>;
+
+    /// Create or update a session, extending its expiry by `ttl_minutes`
+    ///
+    /// Fails with `TenantMismatch` when the session belongs to another tenant.
+    async fn upsert_session(
+        &self,
+        tenant_id: Uuid,
+        session_id: Uuid,
+        state: serde_json::Value,
+        ttl_minutes: i64,
+    ) -> Result<Session>;
+
+    /// Delete expired sessions; returns how many were deleted
+    async fn delete_expired_sessions(&self, batch_size: u64) -> Result<u64>;
+}
+
+/// Citation graphs and persisted PageRank scores
+#[async_trait]
+pub trait PageRankRepository: Send + Sync {
+    /// Papers whose citations are newer than their PageRank, by tenant
+    async fn pagerank_pending_papers(&self) -> Result<HashMap<Uuid, Vec<Uuid>>>;
+
+    async fn tenants_with_papers(&self) -> Result<Vec<Uuid>>;
+
+    async fn citation_graph(&self, tenant_id: Uuid) -> Result<TenantCitations>;
+
+    async fn paper_pageranks(&self, tenant_id: Uuid) -> Result<HashMap<Uuid, f64>>;
+
+    async fn save_pageranks(&self, scores: &[(Uuid, f64)], computed_at: DateTimeWithTimeZone) -> Result<()>;
+}
+
+#[async_trait]
+impl JobRepository for Repository {
+    async fn find_job_by_id(&self, id: Uuid) -> Result<Option<IngestionJob>> {
+        Repository::find_job_by_id(self, id).await
+    }
+
+    async fn find_checkpoints(&self, job_id: Uuid) -> Result<Vec<JobCheckpoint>> {
+        Repository::find_checkpoints(self, job_id).await
+    }
+}
+
+#[async_trait]
+impl EmbeddingRepository for Repository {
+    async fn stored_chunk_indexes(&self, paper_id: Uuid) -> Result<HashSet<i32>> {
+        Repository::stored_chunk_indexes(self, paper_id).await
+    }
+
+    async fn upsert_chunks(&self, paper_id: Uuid, chunks: Vec<NewChunk>, embedding_version: i32) -> Result<Vec<Uuid>> {
+        Repository::upsert_chunks(self, paper_id, chunks, embedding_version).await
+    }
+
+    async fn update_job_progress(&self, job_id: Uuid, chunks_processed: i32) -> Result<()> {
+        Repository::update_job_progress(self, job_id, chunks_processed).await
+    }
+
+    async fn set_failed_chunks(&self, job_id: Uuid, chunk_indexes: &[i32]) -> Result<()> {
+        Repository::set_failed_chunks(self, job_id, chunk_indexes).await
+    }
+
+    async fn update_job_status(
+        &self,
+        job_id: Uuid,
+        status: JobStatus,
+        paper_id: Option<Uuid>,
+        chunks_total: Option<i32>,
+        error_message: Option<String>,
+    ) -> Result<IngestionJob> {
+        Repository::update_job_status(self, job_id, status, paper_id, chunks_total, error_message).await
+    }
+
+    async fn delete_checkpoints(&self, job_id: Uuid) -> Result<u64> {
+        Repository::delete_checkpoints(self, job_id).await
+    }
+}
+
+#[async_trait]
+impl SessionRepository for Repository {
+    async fn find_session(&self, session_id: Uuid) -> Result<Option<Session>> {
+        Repository::find_session(self, session_id).await
+    }
+
+    async fn upsert_session(
+        &self,
+        tenant_id: Uuid,
+        session_id: Uuid,
+        state: serde_json::Value,
+        ttl_minutes: i64,
+    ) -> Result<Session> {
+        Repository::upsert_session(self, tenant_id, session_id, state, ttl_minutes).await
+    }
+
+    async fn delete_expired_sessions(&self, batch_size: u64) -> Result<u64> {
+        Repository::delete_expired_sessions(self, batch_size).await
+    }
+}
+
+#[async_trait]
+impl PageRankRepository for Repository {
+    async fn pagerank_pending_papers(&self) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        Repository::pagerank_pending_papers(self).await
+    }
+
+    async fn tenants_with_papers(&self) -> Result<Vec<Uuid>> {
+        Repository::tenants_with_papers(self).await
+    }
+
+    async fn citation_graph(&self, tenant_id: Uuid) -> Result<TenantCitations> {
+        Repository::citation_graph(self, tenant_id).await
+    }
+
+    async fn paper_pageranks(&self, tenant_id: Uuid) -> Result<HashMap<Uuid, f64>> {
+        Repository::paper_pageranks(self, tenant_id).await
+    }
+
+    async fn save_pageranks(&self, scores: &[(Uuid, f64)], computed_at: DateTimeWithTimeZone) -> Result<()> {
+        Repository::save_pageranks(self, scores, computed_at).await
+    }
+}
@@ -0,0 +1,111 @@
+//! Request deadlines
+//!
+//! A deadline is set where a request enters a service and applies to the
+//! work done for it in the same task. Outgoing gRPC calls carry the time
+//! left as their `grpc-timeout` (see
+//! [`RequestContext`](crate::request_context::RequestContext)), where the
+//! receiving service picks it up again. Search queries run with a `statement_timeout` no longer than the
+//! time left. So the work of a client that has given up is cancelled,
+//! instead of running on to completion. Tasks spawned for a request don't
+//! inherit its deadline unless they are wrapped in [`with_deadline`].
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+use axum::http::HeaderMap;
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// gRPC header carrying a request's timeout
+const GRPC_TIMEOUT: &str = "grpc-timeout";
+
+/// Longest value `grpc-timeout` can hold per unit
+const MAX_GRPC_TIMEOUT_DIGITS: usize = 8;
+
+/// Run `future` with `deadline`; a deadline already in effect is kept when
+/// it is earlier
+pub async fn with_deadline<F: Future>(deadline: Instant, future: F) -> F::Output {
+    let deadline = current().map_or(deadline, |outer| outer.min(deadline));
+    DEADLINE.scope(deadline, future).await
+}
+
+/// Run `future` with a deadline `timeout` from now, or as is without one
+pub async fn with_timeout<F: Future>(timeout: Option<Duration>, future: F) -> F::Output {
+    match timeout {
+        Some(timeout) => with_deadline(Instant::now() + timeout, future).await,
+        None => future.await,
+    }
+}
+
+/// The current task's deadline
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Time left until the current deadline, zero once it has passed
+pub fn remaining() -> Option<Duration> {
+    current().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// Timeout an incoming gRPC request was sent with
+pub fn grpc_timeout(headers: &HeaderMap) -> Option<Duration> {
+    headers.get(GRPC_TIMEOUT)?.to_str().ok().and_then(parse_grpc_timeout)
+}
+
+/// Parse a `grpc-timeout` value: up to 8 digits and a unit (`H`, `M`, `S`,
+/// `m`, `u` or `n`)
+fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let (digits, unit) = value.split_at(value.len().checked_sub(1)?);
+    if digits.is_empty() || digits.len() > MAX_GRPC_TIMEOUT_DIGITS {
+        return None;
+    }
+    let amount: u64 = digits.parse().ok()?;
+
+    Some(match unit {
+        "H" => Duration::from_secs(amount * 3600),
+        "M" => Duration::from_secs(amount * 60),
+        "S" => Duration::from_secs(amount),
+        "m" => Duration::from_millis(amount),
+        "u" => Duration::from_micros(amount),
+        "n" => Duration::from_nanos(amount),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("5S"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_grpc_timeout("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_grpc_timeout("2H"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_grpc_timeout("S"), None);
+        assert_eq!(parse_grpc_timeout("123456789S"), None);
+        assert_eq!(parse_grpc_timeout("5s"), None);
+        assert_eq!(parse_grpc_timeout(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_inner_deadline_cannot_extend_outer() {
+        assert!(remaining().is_none());
+
+        let outer = Instant::now() + Duration::from_secs(1);
+        with_deadline(outer, async {
+            with_timeout(Some(Duration::from_secs(60)), async {
+                assert_eq!(current(), Some(outer));
+            })
+            .await;
+
+            with_timeout(Some(Duration::from_millis(10)), async {
+                assert!(current().unwrap() < outer);
+            })
+            .await;
+
+            assert!(remaining().unwrap() <= Duration::from_secs(1));
+        })
+        .await;
+    }
+}
@@ -0,0 +1,259 @@
+//! Azure OpenAI embedding provider
+//!
+//! Azure serves OpenAI models from per-resource deployments:
+//! `{endpoint}/openai/deployments/{deployment}/embeddings?api-version=...`.
+//! Requests authenticate with the resource's API key or with an Azure AD
+//! token for a service principal.
+
+use super::{check_status, openai_dimension, shortened_dimension, with_retry, Embedder, OpenAIResponse};
+use crate::config::{AzureEmbeddingConfig, EmbeddingProviderConfig};
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Scope of Azure AD tokens for Azure OpenAI
+const AAD_SCOPE: &str = "https://cognitiveservices.azure.com/.default";
+
+/// Tokens are refreshed this long before they expire
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(300);
+
+/// Inputs per request; older deployments accept at most 16
+const BATCH_SIZE: usize = 16;
+
+enum AzureAuth {
+    ApiKey(String),
+    /// Client-credentials flow, with the current token cached
+    ServicePrincipal {
+        tenant_id: String,
+        client_id: String,
+        client_secret: String,
+        token: Mutex<Option<(String, Instant)>>,
+    },
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// Azure OpenAI embedding client
+pub struct AzureOpenAIEmbedder {
+    client: reqwest::Client,
+    auth: AzureAuth,
+    url: String,
+    model: String,
+    dimension: usize,
+    /// Shortened output dimension sent with each request
+    dimensions: Option<usize>,
+}
+
+impl AzureOpenAIEmbedder {
+    /// Create an embedder for `provider.model` deployed on the resource at
+    /// `provider.api_base`
+    ///
+    /// # Panics
+    ///
+    /// If the endpoint is missing, or neither an API key nor a complete
+    /// service principal is configured.
+    pub fn new(provider: &EmbeddingProviderConfig, azure: &AzureEmbeddingConfig) -> Self {
+        let endpoint = provider.api_base.as_deref().expect("Azure OpenAI endpoint (api_base) required");
+        let deployment = azure.deployment.as_deref().unwrap_or(&provider.model);
+        
+        let auth = match (&provider.api_key, &azure.tenant_id, &azure.client_id, &azure.client_secret) {
+            (Some(key), ..) => AzureAuth::ApiKey(key.clone()),
+            (None, Some(tenant_id), Some(client_id), Some(client_secret)) => AzureAuth::ServicePrincipal {
+                tenant_id: tenant_id.clone(),
+                client_id: client_id.clone(),
+                client_secret: client_secret.clone(),
+                token: Mutex::new(None),
+            },
+            _ => panic!("Azure OpenAI API key or service principal (tenant_id, client_id, client_secret) required"),
+        };
+        
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client");
+        
+        let dimensions = shortened_dimension(&provider.model, provider.dimensions);
+        Self {
+            client,
+            auth,
+            url: deployment_url(endpoint, deployment, &azure.api_version),
+            model: provider.model.clone(),
+            dimension: dimensions.unwrap_or_else(|| openai_dimension(&provider.model)),
+            dimensions,
+        }
+    }
+    
+    /// Authorization header for the next request
+    async fn auth_header(&self) -> Result<(&'static str, String)> {
+        match &self.auth {
+            AzureAuth::ApiKey(key) => Ok(("api-key", key.clone())),
+            AzureAuth::ServicePrincipal { tenant_id, client_id, client_secret, token } => {
+                let mut token = token.lock().await;
+                if let Some((value, expires_at)) = token.as_ref() {
+                    if Instant::now() + TOKEN_REFRESH_MARGIN < *expires_at {
+                        return Ok(("Authorization", format!("Bearer {}", value)));
+                    }
+                }
+                
+                let fresh = self.fetch_token(tenant_id, client_id, client_secret).await?;
+                let header = format!("Bearer {}", fresh.access_token);
+                *token = Some((fresh.access_token, Instant::now() + Duration::from_secs(fresh.expires_in)));
+                Ok(("Authorization", header))
+            }
+        }
+    }
+    
+    async fn fetch_token(&self, tenant_id: &str, client_id: &str, client_secret: &str) -> Result<TokenResponse> {
+        let url = format!("https://login.microsoftonline.com/{}/oauth2/v2.0/token", tenant_id);
+        let response = self.client
+            .post(&url)
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", client_id),
+                ("client_secret", client_secret),
+                ("scope", AAD_SCOPE),
+            ])
+            .send()
+            .await
+            .map_err(|e| AppError::EmbeddingError {
+                message: format!("Azure AD token request failed: {}", e),
+            })?;
+        
+        check_status(response).await?.json().await.map_err(|e| AppError::EmbeddingError {
+            message: format!("Failed to parse Azure AD token response: {}", e),
+        })
+    }
+    
+    async fn make_request(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let (header, value) = self.auth_header().await?;
+        let response = self.client
+            .post(&self.url)
+            .header(header, value)
+            .json(&request_body(texts, self.dimensions))
+            .send()
+            .await
+            .map_err(|e| AppError::EmbeddingError {
+                message: format!("Request failed: {}", e),
+            })?;
+        
+        let result: OpenAIResponse = check_status(response).await?.json().await.map_err(|e| {
+            AppError::EmbeddingError {
+                message: format!("Failed to parse response: {}", e),
+            }
+        })?;
+        
+        Ok(result.data.into_iter().map(|e| e.embedding).collect())
+    }
+}
+
+#[async_trait]
+impl Embedder for AzureOpenAIEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let texts = [text.to_string()];
+        let embeddings = with_retry(|| self.make_request(&texts)).await?;
+        embeddings.into_iter().next().ok_or_else(|| AppError::EmbeddingError {
+            message: "Empty response".to_string(),
+        })
+    }
+    
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut all_embeddings = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(BATCH_SIZE) {
+            all_embeddings.extend(with_retry(|| self.make_request(chunk)).await?);
+        }
+        Ok(all_embeddings)
+    }
+    
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+    
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+fn request_body(texts: &[String], dimensions: Option<usize>) -> serde_json::Value {
+    match dimensions {
+        Some(dimensions) => serde_json::json!({ "input": texts, "dimensions": dimensions }),
+        None => serde_json::json!({ "input": texts }),
+    }
+}
+
+fn deployment_url(endpoint: &str, deployment: &str, api_version: &str) -> String {
+    format!(
+        "{}/openai/deployments/{}/embeddings?api-version={}",
+        endpoint.trim_end_matches('/'),
+        deployment,
+        api_version
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn provider(api_key: Option<&str>) -> EmbeddingProviderConfig {
+        EmbeddingProviderConfig {
+            provider: "azure".to_string(),
+            api_key: api_key.map(String::from),
+            api_base: Some("https://research.openai.azure.com/".to_string()),
+            model: "text-embedding-3-small".to_string(),
+            dimensions: None,
+        }
+    }
+    
+    #[test]
+    fn test_builds_deployment_url() {
+        let embedder = AzureOpenAIEmbedder::new(&provider(Some("key")), &AzureEmbeddingConfig::default());
+        assert_eq!(
+            embedder.url,
+            "https://research.openai.azure.com/openai/deployments/text-embedding-3-small/embeddings?api-version=2024-02-01"
+        );
+        assert_eq!(embedder.dimension(), 1536);
+        
+        let azure = AzureEmbeddingConfig {
+            deployment: Some("embeddings-prod".to_string()),
+            ..Default::default()
+        };
+        let embedder = AzureOpenAIEmbedder::new(&provider(Some("key")), &azure);
+        assert!(embedder.url.contains("/deployments/embeddings-prod/"));
+        assert_eq!(embedder.model_name(), "text-embedding-3-small");
+    }
+    
+    #[test]
+    fn test_falls_back_to_service_principal() {
+        let azure = AzureEmbeddingConfig {
+            tenant_id: Some("tenant".to_string()),
+            client_id: Some("client".to_string()),
+            client_secret: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let embedder = AzureOpenAIEmbedder::new(&provider(None), &azure);
+        assert!(matches!(embedder.auth, AzureAuth::ServicePrincipal { .. }));
+    }
+    
+    #[test]
+    fn test_requests_shortened_dimensions() {
+        let provider = EmbeddingProviderConfig {
+            dimensions: Some(512),
+            ..provider(Some("key"))
+        };
+        let embedder = AzureOpenAIEmbedder::new(&provider, &AzureEmbeddingConfig::default());
+        assert_eq!(embedder.dimension(), 512);
+        assert_eq!(request_body(&["a".to_string()], embedder.dimensions)["dimensions"], 512);
+        assert!(request_body(&["a".to_string()], None).get("dimensions").is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_api_key_header() {
+        let embedder = AzureOpenAIEmbedder::new(&provider(Some("key")), &AzureEmbeddingConfig::default());
+        assert_eq!(embedder.auth_header().await.unwrap(), ("api-key", "key".to_string()));
+    }
+}
@@ -0,0 +1,309 @@
+//! AWS Bedrock embedding provider
+//!
+//! Calls `InvokeModel` on the Bedrock runtime with SigV4-signed requests,
+//! using credentials and region from the default AWS chain. Titan models
+//! embed one text per call, so batches are sent as concurrent calls; Cohere
+//! models take up to 96 texts per call.
+
+use super::{check_status, with_retry, Embedder};
+use crate::config::{BedrockEmbeddingConfig, EmbeddingProviderConfig};
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use aws_config::{BehaviorVersion, SdkConfig};
+use aws_credential_types::provider::ProvideCredentials;
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_sigv4::sign::v4;
+use futures::{StreamExt, TryStreamExt};
+use serde::Deserialize;
+use std::time::{Duration, SystemTime};
+use tokio::sync::OnceCell;
+
+/// Titan calls in flight at once for one batch
+const TITAN_CONCURRENCY: usize = 8;
+
+/// Texts per Cohere call
+const COHERE_BATCH_SIZE: usize = 96;
+
+/// Request and response format of a Bedrock model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelFamily {
+    /// `amazon.titan-embed-*`: `{"inputText"}` in, `{"embedding"}` out
+    Titan,
+    /// `cohere.embed-*`: `{"texts", "input_type"}` in, `{"embeddings"}` out
+    Cohere,
+}
+
+impl ModelFamily {
+    fn of(model: &str) -> Option<Self> {
+        if model.starts_with("amazon.titan-embed") {
+            Some(ModelFamily::Titan)
+        } else if model.starts_with("cohere.embed") {
+            Some(ModelFamily::Cohere)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TitanResponse {
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct CohereResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// AWS Bedrock embedding client
+pub struct BedrockEmbedder {
+    client: reqwest::Client,
+    aws: OnceCell<SdkConfig>,
+    family: ModelFamily,
+    model: String,
+    dimension: usize,
+    region: Option<String>,
+    endpoint: Option<String>,
+    input_type: String,
+}
+
+impl BedrockEmbedder {
+    /// Create an embedder for the Bedrock model `provider.model`
+    ///
+    /// `default_dimension` is used for models whose dimension isn't known.
+    ///
+    /// # Panics
+    ///
+    /// If the model is neither a Titan nor a Cohere embedding model.
+    pub fn new(
+        provider: &EmbeddingProviderConfig,
+        bedrock: &BedrockEmbeddingConfig,
+        default_dimension: usize,
+    ) -> Self {
+        let family = ModelFamily::of(&provider.model)
+            .unwrap_or_else(|| panic!("Unsupported Bedrock embedding model: {}", provider.model));
+        
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client");
+        
+        Self {
+            client,
+            aws: OnceCell::new(),
+            family,
+            model: provider.model.clone(),
+            dimension: bedrock_dimension(&provider.model).unwrap_or(default_dimension),
+            region: bedrock.region.clone(),
+            endpoint: provider.api_base.clone(),
+            input_type: bedrock.input_type.clone(),
+        }
+    }
+    
+    fn request_body(&self, texts: &[String]) -> serde_json::Value {
+        match self.family {
+            ModelFamily::Titan => serde_json::json!({ "inputText": texts[0] }),
+            ModelFamily::Cohere => serde_json::json!({
+                "texts": texts,
+                "input_type": self.input_type,
+                "truncate": "END",
+            }),
+        }
+    }
+    
+    /// Invoke the model on `texts` (one text for Titan)
+    async fn invoke(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let aws = self
+            .aws
+            .get_or_init(|| aws_config::load_defaults(BehaviorVersion::latest()))
+            .await;
+        
+        let region = self
+            .region
+            .clone()
+            .or_else(|| aws.region().map(|r| r.to_string()))
+            .ok_or_else(|| AppError::Configuration {
+                message: "AWS region is not configured for Bedrock".to_string(),
+            })?;
+        let credentials = aws
+            .credentials_provider()
+            .ok_or_else(|| AppError::Configuration {
+                message: "AWS credentials are not configured".to_string(),
+            })?
+            .provide_credentials()
+            .await
+            .map_err(|e| AppError::EmbeddingError {
+                message: format!("Failed to load AWS credentials: {}", e),
+            })?;
+        
+        let url = invoke_url(self.endpoint.as_deref(), &region, &self.model);
+        let body = serde_json::to_vec(&self.request_body(texts))?;
+        let headers = [
+            ("content-type", "application/json"),
+            ("accept", "application/json"),
+        ];
+        
+        // Sign the request with SigV4
+        let identity = credentials.into();
+        let signing_params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(&region)
+            .name("bedrock")
+            .time(SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()
+            .map_err(|e| AppError::Internal {
+                message: format!("Failed to build signing params: {}", e),
+            })?
+            .into();
+        let signable = SignableRequest::new(
+            "POST",
+            url.as_str(),
+            headers.iter().copied(),
+            SignableBody::Bytes(&body),
+        )
+        .and_then(|request| sign(request, &signing_params))
+        .map_err(|e| AppError::Internal {
+            message: format!("Failed to sign Bedrock request: {}", e),
+        })?;
+        let (instructions, _signature) = signable.into_parts();
+        
+        let mut request = self.client.post(&url).body(body.clone());
+        for (name, value) in headers.iter().copied().chain(instructions.headers()) {
+            request = request.header(name, value);
+        }
+        
+        let response = request.send().await.map_err(|e| AppError::EmbeddingError {
+            message: format!("Request failed: {}", e),
+        })?;
+        let body = check_status(response).await?.bytes().await.map_err(|e| AppError::EmbeddingError {
+            message: format!("Failed to read response: {}", e),
+        })?;
+        self.parse_response(&body)
+    }
+    
+    fn parse_response(&self, body: &[u8]) -> Result<Vec<Vec<f32>>> {
+        let parsed = match self.family {
+            ModelFamily::Titan => serde_json::from_slice::<TitanResponse>(body).map(|r| vec![r.embedding]),
+            ModelFamily::Cohere => serde_json::from_slice::<CohereResponse>(body).map(|r| r.embeddings),
+        };
+        parsed.map_err(|e| AppError::EmbeddingError {
+            message: format!("Failed to parse response: {}", e),
+        })
+    }
+}
+
+#[async_trait]
+impl Embedder for BedrockEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let texts = [text.to_string()];
+        let embeddings = with_retry(|| self.invoke(&texts)).await?;
+        embeddings.into_iter().next().ok_or_else(|| AppError::EmbeddingError {
+            message: "Empty response".to_string(),
+        })
+    }
+    
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        match self.family {
+            ModelFamily::Titan => {
+                let calls: Vec<_> = texts
+                    .chunks(1)
+                    .map(|text| with_retry(move || self.invoke(text)))
+                    .collect();
+                let embeddings: Vec<Vec<Vec<f32>>> = futures::stream::iter(calls)
+                    .buffered(TITAN_CONCURRENCY)
+                    .try_collect()
+                    .await?;
+                Ok(embeddings.into_iter().flatten().collect())
+            }
+            ModelFamily::Cohere => {
+                let mut all_embeddings = Vec::with_capacity(texts.len());
+                for chunk in texts.chunks(COHERE_BATCH_SIZE) {
+                    all_embeddings.extend(with_retry(|| self.invoke(chunk)).await?);
+                }
+                Ok(all_embeddings)
+            }
+        }
+    }
+    
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+    
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+/// Embedding dimension of a Bedrock model, if known
+fn bedrock_dimension(model: &str) -> Option<usize> {
+    if model.starts_with("amazon.titan-embed-text-v2") {
+        Some(1024)
+    } else if model.starts_with("amazon.titan-embed-text-v1") || model.starts_with("amazon.titan-embed-g1-text") {
+        Some(1536)
+    } else if model.starts_with("cohere.embed-english-v3") || model.starts_with("cohere.embed-multilingual-v3") {
+        Some(1024)
+    } else {
+        None
+    }
+}
+
+/// `InvokeModel` URL; the `:` in versioned model IDs must be escaped
+fn invoke_url(endpoint: Option<&str>, region: &str, model: &str) -> String {
+    let endpoint = endpoint
+        .map(|e| e.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| format!("https://bedrock-runtime.{}.amazonaws.com", region));
+    format!("{}/model/{}/invoke", endpoint, model.replace(':', "%3A"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn embedder(model: &str) -> BedrockEmbedder {
+        let provider = EmbeddingProviderConfig {
+            provider: "bedrock".to_string(),
+            api_key: None,
+            api_base: None,
+            model: model.to_string(),
+            dimensions: None,
+        };
+        BedrockEmbedder::new(&provider, &BedrockEmbeddingConfig::default(), 768)
+    }
+    
+    #[test]
+    fn test_titan_request_and_response() {
+        let titan = embedder("amazon.titan-embed-text-v2:0");
+        assert_eq!(titan.dimension(), 1024);
+        assert_eq!(
+            titan.request_body(&["graph neural networks".to_string()]),
+            serde_json::json!({ "inputText": "graph neural networks" })
+        );
+        let parsed = titan.parse_response(br#"{"embedding": [0.5, -0.5], "inputTextTokenCount": 3}"#).unwrap();
+        assert_eq!(parsed, vec![vec![0.5, -0.5]]);
+    }
+    
+    #[test]
+    fn test_cohere_request_and_response() {
+        let cohere = embedder("cohere.embed-english-v3");
+        assert_eq!(cohere.dimension(), 1024);
+        let body = cohere.request_body(&["a".to_string(), "b".to_string()]);
+        assert_eq!(body["texts"], serde_json::json!(["a", "b"]));
+        assert_eq!(body["input_type"], "search_document");
+        
+        let parsed = cohere.parse_response(br#"{"id": "x", "embeddings": [[1.0], [2.0]], "texts": ["a", "b"]}"#).unwrap();
+        assert_eq!(parsed, vec![vec![1.0], vec![2.0]]);
+    }
+    
+    #[test]
+    fn test_invoke_url() {
+        assert_eq!(
+            invoke_url(None, "us-east-1", "amazon.titan-embed-text-v2:0"),
+            "https://bedrock-runtime.us-east-1.amazonaws.com/model/amazon.titan-embed-text-v2%3A0/invoke"
+        );
+        assert_eq!(
+            invoke_url(Some("https://vpce.example.com/"), "us-east-1", "cohere.embed-english-v3"),
+            "https://vpce.example.com/model/cohere.embed-english-v3/invoke"
+        );
+    }
+}
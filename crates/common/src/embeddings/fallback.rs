@@ -0,0 +1,196 @@
+//! Embedding provider fallback chain
+
+use super::{Embedder, TaggedEmbeddings};
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Embedder that tries providers in order
+///
+/// A provider that fails with a retryable error (down, timing out,
+/// rate-limited) hands the call to the next one and is then skipped for
+/// `cooldown`, so a throttled primary isn't hit by every call. Errors that
+/// would fail the same way anywhere, like rejected input, are returned
+/// without trying the rest. When every provider is cooling down they are
+/// all tried anyway, in order.
+///
+/// [`Embedder::model_name`] and [`Embedder::dimension`] describe the
+/// primary; [`Embedder::embed_batch_tagged`] names the provider that
+/// answered.
+pub struct FallbackEmbedder {
+    embedders: Vec<Arc<dyn Embedder>>,
+    cooldown: Duration,
+    /// When each provider last failed
+    failed_at: Mutex<Vec<Option<Instant>>>,
+}
+
+impl FallbackEmbedder {
+    /// Chain `embedders`, primary first
+    ///
+    /// # Panics
+    ///
+    /// If `embedders` is empty.
+    pub fn new(embedders: Vec<Arc<dyn Embedder>>, cooldown: Duration) -> Self {
+        assert!(!embedders.is_empty(), "fallback chain needs at least one embedder");
+        let failed_at = Mutex::new(vec![None; embedders.len()]);
+        Self { embedders, cooldown, failed_at }
+    }
+    
+    /// Provider indexes to try: those not cooling down, then the rest
+    fn order(&self) -> Vec<usize> {
+        let failed_at = self.failed_at.lock().unwrap();
+        let cooling = |i: &usize| failed_at[*i].is_some_and(|at| at.elapsed() < self.cooldown);
+        let (cooling, ready): (Vec<usize>, Vec<usize>) = (0..self.embedders.len()).partition(cooling);
+        ready.into_iter().chain(cooling).collect()
+    }
+    
+    fn set_failed(&self, index: usize, failed: bool) {
+        self.failed_at.lock().unwrap()[index] = failed.then(Instant::now);
+    }
+}
+
+#[async_trait]
+impl Embedder for FallbackEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let tagged = self.embed_batch_tagged(&[text.to_string()]).await?;
+        tagged.embeddings.into_iter().next().ok_or_else(|| AppError::EmbeddingError {
+            message: "Empty response".to_string(),
+        })
+    }
+    
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(self.embed_batch_tagged(texts).await?.embeddings)
+    }
+    
+    async fn embed_batch_tagged(&self, texts: &[String]) -> Result<TaggedEmbeddings> {
+        let mut last_error = None;
+        
+        for index in self.order() {
+            let embedder = &self.embedders[index];
+            match embedder.embed_batch(texts).await {
+                Ok(embeddings) => {
+                    self.set_failed(index, false);
+                    if index > 0 {
+                        tracing::info!(model = %embedder.model_name(), "Embedded with fallback provider");
+                    }
+                    return Ok(TaggedEmbeddings {
+                        model: embedder.model_name().to_string(),
+                        embeddings,
+                    });
+                }
+                Err(e) if e.is_retryable() => {
+                    tracing::warn!(
+                        model = %embedder.model_name(),
+                        error = %e,
+                        "Embedding provider failed, trying the next one"
+                    );
+                    self.set_failed(index, true);
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        
+        Err(last_error.unwrap_or_else(|| AppError::EmbeddingError {
+            message: "No embedding provider available".to_string(),
+        }))
+    }
+    
+    fn model_name(&self) -> &str {
+        self.embedders[0].model_name()
+    }
+    
+    fn dimension(&self) -> usize {
+        self.embedders[0].dimension()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::{HashEmbedder, MockEmbedder};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    
+    /// Fails every call with `error`, counting calls
+    struct FailingEmbedder {
+        error: fn() -> AppError,
+        calls: AtomicUsize,
+    }
+    
+    impl FailingEmbedder {
+        fn new(error: fn() -> AppError) -> Arc<Self> {
+            Arc::new(Self { error, calls: AtomicUsize::new(0) })
+        }
+    }
+    
+    #[async_trait]
+    impl Embedder for FailingEmbedder {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err((self.error)())
+        }
+        
+        async fn embed_batch(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err((self.error)())
+        }
+        
+        fn model_name(&self) -> &str {
+            "failing"
+        }
+        
+        fn dimension(&self) -> usize {
+            768
+        }
+    }
+    
+    fn throttled() -> AppError {
+        AppError::EmbeddingThrottled { retry_after_secs: Some(1) }
+    }
+    
+    fn rejected() -> AppError {
+        AppError::EmbeddingRejected { message: "input too long".to_string() }
+    }
+    
+    #[tokio::test]
+    async fn test_falls_back_and_tags_model() {
+        let primary = FailingEmbedder::new(throttled);
+        let chain = FallbackEmbedder::new(
+            vec![primary.clone(), Arc::new(MockEmbedder::new(768))],
+            Duration::from_secs(60),
+        );
+        
+        let tagged = chain.embed_batch_tagged(&["text".to_string()]).await.unwrap();
+        assert_eq!(tagged.model, "mock-embedding");
+        assert_eq!(tagged.embeddings.len(), 1);
+        assert_eq!(chain.model_name(), "failing");
+        
+        // The throttled primary is skipped while it cools down
+        chain.embed("text").await.unwrap();
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_primary_answers_when_healthy() {
+        let chain = FallbackEmbedder::new(
+            vec![Arc::new(HashEmbedder::new(768)), Arc::new(MockEmbedder::new(768))],
+            Duration::from_secs(60),
+        );
+        let tagged = chain.embed_batch_tagged(&["text".to_string()]).await.unwrap();
+        assert_eq!(tagged.model, "hash-embedding");
+    }
+    
+    #[tokio::test]
+    async fn test_fatal_errors_skip_the_chain() {
+        let fallback = FailingEmbedder::new(throttled);
+        let chain = FallbackEmbedder::new(
+            vec![FailingEmbedder::new(rejected), fallback.clone()],
+            Duration::from_secs(60),
+        );
+        
+        let err = chain.embed("text").await.unwrap_err();
+        assert!(matches!(err, AppError::EmbeddingRejected { .. }));
+        assert_eq!(fallback.calls.load(Ordering::SeqCst), 0);
+    }
+}
@@ -2,9 +2,25 @@
 //!
 //! Provides a unified interface for multiple embedding providers:
 //! - OpenAI (text-embedding-ada-002, text-embedding-3-small)
-//! - Anthropic
+//! - Azure OpenAI deployments of the OpenAI models
+//! - AWS Bedrock (Titan and Cohere embedding models)
 //! - Local models (e.g., E5, all-MiniLM)
+//!
+//! [`FallbackEmbedder`] chains providers, moving down the chain while the
+//! ones ahead of it are down or rate-limited.
+//! [`Preprocessor`] prepares passages and queries before they are embedded.
+
+mod azure;
+mod bedrock;
+mod fallback;
+mod preprocess;
 
+pub use azure::AzureOpenAIEmbedder;
+pub use bedrock::BedrockEmbedder;
+pub use fallback::FallbackEmbedder;
+pub use preprocess::{InputKind, Preprocessor};
+
+use crate::config::{EmbeddingConfig, EmbeddingProviderConfig};
 use crate::errors::{AppError, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -20,6 +36,17 @@ pub trait Embedder: Send + Sync {
     /// Generate embeddings for multiple texts (batch)
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
     
+    /// Generate embeddings for a batch and name the model that produced them
+    ///
+    /// Single-model embedders report [`Embedder::model_name`]; a fallback
+    /// chain reports whichever provider answered.
+    async fn embed_batch_tagged(&self, texts: &[String]) -> Result<TaggedEmbeddings> {
+        Ok(TaggedEmbeddings {
+            model: self.model_name().to_string(),
+            embeddings: self.embed_batch(texts).await?,
+        })
+    }
+    
     /// Get the model name
     fn model_name(&self) -> &str;
     
@@ -27,12 +54,24 @@ pub trait Embedder: Send + Sync {
     fn dimension(&self) -> usize;
 }
 
+/// Embeddings and the model that produced them
+///
+/// Vectors from different models aren't comparable, so stored chunks record
+/// the model and vector search can be restricted to one.
+#[derive(Debug, Clone)]
+pub struct TaggedEmbeddings {
+    pub model: String,
+    pub embeddings: Vec<Vec<f32>>,
+}
+
 /// OpenAI embedding client
 pub struct OpenAIEmbedder {
     client: reqwest::Client,
     api_key: String,
     model: String,
     dimension: usize,
+    /// Shortened output dimension sent with each request
+    dimensions: Option<usize>,
     base_url: String,
 }
 
@@ -40,6 +79,8 @@ pub struct OpenAIEmbedder {
 struct OpenAIRequest {
     input: Vec<String>,
     model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<usize>,
 }
 
 #[derive(Deserialize)]
@@ -56,12 +97,7 @@ impl OpenAIEmbedder {
     /// Create a new OpenAI embedder
     pub fn new(api_key: String, model: Option<String>, base_url: Option<String>) -> Self {
         let model = model.unwrap_or_else(|| "text-embedding-ada-002".to_string());
-        let dimension = match model.as_str() {
-            "text-embedding-ada-002" => 1536,
-            "text-embedding-3-small" => 1536,
-            "text-embedding-3-large" => 3072,
-            _ => 768,
-        };
+        let dimension = openai_dimension(&model);
         
         let client = reqwest::Client::builder()
             .timeout(Duration::from_secs(30))
@@ -73,39 +109,16 @@ impl OpenAIEmbedder {
             api_key,
             model,
             dimension,
+            dimensions: None,
             base_url: base_url.unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
         }
     }
     
-    /// Make request with retry
-    async fn request_with_retry(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
-        let max_retries = 3;
-        let mut last_error = None;
-        
-        for attempt in 0..max_retries {
-            if attempt > 0 {
-                // Exponential backoff
-                let delay = Duration::from_millis(100 * (2_u64.pow(attempt as u32)));
-                tokio::time::sleep(delay).await;
-            }
-            
-            match self.make_request(texts).await {
-                Ok(embeddings) => return Ok(embeddings),
-                Err(e) => {
-                    tracing::warn!(
-                        attempt = attempt + 1,
-                        max_retries = max_retries,
-                        error = %e,
-                        "Embedding request failed, retrying"
-                    );
-                    last_error = Some(e);
-                }
-            }
-        }
-        
-        Err(last_error.unwrap_or_else(|| AppError::EmbeddingError {
-            message: "Unknown error after retries".to_string(),
-        }))
+    /// Request `dimensions`-long embeddings, if the model can shorten them
+    pub fn with_dimensions(mut self, dimensions: Option<usize>) -> Self {
+        self.dimensions = shortened_dimension(&self.model, dimensions);
+        self.dimension = self.dimensions.unwrap_or(self.dimension);
+        self
     }
     
     async fn make_request(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
@@ -114,6 +127,7 @@ impl OpenAIEmbedder {
         let request = OpenAIRequest {
             input: texts.to_vec(),
             model: self.model.clone(),
+            dimensions: self.dimensions,
         };
         
         let response = self.client
@@ -127,13 +141,7 @@ impl OpenAIEmbedder {
                 message: format!("Request failed: {}", e),
             })?;
         
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(AppError::EmbeddingError {
-                message: format!("API error {}: {}", status, body),
-            });
-        }
+        let response = check_status(response).await?;
         
         let result: OpenAIResponse = response.json().await.map_err(|e| {
             AppError::EmbeddingError {
@@ -148,7 +156,8 @@ impl OpenAIEmbedder {
 #[async_trait]
 impl Embedder for OpenAIEmbedder {
     async fn embed(&self, text: &str) -> Result<Vec<f32>> {
-        let embeddings = self.request_with_retry(&[text.to_string()]).await?;
+        let texts = [text.to_string()];
+        let embeddings = with_retry(|| self.make_request(&texts)).await?;
         embeddings.into_iter().next().ok_or_else(|| AppError::EmbeddingError {
             message: "Empty response".to_string(),
         })
@@ -161,7 +170,7 @@ impl Embedder for OpenAIEmbedder {
         let mut all_embeddings = Vec::with_capacity(texts.len());
         
         for chunk in texts.chunks(BATCH_SIZE) {
-            let embeddings = self.request_with_retry(chunk).await?;
+            let embeddings = with_retry(|| self.make_request(chunk)).await?;
             all_embeddings.extend(embeddings);
         }
         
@@ -177,7 +186,11 @@ impl Embedder for OpenAIEmbedder {
     }
 }
 
-/// Mock embedder for testing
+/// Mock embedder for testing and offline runs
+///
+/// Vectors are pseudo-random but seeded from the text, so identical text
+/// always yields the identical unit vector. Unlike [`HashEmbedder`], similar
+/// texts are not close together.
 pub struct MockEmbedder {
     dimension: usize,
 }
@@ -186,22 +199,38 @@ impl MockEmbedder {
     pub fn new(dimension: usize) -> Self {
         Self { dimension }
     }
+    
+    fn embed_sync(&self, text: &str) -> Vec<f32> {
+        // splitmix64, seeded with the text's hash
+        let mut state = fnv1a(text);
+        let mut vector: Vec<f32> = (0..self.dimension)
+            .map(|_| {
+                state = state.wrapping_add(0x9e3779b97f4a7c15);
+                let mut z = state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+                z ^= z >> 31;
+                (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+            })
+            .collect();
+        
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        
+        vector
+    }
 }
 
 #[async_trait]
 impl Embedder for MockEmbedder {
-    async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        Ok((0..self.dimension).map(|_| rng.gen::<f32>()).collect())
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.embed_sync(text))
     }
     
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
-        let mut embeddings = Vec::with_capacity(texts.len());
-        for _ in texts {
-            embeddings.push(self.embed("").await?);
-        }
-        Ok(embeddings)
+        Ok(texts.iter().map(|t| self.embed_sync(t)).collect())
     }
     
     fn model_name(&self) -> &str {
@@ -213,28 +242,239 @@ impl Embedder for MockEmbedder {
     }
 }
 
+/// 64-bit FNV-1a hash
+fn fnv1a(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Deterministic feature-hashing embedder
+///
+/// Tokens are hashed into buckets with a signed FNV-1a hash and the result is
+/// L2-normalized, so identical text always yields identical vectors and texts
+/// sharing vocabulary land close together. Intended for synthetic corpora,
+/// load tests and relevance experiments, not production retrieval.
+pub struct HashEmbedder {
+    dimension: usize,
+}
+
+impl HashEmbedder {
+    pub fn new(dimension: usize) -> Self {
+        Self { dimension }
+    }
+    
+    fn embed_sync(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimension];
+        
+        for token in text.split(|c: char| !c.is_alphanumeric()).filter(|t| !t.is_empty()) {
+            let hash = fnv1a(&token.to_lowercase());
+            let bucket = (hash % self.dimension as u64) as usize;
+            let sign = if (hash >> 63) == 0 { 1.0 } else { -1.0 };
+            vector[bucket] += sign;
+        }
+        
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        
+        vector
+    }
+}
+
+#[async_trait]
+impl Embedder for HashEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.embed_sync(text))
+    }
+    
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|t| self.embed_sync(t)).collect())
+    }
+    
+    fn model_name(&self) -> &str {
+        "hash-embedding"
+    }
+    
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+}
+
+/// Embedding dimension of an OpenAI model
+fn openai_dimension(model: &str) -> usize {
+    match model {
+        "text-embedding-ada-002" => 1536,
+        "text-embedding-3-small" => 1536,
+        "text-embedding-3-large" => 3072,
+        _ => 768,
+    }
+}
+
+/// Validate a requested shortened dimension for an OpenAI model
+///
+/// text-embedding-3 models are trained so their leading dimensions form a
+/// usable embedding on their own (Matryoshka representation), and return
+/// any shorter size on request. Other models, and sizes that aren't
+/// shorter than the native one, get the native size with a warning.
+fn shortened_dimension(model: &str, requested: Option<usize>) -> Option<usize> {
+    let requested = requested?;
+    let native = openai_dimension(model);
+    if !model.starts_with("text-embedding-3") {
+        tracing::warn!(model = model, "Model can't shorten embeddings, ignoring dimensions");
+        return None;
+    }
+    if requested == 0 || requested >= native {
+        tracing::warn!(
+            model = model,
+            dimensions = requested,
+            native = native,
+            "Requested dimensions must be below the model's, ignoring"
+        );
+        return None;
+    }
+    Some(requested)
+}
+
+/// Run a provider request, retrying retryable failures
+///
+/// A provider-suggested delay replaces the backoff when it is longer.
+async fn with_retry<T, F, Fut>(mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let max_retries = 3;
+    let mut last_error: Option<AppError> = None;
+    
+    for attempt in 0..max_retries {
+        if attempt > 0 {
+            // Exponential backoff
+            let backoff = Duration::from_millis(100 * (2_u64.pow(attempt as u32)));
+            let delay = last_error
+                .as_ref()
+                .and_then(AppError::retry_after)
+                .map_or(backoff, |retry_after| retry_after.max(backoff));
+            tokio::time::sleep(delay).await;
+        }
+        
+        match request().await {
+            Ok(result) => return Ok(result),
+            Err(e) if !e.is_retryable() => return Err(e),
+            Err(e) => {
+                tracing::warn!(
+                    attempt = attempt + 1,
+                    max_retries = max_retries,
+                    error = %e,
+                    "Embedding request failed, retrying"
+                );
+                last_error = Some(e);
+            }
+        }
+    }
+    
+    Err(last_error.unwrap_or_else(|| AppError::EmbeddingError {
+        message: "Unknown error after retries".to_string(),
+    }))
+}
+
+/// Map a provider's error status to an embedding error
+///
+/// 429 becomes [`AppError::EmbeddingThrottled`] with the provider's
+/// `Retry-After`; other client errors fail the same way every time and are
+/// not retried.
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after_secs = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok());
+        return Err(AppError::EmbeddingThrottled { retry_after_secs });
+    }
+    
+    let body = response.text().await.unwrap_or_default();
+    let message = format!("API error {}: {}", status, body);
+    // Other 4xx responses (bad input, bad key) fail the same way every time
+    if status.is_client_error() && status != reqwest::StatusCode::REQUEST_TIMEOUT {
+        return Err(AppError::EmbeddingRejected { message });
+    }
+    Err(AppError::EmbeddingError { message })
+}
+
 /// Create an embedder based on configuration
+///
+/// `provider` picks the provider and model; provider-specific settings come
+/// from the `azure` and `bedrock` sections of `config`.
 pub fn create_embedder(
-    provider: &str,
-    api_key: Option<String>,
-    model: Option<String>,
-    base_url: Option<String>,
+    config: &EmbeddingConfig,
+    provider: &EmbeddingProviderConfig,
 ) -> Arc<dyn Embedder> {
-    match provider {
+    match provider.provider.as_str() {
         "openai" => {
-            let key = api_key.expect("OpenAI API key required");
-            Arc::new(OpenAIEmbedder::new(key, model, base_url))
+            let key = provider.api_key.clone().expect("OpenAI API key required");
+            Arc::new(
+                OpenAIEmbedder::new(key, Some(provider.model.clone()), provider.api_base.clone())
+                    .with_dimensions(provider.dimensions),
+            )
+        }
+        "azure" => {
+            Arc::new(AzureOpenAIEmbedder::new(provider, &config.azure))
+        }
+        "bedrock" => {
+            Arc::new(BedrockEmbedder::new(provider, &config.bedrock, config.dimension))
         }
         "mock" => {
             Arc::new(MockEmbedder::new(768))
         }
-        _ => {
-            tracing::warn!(provider = provider, "Unknown embedding provider, using mock");
+        "hash" => {
+            Arc::new(HashEmbedder::new(crate::DEFAULT_EMBEDDING_DIMENSION))
+        }
+        other => {
+            tracing::warn!(provider = other, "Unknown embedding provider, using mock");
             Arc::new(MockEmbedder::new(768))
         }
     }
 }
 
+/// Create the configured embedder, chained with its fallbacks
+///
+/// Fallbacks whose dimension differs from the primary's are left out, since
+/// their vectors couldn't be stored alongside the primary's.
+pub fn create_embedder_chain(config: &EmbeddingConfig) -> Arc<dyn Embedder> {
+    let primary = create_embedder(config, &config.primary());
+    if config.fallbacks.is_empty() {
+        return primary;
+    }
+    
+    let mut chain = vec![primary];
+    for fallback in &config.fallbacks {
+        let embedder = create_embedder(config, fallback);
+        if embedder.dimension() != chain[0].dimension() {
+            tracing::error!(
+                provider = %fallback.provider,
+                model = %embedder.model_name(),
+                dimension = embedder.dimension(),
+                expected = chain[0].dimension(),
+                "Fallback embedder dimension differs from the primary's, skipping it"
+            );
+            continue;
+        }
+        chain.push(embedder);
+    }
+    
+    Arc::new(FallbackEmbedder::new(chain, Duration::from_secs(config.fallback_cooldown_secs)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,6 +484,12 @@ mod tests {
         let embedder = MockEmbedder::new(768);
         let embedding = embedder.embed("test text").await.unwrap();
         assert_eq!(embedding.len(), 768);
+        
+        // Deterministic per text, different across texts
+        assert_eq!(embedding, embedder.embed("test text").await.unwrap());
+        assert_ne!(embedding, embedder.embed("other text").await.unwrap());
+        let norm: f32 = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
     }
     
     #[tokio::test]
@@ -254,4 +500,35 @@ mod tests {
         assert_eq!(embeddings.len(), 2);
         assert_eq!(embeddings[0].len(), 768);
     }
+    
+    #[test]
+    fn test_shortened_dimensions() {
+        let embedder = OpenAIEmbedder::new("key".to_string(), Some("text-embedding-3-large".to_string()), None)
+            .with_dimensions(Some(256));
+        assert_eq!(embedder.dimension(), 256);
+        let request = OpenAIRequest {
+            input: vec!["text".to_string()],
+            model: embedder.model.clone(),
+            dimensions: embedder.dimensions,
+        };
+        assert_eq!(serde_json::to_value(&request).unwrap()["dimensions"], 256);
+        
+        // ada-002 can't shorten, and a size above the native one is no shortening
+        let ada = OpenAIEmbedder::new("key".to_string(), None, None).with_dimensions(Some(256));
+        assert_eq!((ada.dimension(), ada.dimensions), (1536, None));
+        let small = OpenAIEmbedder::new("key".to_string(), Some("text-embedding-3-small".to_string()), None)
+            .with_dimensions(Some(2048));
+        assert_eq!((small.dimension(), small.dimensions), (1536, None));
+    }
+    
+    #[tokio::test]
+    async fn test_hash_embedder_deterministic() {
+        let embedder = HashEmbedder::new(768);
+        let a = embedder.embed("attention is all you need").await.unwrap();
+        let b = embedder.embed("attention is all you need").await.unwrap();
+        assert_eq!(a, b);
+        
+        let norm: f32 = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+    }
 }
@@ -0,0 +1,218 @@
+//! Embedding input preprocessing
+//!
+//! Prepares text before it is embedded: drops reference lists, collapses
+//! whitespace, adds the passage/query prefixes instruction-tuned models such
+//! as E5 were trained with, and truncates to the model's input limit with
+//! the cl100k tokenizer. The embedding worker prepares passages and the
+//! gateway prepares queries with the same [`Preprocessor`] settings, so
+//! stored vectors and query vectors come from text prepared the same way.
+
+use crate::chunking::detect_sections;
+use crate::config::PreprocessConfig;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tiktoken_rs::CoreBPE;
+
+/// Input limit of models not listed in [`model_max_tokens`]
+const DEFAULT_MAX_TOKENS: usize = 8191;
+
+/// What an input is embedded as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputKind {
+    /// Document text to be searched
+    #[default]
+    Passage,
+    /// Search query
+    Query,
+}
+
+/// Prepares embedding inputs
+#[derive(Debug, Clone)]
+pub struct Preprocessor {
+    strip_references: bool,
+    collapse_whitespace: bool,
+    passage_prefix: String,
+    query_prefix: String,
+    max_tokens: usize,
+}
+
+impl Default for Preprocessor {
+    /// Only truncates, at the default input limit
+    fn default() -> Self {
+        Self {
+            strip_references: false,
+            collapse_whitespace: false,
+            passage_prefix: String::new(),
+            query_prefix: String::new(),
+            max_tokens: DEFAULT_MAX_TOKENS,
+        }
+    }
+}
+
+impl Preprocessor {
+    /// Preprocessor for inputs to `model`
+    ///
+    /// Prefixes and the token limit not set in `config` come from the model.
+    pub fn new(config: &PreprocessConfig, model: &str) -> Self {
+        let e5 = is_e5(model);
+        let default_prefix = |prefix: &str| if e5 { prefix.to_string() } else { String::new() };
+        Self {
+            strip_references: config.strip_references,
+            collapse_whitespace: config.collapse_whitespace,
+            passage_prefix: config.passage_prefix.clone().unwrap_or_else(|| default_prefix("passage: ")),
+            query_prefix: config.query_prefix.clone().unwrap_or_else(|| default_prefix("query: ")),
+            max_tokens: config.max_tokens.unwrap_or_else(|| model_max_tokens(model)),
+        }
+    }
+    
+    /// Prepare one input
+    pub fn apply(&self, text: &str, kind: InputKind) -> String {
+        let mut text = match kind {
+            InputKind::Passage if self.strip_references => strip_references(text),
+            _ => text.to_string(),
+        };
+        if self.collapse_whitespace {
+            text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        
+        let prefix = match kind {
+            InputKind::Passage => &self.passage_prefix,
+            InputKind::Query => &self.query_prefix,
+        };
+        let budget = self.max_tokens.saturating_sub(count_tokens(prefix));
+        format!("{}{}", prefix, truncate_tokens(&text, budget))
+    }
+    
+    /// Prepare a batch of inputs of the same kind
+    pub fn apply_all(&self, texts: &[String], kind: InputKind) -> Vec<String> {
+        texts.iter().map(|text| self.apply(text, kind)).collect()
+    }
+}
+
+fn tokenizer() -> &'static CoreBPE {
+    static TOKENIZER: OnceLock<CoreBPE> = OnceLock::new();
+    TOKENIZER.get_or_init(|| tiktoken_rs::cl100k_base().expect("cl100k tokenizer data is bundled"))
+}
+
+/// Tokens in `text` under cl100k
+fn count_tokens(text: &str) -> usize {
+    tokenizer().encode_ordinary(text).len()
+}
+
+/// The longest prefix of `text` of at most `max_tokens` tokens
+fn truncate_tokens(text: &str, max_tokens: usize) -> String {
+    let tokens = tokenizer().encode_ordinary(text);
+    if tokens.len() <= max_tokens {
+        return text.to_string();
+    }
+    
+    // A cut inside a multi-byte character doesn't decode; drop tokens until
+    // it does
+    let mut end = max_tokens;
+    while end > 0 {
+        if let Ok(truncated) = tokenizer().decode(tokens[..end].to_vec()) {
+            return truncated;
+        }
+        end -= 1;
+    }
+    String::new()
+}
+
+/// Remove "References" and "Bibliography" sections
+///
+/// Text that is nothing but references is kept as it is.
+fn strip_references(text: &str) -> String {
+    let references: Vec<_> = detect_sections(text)
+        .into_iter()
+        .filter(|s| matches!(s.title.to_lowercase().as_str(), "references" | "bibliography"))
+        .collect();
+    if references.is_empty() {
+        return text.to_string();
+    }
+    
+    let mut kept = String::with_capacity(text.len());
+    let mut pos = 0;
+    for section in references {
+        kept.push_str(&text[pos..section.start_pos]);
+        pos = section.end_pos;
+    }
+    kept.push_str(&text[pos..]);
+    
+    if kept.trim().is_empty() {
+        text.to_string()
+    } else {
+        kept
+    }
+}
+
+fn is_e5(model: &str) -> bool {
+    let model = model.to_lowercase();
+    model.contains("e5-") || model.ends_with("/e5")
+}
+
+/// Input limit of `model`, in tokens
+fn model_max_tokens(model: &str) -> usize {
+    if is_e5(model) || model.starts_with("cohere.embed") {
+        512
+    } else if model.starts_with("amazon.titan-embed") {
+        8192
+    } else {
+        DEFAULT_MAX_TOKENS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_strips_references_and_collapses_whitespace() {
+        let preprocessor = Preprocessor::new(&PreprocessConfig::default(), "text-embedding-3-small");
+        let text = "5 Conclusion\nWe   win.\n\nReferences\n[1] A. Author. A paper.\nAppendix A\nProofs.";
+        assert_eq!(
+            preprocessor.apply(text, InputKind::Passage),
+            "5 Conclusion We win. Appendix A Proofs."
+        );
+        
+        // Queries are never cut, and a references-only passage is kept
+        assert_eq!(preprocessor.apply("references  on GNNs", InputKind::Query), "references on GNNs");
+        assert_eq!(
+            preprocessor.apply("References\n[1] A paper.", InputKind::Passage),
+            "References [1] A paper."
+        );
+    }
+    
+    #[test]
+    fn test_prefixes_inputs_for_e5_models() {
+        let e5 = Preprocessor::new(&PreprocessConfig::default(), "intfloat/e5-base-v2");
+        assert_eq!(e5.apply("attention", InputKind::Passage), "passage: attention");
+        assert_eq!(e5.apply("attention", InputKind::Query), "query: attention");
+        
+        let openai = Preprocessor::new(&PreprocessConfig::default(), "text-embedding-ada-002");
+        assert_eq!(openai.apply("attention", InputKind::Query), "attention");
+        
+        let custom = PreprocessConfig {
+            query_prefix: Some("Represent this question: ".to_string()),
+            ..Default::default()
+        };
+        let custom = Preprocessor::new(&custom, "text-embedding-ada-002");
+        assert_eq!(custom.apply("attention", InputKind::Query), "Represent this question: attention");
+    }
+    
+    #[test]
+    fn test_truncates_to_token_limit() {
+        let config = PreprocessConfig {
+            max_tokens: Some(16),
+            ..Default::default()
+        };
+        let text = "graph neural networks ".repeat(50);
+        
+        let e5 = Preprocessor::new(&config, "intfloat/e5-base-v2");
+        let truncated = e5.apply(&text, InputKind::Passage);
+        assert!(truncated.starts_with("passage: graph neural networks"));
+        assert!(count_tokens(&truncated) <= 16);
+        
+        assert_eq!(Preprocessor::default().apply("short text", InputKind::Passage), "short text");
+    }
+}
@@ -5,14 +5,18 @@
 //! - HTTP status code mapping
 //! - Structured error responses
 //! - Error codes for client handling
+//! - Retryable vs. fatal classification for workers and clients
+//! - RFC 7807 problem details (`application/problem+json`)
 
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Result type alias using AppError
@@ -58,6 +62,7 @@ pub enum ErrorCode {
     DatabaseError,
     ConnectionError,
     TransactionError,
+    QueryTimeout,
     
     // External service errors (8xxx)
     UpstreamError,
@@ -66,11 +71,13 @@ pub enum ErrorCode {
     CircuitBreakerOpen,
     QueueError,
     CacheError,
+    StorageError,
     
     // Internal errors (9xxx)
     InternalError,
     ConfigurationError,
     SerializationError,
+    RequestTimeout,
     
     // Service unavailable
     ServiceUnavailable,
@@ -116,6 +123,7 @@ impl ErrorCode {
             ErrorCode::DatabaseError => 7001,
             ErrorCode::ConnectionError => 7002,
             ErrorCode::TransactionError => 7003,
+            ErrorCode::QueryTimeout => 7004,
             
             // External (8xxx)
             ErrorCode::UpstreamError => 8001,
@@ -124,17 +132,50 @@ impl ErrorCode {
             ErrorCode::CircuitBreakerOpen => 8004,
             ErrorCode::QueueError => 8005,
             ErrorCode::CacheError => 8006,
+            ErrorCode::StorageError => 8007,
             
             // Internal (9xxx)
             ErrorCode::InternalError => 9001,
             ErrorCode::ConfigurationError => 9002,
             ErrorCode::SerializationError => 9003,
+            ErrorCode::RequestTimeout => 9004,
             
             ErrorCode::ServiceUnavailable => 9999,
         }
     }
 }
 
+impl ErrorCode {
+    /// Problem type URI for this error (RFC 7807 `type`)
+    pub fn problem_type(&self) -> String {
+        format!("{}/{}", PROBLEM_TYPE_BASE, self.name().to_lowercase().replace('_', "-"))
+    }
+    
+    /// Short human-readable summary of this kind of error (RFC 7807 `title`)
+    pub fn title(&self) -> String {
+        let name = self.name().to_lowercase().replace('_', " ");
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => name,
+        }
+    }
+    
+    /// Serialized name, e.g. `VALIDATION_ERROR`
+    fn name(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+/// Base URI of the problem types documented for each [`ErrorCode`]
+pub const PROBLEM_TYPE_BASE: &str = "https://paperforge.dev/problems";
+
+/// Media type of RFC 7807 problem details
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
 /// Application error types
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -145,6 +186,9 @@ pub enum AppError {
         field: Option<String> 
     },
     
+    #[error("Validation failed: {errors}")]
+    InvalidFields { errors: ValidationErrors },
+    
     #[error("Required field missing: {field}")]
     MissingField { field: String },
     
@@ -195,6 +239,9 @@ pub enum AppError {
     #[error("Rate limit exceeded: {limit} requests per second")]
     RateLimited { limit: u32 },
     
+    #[error("Quota exceeded: {message}")]
+    QuotaExceeded { message: String },
+    
     // Database errors
     #[error("Database error: {0}")]
     Database(#[from] sea_orm::DbErr),
@@ -202,6 +249,9 @@ pub enum AppError {
     #[error("Database connection error: {message}")]
     DatabaseConnection { message: String },
     
+    #[error("Query timed out after {timeout_ms}ms")]
+    QueryTimeout { timeout_ms: u64 },
+    
     // External service errors
     #[error("Embedding service error: {message}")]
     EmbeddingError { message: String },
@@ -209,6 +259,12 @@ pub enum AppError {
     #[error("Embedding timeout after {timeout_ms}ms")]
     EmbeddingTimeout { timeout_ms: u64 },
     
+    #[error("Embedding request rejected: {message}")]
+    EmbeddingRejected { message: String },
+    
+    #[error("Embedding provider rate limit exceeded")]
+    EmbeddingThrottled { retry_after_secs: Option<u64> },
+    
     #[error("Circuit breaker open for service: {service}")]
     CircuitBreakerOpen { service: String },
     
@@ -218,6 +274,9 @@ pub enum AppError {
     #[error("Cache error: {message}")]
     CacheError { message: String },
     
+    #[error("Storage error: {message}")]
+    StorageError { message: String },
+    
     #[error("HTTP client error: {0}")]
     HttpClient(#[from] reqwest::Error),
     
@@ -234,6 +293,9 @@ pub enum AppError {
     #[error("Service unavailable: {message}")]
     ServiceUnavailable { message: String },
     
+    #[error("Request timed out after {timeout_ms}ms")]
+    RequestTimeout { timeout_ms: u64 },
+    
     // Generic
     #[error("{0}")]
     Other(#[from] anyhow::Error),
@@ -244,6 +306,7 @@ impl AppError {
     pub fn code(&self) -> ErrorCode {
         match self {
             AppError::Validation { .. } => ErrorCode::ValidationError,
+            AppError::InvalidFields { .. } => ErrorCode::ValidationError,
             AppError::MissingField { .. } => ErrorCode::MissingField,
             AppError::InvalidFormat { .. } => ErrorCode::InvalidFormat,
             AppError::PayloadTooLarge { .. } => ErrorCode::PayloadTooLarge,
@@ -259,18 +322,24 @@ impl AppError {
             AppError::Duplicate { .. } => ErrorCode::Conflict,
             AppError::DuplicateIdempotencyKey { .. } => ErrorCode::DuplicateIdempotencyKey,
             AppError::RateLimited { .. } => ErrorCode::RateLimited,
+            AppError::QuotaExceeded { .. } => ErrorCode::QuotaExceeded,
             AppError::Database(_) => ErrorCode::DatabaseError,
             AppError::DatabaseConnection { .. } => ErrorCode::ConnectionError,
+            AppError::QueryTimeout { .. } => ErrorCode::QueryTimeout,
             AppError::EmbeddingError { .. } => ErrorCode::EmbeddingError,
             AppError::EmbeddingTimeout { .. } => ErrorCode::EmbeddingTimeout,
+            AppError::EmbeddingRejected { .. } => ErrorCode::EmbeddingError,
+            AppError::EmbeddingThrottled { .. } => ErrorCode::UpstreamError,
             AppError::CircuitBreakerOpen { .. } => ErrorCode::CircuitBreakerOpen,
             AppError::QueueError { .. } => ErrorCode::QueueError,
             AppError::CacheError { .. } => ErrorCode::CacheError,
+            AppError::StorageError { .. } => ErrorCode::StorageError,
             AppError::HttpClient(_) => ErrorCode::UpstreamError,
             AppError::Internal { .. } => ErrorCode::InternalError,
             AppError::Configuration { .. } => ErrorCode::ConfigurationError,
             AppError::Serialization(_) => ErrorCode::SerializationError,
             AppError::ServiceUnavailable { .. } => ErrorCode::ServiceUnavailable,
+            AppError::RequestTimeout { .. } => ErrorCode::RequestTimeout,
             AppError::Other(_) => ErrorCode::InternalError,
         }
     }
@@ -280,6 +349,7 @@ impl AppError {
         match self {
             // 400 Bad Request
             AppError::Validation { .. } |
+            AppError::InvalidFields { .. } |
             AppError::MissingField { .. } |
             AppError::InvalidFormat { .. } => StatusCode::BAD_REQUEST,
             
@@ -306,7 +376,8 @@ impl AppError {
             AppError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
             
             // 429 Too Many Requests
-            AppError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::RateLimited { .. } |
+            AppError::QuotaExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
             
             // 500 Internal Server Error
             AppError::Database(_) |
@@ -319,13 +390,36 @@ impl AppError {
             // 502 Bad Gateway
             AppError::EmbeddingError { .. } |
             AppError::EmbeddingTimeout { .. } |
+            AppError::EmbeddingRejected { .. } |
             AppError::HttpClient(_) => StatusCode::BAD_GATEWAY,
             
             // 503 Service Unavailable
+            AppError::EmbeddingThrottled { .. } |
             AppError::CircuitBreakerOpen { .. } |
             AppError::QueueError { .. } |
             AppError::CacheError { .. } |
+            AppError::StorageError { .. } |
             AppError::ServiceUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            
+            // 504 Gateway Timeout
+            AppError::QueryTimeout { .. } |
+            AppError::RequestTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+    
+    /// Machine-readable details for the response body
+    ///
+    /// Validation errors report `{field: [messages]}`.
+    pub fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            AppError::InvalidFields { errors } => serde_json::to_value(errors).ok(),
+            AppError::Validation { message, field: Some(field) } => {
+                Some(serde_json::json!({ field: [message] }))
+            }
+            AppError::MissingField { field } => {
+                Some(serde_json::json!({ field: ["is required"] }))
+            }
+            _ => None,
         }
     }
     
@@ -338,15 +432,179 @@ impl AppError {
     pub fn is_client_error(&self) -> bool {
         self.status_code().is_client_error()
     }
+    
+    /// Check if the failed operation may succeed when retried
+    ///
+    /// Transient failures (connectivity, timeouts, throttling, unavailable
+    /// dependencies) are retryable; bad input, auth failures and bugs are
+    /// not, and retrying them only delays the dead letter queue.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AppError::RateLimited { .. } |
+            AppError::DatabaseConnection { .. } |
+            AppError::EmbeddingError { .. } |
+            AppError::EmbeddingTimeout { .. } |
+            AppError::EmbeddingThrottled { .. } |
+            AppError::CircuitBreakerOpen { .. } |
+            AppError::QueueError { .. } |
+            AppError::CacheError { .. } |
+            AppError::StorageError { .. } |
+            AppError::ServiceUnavailable { .. } |
+            AppError::RequestTimeout { .. } => true,
+            
+            AppError::Database(err) => matches!(
+                err,
+                sea_orm::DbErr::Conn(_) | sea_orm::DbErr::ConnectionAcquire(_)
+            ),
+            
+            AppError::HttpClient(err) => {
+                err.is_timeout()
+                    || err.is_connect()
+                    || err.status().is_some_and(|status| {
+                        status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+                    })
+            }
+            
+            _ => false,
+        }
+    }
+    
+    /// How long to wait before retrying, when the error suggests a delay
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AppError::RateLimited { .. } => Some(Duration::from_secs(1)),
+            AppError::EmbeddingThrottled { retry_after_secs } => {
+                Some(Duration::from_secs(retry_after_secs.unwrap_or(5)))
+            }
+            AppError::CircuitBreakerOpen { .. } => Some(Duration::from_secs(30)),
+            AppError::ServiceUnavailable { .. } => Some(Duration::from_secs(5)),
+            _ => None,
+        }
+    }
+}
+
+/// Errors that can tell a retryable failure from a fatal one
+///
+/// Implemented by [`AppError`] and the workers' own error types so queue
+/// consumers can settle failed messages uniformly.
+pub trait Retryable: fmt::Display {
+    /// Check if the failed operation may succeed when retried
+    fn is_retryable(&self) -> bool;
+    
+    /// How long to wait before retrying, when known
+    fn retry_after(&self) -> Option<Duration>;
+}
+
+impl Retryable for AppError {
+    fn is_retryable(&self) -> bool {
+        AppError::is_retryable(self)
+    }
+    
+    fn retry_after(&self) -> Option<Duration> {
+        AppError::retry_after(self)
+    }
+}
+
+impl From<validator::ValidationErrors> for AppError {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        AppError::InvalidFields { errors: errors.into() }
+    }
+}
+
+/// Validation failures grouped by field
+///
+/// Nested fields use dotted paths and list items their index, e.g.
+/// `paper.title` or `authors[1].name`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ValidationErrors {
+    fields: BTreeMap<String, Vec<String>>,
+}
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Record a failure for `field`
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.fields.entry(field.into()).or_default().push(message.into());
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+    
+    /// Messages for each failing field
+    pub fn fields(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.fields
+    }
+    
+    fn collect(&mut self, prefix: &str, errors: &validator::ValidationErrors) {
+        use validator::ValidationErrorsKind;
+        
+        for (field, kind) in errors.errors() {
+            let path = if prefix.is_empty() {
+                field.to_string()
+            } else {
+                format!("{}.{}", prefix, field)
+            };
+            match kind {
+                ValidationErrorsKind::Field(failures) => {
+                    for failure in failures {
+                        let message = failure
+                            .message
+                            .as_ref()
+                            .map(|m| m.to_string())
+                            .unwrap_or_else(|| format!("failed `{}` check", failure.code));
+                        self.add(path.clone(), message);
+                    }
+                }
+                ValidationErrorsKind::Struct(nested) => self.collect(&path, nested),
+                ValidationErrorsKind::List(items) => {
+                    for (index, nested) in items {
+                        self.collect(&format!("{}[{}]", path, index), nested);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl From<validator::ValidationErrors> for ValidationErrors {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        let mut collected = Self::new();
+        collected.collect("", &errors);
+        collected
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (field, messages) in &self.fields {
+            for message in messages {
+                if !first {
+                    f.write_str("; ")?;
+                }
+                write!(f, "{}: {}", field, message)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Structured error response for API
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// Error responses carry a copy in their extensions so middleware can fill
+/// in `request_id` and re-render the body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: ErrorDetails,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorDetails {
     pub code: ErrorCode,
     pub message: String,
@@ -356,11 +614,58 @@ pub struct ErrorDetails {
     pub request_id: Option<String>,
 }
 
+/// RFC 7807 problem details
+///
+/// Alternative error body for clients that standardize on problem details.
+/// PaperForge's `code` and validation `errors` are carried as extension
+/// members.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    pub code: ErrorCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<serde_json::Value>,
+}
+
+impl ProblemDetails {
+    /// Problem details for an error rendered with `status`
+    pub fn new(status: StatusCode, error: &ErrorDetails) -> Self {
+        Self {
+            problem_type: error.code.problem_type(),
+            title: error.code.title(),
+            status: status.as_u16(),
+            detail: error.message.clone(),
+            instance: error.request_id.clone(),
+            code: error.code,
+            errors: error.details.clone(),
+        }
+    }
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = (status, Json(self)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let status = self.status_code();
         let code = self.code();
         let message = self.to_string();
+        let retry_after = self.retry_after();
+        let details = self.details();
         
         // Log based on severity
         if self.is_server_error() {
@@ -383,12 +688,39 @@ impl IntoResponse for AppError {
             error: ErrorDetails {
                 code,
                 message,
-                details: None,
-                request_id: None, // Should be filled by middleware
+                details,
+                request_id: None, // Filled in by middleware
             },
         };
         
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body.clone())).into_response();
+        response.extensions_mut().insert(body);
+        if let Some(delay) = retry_after {
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(delay.as_secs().max(1)),
+            );
+        }
+        response
+    }
+}
+
+impl From<AppError> for tonic::Status {
+    fn from(err: AppError) -> Self {
+        let message = err.to_string();
+        match err.status_code() {
+            StatusCode::BAD_REQUEST | StatusCode::PAYLOAD_TOO_LARGE => {
+                tonic::Status::invalid_argument(message)
+            }
+            StatusCode::UNAUTHORIZED => tonic::Status::unauthenticated(message),
+            StatusCode::FORBIDDEN => tonic::Status::permission_denied(message),
+            StatusCode::NOT_FOUND => tonic::Status::not_found(message),
+            StatusCode::CONFLICT => tonic::Status::already_exists(message),
+            StatusCode::TOO_MANY_REQUESTS => tonic::Status::resource_exhausted(message),
+            StatusCode::SERVICE_UNAVAILABLE => tonic::Status::unavailable(message),
+            StatusCode::GATEWAY_TIMEOUT => tonic::Status::deadline_exceeded(message),
+            _ => tonic::Status::internal(message),
+        }
     }
 }
 
@@ -430,6 +762,57 @@ mod tests {
         assert!(err.is_client_error());
     }
     
+    #[test]
+    fn test_validation_details() {
+        use validator::Validate;
+        
+        #[derive(Validate)]
+        struct Author {
+            #[validate(length(min = 1, message = "must not be empty"))]
+            name: String,
+        }
+        
+        #[derive(Validate)]
+        struct Paper {
+            #[validate(length(min = 1, max = 5))]
+            title: String,
+            #[validate(nested)]
+            authors: Vec<Author>,
+        }
+        
+        let paper = Paper {
+            title: "A very long title".into(),
+            authors: vec![Author { name: "Ada".into() }, Author { name: String::new() }],
+        };
+        let err: AppError = paper.validate().unwrap_err().into();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            err.details().unwrap(),
+            serde_json::json!({
+                "authors[1].name": ["must not be empty"],
+                "title": ["failed `length` check"],
+            })
+        );
+    }
+    
+    #[test]
+    fn test_problem_details() {
+        let details = ErrorDetails {
+            code: ErrorCode::PaperNotFound,
+            message: "Paper not found: 42".into(),
+            details: None,
+            request_id: Some("req-1".into()),
+        };
+        let problem = ProblemDetails::new(StatusCode::NOT_FOUND, &details);
+        assert_eq!(problem.problem_type, "https://paperforge.dev/problems/paper-not-found");
+        assert_eq!(problem.title, "Paper not found");
+        assert_eq!(problem.instance.as_deref(), Some("req-1"));
+        
+        let response = problem.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+    }
+    
     #[test]
     fn test_server_error() {
         let err = AppError::Internal { 
@@ -438,4 +821,33 @@ mod tests {
         assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
         assert!(err.is_server_error());
     }
+    
+    #[test]
+    fn test_retry_classification() {
+        let err = AppError::EmbeddingThrottled { retry_after_secs: Some(12) };
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(12)));
+        
+        let err = AppError::EmbeddingRejected { message: "input too long".into() };
+        assert!(!err.is_retryable());
+        assert_eq!(err.retry_after(), None);
+        
+        assert!(!AppError::InvalidApiKey.is_retryable());
+        
+        let err = AppError::QuotaExceeded { message: "monthly budget spent".into() };
+        assert!(!err.is_retryable());
+        assert_eq!(err.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        
+        let response = AppError::RateLimited { limit: 50 }.into_response();
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+    }
+    
+    #[test]
+    fn test_grpc_status_mapping() {
+        let status: tonic::Status = AppError::TenantMismatch.into();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        
+        let status: tonic::Status = AppError::InvalidApiKey.into();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
 }
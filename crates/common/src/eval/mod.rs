@@ -0,0 +1,219 @@
+//! Search evaluation
+//!
+//! Scores rankings against relevance judgments with standard retrieval
+//! metrics:
+//! - nDCG@k (binary gains)
+//! - MRR (reciprocal rank of the first relevant hit)
+//! - recall@k
+//!
+//! Judgments are read from JSONL, one case per line:
+//!
+//! ```json
+//! {"query": "attention in transformers", "relevant_papers": ["..."], "relevant_chunks": ["..."]}
+//! ```
+//!
+//! A hit is relevant when its chunk or its paper is listed. Cases judged
+//! only by paper count each paper once, at its best-ranked chunk, so a paper
+//! split into many chunks can't fill the top `k` on its own.
+
+use crate::errors::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// A query and the results that should come back for it
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalCase {
+    pub query: String,
+    
+    #[serde(default)]
+    pub relevant_papers: Vec<Uuid>,
+    
+    #[serde(default)]
+    pub relevant_chunks: Vec<Uuid>,
+}
+
+impl EvalCase {
+    /// Whether relevance is judged per paper rather than per chunk
+    fn by_paper(&self) -> bool {
+        self.relevant_chunks.is_empty()
+    }
+    
+    /// Number of relevant items, the denominator of recall
+    fn relevant_count(&self) -> usize {
+        if self.by_paper() {
+            self.relevant_papers.len()
+        } else {
+            self.relevant_chunks.len() + self.relevant_papers.len()
+        }
+    }
+}
+
+/// One ranked search result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RankedHit {
+    pub chunk_id: Uuid,
+    pub paper_id: Uuid,
+}
+
+/// Parse JSONL judgments, skipping blank lines
+pub fn parse_cases(input: &str) -> Result<Vec<EvalCase>> {
+    let mut cases = Vec::new();
+    for (line_no, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        
+        let case: EvalCase = serde_json::from_str(line).map_err(|e| AppError::Validation {
+            message: format!("line {}: {}", line_no + 1, e),
+            field: None,
+        })?;
+        if case.relevant_count() == 0 {
+            return Err(AppError::Validation {
+                message: format!("line {}: case has no relevant papers or chunks", line_no + 1),
+                field: None,
+            });
+        }
+        cases.push(case);
+    }
+    Ok(cases)
+}
+
+/// Relevance of each ranked hit, in rank order
+pub fn judge(case: &EvalCase, hits: &[RankedHit]) -> Vec<bool> {
+    let papers: HashSet<Uuid> = case.relevant_papers.iter().copied().collect();
+    let chunks: HashSet<Uuid> = case.relevant_chunks.iter().copied().collect();
+    
+    if case.by_paper() {
+        let mut seen = HashSet::new();
+        hits.iter()
+            .filter(|hit| seen.insert(hit.paper_id))
+            .map(|hit| papers.contains(&hit.paper_id))
+            .collect()
+    } else {
+        hits.iter()
+            .map(|hit| chunks.contains(&hit.chunk_id) || papers.contains(&hit.paper_id))
+            .collect()
+    }
+}
+
+/// Normalized discounted cumulative gain over the top `k`
+pub fn ndcg_at_k(relevance: &[bool], relevant_count: usize, k: usize) -> f64 {
+    let dcg: f64 = relevance.iter()
+        .take(k)
+        .enumerate()
+        .filter(|(_, relevant)| **relevant)
+        .map(|(rank, _)| 1.0 / (rank as f64 + 2.0).log2())
+        .sum();
+    let ideal: f64 = (0..relevant_count.min(k))
+        .map(|rank| 1.0 / (rank as f64 + 2.0).log2())
+        .sum();
+    
+    if ideal == 0.0 { 0.0 } else { dcg / ideal }
+}
+
+/// Reciprocal rank of the first relevant hit, 0 when there is none
+pub fn reciprocal_rank(relevance: &[bool]) -> f64 {
+    relevance.iter()
+        .position(|relevant| *relevant)
+        .map_or(0.0, |rank| 1.0 / (rank as f64 + 1.0))
+}
+
+/// Share of the relevant items found in the top `k`
+pub fn recall_at_k(relevance: &[bool], relevant_count: usize, k: usize) -> f64 {
+    if relevant_count == 0 {
+        return 0.0;
+    }
+    let found = relevance.iter().take(k).filter(|relevant| **relevant).count();
+    found.min(relevant_count) as f64 / relevant_count as f64
+}
+
+/// Metrics for one case
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CaseScore {
+    pub ndcg: f64,
+    pub mrr: f64,
+    pub recall: f64,
+}
+
+impl CaseScore {
+    /// Score a case's ranking at cutoff `k`
+    pub fn new(case: &EvalCase, hits: &[RankedHit], k: usize) -> Self {
+        let relevance = judge(case, hits);
+        let relevant_count = case.relevant_count();
+        Self {
+            ndcg: ndcg_at_k(&relevance, relevant_count, k),
+            mrr: reciprocal_rank(&relevance[..relevance.len().min(k)]),
+            recall: recall_at_k(&relevance, relevant_count, k),
+        }
+    }
+}
+
+/// Mean metrics over a set of cases
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EvalSummary {
+    pub queries: usize,
+    pub ndcg: f64,
+    pub mrr: f64,
+    pub recall: f64,
+}
+
+impl EvalSummary {
+    /// Average per-case scores
+    pub fn from_scores(scores: &[CaseScore]) -> Self {
+        if scores.is_empty() {
+            return Self::default();
+        }
+        
+        let n = scores.len() as f64;
+        Self {
+            queries: scores.len(),
+            ndcg: scores.iter().map(|s| s.ndcg).sum::<f64>() / n,
+            mrr: scores.iter().map(|s| s.mrr).sum::<f64>() / n,
+            recall: scores.iter().map(|s| s.recall).sum::<f64>() / n,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn hit(chunk: u128, paper: u128) -> RankedHit {
+        RankedHit {
+            chunk_id: Uuid::from_u128(chunk),
+            paper_id: Uuid::from_u128(paper),
+        }
+    }
+    
+    #[test]
+    fn test_metrics() {
+        let relevance = [false, true, false, true];
+        
+        assert_eq!(reciprocal_rank(&relevance), 0.5);
+        assert_eq!(recall_at_k(&relevance, 4, 2), 0.25);
+        assert_eq!(ndcg_at_k(&[true, true], 2, 10), 1.0);
+        
+        let expected = (1.0 / 3f64.log2() + 1.0 / 5f64.log2()) / (1.0 + 1.0 / 3f64.log2());
+        assert!((ndcg_at_k(&relevance, 2, 10) - expected).abs() < 1e-12);
+        assert_eq!(ndcg_at_k(&[false, false], 2, 10), 0.0);
+    }
+    
+    #[test]
+    fn test_paper_judgments_count_each_paper_once() {
+        let cases = parse_cases(&format!(
+            "{{\"query\": \"q\", \"relevant_papers\": [\"{}\", \"{}\"]}}\n\n",
+            Uuid::from_u128(10),
+            Uuid::from_u128(20)
+        )).unwrap();
+        let hits = [hit(1, 10), hit(2, 10), hit(3, 30), hit(4, 20)];
+        
+        assert_eq!(judge(&cases[0], &hits), vec![true, false, true]);
+        
+        let score = CaseScore::new(&cases[0], &hits, 2);
+        assert_eq!(score.mrr, 1.0);
+        assert_eq!(score.recall, 0.5);
+        
+        assert!(parse_cases(r#"{"query": "q"}"#).is_err());
+    }
+}
@@ -3,21 +3,55 @@
 //! Shared code for all PaperForge microservices including:
 //! - Database models and repository patterns
 //! - Embedding client abstraction
+//! - Text chunking and section detection
+//! - Crossref metadata resolution
+//! - Document downloads from user-supplied URLs, guarded against SSRF
+//! - Raw document storage (S3, local filesystem)
 //! - Error types and handling
 //! - Configuration management
 //! - Authentication utilities
+//! - Audit logging
+//! - Session and query analytics
+//! - Request deadlines and request-scoped context
 //! - Metrics and observability
+//! - SLO burn-rate tracking
+//! - LLM and embedding usage metering
+//! - Search score calibration
+//! - Saved search alerts (webhooks, email)
+//! - Context engine sessions (Postgres, optionally cached in Redis)
+//! - Search evaluation metrics
+//! - Per-tenant BM25 indexes (`bm25-index` feature)
 //! - gRPC protocol definitions
 
+pub mod alerts;
+pub mod analytics;
+pub mod audit;
 pub mod auth;
+pub mod authors;
+pub mod calibration;
+#[cfg(feature = "bm25-index")]
+pub mod bm25_index;
+pub mod chunking;
 pub mod config;
 pub mod context;
+pub mod crossref;
 pub mod db;
+pub mod deadline;
 pub mod embeddings;
 pub mod errors;
+pub mod eval;
+pub mod fetcher;
 pub mod metrics;
+pub mod outbox;
 pub mod queue;
+pub mod references;
+pub mod reindex;
+pub mod request_context;
+pub mod sessions;
+pub mod slo;
+pub mod storage;
 pub mod cache;
+pub mod usage;
 
 // gRPC proto definitions (generated at build time)
 pub mod proto {
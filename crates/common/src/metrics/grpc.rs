@@ -0,0 +1,134 @@
+//! gRPC server metrics and tracing
+//!
+//! [`GrpcMetricsLayer`] wraps a tonic server: every call runs in a `grpc`
+//! span carrying its method and request ID, and is counted in
+//! `paperforge_grpc_requests_total` and `paperforge_grpc_request_duration_seconds`
+//! by method and status code.
+//!
+//! The status is read from the response headers, where tonic puts it for
+//! calls that fail before a message is sent. Calls that get as far as
+//! streaming a response carry their status in the trailers and are counted
+//! as `Ok`.
+
+use super::METRICS_PREFIX;
+use axum::http;
+use metrics::{counter, histogram};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tonic::Code;
+use tower::{Layer, Service};
+use tracing::Instrument;
+
+/// Tower layer recording metrics and spans for incoming gRPC calls
+#[derive(Debug, Clone, Default)]
+pub struct GrpcMetricsLayer;
+
+impl GrpcMetricsLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for GrpcMetricsLayer {
+    type Service = GrpcMetricsService<S>;
+    
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcMetricsService { inner }
+    }
+}
+
+/// Service produced by [`GrpcMetricsLayer`]
+#[derive(Debug, Clone)]
+pub struct GrpcMetricsService<S> {
+    inner: S,
+}
+
+impl<S, B, R> Service<http::Request<B>> for GrpcMetricsService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<R>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+    
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+    
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        // Use the service that was polled ready and leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        
+        let method = request.uri().path().to_string();
+        let request_id = request
+            .headers()
+            .get("x-request-id")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let span = tracing::info_span!("grpc", method = %method, request_id = %request_id);
+        
+        Box::pin(
+            async move {
+                let start = Instant::now();
+                let result = inner.call(request).await;
+                let code = match &result {
+                    Ok(response) => response_code(response.headers()),
+                    Err(_) => Code::Internal,
+                };
+                record_grpc_request(&method, code, start.elapsed().as_secs_f64());
+                if code != Code::Ok {
+                    tracing::debug!(code = ?code, "gRPC call failed");
+                }
+                result
+            }
+            .instrument(span),
+        )
+    }
+}
+
+/// Status code of a response, `Ok` when it's left for the trailers
+fn response_code(headers: &http::HeaderMap) -> Code {
+    headers
+        .get("grpc-status")
+        .map(|value| Code::from_bytes(value.as_bytes()))
+        .unwrap_or(Code::Ok)
+}
+
+/// Helper to record a finished gRPC call
+pub fn record_grpc_request(method: &str, code: Code, duration_secs: f64) {
+    let status = format!("{:?}", code);
+    
+    counter!(
+        format!("{}_grpc_requests_total", METRICS_PREFIX),
+        "method" => method.to_string(),
+        "status" => status.clone()
+    )
+    .increment(1);
+    
+    histogram!(
+        format!("{}_grpc_request_duration_seconds", METRICS_PREFIX),
+        "method" => method.to_string(),
+        "status" => status
+    )
+    .record(duration_secs);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_response_code() {
+        let mut headers = http::HeaderMap::new();
+        assert_eq!(response_code(&headers), Code::Ok);
+        
+        headers.insert("grpc-status", http::HeaderValue::from_static("5"));
+        assert_eq!(response_code(&headers), Code::NotFound);
+    }
+}
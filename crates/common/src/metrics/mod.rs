@@ -1,17 +1,31 @@
 //! Metrics and observability utilities
 //!
 //! Provides Prometheus metrics with SLO-aligned histograms
-//! and standardized naming conventions.
+//! and standardized naming conventions. Per-tenant metrics carry a
+//! `tenant` label bounded by [`tenant_label`].
 
 use metrics::{
     counter, describe_counter, describe_gauge, describe_histogram, 
     gauge, histogram, Counter, Gauge, Histogram, Unit,
 };
 use std::time::Instant;
+use uuid::Uuid;
+
+mod grpc;
+mod tenants;
+
+pub use grpc::{record_grpc_request, GrpcMetricsLayer, GrpcMetricsService};
+pub use tenants::{configure_tenant_labels, optional_tenant_label, tenant_label, TenantLabels, OTHER_TENANT};
 
 /// Metrics prefix for all PaperForge metrics  
 pub const METRICS_PREFIX: &str = "paperforge";
 
+/// P50 request latency objective (in seconds)
+pub const SLO_P50_SECS: f64 = 0.050;
+
+/// P99 request latency objective (in seconds)
+pub const SLO_P99_SECS: f64 = 0.150;
+
 /// SLO-aligned histogram buckets for request latency (in seconds)
 /// Targets: P50 < 50ms, P99 < 150ms
 pub const LATENCY_BUCKETS: &[f64] = &[
@@ -19,10 +33,10 @@ pub const LATENCY_BUCKETS: &[f64] = &[
     0.005,  // 5ms
     0.010,  // 10ms
     0.025,  // 25ms
-    0.050,  // 50ms - P50 target
+    SLO_P50_SECS,  // 50ms - P50 target
     0.075,  // 75ms
     0.100,  // 100ms
-    0.150,  // 150ms - P99 target
+    SLO_P99_SECS,  // 150ms - P99 target
     0.250,  // 250ms
     0.500,  // 500ms
     1.000,  // 1s
@@ -129,6 +143,18 @@ pub fn register_metrics() {
         "Idle database connections"
     );
     
+    describe_histogram!(
+        format!("{}_db_acquire_duration_seconds", METRICS_PREFIX),
+        Unit::Seconds,
+        "Time to acquire a database connection from the pool"
+    );
+    
+    describe_counter!(
+        format!("{}_db_acquire_timeouts_total", METRICS_PREFIX),
+        Unit::Count,
+        "Database connection acquires that timed out"
+    );
+    
     describe_histogram!(
         format!("{}_db_query_duration_seconds", METRICS_PREFIX),
         Unit::Seconds,
@@ -142,6 +168,24 @@ pub fn register_metrics() {
         "Number of messages in queue"
     );
     
+    describe_gauge!(
+        format!("{}_queue_in_flight", METRICS_PREFIX),
+        Unit::Count,
+        "Messages received from the queue but not yet deleted"
+    );
+    
+    describe_gauge!(
+        format!("{}_queue_delayed", METRICS_PREFIX),
+        Unit::Count,
+        "Messages in the queue not yet available to receive"
+    );
+    
+    describe_histogram!(
+        format!("{}_queue_message_age_seconds", METRICS_PREFIX),
+        Unit::Seconds,
+        "Time messages waited in the queue before being received"
+    );
+    
     describe_counter!(
         format!("{}_queue_messages_processed_total", METRICS_PREFIX),
         Unit::Count,
@@ -161,6 +205,95 @@ pub fn register_metrics() {
         "Total cache misses"
     );
     
+    describe_histogram!(
+        format!("{}_cache_compression_ratio", METRICS_PREFIX),
+        Unit::Count,
+        "Stored size of compressed cache values over their original size"
+    );
+    
+    // Query understanding metrics
+    describe_counter!(
+        format!("{}_query_intents_total", METRICS_PREFIX),
+        Unit::Count,
+        "Parsed queries by detected intent"
+    );
+    
+    // LLM and embedding usage metrics
+    describe_counter!(
+        format!("{}_usage_tokens_total", METRICS_PREFIX),
+        Unit::Count,
+        "LLM and embedding tokens by kind, model, direction and tenant"
+    );
+    
+    describe_counter!(
+        format!("{}_usage_cost_micros_total", METRICS_PREFIX),
+        Unit::Count,
+        "Estimated LLM and embedding cost in micro-dollars"
+    );
+    
+    // Database routing metrics
+    describe_counter!(
+        format!("{}_db_reads_total", METRICS_PREFIX),
+        Unit::Count,
+        "Database reads by route (primary or replica) and reason"
+    );
+    
+    describe_gauge!(
+        format!("{}_db_replica_up", METRICS_PREFIX),
+        Unit::Count,
+        "Whether each read replica passed its last health check"
+    );
+    
+    describe_gauge!(
+        format!("{}_db_replica_lag_bytes", METRICS_PREFIX),
+        Unit::Bytes,
+        "WAL bytes each read replica has yet to replay"
+    );
+    
+    describe_gauge!(
+        format!("{}_db_replica_latency_seconds", METRICS_PREFIX),
+        Unit::Seconds,
+        "Moving average of each read replica's health check round trip"
+    );
+    
+    // gRPC server metrics
+    describe_counter!(
+        format!("{}_grpc_requests_total", METRICS_PREFIX),
+        Unit::Count,
+        "gRPC calls served, by method and status code"
+    );
+    
+    describe_histogram!(
+        format!("{}_grpc_request_duration_seconds", METRICS_PREFIX),
+        Unit::Seconds,
+        "gRPC call latency in seconds, by method and status code"
+    );
+    
+    // SLO metrics
+    describe_gauge!(
+        format!("{}_slo_error_rate", METRICS_PREFIX),
+        Unit::Count,
+        "Share of requests failing with a server error, by window"
+    );
+    
+    describe_gauge!(
+        format!("{}_slo_error_burn_rate", METRICS_PREFIX),
+        Unit::Count,
+        "Error rate over the availability error budget, by window"
+    );
+    
+    describe_gauge!(
+        format!("{}_slo_latency_burn_rate", METRICS_PREFIX),
+        Unit::Count,
+        "Share of requests slower than the P99 target over the 1% allowed, by window"
+    );
+    
+    describe_gauge!(
+        format!("{}_slo_latency_p99_seconds", METRICS_PREFIX),
+        Unit::Seconds,
+        "Estimated P99 request latency, by window"
+    );
+    
     tracing::info!("Metrics registered");
 }
 
@@ -203,47 +336,56 @@ impl RequestMetrics {
 }
 
 /// Helper to record search metrics
-pub fn record_search(duration_secs: f64, mode: &str, result_count: usize) {
+pub fn record_search(duration_secs: f64, mode: &str, result_count: usize, tenant_id: Uuid) {
+    let tenant = tenant_label(tenant_id);
+    
     counter!(
         format!("{}_search_queries_total", METRICS_PREFIX),
-        "mode" => mode.to_string()
+        "mode" => mode.to_string(),
+        "tenant" => tenant.clone()
     )
     .increment(1);
     
     histogram!(
         format!("{}_search_duration_seconds", METRICS_PREFIX),
-        "mode" => mode.to_string()
+        "mode" => mode.to_string(),
+        "tenant" => tenant.clone()
     )
     .record(duration_secs);
     
     gauge!(
         format!("{}_search_results_count", METRICS_PREFIX),
-        "mode" => mode.to_string()
+        "mode" => mode.to_string(),
+        "tenant" => tenant
     )
     .set(result_count as f64);
 }
 
 /// Helper to record embedding metrics
-pub fn record_embedding(duration_secs: f64, model: &str, batch_size: usize, success: bool) {
+pub fn record_embedding(duration_secs: f64, model: &str, success: bool, tenant_id: Option<Uuid>) {
     let status = if success { "success" } else { "error" };
+    let tenant = optional_tenant_label(tenant_id);
     
     counter!(
         format!("{}_embedding_requests_total", METRICS_PREFIX),
         "model" => model.to_string(),
-        "status" => status.to_string()
+        "status" => status.to_string(),
+        "tenant" => tenant.clone()
     )
     .increment(1);
     
     if success {
         histogram!(
             format!("{}_embedding_duration_seconds", METRICS_PREFIX),
-            "model" => model.to_string()
+            "model" => model.to_string(),
+            "tenant" => tenant
         )
         .record(duration_secs);
     } else {
         counter!(
             format!("{}_embedding_errors_total", METRICS_PREFIX),
-            "model" => model.to_string()
+            "model" => model.to_string(),
+            "tenant" => tenant
         )
         .increment(1);
     }
@@ -266,22 +408,211 @@ pub fn record_cache(hit: bool, cache_name: &str) {
     }
 }
 
+/// Helper to record how well a cache value compressed
+pub fn record_cache_compression(algorithm: &str, original_bytes: usize, stored_bytes: usize) {
+    histogram!(
+        format!("{}_cache_compression_ratio", METRICS_PREFIX),
+        "algorithm" => algorithm.to_string()
+    )
+    .record(stored_bytes as f64 / original_bytes.max(1) as f64);
+}
+
+/// Helper to record the intent of a parsed query
+pub fn record_query_intent(intent: &str) {
+    counter!(
+        format!("{}_query_intents_total", METRICS_PREFIX),
+        "intent" => intent.to_string()
+    )
+    .increment(1);
+}
+
+/// Helper to record the tokens and estimated cost of an LLM or embedding call
+pub fn record_usage(
+    kind: &str,
+    model: &str,
+    tenant_id: Option<Uuid>,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    cost_micros: u64,
+) {
+    let tenant = optional_tenant_label(tenant_id);
+    
+    for (direction, tokens) in [("prompt", prompt_tokens), ("completion", completion_tokens)] {
+        if tokens > 0 {
+            counter!(
+                format!("{}_usage_tokens_total", METRICS_PREFIX),
+                "kind" => kind.to_string(),
+                "model" => model.to_string(),
+                "direction" => direction,
+                "tenant" => tenant.clone()
+            )
+            .increment(tokens);
+        }
+    }
+    
+    counter!(
+        format!("{}_usage_cost_micros_total", METRICS_PREFIX),
+        "kind" => kind.to_string(),
+        "model" => model.to_string(),
+        "tenant" => tenant
+    )
+    .increment(cost_micros);
+}
+
+/// Helper to record how long a database query took
+pub fn record_db_query(query: &str, duration_secs: f64) {
+    histogram!(
+        format!("{}_db_query_duration_seconds", METRICS_PREFIX),
+        "query" => query.to_string()
+    )
+    .record(duration_secs);
+}
+
+/// Helper to record a connection pool's in-use and idle connections
+pub fn record_db_pool(pool: &str, active: u32, idle: u32) {
+    gauge!(
+        format!("{}_db_connections_active", METRICS_PREFIX),
+        "pool" => pool.to_string()
+    )
+    .set(active as f64);
+    
+    gauge!(
+        format!("{}_db_connections_idle", METRICS_PREFIX),
+        "pool" => pool.to_string()
+    )
+    .set(idle as f64);
+}
+
+/// Helper to record how long acquiring a pooled connection took
+pub fn record_db_acquire(pool: &str, duration_secs: f64, timed_out: bool) {
+    histogram!(
+        format!("{}_db_acquire_duration_seconds", METRICS_PREFIX),
+        "pool" => pool.to_string()
+    )
+    .record(duration_secs);
+    
+    if timed_out {
+        counter!(
+            format!("{}_db_acquire_timeouts_total", METRICS_PREFIX),
+            "pool" => pool.to_string()
+        )
+        .increment(1);
+    }
+}
+
+/// Helper to record where a database read was routed
+pub fn record_db_read(route: &str, reason: &str) {
+    counter!(
+        format!("{}_db_reads_total", METRICS_PREFIX),
+        "route" => route.to_string(),
+        "reason" => reason.to_string()
+    )
+    .increment(1);
+}
+
+/// Helper to record a read replica's health, lag and latency
+pub fn record_replica_status(replica: &str, up: bool, lag_bytes: u64, latency_secs: f64) {
+    gauge!(
+        format!("{}_db_replica_up", METRICS_PREFIX),
+        "replica" => replica.to_string()
+    )
+    .set(if up { 1.0 } else { 0.0 });
+    gauge!(
+        format!("{}_db_replica_lag_bytes", METRICS_PREFIX),
+        "replica" => replica.to_string()
+    )
+    .set(lag_bytes as f64);
+    gauge!(
+        format!("{}_db_replica_latency_seconds", METRICS_PREFIX),
+        "replica" => replica.to_string()
+    )
+    .set(latency_secs);
+}
+
+/// Helper to record a queue's message counts
+pub fn record_queue_depth(queue: &str, visible: u64, in_flight: u64, delayed: u64) {
+    gauge!(
+        format!("{}_queue_depth", METRICS_PREFIX),
+        "queue" => queue.to_string()
+    )
+    .set(visible as f64);
+    
+    gauge!(
+        format!("{}_queue_in_flight", METRICS_PREFIX),
+        "queue" => queue.to_string()
+    )
+    .set(in_flight as f64);
+    
+    gauge!(
+        format!("{}_queue_delayed", METRICS_PREFIX),
+        "queue" => queue.to_string()
+    )
+    .set(delayed as f64);
+}
+
+/// Helper to record how long a message waited before being received
+pub fn record_queue_message_age(queue: &str, age_secs: f64) {
+    histogram!(
+        format!("{}_queue_message_age_seconds", METRICS_PREFIX),
+        "queue" => queue.to_string()
+    )
+    .record(age_secs);
+}
+
+/// Helper to record a rolling window's SLO measurements
+pub fn record_slo(
+    window: &str,
+    error_rate: f64,
+    error_burn_rate: f64,
+    latency_burn_rate: f64,
+    p99_secs: Option<f64>,
+) {
+    gauge!(
+        format!("{}_slo_error_rate", METRICS_PREFIX),
+        "window" => window.to_string()
+    )
+    .set(error_rate);
+    
+    gauge!(
+        format!("{}_slo_error_burn_rate", METRICS_PREFIX),
+        "window" => window.to_string()
+    )
+    .set(error_burn_rate);
+    
+    gauge!(
+        format!("{}_slo_latency_burn_rate", METRICS_PREFIX),
+        "window" => window.to_string()
+    )
+    .set(latency_burn_rate);
+    
+    if let Some(p99_secs) = p99_secs {
+        gauge!(
+            format!("{}_slo_latency_p99_seconds", METRICS_PREFIX),
+            "window" => window.to_string()
+        )
+        .set(p99_secs);
+    }
+}
+
 /// Helper to record ingestion metrics
-pub fn record_ingestion(duration_secs: f64, chunks_created: usize, tenant_id: &str) {
+pub fn record_ingestion(duration_secs: f64, chunks_created: usize, tenant_id: Uuid) {
+    let tenant = tenant_label(tenant_id);
+    
     counter!(
         format!("{}_papers_ingested_total", METRICS_PREFIX),
-        "tenant" => tenant_id.to_string()
+        "tenant" => tenant.clone()
     )
     .increment(1);
     
     counter!(
         format!("{}_chunks_created_total", METRICS_PREFIX),
-        "tenant" => tenant_id.to_string()
+        "tenant" => tenant.clone()
     )
     .increment(chunks_created as u64);
     
     histogram!(
-        format!("{}_ingestion_duration_seconds", METRICS_PREFIX)
+        format!("{}_ingestion_duration_seconds", METRICS_PREFIX),
+        "tenant" => tenant
     )
     .record(duration_secs);
 }
@@ -0,0 +1,152 @@
+//! Tenant labels for metrics
+//!
+//! Labelling series by raw tenant UUID adds a series per tenant to every
+//! metric, without bound. Instead the most active tenants get their own
+//! label and the rest share `"other"`. Tenants on the allowlist are always
+//! labelled. The labelled set is recomputed from activity every refresh
+//! interval; in between, tenants are labelled as they're first seen while
+//! there is room under the limit.
+
+use crate::config::{AppConfig, ObservabilityConfig};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Label shared by tenants outside the labelled set
+pub const OTHER_TENANT: &str = "other";
+
+static TENANT_LABELS: OnceLock<TenantLabels> = OnceLock::new();
+
+/// Set the process-wide tenant labelling policy
+///
+/// Call once at startup, before any metric is recorded; later calls are
+/// ignored.
+pub fn configure_tenant_labels(config: &ObservabilityConfig) {
+    let _ = TENANT_LABELS.set(TenantLabels::from_config(config));
+}
+
+/// Label to record a tenant's metrics under
+pub fn tenant_label(tenant_id: Uuid) -> String {
+    TENANT_LABELS
+        .get_or_init(|| TenantLabels::from_config(&AppConfig::default().observability))
+        .label(tenant_id)
+}
+
+/// Label for a tenant that may be unknown, e.g. background work
+pub fn optional_tenant_label(tenant_id: Option<Uuid>) -> String {
+    tenant_id.map(tenant_label).unwrap_or_else(|| OTHER_TENANT.to_string())
+}
+
+/// Decides which tenants get their own label
+pub struct TenantLabels {
+    limit: usize,
+    allowlist: HashSet<Uuid>,
+    refresh: Duration,
+    state: Mutex<LabelState>,
+}
+
+struct LabelState {
+    labelled: HashSet<Uuid>,
+    /// Metrics recorded per tenant since `window_start`
+    activity: HashMap<Uuid, u64>,
+    window_start: Instant,
+}
+
+impl TenantLabels {
+    pub fn new(limit: usize, allowlist: impl IntoIterator<Item = Uuid>, refresh: Duration) -> Self {
+        Self {
+            limit,
+            allowlist: allowlist.into_iter().collect(),
+            refresh,
+            state: Mutex::new(LabelState {
+                labelled: HashSet::new(),
+                activity: HashMap::new(),
+                window_start: Instant::now(),
+            }),
+        }
+    }
+    
+    pub fn from_config(config: &ObservabilityConfig) -> Self {
+        Self::new(
+            config.metrics_tenant_limit,
+            config.metrics_tenant_allowlist.iter().copied(),
+            Duration::from_secs(config.metrics_tenant_refresh_secs),
+        )
+    }
+    
+    /// The tenant's own ID if it's labelled, `"other"` otherwise
+    pub fn label(&self, tenant_id: Uuid) -> String {
+        if self.allowlist.contains(&tenant_id) {
+            return tenant_id.to_string();
+        }
+        
+        let mut state = self.state.lock().unwrap();
+        if state.window_start.elapsed() >= self.refresh {
+            state.rank(self.limit);
+        }
+        
+        *state.activity.entry(tenant_id).or_default() += 1;
+        if state.labelled.len() < self.limit {
+            state.labelled.insert(tenant_id);
+        }
+        
+        if state.labelled.contains(&tenant_id) {
+            tenant_id.to_string()
+        } else {
+            OTHER_TENANT.to_string()
+        }
+    }
+}
+
+impl LabelState {
+    /// Label the `limit` most active tenants of the last window and start
+    /// a new one
+    fn rank(&mut self, limit: usize) {
+        let mut ranked: Vec<_> = self.activity.drain().collect();
+        ranked.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        self.labelled = ranked.into_iter().take(limit).map(|(tenant_id, _)| tenant_id).collect();
+        self.window_start = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_tenants_past_limit_share_label() {
+        let labels = TenantLabels::new(2, [], Duration::from_secs(3600));
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        
+        assert_eq!(labels.label(a), a.to_string());
+        assert_eq!(labels.label(b), b.to_string());
+        assert_eq!(labels.label(c), OTHER_TENANT);
+        assert_eq!(labels.label(a), a.to_string());
+    }
+    
+    #[test]
+    fn test_allowlisted_tenant_always_labelled() {
+        let pinned = Uuid::new_v4();
+        let labels = TenantLabels::new(0, [pinned], Duration::from_secs(3600));
+        
+        assert_eq!(labels.label(pinned), pinned.to_string());
+        assert_eq!(labels.label(Uuid::new_v4()), OTHER_TENANT);
+    }
+    
+    #[test]
+    fn test_refresh_labels_most_active() {
+        let labels = TenantLabels::new(1, [], Duration::from_secs(3600));
+        let (early, busy) = (Uuid::new_v4(), Uuid::new_v4());
+        
+        // The first tenant seen takes the only label until the next refresh
+        assert_eq!(labels.label(early), early.to_string());
+        for _ in 0..5 {
+            assert_eq!(labels.label(busy), OTHER_TENANT);
+        }
+        
+        labels.state.lock().unwrap().rank(1);
+        assert_eq!(labels.label(busy), busy.to_string());
+        assert_eq!(labels.label(early), OTHER_TENANT);
+    }
+}
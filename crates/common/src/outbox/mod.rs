@@ -0,0 +1,165 @@
+//! Transactional outbox relay
+//!
+//! Queue messages are written to the `outbox` table in the same database
+//! transaction as the rows they describe, then published to SQS by
+//! [`OutboxRelay`]. A crash between commit and publish only delays delivery;
+//! consumers must tolerate duplicates (at-least-once semantics).
+
+use crate::db::models::OutboxMessage;
+use crate::db::Repository;
+use crate::errors::Result;
+use crate::queue::{Envelope, Queue, Versioned};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+/// Logical name of the ingestion queue
+pub const INGESTION_QUEUE: &str = "ingestion";
+
+/// Logical name of the embedding queue
+pub const EMBEDDING_QUEUE: &str = "embedding";
+
+/// A queue message as written to the outbox: its body and envelope
+#[derive(Debug, Clone)]
+pub struct OutboxPayload {
+    pub payload: serde_json::Value,
+    pub envelope: Envelope,
+}
+
+impl OutboxPayload {
+    pub fn new<M: Versioned + Serialize>(message: &M) -> Self {
+        Self {
+            payload: serde_json::json!(message),
+            envelope: Envelope::for_message(message),
+        }
+    }
+
+    /// Carry the trace context of the request that produced the message
+    pub fn with_traceparent(mut self, traceparent: Option<&str>) -> Self {
+        self.envelope = self.envelope.with_traceparent(traceparent);
+        self
+    }
+}
+
+/// Relay configuration
+#[derive(Debug, Clone)]
+pub struct OutboxRelayConfig {
+    /// Maximum messages leased per poll
+    pub batch_size: u64,
+    /// Delay between polls when the outbox is empty
+    pub poll_interval: Duration,
+    /// How long a leased message is hidden from other relays
+    pub lease_secs: u64,
+}
+
+impl Default for OutboxRelayConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 50,
+            poll_interval: Duration::from_secs(1),
+            lease_secs: 60,
+        }
+    }
+}
+
+/// Publishes outbox rows to their queues
+pub struct OutboxRelay {
+    repository: Repository,
+    queues: HashMap<String, Arc<Queue>>,
+    config: OutboxRelayConfig,
+}
+
+impl OutboxRelay {
+    /// Create a relay with no queues registered
+    pub fn new(repository: Repository, config: OutboxRelayConfig) -> Self {
+        Self {
+            repository,
+            queues: HashMap::new(),
+            config,
+        }
+    }
+
+    /// Register the queue that messages with the given logical name go to
+    pub fn with_queue(mut self, name: &str, queue: Arc<Queue>) -> Self {
+        self.queues.insert(name.to_string(), queue);
+        self
+    }
+
+    /// Publish one leased batch; returns the number of messages published
+    pub async fn run_once(&self) -> Result<usize> {
+        let names: Vec<String> = self.queues.keys().cloned().collect();
+        let messages = self
+            .repository
+            .claim_outbox_batch(&names, self.config.batch_size, self.config.lease_secs)
+            .await?;
+
+        let mut by_queue: HashMap<&str, Vec<&OutboxMessage>> = HashMap::new();
+        for message in &messages {
+            by_queue.entry(message.queue.as_str()).or_default().push(message);
+        }
+
+        let mut published = 0;
+
+        for (name, batch) in by_queue {
+            // Only rows for registered queues are claimed
+            let queue = &self.queues[name];
+
+            let payloads: Vec<(&serde_json::Value, Envelope)> =
+                batch.iter().map(|m| (&m.payload, m.envelope())).collect();
+            let result = match queue.send_batch(&payloads).await {
+                Ok(result) => result,
+                Err(e) => {
+                    error!(queue = %name, error = %e, "Failed to publish outbox batch");
+                    for message in batch {
+                        self.repository
+                            .mark_outbox_failed(message.id, &e.to_string())
+                            .await?;
+                    }
+                    continue;
+                }
+            };
+
+            let sent: Vec<_> = result.sent.iter().map(|(i, _)| batch[*i].id).collect();
+            self.repository.mark_outbox_published(&sent).await?;
+            published += sent.len();
+            debug!(queue = %name, count = sent.len(), "Outbox messages published");
+
+            for failure in result.failed {
+                let message = batch[failure.index];
+                let reason = failure.message.unwrap_or(failure.code);
+                error!(
+                    outbox_id = %message.id,
+                    attempts = message.attempts,
+                    error = %reason,
+                    "Failed to publish outbox message"
+                );
+                self.repository.mark_outbox_failed(message.id, &reason).await?;
+            }
+        }
+
+        Ok(published)
+    }
+
+    /// Run the relay loop on the current runtime
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            info!(
+                queues = ?self.queues.keys().collect::<Vec<_>>(),
+                "Starting outbox relay"
+            );
+
+            loop {
+                match self.run_once().await {
+                    // Keep draining while there is a backlog
+                    Ok(n) if n as u64 >= self.config.batch_size => continue,
+                    Ok(_) => {}
+                    Err(e) => error!(error = %e, "Outbox relay poll failed"),
+                }
+                tokio::time::sleep(self.config.poll_interval).await;
+            }
+        })
+    }
+}
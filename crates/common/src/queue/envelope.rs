@@ -0,0 +1,243 @@
+//! Versioned message envelopes
+//!
+//! Every message carries an envelope as SQS message attributes: its type,
+//! the schema version of its payload, the tenant it belongs to and the W3C
+//! trace context of the request that produced it. Consumers check the
+//! envelope before decoding the body, so during a rolling deployment a
+//! message from a newer producer is handed back to the queue for a consumer
+//! that can read it, instead of failing to parse (or being misread) and
+//! ending up in the DLQ. The envelope counts these hand-backs, so a message
+//! no consumer will ever read is dead-lettered in the end. Messages without
+//! an envelope, sent before there were any, are decoded as before.
+
+use aws_sdk_sqs::types::{Message, MessageAttributeValue};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Message attribute naming the message type
+pub const TYPE_ATTRIBUTE: &str = "type";
+
+/// Message attribute holding the payload's schema version
+pub const SCHEMA_VERSION_ATTRIBUTE: &str = "schema_version";
+
+/// Message attribute holding the tenant the message belongs to
+pub const TENANT_ID_ATTRIBUTE: &str = "tenant_id";
+
+/// Message attribute, and HTTP header, carrying W3C trace context
+pub const TRACEPARENT_ATTRIBUTE: &str = "traceparent";
+
+/// Message attribute counting attempts made before the message was re-sent
+pub const ATTEMPTS_ATTRIBUTE: &str = "attempts";
+
+/// Message attribute counting consumers that couldn't read the message and
+/// handed it back
+pub const DEFERRALS_ATTRIBUTE: &str = "deferrals";
+
+/// A message with a named, versioned payload schema
+///
+/// Bump `SCHEMA_VERSION` when consumers of the previous version can't read
+/// the payload anymore. Adding an optional field doesn't need a bump, since
+/// unknown fields are ignored when decoding.
+pub trait Versioned {
+    const MESSAGE_TYPE: &'static str;
+    const SCHEMA_VERSION: u32;
+
+    /// Tenant the message belongs to, if it has one
+    fn tenant_id(&self) -> Option<Uuid> {
+        None
+    }
+}
+
+/// A message a consumer decodes from its queue
+pub trait Consumable: DeserializeOwned {
+    /// Message types this build reads, with the newest schema version of each
+    const ACCEPTS: &'static [(&'static str, u32)];
+
+    /// Decode a body whose envelope this build accepts
+    fn decode(_envelope: &Envelope, body: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(body)
+    }
+}
+
+/// Type, schema version and context of a queue message
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    /// `None` for messages sent before envelopes
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub message_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traceparent: Option<String>,
+    /// Failed attempts before a retry re-sent the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+    /// Times the message was handed back by a consumer that couldn't read it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deferrals: Option<u32>,
+}
+
+impl Envelope {
+    /// Envelope for `message`
+    pub fn for_message<M: Versioned>(message: &M) -> Self {
+        Self {
+            message_type: Some(M::MESSAGE_TYPE.to_string()),
+            schema_version: Some(M::SCHEMA_VERSION),
+            tenant_id: message.tenant_id(),
+            traceparent: None,
+            attempts: None,
+            deferrals: None,
+        }
+    }
+
+    /// Attach trace context; a malformed `traceparent` is dropped
+    pub fn with_traceparent(mut self, traceparent: Option<&str>) -> Self {
+        self.traceparent = traceparent.filter(|t| is_valid_traceparent(t)).map(str::to_string);
+        self
+    }
+
+    /// Envelope of a received message, empty when it was sent without one
+    pub fn from_message(message: &Message) -> Self {
+        let attribute = |name: &str| {
+            message
+                .message_attributes
+                .as_ref()?
+                .get(name)?
+                .string_value
+                .clone()
+        };
+
+        Self {
+            message_type: attribute(TYPE_ATTRIBUTE),
+            schema_version: attribute(SCHEMA_VERSION_ATTRIBUTE).and_then(|v| v.parse().ok()),
+            tenant_id: attribute(TENANT_ID_ATTRIBUTE).and_then(|v| v.parse().ok()),
+            traceparent: attribute(TRACEPARENT_ATTRIBUTE),
+            attempts: attribute(ATTEMPTS_ATTRIBUTE).and_then(|v| v.parse().ok()),
+            deferrals: attribute(DEFERRALS_ATTRIBUTE).and_then(|v| v.parse().ok()),
+        }
+    }
+
+    /// Whether the message was sent before envelopes existed
+    pub fn is_legacy(&self) -> bool {
+        self.message_type.is_none()
+    }
+
+    /// Whether a consumer of `T` can decode messages with this envelope
+    ///
+    /// Legacy messages are always accepted. A type the consumer doesn't know
+    /// is not, since it most likely comes from a newer producer.
+    pub fn accepted_by<T: Consumable>(&self) -> bool {
+        let Some(message_type) = self.message_type.as_deref() else {
+            return true;
+        };
+        let version = self.schema_version.unwrap_or(1);
+        T::ACCEPTS
+            .iter()
+            .any(|&(accepted, newest)| accepted == message_type && version <= newest)
+    }
+
+    /// SQS message attributes carrying the envelope
+    pub(crate) fn to_attributes(&self) -> HashMap<String, MessageAttributeValue> {
+        let fields = [
+            (TYPE_ATTRIBUTE, "String", self.message_type.clone()),
+            (SCHEMA_VERSION_ATTRIBUTE, "Number", self.schema_version.map(|v| v.to_string())),
+            (TENANT_ID_ATTRIBUTE, "String", self.tenant_id.map(|t| t.to_string())),
+            (TRACEPARENT_ATTRIBUTE, "String", self.traceparent.clone()),
+            (ATTEMPTS_ATTRIBUTE, "Number", self.attempts.map(|v| v.to_string())),
+            (DEFERRALS_ATTRIBUTE, "Number", self.deferrals.map(|v| v.to_string())),
+        ];
+
+        fields
+            .into_iter()
+            .filter_map(|(name, data_type, value)| {
+                let value = MessageAttributeValue::builder()
+                    .data_type(data_type)
+                    .string_value(value?)
+                    .build()
+                    .ok()?;
+                Some((name.to_string(), value))
+            })
+            .collect()
+    }
+}
+
+/// Whether `value` is a version 00 W3C `traceparent`
+fn is_valid_traceparent(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let hex = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit());
+
+    matches!(parts.as_slice(), [version, trace_id, parent_id, flags]
+        if *version == "00"
+            && hex(trace_id, 32)
+            && hex(parent_id, 16)
+            && hex(flags, 2)
+            && trace_id.bytes().any(|b| b != b'0')
+            && parent_id.bytes().any(|b| b != b'0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Job {
+        #[allow(dead_code)]
+        job_id: Uuid,
+    }
+
+    impl Versioned for Job {
+        const MESSAGE_TYPE: &'static str = "test.job";
+        const SCHEMA_VERSION: u32 = 2;
+    }
+
+    impl Consumable for Job {
+        const ACCEPTS: &'static [(&'static str, u32)] = &[(Job::MESSAGE_TYPE, Job::SCHEMA_VERSION)];
+    }
+
+    fn envelope(message_type: &str, schema_version: u32) -> Envelope {
+        Envelope {
+            message_type: Some(message_type.to_string()),
+            schema_version: Some(schema_version),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_newer_and_unknown_messages_are_not_accepted() {
+        assert!(Envelope::default().accepted_by::<Job>());
+        assert!(envelope("test.job", 1).accepted_by::<Job>());
+        assert!(envelope("test.job", 2).accepted_by::<Job>());
+        assert!(!envelope("test.job", 3).accepted_by::<Job>());
+        assert!(!envelope("test.other", 1).accepted_by::<Job>());
+    }
+
+    #[test]
+    fn test_envelope_round_trips_through_attributes() {
+        let envelope = Envelope {
+            tenant_id: Some(Uuid::new_v4()),
+            attempts: Some(2),
+            deferrals: Some(1),
+            ..envelope("test.job", 2)
+        }
+        .with_traceparent(Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"));
+
+        let message = Message::builder()
+            .body("{}")
+            .set_message_attributes(Some(envelope.to_attributes()))
+            .build();
+        assert_eq!(Envelope::from_message(&message), envelope);
+        assert!(Envelope::from_message(&Message::builder().body("{}").build()).is_legacy());
+    }
+
+    #[test]
+    fn test_malformed_traceparent_is_dropped() {
+        let with = |t| Envelope::default().with_traceparent(Some(t)).traceparent;
+        assert!(with("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_some());
+        assert!(with("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none());
+        assert!(with("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none());
+        assert!(with("not a traceparent").is_none());
+    }
+}
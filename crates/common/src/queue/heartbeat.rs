@@ -0,0 +1,106 @@
+//! Visibility heartbeats for messages being processed
+//!
+//! A received message is hidden from other consumers for the queue's
+//! visibility timeout. Processing that takes longer, say extracting a large
+//! PDF, would let SQS deliver the message again while the first attempt is
+//! still running. A [`Heartbeat`] keeps extending the message's visibility
+//! until it is stopped or dropped, up to a hard deadline after which the
+//! message is allowed to reappear, so a wedged consumer can't hold it
+//! forever.
+
+use super::Queue;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// Longest visibility timeout SQS accepts
+const MAX_VISIBILITY: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// Keeps one message hidden while it is processed; stops when dropped
+pub struct Heartbeat {
+    task: JoinHandle<()>,
+}
+
+impl Heartbeat {
+    /// Stop extending; the message reappears when its current visibility
+    /// timeout runs out
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// How long to extend visibility by, `elapsed` into processing
+///
+/// Each extension covers one visibility timeout, cut short at `deadline`;
+/// `None` once the deadline has passed.
+fn next_extension(elapsed: Duration, visibility_timeout: Duration, deadline: Duration) -> Option<Duration> {
+    let remaining = deadline.checked_sub(elapsed).filter(|d| !d.is_zero())?;
+    Some(visibility_timeout.min(remaining).min(MAX_VISIBILITY).max(Duration::from_secs(1)))
+}
+
+impl Queue {
+    /// Keep a received message hidden for up to `deadline` while it's
+    /// processed
+    ///
+    /// Visibility is extended every third of the visibility timeout, so
+    /// one failed call doesn't let the message reappear.
+    pub fn heartbeat(self: &Arc<Self>, receipt_handle: &str, deadline: Duration) -> Heartbeat {
+        let queue = self.clone();
+        let receipt_handle = receipt_handle.to_string();
+        let visibility_timeout = Duration::from_secs(self.config.visibility_timeout.max(1) as u64);
+        let interval = (visibility_timeout / 3).max(Duration::from_secs(1));
+
+        let task = tokio::spawn(async move {
+            let start = Instant::now();
+            let mut ticker = tokio::time::interval_at(start + interval, interval);
+
+            loop {
+                ticker.tick().await;
+                let Some(extension) = next_extension(start.elapsed(), visibility_timeout, deadline) else {
+                    warn!(
+                        deadline_secs = deadline.as_secs(),
+                        "Message still processing at its deadline, letting it reappear"
+                    );
+                    return;
+                };
+                match queue.extend_visibility(&receipt_handle, extension.as_secs() as i32).await {
+                    Ok(()) => debug!(extension_secs = extension.as_secs(), "Message heartbeat"),
+                    Err(e) => warn!(error = %e, "Failed to extend message visibility"),
+                }
+            }
+        });
+
+        Heartbeat { task }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(30);
+    const DEADLINE: Duration = Duration::from_secs(100);
+
+    #[test]
+    fn test_extends_by_visibility_timeout() {
+        assert_eq!(next_extension(Duration::from_secs(10), TIMEOUT, DEADLINE), Some(TIMEOUT));
+    }
+
+    #[test]
+    fn test_extension_stops_at_deadline() {
+        assert_eq!(
+            next_extension(Duration::from_secs(90), TIMEOUT, DEADLINE),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(next_extension(DEADLINE, TIMEOUT, DEADLINE), None);
+        assert_eq!(next_extension(Duration::from_secs(120), TIMEOUT, DEADLINE), None);
+    }
+}
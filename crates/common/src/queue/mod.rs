@@ -4,16 +4,50 @@
 //! - SQS client wrapper with retry logic
 //! - Message serialization/deserialization
 //! - Dead letter queue handling
+//! - FIFO queues (detected from the `.fifo` URL suffix)
+//! - Queue depth monitoring
+//! - Visibility heartbeats for long-running messages
+//! - Versioned envelopes carried as message attributes
+//! - Delayed retries with jittered exponential backoff
 
-use crate::errors::{AppError, Result};
+use crate::errors::{AppError, Result, Retryable};
+use crate::metrics;
 use aws_sdk_sqs::Client as SqsClient;
-use aws_sdk_sqs::types::Message;
+use aws_sdk_sqs::types::{
+    BatchResultErrorEntry, DeleteMessageBatchRequestEntry, Message, MessageSystemAttributeName,
+    SendMessageBatchRequestEntry,
+};
 use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use std::time::Duration;
 use backoff::{ExponentialBackoff, future::retry};
 use tracing::{debug, error, info, warn};
 
+mod envelope;
+mod heartbeat;
+mod monitor;
+mod retry;
+
+pub use envelope::{
+    Consumable, Envelope, Versioned, ATTEMPTS_ATTRIBUTE, DEFERRALS_ATTRIBUTE, SCHEMA_VERSION_ATTRIBUTE,
+    TENANT_ID_ATTRIBUTE, TRACEPARENT_ATTRIBUTE, TYPE_ATTRIBUTE,
+};
+pub use heartbeat::Heartbeat;
+pub use monitor::{queue_name, QueueDepth, QueueMonitor};
+pub use retry::{RetryBackoff, RetryOutcome};
+
+/// Maximum number of entries SQS accepts in a single batch request
+pub const MAX_BATCH_SIZE: usize = 10;
+
+/// Longest per-message delay SQS accepts (seconds)
+const MAX_DELAY_SECONDS: i32 = 900;
+
+/// Times an unreadable message is handed back before it is dead-lettered;
+/// about two hours at the default visibility timeout, long enough for a
+/// rolling deployment to bring up a consumer that reads it
+const MAX_DEFERRALS: u32 = 24;
+
 /// SQS queue configuration
 #[derive(Debug, Clone)]
 pub struct QueueConfig {
@@ -21,7 +55,7 @@ pub struct QueueConfig {
     pub url: String,
     /// Dead letter queue URL (optional)
     pub dlq_url: Option<String>,
-    /// Maximum receive count before moving to DLQ
+    /// Maximum attempts at a message before moving it to the DLQ
     pub max_receive_count: u32,
     /// Visibility timeout in seconds
     pub visibility_timeout: i32,
@@ -29,6 +63,15 @@ pub struct QueueConfig {
     pub wait_time_seconds: i32,
     /// Maximum number of messages per poll
     pub max_messages: i32,
+    /// Delays between attempts of a failed message
+    pub retry_backoff: RetryBackoff,
+}
+
+impl QueueConfig {
+    /// Whether the queue is a FIFO queue
+    pub fn is_fifo(&self) -> bool {
+        is_fifo_url(&self.url)
+    }
 }
 
 impl Default for QueueConfig {
@@ -40,6 +83,7 @@ impl Default for QueueConfig {
             visibility_timeout: 30,
             wait_time_seconds: 20,
             max_messages: 10,
+            retry_backoff: RetryBackoff::default(),
         }
     }
 }
@@ -64,85 +108,161 @@ impl Queue {
         Self { client, config }
     }
     
-    /// Send a message to the queue
-    pub async fn send<T: Serialize>(&self, message: &T) -> Result<String> {
-        let body = serde_json::to_string(message)
-            .map_err(|e| AppError::QueueError { 
-                message: format!("Failed to serialize message: {}", e) 
-            })?;
+    /// Send a message to the queue, with its envelope
+    pub async fn send<M: Versioned + Serialize>(&self, message: &M) -> Result<String> {
+        let body = serialize(message)?;
+        let message_id = self
+            .send_body(&self.config.url, &body, &Envelope::for_message(message), None, "send message")
+            .await?;
         
-        let result = self.client
-            .send_message()
-            .queue_url(&self.config.url)
-            .message_body(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::QueueError {
-                message: format!("Failed to send message: {}", e),
-            })?;
-        
-        let message_id = result.message_id.unwrap_or_default();
         debug!(message_id = %message_id, "Message sent to queue");
-        
         Ok(message_id)
     }
     
     /// Send a message with delay
-    pub async fn send_delayed<T: Serialize>(&self, message: &T, delay_seconds: i32) -> Result<String> {
-        let body = serde_json::to_string(message)
-            .map_err(|e| AppError::QueueError { 
-                message: format!("Failed to serialize message: {}", e) 
-            })?;
-        
-        let result = self.client
-            .send_message()
-            .queue_url(&self.config.url)
-            .message_body(&body)
-            .delay_seconds(delay_seconds)
-            .send()
-            .await
-            .map_err(|e| AppError::QueueError {
-                message: format!("Failed to send delayed message: {}", e),
-            })?;
+    ///
+    /// FIFO queues do not support per-message delays; the message is sent
+    /// immediately and the queue's own delay setting applies.
+    pub async fn send_delayed<M: Versioned + Serialize>(&self, message: &M, delay_seconds: i32) -> Result<String> {
+        let body = serialize(message)?;
+        let message_id = self
+            .send_body(
+                &self.config.url,
+                &body,
+                &Envelope::for_message(message),
+                Some(delay_seconds),
+                "send delayed message",
+            )
+            .await?;
         
-        let message_id = result.message_id.unwrap_or_default();
         debug!(message_id = %message_id, delay_seconds, "Delayed message sent to queue");
-        
         Ok(message_id)
     }
     
-    /// Receive messages from the queue
-    pub async fn receive(&self) -> Result<Vec<Message>> {
+    /// Send one body with its envelope to `queue_url`
+    ///
+    /// `action` describes the send in the error, e.g. "send to DLQ".
+    async fn send_body(
+        &self,
+        queue_url: &str,
+        body: &str,
+        envelope: &Envelope,
+        delay_seconds: Option<i32>,
+        action: &str,
+    ) -> Result<String> {
+        let fifo = is_fifo_url(queue_url).then(|| FifoAttributes::new(body, envelope));
+        if fifo.is_some() && delay_seconds.is_some() {
+            warn!(delay_seconds, "Per-message delay is not supported on FIFO queues, ignoring");
+        }
+        
         let result = self.client
-            .receive_message()
-            .queue_url(&self.config.url)
-            .max_number_of_messages(self.config.max_messages)
-            .visibility_timeout(self.config.visibility_timeout)
-            .wait_time_seconds(self.config.wait_time_seconds)
+            .send_message()
+            .queue_url(queue_url)
+            .message_body(body)
+            .set_message_attributes(Some(envelope.to_attributes()).filter(|a| !a.is_empty()))
+            .set_delay_seconds(delay_seconds.filter(|_| fifo.is_none()))
+            .set_message_group_id(fifo.as_ref().map(|f| f.group_id.clone()))
+            .set_message_deduplication_id(fifo.map(|f| f.deduplication_id))
             .send()
             .await
             .map_err(|e| AppError::QueueError {
-                message: format!("Failed to receive messages: {}", e),
+                message: format!("Failed to {}: {}", action, e),
             })?;
         
-        let messages = result.messages.unwrap_or_default();
-        debug!(count = messages.len(), "Received messages from queue");
+        Ok(result.message_id.unwrap_or_default())
+    }
+    
+    /// Send messages in batches of up to [`MAX_BATCH_SIZE`]
+    ///
+    /// Each message is sent with its envelope. Entries are reported by their
+    /// index in `messages`. A failed entry does not fail the call; only
+    /// request-level errors are returned as `Err`.
+    pub async fn send_batch<T: Serialize>(&self, messages: &[(T, Envelope)]) -> Result<BatchSendResult> {
+        let mut result = BatchSendResult::default();
         
-        Ok(messages)
+        for (batch_index, batch) in messages.chunks(MAX_BATCH_SIZE).enumerate() {
+            let offset = batch_index * MAX_BATCH_SIZE;
+            let mut entries = Vec::with_capacity(batch.len());
+            
+            for (i, (message, envelope)) in batch.iter().enumerate() {
+                let body = serialize(message)?;
+                
+                let fifo = self.fifo_attributes(&body, envelope);
+                let entry = SendMessageBatchRequestEntry::builder()
+                    .id((offset + i).to_string())
+                    .message_body(body)
+                    .set_message_attributes(Some(envelope.to_attributes()).filter(|a| !a.is_empty()))
+                    .set_message_group_id(fifo.as_ref().map(|f| f.group_id.clone()))
+                    .set_message_deduplication_id(fifo.map(|f| f.deduplication_id))
+                    .build()
+                    .map_err(|e| AppError::QueueError {
+                        message: format!("Failed to build batch entry: {}", e),
+                    })?;
+                entries.push(entry);
+            }
+            
+            let output = self.client
+                .send_message_batch()
+                .queue_url(&self.config.url)
+                .set_entries(Some(entries))
+                .send()
+                .await
+                .map_err(|e| AppError::QueueError {
+                    message: format!("Failed to send message batch: {}", e),
+                })?;
+            
+            for entry in output.successful {
+                if let Ok(index) = entry.id.parse() {
+                    result.sent.push((index, entry.message_id));
+                }
+            }
+            for entry in output.failed {
+                result.failed.extend(BatchFailure::from_entry(entry));
+            }
+        }
+        
+        debug!(
+            sent = result.sent.len(),
+            failed = result.failed.len(),
+            "Message batch sent to queue"
+        );
+        
+        Ok(result)
     }
     
-    /// Receive and parse typed messages from the queue
-    /// Returns tuples of (parsed_message, receipt_handle)
-    pub async fn receive<T: DeserializeOwned>(&self) -> Result<Vec<(T, String)>> {
+    /// Receive and decode typed messages from the queue
+    ///
+    /// Messages whose envelope `T` doesn't accept are deferred for a
+    /// consumer that does; bodies that fail to decode are skipped and
+    /// redelivered until the redrive policy moves them to the DLQ.
+    pub async fn receive<T: Consumable>(&self) -> Result<Vec<Received<T>>> {
         let messages = self.receive_raw().await?;
         let mut parsed = Vec::with_capacity(messages.len());
         
         for msg in messages {
-            let receipt_handle = msg.receipt_handle.clone().unwrap_or_default();
-            match Self::parse_message(&msg) {
-                Ok(parsed_msg) => parsed.push((parsed_msg, receipt_handle)),
+            let envelope = Envelope::from_message(&msg);
+            let receive_count = receive_count(&msg);
+            let receipt_handle = msg.receipt_handle.unwrap_or_default();
+            let body = msg.body.unwrap_or_default();
+            
+            if !envelope.accepted_by::<T>() {
+                self.defer(envelope, body, receipt_handle, receive_count).await;
+                continue;
+            }
+            match T::decode(&envelope, &body) {
+                Ok(message) => parsed.push(Received {
+                    message,
+                    receipt_handle,
+                    envelope,
+                    body,
+                    receive_count,
+                }),
                 Err(e) => {
-                    warn!(error = %e, "Failed to parse message, skipping");
+                    warn!(
+                        error = %e,
+                        message_type = envelope.message_type.as_deref().unwrap_or("-"),
+                        "Failed to parse message, skipping"
+                    );
                 }
             }
         }
@@ -150,7 +270,66 @@ impl Queue {
         Ok(parsed)
     }
     
+    /// Hand a message this build can't read back to the queue
+    ///
+    /// It's sent again with a delay and the original deleted, so a message
+    /// waiting for a newer consumer doesn't use up its receive count and
+    /// land in the DLQ. FIFO queues would drop the copy as a duplicate, so
+    /// there the original is just hidden for the delay instead, and the
+    /// redrive policy bounds how often.
+    ///
+    /// The copy counts the deferral in its envelope. Once a message has
+    /// been deferred [`MAX_DEFERRALS`] times it is moved to the DLQ: by then
+    /// no consumer is coming that can read it.
+    async fn defer(&self, envelope: Envelope, body: String, receipt_handle: String, receive_count: u32) {
+        let deferrals = envelope.deferrals.unwrap_or(0) + 1;
+        if deferrals > MAX_DEFERRALS && !self.config.is_fifo() {
+            let message = Received { message: (), receipt_handle, envelope, body, receive_count };
+            let reason = format!(
+                "No consumer could read message type {} (schema version {}) after {} deferrals",
+                message.envelope.message_type.as_deref().unwrap_or("-"),
+                message.envelope.schema_version.unwrap_or(1),
+                MAX_DEFERRALS,
+            );
+            let result = match self.move_to_dlq(&message, &reason).await {
+                Ok(()) => self.delete(&message.receipt_handle).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                error!(error = %e, "Failed to dead-letter unreadable message");
+            }
+            return;
+        }
+        
+        let delay = self.config.visibility_timeout.clamp(1, MAX_DELAY_SECONDS);
+        warn!(
+            message_type = envelope.message_type.as_deref().unwrap_or("-"),
+            schema_version = envelope.schema_version,
+            deferrals,
+            delay_secs = delay,
+            "Message is not readable by this consumer, deferring"
+        );
+        
+        let result = if self.config.is_fifo() {
+            self.extend_visibility(&receipt_handle, delay).await
+        } else {
+            let envelope = Envelope {
+                deferrals: Some(deferrals),
+                ..envelope
+            };
+            match self.send_body(&self.config.url, &body, &envelope, Some(delay), "defer message").await {
+                Ok(_) => self.delete(&receipt_handle).await,
+                Err(e) => Err(e),
+            }
+        };
+        if let Err(e) = result {
+            error!(error = %e, "Failed to defer message");
+        }
+    }
+    
     /// Receive raw messages from the queue
+    ///
+    /// Records how long each message waited in the queue.
     pub async fn receive_raw(&self) -> Result<Vec<Message>> {
         let result = self.client
             .receive_message()
@@ -158,6 +337,9 @@ impl Queue {
             .max_number_of_messages(self.config.max_messages)
             .visibility_timeout(self.config.visibility_timeout)
             .wait_time_seconds(self.config.wait_time_seconds)
+            .message_system_attribute_names(MessageSystemAttributeName::SentTimestamp)
+            .message_system_attribute_names(MessageSystemAttributeName::ApproximateReceiveCount)
+            .message_attribute_names("All")
             .send()
             .await
             .map_err(|e| AppError::QueueError {
@@ -167,6 +349,14 @@ impl Queue {
         let messages = result.messages.unwrap_or_default();
         debug!(count = messages.len(), "Received messages from queue");
         
+        let now_millis = chrono::Utc::now().timestamp_millis();
+        for message in &messages {
+            if let Some(sent_millis) = sent_timestamp(message) {
+                let age_secs = (now_millis - sent_millis).max(0) as f64 / 1000.0;
+                metrics::record_queue_message_age(queue_name(&self.config.url), age_secs);
+            }
+        }
+        
         Ok(messages)
     }
     
@@ -186,6 +376,57 @@ impl Queue {
         Ok(())
     }
     
+    /// Delete processed messages in batches of up to [`MAX_BATCH_SIZE`]
+    ///
+    /// Entries are reported by their index in `receipt_handles`.
+    pub async fn delete_batch(&self, receipt_handles: &[String]) -> Result<BatchDeleteResult> {
+        let mut result = BatchDeleteResult::default();
+        
+        for (batch_index, batch) in receipt_handles.chunks(MAX_BATCH_SIZE).enumerate() {
+            let offset = batch_index * MAX_BATCH_SIZE;
+            let entries = batch
+                .iter()
+                .enumerate()
+                .map(|(i, handle)| {
+                    DeleteMessageBatchRequestEntry::builder()
+                        .id((offset + i).to_string())
+                        .receipt_handle(handle)
+                        .build()
+                        .map_err(|e| AppError::QueueError {
+                            message: format!("Failed to build batch entry: {}", e),
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            
+            let output = self.client
+                .delete_message_batch()
+                .queue_url(&self.config.url)
+                .set_entries(Some(entries))
+                .send()
+                .await
+                .map_err(|e| AppError::QueueError {
+                    message: format!("Failed to delete message batch: {}", e),
+                })?;
+            
+            for entry in output.successful {
+                if let Ok(index) = entry.id.parse() {
+                    result.deleted.push(index);
+                }
+            }
+            for entry in output.failed {
+                result.failed.extend(BatchFailure::from_entry(entry));
+            }
+        }
+        
+        debug!(
+            deleted = result.deleted.len(),
+            failed = result.failed.len(),
+            "Message batch deleted from queue"
+        );
+        
+        Ok(result)
+    }
+    
     /// Change visibility timeout (extend processing time)
     pub async fn extend_visibility(&self, receipt_handle: &str, additional_seconds: i32) -> Result<()> {
         self.client
@@ -203,6 +444,11 @@ impl Queue {
         Ok(())
     }
     
+    /// FIFO attributes for a message body, if this is a FIFO queue
+    fn fifo_attributes(&self, body: &str, envelope: &Envelope) -> Option<FifoAttributes> {
+        self.config.is_fifo().then(|| FifoAttributes::new(body, envelope))
+    }
+    
     /// Parse message body as JSON
     pub fn parse_message<T: DeserializeOwned>(message: &Message) -> Result<T> {
         let body = message.body.as_ref().ok_or_else(|| AppError::QueueError {
@@ -219,14 +465,19 @@ impl Queue {
     // =========================================================================
     
     /// Move a message to the dead letter queue
-    pub async fn move_to_dlq<T: Serialize>(&self, message: &T, reason: &str) -> Result<()> {
+    ///
+    /// The body is kept as received rather than re-serialized from the
+    /// decoded message, so fields this build doesn't know survive.
+    pub async fn move_to_dlq<T>(&self, message: &Received<T>, reason: &str) -> Result<()> {
         let dlq_url = self.config.dlq_url.as_ref().ok_or_else(|| AppError::QueueError {
             message: "No DLQ configured".to_string(),
         })?;
         
         // Wrap the message with error context
         let dlq_message = DlqMessage {
-            original_message: serde_json::to_value(message).unwrap_or_default(),
+            original_message: serde_json::from_str(&message.body)
+                .unwrap_or_else(|_| serde_json::Value::String(message.body.clone())),
+            envelope: message.envelope.clone(),
             failure_reason: reason.to_string(),
             failed_at: chrono::Utc::now(),
             source_queue: self.config.url.clone(),
@@ -237,17 +488,44 @@ impl Queue {
                 message: format!("Failed to serialize DLQ message: {}", e) 
             })?;
         
+        self.send_body(dlq_url, &body, &message.envelope, None, "send to DLQ").await?;
+        
+        warn!(reason = %reason, "Message moved to DLQ");
+        Ok(())
+    }
+    
+    /// Settle a message whose processing failed with `error`
+    ///
+    /// Retryable errors schedule another attempt through [`Queue::retry`].
+    /// Fatal errors send the message straight to the DLQ instead of waiting
+    /// for its attempts to run out. Returns `true` when the caller should
+    /// delete the original.
+    pub async fn handle_failure<T, E: Retryable>(&self, message: &Received<T>, error: &E) -> Result<bool> {
+        if error.is_retryable() {
+            let outcome = self.retry(message, message.attempt(), error).await?;
+            return Ok(outcome.delete_original());
+        }
+        
+        if self.config.dlq_url.is_none() {
+            warn!(error = %error, "Fatal message failure but no DLQ configured, leaving for redelivery");
+            return Ok(false);
+        }
+        
+        self.move_to_dlq(message, &error.to_string()).await?;
+        Ok(true)
+    }
+    
+    /// Check that the queue is reachable and this service may use it
+    pub async fn ping(&self) -> Result<()> {
         self.client
-            .send_message()
-            .queue_url(dlq_url)
-            .message_body(&body)
+            .get_queue_attributes()
+            .queue_url(&self.config.url)
+            .attribute_names(aws_sdk_sqs::types::QueueAttributeName::QueueArn)
             .send()
             .await
             .map_err(|e| AppError::QueueError {
-                message: format!("Failed to send to DLQ: {}", e),
+                message: format!("Failed to get queue attributes: {}", e),
             })?;
-        
-        warn!(reason = %reason, "Message moved to DLQ");
         Ok(())
     }
     
@@ -286,6 +564,7 @@ impl Queue {
             .queue_url(dlq_url)
             .max_number_of_messages(10)
             .visibility_timeout(30)
+            .message_attribute_names("All")
             .send()
             .await
             .map_err(|e| AppError::QueueError {
@@ -299,6 +578,9 @@ impl Queue {
     }
     
     /// Redrive a message from DLQ back to the main queue
+    ///
+    /// Messages moved by [`Queue::move_to_dlq`] are unwrapped and sent with
+    /// their original envelope; ones the redrive policy moved are sent as is.
     pub async fn redrive_message(&self, message: &Message) -> Result<()> {
         let dlq_url = self.config.dlq_url.as_ref().ok_or_else(|| AppError::QueueError {
             message: "No DLQ configured".to_string(),
@@ -309,15 +591,17 @@ impl Queue {
         })?;
         
         // Send back to main queue
-        self.client
-            .send_message()
-            .queue_url(&self.config.url)
-            .message_body(body)
-            .send()
-            .await
-            .map_err(|e| AppError::QueueError {
-                message: format!("Failed to redrive message: {}", e),
-            })?;
+        let (body, envelope) = match serde_json::from_str::<DlqMessage>(body) {
+            Ok(dead) => {
+                let original = match dead.original_message {
+                    serde_json::Value::String(raw) => raw,
+                    value => value.to_string(),
+                };
+                (original, dead.envelope)
+            }
+            Err(_) => (body.clone(), Envelope::from_message(message)),
+        };
+        self.send_body(&self.config.url, &body, &envelope, None, "redrive message").await?;
         
         // Delete from DLQ
         if let Some(receipt_handle) = message.receipt_handle.as_ref() {
@@ -365,11 +649,151 @@ impl Queue {
     }
 }
 
+/// How many times a message has been received, this time included
+fn receive_count(message: &Message) -> u32 {
+    message
+        .attributes
+        .as_ref()
+        .and_then(|attrs| attrs.get(&MessageSystemAttributeName::ApproximateReceiveCount))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1)
+}
+
+/// Serialize a message body
+fn serialize<T: Serialize>(message: &T) -> Result<String> {
+    serde_json::to_string(message).map_err(|e| AppError::QueueError {
+        message: format!("Failed to serialize message: {}", e),
+    })
+}
+
+/// When a message was sent, in milliseconds since the epoch
+fn sent_timestamp(message: &Message) -> Option<i64> {
+    message
+        .attributes
+        .as_ref()?
+        .get(&MessageSystemAttributeName::SentTimestamp)?
+        .parse()
+        .ok()
+}
+
+/// Check whether a queue URL refers to a FIFO queue
+pub fn is_fifo_url(url: &str) -> bool {
+    url.ends_with(".fifo")
+}
+
+/// Message group and deduplication IDs for a FIFO send
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FifoAttributes {
+    /// Messages in the same group are delivered in order
+    pub group_id: String,
+    /// Sends with the same ID within five minutes are dropped by SQS
+    pub deduplication_id: String,
+}
+
+impl FifoAttributes {
+    /// Derive FIFO attributes from a serialized message and its envelope
+    ///
+    /// Messages are grouped by `paper_id`, falling back to `tenant_id`, so
+    /// work for one paper is processed in order. The deduplication ID is a
+    /// hash of the tenant, the `job_id` (or the whole body when there is
+    /// none) and the envelope's attempt count: repeated sends of one attempt
+    /// are suppressed, while one tenant's IDs can't suppress another's and
+    /// a retry isn't mistaken for the attempt before it. Hashing also keeps
+    /// caller-chosen values out of the ID, whose characters SQS restricts.
+    pub fn new(body: &str, envelope: &Envelope) -> Self {
+        let value: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+        let field = |name: &str| {
+            value
+                .get(name)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        
+        let group_id = field("paper_id")
+            .or_else(|| field("tenant_id"))
+            .unwrap_or_else(|| "default".to_string());
+        
+        let tenant = field("tenant_id")
+            .or_else(|| envelope.tenant_id.map(|id| id.to_string()))
+            .unwrap_or_default();
+        let job = field("job_id").unwrap_or_else(|| hex::encode(Sha256::digest(body.as_bytes())));
+        let mut hasher = Sha256::new();
+        for part in [tenant.as_str(), job.as_str(), &envelope.attempts.unwrap_or(0).to_string()] {
+            // Length-prefixed so the parts can't run into each other
+            hasher.update((part.len() as u64).to_be_bytes());
+            hasher.update(part.as_bytes());
+        }
+        let deduplication_id = hex::encode(hasher.finalize());
+        
+        Self { group_id, deduplication_id }
+    }
+}
+
+/// A batch entry that SQS rejected
+#[derive(Debug, Clone)]
+pub struct BatchFailure {
+    /// Index of the entry in the input slice
+    pub index: usize,
+    /// SQS error code
+    pub code: String,
+    /// Error message, if provided
+    pub message: Option<String>,
+    /// Whether the caller caused the failure (retrying will not help)
+    pub sender_fault: bool,
+}
+
+impl BatchFailure {
+    fn from_entry(entry: BatchResultErrorEntry) -> Option<Self> {
+        Some(Self {
+            index: entry.id.parse().ok()?,
+            code: entry.code,
+            message: entry.message,
+            sender_fault: entry.sender_fault,
+        })
+    }
+}
+
+/// A decoded message and what's needed to settle it
+#[derive(Debug, Clone)]
+pub struct Received<T> {
+    pub message: T,
+    pub receipt_handle: String,
+    pub envelope: Envelope,
+    /// Body as received, so the message can be dead-lettered without
+    /// dropping fields this build doesn't know
+    pub body: String,
+    /// SQS receive count; attempts before a re-send are in the envelope
+    pub receive_count: u32,
+}
+
+/// Outcome of [`Queue::send_batch`]
+#[derive(Debug, Clone, Default)]
+pub struct BatchSendResult {
+    /// `(index, message_id)` of each sent entry
+    pub sent: Vec<(usize, String)>,
+    /// Entries SQS rejected
+    pub failed: Vec<BatchFailure>,
+}
+
+/// Outcome of [`Queue::delete_batch`]
+#[derive(Debug, Clone, Default)]
+pub struct BatchDeleteResult {
+    /// Indices of deleted entries
+    pub deleted: Vec<usize>,
+    /// Entries SQS rejected
+    pub failed: Vec<BatchFailure>,
+}
+
 /// Dead Letter Queue message wrapper
 #[derive(Debug, Clone, Serialize, serde::Deserialize)]
 pub struct DlqMessage {
     /// Original message content
     pub original_message: serde_json::Value,
+    /// Original message's envelope; empty for messages wrapped before
+    /// envelopes existed
+    #[serde(default)]
+    pub envelope: Envelope,
     /// Reason for failure
     pub failure_reason: String,
     /// When the message failed
@@ -385,10 +809,30 @@ pub struct IngestionJobMessage {
     pub tenant_id: uuid::Uuid,
     pub paper_title: String,
     pub paper_abstract: String,
+    #[serde(default)]
+    pub paper_source: Option<String>,
+    #[serde(default)]
+    pub external_id: Option<String>,
+    /// Normalized DOI, used to resolve canonical metadata
+    #[serde(default)]
+    pub doi: Option<String>,
+    #[serde(default)]
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub metadata: serde_json::Value,
     pub idempotency_key: Option<String>,
     pub options: IngestionJobOptions,
 }
 
+impl Versioned for IngestionJobMessage {
+    const MESSAGE_TYPE: &'static str = "paper.submit";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn tenant_id(&self) -> Option<uuid::Uuid> {
+        Some(self.tenant_id)
+    }
+}
+
 /// Ingestion job options
 #[derive(Debug, Clone, Serialize, serde::Deserialize)]
 pub struct IngestionJobOptions {
@@ -398,6 +842,85 @@ pub struct IngestionJobOptions {
     pub chunk_overlap: usize,
 }
 
+impl Default for IngestionJobOptions {
+    fn default() -> Self {
+        Self {
+            embedding_model: crate::DEFAULT_EMBEDDING_MODEL.to_string(),
+            chunk_strategy: "recursive".to_string(),
+            chunk_size: 1000,
+            chunk_overlap: 200,
+        }
+    }
+}
+
+/// Request to re-chunk and re-embed an existing paper
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct ReprocessPaperMessage {
+    pub job_id: uuid::Uuid,
+    pub tenant_id: uuid::Uuid,
+    pub paper_id: uuid::Uuid,
+    /// Chunk size override (characters)
+    pub chunk_size: Option<usize>,
+    /// Chunk overlap override (characters)
+    pub chunk_overlap: Option<usize>,
+}
+
+impl Versioned for ReprocessPaperMessage {
+    const MESSAGE_TYPE: &'static str = "paper.reprocess";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn tenant_id(&self) -> Option<uuid::Uuid> {
+        Some(self.tenant_id)
+    }
+}
+
+/// Request to ingest a PDF already in document storage, such as one
+/// unpacked from an uploaded archive
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct StoredDocumentMessage {
+    pub job_id: uuid::Uuid,
+    pub tenant_id: uuid::Uuid,
+    /// Upload batch the document came in with
+    pub batch_id: Option<uuid::Uuid>,
+    /// Storage key of the PDF
+    pub source_key: String,
+    /// Path of the document in its archive
+    pub file_name: String,
+    pub options: IngestionJobOptions,
+}
+
+impl Versioned for StoredDocumentMessage {
+    const MESSAGE_TYPE: &'static str = "document.ingest";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn tenant_id(&self) -> Option<uuid::Uuid> {
+        Some(self.tenant_id)
+    }
+}
+
+/// Request to ingest a document downloaded from a user-supplied URL
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct UrlImportMessage {
+    pub job_id: uuid::Uuid,
+    pub tenant_id: uuid::Uuid,
+    /// PDF or HTML page to download
+    pub url: String,
+    /// Title to use instead of the one found in the document
+    pub title: Option<String>,
+    pub metadata: serde_json::Value,
+    pub idempotency_key: Option<String>,
+    pub options: IngestionJobOptions,
+}
+
+impl Versioned for UrlImportMessage {
+    const MESSAGE_TYPE: &'static str = "paper.import_url";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn tenant_id(&self) -> Option<uuid::Uuid> {
+        Some(self.tenant_id)
+    }
+}
+
 /// Embedding job message
 #[derive(Debug, Clone, Serialize, serde::Deserialize)]
 pub struct EmbeddingJobMessage {
@@ -437,6 +960,11 @@ mod tests {
             tenant_id: uuid::Uuid::new_v4(),
             paper_title: "Test Paper".to_string(),
             paper_abstract: "Test abstract".to_string(),
+            paper_source: None,
+            external_id: None,
+            doi: Some("10.1038/nature14539".to_string()),
+            published_at: None,
+            metadata: serde_json::json!({}),
             idempotency_key: Some("test-key".to_string()),
             options: IngestionJobOptions {
                 embedding_model: "text-embedding-ada-002".to_string(),
@@ -452,4 +980,82 @@ mod tests {
         assert_eq!(msg.job_id, parsed.job_id);
         assert_eq!(msg.paper_title, parsed.paper_title);
     }
+    
+    #[test]
+    fn test_batch_failure_from_entry() {
+        let entry = BatchResultErrorEntry::builder()
+            .id("7")
+            .code("InvalidMessageContents")
+            .sender_fault(true)
+            .build()
+            .unwrap();
+        
+        let failure = BatchFailure::from_entry(entry).unwrap();
+        
+        assert_eq!(failure.index, 7);
+        assert_eq!(failure.code, "InvalidMessageContents");
+        assert!(failure.sender_fault);
+    }
+    
+    #[test]
+    fn test_dlq_message_without_envelope() {
+        let body = serde_json::json!({
+            "original_message": { "job_id": uuid::Uuid::new_v4() },
+            "failure_reason": "boom",
+            "failed_at": chrono::Utc::now(),
+            "source_queue": "https://sqs.us-east-1.amazonaws.com/123/ingestion",
+        });
+        
+        let parsed: DlqMessage = serde_json::from_value(body).unwrap();
+        assert!(parsed.envelope.is_legacy());
+    }
+    
+    #[test]
+    fn test_fifo_attributes() {
+        assert!(is_fifo_url("https://sqs.us-east-1.amazonaws.com/123/ingestion.fifo"));
+        assert!(!is_fifo_url("https://sqs.us-east-1.amazonaws.com/123/ingestion"));
+        
+        let paper_id = uuid::Uuid::new_v4();
+        let body = serde_json::json!({
+            "job_id": uuid::Uuid::new_v4(),
+            "tenant_id": uuid::Uuid::new_v4(),
+            "paper_id": paper_id,
+            "idempotency_key": "upload-1",
+        })
+        .to_string();
+        let attrs = FifoAttributes::new(&body, &Envelope::default());
+        assert_eq!(attrs.group_id, paper_id.to_string());
+        assert_eq!(attrs, FifoAttributes::new(&body, &Envelope::default()));
+        
+        // Without a job ID, identical bodies deduplicate by hash
+        let body = serde_json::json!({ "tenant_id": paper_id }).to_string();
+        let attrs = FifoAttributes::new(&body, &Envelope::default());
+        assert_eq!(attrs.group_id, paper_id.to_string());
+        assert_eq!(attrs, FifoAttributes::new(&body, &Envelope::default()));
+        assert_eq!(attrs.deduplication_id.len(), 64);
+    }
+    
+    #[test]
+    fn test_fifo_deduplication_is_scoped_to_the_tenant() {
+        let job_id = uuid::Uuid::new_v4();
+        let body = |tenant_id: uuid::Uuid| {
+            serde_json::json!({ "job_id": job_id, "tenant_id": tenant_id, "idempotency_key": "upload-1" }).to_string()
+        };
+        
+        let first = FifoAttributes::new(&body(uuid::Uuid::new_v4()), &Envelope::default());
+        let second = FifoAttributes::new(&body(uuid::Uuid::new_v4()), &Envelope::default());
+        assert_ne!(first.deduplication_id, second.deduplication_id);
+    }
+    
+    #[test]
+    fn test_fifo_deduplication_id_is_valid_for_any_key() {
+        // SQS allows alphanumerics and punctuation, up to 128 characters
+        let long = "k".repeat(500);
+        for key in ["upload 1", "café/ü", "a\nb", long.as_str()] {
+            let body = serde_json::json!({ "tenant_id": uuid::Uuid::new_v4(), "idempotency_key": key }).to_string();
+            let attrs = FifoAttributes::new(&body, &Envelope::default());
+            assert_eq!(attrs.deduplication_id.len(), 64);
+            assert!(attrs.deduplication_id.chars().all(|c| c.is_ascii_hexdigit()));
+        }
+    }
 }
@@ -0,0 +1,137 @@
+//! Queue depth monitoring
+//!
+//! Polls each configured queue's approximate message counts and publishes
+//! them as `paperforge_queue_depth`, `paperforge_queue_in_flight` and
+//! `paperforge_queue_delayed` gauges, labelled with the SQS queue name.
+//!
+//! SQS doesn't report the age of the oldest message through
+//! `GetQueueAttributes` (`ApproximateAgeOfOldestMessage` is a CloudWatch
+//! metric), so message age is recorded as messages are received instead;
+//! see [`Queue::receive_raw`](super::Queue::receive_raw).
+
+use crate::config::QueueConfig;
+use crate::errors::{AppError, Result};
+use crate::metrics;
+use aws_sdk_sqs::types::QueueAttributeName;
+use aws_sdk_sqs::Client as SqsClient;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Name of the queue at an SQS queue URL, its last path segment
+pub fn queue_name(url: &str) -> &str {
+    url.trim_end_matches('/').rsplit('/').next().unwrap_or(url)
+}
+
+/// Approximate message counts of a queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueDepth {
+    pub visible: u64,
+    pub in_flight: u64,
+    pub delayed: u64,
+}
+
+/// Publishes message counts for the ingestion, embedding and dead letter
+/// queues
+pub struct QueueMonitor {
+    client: SqsClient,
+    urls: Vec<String>,
+}
+
+impl QueueMonitor {
+    pub fn new(client: SqsClient, urls: Vec<String>) -> Self {
+        Self { client, urls }
+    }
+    
+    /// Monitor every queue in the config; `None` when none is configured
+    pub async fn from_config(config: &QueueConfig) -> Option<Self> {
+        let urls: Vec<String> = [
+            &config.ingestion_queue_url,
+            &config.embedding_queue_url,
+            &config.dlq_url,
+        ]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+        if urls.is_empty() {
+            return None;
+        }
+        
+        let aws_config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        Some(Self::new(SqsClient::new(&aws_config), urls))
+    }
+    
+    /// Read a queue's approximate message counts
+    pub async fn depth(&self, url: &str) -> Result<QueueDepth> {
+        let result = self.client
+            .get_queue_attributes()
+            .queue_url(url)
+            .attribute_names(QueueAttributeName::ApproximateNumberOfMessages)
+            .attribute_names(QueueAttributeName::ApproximateNumberOfMessagesNotVisible)
+            .attribute_names(QueueAttributeName::ApproximateNumberOfMessagesDelayed)
+            .send()
+            .await
+            .map_err(|e| AppError::QueueError {
+                message: format!("Failed to get queue attributes: {}", e),
+            })?;
+        
+        let attributes = result.attributes.unwrap_or_default();
+        let count = |name: QueueAttributeName| {
+            attributes.get(&name).and_then(|value| value.parse().ok()).unwrap_or(0)
+        };
+        
+        Ok(QueueDepth {
+            visible: count(QueueAttributeName::ApproximateNumberOfMessages),
+            in_flight: count(QueueAttributeName::ApproximateNumberOfMessagesNotVisible),
+            delayed: count(QueueAttributeName::ApproximateNumberOfMessagesDelayed),
+        })
+    }
+    
+    /// Publish every queue's counts once
+    pub async fn poll(&self) {
+        for url in &self.urls {
+            let name = queue_name(url);
+            match self.depth(url).await {
+                Ok(depth) => {
+                    debug!(queue = name, ?depth, "Polled queue depth");
+                    metrics::record_queue_depth(name, depth.visible, depth.in_flight, depth.delayed);
+                }
+                // Gauges keep their last value, so a failed poll isn't read as empty
+                Err(e) => warn!(queue = name, error = %e, "Failed to poll queue depth"),
+            }
+        }
+    }
+    
+    /// Poll every `interval`
+    pub fn spawn(self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            info!(
+                interval_secs = interval.as_secs(),
+                queues = self.urls.len(),
+                "Starting queue depth monitor"
+            );
+            
+            loop {
+                ticker.tick().await;
+                self.poll().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_queue_name() {
+        assert_eq!(
+            queue_name("https://sqs.us-east-1.amazonaws.com/123456789012/paperforge-ingestion"),
+            "paperforge-ingestion"
+        );
+        assert_eq!(queue_name("http://localhost:4566/000000000000/jobs.fifo/"), "jobs.fifo");
+        assert_eq!(queue_name("paperforge-dlq"), "paperforge-dlq");
+    }
+}
@@ -0,0 +1,171 @@
+//! Delayed message retries
+//!
+//! A message whose processing failed with a retryable error is sent again
+//! with a delay that doubles with each attempt, half of it randomized so a
+//! burst of failures doesn't come back at once, and the original deleted.
+//! Attempts are counted in the message's envelope across re-sends, so the
+//! retry curve doesn't depend on the visibility timeout, and a message out
+//! of attempts goes to the DLQ. FIFO queues drop re-sent copies as
+//! duplicates, so there the original is hidden for the delay instead and
+//! the redrive policy moves it to the DLQ.
+
+use super::{Envelope, Queue, Received, MAX_DELAY_SECONDS};
+use crate::errors::{Result, Retryable};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Delays between attempts of a failed message
+#[derive(Debug, Clone)]
+pub struct RetryBackoff {
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Longest delay; SQS caps per-message delays at 15 minutes
+    pub max_delay: Duration,
+}
+
+impl Default for RetryBackoff {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(MAX_DELAY_SECONDS as u64),
+        }
+    }
+}
+
+impl RetryBackoff {
+    /// Delay after the `attempt`th failed attempt, for `jitter` in `[0, 1)`
+    ///
+    /// The delay doubles from `base_delay` up to `max_delay`; `jitter`
+    /// picks a point in its upper half.
+    pub fn delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let doubled = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        doubled / 2 + doubled.mul_f64(jitter.clamp(0.0, 1.0) / 2.0)
+    }
+}
+
+/// What [`Queue::retry`] did with a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetryOutcome {
+    /// Sent again to be tried after `delay`; delete the original
+    Resent { attempt: u32, delay: Duration },
+    /// Left on the queue, reappearing after its visibility runs out
+    Kept,
+    /// Out of attempts and moved to the DLQ; delete the original
+    DeadLettered,
+}
+
+impl RetryOutcome {
+    /// Whether the caller should delete the message it received
+    pub fn delete_original(&self) -> bool {
+        !matches!(self, Self::Kept)
+    }
+}
+
+impl<T> Received<T> {
+    /// Attempts at this message so far, this one included
+    pub fn attempt(&self) -> u32 {
+        self.envelope.attempts.unwrap_or(0) + self.receive_count.max(1)
+    }
+}
+
+impl Queue {
+    /// Schedule another try of a message whose `attempt`th attempt failed
+    ///
+    /// The delay is the backoff for `attempt`, or the error's `retry_after`
+    /// when that is longer. After `max_receive_count` attempts the message
+    /// goes to the DLQ instead, or stays for the redrive policy when there
+    /// is no DLQ.
+    pub async fn retry<T, E: Retryable>(
+        &self,
+        message: &Received<T>,
+        attempt: u32,
+        error: &E,
+    ) -> Result<RetryOutcome> {
+        if attempt >= self.config.max_receive_count {
+            if self.config.dlq_url.is_none() {
+                warn!(attempt, error = %error, "Message out of attempts but no DLQ configured, leaving for redelivery");
+                return Ok(RetryOutcome::Kept);
+            }
+            self.move_to_dlq(message, &error.to_string()).await?;
+            return Ok(RetryOutcome::DeadLettered);
+        }
+
+        let jitter: f64 = rand::random();
+        let delay = self
+            .config
+            .retry_backoff
+            .delay(attempt, jitter)
+            .max(error.retry_after().unwrap_or_default())
+            .clamp(Duration::from_secs(1), Duration::from_secs(MAX_DELAY_SECONDS as u64));
+
+        if self.config.is_fifo() {
+            self.extend_visibility(&message.receipt_handle, delay.as_secs() as i32).await?;
+            return Ok(RetryOutcome::Kept);
+        }
+
+        let envelope = Envelope {
+            attempts: Some(attempt),
+            ..message.envelope.clone()
+        };
+        self.send_body(
+            &self.config.url,
+            &message.body,
+            &envelope,
+            Some(delay.as_secs() as i32),
+            "resend message",
+        )
+        .await?;
+
+        info!(attempt, delay_secs = delay.as_secs(), error = %error, "Message scheduled for retry");
+        Ok(RetryOutcome::Resent { attempt, delay })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backoff() -> RetryBackoff {
+        RetryBackoff {
+            base_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_delay_doubles_up_to_max() {
+        assert_eq!(backoff().delay(1, 0.0), Duration::from_secs(5));
+        assert_eq!(backoff().delay(2, 0.0), Duration::from_secs(10));
+        assert_eq!(backoff().delay(3, 0.0), Duration::from_secs(20));
+        assert_eq!(backoff().delay(4, 0.0), Duration::from_secs(30));
+        assert_eq!(backoff().delay(40, 0.0), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_jitter_stays_in_upper_half() {
+        assert_eq!(backoff().delay(2, 0.5), Duration::from_secs(15));
+        assert!(backoff().delay(2, 0.999) < Duration::from_secs(20));
+        assert_eq!(backoff().delay(2, 7.0), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_attempts_carry_across_resends() {
+        let received = |attempts, receive_count| Received {
+            message: (),
+            receipt_handle: String::new(),
+            envelope: Envelope {
+                attempts,
+                ..Default::default()
+            },
+            body: String::new(),
+            receive_count,
+        };
+
+        assert_eq!(received(None, 0).attempt(), 1);
+        assert_eq!(received(None, 2).attempt(), 2);
+        assert_eq!(received(Some(3), 1).attempt(), 4);
+    }
+}
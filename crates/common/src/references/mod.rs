@@ -0,0 +1,409 @@
+//! Reference parsing and citation contexts
+//!
+//! Splits a paper's reference list into entries and finds the in-text
+//! citations pointing at each, along with the sentence they appear in, so
+//! citation edges can record the claim a citation supports. Two citation
+//! styles are recognized:
+//! - numeric: `[3]`, `[1, 4]`, `[2-5]` against entries labelled `[n]` or `n.`
+//! - author-year: `(Vaswani et al., 2017)`, `Devlin et al. (2019)` against
+//!   entries starting with the first author's surname
+//!
+//! The reference list is the last "References" or "Bibliography" section
+//! found by [`detect_sections`]; text before it is searched for citations.
+
+use crate::chunking::{detect_sections, Section};
+use crate::crossref::normalize_doi;
+use regex_lite::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Most context sentences kept per reference
+const MAX_CONTEXTS: usize = 3;
+
+/// Longest context sentence kept, in characters
+const MAX_CONTEXT_CHARS: usize = 1000;
+
+/// Largest numeric range expanded, e.g. `[1-40]`
+const MAX_RANGE: u32 = 50;
+
+/// Words ending in a period that don't end a sentence
+const ABBREVIATIONS: &[&str] = &["al", "e.g", "i.e", "cf", "fig", "figs", "eq", "eqs", "sec", "vs", "etc", "no", "vol", "pp"];
+
+/// An entry of the reference list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reference {
+    /// Citation key: the entry number, or first author surname and year
+    /// (`vaswani2017`)
+    pub label: String,
+    /// Entry text with whitespace collapsed
+    pub text: String,
+    /// DOI mentioned in the entry, normalized
+    pub doi: Option<String>,
+}
+
+/// A reference and the sentences citing it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceCitation {
+    pub reference: Reference,
+    /// Distinct sentences citing the reference, in order of appearance
+    pub contexts: Vec<String>,
+}
+
+impl ReferenceCitation {
+    /// The citing sentences, one per line
+    pub fn context(&self) -> Option<String> {
+        (!self.contexts.is_empty()).then(|| self.contexts.join("\n"))
+    }
+}
+
+fn numeric_entry() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^(?:\[(\d{1,3})\]|(\d{1,3})\.)\s+(.*)$").unwrap())
+}
+
+fn author_entry() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^([A-Z][^\s,.;()]+),\s").unwrap())
+}
+
+fn year() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\b((?:19|20)\d{2}[a-z]?)\b").unwrap())
+}
+
+fn numeric_citation() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\[(\d{1,3}(?:\s*[,\-–]\s*\d{1,3})*)\]").unwrap())
+}
+
+fn parenthetical_citation() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\(([^()]*\b(?:19|20)\d{2}[a-z]?\b[^()]*)\)").unwrap())
+}
+
+fn author_year() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"([A-Z][^\s,;()]+)(?:\s+et\s+al\.?|\s+(?:and|&)\s+[A-Z][^\s,;()]+)?,?\s+((?:19|20)\d{2}[a-z]?)\b")
+            .unwrap()
+    })
+}
+
+fn narrative_citation() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"([A-Z][^\s,;()]+)(?:\s+et\s+al\.?|\s+(?:and|&)\s+[A-Z][^\s,;()]+)?\s+\(((?:19|20)\d{2}[a-z]?)\)")
+            .unwrap()
+    })
+}
+
+fn author_key(surname: &str, year: &str) -> String {
+    format!("{}{}", surname.to_lowercase(), year)
+}
+
+/// Split reference list text into entries
+///
+/// Entries are numbered when the first one is; otherwise a line starting
+/// with `Surname,` begins a new entry once the current one has a year and
+/// ends with a period.
+pub fn parse_references(text: &str) -> Vec<Reference> {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    let numbered = lines.first().is_some_and(|l| numeric_entry().is_match(l));
+    
+    let mut entries: Vec<(Option<String>, String)> = Vec::new();
+    for line in lines {
+        if numbered {
+            if let Some(caps) = numeric_entry().captures(line) {
+                let number = caps.get(1).or(caps.get(2)).unwrap().as_str();
+                entries.push((Some(number.to_string()), caps[3].to_string()));
+                continue;
+            }
+        } else if author_entry().is_match(line) {
+            let complete = entries.last().is_none_or(|(_, entry)| {
+                year().is_match(entry) && entry.ends_with('.')
+            });
+            if complete {
+                entries.push((None, line.to_string()));
+                continue;
+            }
+        }
+        match entries.last_mut() {
+            // Rejoin words hyphenated across lines
+            Some((_, entry)) if entry.ends_with('-') => {
+                entry.pop();
+                entry.push_str(line);
+            }
+            Some((_, entry)) => {
+                entry.push(' ');
+                entry.push_str(line);
+            }
+            None => {}
+        }
+    }
+    
+    entries.into_iter()
+        .filter_map(|(number, text)| {
+            let label = match number {
+                Some(number) => number,
+                None => {
+                    let surname = author_entry().captures(&text)?[1].to_string();
+                    let year = year().captures(&text)?[1].to_string();
+                    author_key(&surname, &year)
+                }
+            };
+            Some(Reference {
+                label,
+                doi: normalize_doi(&text),
+                text,
+            })
+        })
+        .collect()
+}
+
+/// Labels cited by a numeric citation's contents, e.g. `1, 3-5`
+fn expand_numeric(list: &str) -> Vec<String> {
+    let mut labels = Vec::new();
+    for part in list.split(',') {
+        let bounds: Vec<u32> = part.split(['-', '–'])
+            .filter_map(|n| n.trim().parse().ok())
+            .collect();
+        match bounds[..] {
+            [n] => labels.push(n.to_string()),
+            [from, to] if from <= to && to - from < MAX_RANGE => {
+                labels.extend((from..=to).map(|n| n.to_string()));
+            }
+            _ => {}
+        }
+    }
+    labels
+}
+
+/// In-text citations of `body` as (label, start, end) byte spans
+fn find_citations(body: &str, numbered: bool) -> Vec<(String, usize, usize)> {
+    let mut found = Vec::new();
+    if numbered {
+        for caps in numeric_citation().captures_iter(body) {
+            let span = caps.get(0).unwrap();
+            for label in expand_numeric(&caps[1]) {
+                found.push((label, span.start(), span.end()));
+            }
+        }
+        return found;
+    }
+    
+    for caps in parenthetical_citation().captures_iter(body) {
+        let span = caps.get(0).unwrap();
+        for cited in author_year().captures_iter(&caps[1]) {
+            found.push((author_key(&cited[1], &cited[2]), span.start(), span.end()));
+        }
+    }
+    for caps in narrative_citation().captures_iter(body) {
+        let span = caps.get(0).unwrap();
+        found.push((author_key(&caps[1], &caps[2]), span.start(), span.end()));
+    }
+    found.sort_by_key(|&(_, start, _)| start);
+    found
+}
+
+/// Whether the `.`, `!` or `?` at byte `i` ends a sentence
+fn is_sentence_end(text: &str, i: usize) -> bool {
+    let bytes = text.as_bytes();
+    if !matches!(bytes[i], b'.' | b'!' | b'?') {
+        return false;
+    }
+    if bytes.get(i + 1).is_some_and(|b| !b.is_ascii_whitespace()) {
+        return false;
+    }
+    if bytes[i] != b'.' {
+        return true;
+    }
+    
+    let word_start = text[..i]
+        .rfind(|c: char| c.is_whitespace() || c == '(')
+        .map(|p| p + 1)
+        .unwrap_or(0);
+    let word = &text[word_start..i];
+    // Initials ("J. Smith") and abbreviations ("et al.") aren't sentence ends
+    let initial = word.len() == 1 && word.chars().all(|c| c.is_ascii_uppercase());
+    !initial && !ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+}
+
+/// The sentence around the byte span `start..end`, whitespace collapsed
+fn sentence_around(text: &str, start: usize, end: usize) -> String {
+    let bytes = text.as_bytes();
+    
+    let mut from = 0;
+    for i in (0..start).rev() {
+        if is_sentence_end(text, i) || (bytes[i] == b'\n' && i > 0 && bytes[i - 1] == b'\n') {
+            from = i + 1;
+            break;
+        }
+    }
+    
+    let mut to = text.len();
+    for i in end..text.len() {
+        if is_sentence_end(text, i) {
+            to = i + 1;
+            break;
+        }
+        if bytes[i] == b'\n' && bytes.get(i + 1) == Some(&b'\n') {
+            to = i;
+            break;
+        }
+    }
+    
+    let sentence = text[from..to].split_whitespace().collect::<Vec<_>>().join(" ");
+    match sentence.char_indices().nth(MAX_CONTEXT_CHARS) {
+        Some((cut, _)) => sentence[..cut].to_string(),
+        None => sentence,
+    }
+}
+
+/// Byte range of the text around `pos` up to the neighbouring headings,
+/// so context sentences don't run into a heading line
+fn section_bounds(text: &str, sections: &[Section], pos: usize) -> (usize, usize) {
+    let from = sections.iter()
+        .rev()
+        .find(|s| s.start_pos <= pos)
+        .map(|s| text[s.start_pos..].find('\n').map(|n| s.start_pos + n + 1).unwrap_or(text.len()))
+        .unwrap_or(0);
+    let to = sections.iter()
+        .find(|s| s.start_pos > pos)
+        .map(|s| s.start_pos)
+        .unwrap_or(text.len());
+    (from.min(pos), to.min(text.len()))
+}
+
+/// The reference list among `sections`: the last "References" or
+/// "Bibliography" section
+pub fn reference_section(sections: &[Section]) -> Option<&Section> {
+    sections
+        .iter()
+        .rev()
+        .find(|s| matches!(s.title.to_lowercase().as_str(), "references" | "bibliography"))
+}
+
+/// Parse the reference list of `text` and collect each entry's citing
+/// sentences
+///
+/// References come in order of first citation, followed by those never
+/// cited in the text. Returns nothing when the text has no reference list.
+pub fn extract_citations(text: &str) -> Vec<ReferenceCitation> {
+    let sections = detect_sections(text);
+    let Some(section) = reference_section(&sections) else {
+        return Vec::new();
+    };
+    
+    // Skip the heading line itself
+    let list = &text[section.start_pos..section.end_pos];
+    let list = list.split_once('\n').map(|(_, rest)| rest).unwrap_or("");
+    let references = parse_references(list);
+    if references.is_empty() {
+        return Vec::new();
+    }
+    
+    let numbered = references[0].label.chars().all(|c| c.is_ascii_digit());
+    let body = &text[..section.start_pos];
+    
+    let by_label: HashMap<&str, usize> = references.iter()
+        .enumerate()
+        .map(|(i, r)| (r.label.as_str(), i))
+        .collect();
+    let mut order: Vec<usize> = Vec::new();
+    let mut contexts: Vec<Vec<String>> = vec![Vec::new(); references.len()];
+    
+    for (label, start, end) in find_citations(body, numbered) {
+        let Some(&index) = by_label.get(label.as_str()) else {
+            continue;
+        };
+        if contexts[index].is_empty() {
+            order.push(index);
+        }
+        let (from, to) = section_bounds(body, &sections, start);
+        let sentence = sentence_around(&body[from..to], start - from, end - from);
+        if contexts[index].len() < MAX_CONTEXTS && !contexts[index].contains(&sentence) {
+            contexts[index].push(sentence);
+        }
+    }
+    order.extend((0..references.len()).filter(|i| contexts[*i].is_empty()));
+    
+    let mut references: Vec<Option<Reference>> = references.into_iter().map(Some).collect();
+    order.into_iter()
+        .filter_map(|i| {
+            Some(ReferenceCitation {
+                reference: references[i].take()?,
+                contexts: std::mem::take(&mut contexts[i]),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_numeric_citations() {
+        let text = "A Paper\n\n1 Introduction\n\
+                    Transformers replaced recurrence [2]. Pretraining helps, e.g. for\n\
+                    question answering [1, 3-4]. As Fig. 2 shows, scale matters [2].\n\n\
+                    References\n\
+                    [1] J. Devlin, M. Chang. BERT: Pre-training of deep bidirec-\n\
+                    tional transformers. NAACL, 2019.\n\
+                    [2] A. Vaswani et al. Attention is all you need. doi:10.5555/3295222.3295349\n\
+                    [3] Someone. Another paper.\n\
+                    [4] Someone Else. Yet another.\n\
+                    [5] Never Cited. Unused.\n";
+        
+        let citations = extract_citations(text);
+        let labels: Vec<&str> = citations.iter().map(|c| c.reference.label.as_str()).collect();
+        assert_eq!(labels, vec!["2", "1", "3", "4", "5"]);
+        
+        assert_eq!(citations[0].reference.doi.as_deref(), Some("10.5555/3295222.3295349"));
+        assert_eq!(
+            citations[0].contexts,
+            vec![
+                "Transformers replaced recurrence [2].",
+                "As Fig. 2 shows, scale matters [2].",
+            ]
+        );
+        assert_eq!(
+            citations[1].context().as_deref(),
+            Some("Pretraining helps, e.g. for question answering [1, 3-4].")
+        );
+        assert!(citations[1].reference.text.contains("bidirectional transformers"));
+        assert_eq!(citations[4].context(), None);
+    }
+    
+    #[test]
+    fn test_author_year_citations() {
+        let text = "Introduction\n\
+                    Attention suffices (Vaswani et al., 2017; Devlin and Chang, 2019). \
+                    Devlin et al. (2019) pretrain bidirectionally.\n\
+                    Bibliography\n\
+                    Devlin, J., Chang, M., and Lee, K. BERT: Pre-training of deep\n\
+                    bidirectional transformers. 2019.\n\
+                    Vaswani, A., Shazeer, N. Attention is all you need. 2017.\n";
+        
+        let citations = extract_citations(text);
+        let labels: Vec<&str> = citations.iter().map(|c| c.reference.label.as_str()).collect();
+        assert_eq!(labels, vec!["vaswani2017", "devlin2019"]);
+        assert_eq!(citations[1].contexts.len(), 2);
+        assert_eq!(citations[1].contexts[1], "Devlin et al. (2019) pretrain bidirectionally.");
+    }
+    
+    #[test]
+    fn test_reference_section_ends_at_next_heading() {
+        let text = "Introduction\nSee [1].\nReferences\n[1] A. Author. A paper.\nAppendix A\nProofs.\n";
+        let sections = detect_sections(text);
+        let section = reference_section(&sections).unwrap();
+        assert!(text[section.start_pos..section.end_pos].starts_with("References\n[1]"));
+        assert!(text[section.end_pos..].starts_with("Appendix A"));
+    }
+    
+    #[test]
+    fn test_no_reference_list() {
+        assert!(extract_citations("Introduction\nNothing cited here [1].").is_empty());
+    }
+}
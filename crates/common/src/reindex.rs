@@ -0,0 +1,104 @@
+//! Runner for admin re-indexing jobs
+//!
+//! `POST /v2/admin/reindex` only records a job; a [`ReindexWorker`] on one
+//! of the gateways claims it and renews its lease while it runs. A job whose
+//! lease lapses lost its worker (the instance crashed or was redeployed) and
+//! is marked failed, so it doesn't block new jobs forever.
+
+use crate::db::{models::ReindexJob, Repository};
+use crate::errors::Result;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Worker configuration
+#[derive(Debug, Clone)]
+pub struct ReindexWorkerConfig {
+    /// Delay between checks for pending jobs
+    pub poll_interval: Duration,
+    /// How long a job may go without a heartbeat before it counts as abandoned
+    pub lease_secs: u64,
+}
+
+impl Default for ReindexWorkerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            lease_secs: 60,
+        }
+    }
+}
+
+/// Claims and runs re-indexing jobs
+pub struct ReindexWorker {
+    repository: Repository,
+    config: ReindexWorkerConfig,
+}
+
+impl ReindexWorker {
+    pub fn new(repository: Repository, config: ReindexWorkerConfig) -> Self {
+        Self { repository, config }
+    }
+    
+    /// Fail abandoned jobs, then run the next pending job to its end
+    ///
+    /// Returns the ID of the job that ran, if any. Failures of the job
+    /// itself are recorded on it rather than returned.
+    pub async fn run_once(&self) -> Result<Option<Uuid>> {
+        for job_id in self.repository.fail_stale_reindex_jobs(self.config.lease_secs).await? {
+            warn!(job_id = %job_id, "Re-indexing job lost its worker and was marked failed");
+        }
+        
+        let Some(job) = self.repository.claim_reindex_job(None).await? else {
+            return Ok(None);
+        };
+        // Failures are recorded on the job
+        let _ = self.run(&job).await;
+        Ok(Some(job.id))
+    }
+    
+    /// Run a job claimed with [`Repository::claim_reindex_job`], renewing
+    /// its lease until it ends
+    pub async fn run(&self, job: &ReindexJob) -> Result<()> {
+        info!(job_id = %job.id, "Running re-indexing job");
+        tokio::select! {
+            result = self.repository.run_reindex_job(job) => result,
+            _ = self.keep_lease(job.id) => {
+                warn!(job_id = %job.id, "Re-indexing job is no longer active, stopping");
+                Ok(())
+            }
+        }
+    }
+    
+    /// Renew the job's lease until it is no longer active
+    async fn keep_lease(&self, job_id: Uuid) {
+        let mut ticker = tokio::time::interval(Duration::from_secs((self.config.lease_secs / 3).max(1)));
+        loop {
+            ticker.tick().await;
+            match self.repository.heartbeat_reindex_job(job_id).await {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(e) => warn!(job_id = %job_id, error = %e, "Failed to renew re-indexing lease"),
+            }
+        }
+    }
+    
+    /// Run the worker loop on the current runtime
+    ///
+    /// The first pass runs immediately, so jobs abandoned by a previous
+    /// instance are failed at startup.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.run_once().await {
+                    // Another job may be waiting
+                    Ok(Some(_)) => continue,
+                    Ok(None) => {}
+                    Err(e) => error!(error = %e, "Re-indexing worker poll failed"),
+                }
+                tokio::time::sleep(self.config.poll_interval).await;
+            }
+        })
+    }
+}
@@ -0,0 +1,216 @@
+//! Request-scoped context
+//!
+//! [`RequestContext`] is what a request carries from service to service:
+//! its ID, tenant, deadline, preferred locale and trace context. The gateway
+//! builds it once per request in middleware and handlers take it as an
+//! extractor; gRPC services find it in the request extensions, where
+//! [`GrpcAuthLayer`](crate::auth::GrpcAuthLayer) puts it.
+//! [`RequestContext::propagate`] writes it onto an outgoing gRPC call, from
+//! whose metadata the next service reads it back.
+
+use crate::auth::{AuthContext, AuthState};
+use crate::deadline;
+use crate::errors::{AppError, Result};
+use crate::queue::TRACEPARENT_ATTRIBUTE;
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{header, request::Parts, HeaderMap},
+};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Header, and gRPC metadata key, carrying the request ID
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Header, and gRPC metadata key, naming the tenant
+pub const TENANT_ID_HEADER: &str = "x-tenant-id";
+
+/// Header, and gRPC metadata key, listing the caller's preferred languages
+const ACCEPT_LANGUAGE: &str = "accept-language";
+
+/// Context of the request being served
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    pub request_id: String,
+    /// Authenticated tenant; `None` for unauthenticated requests
+    pub tenant_id: Option<Uuid>,
+    /// When the caller stops waiting for the response
+    pub deadline: Option<Instant>,
+    /// Preferred language tag from `Accept-Language`, such as `en-US`
+    pub locale: Option<String>,
+    /// W3C trace context the request came with
+    pub traceparent: Option<String>,
+}
+
+impl RequestContext {
+    /// Context of an incoming request (or gRPC call) from its headers
+    ///
+    /// The tenant is left unset until the request is authenticated, and
+    /// the deadline to the caller; a request without an ID gets a new one.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        
+        Self {
+            request_id: get(REQUEST_ID_HEADER)
+                .map(String::from)
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            tenant_id: None,
+            deadline: None,
+            locale: get(ACCEPT_LANGUAGE).and_then(preferred_locale),
+            traceparent: get(TRACEPARENT_ATTRIBUTE).map(String::from),
+        }
+    }
+    
+    /// Take the tenant and request ID of the authenticated caller
+    pub fn with_auth(mut self, auth: &AuthContext) -> Self {
+        self.tenant_id = Some(auth.tenant_id);
+        self.request_id = auth.request_id.clone();
+        self
+    }
+    
+    /// Set the deadline `timeout` from now; without one there's none
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.deadline = timeout.map(|timeout| Instant::now() + timeout);
+        self
+    }
+    
+    /// Time left until the deadline, zero once it has passed
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+    
+    /// Run `future` within the deadline, so queries it runs are cut short
+    /// by it
+    pub async fn scope<F: Future>(&self, future: F) -> F::Output {
+        match self.deadline {
+            Some(deadline) => deadline::with_deadline(deadline, future).await,
+            None => future.await,
+        }
+    }
+    
+    /// Carry the context over to an outgoing gRPC call
+    ///
+    /// The deadline becomes the call's timeout.
+    pub fn propagate<T>(&self, request: &mut tonic::Request<T>) {
+        if let Some(remaining) = self.remaining() {
+            request.set_timeout(remaining);
+        }
+        
+        let metadata = request.metadata_mut();
+        let fields = [
+            (REQUEST_ID_HEADER, Some(self.request_id.clone())),
+            (TENANT_ID_HEADER, self.tenant_id.map(|t| t.to_string())),
+            (ACCEPT_LANGUAGE, self.locale.clone()),
+            (TRACEPARENT_ATTRIBUTE, self.traceparent.clone()),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value.and_then(|v| v.parse().ok()) {
+                metadata.insert(name, value);
+            }
+        }
+    }
+}
+
+/// Axum extractor for RequestContext
+///
+/// The context is built by the gateway's middleware; a request that
+/// carries credentials is authenticated to fill in its tenant.
+impl<S> FromRequestParts<S> for RequestContext
+where
+    S: Send + Sync,
+    AuthState: FromRef<S>,
+{
+    type Rejection = AppError;
+    
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        let context = parts
+            .extensions
+            .get::<RequestContext>()
+            .cloned()
+            .unwrap_or_else(|| RequestContext::from_headers(&parts.headers));
+        
+        if context.tenant_id.is_some() || !parts.headers.contains_key(header::AUTHORIZATION) {
+            return Ok(context);
+        }
+        let auth = AuthContext::from_request_parts(parts, state).await?;
+        Ok(context.with_auth(&auth))
+    }
+}
+
+/// Get the context of a gRPC request
+///
+/// Authenticated calls carry it in their extensions; for others it's read
+/// from the metadata.
+pub fn grpc_request_context<T>(request: &tonic::Request<T>) -> RequestContext {
+    request.extensions().get::<RequestContext>().cloned().unwrap_or_else(|| {
+        let headers = request.metadata().clone().into_headers();
+        RequestContext::from_headers(&headers).with_timeout(deadline::grpc_timeout(&headers))
+    })
+}
+
+/// Language tag with the highest weight in an `Accept-Language` value
+///
+/// `*` means any language, which says nothing about the preferred one.
+fn preferred_locale(accept_language: &str) -> Option<String> {
+    let mut best: Option<(&str, f32)> = None;
+    for entry in accept_language.split(',') {
+        let mut parts = entry.split(';');
+        let tag = parts.next().unwrap_or_default().trim();
+        let weight = parts
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok());
+        
+        match weight {
+            Some(weight) if weight > 0.0 && !tag.is_empty() && tag != "*" => {
+                if !best.is_some_and(|(_, best)| best >= weight) {
+                    best = Some((tag, weight));
+                }
+            }
+            _ => {}
+        }
+    }
+    best.map(|(tag, _)| tag.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_preferred_locale() {
+        assert_eq!(preferred_locale("fr-CH, fr;q=0.9, en;q=0.8").as_deref(), Some("fr-CH"));
+        assert_eq!(preferred_locale("en;q=0.5, de;q=0.9").as_deref(), Some("de"));
+        assert_eq!(preferred_locale("*, en;q=0.1").as_deref(), Some("en"));
+        assert_eq!(preferred_locale("en;q=0").as_deref(), None);
+        assert_eq!(preferred_locale("").as_deref(), None);
+    }
+    
+    #[test]
+    fn test_context_round_trips_through_metadata() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, "req-1".parse().unwrap());
+        headers.insert(ACCEPT_LANGUAGE, "de-DE,de;q=0.9".parse().unwrap());
+        headers.insert(
+            TRACEPARENT_ATTRIBUTE,
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".parse().unwrap(),
+        );
+        let mut context = RequestContext::from_headers(&headers).with_timeout(Some(Duration::from_secs(5)));
+        context.tenant_id = Some(Uuid::new_v4());
+        
+        let mut request = tonic::Request::new(());
+        context.propagate(&mut request);
+        let received = grpc_request_context(&tonic::Request::from_parts(
+            request.metadata().clone(),
+            Default::default(),
+            (),
+        ));
+        
+        assert_eq!(received.request_id, "req-1");
+        assert_eq!(received.locale.as_deref(), Some("de-DE"));
+        assert_eq!(received.traceparent, context.traceparent);
+        assert!(received.remaining().unwrap() <= Duration::from_secs(5));
+        assert!(received.remaining().unwrap() > Duration::from_secs(4));
+    }
+}
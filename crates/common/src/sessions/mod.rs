@@ -0,0 +1,145 @@
+//! Context engine session store
+//!
+//! Postgres is the system of record for sessions. With a Redis cache
+//! attached, sessions are written through to Redis and looked up there
+//! first, so the intelligence path doesn't pay a database round trip per
+//! request. Cache failures fall back to Postgres.
+//!
+//! Expired sessions are removed by a background sweeper.
+
+use crate::cache::{keys, Cache};
+use crate::config::SessionConfig;
+use crate::db::models::Session;
+use crate::db::SessionRepository;
+use crate::errors::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+/// Rows deleted per statement by the sweeper
+const SWEEP_BATCH_SIZE: u64 = 1000;
+
+/// Reads and writes sessions, optionally through a Redis cache
+#[derive(Clone)]
+pub struct SessionStore {
+    repo: Arc<dyn SessionRepository>,
+    cache: Option<Arc<Cache>>,
+    ttl_minutes: i64,
+}
+
+impl SessionStore {
+    pub fn new(repo: impl SessionRepository + 'static, config: &SessionConfig) -> Self {
+        Self {
+            repo: Arc::new(repo),
+            cache: None,
+            ttl_minutes: config.ttl_minutes,
+        }
+    }
+    
+    /// Serve lookups from Redis in front of Postgres
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+    
+    /// Look up a session, expired or not
+    pub async fn find(&self, session_id: Uuid) -> Result<Option<Session>> {
+        let key = keys::session(session_id);
+        if let Some(cache) = &self.cache {
+            match cache.get::<Session>(&key).await {
+                Ok(Some(session)) => return Ok(Some(session)),
+                Ok(None) => {}
+                Err(e) => warn!(error = %e, "Session cache lookup failed, reading from database"),
+            }
+        }
+        
+        let session = self.repo.find_session(session_id).await?;
+        if let Some(session) = session.as_ref().filter(|session| !session.is_expired()) {
+            self.cache_session(session).await;
+        }
+        Ok(session)
+    }
+    
+    /// Look up a live session of the tenant
+    pub async fn find_active(&self, tenant_id: Uuid, session_id: Uuid) -> Result<Option<Session>> {
+        Ok(self
+            .find(session_id)
+            .await?
+            .filter(|session| session.tenant_id == tenant_id && !session.is_expired()))
+    }
+    
+    /// Create or update a session, extending its expiry
+    pub async fn save(&self, tenant_id: Uuid, session_id: Uuid, state: serde_json::Value) -> Result<Session> {
+        let session = self.repo.upsert_session(tenant_id, session_id, state, self.ttl_minutes).await?;
+        self.cache_session(&session).await;
+        Ok(session)
+    }
+    
+    /// Cache a session until it expires
+    async fn cache_session(&self, session: &Session) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        let remaining = (session.expires_at.timestamp() - chrono::Utc::now().timestamp()).max(1) as u64;
+        if let Err(e) = cache.set_with_ttl(&keys::session(session.id), session, remaining).await {
+            // A stale entry would shadow this write, so try to drop it
+            warn!(error = %e, session_id = %session.id, "Failed to cache session");
+            let _ = cache.delete(&keys::session(session.id)).await;
+        }
+    }
+    
+    /// Delete expired sessions every `interval`
+    pub fn spawn_sweeper(&self, interval: Duration) -> JoinHandle<()> {
+        let repo = self.repo.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            info!(interval_secs = interval.as_secs(), "Starting expired session sweeper");
+            
+            loop {
+                ticker.tick().await;
+                match repo.delete_expired_sessions(SWEEP_BATCH_SIZE).await {
+                    Ok(0) => debug!("No expired sessions"),
+                    Ok(deleted) => info!(deleted, "Deleted expired sessions"),
+                    Err(e) => error!(error = %e, "Failed to delete expired sessions"),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory::MemoryRepository;
+
+    fn store() -> SessionStore {
+        SessionStore::new(MemoryRepository::new(), &SessionConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_find_active_is_tenant_scoped() {
+        let store = store();
+        let (tenant_id, session_id) = (Uuid::new_v4(), Uuid::new_v4());
+        store.save(tenant_id, session_id, serde_json::json!({"turns": 1})).await.unwrap();
+
+        let session = store.find_active(tenant_id, session_id).await.unwrap().unwrap();
+        assert_eq!(session.state["turns"], 1);
+        assert!(store.find_active(Uuid::new_v4(), session_id).await.unwrap().is_none());
+        assert!(store.find_active(tenant_id, Uuid::new_v4()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_save_extends_and_keeps_tenant() {
+        let store = store();
+        let (tenant_id, session_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let first = store.save(tenant_id, session_id, serde_json::json!({"turns": 1})).await.unwrap();
+        let second = store.save(tenant_id, session_id, serde_json::json!({"turns": 2})).await.unwrap();
+        assert!(second.expires_at >= first.expires_at);
+        assert_eq!(second.state["turns"], 2);
+
+        let hijack = store.save(Uuid::new_v4(), session_id, serde_json::json!({})).await;
+        assert!(matches!(hijack, Err(crate::errors::AppError::TenantMismatch)));
+    }
+}
@@ -0,0 +1,279 @@
+//! Service level objectives
+//!
+//! Request outcomes are tracked in-process over rolling 5-minute, 1-hour
+//! and 6-hour windows. Each window reports its error rate, latency
+//! percentiles and burn rates against the objectives: 99.9% availability,
+//! P50 under 50ms and P99 under 150ms (the targets [`LATENCY_BUCKETS`] is
+//! aligned to).
+//!
+//! A burn rate of 1 spends the error budget exactly over the SLO period; a
+//! fast-burn alert typically fires when both the 5-minute and 1-hour rates
+//! exceed 14.4. The rates are published as gauges so Prometheus alerts can
+//! use them directly.
+
+use crate::metrics::{self, LATENCY_BUCKETS, SLO_P99_SECS};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Share of requests that must succeed
+pub const AVAILABILITY_TARGET: f64 = 0.999;
+
+/// Width of the slots requests are counted in
+const SLOT_SECS: u64 = 10;
+
+/// Latency histogram buckets, plus one for requests slower than the last
+const BUCKETS: usize = LATENCY_BUCKETS.len() + 1;
+
+/// A rolling window SLOs are reported over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SloWindow {
+    FiveMinutes,
+    OneHour,
+    SixHours,
+}
+
+impl SloWindow {
+    pub const ALL: [SloWindow; 3] = [SloWindow::FiveMinutes, SloWindow::OneHour, SloWindow::SixHours];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SloWindow::FiveMinutes => "5m",
+            SloWindow::OneHour => "1h",
+            SloWindow::SixHours => "6h",
+        }
+    }
+    
+    pub fn duration(&self) -> Duration {
+        match self {
+            SloWindow::FiveMinutes => Duration::from_secs(5 * 60),
+            SloWindow::OneHour => Duration::from_secs(60 * 60),
+            SloWindow::SixHours => Duration::from_secs(6 * 60 * 60),
+        }
+    }
+    
+    fn slots(&self) -> u64 {
+        self.duration().as_secs() / SLOT_SECS
+    }
+}
+
+/// How a window measured up against the objectives
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowReport {
+    pub window: &'static str,
+    pub requests: u64,
+    /// Requests that failed with a server error
+    pub errors: u64,
+    pub error_rate: f64,
+    /// Upper bound of the latency bucket holding the percentile; `None`
+    /// without requests
+    pub p50_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+    /// Error rate over the error budget
+    pub error_burn_rate: f64,
+    /// Share of requests slower than the P99 target, over the 1% allowed
+    pub latency_burn_rate: f64,
+}
+
+/// Requests counted in one slot
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    /// Slots since the tracker started; tells a stale slot from a live one
+    index: u64,
+    requests: u64,
+    errors: u64,
+    /// Requests slower than the P99 target
+    slow: u64,
+    latency: [u64; BUCKETS],
+}
+
+impl Slot {
+    fn empty(index: u64) -> Self {
+        Self {
+            index,
+            requests: 0,
+            errors: 0,
+            slow: 0,
+            latency: [0; BUCKETS],
+        }
+    }
+}
+
+/// Rolling request counts for SLO reporting
+pub struct SloTracker {
+    started: Instant,
+    /// Ring of slots covering the longest window
+    slots: Mutex<Vec<Slot>>,
+}
+
+impl Default for SloTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SloTracker {
+    pub fn new() -> Self {
+        let len = SloWindow::SixHours.slots() as usize;
+        Self {
+            started: Instant::now(),
+            slots: Mutex::new(vec![Slot::empty(u64::MAX); len]),
+        }
+    }
+    
+    fn current_slot(&self) -> u64 {
+        self.started.elapsed().as_secs() / SLOT_SECS
+    }
+    
+    /// Count a finished request; `error` for server errors
+    pub fn record(&self, latency: Duration, error: bool) {
+        self.record_in(self.current_slot(), latency.as_secs_f64(), error);
+    }
+    
+    fn record_in(&self, index: u64, latency_secs: f64, error: bool) {
+        let mut slots = self.slots.lock().unwrap();
+        let len = slots.len() as u64;
+        let slot = &mut slots[(index % len) as usize];
+        if slot.index != index {
+            *slot = Slot::empty(index);
+        }
+        
+        slot.requests += 1;
+        if error {
+            slot.errors += 1;
+        }
+        if latency_secs > SLO_P99_SECS {
+            slot.slow += 1;
+        }
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| latency_secs <= *bound)
+            .unwrap_or(BUCKETS - 1);
+        slot.latency[bucket] += 1;
+    }
+    
+    /// Report a window ending now
+    pub fn report(&self, window: SloWindow) -> WindowReport {
+        self.report_at(self.current_slot(), window)
+    }
+    
+    fn report_at(&self, index: u64, window: SloWindow) -> WindowReport {
+        let oldest = index.saturating_sub(window.slots() - 1);
+        let (mut requests, mut errors, mut slow) = (0, 0, 0);
+        let mut latency = [0u64; BUCKETS];
+        for slot in self.slots.lock().unwrap().iter() {
+            if slot.index < oldest || slot.index > index {
+                continue;
+            }
+            requests += slot.requests;
+            errors += slot.errors;
+            slow += slot.slow;
+            for (total, count) in latency.iter_mut().zip(slot.latency) {
+                *total += count;
+            }
+        }
+        
+        let rate = |count: u64| if requests == 0 { 0.0 } else { count as f64 / requests as f64 };
+        WindowReport {
+            window: window.as_str(),
+            requests,
+            errors,
+            error_rate: rate(errors),
+            p50_ms: percentile(&latency, requests, 0.50).map(|secs| secs * 1000.0),
+            p99_ms: percentile(&latency, requests, 0.99).map(|secs| secs * 1000.0),
+            error_burn_rate: rate(errors) / (1.0 - AVAILABILITY_TARGET),
+            latency_burn_rate: rate(slow) / 0.01,
+        }
+    }
+    
+    /// Publish every window's burn rates as gauges
+    pub fn publish(&self) {
+        for window in SloWindow::ALL {
+            let report = self.report(window);
+            metrics::record_slo(
+                window.as_str(),
+                report.error_rate,
+                report.error_burn_rate,
+                report.latency_burn_rate,
+                report.p99_ms.map(|ms| ms / 1000.0),
+            );
+        }
+    }
+    
+    /// Publish the gauges every `interval`
+    pub fn spawn_publisher(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                tracker.publish();
+            }
+        })
+    }
+}
+
+/// Upper bound, in seconds, of the bucket holding quantile `q`
+fn percentile(latency: &[u64; BUCKETS], requests: u64, q: f64) -> Option<f64> {
+    if requests == 0 {
+        return None;
+    }
+    
+    let rank = ((requests as f64) * q).ceil().max(1.0) as u64;
+    let mut seen = 0;
+    for (bucket, count) in latency.iter().enumerate() {
+        seen += count;
+        if seen >= rank {
+            // Requests past the last bucket are reported at its bound
+            return Some(LATENCY_BUCKETS[bucket.min(LATENCY_BUCKETS.len() - 1)]);
+        }
+    }
+    LATENCY_BUCKETS.last().copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_report_rates_and_percentiles() {
+        let tracker = SloTracker::new();
+        for _ in 0..98 {
+            tracker.record_in(0, 0.020, false);
+        }
+        tracker.record_in(0, 0.400, true);
+        tracker.record_in(0, 0.400, false);
+        
+        let report = tracker.report_at(0, SloWindow::FiveMinutes);
+        assert_eq!(report.requests, 100);
+        assert_eq!(report.errors, 1);
+        assert!((report.p50_ms.unwrap() - 25.0).abs() < 1e-9);
+        assert_eq!(report.p99_ms, Some(500.0));
+        assert!((report.error_burn_rate - 10.0).abs() < 1e-9);
+        assert!((report.latency_burn_rate - 2.0).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_old_slots_leave_short_windows() {
+        let tracker = SloTracker::new();
+        tracker.record_in(0, 0.010, true);
+        tracker.record_in(100, 0.010, false);
+        
+        assert_eq!(tracker.report_at(100, SloWindow::FiveMinutes).requests, 1);
+        assert_eq!(tracker.report_at(100, SloWindow::OneHour).requests, 2);
+        
+        // Slot 0 is reused once the ring wraps around
+        let wrapped = SloWindow::SixHours.slots();
+        tracker.record_in(wrapped, 0.010, false);
+        assert_eq!(tracker.report_at(wrapped, SloWindow::SixHours).errors, 0);
+    }
+    
+    #[test]
+    fn test_empty_window() {
+        let report = SloTracker::new().report(SloWindow::OneHour);
+        assert_eq!(report.requests, 0);
+        assert_eq!(report.error_burn_rate, 0.0);
+        assert!(report.p99_ms.is_none());
+    }
+}
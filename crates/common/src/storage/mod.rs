@@ -0,0 +1,304 @@
+//! Raw document storage
+//!
+//! Keeps the original uploaded document and its extracted text, so the UI
+//! can show the source next to search hits. Two backends are available:
+//! S3 (or an S3-compatible store such as MinIO) and a local directory for
+//! development. Both hand out time-limited links: S3 presigned URLs, or
+//! HMAC-signed links served by the gateway for the local backend.
+
+use crate::config::{StorageBackend, StorageConfig};
+use crate::errors::{AppError, Result};
+use async_trait::async_trait;
+use aws_sdk_s3::{presigning::PresigningConfig, primitives::ByteStream};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Object name of the original document within a job's prefix
+pub const SOURCE_OBJECT: &str = "source.pdf";
+
+/// Object name of the extracted text within a job's prefix
+pub const TEXT_OBJECT: &str = "text.txt";
+
+/// Key of a document object stored for an ingestion job
+pub fn document_key(tenant_id: Uuid, job_id: Uuid, object: &str) -> String {
+    format!("{}/{}/{}", tenant_id, job_id, object)
+}
+
+/// A store for document objects
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// Write an object, replacing any existing one
+    async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()>;
+    
+    /// Read an object; `None` if it doesn't exist
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    
+    /// Delete an object; deleting one that doesn't exist is not an error
+    async fn delete(&self, key: &str) -> Result<()>;
+    
+    /// A URL that grants read access to the object until it expires
+    async fn presign(&self, key: &str, expires_in: Duration) -> Result<String>;
+    
+    /// Check a link issued by [`presign`](Self::presign), for backends
+    /// whose links are served by the gateway
+    fn verify_link(&self, _key: &str, _expires: i64, _signature: &str) -> bool {
+        false
+    }
+}
+
+/// Create the configured store; `None` when storage is disabled
+pub async fn create_store(config: &StorageConfig) -> Result<Option<Arc<dyn ObjectStore>>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    
+    let store: Arc<dyn ObjectStore> = match config.backend {
+        StorageBackend::Local => Arc::new(LocalStore::new(config)),
+        StorageBackend::S3 => Arc::new(S3Store::new(config).await?),
+    };
+    Ok(Some(store))
+}
+
+fn storage_error(err: impl std::fmt::Display) -> AppError {
+    AppError::StorageError {
+        message: err.to_string(),
+    }
+}
+
+/// S3 (or S3-compatible) object store
+pub struct S3Store {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+}
+
+impl S3Store {
+    /// Create a store using the default AWS credential chain
+    pub async fn new(config: &StorageConfig) -> Result<Self> {
+        let bucket = config.bucket.clone().ok_or_else(|| AppError::Configuration {
+            message: "storage.bucket is required for the s3 backend".to_string(),
+        })?;
+        
+        let aws_config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        let mut s3_config = aws_sdk_s3::config::Builder::from(&aws_config);
+        if let Some(endpoint) = &config.endpoint {
+            // S3-compatible stores generally don't support virtual-hosted buckets
+            s3_config = s3_config.endpoint_url(endpoint).force_path_style(true);
+        }
+        
+        Ok(Self {
+            client: aws_sdk_s3::Client::from_conf(s3_config.build()),
+            bucket,
+        })
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3Store {
+    async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(storage_error)?;
+        
+        Ok(())
+    }
+    
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let output = match self.client.get_object().bucket(&self.bucket).key(key).send().await {
+            Ok(output) => output,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+            Err(e) => return Err(storage_error(e)),
+        };
+        
+        let body = output.body.collect().await.map_err(storage_error)?;
+        Ok(Some(body.into_bytes().to_vec()))
+    }
+    
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(storage_error)?;
+        
+        Ok(())
+    }
+    
+    async fn presign(&self, key: &str, expires_in: Duration) -> Result<String> {
+        let presigning = PresigningConfig::expires_in(expires_in).map_err(storage_error)?;
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .presigned(presigning)
+            .await
+            .map_err(storage_error)?;
+        
+        Ok(request.uri().to_string())
+    }
+}
+
+/// Object store in a local directory
+///
+/// Links point at the gateway's `/v2/storage` route and carry an HMAC of
+/// the key and expiry. Without a configured `signing_secret` a random one
+/// is generated, so links only work on the gateway that issued them.
+pub struct LocalStore {
+    root: PathBuf,
+    public_url: String,
+    secret: Vec<u8>,
+}
+
+impl LocalStore {
+    pub fn new(config: &StorageConfig) -> Self {
+        let secret = match &config.signing_secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => {
+                let mut secret = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut secret);
+                secret
+            }
+        };
+        
+        Self {
+            root: PathBuf::from(&config.local_path),
+            public_url: config.public_url.trim_end_matches('/').to_string(),
+            secret,
+        }
+    }
+    
+    /// Path of `key` under the root, rejecting keys that would escape it
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+        if key.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(AppError::Validation {
+                message: format!("Invalid object key: {}", key),
+                field: Some("key".to_string()),
+            });
+        }
+        Ok(self.root.join(relative))
+    }
+    
+    fn link_mac(&self, key: &str, expires: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts any key length");
+        mac.update(key.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+}
+
+#[async_trait]
+impl ObjectStore for LocalStore {
+    async fn put(&self, key: &str, body: Vec<u8>, _content_type: &str) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(storage_error)?;
+        }
+        tokio::fs::write(&path, body).await.map_err(storage_error)
+    }
+    
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(body) => Ok(Some(body)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(storage_error(e)),
+        }
+    }
+    
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(storage_error(e)),
+        }
+    }
+    
+    async fn presign(&self, key: &str, expires_in: Duration) -> Result<String> {
+        self.path(key)?;
+        let expires = Utc::now().timestamp() + expires_in.as_secs() as i64;
+        let signature = hex::encode(self.link_mac(key, expires).finalize().into_bytes());
+        Ok(format!(
+            "{}/{}?expires={}&signature={}",
+            self.public_url, key, expires, signature
+        ))
+    }
+    
+    fn verify_link(&self, key: &str, expires: i64, signature: &str) -> bool {
+        if expires < Utc::now().timestamp() {
+            return false;
+        }
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        self.link_mac(key, expires).verify_slice(&signature).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn local_store() -> LocalStore {
+        LocalStore::new(&StorageConfig {
+            local_path: std::env::temp_dir()
+                .join(format!("paperforge-storage-{}", Uuid::new_v4()))
+                .display()
+                .to_string(),
+            signing_secret: Some("test-secret".to_string()),
+            ..Default::default()
+        })
+    }
+    
+    #[tokio::test]
+    async fn test_local_round_trip() {
+        let store = local_store();
+        let key = document_key(Uuid::new_v4(), Uuid::new_v4(), TEXT_OBJECT);
+        
+        assert_eq!(store.get(&key).await.unwrap(), None);
+        store.put(&key, b"extracted text".to_vec(), "text/plain").await.unwrap();
+        assert_eq!(store.get(&key).await.unwrap().as_deref(), Some(&b"extracted text"[..]));
+        
+        store.delete(&key).await.unwrap();
+        assert_eq!(store.get(&key).await.unwrap(), None);
+        store.delete(&key).await.unwrap();
+        
+        assert!(store.put("../outside", Vec::new(), "text/plain").await.is_err());
+        assert!(store.get("/etc/passwd").await.is_err());
+        assert!(store.delete("../outside").await.is_err());
+        
+        std::fs::remove_dir_all(&store.root).unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_local_links() {
+        let store = local_store();
+        let url = store.presign("t/j/source.pdf", Duration::from_secs(60)).await.unwrap();
+        
+        let (path, query) = url.split_once('?').unwrap();
+        assert!(path.ends_with("/v2/storage/t/j/source.pdf"));
+        let params: std::collections::HashMap<&str, &str> =
+            query.split('&').filter_map(|p| p.split_once('=')).collect();
+        let expires: i64 = params["expires"].parse().unwrap();
+        
+        assert!(store.verify_link("t/j/source.pdf", expires, params["signature"]));
+        assert!(!store.verify_link("t/j/text.txt", expires, params["signature"]));
+        assert!(!store.verify_link("t/j/source.pdf", expires + 1, params["signature"]));
+    }
+}
@@ -0,0 +1,295 @@
+//! LLM and embedding usage metering
+//!
+//! Every LLM completion and embedding call is counted in metrics and, when
+//! it runs for a tenant, appended to the `usage_ledger` table with its
+//! estimated cost. Tenants with a monthly budget are refused further calls
+//! ([`AppError::QuotaExceeded`]) once it is spent.
+//!
+//! Calls are attributed through a task-local scope: services wrap the work
+//! done for a tenant in [`attribute_to`], and the [`LlmClient`] and
+//! [`MeteredEmbedder`] record against [`current_tenant`].
+//!
+//! [`LlmClient`]: crate::context::LlmClient
+
+use crate::context::estimate_tokens;
+use crate::db::models::UsageRecordActiveModel;
+use crate::db::Repository;
+use crate::embeddings::{Embedder, TaggedEmbeddings};
+use crate::errors::{AppError, Result};
+use crate::metrics;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use sea_orm::Set;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+use uuid::Uuid;
+
+tokio::task_local! {
+    static CURRENT_TENANT: Uuid;
+}
+
+/// How long a tenant's budget and month-to-date spend are cached
+const BUDGET_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// USD per million prompt and completion tokens, by model name prefix; the
+/// longest matching prefix wins and unknown models are free
+const MODEL_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4-turbo", 10.00, 30.00),
+    ("gpt-4", 30.00, 60.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("text-embedding-ada-002", 0.10, 0.0),
+];
+
+/// Run `future` with its LLM and embedding calls attributed to `tenant_id`
+pub async fn attribute_to<F: Future>(tenant_id: Uuid, future: F) -> F::Output {
+    CURRENT_TENANT.scope(tenant_id, future).await
+}
+
+/// Tenant the current task's calls are attributed to
+pub fn current_tenant() -> Option<Uuid> {
+    CURRENT_TENANT.try_with(|tenant_id| *tenant_id).ok()
+}
+
+/// What a metered call was
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageKind {
+    Llm,
+    Embedding,
+}
+
+impl UsageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageKind::Llm => "llm",
+            UsageKind::Embedding => "embedding",
+        }
+    }
+}
+
+/// Estimated cost of a call in micro-dollars (1e-6 USD)
+pub fn estimate_cost_micros(model: &str, prompt_tokens: u64, completion_tokens: u64) -> i64 {
+    let model = model.to_lowercase();
+    let Some((_, prompt_price, completion_price)) = MODEL_PRICES
+        .iter()
+        .filter(|(prefix, _, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+    else {
+        return 0;
+    };
+    
+    // USD per million tokens is micro-dollars per token
+    (prompt_tokens as f64 * prompt_price + completion_tokens as f64 * completion_price).round() as i64
+}
+
+/// Start of the calendar month (UTC) containing `now`
+pub fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BudgetState {
+    budget_micros: Option<i64>,
+    spent_micros: i64,
+    period_start: DateTime<Utc>,
+    loaded_at: Instant,
+}
+
+/// Records usage and enforces tenants' monthly budgets
+pub struct UsageMeter {
+    repo: Repository,
+    budgets: Mutex<HashMap<Uuid, BudgetState>>,
+}
+
+impl UsageMeter {
+    /// Create a new meter
+    pub fn new(repo: Repository) -> Self {
+        Self {
+            repo,
+            budgets: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    /// Refuse the call when the current tenant's monthly budget is spent
+    ///
+    /// Calls outside a tenant scope are never refused, and neither are
+    /// calls whose budget can't be loaded.
+    pub async fn check_budget(&self) -> Result<()> {
+        let Some(tenant_id) = current_tenant() else {
+            return Ok(());
+        };
+        
+        let state = match self.budget_state(tenant_id).await {
+            Ok(state) => state,
+            Err(e) => {
+                warn!(tenant_id = %tenant_id, error = %e, "Failed to load usage budget");
+                return Ok(());
+            }
+        };
+        
+        match state.budget_micros {
+            Some(budget) if state.spent_micros >= budget => Err(AppError::QuotaExceeded {
+                message: format!(
+                    "monthly usage budget of ${:.2} is spent",
+                    budget as f64 / 1_000_000.0
+                ),
+            }),
+            _ => Ok(()),
+        }
+    }
+    
+    /// Record a call for the current tenant
+    ///
+    /// The ledger entry is written in the background.
+    pub async fn record(&self, kind: UsageKind, model: &str, prompt_tokens: u64, completion_tokens: u64) {
+        let cost_micros = estimate_cost_micros(model, prompt_tokens, completion_tokens);
+        let tenant_id = current_tenant();
+        
+        metrics::record_usage(
+            kind.as_str(),
+            model,
+            tenant_id,
+            prompt_tokens,
+            completion_tokens,
+            cost_micros as u64,
+        );
+        
+        let Some(tenant_id) = tenant_id else {
+            return;
+        };
+        
+        if let Some(state) = self.budgets.lock().unwrap().get_mut(&tenant_id) {
+            state.spent_micros += cost_micros;
+        }
+        
+        let entry = UsageRecordActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(tenant_id),
+            kind: Set(kind.as_str().to_string()),
+            model: Set(model.to_string()),
+            prompt_tokens: Set(prompt_tokens.min(i32::MAX as u64) as i32),
+            completion_tokens: Set(completion_tokens.min(i32::MAX as u64) as i32),
+            cost_micros: Set(cost_micros),
+            created_at: Set(Utc::now().into()),
+        };
+        let repo = self.repo.clone();
+        tokio::spawn(async move {
+            if let Err(e) = repo.record_usage(entry).await {
+                warn!(tenant_id = %tenant_id, error = %e, "Failed to record usage");
+            }
+        });
+    }
+    
+    /// Drop a tenant's cached budget, e.g. after it changes
+    pub fn invalidate(&self, tenant_id: Uuid) {
+        self.budgets.lock().unwrap().remove(&tenant_id);
+    }
+    
+    async fn budget_state(&self, tenant_id: Uuid) -> Result<BudgetState> {
+        let period_start = month_start(Utc::now());
+        if let Some(state) = self.budgets.lock().unwrap().get(&tenant_id) {
+            if state.period_start == period_start && state.loaded_at.elapsed() < BUDGET_CACHE_TTL {
+                return Ok(*state);
+            }
+        }
+        
+        let budget_micros = self.repo
+            .find_tenant_settings(tenant_id)
+            .await?
+            .and_then(|settings| settings.monthly_budget_micros);
+        let spent_micros = match budget_micros {
+            Some(_) => self.repo.tenant_spend_since(tenant_id, period_start).await?,
+            None => 0,
+        };
+        
+        let state = BudgetState {
+            budget_micros,
+            spent_micros,
+            period_start,
+            loaded_at: Instant::now(),
+        };
+        self.budgets.lock().unwrap().insert(tenant_id, state);
+        Ok(state)
+    }
+}
+
+/// Embedder that checks budgets and records usage around another embedder
+///
+/// Providers don't report token counts through [`Embedder`], so tokens are
+/// estimated from the input text.
+pub struct MeteredEmbedder {
+    inner: Arc<dyn Embedder>,
+    meter: Arc<UsageMeter>,
+}
+
+impl MeteredEmbedder {
+    pub fn new(inner: Arc<dyn Embedder>, meter: Arc<UsageMeter>) -> Self {
+        Self { inner, meter }
+    }
+}
+
+#[async_trait]
+impl Embedder for MeteredEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let tagged = self.embed_batch_tagged(&[text.to_string()]).await?;
+        tagged.embeddings.into_iter().next().ok_or_else(|| AppError::EmbeddingError {
+            message: "Empty response".to_string(),
+        })
+    }
+    
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(self.embed_batch_tagged(texts).await?.embeddings)
+    }
+    
+    /// Usage is recorded against the model that answered, which differs
+    /// from [`Embedder::model_name`] when a fallback provider stepped in
+    async fn embed_batch_tagged(&self, texts: &[String]) -> Result<TaggedEmbeddings> {
+        self.meter.check_budget().await?;
+        let tagged = self.inner.embed_batch_tagged(texts).await?;
+        let tokens: usize = texts.iter().map(|t| estimate_tokens(t)).sum();
+        self.meter
+            .record(UsageKind::Embedding, &tagged.model, tokens as u64, 0)
+            .await;
+        Ok(tagged)
+    }
+    
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+    
+    fn dimension(&self) -> usize {
+        self.inner.dimension()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_cost_estimate() {
+        assert_eq!(estimate_cost_micros("gpt-4o-mini", 1_000, 500), 450);
+        assert_eq!(estimate_cost_micros("gpt-4o-2024-08-06", 1_000, 0), 2_500);
+        assert_eq!(estimate_cost_micros("text-embedding-3-small", 50_000, 0), 1_000);
+        assert_eq!(estimate_cost_micros("hash-embedding", 50_000, 0), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_tenant_attribution() {
+        let tenant_id = Uuid::new_v4();
+        
+        assert_eq!(current_tenant(), None);
+        assert_eq!(attribute_to(tenant_id, async { current_tenant() }).await, Some(tenant_id));
+        
+        let start = month_start(Utc.with_ymd_and_hms(2024, 3, 17, 12, 30, 0).unwrap());
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+    }
+}
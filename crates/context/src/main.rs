@@ -26,7 +26,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting PaperForge Context Engine v{}", VERSION);
     
     // Load configuration
-    let config = AppConfig::load().map_err(|e| {
+    let config = AppConfig::load().await.map_err(|e| {
         tracing::error!(error = %e, "Failed to load configuration");
         e
     })?;
@@ -35,7 +35,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Initialize database connection
     info!("Connecting to database...");
-    let _db = DbPool::new(&config.database).await?;
+    let db = DbPool::new(&config.database).await?;
+    db.spawn_metrics_collector(std::time::Duration::from_secs(config.database.pool_metrics_interval_secs));
     
     // TODO: Initialize Redis connection
     // TODO: Initialize LLM client
@@ -0,0 +1,292 @@
+//! PaperForge End-to-End Test Harness
+//!
+//! [`TestStack`] starts Postgres with pgvector, Redis and LocalStack SQS in
+//! containers, applies the schema and migrations, and runs the gateway
+//! router, ingestion processor and embedding processor in-process against
+//! them. Queue messages only move when a test calls [`TestStack::drain`], so
+//! each test decides when the pipeline advances.
+//!
+//! The suite needs Docker and is ignored by default. Each stack points the
+//! AWS environment at its own LocalStack, so tests run one at a time:
+//!
+//! ```text
+//! cargo test -p paperforge-e2e -- --ignored --test-threads=1
+//! ```
+
+use anyhow::{anyhow, bail, Context};
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use paperforge_common::{
+    auth::{api_key_lookup_prefix, generate_api_key, hash_api_key, API_KEY_SCOPES},
+    config::{AppConfig, ConfigWatcher},
+    db::{DbPool, Repository},
+    embeddings::{Embedder, MockEmbedder},
+    outbox::{OutboxRelay, OutboxRelayConfig, EMBEDDING_QUEUE, INGESTION_QUEUE},
+    queue::{Queue, QueueConfig},
+};
+use paperforge_embedding_worker::processor::{EmbeddingConfig, EmbeddingJob, EmbeddingProcessor, JobOutcome};
+use paperforge_gateway::{create_router, middleware::rate_limit::ReloadableRateLimiter, AppState};
+use paperforge_ingestion::processor::{IngestionProcessor, IngestionQueueMessage};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use testcontainers::{runners::AsyncRunner, ContainerAsync, ImageExt};
+use testcontainers_modules::{localstack::LocalStack, postgres::Postgres, redis::Redis};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Drain rounds before giving up on a pipeline that never settles
+const MAX_DRAIN_ROUNDS: usize = 20;
+
+/// Largest response body the harness reads
+const MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Tenant created for a test, with a key carrying every scope
+pub struct TestTenant {
+    pub id: Uuid,
+    pub api_key: String,
+}
+
+/// Containerized dependencies with the services wired to them
+pub struct TestStack {
+    pub config: AppConfig,
+    pub db: DbPool,
+    router: Router,
+    relay: OutboxRelay,
+    ingestion_queue: Arc<Queue>,
+    embedding_queue: Arc<Queue>,
+    ingestion: IngestionProcessor,
+    embedding: EmbeddingProcessor,
+    // Containers stop when dropped, so they live as long as the stack
+    _postgres: ContainerAsync<Postgres>,
+    _redis: ContainerAsync<Redis>,
+    _localstack: ContainerAsync<LocalStack>,
+}
+
+impl TestStack {
+    /// Start the containers and connect every service to them
+    pub async fn start() -> anyhow::Result<Self> {
+        let postgres = Postgres::default()
+            .with_name("pgvector/pgvector")
+            .with_tag("pg16")
+            .start()
+            .await
+            .context("Failed to start Postgres")?;
+        let redis = Redis::default().start().await.context("Failed to start Redis")?;
+        let localstack = LocalStack::default()
+            .with_env_var("SERVICES", "sqs")
+            .start()
+            .await
+            .context("Failed to start LocalStack")?;
+
+        let database_url = format!(
+            "postgres://postgres:postgres@{}:{}/postgres",
+            postgres.get_host().await?,
+            postgres.get_host_port_ipv4(5432).await?,
+        );
+        migrate(&database_url).await?;
+
+        // Queue::new reads the standard AWS environment, so point it at LocalStack
+        std::env::set_var(
+            "AWS_ENDPOINT_URL",
+            format!("http://{}:{}", localstack.get_host().await?, localstack.get_host_port_ipv4(4566).await?),
+        );
+        std::env::set_var("AWS_REGION", "us-east-1");
+        std::env::set_var("AWS_ACCESS_KEY_ID", "test");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "test");
+        let aws = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        let sqs = aws_sdk_sqs::Client::new(&aws);
+
+        let mut config = AppConfig::default();
+        config.database.url = database_url;
+        config.redis.url = format!(
+            "redis://{}:{}",
+            redis.get_host().await?,
+            redis.get_host_port_ipv4(6379).await?,
+        );
+        config.queue.ingestion_queue_url = Some(create_queue(&sqs, "paperforge-ingestion").await?);
+        config.queue.embedding_queue_url = Some(create_queue(&sqs, "paperforge-embedding").await?);
+        config.queue.dlq_url = Some(create_queue(&sqs, "paperforge-dlq").await?);
+        // No external LLM or embedding provider; both are mocked deterministically
+        config.context.offline = true;
+        config.rate_limit.enabled = false;
+
+        let db = DbPool::new(&config.database)
+            .await?
+            .with_vector_storage(config.embedding.vector_storage())
+            .with_embedding_dimension(config.embedding.dimension);
+
+        let watcher = ConfigWatcher::new(config.clone());
+        let state = AppState::from_config(&config, watcher.shared(), ReloadableRateLimiter::new(&config.rate_limit))
+            .await
+            .map_err(|e| anyhow!("Failed to build gateway state: {}", e))?;
+        let router = create_router(state);
+
+        let ingestion_queue = Arc::new(Queue::new(test_queue_config(&config.queue.ingestion_queue_url)).await?);
+        let embedding_queue = Arc::new(Queue::new(test_queue_config(&config.queue.embedding_queue_url)).await?);
+        let relay = OutboxRelay::new(Repository::new(db.clone()), OutboxRelayConfig::default())
+            .with_queue(INGESTION_QUEUE, ingestion_queue.clone())
+            .with_queue(EMBEDDING_QUEUE, embedding_queue.clone());
+
+        let ingestion = paperforge_ingestion::build_processor(&config, db.clone(), Some(embedding_queue.clone()), None)
+            .map_err(|e| anyhow!("Failed to build ingestion processor: {}", e))?;
+
+        // Same model as the gateway's offline query embeddings
+        let embedder: Arc<dyn Embedder> = Arc::new(MockEmbedder::new(config.embedding.dimension));
+        let embedding = EmbeddingProcessor::new(db.clone(), embedder, EmbeddingConfig::from(&config.embedding_worker));
+
+        Ok(Self {
+            config,
+            db,
+            router,
+            relay,
+            ingestion_queue,
+            embedding_queue,
+            ingestion,
+            embedding,
+            _postgres: postgres,
+            _redis: redis,
+            _localstack: localstack,
+        })
+    }
+
+    /// Create a tenant whose API key carries every scope
+    pub async fn tenant(&self, name: &str) -> anyhow::Result<TestTenant> {
+        self.tenant_with_scopes(name, API_KEY_SCOPES).await
+    }
+
+    /// Create a tenant whose API key carries only the given scopes
+    pub async fn tenant_with_scopes(&self, name: &str, scopes: &[&str]) -> anyhow::Result<TestTenant> {
+        let api_key = generate_api_key();
+        let hash = hash_api_key(&api_key)?;
+        let scopes: Vec<String> = scopes.iter().map(|s| s.to_string()).collect();
+        let tenant = Repository::new(self.db.clone())
+            .create_tenant(name, &hash, api_key_lookup_prefix(&api_key), &scopes, 1000)
+            .await?;
+        Ok(TestTenant { id: tenant.id, api_key })
+    }
+
+    /// Send a request through the gateway router, returning the status and JSON body
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        tenant: &TestTenant,
+        body: Option<Value>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let builder = Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::AUTHORIZATION, format!("Bearer {}", tenant.api_key));
+        let request = match body {
+            Some(body) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(&body)?))?,
+            None => builder.body(Body::empty())?,
+        };
+
+        let response = self.router.clone().oneshot(request).await?;
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), MAX_RESPONSE_BYTES).await?;
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes)
+                .with_context(|| format!("Non-JSON response: {}", String::from_utf8_lossy(&bytes)))?
+        };
+        Ok((status, body))
+    }
+
+    /// A relay that publishes to one queue only, like a service that owns it
+    pub fn relay_for(&self, name: &str) -> OutboxRelay {
+        let queue = if name == INGESTION_QUEUE {
+            self.ingestion_queue.clone()
+        } else {
+            self.embedding_queue.clone()
+        };
+        OutboxRelay::new(Repository::new(self.db.clone()), OutboxRelayConfig::default()).with_queue(name, queue)
+    }
+
+    /// Relay the outbox and process queued messages until nothing is left
+    ///
+    /// Runs what the gateway relay, ingestion service and embedding worker
+    /// would do in the background; returns the number of jobs processed.
+    pub async fn drain(&self) -> anyhow::Result<usize> {
+        let mut processed = 0;
+        for _ in 0..MAX_DRAIN_ROUNDS {
+            let mut progressed = self.relay.run_once().await? > 0;
+
+            for received in self.ingestion_queue.receive::<IngestionQueueMessage>().await? {
+                let job_id = received.message.job_id();
+                self.ingestion
+                    .handle_message(received.message)
+                    .await
+                    .with_context(|| format!("Ingestion job {} failed", job_id))?;
+                self.ingestion_queue.delete(&received.receipt_handle).await?;
+                processed += 1;
+                progressed = true;
+            }
+
+            for received in self.embedding_queue.receive::<EmbeddingJob>().await? {
+                let job_id = received.message.job_id;
+                match self.embedding.process_job(received.message).await {
+                    Ok(JobOutcome::Completed) => {}
+                    Ok(JobOutcome::Retry { .. }) => bail!("Embedding job {} rejected chunks", job_id),
+                    Err(e) => bail!("Embedding job {} failed: {}", job_id, e),
+                }
+                self.embedding_queue.delete(&received.receipt_handle).await?;
+                processed += 1;
+                progressed = true;
+            }
+
+            if !progressed {
+                return Ok(processed);
+            }
+        }
+        bail!("Pipeline still busy after {} drain rounds", MAX_DRAIN_ROUNDS)
+    }
+}
+
+/// Queue settings that keep empty polls short
+fn test_queue_config(url: &Option<String>) -> QueueConfig {
+    QueueConfig {
+        url: url.clone().unwrap_or_default(),
+        wait_time_seconds: 1,
+        ..Default::default()
+    }
+}
+
+async fn create_queue(sqs: &aws_sdk_sqs::Client, name: &str) -> anyhow::Result<String> {
+    let output = sqs.create_queue().queue_name(name).send().await?;
+    output.queue_url.with_context(|| format!("LocalStack returned no URL for {}", name))
+}
+
+/// Apply `docs/schema.sql`, then every migration in order
+///
+/// The migrations are idempotent, so running them over the full schema
+/// checks they still apply cleanly.
+async fn migrate(database_url: &str) -> anyhow::Result<()> {
+    let docs = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../docs");
+    let pool = sqlx::PgPool::connect(database_url).await?;
+
+    let mut scripts = vec![docs.join("schema.sql")];
+    let mut migrations: Vec<PathBuf> = std::fs::read_dir(docs.join("migrations"))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    migrations.retain(|path| path.extension().is_some_and(|ext| ext == "sql"));
+    migrations.sort();
+    scripts.extend(migrations);
+
+    for script in scripts {
+        let sql = std::fs::read_to_string(&script)?;
+        sqlx::raw_sql(&sql)
+            .execute(&pool)
+            .await
+            .with_context(|| format!("Failed to apply {}", script.display()))?;
+    }
+    pool.close().await;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! Redis cache shared between services
+
+use paperforge_common::cache::{Cache, CacheConfig};
+use paperforge_e2e::TestStack;
+use uuid::Uuid;
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_search_generation_is_shared_across_prefixes() {
+    let stack = TestStack::start().await.unwrap();
+
+    // The gateway and embedding worker use the default prefix, the search
+    // service its own
+    let gateway = Cache::new(CacheConfig::from_redis(&stack.config.redis)).await.unwrap();
+    let search = Cache::new(CacheConfig {
+        key_prefix: stack.config.search.cache_key_prefix.clone(),
+        ..CacheConfig::from_redis(&stack.config.redis)
+    })
+    .await
+    .unwrap();
+
+    let tenant_id = Uuid::new_v4();
+    assert_eq!(search.search_generation(tenant_id).await.unwrap(), 0);
+    assert_eq!(gateway.invalidate_search(tenant_id).await.unwrap(), 1);
+    assert_eq!(search.search_generation(tenant_id).await.unwrap(), 1);
+
+    assert_eq!(search.invalidate_search(tenant_id).await.unwrap(), 2);
+    assert_eq!(gateway.search_generation(tenant_id).await.unwrap(), 2);
+    assert_eq!(gateway.search_generation(Uuid::new_v4()).await.unwrap(), 0);
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_default_ttl_follows_reload() {
+    let stack = TestStack::start().await.unwrap();
+    let cache = Cache::new(CacheConfig::from_redis(&stack.config.redis)).await.unwrap();
+
+    cache.set_default_ttl(1);
+    assert_eq!(cache.default_ttl(), 1);
+    let key = format!("ttl:{}", Uuid::new_v4());
+    cache.set(&key, &"value").await.unwrap();
+    assert!(cache.exists(&key).await.unwrap());
+
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert!(!cache.exists(&key).await.unwrap());
+}
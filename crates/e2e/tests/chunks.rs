@@ -0,0 +1,48 @@
+//! Chunk writes by re-delivered embedding jobs
+
+use paperforge_common::db::{models::ChunkType, NewChunk, Repository};
+use paperforge_e2e::TestStack;
+
+fn chunks(count: i32, model: &str, dimension: usize) -> Vec<NewChunk> {
+    (0..count)
+        .map(|index| NewChunk {
+            index,
+            content: format!("chunk {}", index),
+            embedding: vec![0.5; dimension],
+            token_count: 2,
+            chunk_type: ChunkType::default(),
+            metadata: serde_json::json!({}),
+            embedding_model: model.to_string(),
+            char_range: None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_redelivered_chunks_keep_their_rows() {
+    let stack = TestStack::start().await.unwrap();
+    let tenant = stack.tenant("e2e-chunks").await.unwrap();
+    let repo = Repository::new(stack.db.clone());
+    let dimension = stack.db.column_dimension().await.unwrap().unwrap_or(stack.config.embedding.dimension);
+    let paper = repo
+        .create_paper(tenant.id, "Chunks".to_string(), String::new(), None, None, serde_json::json!({}), None)
+        .await
+        .unwrap();
+
+    let first = repo.upsert_chunks(paper.id, chunks(3, "model-a", dimension), 1).await.unwrap();
+    assert_eq!(first.len(), 3);
+
+    // The job is delivered again, with one more chunk than before
+    let again = repo.upsert_chunks(paper.id, chunks(4, "model-a", dimension), 1).await.unwrap();
+    assert_eq!(again[..3], first[..]);
+    assert!(!first.contains(&again[3]));
+
+    // Another model replaces the embeddings in place
+    let replaced = repo.upsert_chunks(paper.id, chunks(4, "model-b", dimension), 2).await.unwrap();
+    assert_eq!(replaced, again);
+
+    let stored = repo.get_chunks_by_paper(paper.id).await.unwrap();
+    assert_eq!(stored.iter().map(|c| c.id).collect::<Vec<_>>(), again);
+    assert!(stored.iter().all(|c| c.embedding_model == "model-b" && c.embedding_version == 2));
+}
@@ -0,0 +1,55 @@
+//! Outbox relays sharing one database
+
+use paperforge_common::{
+    db::Repository,
+    outbox::{OutboxPayload, EMBEDDING_QUEUE, INGESTION_QUEUE},
+    queue::{IngestionJobOptions, UrlImportMessage},
+};
+use paperforge_e2e::TestStack;
+use serde_json::json;
+use uuid::Uuid;
+
+async fn write_outbox(repo: &Repository, queue: &'static str) {
+    let message = UrlImportMessage {
+        job_id: Uuid::new_v4(),
+        tenant_id: Uuid::new_v4(),
+        url: "https://example.org/paper.pdf".to_string(),
+        title: None,
+        metadata: json!({}),
+        idempotency_key: None,
+        options: IngestionJobOptions::default(),
+    };
+    repo.transaction(|uow| {
+        let payload = OutboxPayload::new(&message);
+        Box::pin(async move { uow.write_outbox(queue, payload).await })
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_relays_only_claim_their_own_queues() {
+    let stack = TestStack::start().await.unwrap();
+    let repo = Repository::new(stack.db.clone());
+    write_outbox(&repo, INGESTION_QUEUE).await;
+    write_outbox(&repo, EMBEDDING_QUEUE).await;
+
+    // Like the gateway and ingestion service, each relay owns one queue
+    let ingestion_relay = stack.relay_for(INGESTION_QUEUE);
+    let embedding_relay = stack.relay_for(EMBEDDING_QUEUE);
+
+    assert_eq!(ingestion_relay.run_once().await.unwrap(), 1);
+    assert_eq!(ingestion_relay.run_once().await.unwrap(), 0);
+    // The embedding row was neither leased nor failed by the other relay
+    assert_eq!(embedding_relay.run_once().await.unwrap(), 1);
+    assert_eq!(embedding_relay.run_once().await.unwrap(), 0);
+
+    let unpublished: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM outbox WHERE published_at IS NULL OR last_error IS NOT NULL",
+    )
+    .fetch_one(&sqlx::PgPool::connect(&stack.config.database.url).await.unwrap())
+    .await
+    .unwrap();
+    assert_eq!(unpublished, 0);
+}
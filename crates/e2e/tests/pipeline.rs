@@ -0,0 +1,126 @@
+//! Ingest -> embed -> search -> intelligence flows against real dependencies
+
+use axum::http::{Method, StatusCode};
+use paperforge_e2e::{TestStack, TestTenant};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const ABSTRACT: &str = "We introduce the quokkaformer, a transformer variant whose attention \
+    heads are pruned by a learned gating schedule. On long-document retrieval the \
+    quokkaformer matches dense attention while using a third of the memory.";
+
+/// Submit a paper and run the pipeline, returning the job response
+async fn ingest(stack: &TestStack, tenant: &TestTenant, title: &str, idempotency_key: &str) -> Value {
+    let (status, created) = stack
+        .request(
+            Method::POST,
+            "/v2/papers",
+            tenant,
+            Some(json!({
+                "idempotency_key": idempotency_key,
+                "paper": { "title": title, "abstract": ABSTRACT },
+            })),
+        )
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::ACCEPTED, "{}", created);
+    assert!(stack.drain().await.unwrap() >= 2, "expected ingestion and embedding jobs");
+
+    let (status, job) = stack
+        .request(Method::GET, &format!("/v2/jobs/{}", created["job_id"].as_str().unwrap()), tenant, None)
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::OK, "{}", job);
+    job
+}
+
+async fn search(stack: &TestStack, tenant: &TestTenant, query: &str, mode: &str) -> Vec<Uuid> {
+    let (status, body) = stack
+        .request(
+            Method::POST,
+            "/v2/search",
+            tenant,
+            Some(json!({ "query": query, "options": { "mode": mode } })),
+        )
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["paper_id"].as_str().unwrap().parse().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_ingested_paper_is_searchable() {
+    let stack = TestStack::start().await.unwrap();
+    let tenant = stack.tenant("e2e-search").await.unwrap();
+
+    let job = ingest(&stack, &tenant, "Quokkaformers for Long Documents", "quokka-1").await;
+    assert_eq!(job["status"], "completed", "{}", job);
+    assert!(job["chunks_created"].as_i64().unwrap() > 0);
+    let paper_id: Uuid = job["paper_id"].as_str().unwrap().parse().unwrap();
+
+    assert!(search(&stack, &tenant, "quokkaformer", "bm25").await.contains(&paper_id));
+    assert!(search(&stack, &tenant, "quokkaformer gating", "hybrid").await.contains(&paper_id));
+
+    let (status, body) = stack
+        .request(
+            Method::POST,
+            "/v2/intelligence/search",
+            &tenant,
+            Some(json!({ "query": "how does the quokkaformer prune attention heads?" })),
+        )
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["query_understanding"].is_object());
+    for result in body["results"].as_array().unwrap() {
+        assert_eq!(result["paper_id"], paper_id.to_string());
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_search_is_tenant_scoped() {
+    let stack = TestStack::start().await.unwrap();
+    let owner = stack.tenant("e2e-owner").await.unwrap();
+    let other = stack.tenant("e2e-other").await.unwrap();
+
+    let job = ingest(&stack, &owner, "Quokkaformers for Long Documents", "quokka-owner").await;
+    let paper_id: Uuid = job["paper_id"].as_str().unwrap().parse().unwrap();
+
+    assert!(!search(&stack, &other, "quokkaformer", "bm25").await.contains(&paper_id));
+    let (status, _) = stack
+        .request(Method::GET, &format!("/v2/papers/{}", paper_id), &other, None)
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_resubmission_reuses_job() {
+    let stack = TestStack::start().await.unwrap();
+    let tenant = stack.tenant("e2e-idempotency").await.unwrap();
+
+    let first = ingest(&stack, &tenant, "Quokkaformers for Long Documents", "quokka-retry").await;
+    let (status, again) = stack
+        .request(
+            Method::POST,
+            "/v2/papers",
+            &tenant,
+            Some(json!({
+                "idempotency_key": "quokka-retry",
+                "paper": { "title": "Quokkaformers for Long Documents", "abstract": ABSTRACT },
+            })),
+        )
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::OK, "{}", again);
+    assert_eq!(again["job_id"], first["job_id"]);
+    assert_eq!(stack.drain().await.unwrap(), 0);
+}
@@ -0,0 +1,67 @@
+//! Re-indexing jobs run by leased workers
+
+use paperforge_common::{
+    db::{models::ReindexStatus, Repository},
+    errors::AppError,
+    reindex::{ReindexWorker, ReindexWorkerConfig},
+};
+use paperforge_e2e::TestStack;
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_only_one_job_is_active() {
+    let stack = TestStack::start().await.unwrap();
+    let repo = Repository::new(stack.db.clone());
+
+    let (first, second) = tokio::join!(
+        repo.create_reindex_job(None, true, false, "test"),
+        repo.create_reindex_job(None, true, false, "test"),
+    );
+    // Both requests passed any check for an active job; the index turns one away
+    assert!(first.is_ok() != second.is_ok());
+    let err = first.and(second).unwrap_err();
+    assert!(matches!(err, AppError::Duplicate { .. }));
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_worker_runs_pending_job() {
+    let stack = TestStack::start().await.unwrap();
+    let repo = Repository::new(stack.db.clone());
+    let job = repo.create_reindex_job(None, true, false, "test").await.unwrap();
+
+    let worker = ReindexWorker::new(repo.clone(), ReindexWorkerConfig::default());
+    assert_eq!(worker.run_once().await.unwrap(), Some(job.id));
+    assert_eq!(worker.run_once().await.unwrap(), None);
+
+    let job = repo.find_reindex_job(job.id).await.unwrap().unwrap();
+    assert_eq!(job.status, ReindexStatus::Completed.as_str());
+    assert!(job.heartbeat_at.is_some());
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_abandoned_job_is_failed_on_startup() {
+    let stack = TestStack::start().await.unwrap();
+    let repo = Repository::new(stack.db.clone());
+    let job = repo.create_reindex_job(None, true, false, "test").await.unwrap();
+
+    // A worker claimed the job and died without finishing it
+    assert!(repo.claim_reindex_job(Some(job.id)).await.unwrap().is_some());
+    sqlx::query("UPDATE reindex_jobs SET status = 'text_search', heartbeat_at = NOW() - INTERVAL '10 minutes'")
+        .execute(&sqlx::PgPool::connect(&stack.config.database.url).await.unwrap())
+        .await
+        .unwrap();
+
+    let worker = ReindexWorker::new(repo.clone(), ReindexWorkerConfig::default());
+    assert_eq!(worker.run_once().await.unwrap(), None);
+
+    let failed = repo.find_reindex_job(job.id).await.unwrap().unwrap();
+    assert_eq!(failed.status, ReindexStatus::Failed.as_str());
+    assert!(failed.error_message.is_some());
+    assert!(repo.find_active_reindex_job().await.unwrap().is_none());
+    assert!(!repo.heartbeat_reindex_job(job.id).await.unwrap());
+
+    // New jobs can start again
+    repo.create_reindex_job(None, true, false, "test").await.unwrap();
+}
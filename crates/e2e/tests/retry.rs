@@ -0,0 +1,66 @@
+//! Failed ingestion jobs re-sent by the retry sweep
+
+use chrono::{Duration, Utc};
+use paperforge_common::{
+    db::Repository,
+    outbox::{OutboxPayload, INGESTION_QUEUE},
+    queue::{Envelope, FifoAttributes, IngestionJobOptions, UrlImportMessage},
+};
+use paperforge_e2e::TestStack;
+use serde_json::json;
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_requeued_jobs_get_a_new_deduplication_id() {
+    let stack = TestStack::start().await.unwrap();
+    let tenant = stack.tenant("e2e-retry").await.unwrap();
+    let repo = Repository::new(stack.db.clone());
+
+    let job = repo
+        .create_job_with_outbox(tenant.id, None, None, INGESTION_QUEUE, |job| {
+            OutboxPayload::new(&UrlImportMessage {
+                job_id: job.id,
+                tenant_id: tenant.id,
+                url: "https://example.org/paper.pdf".to_string(),
+                title: None,
+                metadata: json!({}),
+                idempotency_key: Some("retry-1".to_string()),
+                options: IngestionJobOptions::default(),
+            })
+        })
+        .await
+        .unwrap();
+
+    repo.record_job_attempt(job.id).await.unwrap();
+    let retry_at = Utc::now() - Duration::seconds(1);
+    repo.fail_job_attempt(job.id, "boom", Some(retry_at.into())).await.unwrap();
+    let requeued = repo.requeue_due_jobs(10, INGESTION_QUEUE).await.unwrap();
+    assert_eq!(requeued.len(), 1);
+
+    let pool = sqlx::PgPool::connect(&stack.config.database.url).await.unwrap();
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+        "SELECT payload::text, attributes::text FROM outbox WHERE payload->>'job_id' = $1 ORDER BY created_at",
+    )
+    .bind(job.id.to_string())
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(rows.len(), 2);
+
+    let envelopes: Vec<Envelope> = rows
+        .iter()
+        .map(|(_, attributes)| serde_json::from_str(attributes.as_deref().unwrap()).unwrap())
+        .collect();
+    assert_eq!(envelopes[0].attempts, None);
+    assert_eq!(envelopes[1].attempts, Some(1));
+
+    // The body is sent again unchanged, but outside the first send's
+    // deduplication window
+    assert_eq!(rows[0].0, rows[1].0);
+    let fifo: Vec<FifoAttributes> = rows
+        .iter()
+        .zip(&envelopes)
+        .map(|((payload, _), envelope)| FifoAttributes::new(payload, envelope))
+        .collect();
+    assert_ne!(fifo[0].deduplication_id, fifo[1].deduplication_id);
+}
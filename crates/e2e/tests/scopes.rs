@@ -0,0 +1,27 @@
+//! API key scopes enforced by the gateway handlers
+
+use axum::http::{Method, StatusCode};
+use paperforge_e2e::TestStack;
+use serde_json::json;
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_read_only_key_cannot_create_papers() {
+    let stack = TestStack::start().await.unwrap();
+    let tenant = stack.tenant_with_scopes("e2e-read-only", &["read"]).await.unwrap();
+
+    let (status, _) = stack
+        .request(
+            Method::POST,
+            "/v2/papers",
+            &tenant,
+            Some(json!({ "paper": { "title": "Read only", "abstract": "Should not be stored" } })),
+        )
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Reads are still allowed
+    let (status, _) = stack.request(Method::GET, "/v2/collections", &tenant, None).await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,158 @@
+//! Adaptive batching and provider rate limits
+//!
+//! [`BatchSizer`] grows the batch while the provider answers quickly and
+//! shrinks it when batches get slow or the provider throttles.
+//! [`ProviderLimiter`] spreads calls over the provider's per-minute request
+//! and token limits, so the worker waits before a call instead of being
+//! told to back off after it.
+
+use governor::{
+    clock::DefaultClock,
+    state::{InMemoryState, NotKeyed},
+    Quota, RateLimiter,
+};
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Batch size control from provider latency and throttling
+///
+/// Fast full batches grow the size by a quarter; a batch slower than the
+/// target shrinks it by a quarter and a throttled one halves it.
+#[derive(Debug)]
+pub struct BatchSizer {
+    min: usize,
+    max: usize,
+    target_latency: Duration,
+    current: AtomicUsize,
+}
+
+impl BatchSizer {
+    pub fn new(initial: usize, min: usize, max: usize, target_latency: Duration) -> Self {
+        let min = min.max(1);
+        let max = max.max(min);
+        Self {
+            min,
+            max,
+            target_latency,
+            current: AtomicUsize::new(initial.clamp(min, max)),
+        }
+    }
+
+    /// Chunks to send in the next call
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Adjust after a successful call embedding `batch_len` chunks
+    pub fn record_success(&self, batch_len: usize, latency: Duration) {
+        let current = self.current();
+        let next = if latency > self.target_latency {
+            current - current / 4
+        } else if batch_len >= current && latency < self.target_latency / 2 {
+            current + (current / 4).max(1)
+        } else {
+            current
+        };
+        self.set(next);
+    }
+
+    /// Adjust after the provider rejected a call with 429
+    pub fn record_throttled(&self) {
+        self.set(self.current() / 2);
+    }
+
+    fn set(&self, size: usize) {
+        self.current.store(size.clamp(self.min, self.max), Ordering::Relaxed);
+    }
+}
+
+type DirectLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+
+/// Token buckets matched to the provider's requests- and tokens-per-minute
+/// limits
+pub struct ProviderLimiter {
+    requests: Option<DirectLimiter>,
+    tokens: Option<(DirectLimiter, NonZeroU32)>,
+}
+
+impl ProviderLimiter {
+    /// Limits of 0 are not enforced
+    pub fn new(requests_per_minute: u32, tokens_per_minute: u32) -> Self {
+        Self {
+            requests: NonZeroU32::new(requests_per_minute)
+                .map(|rpm| RateLimiter::direct(Quota::per_minute(rpm))),
+            tokens: NonZeroU32::new(tokens_per_minute)
+                .map(|tpm| (RateLimiter::direct(Quota::per_minute(tpm)), tpm)),
+        }
+    }
+
+    /// Wait until a call sending `tokens` tokens fits within both limits
+    ///
+    /// A call larger than the whole per-minute token budget waits for the
+    /// full budget.
+    pub async fn acquire(&self, tokens: u32) {
+        if let Some(requests) = &self.requests {
+            requests.until_ready().await;
+        }
+        if let Some((limiter, capacity)) = &self.tokens {
+            let n = NonZeroU32::new(tokens).unwrap_or(NonZeroU32::MIN).min(*capacity);
+            // `n` never exceeds the bucket's capacity, so this can't fail
+            let _ = limiter.until_n_ready(n).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizer() -> BatchSizer {
+        BatchSizer::new(20, 1, 100, Duration::from_secs(2))
+    }
+
+    #[test]
+    fn test_grows_on_fast_full_batches() {
+        let sizer = sizer();
+        sizer.record_success(20, Duration::from_millis(300));
+        assert_eq!(sizer.current(), 25);
+
+        // A short final batch says nothing about capacity
+        sizer.record_success(3, Duration::from_millis(100));
+        assert_eq!(sizer.current(), 25);
+    }
+
+    #[test]
+    fn test_shrinks_on_slow_or_throttled_batches() {
+        let sizer = sizer();
+        sizer.record_success(20, Duration::from_secs(3));
+        assert_eq!(sizer.current(), 15);
+
+        sizer.record_throttled();
+        assert_eq!(sizer.current(), 7);
+    }
+
+    #[test]
+    fn test_stays_within_bounds() {
+        let sizer = BatchSizer::new(500, 4, 50, Duration::from_secs(2));
+        assert_eq!(sizer.current(), 50);
+        sizer.record_success(50, Duration::from_millis(10));
+        assert_eq!(sizer.current(), 50);
+
+        for _ in 0..10 {
+            sizer.record_throttled();
+        }
+        assert_eq!(sizer.current(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_limiter_admits_calls_within_budget() {
+        let limiter = ProviderLimiter::new(60, 1000);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            limiter.acquire(400).await;
+            limiter.acquire(600).await;
+        })
+        .await
+        .expect("calls within the burst should not wait");
+    }
+}
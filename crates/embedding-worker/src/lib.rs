@@ -0,0 +1,7 @@
+//! PaperForge Embedding Worker
+//!
+//! Batching and job processing behind the `embedding-worker` binary.
+//! Integration tests drive the same processor in-process.
+
+pub mod batching;
+pub mod processor;
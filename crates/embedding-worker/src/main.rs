@@ -6,16 +6,18 @@
 //! 3. Writes embeddings to database
 //! 4. Updates job progress
 
-mod processor;
-
-use crate::processor::{EmbeddingConfig, EmbeddingJob, EmbeddingProcessor};
 use paperforge_common::{
-    config::AppConfig,
-    db::DbPool,
-    embeddings::{create_embedder, Embedder},
-    queue::{Queue, QueueConfig},
+    cache::{Cache, CacheConfig},
+    config::{AppConfig, Service},
+    db::{DbPool, Repository},
+    embeddings::{create_embedder_chain, Embedder, Preprocessor},
+    errors::Retryable,
+    metrics,
+    queue::{Queue, QueueConfig, RetryBackoff},
+    usage::{MeteredEmbedder, UsageMeter},
     VERSION,
 };
+use paperforge_embedding_worker::processor::{EmbeddingConfig, EmbeddingJob, EmbeddingProcessor, JobOutcome};
 use std::sync::Arc;
 use tracing::{error, info, warn, Level};
 
@@ -34,24 +36,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting PaperForge Embedding Worker v{}", VERSION);
 
     // Load configuration
-    let config = AppConfig::load().map_err(|e| {
+    let config = AppConfig::load_for(Service::EmbeddingWorker).await.map_err(|e| {
         tracing::error!(error = %e, "Failed to load configuration");
         e
     })?;
 
     let config = Arc::new(config);
+    metrics::configure_tenant_labels(&config.observability);
 
     // Initialize database connection
     info!("Connecting to database...");
     let db = DbPool::new(&config.database).await?;
+    db.spawn_metrics_collector(std::time::Duration::from_secs(config.database.pool_metrics_interval_secs));
 
     // Initialize embedder
-    let embedder = create_embedder(
-        &config.embedding.provider,
-        config.embedding.api_key.clone(),
-        Some(config.embedding.model.clone()),
-        config.embedding.api_base.clone(),
-    );
+    let embedder = create_embedder_chain(&config.embedding);
+    let usage = Arc::new(UsageMeter::new(Repository::new(db.clone())));
+    let embedder: Arc<dyn Embedder> = Arc::new(MeteredEmbedder::new(embedder, usage));
 
     info!(
         model = %embedder.model_name(),
@@ -59,8 +60,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "Embedder initialized"
     );
 
+    // Refuse to start with a model whose vectors the database can't store
+    db.verify_embedding_dimension(embedder.model_name(), embedder.dimension()).await?;
+
     // Initialize processor
-    let processor = EmbeddingProcessor::new(db, embedder, EmbeddingConfig::default());
+    let processor = EmbeddingProcessor::new(
+        db,
+        embedder,
+        EmbeddingConfig::from(&config.embedding_worker),
+    )
+    .with_preprocessor(Preprocessor::new(&config.embedding.preprocess, &config.embedding.model));
+    let processor = match Cache::new(CacheConfig::from_redis(&config.redis)).await {
+        Ok(cache) => processor.with_cache(Arc::new(cache)),
+        Err(e) => {
+            warn!(error = %e, "Failed to connect to Redis, cached search results will only expire with their TTL");
+            processor
+        }
+    };
 
     // Check for command line arguments for testing
     let args: Vec<String> = std::env::args().collect();
@@ -95,18 +111,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Embedding worker ready, starting queue polling...");
 
     // Initialize embedding queue
-    let embedding_queue = match std::env::var("EMBEDDING_QUEUE_URL") {
-        Ok(url) => {
+    let embedding_queue = match config.queue.embedding_queue_url.clone() {
+        Some(url) => {
             info!(url = %url, "Connecting to embedding queue...");
             let queue_config = QueueConfig {
                 url,
-                dlq_url: std::env::var("DLQ_URL").ok(),
+                dlq_url: config.queue.dlq_url.clone(),
+                max_receive_count: config.queue.max_attempts,
+                retry_backoff: RetryBackoff {
+                    base_delay: std::time::Duration::from_secs(config.queue.retry_base_delay_secs),
+                    max_delay: std::time::Duration::from_secs(config.queue.retry_max_delay_secs),
+                },
                 ..Default::default()
             };
-            Queue::new(queue_config).await?
+            Arc::new(Queue::new(queue_config).await?)
         }
-        Err(_) => {
-            warn!("EMBEDDING_QUEUE_URL not set, waiting for shutdown signal...");
+        None => {
+            warn!("Embedding queue URL not configured, waiting for shutdown signal...");
             tokio::signal::ctrl_c().await?;
             info!("Embedding worker shutting down");
             return Ok(());
@@ -115,18 +136,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Circuit breaker state
     let mut consecutive_failures = 0;
-    const MAX_FAILURES: u32 = 5;
-    const CIRCUIT_BREAK_DURATION: std::time::Duration = std::time::Duration::from_secs(30);
+    let max_failures = config.embedding_worker.max_consecutive_failures;
+    let circuit_break_duration = std::time::Duration::from_secs(config.embedding_worker.circuit_break_secs);
+    let max_processing = std::time::Duration::from_secs(config.queue.max_processing_secs);
+
+    // Shut down for a restart once secrets are rotated
+    let secrets_rotated = config.secrets_rotated(Some(Service::EmbeddingWorker));
+    tokio::pin!(secrets_rotated);
 
     // Start polling loop
     loop {
         // Circuit breaker check
-        if consecutive_failures >= MAX_FAILURES {
+        if consecutive_failures >= max_failures {
             warn!(
                 failures = consecutive_failures,
                 "Circuit breaker open, pausing..."
             );
-            tokio::time::sleep(CIRCUIT_BREAK_DURATION).await;
+            tokio::time::sleep(circuit_break_duration).await;
             consecutive_failures = 0;
             info!("Circuit breaker reset, resuming...");
         }
@@ -136,36 +162,79 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 info!("Shutdown signal received");
                 break;
             }
+            _ = &mut secrets_rotated => break,
             result = embedding_queue.receive::<EmbeddingJob>() => {
                 match result {
                     Ok(messages) => {
-                        for (job, receipt_handle) in messages {
+                        let mut processed = Vec::with_capacity(messages.len());
+                        // Messages stay hidden while earlier ones in the batch are processed,
+                        // until they are deleted below
+                        let heartbeats: Vec<_> = messages
+                            .iter()
+                            .map(|received| embedding_queue.heartbeat(&received.receipt_handle, max_processing))
+                            .collect();
+
+                        for (received, heartbeat) in messages.into_iter().zip(&heartbeats) {
+                            let job = &received.message;
+                            let receipt_handle = received.receipt_handle.clone();
                             info!(
                                 job_id = %job.job_id,
                                 chunk_count = job.chunks.len(),
+                                schema_version = received.envelope.schema_version,
+                                traceparent = received.envelope.traceparent.as_deref(),
                                 "Received embedding job"
                             );
 
                             match processor.process_job(job.clone()).await {
-                                Ok(()) => {
+                                Ok(JobOutcome::Completed) => {
+                                    consecutive_failures = 0;
+                                    processed.push(receipt_handle);
+                                }
+                                Ok(JobOutcome::Retry { job, delay }) => {
                                     consecutive_failures = 0;
-                                    // Delete message on success
-                                    if let Err(e) = embedding_queue.delete(&receipt_handle).await {
-                                        error!(error = %e, "Failed to delete message");
+                                    // Only drop the original once its rejected chunks are queued again
+                                    match embedding_queue.send_delayed(&job, delay.as_secs() as i32).await {
+                                        Ok(_) => processed.push(receipt_handle),
+                                        Err(e) => error!(
+                                            job_id = %job.job_id,
+                                            error = %e,
+                                            "Failed to queue rejected chunks for retry"
+                                        ),
                                     }
                                 }
                                 Err(e) => {
-                                    consecutive_failures += 1;
+                                    // Only transient failures count towards the circuit breaker
+                                    if e.is_retryable() {
+                                        consecutive_failures += 1;
+                                    }
                                     error!(
                                         job_id = %job.job_id,
                                         error = %e,
+                                        retryable = e.is_retryable(),
                                         failures = consecutive_failures,
                                         "Failed to process embedding job"
                                     );
-                                    // Message will be re-delivered or moved to DLQ
+                                    // Fatal failures go straight to the DLQ; retryable ones are re-sent with backoff
+                                    heartbeat.stop();
+                                    match embedding_queue.handle_failure(&received, &e).await {
+                                        Ok(true) => processed.push(receipt_handle),
+                                        Ok(false) => {}
+                                        Err(e) => error!(error = %e, "Failed to settle failed message"),
+                                    }
+                                }
+                            }
+                        }
+
+                        // Delete processed messages in one round trip
+                        match embedding_queue.delete_batch(&processed).await {
+                            Ok(result) => {
+                                for failure in result.failed {
+                                    error!(code = %failure.code, "Failed to delete message");
                                 }
                             }
+                            Err(e) => error!(error = %e, "Failed to delete messages"),
                         }
+                        drop(heartbeats);
                     }
                     Err(e) => {
                         consecutive_failures += 1;
@@ -2,10 +2,17 @@
 //!
 //! Processes embedding jobs: generates vectors and stores them in the database.
 
-use paperforge_common::db::{DbPool, Repository, models::JobStatus};
-use paperforge_common::embeddings::Embedder;
+use crate::batching::{BatchSizer, ProviderLimiter};
+use paperforge_common::cache::Cache;
+use paperforge_common::config::EmbeddingWorkerConfig;
+use paperforge_common::db::{DbPool, EmbeddingRepository, NewChunk, Repository, models::{ChunkType, JobStatus}};
+use paperforge_common::embeddings::{Embedder, InputKind, Preprocessor, TaggedEmbeddings};
+use paperforge_common::errors::{AppError, Retryable};
+use paperforge_common::queue::{Consumable, Versioned};
+use paperforge_common::{metrics, usage};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, instrument, warn};
 use uuid::Uuid;
 
@@ -16,6 +23,29 @@ pub struct EmbeddingJob {
     pub paper_id: Uuid,
     pub chunks: Vec<ChunkData>,
     pub embedding_model: String,
+    /// Earlier tries of these chunks; non-zero when the job re-queues chunks
+    /// the provider rejected
+    #[serde(default)]
+    pub attempt: u32,
+}
+
+impl Versioned for EmbeddingJob {
+    const MESSAGE_TYPE: &'static str = "embedding.job";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+impl Consumable for EmbeddingJob {
+    const ACCEPTS: &'static [(&'static str, u32)] = &[(Self::MESSAGE_TYPE, Self::SCHEMA_VERSION)];
+}
+
+/// Result of a processed job
+#[derive(Debug)]
+pub enum JobOutcome {
+    /// Every chunk is stored, or the rejected ones are out of attempts
+    Completed,
+    /// Chunks the provider rejected, to be queued again on their own after
+    /// `delay`; the job's other chunks are stored
+    Retry { job: EmbeddingJob, delay: Duration },
 }
 
 /// Chunk data for embedding
@@ -24,31 +54,88 @@ pub struct ChunkData {
     pub index: i32,
     pub content: String,
     pub token_count: i32,
+    #[serde(default)]
+    pub chunk_type: ChunkType,
+    /// Where the chunk is in its source PDF
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+    /// Byte range of a text chunk in the paper's text
+    #[serde(default)]
+    pub char_range: Option<std::ops::Range<usize>>,
 }
 
+/// Throttled calls retried for one batch before the job fails
+const MAX_THROTTLED_RETRIES: u32 = 5;
+
+/// Wait after a 429 that carries no `Retry-After`
+const DEFAULT_THROTTLE_DELAY: Duration = Duration::from_secs(5);
+
+/// Delay before rejected chunks are first retried; doubles with each attempt
+const CHUNK_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Longest delay SQS allows on a message
+const MAX_CHUNK_RETRY_DELAY: Duration = Duration::from_secs(900);
+
 /// Embedding processor configuration
 #[derive(Debug, Clone)]
 pub struct EmbeddingConfig {
-    /// Batch size for embedding API calls
+    /// Initial batch size for embedding API calls
     pub batch_size: usize,
+    /// Smallest batch size adaptive batching shrinks to
+    pub min_batch_size: usize,
+    /// Largest batch size adaptive batching grows to
+    pub max_batch_size: usize,
+    /// Provider latency per batch above which batches shrink
+    pub target_batch_latency: Duration,
+    /// Provider requests per minute; 0 for no limit
+    pub requests_per_minute: u32,
+    /// Provider tokens per minute; 0 for no limit
+    pub tokens_per_minute: u32,
     /// Embedding model version for tracking
     pub embedding_version: i32,
+    /// Tries for a chunk the provider rejects
+    pub max_chunk_attempts: u32,
 }
 
 impl Default for EmbeddingConfig {
     fn default() -> Self {
         Self {
             batch_size: 20,
+            min_batch_size: 1,
+            max_batch_size: 100,
+            target_batch_latency: Duration::from_secs(2),
+            requests_per_minute: 0,
+            tokens_per_minute: 0,
             embedding_version: 1,
+            max_chunk_attempts: 3,
+        }
+    }
+}
+
+impl From<&EmbeddingWorkerConfig> for EmbeddingConfig {
+    fn from(config: &EmbeddingWorkerConfig) -> Self {
+        Self {
+            batch_size: config.batch_size,
+            min_batch_size: config.min_batch_size,
+            max_batch_size: config.max_batch_size,
+            target_batch_latency: Duration::from_millis(config.target_batch_latency_ms),
+            requests_per_minute: config.requests_per_minute,
+            tokens_per_minute: config.tokens_per_minute,
+            embedding_version: config.embedding_version,
+            max_chunk_attempts: config.max_chunk_attempts,
         }
     }
 }
 
 /// Embedding worker processor
 pub struct EmbeddingProcessor {
-    repository: Repository,
+    repository: Arc<dyn EmbeddingRepository>,
     embedder: Arc<dyn Embedder>,
     config: EmbeddingConfig,
+    batch_sizer: BatchSizer,
+    limiter: ProviderLimiter,
+    preprocessor: Preprocessor,
+    cache: Option<Arc<Cache>>,
 }
 
 impl EmbeddingProcessor {
@@ -57,28 +144,101 @@ impl EmbeddingProcessor {
         embedder: Arc<dyn Embedder>,
         config: EmbeddingConfig,
     ) -> Self {
+        Self::with_repository(Arc::new(Repository::new(db_pool)), embedder, config)
+    }
+
+    /// A processor storing chunks through `repository`
+    pub fn with_repository(
+        repository: Arc<dyn EmbeddingRepository>,
+        embedder: Arc<dyn Embedder>,
+        config: EmbeddingConfig,
+    ) -> Self {
+        let batch_sizer = BatchSizer::new(
+            config.batch_size,
+            config.min_batch_size,
+            config.max_batch_size,
+            config.target_batch_latency,
+        );
+        let limiter = ProviderLimiter::new(config.requests_per_minute, config.tokens_per_minute);
+
         Self {
-            repository: Repository::new(db_pool),
+            repository,
             embedder,
             config,
+            batch_sizer,
+            limiter,
+            preprocessor: Preprocessor::default(),
+            cache: None,
         }
     }
 
+    /// Prepare chunk text with `preprocessor` before embedding it
+    pub fn with_preprocessor(mut self, preprocessor: Preprocessor) -> Self {
+        self.preprocessor = preprocessor;
+        self
+    }
+
+    /// Invalidate the tenant's cached search results in `cache` once a job
+    /// has stored chunks
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     /// Process an embedding job
     #[instrument(skip(self, job), fields(job_id = %job.job_id, paper_id = %job.paper_id))]
-    pub async fn process_job(&self, job: EmbeddingJob) -> Result<(), EmbeddingError> {
+    pub async fn process_job(&self, job: EmbeddingJob) -> Result<JobOutcome, EmbeddingError> {
+        // Attribute the job's embedding usage to the tenant that owns it
+        let tenant_id = self
+            .repository
+            .find_job_by_id(job.job_id)
+            .await?
+            .map(|ingestion_job| ingestion_job.tenant_id);
+
+        let Some(tenant_id) = tenant_id else {
+            return self.embed_job(job).await;
+        };
+        let outcome = usage::attribute_to(tenant_id, self.embed_job(job)).await;
+
+        // Chunks are stored batch by batch, so even a failed job may have
+        // changed the tenant's results
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.invalidate_search(tenant_id).await {
+                warn!(error = %e, tenant_id = %tenant_id, "Failed to invalidate search cache");
+            }
+        }
+        outcome
+    }
+
+    async fn embed_job(&self, job: EmbeddingJob) -> Result<JobOutcome, EmbeddingError> {
         info!(
             chunk_count = job.chunks.len(),
             model = %job.embedding_model,
+            attempt = job.attempt,
             "Processing embedding job"
         );
 
-        let total_chunks = job.chunks.len();
+        // Chunks stored by an earlier delivery of the job aren't embedded again
+        let stored = self.repository.stored_chunk_indexes(job.paper_id).await?;
+        let pending: Vec<ChunkData> = job
+            .chunks
+            .iter()
+            .filter(|c| !stored.contains(&c.index))
+            .cloned()
+            .collect();
+        if pending.len() < job.chunks.len() {
+            debug!(skipped = job.chunks.len() - pending.len(), "Skipping chunks already stored");
+        }
+
+        let total_chunks = pending.len();
         let mut processed = 0;
-        let mut all_chunk_data = Vec::with_capacity(total_chunks);
+        let mut stored_count = stored.len();
+        let mut failed = Vec::new();
 
-        // Process chunks in batches
-        for batch in job.chunks.chunks(self.config.batch_size) {
+        // Process chunks in batches sized by recent provider behaviour
+        while processed < total_chunks {
+            let batch_size = self.batch_sizer.current().min(total_chunks - processed);
+            let batch = &pending[processed..processed + batch_size];
             debug!(
                 batch_size = batch.len(),
                 processed = processed,
@@ -86,60 +246,199 @@ impl EmbeddingProcessor {
                 "Processing batch"
             );
 
-            // Extract texts for embedding
-            let texts: Vec<String> = batch.iter().map(|c| c.content.clone()).collect();
+            let (chunks, consumed) = match self.embed_batch(batch).await {
+                Ok(tagged) => {
+                    // A throttled batch is retried with fewer chunks
+                    let batch = &batch[..tagged.embeddings.len()];
+                    (self.new_chunks(batch, tagged, &job.embedding_model), batch.len())
+                }
+                // Rejected input fails the same way on retry; find the chunks at fault
+                Err(EmbeddingError::EmbeddingFailed(e)) if !e.is_retryable() => {
+                    let (chunks, rejected) = self.isolate_rejected(batch, e).await?;
+                    failed.extend(rejected);
+                    (chunks, batch.len())
+                }
+                Err(e) => return Err(e),
+            };
 
-            // Generate embeddings
-            let embeddings = self
-                .embedder
-                .embed_batch(&texts)
-                .await
-                .map_err(|e| EmbeddingError::EmbeddingFailed(e.to_string()))?;
-
-            // Pair chunks with embeddings
-            for (chunk, embedding) in batch.iter().zip(embeddings.into_iter()) {
-                all_chunk_data.push((
-                    chunk.index,
-                    chunk.content.clone(),
-                    embedding,
-                    chunk.token_count,
-                ));
+            // Commit each batch, so a later failure doesn't cost this one's embeddings
+            if !chunks.is_empty() {
+                stored_count += chunks.len();
+                self.repository
+                    .upsert_chunks(job.paper_id, chunks, self.config.embedding_version)
+                    .await?;
             }
-
-            processed += batch.len();
+            processed += consumed;
 
             // Update job progress
             if let Err(e) = self
                 .repository
-                .update_job_progress(job.job_id, processed as i32)
+                .update_job_progress(job.job_id, stored_count as i32)
                 .await
             {
                 warn!(error = %e, "Failed to update job progress");
             }
         }
 
-        // Store all chunks in database
-        info!("Storing {} chunks in database...", all_chunk_data.len());
-
+        let failed_indexes: Vec<i32> = failed.iter().map(|c| c.index).collect();
         self.repository
-            .create_chunks(
-                job.paper_id,
-                all_chunk_data,
-                &job.embedding_model,
-                self.config.embedding_version,
-            )
-            .await
-            .map_err(|e| EmbeddingError::DatabaseError(e.to_string()))?;
+            .set_failed_chunks(job.job_id, &failed_indexes)
+            .await?;
+
+        if !failed.is_empty() && job.attempt + 1 < self.config.max_chunk_attempts {
+            let delay = CHUNK_RETRY_DELAY
+                .saturating_mul(2u32.saturating_pow(job.attempt))
+                .min(MAX_CHUNK_RETRY_DELAY);
+            warn!(
+                failed = ?failed_indexes,
+                attempt = job.attempt + 1,
+                delay_secs = delay.as_secs(),
+                "Chunks rejected by the embedding provider, retrying them on their own"
+            );
+            return Ok(JobOutcome::Retry {
+                job: EmbeddingJob {
+                    chunks: failed,
+                    attempt: job.attempt + 1,
+                    ..job
+                },
+                delay,
+            });
+        }
+
+        // Out of attempts, the paper stays searchable without the failed chunks
+        let error_message = (!failed.is_empty()).then(|| {
+            error!(failed = ?failed_indexes, "Chunks could not be embedded, completing job without them");
+            format!("{} chunks could not be embedded", failed.len())
+        });
 
         // Mark job as completed
         self.repository
-            .update_job_status(job.job_id, JobStatus::Completed, None, None, None)
-            .await
-            .map_err(|e| EmbeddingError::DatabaseError(e.to_string()))?;
+            .update_job_status(job.job_id, JobStatus::Completed, None, None, error_message)
+            .await?;
+
+        // Ingestion checkpoints are only needed while the job can be retried
+        if let Err(e) = self.repository.delete_checkpoints(job.job_id).await {
+            warn!(error = %e, "Failed to delete job checkpoints");
+        }
 
         info!("Embedding job completed successfully");
 
-        Ok(())
+        Ok(JobOutcome::Completed)
+    }
+
+    /// Pair chunks with their embeddings, tagged with the model that
+    /// produced them
+    fn new_chunks(&self, batch: &[ChunkData], tagged: TaggedEmbeddings, requested_model: &str) -> Vec<NewChunk> {
+        if tagged.model != requested_model {
+            debug!(
+                requested = %requested_model,
+                model = %tagged.model,
+                "Batch embedded with a different model than requested"
+            );
+        }
+
+        batch
+            .iter()
+            .zip(tagged.embeddings)
+            .map(|(chunk, embedding)| NewChunk {
+                index: chunk.index,
+                content: chunk.content.clone(),
+                embedding,
+                token_count: chunk.token_count,
+                chunk_type: chunk.chunk_type,
+                metadata: chunk.metadata.clone().unwrap_or_else(|| serde_json::json!({})),
+                embedding_model: tagged.model.clone(),
+                char_range: chunk.char_range.clone(),
+            })
+            .collect()
+    }
+
+    /// Embed a rejected batch one chunk at a time
+    ///
+    /// Returns the embedded chunks and the chunks the provider rejected on
+    /// their own. A transient failure fails the whole batch as before.
+    async fn isolate_rejected(
+        &self,
+        batch: &[ChunkData],
+        error: AppError,
+    ) -> Result<(Vec<NewChunk>, Vec<ChunkData>), EmbeddingError> {
+        if let [chunk] = batch {
+            warn!(chunk_index = chunk.index, error = %error, "Chunk rejected by the embedding provider");
+            return Ok((Vec::new(), vec![chunk.clone()]));
+        }
+
+        debug!(batch_size = batch.len(), error = %error, "Batch rejected, embedding its chunks one by one");
+        let mut embedded = Vec::new();
+        let mut rejected = Vec::new();
+        for chunk in batch {
+            match self.embed_batch(std::slice::from_ref(chunk)).await {
+                Ok(tagged) => {
+                    let model = tagged.model.clone();
+                    embedded.extend(self.new_chunks(std::slice::from_ref(chunk), tagged, &model));
+                }
+                Err(EmbeddingError::EmbeddingFailed(e)) if !e.is_retryable() => {
+                    warn!(chunk_index = chunk.index, error = %e, "Chunk rejected by the embedding provider");
+                    rejected.push(chunk.clone());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok((embedded, rejected))
+    }
+
+    /// Embed one batch within the provider's rate limits
+    ///
+    /// A throttled call shrinks the batch size and, after waiting out the
+    /// provider's `Retry-After`, is retried with only as many chunks as the
+    /// new size allows; the rest go in later batches. Embeddings are returned
+    /// for a prefix of `batch`.
+    async fn embed_batch(&self, mut batch: &[ChunkData]) -> Result<TaggedEmbeddings, EmbeddingError> {
+        let mut throttled = 0;
+        loop {
+            let texts: Vec<String> = batch
+                .iter()
+                .map(|c| self.preprocessor.apply(&c.content, InputKind::Passage))
+                .collect();
+            let tokens = batch.iter().map(|c| c.token_count.max(0) as u32).sum();
+            self.limiter.acquire(tokens).await;
+
+            let start = Instant::now();
+            let tagged = self.embedder.embed_batch_tagged(&texts).await;
+            let latency = start.elapsed();
+            metrics::record_embedding(
+                latency.as_secs_f64(),
+                tagged.as_ref().map_or(self.embedder.model_name(), |t| t.model.as_str()),
+                tagged.is_ok(),
+                usage::current_tenant(),
+            );
+
+            match tagged {
+                Ok(tagged) if tagged.embeddings.len() == batch.len() => {
+                    self.batch_sizer.record_success(batch.len(), latency);
+                    return Ok(tagged);
+                }
+                Ok(tagged) => {
+                    return Err(EmbeddingError::EmbeddingFailed(AppError::EmbeddingError {
+                        message: format!("Expected {} embeddings, got {}", batch.len(), tagged.embeddings.len()),
+                    }));
+                }
+                Err(e @ AppError::EmbeddingThrottled { .. }) if throttled < MAX_THROTTLED_RETRIES => {
+                    throttled += 1;
+                    self.batch_sizer.record_throttled();
+                    let delay = e.retry_after().unwrap_or(DEFAULT_THROTTLE_DELAY);
+                    warn!(
+                        attempt = throttled,
+                        delay_ms = delay.as_millis() as u64,
+                        batch_size = self.batch_sizer.current(),
+                        "Embedding provider throttled, backing off"
+                    );
+                    tokio::time::sleep(delay).await;
+                    batch = &batch[..self.batch_sizer.current().min(batch.len())];
+                }
+                Err(e) => return Err(EmbeddingError::EmbeddingFailed(e)),
+            }
+        }
     }
 
     /// Process a single chunk (for testing)
@@ -147,17 +446,19 @@ impl EmbeddingProcessor {
         self.embedder
             .embed(text)
             .await
-            .map_err(|e| EmbeddingError::EmbeddingFailed(e.to_string()))
+            .map_err(EmbeddingError::EmbeddingFailed)
     }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum EmbeddingError {
     #[error("Embedding generation failed: {0}")]
-    EmbeddingFailed(String),
+    EmbeddingFailed(#[source] AppError),
 
-    #[error("Database error: {0}")]
-    DatabaseError(String),
+    /// A failure outside the provider, e.g. storing chunks; retried only
+    /// when it is transient
+    #[error(transparent)]
+    App(AppError),
 
     #[error("Queue error: {0}")]
     QueueError(String),
@@ -166,8 +467,249 @@ pub enum EmbeddingError {
     ConfigError(String),
 }
 
-impl From<paperforge_common::errors::AppError> for EmbeddingError {
-    fn from(e: paperforge_common::errors::AppError) -> Self {
-        EmbeddingError::DatabaseError(e.to_string())
+impl From<AppError> for EmbeddingError {
+    fn from(e: AppError) -> Self {
+        match e {
+            // Retrying won't fix a misconfiguration, e.g. a dimension mismatch
+            AppError::Configuration { message } => EmbeddingError::ConfigError(message),
+            e => EmbeddingError::App(e),
+        }
+    }
+}
+
+impl Retryable for EmbeddingError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            EmbeddingError::EmbeddingFailed(e) | EmbeddingError::App(e) => e.is_retryable(),
+            EmbeddingError::QueueError(_) => true,
+            EmbeddingError::ConfigError(_) => false,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            EmbeddingError::EmbeddingFailed(e) | EmbeddingError::App(e) => e.retry_after(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use paperforge_common::db::{memory::MemoryRepository, models::IngestionJob, JobRepository};
+    use std::sync::Mutex;
+
+    /// Embedder that rejects or fails batches holding given chunk texts
+    #[derive(Default)]
+    struct FakeEmbedder {
+        /// Texts the provider rejects as invalid input
+        rejected: Vec<&'static str>,
+        /// Texts whose batch fails with a transient error
+        unavailable: Vec<&'static str>,
+        /// Texts of each call, in order
+        calls: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Embedder for FakeEmbedder {
+        async fn embed(&self, text: &str) -> paperforge_common::errors::Result<Vec<f32>> {
+            Ok(self.embed_batch(&[text.to_string()]).await?.remove(0))
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> paperforge_common::errors::Result<Vec<Vec<f32>>> {
+            self.calls.lock().unwrap().push(texts.to_vec());
+            let holds = |needles: &[&str]| texts.iter().any(|t| needles.contains(&t.as_str()));
+            if holds(&self.rejected) {
+                return Err(AppError::EmbeddingRejected {
+                    message: "invalid input".to_string(),
+                });
+            }
+            if holds(&self.unavailable) {
+                return Err(AppError::EmbeddingError {
+                    message: "provider unavailable".to_string(),
+                });
+            }
+            Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+        }
+
+        fn model_name(&self) -> &str {
+            "fake"
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    fn processor(repo: &Arc<MemoryRepository>, embedder: &Arc<FakeEmbedder>, max_chunk_attempts: u32) -> EmbeddingProcessor {
+        let config = EmbeddingConfig {
+            batch_size: 2,
+            max_batch_size: 2,
+            max_chunk_attempts,
+            ..Default::default()
+        };
+        EmbeddingProcessor::with_repository(repo.clone(), embedder.clone(), config)
+    }
+
+    /// A job embedding chunks `indexes`, each with text `chunk {index}`
+    fn job(repo: &MemoryRepository, indexes: impl IntoIterator<Item = i32>, attempt: u32) -> EmbeddingJob {
+        let job_id = Uuid::new_v4();
+        repo.insert_job(IngestionJob {
+            id: job_id,
+            tenant_id: Uuid::new_v4(),
+            paper_id: None,
+            batch_id: None,
+            status: "embedding".to_string(),
+            stage: "embedding".to_string(),
+            chunks_total: 0,
+            chunks_processed: 0,
+            error_message: None,
+            failed_chunks: serde_json::json!([]),
+            idempotency_key: None,
+            attempt_count: 1,
+            next_retry_at: None,
+            queue_message: None,
+            queue_attributes: None,
+            created_at: Utc::now().fixed_offset(),
+            started_at: None,
+            completed_at: None,
+        });
+        EmbeddingJob {
+            job_id,
+            paper_id: Uuid::new_v4(),
+            chunks: indexes
+                .into_iter()
+                .map(|index| ChunkData {
+                    index,
+                    content: format!("chunk {}", index),
+                    token_count: 2,
+                    chunk_type: ChunkType::default(),
+                    metadata: None,
+                    char_range: None,
+                })
+                .collect(),
+            embedding_model: "fake".to_string(),
+            attempt,
+        }
+    }
+
+    fn stored_indexes(repo: &MemoryRepository, paper_id: Uuid) -> Vec<i32> {
+        repo.chunks(paper_id).iter().map(|c| c.index).collect()
+    }
+
+    #[test]
+    fn test_app_errors_keep_their_retryability() {
+        let err = EmbeddingError::from(AppError::Configuration {
+            message: "dimension mismatch".to_string(),
+        });
+        assert!(matches!(err, EmbeddingError::ConfigError(_)));
+        assert!(!err.is_retryable());
+
+        // A job deleted while its chunks were embedded won't come back
+        let err = EmbeddingError::from(AppError::JobNotFound { id: "job".to_string() });
+        assert!(!err.is_retryable());
+
+        let err = EmbeddingError::from(AppError::ServiceUnavailable {
+            message: "database restarting".to_string(),
+        });
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(5)));
+    }
+
+    #[tokio::test]
+    async fn test_commits_each_batch_and_skips_stored_chunks() {
+        let repo = Arc::new(MemoryRepository::new());
+        let embedder = Arc::new(FakeEmbedder {
+            unavailable: vec!["chunk 4"],
+            ..Default::default()
+        });
+        let job = job(&repo, 0..5, 0);
+        let (job_id, paper_id) = (job.job_id, job.paper_id);
+
+        // An earlier delivery stored chunk 0
+        let first = processor(&repo, &embedder, 3);
+        let chunk = first.new_chunks(&job.chunks[..1], TaggedEmbeddings {
+            model: "fake".to_string(),
+            embeddings: vec![vec![1.0, 0.0]],
+        }, "fake");
+        repo.upsert_chunks(paper_id, chunk, 1).await.unwrap();
+
+        // The second batch fails; the first stays stored
+        let err = first.process_job(job).await.unwrap_err();
+        assert!(err.is_retryable());
+        assert_eq!(stored_indexes(&repo, paper_id), vec![0, 1, 2]);
+        let calls = embedder.calls.lock().unwrap().clone();
+        assert_eq!(calls[0], vec!["chunk 1", "chunk 2"]);
+        assert!(calls.iter().flatten().all(|text| text != "chunk 0"));
+
+        let stored = repo.find_job_by_id(job_id).await.unwrap().unwrap();
+        assert_eq!(stored.chunks_processed, 3);
+        assert_eq!(stored.status, "embedding");
+    }
+
+    #[tokio::test]
+    async fn test_isolates_rejected_chunks_and_retries_them() {
+        let repo = Arc::new(MemoryRepository::new());
+        let embedder = Arc::new(FakeEmbedder {
+            rejected: vec!["chunk 1"],
+            ..Default::default()
+        });
+        let job = job(&repo, 0..4, 0);
+        let (job_id, paper_id) = (job.job_id, job.paper_id);
+
+        let outcome = processor(&repo, &embedder, 3).process_job(job).await.unwrap();
+        let JobOutcome::Retry { job: retry, delay } = outcome else {
+            panic!("expected a retry, got {:?}", outcome);
+        };
+        assert_eq!(retry.chunks.iter().map(|c| c.index).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(retry.attempt, 1);
+        assert_eq!(delay, CHUNK_RETRY_DELAY);
+
+        // Chunk 0 was embedded on its own once its batch was rejected
+        assert_eq!(stored_indexes(&repo, paper_id), vec![0, 2, 3]);
+        assert_eq!(embedder.calls.lock().unwrap()[1], vec!["chunk 0"]);
+        let stored = repo.find_job_by_id(job_id).await.unwrap().unwrap();
+        assert_eq!(stored.failed_chunk_indexes(), vec![1]);
+        assert_eq!(stored.status, "embedding");
+    }
+
+    #[tokio::test]
+    async fn test_retry_delay_is_capped() {
+        let repo = Arc::new(MemoryRepository::new());
+        let embedder = Arc::new(FakeEmbedder {
+            rejected: vec!["chunk 1"],
+            ..Default::default()
+        });
+        let job = job(&repo, [1], 8);
+
+        let outcome = processor(&repo, &embedder, 20).process_job(job).await.unwrap();
+        let JobOutcome::Retry { job: retry, delay } = outcome else {
+            panic!("expected a retry, got {:?}", outcome);
+        };
+        assert_eq!(retry.attempt, 9);
+        assert_eq!(delay, MAX_CHUNK_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_completes_without_chunks_out_of_attempts() {
+        let repo = Arc::new(MemoryRepository::new());
+        let embedder = Arc::new(FakeEmbedder {
+            rejected: vec!["chunk 1"],
+            ..Default::default()
+        });
+        let job = job(&repo, [1], 2);
+        let job_id = job.job_id;
+
+        let outcome = processor(&repo, &embedder, 3).process_job(job).await.unwrap();
+        assert!(matches!(outcome, JobOutcome::Completed));
+
+        let stored = repo.find_job_by_id(job_id).await.unwrap().unwrap();
+        assert_eq!(stored.status, "completed");
+        assert_eq!(stored.error_message.as_deref(), Some("1 chunks could not be embedded"));
+        assert_eq!(stored.failed_chunk_indexes(), vec![1]);
+        assert!(stored.completed_at.is_some());
     }
 }
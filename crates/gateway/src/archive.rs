@@ -0,0 +1,335 @@
+//! Unpacking of uploaded archives of PDFs
+//!
+//! Zip, tar and gzipped tar archives are read from memory. Entries that are
+//! not PDFs, or are over the per-entry size limit, are skipped and reported
+//! rather than failing the upload; too many entries or PDFs, or too many
+//! bytes once unpacked, reject the whole archive.
+
+use flate2::read::GzDecoder;
+use paperforge_common::{
+    db::models::SkippedEntry,
+    errors::{AppError, Result},
+};
+use std::io::{Cursor, Read};
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const TAR_MAGIC: &[u8] = b"ustar";
+const TAR_MAGIC_OFFSET: usize = 257;
+const PDF_MAGIC: &[u8] = b"%PDF";
+
+/// Skipped entries listed by name; the rest are only counted
+pub const MAX_LISTED_SKIPPED: usize = 100;
+
+/// Archive formats accepted for upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Detect the format from the body's leading bytes, falling back to the
+    /// declared content type for tars without a ustar header
+    pub fn detect(body: &[u8], content_type: Option<&str>) -> Option<Self> {
+        if body.starts_with(ZIP_MAGIC) {
+            return Some(Self::Zip);
+        }
+        if body.starts_with(GZIP_MAGIC) {
+            return Some(Self::TarGz);
+        }
+        if body.get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + TAR_MAGIC.len()) == Some(TAR_MAGIC) {
+            return Some(Self::Tar);
+        }
+        
+        let content_type = content_type?.split(';').next()?.trim().to_ascii_lowercase();
+        match content_type.as_str() {
+            "application/zip" | "application/x-zip-compressed" => Some(Self::Zip),
+            "application/x-tar" => Some(Self::Tar),
+            "application/gzip" | "application/x-gzip" | "application/x-gtar" => Some(Self::TarGz),
+            _ => None,
+        }
+    }
+}
+
+/// Limits on what an archive may unpack to
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveLimits {
+    /// Most entries of any kind read from one archive, directories and
+    /// skipped entries included
+    pub max_total_entries: usize,
+    /// Most PDFs taken from one archive
+    pub max_entries: usize,
+    /// Largest PDF taken; larger entries are skipped
+    pub max_entry_bytes: u64,
+    /// Most bytes unpacked across all PDFs taken
+    pub max_unpacked_bytes: u64,
+}
+
+/// A PDF taken from an archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    /// Path of the entry in the archive
+    pub name: String,
+    pub body: Vec<u8>,
+}
+
+/// Contents of an unpacked archive
+#[derive(Debug, Default)]
+pub struct Unpacked {
+    /// PDFs, in archive order
+    pub documents: Vec<ArchiveEntry>,
+    /// The first [`MAX_LISTED_SKIPPED`] entries skipped
+    pub skipped: Vec<SkippedEntry>,
+    /// All entries skipped, listed or not
+    pub skipped_count: usize,
+}
+
+/// Unpack the PDFs in an archive
+pub fn unpack(body: &[u8], format: ArchiveFormat, limits: &ArchiveLimits) -> Result<Unpacked> {
+    let mut unpacker = Unpacker { limits, entries: 0, unpacked_bytes: 0, contents: Unpacked::default() };
+    
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(Cursor::new(body)).map_err(malformed)?;
+            for i in 0..archive.len() {
+                unpacker.count()?;
+                let entry = archive.by_index(i).map_err(malformed)?;
+                let name = entry.name().to_string();
+                let (is_file, size) = (entry.is_file(), entry.size());
+                unpacker.add(name, is_file, size, entry)?;
+            }
+        }
+        ArchiveFormat::Tar => unpacker.add_tar(body)?,
+        ArchiveFormat::TarGz => unpacker.add_tar(GzDecoder::new(body))?,
+    }
+    
+    Ok(unpacker.contents)
+}
+
+struct Unpacker<'a> {
+    limits: &'a ArchiveLimits,
+    /// Entries read so far, of any kind
+    entries: usize,
+    unpacked_bytes: u64,
+    contents: Unpacked,
+}
+
+impl Unpacker<'_> {
+    fn add_tar(&mut self, reader: impl Read) -> Result<()> {
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().map_err(malformed)? {
+            self.count()?;
+            let entry = entry.map_err(malformed)?;
+            let name = entry.path().map_err(malformed)?.to_string_lossy().into_owned();
+            let kind = entry.header().entry_type();
+            if kind.is_dir() {
+                continue;
+            }
+            // PAX and GNU long-name headers describe the entry that follows
+            if kind.is_pax_global_extensions() || kind.is_pax_local_extensions() || kind.is_gnu_longname() {
+                continue;
+            }
+            let size = entry.size();
+            self.add(name, kind.is_file(), size, entry)?;
+        }
+        Ok(())
+    }
+    
+    /// Count an entry against `max_total_entries`
+    fn count(&mut self) -> Result<()> {
+        self.entries += 1;
+        if self.entries > self.limits.max_total_entries {
+            return Err(AppError::Validation {
+                message: format!("Archive has more than {} entries", self.limits.max_total_entries),
+                field: None,
+            });
+        }
+        Ok(())
+    }
+    
+    fn add(&mut self, name: String, is_file: bool, size: u64, entry: impl Read) -> Result<()> {
+        if name.ends_with('/') {
+            return Ok(());
+        }
+        if !is_file {
+            return self.skip(name, "not a regular file");
+        }
+        if !name.to_ascii_lowercase().ends_with(".pdf") {
+            return self.skip(name, "not a PDF");
+        }
+        if size > self.limits.max_entry_bytes {
+            return self.skip(name, &too_large(self.limits.max_entry_bytes));
+        }
+        
+        // Declared sizes aren't trusted: read at most one byte past the limit
+        let mut body = Vec::with_capacity(size as usize);
+        entry
+            .take(self.limits.max_entry_bytes + 1)
+            .read_to_end(&mut body)
+            .map_err(malformed)?;
+        if body.len() as u64 > self.limits.max_entry_bytes {
+            return self.skip(name, &too_large(self.limits.max_entry_bytes));
+        }
+        if !body.starts_with(PDF_MAGIC) {
+            return self.skip(name, "not a PDF");
+        }
+        
+        if self.contents.documents.len() == self.limits.max_entries {
+            return Err(AppError::Validation {
+                message: format!("Archive has more than {} PDFs", self.limits.max_entries),
+                field: None,
+            });
+        }
+        self.unpacked_bytes += body.len() as u64;
+        if self.unpacked_bytes > self.limits.max_unpacked_bytes {
+            return Err(AppError::PayloadTooLarge {
+                size: self.unpacked_bytes as usize,
+                limit: self.limits.max_unpacked_bytes as usize,
+            });
+        }
+        
+        self.contents.documents.push(ArchiveEntry { name, body });
+        Ok(())
+    }
+    
+    fn skip(&mut self, name: String, reason: &str) -> Result<()> {
+        self.contents.skipped_count += 1;
+        if self.contents.skipped.len() < MAX_LISTED_SKIPPED {
+            self.contents.skipped.push(SkippedEntry { name, reason: reason.to_string() });
+        }
+        Ok(())
+    }
+}
+
+fn too_large(limit: u64) -> String {
+    format!("larger than {} bytes", limit)
+}
+
+fn malformed(e: impl std::fmt::Display) -> AppError {
+    AppError::Validation {
+        message: format!("Malformed archive: {}", e),
+        field: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    
+    const LIMITS: ArchiveLimits = ArchiveLimits {
+        max_total_entries: 200,
+        max_entries: 10,
+        max_entry_bytes: 1024,
+        max_unpacked_bytes: 4096,
+    };
+    
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, body) in entries {
+            if name.ends_with('/') {
+                writer.add_directory(*name, options).unwrap();
+            } else {
+                writer.start_file(*name, options).unwrap();
+                writer.write_all(body).unwrap();
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+    
+    fn tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, body) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(body.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *body).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+    
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(ArchiveFormat::detect(&zip(&[]), None), Some(ArchiveFormat::Zip));
+        assert_eq!(ArchiveFormat::detect(&tar(&[("a.pdf", b"%PDF-1.7")]), None), Some(ArchiveFormat::Tar));
+        assert_eq!(ArchiveFormat::detect(b"\x1f\x8b\x08", None), Some(ArchiveFormat::TarGz));
+        assert_eq!(ArchiveFormat::detect(b"old tar", Some("application/x-tar")), Some(ArchiveFormat::Tar));
+        assert_eq!(ArchiveFormat::detect(b"%PDF-1.7", Some("application/pdf")), None);
+    }
+    
+    #[test]
+    fn test_unpack_zip_skips_non_pdfs() {
+        let body = zip(&[
+            ("papers/", b""),
+            ("papers/a.pdf", b"%PDF-1.7 a"),
+            ("papers/notes.txt", b"notes"),
+            ("papers/fake.pdf", b"<html>"),
+            ("papers/b.PDF", b"%PDF-1.4 b"),
+        ]);
+        let unpacked = unpack(&body, ArchiveFormat::Zip, &LIMITS).unwrap();
+        
+        let names: Vec<_> = unpacked.documents.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["papers/a.pdf", "papers/b.PDF"]);
+        assert_eq!(unpacked.documents[0].body, b"%PDF-1.7 a");
+        let skipped: Vec<_> = unpacked.skipped.iter().map(|s| (s.name.as_str(), s.reason.as_str())).collect();
+        assert_eq!(skipped, [("papers/notes.txt", "not a PDF"), ("papers/fake.pdf", "not a PDF")]);
+    }
+    
+    #[test]
+    fn test_unpack_tar_gz() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(&tar(&[("a.pdf", b"%PDF-1.7 a")])).unwrap();
+        let body = encoder.finish().unwrap();
+        
+        let unpacked = unpack(&body, ArchiveFormat::TarGz, &LIMITS).unwrap();
+        assert_eq!(unpacked.documents.len(), 1);
+        assert_eq!(unpacked.documents[0].name, "a.pdf");
+    }
+    
+    #[test]
+    fn test_unpack_enforces_limits() {
+        let large = [b"%PDF".as_slice(), &[b'x'; 1024]].concat();
+        let unpacked = unpack(&tar(&[("large.pdf", &large), ("a.pdf", b"%PDF")]), ArchiveFormat::Tar, &LIMITS).unwrap();
+        assert_eq!(unpacked.documents.len(), 1);
+        assert_eq!(unpacked.skipped[0].reason, "larger than 1024 bytes");
+        
+        let many: Vec<_> = (0..11).map(|i| format!("{}.pdf", i)).collect();
+        let entries: Vec<_> = many.iter().map(|name| (name.as_str(), b"%PDF".as_slice())).collect();
+        let err = unpack(&zip(&entries), ArchiveFormat::Zip, &LIMITS).unwrap_err();
+        assert!(matches!(err, AppError::Validation { .. }));
+        
+        let entry = [b"%PDF".as_slice(), &[b'x'; 1000]].concat();
+        let entries: Vec<_> = many[..5].iter().map(|name| (name.as_str(), entry.as_slice())).collect();
+        let err = unpack(&zip(&entries), ArchiveFormat::Zip, &LIMITS).unwrap_err();
+        assert!(matches!(err, AppError::PayloadTooLarge { limit: 4096, .. }));
+    }
+    
+    #[test]
+    fn test_unpack_counts_every_entry() {
+        let names: Vec<_> = (0..150).map(|i| format!("{}.txt", i)).collect();
+        let mut entries: Vec<_> = names.iter().map(|name| (name.as_str(), b"notes".as_slice())).collect();
+        entries.push(("a.pdf", b"%PDF"));
+        
+        let unpacked = unpack(&tar(&entries), ArchiveFormat::Tar, &LIMITS).unwrap();
+        assert_eq!(unpacked.documents.len(), 1);
+        assert_eq!(unpacked.skipped.len(), MAX_LISTED_SKIPPED);
+        assert_eq!(unpacked.skipped_count, 150);
+        assert_eq!(unpacked.skipped[0].name, "0.txt");
+        
+        let limits = ArchiveLimits { max_total_entries: 100, ..LIMITS };
+        for (body, format) in [(tar(&entries), ArchiveFormat::Tar), (zip(&entries), ArchiveFormat::Zip)] {
+            let err = unpack(&body, format, &limits).unwrap_err();
+            assert!(matches!(err, AppError::Validation { ref message, .. } if message.contains("100 entries")));
+        }
+    }
+    
+    #[test]
+    fn test_unpack_malformed() {
+        let err = unpack(b"PK\x03\x04garbage", ArchiveFormat::Zip, &LIMITS).unwrap_err();
+        assert!(matches!(err, AppError::Validation { .. }));
+    }
+}
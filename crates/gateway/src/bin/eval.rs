@@ -0,0 +1,193 @@
+//! Search evaluation harness
+//!
+//! Runs judged queries through `POST /v2/search` in each retrieval mode and
+//! reports nDCG@k, MRR and recall@k, so chunking and scoring changes can be
+//! measured against a fixed set of judgments.
+//!
+//! ```text
+//! PAPERFORGE_API_KEY=... eval judgments.jsonl [--url URL] [--k 10] [--modes vector,bm25,hybrid] [--json]
+//! ```
+//!
+//! See [`paperforge_common::eval`] for the judgment format.
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use uuid::Uuid;
+
+use paperforge_common::eval::{parse_cases, CaseScore, EvalCase, EvalSummary, RankedHit};
+
+const DEFAULT_URL: &str = "http://localhost:8080";
+const DEFAULT_K: usize = 10;
+const DEFAULT_MODES: &[&str] = &["vector", "bm25", "hybrid"];
+
+struct Args {
+    path: String,
+    url: String,
+    k: usize,
+    modes: Vec<String>,
+    json: bool,
+}
+
+impl Args {
+    fn parse() -> anyhow::Result<Self> {
+        let mut args = std::env::args().skip(1);
+        let mut parsed = Args {
+            path: String::new(),
+            url: std::env::var("PAPERFORGE_URL").unwrap_or_else(|_| DEFAULT_URL.to_string()),
+            k: DEFAULT_K,
+            modes: DEFAULT_MODES.iter().map(|m| m.to_string()).collect(),
+            json: false,
+        };
+        
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--url" => parsed.url = args.next().context("--url needs a value")?,
+                "--k" => parsed.k = args.next().context("--k needs a value")?.parse()?,
+                "--modes" => {
+                    parsed.modes = args.next()
+                        .context("--modes needs a value")?
+                        .split(',')
+                        .map(|m| m.trim().to_string())
+                        .filter(|m| !m.is_empty())
+                        .collect();
+                }
+                "--json" => parsed.json = true,
+                _ if arg.starts_with("--") => bail!("Unknown option: {}", arg),
+                _ => parsed.path = arg,
+            }
+        }
+        
+        if parsed.path.is_empty() {
+            bail!("Usage: eval <judgments.jsonl> [--url URL] [--k N] [--modes vector,bm25,hybrid] [--json]");
+        }
+        if parsed.k == 0 {
+            bail!("--k must be at least 1");
+        }
+        Ok(parsed)
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    results: Vec<SearchHit>,
+}
+
+#[derive(Deserialize)]
+struct SearchHit {
+    chunk_id: Uuid,
+    paper_id: Uuid,
+}
+
+/// Metrics for one retrieval mode
+#[derive(Serialize)]
+struct ModeReport {
+    mode: String,
+    #[serde(flatten)]
+    summary: EvalSummary,
+    failed: usize,
+    mean_latency_ms: f64,
+}
+
+async fn search(
+    client: &reqwest::Client,
+    args: &Args,
+    api_key: &str,
+    mode: &str,
+    case: &EvalCase,
+) -> anyhow::Result<Vec<RankedHit>> {
+    let response = client
+        .post(format!("{}/v2/search", args.url.trim_end_matches('/')))
+        .bearer_auth(api_key)
+        .json(&serde_json::json!({
+            "query": case.query,
+            "options": { "mode": mode, "limit": args.k },
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<SearchResponse>()
+        .await?;
+    
+    Ok(response.results
+        .into_iter()
+        .map(|hit| RankedHit { chunk_id: hit.chunk_id, paper_id: hit.paper_id })
+        .collect())
+}
+
+async fn evaluate_mode(
+    client: &reqwest::Client,
+    args: &Args,
+    api_key: &str,
+    mode: &str,
+    cases: &[EvalCase],
+) -> ModeReport {
+    let mut scores = Vec::with_capacity(cases.len());
+    let mut failed = 0;
+    let mut latency_ms = 0.0;
+    
+    for case in cases {
+        let start = Instant::now();
+        match search(client, args, api_key, mode, case).await {
+            Ok(hits) => {
+                latency_ms += start.elapsed().as_secs_f64() * 1000.0;
+                scores.push(CaseScore::new(case, &hits, args.k));
+            }
+            Err(e) => {
+                eprintln!("[{}] search failed for {:?}: {}", mode, case.query, e);
+                failed += 1;
+            }
+        }
+    }
+    
+    ModeReport {
+        mode: mode.to_string(),
+        mean_latency_ms: if scores.is_empty() { 0.0 } else { latency_ms / scores.len() as f64 },
+        summary: EvalSummary::from_scores(&scores),
+        failed,
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse()?;
+    let api_key = std::env::var("PAPERFORGE_API_KEY").context("PAPERFORGE_API_KEY must be set")?;
+    
+    let input = std::fs::read_to_string(&args.path)
+        .with_context(|| format!("Failed to read {}", args.path))?;
+    let cases = parse_cases(&input)?;
+    if cases.is_empty() {
+        bail!("{} has no judgments", args.path);
+    }
+    
+    let client = reqwest::Client::new();
+    let mut reports = Vec::with_capacity(args.modes.len());
+    for mode in &args.modes {
+        reports.push(evaluate_mode(&client, &args, &api_key, mode, &cases).await);
+    }
+    
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+    
+    let k = args.k;
+    println!("{} queries, k = {}", cases.len(), k);
+    println!(
+        "{:<8} {:>8} {:>8} {:>8} {:>10} {:>8}",
+        "mode", format!("nDCG@{}", k), "MRR", format!("R@{}", k), "latency", "failed"
+    );
+    for report in &reports {
+        println!(
+            "{:<8} {:>8.4} {:>8.4} {:>8.4} {:>8.1}ms {:>8}",
+            report.mode,
+            report.summary.ndcg,
+            report.summary.mrr,
+            report.summary.recall,
+            report.mean_latency_ms,
+            report.failed
+        );
+    }
+    
+    Ok(())
+}
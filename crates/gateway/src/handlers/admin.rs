@@ -0,0 +1,265 @@
+//! Admin handlers (require the `admin` scope)
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::AppState;
+use paperforge_common::{
+    audit::AuditEvent,
+    auth::AuthContext,
+    db::{
+        models::{AuditLog, ReindexJob},
+        AuditLogFilter, Repository,
+    },
+    errors::{AppError, Result},
+};
+
+/// Audit log query parameters
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    /// Must be the caller's tenant if given
+    pub tenant_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Return entries older than this (for paging back)
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+fn default_limit() -> u64 { 100 }
+
+/// Audit log page
+#[derive(Serialize)]
+pub struct AuditResponse {
+    pub entries: Vec<AuditLog>,
+    /// Pass as `until` to fetch the next page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_until: Option<String>,
+}
+
+/// Request to move messages from the DLQ back to the ingestion queue
+#[derive(Debug, Deserialize)]
+pub struct RedriveRequest {
+    #[serde(default = "default_max_messages")]
+    pub max_messages: usize,
+}
+
+fn default_max_messages() -> usize { 100 }
+
+/// Redrive result
+#[derive(Serialize)]
+pub struct RedriveResponse {
+    pub redriven: usize,
+}
+
+/// Request to rebuild search indexes
+#[derive(Debug, Deserialize)]
+pub struct ReindexRequest {
+    /// Limit the text-search rebuild to one tenant; all tenants if omitted
+    pub tenant_id: Option<Uuid>,
+    /// Recompute chunk text-search vectors
+    #[serde(default = "default_true")]
+    pub text_search: bool,
+    /// Rebuild the vector indexes on chunks (shared by all tenants)
+    #[serde(default)]
+    pub vector_indexes: bool,
+}
+
+fn default_true() -> bool { true }
+
+/// Re-indexing job response
+#[derive(Serialize)]
+pub struct ReindexJobResponse {
+    pub job_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<Uuid>,
+    pub text_search: bool,
+    pub vector_indexes: bool,
+    pub status: String,
+    pub chunks_done: i64,
+    pub chunks_total: i64,
+    pub indexes_done: i32,
+    pub indexes_total: i32,
+    pub progress_percent: f64,
+    pub requested_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<ReindexJob> for ReindexJobResponse {
+    fn from(job: ReindexJob) -> Self {
+        Self {
+            progress_percent: job.progress_percent(),
+            job_id: job.id,
+            tenant_id: job.tenant_id,
+            text_search: job.text_search,
+            vector_indexes: job.vector_indexes,
+            status: job.status,
+            chunks_done: job.chunks_done,
+            chunks_total: job.chunks_total,
+            indexes_done: job.indexes_done,
+            indexes_total: job.indexes_total,
+            requested_by: job.requested_by,
+            error_message: job.error_message,
+            started_at: job.started_at.map(|dt| dt.to_rfc3339()),
+            completed_at: job.completed_at.map(|dt| dt.to_rfc3339()),
+            created_at: job.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Query the caller's tenant's audit log, newest first
+pub async fn list_audit_logs(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditResponse>> {
+    auth.require_scope("admin")?;
+    if query.tenant_id.is_some_and(|tenant_id| tenant_id != auth.tenant_id) {
+        return Err(AppError::TenantMismatch);
+    }
+    let repo = Repository::new(state.db.clone());
+    
+    let filter = AuditLogFilter {
+        tenant_id: Some(auth.tenant_id),
+        actor: query.actor,
+        action: query.action,
+        resource_type: query.resource_type,
+        resource_id: query.resource_id,
+        since: query.since,
+        until: query.until,
+    };
+    let limit = query.limit.clamp(1, 1000);
+    let entries = repo.list_audit_logs(&filter, limit).await?;
+    
+    let next_until = (entries.len() as u64 == limit)
+        .then(|| entries.last().map(|e| e.created_at.to_rfc3339()))
+        .flatten();
+    
+    Ok(Json(AuditResponse { entries, next_until }))
+}
+
+/// Move messages from the ingestion DLQ back to the main queue
+pub async fn redrive_dlq(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<RedriveRequest>,
+) -> Result<Json<RedriveResponse>> {
+    auth.require_scope("admin")?;
+    
+    let queue = state.queue.as_ref().ok_or_else(|| AppError::ServiceUnavailable {
+        message: "Ingestion queue is not configured".to_string(),
+    })?;
+    
+    let redriven = queue.redrive_all(request.max_messages.min(10_000)).await?;
+    
+    state.audit.record(
+        AuditEvent::new("dlq.redrive", "queue")
+            .by(&auth)
+            .resource_id("ingestion")
+            .after(&serde_json::json!({ "redriven": redriven })),
+    ).await;
+    
+    tracing::info!(
+        redriven,
+        tenant_id = %auth.tenant_id,
+        "DLQ redrive requested"
+    );
+    
+    Ok(Json(RedriveResponse { redriven }))
+}
+
+/// Start rebuilding text-search vectors and/or vector indexes
+///
+/// Needed after changing the text-search configuration or the chunking
+/// strategy. The rebuild is run by a gateway's [`ReindexWorker`], one job
+/// at a time; poll `GET /v2/admin/reindex/{id}` for progress.
+///
+/// [`ReindexWorker`]: paperforge_common::reindex::ReindexWorker
+pub async fn start_reindex(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<ReindexRequest>,
+) -> Result<(StatusCode, Json<ReindexJobResponse>)> {
+    auth.require_scope("admin")?;
+    
+    if !request.text_search && !request.vector_indexes {
+        return Err(AppError::Validation {
+            message: "Nothing to rebuild; enable text_search or vector_indexes".to_string(),
+            field: Some("text_search".to_string()),
+        });
+    }
+    
+    let repo = Repository::new(state.db.clone());
+    if let Some(tenant_id) = request.tenant_id {
+        repo.find_tenant_by_id(tenant_id).await?.ok_or_else(|| AppError::NotFound {
+            resource_type: "tenant".to_string(),
+            id: tenant_id.to_string(),
+        })?;
+    }
+    if let Some(active) = repo.find_active_reindex_job().await? {
+        return Err(AppError::Duplicate {
+            message: format!("Re-indexing job {} is already running", active.id),
+        });
+    }
+    
+    let job = repo.create_reindex_job(
+        request.tenant_id,
+        request.text_search,
+        request.vector_indexes,
+        &auth.actor(),
+    ).await?;
+    
+    state.audit.record(
+        AuditEvent::new("index.rebuild", "reindex_job")
+            .by(&auth)
+            .resource_id(job.id.to_string())
+            .after(&serde_json::json!({
+                "tenant_id": request.tenant_id,
+                "text_search": request.text_search,
+                "vector_indexes": request.vector_indexes,
+            })),
+    ).await;
+    
+    tracing::info!(
+        job_id = %job.id,
+        target_tenant_id = ?request.tenant_id,
+        text_search = request.text_search,
+        vector_indexes = request.vector_indexes,
+        "Re-indexing requested"
+    );
+    
+    Ok((StatusCode::ACCEPTED, Json(job.into())))
+}
+
+/// Get a re-indexing job
+pub async fn get_reindex(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<ReindexJobResponse>> {
+    auth.require_scope("admin")?;
+    
+    let job = Repository::new(state.db.clone())
+        .find_reindex_job(job_id)
+        .await?
+        .ok_or_else(|| AppError::JobNotFound {
+            id: job_id.to_string(),
+        })?;
+    
+    Ok(Json(job.into()))
+}
@@ -0,0 +1,141 @@
+//! Query analytics handlers
+//!
+//! What the tenant's users search for, and which of those searches come
+//! back empty.
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+use paperforge_common::{
+    analytics::zero_result_rate,
+    auth::AuthContext,
+    db::{Repository, TopQuery},
+    errors::{AppError, Result},
+};
+
+/// Most queries a single request returns
+const MAX_QUERIES: u64 = 100;
+
+/// Query analytics parameters
+#[derive(Debug, Deserialize)]
+pub struct QueryAnalyticsQuery {
+    /// Start of the period; defaults to 30 days ago
+    pub since: Option<DateTime<Utc>>,
+    /// Queries to return
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+fn default_limit() -> u64 { 20 }
+
+impl QueryAnalyticsQuery {
+    fn period_start(&self) -> DateTime<Utc> {
+        self.since.unwrap_or_else(|| Utc::now() - Duration::days(30))
+    }
+    
+    fn validate(&self) -> Result<()> {
+        if self.limit == 0 || self.limit > MAX_QUERIES {
+            return Err(AppError::Validation {
+                message: format!("limit must be between 1 and {}", MAX_QUERIES),
+                field: Some("limit".to_string()),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A query and how its searches went
+#[derive(Debug, Serialize)]
+pub struct QueryStats {
+    pub query: String,
+    pub searches: i64,
+    pub zero_result_searches: i64,
+    pub zero_result_rate: f64,
+    pub avg_latency_ms: f64,
+    pub last_searched_at: String,
+}
+
+impl From<TopQuery> for QueryStats {
+    fn from(query: TopQuery) -> Self {
+        Self {
+            zero_result_rate: zero_result_rate(query.searches, query.zero_result_searches),
+            query: query.query,
+            searches: query.searches,
+            zero_result_searches: query.zero_result_searches,
+            avg_latency_ms: query.avg_latency_ms,
+            last_searched_at: query.last_searched_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Most frequent queries
+#[derive(Debug, Serialize)]
+pub struct TopQueriesResponse {
+    pub period_start: String,
+    pub total_searches: i64,
+    pub queries: Vec<QueryStats>,
+}
+
+/// Searches that returned nothing
+#[derive(Debug, Serialize)]
+pub struct ZeroResultsResponse {
+    pub period_start: String,
+    pub total_searches: i64,
+    pub zero_result_searches: i64,
+    pub zero_result_rate: f64,
+    /// Most frequent queries with no results
+    pub queries: Vec<QueryStats>,
+}
+
+/// Get the tenant's most frequent queries
+///
+/// GET /v2/analytics/queries/top?since=...&limit=20
+pub async fn top_queries(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Query(query): Query<QueryAnalyticsQuery>,
+) -> Result<Json<TopQueriesResponse>> {
+    auth.require_scope("read")?;
+    query.validate()?;
+    let repo = Repository::new(state.db.clone());
+    let period_start = query.period_start();
+    
+    let totals = repo.query_totals(auth.tenant_id, period_start).await?;
+    let queries = repo.top_queries(auth.tenant_id, period_start, false, query.limit).await?;
+    
+    Ok(Json(TopQueriesResponse {
+        period_start: period_start.to_rfc3339(),
+        total_searches: totals.searches,
+        queries: queries.into_iter().map(QueryStats::from).collect(),
+    }))
+}
+
+/// Get the tenant's zero-result rate and most frequent zero-result queries
+///
+/// GET /v2/analytics/queries/zero-results?since=...&limit=20
+pub async fn zero_result_queries(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Query(query): Query<QueryAnalyticsQuery>,
+) -> Result<Json<ZeroResultsResponse>> {
+    auth.require_scope("read")?;
+    query.validate()?;
+    let repo = Repository::new(state.db.clone());
+    let period_start = query.period_start();
+    
+    let totals = repo.query_totals(auth.tenant_id, period_start).await?;
+    let queries = repo.top_queries(auth.tenant_id, period_start, true, query.limit).await?;
+    
+    Ok(Json(ZeroResultsResponse {
+        period_start: period_start.to_rfc3339(),
+        total_searches: totals.searches,
+        zero_result_searches: totals.zero_result_searches,
+        zero_result_rate: zero_result_rate(totals.searches, totals.zero_result_searches),
+        queries: queries.into_iter().map(QueryStats::from).collect(),
+    }))
+}
@@ -0,0 +1,228 @@
+//! Token issuance handlers
+
+use axum::{extract::State, Json};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+use paperforge_common::{
+    auth::{generate_refresh_token, hash_token},
+    db::{
+        models::{RefreshToken, Tenant},
+        Repository,
+    },
+    errors::{AppError, Result},
+};
+
+/// Request to exchange an API key for tokens
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    pub api_key: String,
+    
+    /// Scopes to grant; defaults to every scope of the API key
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+}
+
+/// Request to rotate a refresh token
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Issued tokens
+#[derive(Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_in: i64,
+    pub refresh_token: String,
+    pub scopes: Vec<String>,
+}
+
+/// Exchange an API key for a short-lived JWT and a refresh token
+pub async fn issue_token(
+    State(state): State<AppState>,
+    Json(request): Json<TokenRequest>,
+) -> Result<Json<TokenResponse>> {
+    let jwt = state.auth.jwt()?;
+    let tenant = state.auth.authenticate_api_key(&request.api_key).await?;
+    
+    let granted = tenant.api_key_scope_list();
+    let scopes = match request.scopes {
+        Some(requested) => {
+            if let Some(scope) = requested.iter().find(|s| !granted.contains(s)) {
+                return Err(AppError::Forbidden {
+                    message: format!("Scope not granted to API key: {}", scope),
+                });
+            }
+            requested
+        }
+        None => granted,
+    };
+    
+    let repo = Repository::new(state.db.clone());
+    let refresh_token = generate_refresh_token();
+    let expires_at = Utc::now() + Duration::seconds(state.config.load().auth.refresh_token_ttl_secs as i64);
+    repo.create_refresh_token(tenant.id, &hash_token(&refresh_token), &scopes, expires_at)
+        .await?;
+    
+    let access_token = jwt.generate_token(tenant.id, tenant.id, scopes.clone())?;
+    
+    tracing::info!(
+        tenant_id = %tenant.id,
+        scopes = ?scopes,
+        "Issued access token for API key"
+    );
+    
+    Ok(Json(TokenResponse {
+        access_token,
+        token_type: "Bearer",
+        expires_in: jwt.expiration_secs(),
+        refresh_token,
+        scopes,
+    }))
+}
+
+/// Rotate a refresh token and issue a new access token
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    Json(request): Json<RefreshRequest>,
+) -> Result<Json<TokenResponse>> {
+    let jwt = state.auth.jwt()?;
+    let repo = Repository::new(state.db.clone());
+    
+    let invalid = || AppError::Unauthorized {
+        message: "Invalid or expired refresh token".to_string(),
+    };
+    let presented = hash_token(&request.refresh_token);
+    
+    // The key the token was obtained with must still stand
+    let current = repo.find_refresh_token(&presented).await?.ok_or_else(invalid)?;
+    let tenant = repo
+        .find_tenant_by_id(current.tenant_id)
+        .await?
+        .ok_or_else(invalid)?;
+    check_issuing_key(&tenant, &current)?;
+    
+    let refresh_token = generate_refresh_token();
+    let expires_at = Utc::now() + Duration::seconds(state.config.load().auth.refresh_token_ttl_secs as i64);
+    let token = repo
+        .rotate_refresh_token(&presented, &hash_token(&refresh_token), expires_at)
+        .await?
+        .ok_or_else(invalid)?;
+    
+    let scopes = token.scope_list();
+    let access_token = jwt.generate_token(tenant.id, tenant.id, scopes.clone())?;
+    
+    tracing::info!(
+        tenant_id = %tenant.id,
+        family_id = %token.family_id,
+        "Refresh token rotated"
+    );
+    
+    Ok(Json(TokenResponse {
+        access_token,
+        token_type: "Bearer",
+        expires_in: jwt.expiration_secs(),
+        refresh_token,
+        scopes,
+    }))
+}
+
+/// Check that the API key a refresh token was obtained with still stands
+///
+/// Rotating a key revokes its refresh tokens, so a token older than the
+/// tenant's current key comes from a replaced key. Scopes the current key
+/// no longer grants aren't renewed either.
+fn check_issuing_key(tenant: &Tenant, token: &RefreshToken) -> Result<()> {
+    let reject = |message: &str| AppError::Unauthorized { message: message.to_string() };
+    if !tenant.is_active {
+        return Err(reject("Tenant is inactive"));
+    }
+    if tenant.is_api_key_expired() {
+        return Err(reject("API key has expired"));
+    }
+    if token.created_at < tenant.api_key_created_at {
+        return Err(reject("API key has been rotated"));
+    }
+    let granted = tenant.api_key_scope_list();
+    if let Some(scope) = token.scope_list().iter().find(|s| !granted.contains(s)) {
+        return Err(reject(&format!("Scope no longer granted to API key: {}", scope)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+    
+    fn tenant(scopes: &str) -> Tenant {
+        let now = Utc::now().fixed_offset();
+        Tenant {
+            id: Uuid::new_v4(),
+            name: "acme".to_string(),
+            api_key_hash: String::new(),
+            api_key_prefix: None,
+            api_key_scopes: scopes.to_string(),
+            api_key_created_at: now - Duration::hours(1),
+            api_key_expires_at: None,
+            api_key_last_used_at: None,
+            signing_secret: None,
+            rate_limit_rps: 100,
+            resolve_metadata: false,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+    
+    fn token(tenant: &Tenant, scopes: &str) -> RefreshToken {
+        let now = Utc::now().fixed_offset();
+        RefreshToken {
+            id: Uuid::new_v4(),
+            tenant_id: tenant.id,
+            family_id: Uuid::new_v4(),
+            token_hash: String::new(),
+            scopes: scopes.to_string(),
+            expires_at: now + Duration::days(1),
+            revoked_at: None,
+            replaced_by: None,
+            created_at: now,
+        }
+    }
+    
+    #[test]
+    fn test_refresh_requires_current_key() {
+        let tenant = tenant("read write");
+        assert!(check_issuing_key(&tenant, &token(&tenant, "read")).is_ok());
+        
+        let expired = Tenant {
+            api_key_expires_at: Some((Utc::now() - Duration::minutes(1)).fixed_offset()),
+            ..tenant.clone()
+        };
+        assert!(check_issuing_key(&expired, &token(&tenant, "read")).is_err());
+        
+        let inactive = Tenant { is_active: false, ..tenant.clone() };
+        assert!(check_issuing_key(&inactive, &token(&tenant, "read")).is_err());
+    }
+    
+    #[test]
+    fn test_refresh_rejects_tokens_of_rotated_key() {
+        let tenant = tenant("read write");
+        let issued = token(&tenant, "read write");
+        
+        let rotated = Tenant {
+            api_key_created_at: Utc::now().fixed_offset() + Duration::seconds(1),
+            ..tenant.clone()
+        };
+        let err = check_issuing_key(&rotated, &issued).unwrap_err();
+        assert!(err.to_string().contains("rotated"), "{}", err);
+        
+        // A key rotated to fewer scopes doesn't renew the dropped ones
+        let narrowed = Tenant { api_key_scopes: "read".to_string(), ..tenant.clone() };
+        assert!(check_issuing_key(&narrowed, &issued).is_err());
+        assert!(check_issuing_key(&narrowed, &token(&tenant, "read")).is_ok());
+    }
+}
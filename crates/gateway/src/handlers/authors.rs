@@ -0,0 +1,100 @@
+//! Author handlers
+//!
+//! Authors are created at ingestion from the paper's author metadata, one
+//! per ORCID iD or, without one, per normalized name. Searches can be
+//! narrowed to an author with `author:` in the query.
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::AppState;
+use paperforge_common::{
+    auth::AuthContext,
+    db::{models::Paper, Repository},
+    errors::{AppError, Result},
+};
+
+/// Paging through an author's papers
+#[derive(Debug, Deserialize)]
+pub struct AuthorPapersQuery {
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+    #[serde(default)]
+    pub offset: u64,
+}
+
+fn default_limit() -> u64 { 50 }
+
+/// An author
+#[derive(Serialize)]
+pub struct AuthorResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub orcid: Option<String>,
+}
+
+/// A paper by the author
+#[derive(Serialize)]
+pub struct AuthorPaper {
+    pub id: Uuid,
+    pub title: String,
+    pub published_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<Paper> for AuthorPaper {
+    fn from(paper: Paper) -> Self {
+        Self {
+            id: paper.id,
+            title: paper.title,
+            published_at: paper.published_at.map(|dt| dt.to_rfc3339()),
+            created_at: paper.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A page of an author's papers
+#[derive(Serialize)]
+pub struct AuthorPapersResponse {
+    pub author: AuthorResponse,
+    /// Live papers by the author
+    pub total: i64,
+    pub papers: Vec<AuthorPaper>,
+}
+
+/// List an author's papers, most recently published first
+pub async fn list_author_papers(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AuthorPapersQuery>,
+) -> Result<Json<AuthorPapersResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    let author = repo
+        .find_author(auth.tenant_id, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound {
+            resource_type: "author".to_string(),
+            id: id.to_string(),
+        })?;
+    
+    let total = repo.count_author_papers(id).await?;
+    let papers = repo
+        .author_papers(id, query.limit.clamp(1, 500), query.offset)
+        .await?;
+    
+    Ok(Json(AuthorPapersResponse {
+        author: AuthorResponse {
+            id: author.id,
+            name: author.name,
+            orcid: author.orcid,
+        },
+        total,
+        papers: papers.into_iter().map(Into::into).collect(),
+    }))
+}
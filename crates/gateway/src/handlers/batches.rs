@@ -0,0 +1,335 @@
+//! Archive upload and batch progress handlers
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::archive::{self, ArchiveFormat, ArchiveLimits};
+use crate::handlers::tenant::IngestionDefaults;
+use crate::AppState;
+use paperforge_common::{
+    audit::AuditEvent,
+    auth::AuthContext,
+    db::{
+        models::{IngestionBatch, IngestionJob, JobStatus, SkippedEntry},
+        Repository,
+    },
+    errors::{AppError, Result},
+    outbox::{OutboxPayload, INGESTION_QUEUE},
+    queue::{IngestionJobOptions, StoredDocumentMessage},
+    request_context::RequestContext,
+    storage::document_key,
+};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UploadArchiveQuery {
+    /// File name of the archive, kept with the batch
+    pub name: Option<String>,
+}
+
+/// Response after uploading an archive
+#[derive(Serialize)]
+pub struct UploadArchiveResponse {
+    pub batch_id: Uuid,
+    pub status: String,
+    pub document_count: usize,
+    /// One job per PDF, in archive order
+    pub job_ids: Vec<Uuid>,
+    /// Entries that were not ingested, with the reason; at most
+    /// [`MAX_LISTED_SKIPPED`](archive::MAX_LISTED_SKIPPED)
+    pub skipped: Vec<SkippedEntry>,
+    /// All entries that were not ingested, listed or not
+    pub skipped_count: usize,
+    pub poll_url: String,
+}
+
+/// Batch progress response
+#[derive(Serialize)]
+pub struct BatchResponse {
+    pub batch_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_name: Option<String>,
+    /// `processing` until every job is done, then `completed` or
+    /// `completed_with_errors`
+    pub status: String,
+    pub document_count: i32,
+    /// Number of jobs in each status
+    pub jobs_by_status: BTreeMap<String, usize>,
+    /// Share of jobs that are done, successfully or not
+    pub progress_percent: f64,
+    pub jobs: Vec<BatchJobResponse>,
+    pub skipped: Vec<SkippedEntry>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct BatchJobResponse {
+    pub job_id: Uuid,
+    /// Path of the document in the archive
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paper_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+/// Upload a zip or tar archive of PDFs and start one ingestion job per PDF
+///
+/// The archive is unpacked here and each PDF stored before the jobs are
+/// created, so document storage is required. Entries that aren't PDFs or
+/// are too large are skipped and listed in the response.
+pub async fn upload_archive(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    context: RequestContext,
+    Query(query): Query<UploadArchiveQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<UploadArchiveResponse>)> {
+    auth.require_scope("write")?;
+    let storage = state.storage.clone().ok_or_else(|| AppError::ServiceUnavailable {
+        message: "Archive upload requires document storage".to_string(),
+    })?;
+    
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    let format = ArchiveFormat::detect(&body, content_type).ok_or_else(|| AppError::Validation {
+        message: "Body is not a zip, tar or gzipped tar archive".to_string(),
+        field: None,
+    })?;
+    
+    let (limits, max_file_bytes) = {
+        let config = state.config.load();
+        let ingestion = &config.ingestion;
+        (
+            ArchiveLimits {
+                max_total_entries: ingestion.max_archive_total_entries,
+                max_entries: ingestion.max_archive_entries,
+                max_entry_bytes: ingestion.max_file_bytes,
+                max_unpacked_bytes: ingestion.max_archive_unpacked_bytes,
+            },
+            ingestion.max_file_bytes,
+        )
+    };
+    let unpacked = tokio::task::spawn_blocking(move || archive::unpack(&body, format, &limits))
+        .await
+        .map_err(|e| AppError::Internal {
+            message: format!("Archive unpacking failed: {}", e),
+        })??;
+    if unpacked.documents.is_empty() {
+        return Err(AppError::Validation {
+            message: format!(
+                "Archive has no PDFs of at most {} bytes ({} entries skipped)",
+                max_file_bytes,
+                unpacked.skipped_count
+            ),
+            field: None,
+        });
+    }
+    
+    // Documents are stored under the batch, before its jobs exist
+    let batch_id = Uuid::new_v4();
+    let mut documents = Vec::with_capacity(unpacked.documents.len());
+    for (position, entry) in unpacked.documents.into_iter().enumerate() {
+        let key = document_key(auth.tenant_id, batch_id, &format!("{:04}.pdf", position));
+        storage.put(&key, entry.body, "application/pdf").await?;
+        documents.push((key, entry.name));
+    }
+    
+    let defaults = IngestionDefaults::load(&state, auth.tenant_id).await?;
+    let options = IngestionJobOptions {
+        embedding_model: defaults.embedding_model,
+        chunk_strategy: defaults.chunk_strategy,
+        chunk_size: defaults.chunk_size,
+        chunk_overlap: defaults.chunk_overlap,
+    };
+    
+    let repo = Repository::new(state.db.clone());
+    let (batch, jobs) = repo.create_batch_with_jobs(
+        IngestionBatch {
+            id: batch_id,
+            tenant_id: auth.tenant_id,
+            archive_name: query.name,
+            document_count: documents.len() as i32,
+            skipped: serde_json::json!(unpacked.skipped),
+            created_at: chrono::Utc::now().fixed_offset(),
+        },
+        INGESTION_QUEUE,
+        |position, job| {
+            let (source_key, file_name) = &documents[position];
+            OutboxPayload::new(&StoredDocumentMessage {
+                job_id: job.id,
+                tenant_id: auth.tenant_id,
+                batch_id: Some(batch_id),
+                source_key: source_key.clone(),
+                file_name: file_name.clone(),
+                options: options.clone(),
+            })
+            .with_traceparent(context.traceparent.as_deref())
+        },
+    ).await?;
+    
+    state.audit.record(
+        AuditEvent::new("batch.create", "ingestion_batch")
+            .by(&auth)
+            .resource_id(batch.id)
+            .after(&batch),
+    ).await;
+    
+    tracing::info!(
+        batch_id = %batch.id,
+        tenant_id = %auth.tenant_id,
+        documents = jobs.len(),
+        skipped = unpacked.skipped_count,
+        "Archive ingestion batch created"
+    );
+    
+    Ok((StatusCode::ACCEPTED, Json(UploadArchiveResponse {
+        batch_id: batch.id,
+        status: "pending".to_string(),
+        document_count: jobs.len(),
+        job_ids: jobs.iter().map(|job| job.id).collect(),
+        skipped: unpacked.skipped,
+        skipped_count: unpacked.skipped_count,
+        poll_url: format!("/v2/batches/{}", batch.id),
+    })))
+}
+
+/// Get the progress of an upload batch
+pub async fn get_batch(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<BatchResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    
+    let batch = repo.find_batch(batch_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound {
+            resource_type: "batch".to_string(),
+            id: batch_id.to_string(),
+        })?;
+    
+    if batch.tenant_id != auth.tenant_id {
+        return Err(AppError::TenantMismatch);
+    }
+    
+    let jobs = repo.find_batch_jobs(batch_id).await?;
+    Ok(Json(batch_response(batch, jobs)))
+}
+
+fn batch_response(batch: IngestionBatch, jobs: Vec<IngestionJob>) -> BatchResponse {
+    let mut jobs_by_status = BTreeMap::new();
+    for job in &jobs {
+        *jobs_by_status.entry(job.status.clone()).or_insert(0) += 1;
+    }
+    
+    let done = jobs.iter().filter(|job| job.is_terminal()).count();
+    let status = if done < jobs.len() {
+        "processing"
+    } else if jobs.iter().all(|job| job.job_status() == JobStatus::Completed) {
+        "completed"
+    } else {
+        "completed_with_errors"
+    };
+    let progress_percent = if jobs.is_empty() {
+        0.0
+    } else {
+        done as f64 / jobs.len() as f64 * 100.0
+    };
+    
+    BatchResponse {
+        batch_id: batch.id,
+        status: status.to_string(),
+        document_count: batch.document_count,
+        jobs_by_status,
+        progress_percent,
+        skipped: batch.skipped_entries(),
+        archive_name: batch.archive_name,
+        jobs: jobs
+            .into_iter()
+            .map(|job| BatchJobResponse {
+                job_id: job.id,
+                file_name: job.queue_message
+                    .as_ref()
+                    .and_then(|message| message.get("file_name"))
+                    .and_then(|name| name.as_str())
+                    .map(str::to_string),
+                status: job.status,
+                paper_id: job.paper_id,
+                error_message: job.error_message,
+            })
+            .collect(),
+        created_at: batch.created_at.to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    
+    fn job(status: &str, next_retry_at: Option<chrono::DateTime<Utc>>) -> IngestionJob {
+        IngestionJob {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            paper_id: None,
+            batch_id: None,
+            status: status.to_string(),
+            stage: status.to_string(),
+            chunks_total: 0,
+            chunks_processed: 0,
+            error_message: None,
+            failed_chunks: serde_json::json!([]),
+            idempotency_key: None,
+            attempt_count: 1,
+            next_retry_at: next_retry_at.map(|dt| dt.fixed_offset()),
+            queue_message: Some(serde_json::json!({ "file_name": format!("{}.pdf", status) })),
+            queue_attributes: None,
+            created_at: Utc::now().fixed_offset(),
+            started_at: None,
+            completed_at: None,
+        }
+    }
+    
+    fn batch(document_count: i32) -> IngestionBatch {
+        IngestionBatch {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            archive_name: Some("papers.zip".to_string()),
+            document_count,
+            skipped: serde_json::json!([{ "name": "notes.txt", "reason": "not a PDF" }]),
+            created_at: Utc::now().fixed_offset(),
+        }
+    }
+    
+    #[test]
+    fn test_batch_progress() {
+        let retrying = Some(Utc::now() + Duration::minutes(1));
+        let response = batch_response(batch(3), vec![
+            job("completed", None),
+            job("failed", retrying),
+            job("embedding", None),
+        ]);
+        assert_eq!(response.status, "processing");
+        assert!((response.progress_percent - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(response.jobs_by_status["failed"], 1);
+        assert_eq!(response.jobs[0].file_name.as_deref(), Some("completed.pdf"));
+        assert_eq!(response.skipped[0].reason, "not a PDF");
+        
+        let response = batch_response(batch(2), vec![job("completed", None), job("dead", None)]);
+        assert_eq!(response.status, "completed_with_errors");
+        assert_eq!(response.progress_percent, 100.0);
+        
+        let response = batch_response(batch(1), vec![job("completed", None)]);
+        assert_eq!(response.status, "completed");
+    }
+}
@@ -0,0 +1,88 @@
+//! Chunk handlers
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::Serialize;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::AppState;
+use paperforge_common::{
+    auth::AuthContext,
+    db::{models::Provenance, Repository},
+    errors::{AppError, Result},
+};
+
+/// A chunk and where it is in its source PDF
+#[derive(Serialize)]
+pub struct ChunkResponse {
+    pub chunk_id: Uuid,
+    pub paper_id: Uuid,
+    pub paper_title: String,
+    pub content: String,
+    pub chunk_index: i32,
+    /// `text`, `table`, `figure_caption` or `equation`
+    pub chunk_type: String,
+    /// Pages and character offsets; absent for chunks not from a PDF
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Link to the source PDF that opens at the chunk's page, when the
+    /// original document is stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// When `source_url` stops working
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+/// Get a chunk with its provenance
+///
+/// `source_url` carries a `#page=` fragment, which PDF viewers open at.
+pub async fn get_chunk(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(chunk_id): Path<Uuid>,
+) -> Result<Json<ChunkResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    
+    // Scoped to the tenant's live papers
+    let chunk = repo
+        .chunk_results_by_ids(auth.tenant_id, &[chunk_id])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound {
+            resource_type: "chunk".to_string(),
+            id: chunk_id.to_string(),
+        })?;
+    
+    let mut source_url = None;
+    let mut expires_at = None;
+    if let (Some(storage), Some(provenance)) = (&state.storage, &chunk.provenance) {
+        let source_key = repo
+            .find_paper_by_id(chunk.paper_id)
+            .await?
+            .and_then(|paper| paper.source_key);
+        if let Some(source_key) = source_key {
+            let ttl = Duration::from_secs(state.config.load().storage.presign_ttl_secs);
+            let url = storage.presign(&source_key, ttl).await?;
+            source_url = Some(format!("{}#page={}", url, provenance.page));
+            expires_at = Some((chrono::Utc::now() + ttl).to_rfc3339());
+        }
+    }
+    
+    Ok(Json(ChunkResponse {
+        chunk_id: chunk.chunk_id,
+        paper_id: chunk.paper_id,
+        paper_title: chunk.paper_title,
+        content: chunk.content,
+        chunk_index: chunk.chunk_index,
+        chunk_type: chunk.chunk_type,
+        provenance: chunk.provenance,
+        source_url,
+        expires_at,
+    }))
+}
@@ -1,16 +1,19 @@
 //! Citation graph handlers
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 use crate::AppState;
 use paperforge_common::{
     auth::AuthContext,
-    db::Repository,
+    db::{CitationRelation, Repository},
     errors::{AppError, Result},
 };
 
@@ -33,6 +36,7 @@ pub struct CitationGraph {
 pub struct CitationLink {
     pub paper_id: Uuid,
     pub paper_title: String,
+    /// Sentences of the citing paper that cite it, one per line
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context: Option<String>,
 }
@@ -94,12 +98,153 @@ pub struct GraphEdge {
     pub target: Uuid,
 }
 
+/// Related papers query
+#[derive(Debug, Deserialize)]
+pub struct RelatedPapersQuery {
+    #[serde(default)]
+    pub method: CitationRelation,
+    #[serde(default = "default_related_limit")]
+    pub limit: u64,
+}
+
+fn default_related_limit() -> u64 { 10 }
+
+/// Related papers response
+#[derive(Serialize)]
+pub struct RelatedPapersResponse {
+    pub paper_id: Uuid,
+    pub method: CitationRelation,
+    pub related: Vec<RelatedPaperLink>,
+}
+
+#[derive(Serialize)]
+pub struct RelatedPaperLink {
+    pub paper_id: Uuid,
+    pub paper_title: String,
+    pub shared: i64,
+    pub score: f64,
+}
+
+/// Most papers in an exported graph
+const MAX_EXPORT_NODES: usize = 1000;
+
+/// Deepest graph export, in hops from the root
+const MAX_EXPORT_DEPTH: usize = 3;
+
+/// Graph export format
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// JSON node-link (D3)
+    #[default]
+    Json,
+    /// GraphML (Gephi, Cytoscape, yEd)
+    Graphml,
+}
+
+/// Graph export query
+#[derive(Debug, Deserialize)]
+pub struct ExportGraphQuery {
+    pub root: Uuid,
+    #[serde(default = "default_hops")]
+    pub depth: usize,
+    #[serde(default)]
+    pub format: GraphFormat,
+}
+
+/// Exported citation subgraph, in JSON node-link form
+#[derive(Serialize)]
+pub struct GraphExport {
+    pub root: Uuid,
+    pub depth: usize,
+    /// Whether papers were left out to stay within the node limit
+    pub truncated: bool,
+    pub nodes: Vec<ExportNode>,
+    pub links: Vec<ExportLink>,
+}
+
+#[derive(Serialize)]
+pub struct ExportNode {
+    pub id: Uuid,
+    pub title: String,
+    /// Hops from the root
+    pub depth: usize,
+    pub pagerank: Option<f64>,
+}
+
+/// A citation from `source` to `target`
+#[derive(Serialize)]
+pub struct ExportLink {
+    pub source: Uuid,
+    pub target: Uuid,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+impl GraphExport {
+    /// Render as GraphML
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+            "  <key id=\"depth\" for=\"node\" attr.name=\"depth\" attr.type=\"int\"/>\n",
+            "  <key id=\"pagerank\" for=\"node\" attr.name=\"pagerank\" attr.type=\"double\"/>\n",
+            "  <key id=\"type\" for=\"edge\" attr.name=\"type\" attr.type=\"string\"/>\n",
+            "  <key id=\"context\" for=\"edge\" attr.name=\"context\" attr.type=\"string\"/>\n",
+            "  <graph id=\"citations\" edgedefault=\"directed\">\n",
+        ));
+        
+        for node in &self.nodes {
+            xml.push_str(&format!("    <node id=\"{}\">\n", node.id));
+            xml.push_str(&format!("      <data key=\"title\">{}</data>\n", xml_escape(&node.title)));
+            xml.push_str(&format!("      <data key=\"depth\">{}</data>\n", node.depth));
+            if let Some(pagerank) = node.pagerank {
+                xml.push_str(&format!("      <data key=\"pagerank\">{}</data>\n", pagerank));
+            }
+            xml.push_str("    </node>\n");
+        }
+        for link in &self.links {
+            xml.push_str(&format!("    <edge source=\"{}\" target=\"{}\">\n", link.source, link.target));
+            xml.push_str(&format!("      <data key=\"type\">{}</data>\n", link.kind));
+            if let Some(context) = &link.context {
+                xml.push_str(&format!("      <data key=\"context\">{}</data>\n", xml_escape(context)));
+            }
+            xml.push_str("    </edge>\n");
+        }
+        
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+/// Escape text for XML element content
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace aren't allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 /// Get citations for a paper
 pub async fn get_citations(
     State(state): State<AppState>,
     auth: AuthContext,
     Path(paper_id): Path<Uuid>,
 ) -> Result<Json<CitationResponse>> {
+    auth.require_scope("read")?;
     let repo = Repository::new(state.db.clone());
     
     // Get paper details
@@ -114,14 +259,24 @@ pub async fn get_citations(
         return Err(AppError::TenantMismatch);
     }
     
-    // Get citations
-    let (outgoing, incoming) = repo.get_citations(paper_id).await?;
+    // Get citations, outgoing ones in the order the paper cites them
+    let (mut outgoing, incoming) = repo.get_citations(paper_id).await?;
+    outgoing.sort_by_key(|c| c.position_in_paper.unwrap_or(i32::MAX));
+    
+    let linked: Vec<Uuid> = outgoing.iter().map(|c| c.cited_paper_id)
+        .chain(incoming.iter().map(|c| c.citing_paper_id))
+        .collect();
+    let titles: HashMap<Uuid, String> = repo.find_papers_by_ids(auth.tenant_id, &linked)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.title))
+        .collect();
+    let title = |id: &Uuid| titles.get(id).cloned().unwrap_or_else(|| "Unknown".to_string());
     
-    // Convert to response format (would need to join with papers table for titles)
     let outgoing_links: Vec<CitationLink> = outgoing.iter().map(|c| {
         CitationLink {
             paper_id: c.cited_paper_id,
-            paper_title: "Unknown".to_string(), // TODO: Join with papers
+            paper_title: title(&c.cited_paper_id),
             context: c.citation_context.clone(),
         }
     }).collect();
@@ -129,7 +284,7 @@ pub async fn get_citations(
     let incoming_links: Vec<CitationLink> = incoming.iter().map(|c| {
         CitationLink {
             paper_id: c.citing_paper_id,
-            paper_title: "Unknown".to_string(), // TODO: Join with papers
+            paper_title: title(&c.citing_paper_id),
             context: c.citation_context.clone(),
         }
     }).collect();
@@ -148,12 +303,49 @@ pub async fn get_citations(
     }))
 }
 
+/// Papers related to a paper through the citation graph
+///
+/// `method=cocitation` (default) finds papers often cited together with it;
+/// `method=coupling` finds papers citing the same work.
+pub async fn related_papers(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(paper_id): Path<Uuid>,
+    Query(query): Query<RelatedPapersQuery>,
+) -> Result<Json<RelatedPapersResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    
+    let paper = repo.find_paper_by_id(paper_id)
+        .await?
+        .filter(|paper| paper.tenant_id == auth.tenant_id && paper.deleted_at.is_none())
+        .ok_or_else(|| AppError::PaperNotFound {
+            id: paper_id.to_string(),
+        })?;
+    
+    let related = repo
+        .related_papers(auth.tenant_id, paper.id, query.method, query.limit.clamp(1, 100))
+        .await?;
+    
+    Ok(Json(RelatedPapersResponse {
+        paper_id: paper.id,
+        method: query.method,
+        related: related.into_iter().map(|r| RelatedPaperLink {
+            paper_id: r.paper_id,
+            paper_title: r.title,
+            shared: r.shared,
+            score: r.score,
+        }).collect(),
+    }))
+}
+
 /// Traverse citation graph from seed papers
 pub async fn traverse_citations(
     State(state): State<AppState>,
     auth: AuthContext,
     Json(request): Json<TraverseCitationsRequest>,
 ) -> Result<Json<TraverseCitationsResponse>> {
+    auth.require_scope("read")?;
     let repo = Repository::new(state.db.clone());
     
     if request.seed_papers.is_empty() {
@@ -265,3 +457,126 @@ pub async fn traverse_citations(
         graph: GraphData { nodes, edges },
     }))
 }
+
+/// Export the citation subgraph around a paper
+///
+/// Follows citations in both directions up to `depth` hops (at most 3) and
+/// returns JSON node-link data, or GraphML with `format=graphml`.
+pub async fn export_graph(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Query(query): Query<ExportGraphQuery>,
+) -> Result<Response> {
+    auth.require_scope("read")?;
+    if query.depth == 0 || query.depth > MAX_EXPORT_DEPTH {
+        return Err(AppError::Validation {
+            message: format!("depth must be between 1 and {}", MAX_EXPORT_DEPTH),
+            field: Some("depth".to_string()),
+        });
+    }
+    
+    let repo = Repository::new(state.db.clone());
+    repo.find_paper_by_id(query.root)
+        .await?
+        .filter(|paper| paper.tenant_id == auth.tenant_id && paper.deleted_at.is_none())
+        .ok_or_else(|| AppError::PaperNotFound {
+            id: query.root.to_string(),
+        })?;
+    
+    // Breadth-first, so every paper gets its shortest distance from the root
+    let mut depths = HashMap::from([(query.root, 0)]);
+    let mut seen_links = HashSet::new();
+    let mut links = Vec::new();
+    let mut truncated = false;
+    let mut frontier = vec![query.root];
+    for hop in 1..=query.depth {
+        let mut next = Vec::new();
+        for edge in repo.citation_edges(auth.tenant_id, &frontier).await? {
+            for id in [edge.citing_paper_id, edge.cited_paper_id] {
+                if depths.contains_key(&id) {
+                    continue;
+                }
+                if depths.len() >= MAX_EXPORT_NODES {
+                    truncated = true;
+                    continue;
+                }
+                depths.insert(id, hop);
+                next.push(id);
+            }
+            
+            let endpoints = (edge.citing_paper_id, edge.cited_paper_id);
+            if depths.contains_key(&endpoints.0)
+                && depths.contains_key(&endpoints.1)
+                && seen_links.insert(endpoints)
+            {
+                links.push(ExportLink {
+                    source: edge.citing_paper_id,
+                    target: edge.cited_paper_id,
+                    kind: "cites",
+                    context: edge.citation_context,
+                });
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    
+    let ids: Vec<Uuid> = depths.keys().copied().collect();
+    let mut nodes: Vec<ExportNode> = repo.find_papers_by_ids(auth.tenant_id, &ids)
+        .await?
+        .into_iter()
+        .map(|paper| ExportNode {
+            id: paper.id,
+            depth: depths[&paper.id],
+            title: paper.title,
+            pagerank: paper.pagerank,
+        })
+        .collect();
+    nodes.sort_by_key(|node| (node.depth, node.id));
+    
+    let graph = GraphExport {
+        root: query.root,
+        depth: query.depth,
+        truncated,
+        nodes,
+        links,
+    };
+    
+    Ok(match query.format {
+        GraphFormat::Json => Json(graph).into_response(),
+        GraphFormat::Graphml => (
+            [(header::CONTENT_TYPE, "application/graphml+xml")],
+            graph.to_graphml(),
+        ).into_response(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_graphml_export() {
+        let root = Uuid::from_u128(1);
+        let cited = Uuid::from_u128(2);
+        let graph = GraphExport {
+            root,
+            depth: 1,
+            truncated: false,
+            nodes: vec![
+                ExportNode { id: root, title: "Attention & <Transformers>".to_string(), depth: 0, pagerank: Some(1.5) },
+                ExportNode { id: cited, title: "Seq2Seq".to_string(), depth: 1, pagerank: None },
+            ],
+            links: vec![ExportLink { source: root, target: cited, kind: "cites", context: None }],
+        };
+        
+        let xml = graph.to_graphml();
+        assert!(xml.contains("<data key=\"title\">Attention &amp; &lt;Transformers&gt;</data>"));
+        assert!(xml.contains("<data key=\"pagerank\">1.5</data>"));
+        assert_eq!(xml.matches("<data key=\"pagerank\">").count(), 1);
+        assert!(xml.contains(&format!("<edge source=\"{}\" target=\"{}\">", root, cited)));
+        assert!(xml.ends_with("</graphml>\n"));
+    }
+}
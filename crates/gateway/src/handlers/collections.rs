@@ -0,0 +1,317 @@
+//! Collection handlers
+//!
+//! Collections are named groupings of a tenant's papers. Searches can be
+//! scoped to one with `options.filters.collection_id`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::handlers::{papers::invalidate_search, tenant::nullable};
+use crate::AppState;
+use paperforge_common::{
+    auth::AuthContext,
+    db::{models::Collection, CollectionSummary, Repository},
+    errors::{AppError, Result},
+};
+
+/// Most papers added in one request
+const MAX_PAPERS_PER_REQUEST: usize = 500;
+
+/// Create collection request
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateCollectionRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub name: String,
+    
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+}
+
+/// Update collection request; `null` clears the description
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCollectionRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub name: Option<String>,
+    
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(length(max = 2000))]
+    pub description: Option<Option<String>>,
+}
+
+/// Papers to add to a collection
+#[derive(Debug, Deserialize)]
+pub struct AddPapersRequest {
+    pub paper_ids: Vec<Uuid>,
+}
+
+/// Paging through a collection's papers
+#[derive(Debug, Deserialize)]
+pub struct CollectionPapersQuery {
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+    #[serde(default)]
+    pub offset: u64,
+}
+
+fn default_limit() -> u64 { 100 }
+
+/// A collection
+#[derive(Serialize)]
+pub struct CollectionResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Live papers in the collection
+    pub paper_count: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<CollectionSummary> for CollectionResponse {
+    fn from(summary: CollectionSummary) -> Self {
+        Self {
+            id: summary.id,
+            name: summary.name,
+            description: summary.description,
+            paper_count: summary.paper_count,
+            created_at: summary.created_at.to_rfc3339(),
+            updated_at: summary.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+impl CollectionResponse {
+    fn new(collection: Collection, paper_count: i64) -> Self {
+        Self {
+            id: collection.id,
+            name: collection.name,
+            description: collection.description,
+            paper_count,
+            created_at: collection.created_at.to_rfc3339(),
+            updated_at: collection.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Collections list
+#[derive(Serialize)]
+pub struct CollectionListResponse {
+    pub collections: Vec<CollectionResponse>,
+}
+
+/// A page of a collection's papers
+#[derive(Serialize)]
+pub struct CollectionPapersResponse {
+    pub collection_id: Uuid,
+    pub paper_ids: Vec<Uuid>,
+}
+
+/// Result of adding papers
+#[derive(Serialize)]
+pub struct AddPapersResponse {
+    /// Papers that weren't in the collection before
+    pub added: u64,
+}
+
+/// The tenant's collection `id`, or `NotFound`
+async fn load_collection(repo: &Repository, auth: &AuthContext, id: Uuid) -> Result<Collection> {
+    repo.find_collection(auth.tenant_id, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound {
+            resource_type: "collection".to_string(),
+            id: id.to_string(),
+        })
+}
+
+/// Reject a name another of the tenant's collections already has
+async fn ensure_name_free(repo: &Repository, auth: &AuthContext, name: &str) -> Result<()> {
+    if repo.find_collection_by_name(auth.tenant_id, name).await?.is_some() {
+        return Err(AppError::Duplicate {
+            message: format!("A collection named '{}' already exists", name),
+        });
+    }
+    Ok(())
+}
+
+/// Create a collection
+pub async fn create_collection(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<CreateCollectionRequest>,
+) -> Result<(StatusCode, Json<CollectionResponse>)> {
+    auth.require_scope("write")?;
+    request.validate()?;
+    let repo = Repository::new(state.db.clone());
+    ensure_name_free(&repo, &auth, &request.name).await?;
+    
+    let collection = repo
+        .create_collection(auth.tenant_id, &request.name, request.description)
+        .await?;
+    
+    tracing::info!(
+        collection_id = %collection.id,
+        tenant_id = %auth.tenant_id,
+        "Collection created"
+    );
+    
+    Ok((StatusCode::CREATED, Json(CollectionResponse::new(collection, 0))))
+}
+
+/// List the tenant's collections
+pub async fn list_collections(
+    State(state): State<AppState>,
+    auth: AuthContext,
+) -> Result<Json<CollectionListResponse>> {
+    auth.require_scope("read")?;
+    let collections = Repository::new(state.db.clone())
+        .list_collections(auth.tenant_id)
+        .await?;
+    
+    Ok(Json(CollectionListResponse {
+        collections: collections.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Get a collection
+pub async fn get_collection(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CollectionResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    let collection = load_collection(&repo, &auth, id).await?;
+    let count = repo.count_collection_papers(id).await?;
+    
+    Ok(Json(CollectionResponse::new(collection, count)))
+}
+
+/// Rename a collection or change its description
+pub async fn update_collection(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateCollectionRequest>,
+) -> Result<Json<CollectionResponse>> {
+    auth.require_scope("write")?;
+    request.validate()?;
+    let repo = Repository::new(state.db.clone());
+    let collection = load_collection(&repo, &auth, id).await?;
+    if let Some(name) = request.name.as_deref().filter(|name| *name != collection.name) {
+        ensure_name_free(&repo, &auth, name).await?;
+    }
+    
+    let collection = repo
+        .update_collection(collection, request.name, request.description)
+        .await?;
+    let count = repo.count_collection_papers(id).await?;
+    
+    Ok(Json(CollectionResponse::new(collection, count)))
+}
+
+/// Delete a collection; its papers are kept
+pub async fn delete_collection(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    auth.require_scope("write")?;
+    let deleted = Repository::new(state.db.clone())
+        .delete_collection(auth.tenant_id, id)
+        .await?;
+    if !deleted {
+        return Err(AppError::NotFound {
+            resource_type: "collection".to_string(),
+            id: id.to_string(),
+        });
+    }
+    // Searches scoped to the collection are cached too
+    invalidate_search(&state, auth.tenant_id).await;
+    
+    tracing::info!(collection_id = %id, tenant_id = %auth.tenant_id, "Collection deleted");
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List a collection's papers, most recently added first
+pub async fn list_collection_papers(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<Uuid>,
+    Query(query): Query<CollectionPapersQuery>,
+) -> Result<Json<CollectionPapersResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    load_collection(&repo, &auth, id).await?;
+    
+    let paper_ids = repo
+        .collection_paper_ids(id, query.limit.clamp(1, 1000), query.offset)
+        .await?;
+    
+    Ok(Json(CollectionPapersResponse {
+        collection_id: id,
+        paper_ids,
+    }))
+}
+
+/// Add papers to a collection
+///
+/// Every paper must be a live paper of the tenant; papers already in the
+/// collection are left as they are.
+pub async fn add_collection_papers(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AddPapersRequest>,
+) -> Result<Json<AddPapersResponse>> {
+    auth.require_scope("write")?;
+    if request.paper_ids.is_empty() || request.paper_ids.len() > MAX_PAPERS_PER_REQUEST {
+        return Err(AppError::Validation {
+            message: format!("paper_ids must hold 1 to {} papers", MAX_PAPERS_PER_REQUEST),
+            field: Some("paper_ids".to_string()),
+        });
+    }
+    
+    let repo = Repository::new(state.db.clone());
+    load_collection(&repo, &auth, id).await?;
+    
+    let found = repo.tenant_paper_ids(auth.tenant_id, &request.paper_ids).await?;
+    if let Some(missing) = request.paper_ids.iter().find(|paper_id| !found.contains(paper_id)) {
+        return Err(AppError::PaperNotFound {
+            id: missing.to_string(),
+        });
+    }
+    
+    let added = repo.add_collection_papers(id, &found).await?;
+    if added > 0 {
+        invalidate_search(&state, auth.tenant_id).await;
+    }
+    
+    Ok(Json(AddPapersResponse { added }))
+}
+
+/// Remove a paper from a collection
+pub async fn remove_collection_paper(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path((id, paper_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode> {
+    auth.require_scope("write")?;
+    let repo = Repository::new(state.db.clone());
+    load_collection(&repo, &auth, id).await?;
+    
+    if !repo.remove_collection_paper(id, paper_id).await? {
+        return Err(AppError::PaperNotFound {
+            id: paper_id.to_string(),
+        });
+    }
+    invalidate_search(&state, auth.tenant_id).await;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,151 @@
+//! Embedding handlers
+//!
+//! Embeds caller-supplied text with the configured model, for clients that
+//! compare their own text against exported chunk vectors. Inputs are
+//! preprocessed like stored chunks (`input_type: "passage"`, the default) or
+//! like search queries (`"query"`). Calls are metered and budget-checked like
+//! any other embedding.
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+use paperforge_common::{
+    auth::AuthContext,
+    embeddings::InputKind,
+    errors::{AppError, Result},
+};
+
+/// Longest input accepted, in characters (about 8k tokens)
+const MAX_INPUT_CHARS: usize = 32_000;
+
+/// One text or a batch of texts
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    Single(String),
+    Batch(Vec<String>),
+}
+
+impl EmbeddingInput {
+    fn into_texts(self) -> Vec<String> {
+        match self {
+            EmbeddingInput::Single(text) => vec![text],
+            EmbeddingInput::Batch(texts) => texts,
+        }
+    }
+}
+
+/// Embedding request
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingRequest {
+    pub input: EmbeddingInput,
+    /// How inputs are preprocessed
+    #[serde(default)]
+    pub input_type: InputKind,
+}
+
+/// Embedding of one input
+#[derive(Debug, Serialize)]
+pub struct EmbeddingData {
+    /// Position of the input in the request
+    pub index: usize,
+    pub embedding: Vec<f32>,
+}
+
+/// Embedding response
+#[derive(Debug, Serialize)]
+pub struct EmbeddingResponse {
+    pub model: String,
+    pub dimension: usize,
+    pub data: Vec<EmbeddingData>,
+}
+
+/// Embed one or more texts with the configured model
+pub async fn create_embeddings(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<EmbeddingRequest>,
+) -> Result<Json<EmbeddingResponse>> {
+    auth.require_scope("read")?;
+    let max_inputs = state.config.load().embedding.max_api_inputs;
+    let texts = request.input.into_texts();
+    validate_inputs(&texts, max_inputs)?;
+    let texts = state.preprocessor.apply_all(&texts, request.input_type);
+    
+    let embeddings = state.embedder.embed_batch(&texts).await?;
+    if embeddings.len() != texts.len() {
+        return Err(AppError::EmbeddingError {
+            message: format!("Expected {} embeddings, got {}", texts.len(), embeddings.len()),
+        });
+    }
+    
+    tracing::debug!(
+        inputs = texts.len(),
+        tenant_id = %auth.tenant_id,
+        "Embedded caller text"
+    );
+    
+    Ok(Json(EmbeddingResponse {
+        model: state.embedder.model_name().to_string(),
+        dimension: state.embedder.dimension(),
+        data: embeddings
+            .into_iter()
+            .enumerate()
+            .map(|(index, embedding)| EmbeddingData { index, embedding })
+            .collect(),
+    }))
+}
+
+/// Check the batch size and that every input is non-blank and not too long
+fn validate_inputs(texts: &[String], max_inputs: usize) -> Result<()> {
+    if texts.is_empty() || texts.len() > max_inputs {
+        return Err(AppError::Validation {
+            message: format!("Between 1 and {} inputs are accepted", max_inputs),
+            field: Some("input".to_string()),
+        });
+    }
+    
+    for (i, text) in texts.iter().enumerate() {
+        if text.trim().is_empty() {
+            return Err(AppError::Validation {
+                message: "Input must not be empty".to_string(),
+                field: Some(format!("input[{}]", i)),
+            });
+        }
+        let chars = text.chars().count();
+        if chars > MAX_INPUT_CHARS {
+            return Err(AppError::Validation {
+                message: format!("Input is {} characters; the limit is {}", chars, MAX_INPUT_CHARS),
+                field: Some(format!("input[{}]", i)),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_accepts_single_or_batch_input() {
+        let single: EmbeddingRequest = serde_json::from_str(r#"{"input": "graph neural networks"}"#).unwrap();
+        assert_eq!(single.input_type, InputKind::Passage);
+        assert_eq!(single.input.into_texts(), vec!["graph neural networks"]);
+        
+        let batch: EmbeddingRequest = serde_json::from_str(r#"{"input": ["a", "b"], "input_type": "query"}"#).unwrap();
+        assert_eq!(batch.input_type, InputKind::Query);
+        assert_eq!(batch.input.into_texts(), vec!["a", "b"]);
+    }
+    
+    #[test]
+    fn test_validates_inputs() {
+        let texts = |n: usize| vec!["text".to_string(); n];
+        assert!(validate_inputs(&texts(3), 3).is_ok());
+        assert!(validate_inputs(&texts(0), 3).is_err());
+        assert!(validate_inputs(&texts(4), 3).is_err());
+        assert!(validate_inputs(&["ok".to_string(), "  ".to_string()], 3).is_err());
+        assert!(validate_inputs(&["x".repeat(MAX_INPUT_CHARS + 1)], 3).is_err());
+    }
+}
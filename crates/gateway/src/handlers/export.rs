@@ -0,0 +1,256 @@
+//! Bulk data export handlers
+//!
+//! Exports stream newline-delimited JSON, one chunk per line, in a stable
+//! `(created_at, id)` order. Every line carries the cursor that resumes the
+//! export just after it, so an interrupted download continues from the last
+//! line the client received.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::AppState;
+use paperforge_common::{
+    audit::AuditEvent,
+    auth::AuthContext,
+    db::{ExportChunk, Repository},
+    errors::{AppError, Result},
+};
+
+/// Chunks fetched from the database per page
+const EXPORT_PAGE_SIZE: u64 = 500;
+
+/// Most chunks a single export request returns
+const MAX_EXPORT_LIMIT: u64 = 100_000;
+
+/// Chunk export query parameters
+#[derive(Debug, Deserialize)]
+pub struct ExportChunksQuery {
+    /// Resume after the line that carried this cursor
+    pub cursor: Option<String>,
+    /// Include each chunk's embedding
+    #[serde(default)]
+    pub include_embeddings: bool,
+    /// Chunks to return before the stream ends
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+fn default_limit() -> u64 { 10_000 }
+
+/// One NDJSON line of a chunk export
+#[derive(Serialize)]
+struct ExportLine {
+    cursor: String,
+    chunk_id: Uuid,
+    paper_id: Uuid,
+    paper_title: String,
+    chunk_index: i32,
+    chunk_type: String,
+    content: String,
+    metadata: serde_json::Value,
+    token_count: i32,
+    embedding_model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embedding: Option<Vec<f32>>,
+    created_at: String,
+}
+
+impl From<ExportChunk> for ExportLine {
+    fn from(chunk: ExportChunk) -> Self {
+        Self {
+            cursor: encode_cursor(chunk.created_at.with_timezone(&Utc), chunk.id),
+            embedding: chunk.parse_embedding(),
+            chunk_id: chunk.id,
+            paper_id: chunk.paper_id,
+            paper_title: chunk.paper_title,
+            chunk_index: chunk.chunk_index,
+            chunk_type: chunk.chunk_type,
+            content: chunk.content,
+            metadata: chunk.metadata,
+            token_count: chunk.token_count,
+            embedding_model: chunk.embedding_model,
+            created_at: chunk.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A chunk's position in export order, `(created_at, id)`
+type ChunkKey = (DateTime<Utc>, Uuid);
+
+/// Cursor for resuming after the chunk `(created_at, id)`
+fn encode_cursor(created_at: DateTime<Utc>, id: Uuid) -> String {
+    format!("{}_{}", created_at.timestamp_micros(), id.simple())
+}
+
+fn decode_cursor(cursor: &str) -> Result<ChunkKey> {
+    let invalid = || AppError::Validation {
+        message: "Invalid export cursor".to_string(),
+        field: Some("cursor".to_string()),
+    };
+    
+    let (micros, id) = cursor.split_once('_').ok_or_else(invalid)?;
+    let created_at = micros
+        .parse()
+        .ok()
+        .and_then(DateTime::from_timestamp_micros)
+        .ok_or_else(invalid)?;
+    let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+    
+    Ok((created_at, id))
+}
+
+/// Where a streaming export has got to
+struct ExportProgress {
+    repo: Repository,
+    tenant_id: Uuid,
+    include_embeddings: bool,
+    after: Option<ChunkKey>,
+    remaining: u64,
+    /// Page fetched before the response started, so its errors get a status
+    first_page: Option<Vec<ExportChunk>>,
+}
+
+impl ExportProgress {
+    fn page_size(&self) -> u64 {
+        self.remaining.min(EXPORT_PAGE_SIZE)
+    }
+    
+    async fn next_page(&mut self) -> Result<Vec<ExportChunk>> {
+        if let Some(page) = self.first_page.take() {
+            return Ok(page);
+        }
+        
+        self.repo
+            .export_chunks(
+                self.tenant_id,
+                self.after.map(|(time, id)| (time.fixed_offset(), id)),
+                self.include_embeddings,
+                self.page_size(),
+            )
+            .await
+    }
+}
+
+/// Render a page as NDJSON, returning the bytes and the last chunk's key
+fn render_page(page: Vec<ExportChunk>) -> Result<(Bytes, Option<ChunkKey>)> {
+    let last = page.last().map(|chunk| (chunk.created_at.with_timezone(&Utc), chunk.id));
+    let mut body = Vec::new();
+    for chunk in page {
+        serde_json::to_writer(&mut body, &ExportLine::from(chunk)).map_err(|e| AppError::Internal {
+            message: format!("Failed to serialize export line: {}", e),
+        })?;
+        body.push(b'\n');
+    }
+    
+    Ok((Bytes::from(body), last))
+}
+
+/// Stream the tenant's chunks as NDJSON
+///
+/// GET /v2/export/chunks?cursor=...&include_embeddings=true&limit=10000
+pub async fn export_chunks(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Query(query): Query<ExportChunksQuery>,
+) -> Result<Response> {
+    auth.require_scope("read")?;
+    if query.limit == 0 || query.limit > MAX_EXPORT_LIMIT {
+        return Err(AppError::Validation {
+            message: format!("limit must be between 1 and {}", MAX_EXPORT_LIMIT),
+            field: Some("limit".to_string()),
+        });
+    }
+    let after = query.cursor.as_deref().map(decode_cursor).transpose()?;
+    
+    state.export_limiter.check(&auth.tenant_id)?;
+    
+    let mut progress = ExportProgress {
+        repo: Repository::new(state.db.clone()),
+        tenant_id: auth.tenant_id,
+        include_embeddings: query.include_embeddings,
+        after,
+        remaining: query.limit,
+        first_page: None,
+    };
+    progress.first_page = Some(progress.next_page().await?);
+    
+    state.audit.record(
+        AuditEvent::new("chunks.export", "tenant")
+            .by(&auth)
+            .resource_id(auth.tenant_id)
+            .after(&serde_json::json!({
+                "cursor": query.cursor,
+                "include_embeddings": query.include_embeddings,
+                "limit": query.limit,
+            })),
+    ).await;
+    
+    let stream = futures::stream::unfold(Some(progress), |progress| async move {
+        let mut progress = progress?;
+        if progress.remaining == 0 {
+            return None;
+        }
+        
+        let page_size = progress.page_size();
+        let rendered = progress.next_page().await.and_then(|page| {
+            let fetched = page.len() as u64;
+            render_page(page).map(|(bytes, last)| (bytes, last, fetched))
+        });
+        match rendered {
+            Ok((bytes, last, fetched)) => {
+                if fetched == 0 {
+                    return None;
+                }
+                progress.after = last;
+                progress.remaining = if fetched < page_size {
+                    0
+                } else {
+                    progress.remaining - fetched
+                };
+                Some((Ok(bytes), Some(progress)))
+            }
+            Err(e) => {
+                // The status is already sent; ending the body early tells the
+                // client to resume from the last cursor it received
+                tracing::error!(error = %e, tenant_id = %progress.tenant_id, "Chunk export failed");
+                Some((Err(e), None))
+            }
+        }
+    });
+    
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(stream),
+    ).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_cursor_roundtrip() {
+        let created_at = DateTime::from_timestamp_micros(1_760_000_000_123_456).unwrap();
+        let id = Uuid::new_v4();
+        
+        let cursor = encode_cursor(created_at, id);
+        assert_eq!(decode_cursor(&cursor).unwrap(), (created_at, id));
+    }
+    
+    #[test]
+    fn test_invalid_cursor_rejected() {
+        for cursor in ["", "abc", "123", "123_not-a-uuid", "x_00000000000000000000000000000000"] {
+            assert!(
+                matches!(decode_cursor(cursor), Err(AppError::Validation { .. })),
+                "{cursor:?} should be rejected",
+            );
+        }
+    }
+}
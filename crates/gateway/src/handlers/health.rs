@@ -1,35 +1,135 @@
 //! Health check handlers
 
-use axum::{extract::State, Json};
-use serde::Serialize;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tonic::transport::Channel;
+use tonic_health::pb::{health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest};
 use crate::AppState;
-use paperforge_common::errors::Result;
+use paperforge_common::proto::search::search_service_server::SERVICE_NAME as SEARCH_SERVICE;
+use paperforge_common::slo::{SloWindow, WindowReport};
 
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: String,
 }
 
+/// Readiness query parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct ReadyQuery {
+    /// Include the read replicas and the SLO burn rates
+    #[serde(default)]
+    pub verbose: bool,
+}
+
 #[derive(Serialize)]
 pub struct ReadyResponse {
     pub status: String,
     pub checks: HealthChecks,
+    /// Rolling SLO windows; only with `verbose`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slo: Option<Vec<WindowReport>>,
 }
 
 #[derive(Serialize)]
 pub struct HealthChecks {
     pub database: CheckResult,
+    /// Caching only; the gateway works without it
+    pub redis: CheckResult,
+    /// Ingestion queue, when configured. Submissions wait in the outbox
+    /// while it's down, so it doesn't affect readiness.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue: Option<CheckResult>,
+    /// Search service, when searches are forwarded to it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<CheckResult>,
+    /// Read replicas by name; only with `verbose` and when any are
+    /// configured. Reads fall back to the primary, so they don't affect
+    /// readiness.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<BTreeMap<String, CheckResult>>,
+}
+
+impl HealthChecks {
+    /// Whether every dependency the gateway can't serve without is up
+    fn ready(&self) -> bool {
+        [Some(&self.database), Some(&self.redis), self.queue.as_ref(), self.search.as_ref()]
+            .into_iter()
+            .flatten()
+            .chain(self.replicas.iter().flat_map(|replicas| replicas.values()))
+            .all(|check| check.status == "up" || !check.required)
+    }
 }
 
 #[derive(Serialize)]
 pub struct CheckResult {
     pub status: String,
+    /// Whether the gateway is not ready while this dependency is down
+    pub required: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub latency_ms: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub lag_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
+impl CheckResult {
+    fn up(required: bool, latency: Option<Duration>) -> Self {
+        Self {
+            status: "up".to_string(),
+            required,
+            latency_ms: latency.map(|l| l.as_millis() as u64),
+            lag_bytes: None,
+            error: None,
+        }
+    }
+    
+    fn down(required: bool, error: impl Display) -> Self {
+        Self {
+            status: "down".to_string(),
+            required,
+            latency_ms: None,
+            lag_bytes: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// Run one dependency's probe, counting it as down after `timeout_ms`
+async fn check<E: Display>(
+    required: bool,
+    timeout_ms: u64,
+    probe: impl Future<Output = Result<(), E>>,
+) -> CheckResult {
+    let start = Instant::now();
+    match tokio::time::timeout(Duration::from_millis(timeout_ms), probe).await {
+        Ok(Ok(())) => CheckResult::up(required, Some(start.elapsed())),
+        Ok(Err(e)) => CheckResult::down(required, e),
+        Err(_) => CheckResult::down(required, format!("No response within {}ms", timeout_ms)),
+    }
+}
+
+/// Ask the search service's gRPC health endpoint whether search is serving
+async fn search_serving(mut client: HealthClient<Channel>) -> Result<(), String> {
+    let response = client
+        .check(HealthCheckRequest { service: SEARCH_SERVICE.to_string() })
+        .await
+        .map_err(|status| format!("Health check failed: {}", status.message()))?;
+    
+    match response.into_inner().status() {
+        ServingStatus::Serving => Ok(()),
+        status => Err(format!("Search service is {}", status.as_str_name())),
+    }
+}
+
 /// Liveness probe - always returns healthy if server is running
 pub async fn health() -> Json<HealthResponse> {
     Json(HealthResponse {
@@ -38,28 +138,115 @@ pub async fn health() -> Json<HealthResponse> {
 }
 
 /// Readiness probe - checks all dependencies
-pub async fn ready(State(state): State<AppState>) -> Json<ReadyResponse> {
-    let start = std::time::Instant::now();
+///
+/// The database, Redis, the ingestion queue and the search service are
+/// checked concurrently, each within its `gateway.readiness` timeout.
+/// Responds 503 when the database or the search service is down; the
+/// others are reported but the gateway can serve without them.
+/// `?verbose=true` adds each read replica's status and the SLO windows.
+pub async fn ready(
+    State(state): State<AppState>,
+    Query(query): Query<ReadyQuery>,
+) -> (StatusCode, Json<ReadyResponse>) {
+    let timeouts = state.config.load().gateway.readiness.clone();
     
-    let db_check = match state.db.ping().await {
-        Ok(_) => CheckResult {
-            status: "up".to_string(),
-            latency_ms: Some(start.elapsed().as_millis() as u64),
-            error: None,
-        },
-        Err(e) => CheckResult {
-            status: "down".to_string(),
-            latency_ms: None,
-            error: Some(e.to_string()),
-        },
+    let database = check(true, timeouts.database_timeout_ms, state.db.ping());
+    let redis = async {
+        match &state.cache {
+            Some(cache) => check(false, timeouts.redis_timeout_ms, cache.ping()).await,
+            None => CheckResult::down(false, "Not connected"),
+        }
+    };
+    let queue = async {
+        match &state.queue {
+            Some(queue) => Some(check(false, timeouts.queue_timeout_ms, queue.ping()).await),
+            None => None,
+        }
+    };
+    let search = async {
+        match state.search_health.clone() {
+            Some(client) => Some(check(true, timeouts.search_timeout_ms, search_serving(client)).await),
+            None => None,
+        }
     };
+    let (database, redis, queue, search) = tokio::join!(database, redis, queue, search);
     
-    let all_healthy = db_check.status == "up";
+    let replicas = query.verbose.then(|| {
+        state
+            .db
+            .replica_statuses()
+            .map(|status| {
+                let mut check = if !status.is_up() {
+                    CheckResult::down(false, "Replica unavailable")
+                } else if !status.is_usable() && status.weight() > 0 {
+                    CheckResult::down(false, "Replica lagging")
+                } else {
+                    CheckResult::up(false, Some(status.latency()))
+                };
+                check.lag_bytes = Some(status.lag_bytes());
+                (status.name().to_string(), check)
+            })
+            .collect::<BTreeMap<_, _>>()
+    }).filter(|replicas| !replicas.is_empty());
     
-    Json(ReadyResponse {
-        status: if all_healthy { "ready" } else { "not_ready" }.to_string(),
-        checks: HealthChecks {
-            database: db_check,
-        },
-    })
+    let slo = query.verbose.then(|| {
+        SloWindow::ALL
+            .into_iter()
+            .map(|window| state.slo.report(window))
+            .collect()
+    });
+    
+    let checks = HealthChecks {
+        database,
+        redis,
+        queue,
+        search,
+        replicas,
+    };
+    let ready = checks.ready();
+    
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (
+        status,
+        Json(ReadyResponse {
+            status: if ready { "ready" } else { "not_ready" }.to_string(),
+            checks,
+            slo,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_check_reports_failures_and_timeouts() {
+        let up = check(true, 100, async { Ok::<_, String>(()) }).await;
+        assert_eq!(up.status, "up");
+        assert!(up.latency_ms.is_some());
+        
+        let failed = check(true, 100, async { Err("connection refused") }).await;
+        assert_eq!(failed.status, "down");
+        assert_eq!(failed.error.as_deref(), Some("connection refused"));
+        
+        let hung = check(false, 10, std::future::pending::<Result<(), String>>()).await;
+        assert_eq!(hung.status, "down");
+        assert_eq!(hung.error.as_deref(), Some("No response within 10ms"));
+    }
+    
+    #[test]
+    fn test_only_required_dependencies_affect_readiness() {
+        let mut checks = HealthChecks {
+            database: CheckResult::up(true, None),
+            redis: CheckResult::down(false, "Not connected"),
+            queue: Some(CheckResult::down(false, "unreachable")),
+            search: None,
+            replicas: None,
+        };
+        assert!(checks.ready());
+        
+        checks.search = Some(CheckResult::down(true, "Search service is NOT_SERVING"));
+        assert!(!checks.ready());
+    }
 }
@@ -1,16 +1,33 @@
 //! Intelligence (Context Engine) handlers
 
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::handlers::search::{search_remote, SearchOptions};
 use crate::AppState;
 use paperforge_common::{
+    analytics::{SearchRecord, EVENT_SYNTHESIS},
     auth::AuthContext,
-    db::Repository,
+    context::{
+        self, ComparisonSubject, HydeConfig, HydeExpander, LiteratureReview, QueryIntent,
+        QueryParser, Reasoner, ReasonerConfig, ReasonerContext, ReviewConfig, ReviewPaper,
+        ReviewWriter, SynthesisContext, SynthesisOptions, Synthesizer,
+    },
+    db::{models::ReviewStatus, ChunkResult, Repository, SearchScope},
+    embeddings::InputKind,
     errors::{AppError, Result},
+    metrics,
+    request_context::RequestContext,
+    usage,
 };
 
 /// Intelligent search request
@@ -47,11 +64,88 @@ pub struct IntelligenceOptions {
     /// Result limit
     #[serde(default = "default_limit")]
     pub limit: usize,
+    
+    /// Query expansion applied to vector retrieval
+    #[serde(default)]
+    pub expansion: QueryExpansion,
+    
+    /// Share of the query's own embedding when fused with the HyDE passage
+    /// embedding; 0.0 searches with the passage alone
+    #[serde(default = "default_hyde_query_weight")]
+    pub hyde_query_weight: f32,
+}
+
+/// How the query is expanded before vector retrieval
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryExpansion {
+    /// Embed the query as is
+    #[default]
+    None,
+    /// Embed an LLM-written hypothetical answer passage (HyDE)
+    Hyde,
 }
 
 fn default_mode() -> String { "standard".to_string() }
 fn default_hops() -> usize { 2 }
 fn default_limit() -> usize { 20 }
+fn default_hyde_query_weight() -> f32 { 0.5 }
+
+/// Most reasoning hops a request may ask for
+const MAX_REASONING_HOPS: usize = 5;
+
+/// Results retrieved per reasoning hop
+const REASONING_HOP_LIMIT: usize = 10;
+
+/// Contexts retrieved per compared entity
+const COMPARISON_CONTEXT_LIMIT: usize = 4;
+
+/// Chunks retrieved for a literature review, before grouping by paper
+const REVIEW_RETRIEVAL_LIMIT: usize = 100;
+
+/// Passages kept per paper in a literature review
+const REVIEW_PASSAGES_PER_PAPER: usize = 3;
+
+/// Literature review request
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReviewRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub topic: String,
+    
+    /// Most papers the review draws on
+    #[serde(default = "default_review_papers")]
+    #[validate(range(min = 1, max = 50))]
+    pub max_papers: usize,
+    
+    /// Most sections (sub-topics) in the review
+    #[serde(default = "default_review_sections")]
+    #[validate(range(min = 1, max = 10))]
+    pub max_sections: usize,
+}
+
+fn default_review_papers() -> usize { 30 }
+fn default_review_sections() -> usize { 5 }
+
+/// Literature review job response
+#[derive(Serialize)]
+pub struct ReviewJobResponse {
+    pub job_id: Uuid,
+    pub topic: String,
+    pub status: String,
+    pub sections_written: i32,
+    pub sections_total: i32,
+    pub progress_percent: f64,
+    /// The review, once completed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review: Option<LiteratureReview>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    pub created_at: String,
+}
 
 /// Intelligent search response
 #[derive(Serialize)]
@@ -77,6 +171,10 @@ pub struct IntelligentSearchResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub synthesis: Option<SynthesizedAnswer>,
     
+    /// Structured comparison (synthesis mode, comparison queries)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+    
     pub processing_time_ms: u64,
 }
 
@@ -85,6 +183,9 @@ pub struct QueryUnderstanding {
     pub intent: String,
     pub entities: Vec<Entity>,
     pub expanded_terms: Vec<String>,
+    /// Passage used for HyDE retrieval
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hypothetical_document: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -129,6 +230,9 @@ pub struct CrossReference {
 #[derive(Serialize)]
 pub struct ReasoningChain {
     pub hops: Vec<ReasoningHop>,
+    /// Facts gathered across all hops
+    pub facts: Vec<String>,
+    pub confidence: f32,
 }
 
 #[derive(Serialize)]
@@ -136,6 +240,14 @@ pub struct ReasoningHop {
     pub query: String,
     pub facts_extracted: usize,
     pub next_query: Option<String>,
+    /// Why the next query was chosen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<String>,
+    pub docs_considered: usize,
+    pub confidence: f32,
+    pub latency_ms: u64,
+    /// The hop's search ran out of its latency budget
+    pub timed_out: bool,
 }
 
 #[derive(Serialize)]
@@ -152,36 +264,97 @@ pub struct Citation {
     pub title: String,
 }
 
+#[derive(Serialize)]
+pub struct Comparison {
+    /// Compared entities, in query order
+    pub subjects: Vec<String>,
+    pub dimensions: Vec<ComparisonDimension>,
+    pub summary: String,
+    /// Contexts cited anywhere in the comparison; indices number the
+    /// contexts of all subjects in order
+    pub citations: Vec<Citation>,
+}
+
+#[derive(Serialize)]
+pub struct ComparisonDimension {
+    pub name: String,
+    pub findings: Vec<SubjectFinding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difference: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SubjectFinding {
+    pub subject: String,
+    pub finding: String,
+    pub citations: Vec<usize>,
+}
+
 /// Perform intelligent search with context stitching
 pub async fn intelligent_search(
     State(state): State<AppState>,
     auth: AuthContext,
+    context: RequestContext,
     Json(request): Json<IntelligentSearchRequest>,
 ) -> Result<Json<IntelligentSearchResponse>> {
+    auth.require_scope("read")?;
     let start = Instant::now();
     
-    request.validate().map_err(|e| AppError::Validation {
-        message: e.to_string(),
-        field: None,
-    })?;
+    request.validate()?;
+    if !(0.0..=1.0).contains(&request.options.hyde_query_weight) {
+        return Err(AppError::Validation {
+            message: "hyde_query_weight must be between 0.0 and 1.0".to_string(),
+            field: Some("options.hyde_query_weight".to_string()),
+        });
+    }
+    
+    if let Some(session_id) = request.session_id {
+        if state.sessions.find_active(auth.tenant_id, session_id).await?.is_none() {
+            return Err(AppError::SessionNotFound {
+                id: session_id.to_string(),
+            });
+        }
+    }
     
     let repo = Repository::new(state.db.clone());
     
     // Phase 1: Query Understanding
-    // TODO: Implement actual NLU
-    let query_understanding = QueryUnderstanding {
-        intent: detect_intent(&request.query),
-        entities: extract_entities(&request.query),
-        expanded_terms: expand_query(&request.query),
+    let understanding = state.query_parser.parse(&request.query).await?;
+    let is_comparison = understanding.intent == QueryIntent::Comparison;
+    let mut query_understanding = QueryUnderstanding {
+        intent: understanding.intent.as_str().to_string(),
+        entities: understanding.entities.into_iter().map(|e| Entity {
+            text: e.text,
+            entity_type: e.entity_type.as_str().to_string(),
+        }).collect(),
+        expanded_terms: understanding.expanded_terms,
+        hypothetical_document: None,
     };
     
     // Phase 2: Multi-modal retrieval
-    let mock_embedding: Vec<f32> = (0..768).map(|i| (i as f32).sin()).collect();
-    let search_results = repo.hybrid_search(
+    let (mut query_embedding, scope) = embed_query(&state, &auth, &request.query).await?;
+    if request.options.expansion == QueryExpansion::Hyde {
+        let llm = state.llm.clone().ok_or_else(|| AppError::ServiceUnavailable {
+            message: "HyDE expansion requires an LLM, and none is configured".to_string(),
+        })?;
+        let hyde = HydeExpander::new(llm, state.embedder.clone(), HydeConfig::default())
+            .with_preprocessor(state.preprocessor.clone());
+        match hyde.embed(&request.query, Some(&query_embedding), request.options.hyde_query_weight).await {
+            Ok(expanded) => {
+                query_embedding = expanded.embedding;
+                query_understanding.hypothetical_document = Some(expanded.passage);
+            }
+            Err(e) => tracing::warn!(error = %e, "HyDE expansion failed, searching with the query embedding"),
+        }
+    }
+    let weights = state.config.load().search.clone();
+    let search_results = repo.hybrid_search_weighted(
         &request.query,
-        &mock_embedding,
+        &query_embedding,
         request.options.limit * 2,
-        Some(auth.tenant_id),
+        scope,
+        weights.vector_weight,
+        weights.bm25_weight,
     ).await?;
     
     // Phase 3: Apply citation boost
@@ -208,16 +381,24 @@ pub async fn intelligent_search(
     
     // Phase 5: Multi-hop reasoning (if deep mode)
     let reasoning = if request.options.include_reasoning && request.options.mode == "deep" {
-        Some(perform_reasoning(&request.query, request.options.max_hops))
+        Some(perform_reasoning(&state, &auth, &context, &request.query, request.options.max_hops).await?)
     } else {
         None
     };
     
-    // Phase 6: LLM synthesis (if synthesis mode)
-    let synthesis = if request.options.include_synthesis && request.options.mode == "synthesis" {
-        Some(synthesize_answer(&request.query, &results).await?)
+    // Phase 6: LLM synthesis (if synthesis mode); comparison queries get a
+    // structured comparison built from per-entity retrieval instead
+    let comparison_query = if is_comparison { QueryParser::comparison(&request.query) } else { None };
+    let (synthesis, comparison) = if request.options.include_synthesis && request.options.mode == "synthesis" {
+        match comparison_query {
+            Some(comparison_query) => {
+                let comparison = compare_subjects(&state, &auth, &context, &request.query, comparison_query).await?;
+                (None, Some(comparison))
+            }
+            None => (Some(synthesize_answer(&request.query, &results).await?), None),
+        }
     } else {
-        None
+        (None, None)
     };
     
     let processing_time_ms = start.elapsed().as_millis() as u64;
@@ -231,6 +412,32 @@ pub async fn intelligent_search(
         "Intelligent search completed"
     );
     
+    metrics::record_search(
+        processing_time_ms as f64 / 1000.0,
+        "intelligent",
+        results.len(),
+        auth.tenant_id,
+    );
+    state.analytics.record_search(SearchRecord {
+        tenant_id: auth.tenant_id,
+        session_id: request.session_id,
+        query: request.query.clone(),
+        mode: "intelligent".to_string(),
+        result_count: results.len(),
+        latency_ms: processing_time_ms,
+    });
+    if synthesis.is_some() || comparison.is_some() {
+        state.analytics.record_event(
+            auth.tenant_id,
+            request.session_id,
+            EVENT_SYNTHESIS,
+            serde_json::json!({
+                "query": request.query,
+                "comparison": comparison.is_some(),
+            }),
+        );
+    }
+    
     Ok(Json(IntelligentSearchResponse {
         query: request.query,
         session_id: request.session_id,
@@ -239,41 +446,179 @@ pub async fn intelligent_search(
         context,
         reasoning,
         synthesis,
+        comparison,
         processing_time_ms,
     }))
 }
 
-// Helper functions (placeholders for Phase 3 implementation)
+/// Start generating a literature review on a topic
+///
+/// The review is written in the background; poll
+/// `GET /v2/intelligence/review/{id}` for progress and the result.
+pub async fn create_review(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    context: RequestContext,
+    Json(request): Json<ReviewRequest>,
+) -> Result<(StatusCode, Json<ReviewJobResponse>)> {
+    auth.require_scope("write")?;
+    request.validate()?;
+    
+    let repo = Repository::new(state.db.clone());
+    let job = repo.create_review_job(auth.tenant_id, &request.topic).await?;
+    
+    tracing::info!(
+        job_id = %job.id,
+        topic = %request.topic,
+        tenant_id = %auth.tenant_id,
+        "Literature review requested"
+    );
+    
+    let response = review_job_response(job.clone())?;
+    // The review outlives the request, so it needs its own usage scope and
+    // isn't bound by the request's deadline
+    let context = RequestContext { deadline: None, ..context };
+    tokio::spawn(usage::attribute_to(auth.tenant_id, async move {
+        if let Err(e) = write_review(&state, &auth, &context, job.id, &request).await {
+            tracing::error!(job_id = %job.id, error = %e, "Literature review failed");
+            let repo = Repository::new(state.db.clone());
+            if let Err(e) = repo.update_review_status(job.id, ReviewStatus::Failed, Some(e.to_string())).await {
+                tracing::error!(job_id = %job.id, error = %e, "Failed to record review failure");
+            }
+        }
+    }));
+    
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
 
-fn detect_intent(query: &str) -> String {
-    if query.contains("compare") || query.contains("difference") || query.contains("vs") {
-        "comparison_query".to_string()
-    } else if query.contains("how") || query.contains("what") || query.contains("why") {
-        "explanation_query".to_string()
-    } else if query.contains("find") || query.contains("search") {
-        "discovery_query".to_string()
-    } else {
-        "general_query".to_string()
+/// Get a literature review job
+pub async fn get_review(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<ReviewJobResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    
+    let job = repo.find_review_job(job_id)
+        .await?
+        .ok_or_else(|| AppError::JobNotFound {
+            id: job_id.to_string(),
+        })?;
+    
+    if job.tenant_id != auth.tenant_id {
+        return Err(AppError::TenantMismatch);
     }
+    
+    Ok(Json(review_job_response(job)?))
 }
 
-fn extract_entities(query: &str) -> Vec<Entity> {
-    // Simple keyword extraction (placeholder)
-    query.split_whitespace()
-        .filter(|w| w.len() > 4 && w.chars().all(|c| c.is_alphabetic()))
-        .take(5)
-        .map(|w| Entity {
-            text: w.to_string(),
-            entity_type: "concept".to_string(),
-        })
-        .collect()
+fn review_job_response(job: paperforge_common::db::models::ReviewJob) -> Result<ReviewJobResponse> {
+    let progress_percent = job.progress_percent();
+    let review = job.review
+        .map(serde_json::from_value::<LiteratureReview>)
+        .transpose()
+        .map_err(|e| AppError::Internal {
+            message: format!("Stored review is invalid: {}", e),
+        })?;
+    
+    Ok(ReviewJobResponse {
+        job_id: job.id,
+        topic: job.topic,
+        status: job.status,
+        sections_written: job.sections_written,
+        sections_total: job.sections_total,
+        progress_percent,
+        review,
+        error_message: job.error_message,
+        started_at: job.started_at.map(|dt| dt.to_rfc3339()),
+        completed_at: job.completed_at.map(|dt| dt.to_rfc3339()),
+        created_at: job.created_at.to_rfc3339(),
+    })
 }
 
-fn expand_query(query: &str) -> Vec<String> {
-    // Placeholder for query expansion
-    vec![query.to_string()]
+/// Retrieve, cluster and write a review, recording progress on the job
+async fn write_review(
+    state: &AppState,
+    auth: &AuthContext,
+    context: &RequestContext,
+    job_id: Uuid,
+    request: &ReviewRequest,
+) -> Result<()> {
+    let repo = Repository::new(state.db.clone());
+    
+    repo.update_review_status(job_id, ReviewStatus::Retrieving, None).await?;
+    let results = retrieve(state, auth, context, &request.topic, REVIEW_RETRIEVAL_LIMIT).await?;
+    let papers = review_papers(results, request.max_papers);
+    
+    repo.update_review_status(job_id, ReviewStatus::Clustering, None).await?;
+    let llm = match state.llm.clone() {
+        Some(llm) => llm,
+        None => Arc::new(context::LlmClient::new(context::LLMConfig::default())?),
+    };
+    let writer = ReviewWriter::new(llm, state.embedder.clone(), ReviewConfig {
+        max_sections: request.max_sections,
+        ..Default::default()
+    });
+    let groups = writer.cluster(&papers).await?;
+    
+    repo.update_review_status(job_id, ReviewStatus::Writing, None).await?;
+    repo.update_review_progress(job_id, 0, groups.len() as i32).await?;
+    let mut sections = Vec::with_capacity(groups.len());
+    for (i, group) in groups.iter().enumerate() {
+        sections.push(writer.write_section(&request.topic, &papers, group).await?);
+        repo.update_review_progress(job_id, i as i32 + 1, groups.len() as i32).await?;
+    }
+    
+    let review = LiteratureReview {
+        topic: request.topic.clone(),
+        sections,
+        bibliography: context::bibliography(&papers),
+    };
+    let review = serde_json::to_value(&review).map_err(|e| AppError::Internal {
+        message: format!("Failed to serialize review: {}", e),
+    })?;
+    repo.complete_review_job(job_id, review).await?;
+    
+    tracing::info!(job_id = %job_id, papers = papers.len(), sections = groups.len(), "Literature review completed");
+    Ok(())
+}
+
+/// Group chunk results by paper, best-scoring paper first
+///
+/// Keeping a few passages per paper diversifies the review: one paper with
+/// many matching chunks can't take up the whole retrieval budget.
+fn review_papers(results: Vec<ChunkResult>, max_papers: usize) -> Vec<ReviewPaper> {
+    let mut papers: Vec<ReviewPaper> = Vec::new();
+    let mut positions: HashMap<Uuid, usize> = HashMap::new();
+    
+    for result in results {
+        match positions.get(&result.paper_id) {
+            Some(&i) => {
+                let paper = &mut papers[i];
+                if paper.passages.len() < REVIEW_PASSAGES_PER_PAPER {
+                    paper.passages.push(result.content);
+                }
+            }
+            None if papers.len() < max_papers => {
+                positions.insert(result.paper_id, papers.len());
+                papers.push(ReviewPaper {
+                    paper_id: result.paper_id,
+                    title: result.paper_title,
+                    passages: vec![result.content],
+                    score: result.score as f32,
+                });
+            }
+            None => {}
+        }
+    }
+    
+    papers.sort_by(|a, b| b.score.total_cmp(&a.score));
+    papers
 }
 
+// Helper functions (placeholders for Phase 3 implementation)
+
 async fn stitch_context(
     results: &[IntelligenceResult],
     _state: &AppState,
@@ -297,19 +642,162 @@ async fn stitch_context(
     })
 }
 
-fn perform_reasoning(query: &str, max_hops: usize) -> ReasoningChain {
-    // Placeholder for multi-hop reasoning
-    ReasoningChain {
-        hops: vec![
-            ReasoningHop {
-                query: query.to_string(),
-                facts_extracted: 5,
-                next_query: if max_hops > 1 { Some("follow-up query".to_string()) } else { None },
-            }
-        ],
+/// Run multi-hop reasoning over real searches
+///
+/// Follow-up queries go through the search service when it's configured
+/// and run in-process otherwise, each within the configured hop budget.
+async fn perform_reasoning(
+    state: &AppState,
+    auth: &AuthContext,
+    context: &RequestContext,
+    query: &str,
+    max_hops: usize,
+) -> Result<ReasoningChain> {
+    let config = state.config.load_full();
+    let reasoner = Reasoner::new(ReasonerConfig {
+        max_hops: max_hops.clamp(1, MAX_REASONING_HOPS),
+        hop_timeout: Some(Duration::from_millis(config.context.reasoning_hop_timeout_ms)),
+        ..Default::default()
+    });
+    
+    let search = |hop_query: String| async move {
+        let results = retrieve(state, auth, context, &hop_query, REASONING_HOP_LIMIT).await?;
+        
+        // Fusion scores are tiny in absolute terms; the reasoner's hop
+        // confidence expects scores relative to the best hit
+        let best = results.iter().map(|r| r.score).fold(0.0, f64::max);
+        Ok(results.into_iter().map(|r| ReasonerContext {
+            content: r.content,
+            source: r.paper_title,
+            score: if best > 0.0 { (r.score / best) as f32 } else { 0.0 },
+        }).collect())
+    };
+    
+    let chain = reasoner.reason(query, search).await?;
+    
+    Ok(ReasoningChain {
+        hops: chain.hops.into_iter().map(|hop| ReasoningHop {
+            query: hop.query,
+            facts_extracted: hop.facts.len(),
+            next_query: hop.next_query,
+            rationale: hop.rationale,
+            docs_considered: hop.docs_considered,
+            confidence: hop.confidence,
+            latency_ms: hop.latency_ms,
+            timed_out: hop.timed_out,
+        }).collect(),
+        facts: chain.all_facts,
+        confidence: chain.confidence,
+    })
+}
+
+/// Search for `query`, through the search service when it's configured and
+/// in-process otherwise
+async fn retrieve(
+    state: &AppState,
+    auth: &AuthContext,
+    context: &RequestContext,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<ChunkResult>> {
+    match state.search.clone() {
+        Some(client) => {
+            let options = SearchOptions {
+                mode: "hybrid".to_string(),
+                limit,
+                ..Default::default()
+            };
+            search_remote(client, context, query, &options).await
+        }
+        None => {
+            let weights = state.config.load().search.clone();
+            let (embedding, scope) = embed_query(state, auth, query).await?;
+            Repository::new(state.db.clone()).hybrid_search_weighted(
+                query,
+                &embedding,
+                limit,
+                scope,
+                weights.vector_weight,
+                weights.bm25_weight,
+            ).await
+        }
     }
 }
 
+/// Embed a query, scoping vector search to the tenant's chunks embedded by
+/// the same model
+///
+/// A fallback provider may have embedded the query, and its vectors are
+/// only comparable with chunks it embedded.
+async fn embed_query(state: &AppState, auth: &AuthContext, query: &str) -> Result<(Vec<f32>, SearchScope)> {
+    let query = state.preprocessor.apply(query, InputKind::Query);
+    let tagged = state.embedder.embed_batch_tagged(&[query]).await?;
+    let embedding = tagged.embeddings.into_iter().next().ok_or_else(|| AppError::EmbeddingError {
+        message: "Empty response".to_string(),
+    })?;
+    Ok((embedding, SearchScope::tenant(auth.tenant_id).with_model(tagged.model)))
+}
+
+/// Compare the entities of a comparison query
+///
+/// Each entity is retrieved on its own, in parallel, so one well-covered
+/// entity can't crowd the others out of the context.
+async fn compare_subjects(
+    state: &AppState,
+    auth: &AuthContext,
+    context: &RequestContext,
+    query: &str,
+    comparison: context::ComparisonQuery,
+) -> Result<Comparison> {
+    let retrievals = comparison.subjects.iter().map(|subject| {
+        let subject_query = match &comparison.aspect {
+            Some(aspect) => format!("{} {}", subject, aspect),
+            None => subject.clone(),
+        };
+        async move { retrieve(state, auth, context, &subject_query, COMPARISON_CONTEXT_LIMIT).await }
+    });
+    let retrieved = futures::future::try_join_all(retrievals).await?;
+    
+    let subjects: Vec<ComparisonSubject> = comparison.subjects
+        .into_iter()
+        .zip(retrieved)
+        .map(|(name, results)| ComparisonSubject {
+            name,
+            contexts: results.into_iter().map(|r| SynthesisContext {
+                paper_id: r.paper_id,
+                paper_title: r.paper_title,
+                content: r.content,
+                relevance_score: r.score as f32,
+            }).collect(),
+        })
+        .collect();
+    
+    let synthesizer = match state.llm.clone() {
+        Some(llm) => Synthesizer::from_client(llm),
+        None => Synthesizer::new(context::LLMConfig::default())?,
+    };
+    let compared = synthesizer.compare(query, &subjects, &SynthesisOptions::default()).await?;
+    
+    Ok(Comparison {
+        subjects: compared.subjects,
+        dimensions: compared.dimensions.into_iter().map(|d| ComparisonDimension {
+            name: d.name,
+            findings: d.findings.into_iter().map(|f| SubjectFinding {
+                subject: f.subject,
+                finding: f.finding,
+                citations: f.citations,
+            }).collect(),
+            difference: d.difference,
+        }).collect(),
+        summary: compared.summary,
+        citations: compared.citations.into_iter().map(|c| Citation {
+            index: c.index,
+            paper_id: c.paper_id,
+            title: c.title,
+        }).collect(),
+    })
+}
+
 async fn synthesize_answer(query: &str, results: &[IntelligenceResult]) -> Result<SynthesizedAnswer> {
     // Placeholder for LLM synthesis
     Ok(SynthesizedAnswer {
@@ -322,3 +810,41 @@ async fn synthesize_answer(query: &str, results: &[IntelligenceResult]) -> Resul
         confidence: 0.75,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn chunk(paper_id: Uuid, content: &str, score: f64) -> ChunkResult {
+        ChunkResult {
+            chunk_id: Uuid::new_v4(),
+            paper_id,
+            paper_title: format!("Paper {}", paper_id),
+            content: content.to_string(),
+            chunk_index: 0,
+            score,
+            embedding_model: "test".to_string(),
+            chunk_type: "text".to_string(),
+            provenance: None,
+        }
+    }
+    
+    #[test]
+    fn test_review_papers_diversifies() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let results = vec![
+            chunk(a, "a1", 0.9),
+            chunk(a, "a2", 0.8),
+            chunk(b, "b1", 0.7),
+            chunk(a, "a3", 0.6),
+            chunk(a, "a4", 0.5),
+            chunk(c, "c1", 0.4),
+        ];
+        
+        let papers = review_papers(results, 2);
+        
+        assert_eq!(papers.len(), 2);
+        assert_eq!(papers[0].passages, vec!["a1", "a2", "a3"]);
+        assert_eq!(papers[1].paper_id, b);
+    }
+}
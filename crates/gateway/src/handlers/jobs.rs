@@ -10,7 +10,7 @@ use uuid::Uuid;
 use crate::AppState;
 use paperforge_common::{
     auth::AuthContext,
-    db::Repository,
+    db::{JobRepository, Repository},
     errors::{AppError, Result},
 };
 
@@ -19,11 +19,23 @@ use paperforge_common::{
 pub struct JobResponse {
     pub job_id: Uuid,
     pub status: String,
+    /// Last pipeline stage reached; unlike `status`, kept after a failure
+    pub stage: String,
+    pub attempt_count: i32,
+    /// When a failed job will be retried; absent once it is `dead`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_retry_at: Option<String>,
+    /// Latest checkpoint a retry would resume from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<CheckpointResponse>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub paper_id: Option<Uuid>,
     pub chunks_created: i32,
     pub chunks_total: i32,
     pub progress_percent: f64,
+    /// Indexes of chunks the embedding provider rejected
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed_chunks: Vec<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -33,35 +45,128 @@ pub struct JobResponse {
     pub created_at: String,
 }
 
+/// Saved stage output of a job
+#[derive(Serialize)]
+pub struct CheckpointResponse {
+    pub stage: String,
+    pub saved_at: String,
+}
+
 /// Get job status
 pub async fn get_job(
     State(state): State<AppState>,
     auth: AuthContext,
     Path(job_id): Path<Uuid>,
 ) -> Result<Json<JobResponse>> {
+    auth.require_scope("read")?;
     let repo = Repository::new(state.db.clone());
-    
-    let job = repo.find_job_by_id(job_id)
+    job_response(&repo, auth.tenant_id, job_id).await.map(Json)
+}
+
+/// Look up one of the tenant's jobs with its latest checkpoint
+pub async fn job_response(jobs: &dyn JobRepository, tenant_id: Uuid, job_id: Uuid) -> Result<JobResponse> {
+    let job = jobs.find_job_by_id(job_id)
         .await?
         .ok_or_else(|| AppError::JobNotFound { 
             id: job_id.to_string() 
         })?;
     
     // Verify tenant access
-    if job.tenant_id != auth.tenant_id {
+    if job.tenant_id != tenant_id {
         return Err(AppError::TenantMismatch);
     }
     
-    Ok(Json(JobResponse {
+    let checkpoint = jobs.find_checkpoints(job_id)
+        .await?
+        .pop()
+        .map(|c| CheckpointResponse {
+            stage: c.stage,
+            saved_at: c.created_at.to_rfc3339(),
+        });
+    
+    Ok(JobResponse {
         job_id: job.id,
         status: job.status.clone(),
+        stage: job.stage.clone(),
+        attempt_count: job.attempt_count,
+        next_retry_at: job.next_retry_at.map(|dt| dt.to_rfc3339()),
+        checkpoint,
         paper_id: job.paper_id,
         chunks_created: job.chunks_processed,
         chunks_total: job.chunks_total,
         progress_percent: job.progress_percent(),
+        failed_chunks: job.failed_chunk_indexes(),
         error_message: job.error_message,
         started_at: job.started_at.map(|dt| dt.to_rfc3339()),
         completed_at: job.completed_at.map(|dt| dt.to_rfc3339()),
         created_at: job.created_at.to_rfc3339(),
-    }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use paperforge_common::db::{
+        memory::MemoryRepository,
+        models::{IngestionJob, JobCheckpoint},
+    };
+
+    fn job(tenant_id: Uuid) -> IngestionJob {
+        IngestionJob {
+            id: Uuid::new_v4(),
+            tenant_id,
+            paper_id: None,
+            batch_id: None,
+            status: "embedding".to_string(),
+            stage: "embedding".to_string(),
+            chunks_total: 10,
+            chunks_processed: 5,
+            error_message: None,
+            failed_chunks: serde_json::json!([]),
+            idempotency_key: None,
+            attempt_count: 1,
+            next_retry_at: None,
+            queue_message: None,
+            queue_attributes: None,
+            created_at: Utc::now().fixed_offset(),
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_response_shows_latest_checkpoint() {
+        let repo = MemoryRepository::new();
+        let tenant_id = Uuid::new_v4();
+        let job = job(tenant_id);
+        let now = Utc::now().fixed_offset();
+        for (stage, age) in [("extracted", 2), ("chunked", 1)] {
+            repo.insert_checkpoint(JobCheckpoint {
+                job_id: job.id,
+                stage: stage.to_string(),
+                data: serde_json::json!({}),
+                created_at: now - Duration::minutes(age),
+            });
+        }
+        let job_id = job.id;
+        repo.insert_job(job);
+
+        let response = job_response(&repo, tenant_id, job_id).await.unwrap();
+        assert_eq!(response.checkpoint.unwrap().stage, "chunked");
+        assert_eq!(response.progress_percent, 50.0);
+    }
+
+    #[tokio::test]
+    async fn test_job_response_is_tenant_scoped() {
+        let repo = MemoryRepository::new();
+        let job = job(Uuid::new_v4());
+        let job_id = job.id;
+        repo.insert_job(job);
+
+        let other_tenant = job_response(&repo, Uuid::new_v4(), job_id).await;
+        assert!(matches!(other_tenant, Err(AppError::TenantMismatch)));
+        let missing = job_response(&repo, Uuid::new_v4(), Uuid::new_v4()).await;
+        assert!(matches!(missing, Err(AppError::JobNotFound { .. })));
+    }
 }
@@ -0,0 +1,270 @@
+//! API key management handlers
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::AppState;
+use paperforge_common::{
+    audit::AuditEvent,
+    auth::{api_key_lookup_prefix, generate_api_key, hash_api_key, AuthContext, API_KEY_SCOPES},
+    db::{models::Tenant, Repository},
+    errors::{AppError, Result},
+};
+
+/// API key metadata (never includes the key itself)
+#[derive(Serialize)]
+pub struct ApiKeyInfo {
+    pub tenant_id: Uuid,
+    pub tenant_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<String>,
+    pub expired: bool,
+}
+
+impl From<Tenant> for ApiKeyInfo {
+    fn from(tenant: Tenant) -> Self {
+        Self {
+            expired: tenant.is_api_key_expired(),
+            scopes: tenant.api_key_scope_list(),
+            tenant_id: tenant.id,
+            tenant_name: tenant.name,
+            prefix: tenant.api_key_prefix,
+            created_at: tenant.api_key_created_at.to_rfc3339(),
+            expires_at: tenant.api_key_expires_at.map(|dt| dt.to_rfc3339()),
+            last_used_at: tenant.api_key_last_used_at.map(|dt| dt.to_rfc3339()),
+        }
+    }
+}
+
+/// Request to rotate the caller's API key
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateKeyRequest {
+    /// Scopes for the new key; defaults to the current key's scopes
+    pub scopes: Option<Vec<String>>,
+    
+    /// Lifetime of the new key; omit for a key that never expires
+    pub expires_in_days: Option<u32>,
+}
+
+/// Newly issued API key (shown once)
+#[derive(Serialize)]
+pub struct RotateKeyResponse {
+    pub api_key: String,
+    #[serde(flatten)]
+    pub info: ApiKeyInfo,
+}
+
+/// Query for stale key listing
+#[derive(Debug, Deserialize)]
+pub struct StaleKeysQuery {
+    #[serde(default = "default_unused_days")]
+    pub unused_days: u32,
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+fn default_unused_days() -> u32 { 90 }
+fn default_limit() -> u64 { 100 }
+
+/// Get metadata for the caller's API key
+pub async fn get_current_key(
+    State(state): State<AppState>,
+    auth: AuthContext,
+) -> Result<Json<ApiKeyInfo>> {
+    let repo = Repository::new(state.db.clone());
+    
+    let tenant = repo
+        .find_tenant_by_id(auth.tenant_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound {
+            resource_type: "tenant".to_string(),
+            id: auth.tenant_id.to_string(),
+        })?;
+    
+    Ok(Json(tenant.into()))
+}
+
+/// Replace the caller's API key with a new one
+///
+/// Refresh tokens obtained with the old key are revoked. The old key stops
+/// working on this instance at once and on others once their cached
+/// verifications expire (about a minute). Scopes can only be narrowed unless
+/// the caller is an admin.
+pub async fn rotate_key(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<RotateKeyRequest>,
+) -> Result<Json<RotateKeyResponse>> {
+    auth.require_scope("write")?;
+    let repo = Repository::new(state.db.clone());
+    
+    let tenant = repo
+        .find_tenant_by_id(auth.tenant_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound {
+            resource_type: "tenant".to_string(),
+            id: auth.tenant_id.to_string(),
+        })?;
+    
+    let scopes = new_key_scopes(request.scopes, &auth, &tenant)?;
+    let expires_at = request
+        .expires_in_days
+        .map(|days| Utc::now() + Duration::days(days as i64));
+    
+    let api_key = generate_api_key();
+    let key = api_key.clone();
+    let hash = tokio::task::spawn_blocking(move || hash_api_key(&key))
+        .await
+        .map_err(|e| AppError::Internal {
+            message: format!("API key hashing failed: {}", e),
+        })??;
+    
+    repo.replace_tenant_api_key(
+        tenant.id,
+        &hash,
+        api_key_lookup_prefix(&api_key),
+        &scopes,
+        expires_at,
+    )
+    .await?;
+    state.auth.forget_api_key(tenant.id);
+    
+    tracing::info!(
+        tenant_id = %tenant.id,
+        scopes = ?scopes,
+        expires_at = ?expires_at,
+        "API key rotated"
+    );
+    
+    let before = ApiKeyInfo::from(tenant);
+    let tenant = repo
+        .find_tenant_by_id(before.tenant_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound {
+            resource_type: "tenant".to_string(),
+            id: auth.tenant_id.to_string(),
+        })?;
+    let info = ApiKeyInfo::from(tenant);
+    
+    state.audit.record(
+        AuditEvent::new("api_key.rotate", "tenant")
+            .by(&auth)
+            .resource_id(info.tenant_id)
+            .before(&before)
+            .after(&info),
+    ).await;
+    
+    Ok(Json(RotateKeyResponse { api_key, info }))
+}
+
+/// Scopes of a rotated key: those requested, if the caller holds them all,
+/// else the current key's
+fn new_key_scopes(requested: Option<Vec<String>>, auth: &AuthContext, tenant: &Tenant) -> Result<Vec<String>> {
+    match requested {
+        Some(scopes) => {
+            if let Some(scope) = scopes.iter().find(|s| !auth.has_scope(s)) {
+                return Err(AppError::Forbidden {
+                    message: format!("Cannot grant scope not held by caller: {}", scope),
+                });
+            }
+            Ok(scopes)
+        }
+        None if tenant.api_key_scopes.is_empty() => {
+            Ok(API_KEY_SCOPES.iter().map(|s| s.to_string()).collect())
+        }
+        None => Ok(tenant.api_key_scope_list()),
+    }
+}
+
+/// List the caller's tenant's API keys not used for a while (admin only)
+pub async fn list_stale_keys(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Query(query): Query<StaleKeysQuery>,
+) -> Result<Json<Vec<ApiKeyInfo>>> {
+    auth.require_scope("admin")?;
+    let repo = Repository::new(state.db.clone());
+    
+    let cutoff = Utc::now() - Duration::days(query.unused_days as i64);
+    let tenants = repo.find_stale_api_keys(auth.tenant_id, cutoff, query.limit.min(1000)).await?;
+    
+    Ok(Json(tenants.into_iter().map(Into::into).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn tenant(scopes: &str, expires_at: Option<chrono::DateTime<Utc>>) -> Tenant {
+        let now = Utc::now().fixed_offset();
+        Tenant {
+            id: Uuid::new_v4(),
+            name: "acme".to_string(),
+            api_key_hash: String::new(),
+            api_key_prefix: Some("pk_01234567".to_string()),
+            api_key_scopes: scopes.to_string(),
+            api_key_created_at: now,
+            api_key_expires_at: expires_at.map(|dt| dt.fixed_offset()),
+            api_key_last_used_at: None,
+            signing_secret: None,
+            rate_limit_rps: 100,
+            resolve_metadata: false,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+    
+    fn caller(scopes: &[&str]) -> AuthContext {
+        AuthContext {
+            tenant_id: Uuid::new_v4(),
+            api_key: None,
+            user_id: None,
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            request_id: String::new(),
+        }
+    }
+    
+    #[test]
+    fn test_new_key_scopes() {
+        let current = tenant("read write", None);
+        let scopes = |requested: Option<&[&str]>, auth: &AuthContext| {
+            new_key_scopes(requested.map(|r| r.iter().map(|s| s.to_string()).collect()), auth, &current)
+        };
+        
+        // Narrowing is allowed, widening only for admins
+        assert_eq!(scopes(Some(&["read"]), &caller(&["read", "write"])).unwrap(), ["read"]);
+        assert!(matches!(
+            scopes(Some(&["read", "admin"]), &caller(&["read", "write"])),
+            Err(AppError::Forbidden { .. })
+        ));
+        assert_eq!(scopes(Some(&["admin"]), &caller(&["admin"])).unwrap(), ["admin"]);
+        
+        // Without a request the current scopes carry over
+        assert_eq!(scopes(None, &caller(&["write"])).unwrap(), ["read", "write"]);
+        let legacy = tenant("", None);
+        assert_eq!(new_key_scopes(None, &caller(&["write"]), &legacy).unwrap(), API_KEY_SCOPES);
+    }
+    
+    #[test]
+    fn test_key_info_reports_expiry() {
+        let info = ApiKeyInfo::from(tenant("read", Some(Utc::now() - Duration::minutes(1))));
+        assert!(info.expired);
+        assert!(info.expires_at.is_some());
+        
+        let info = ApiKeyInfo::from(tenant("read", Some(Utc::now() + Duration::days(30))));
+        assert!(!info.expired);
+        assert!(!ApiKeyInfo::from(tenant("read", None)).expired);
+    }
+}
@@ -1,9 +1,23 @@
 //! API handlers module
 
 pub mod health;
+pub mod admin;
+pub mod auth;
+pub mod keys;
 pub mod papers;
+pub mod chunks;
+pub mod authors;
+pub mod collections;
 pub mod jobs;
+pub mod batches;
 pub mod search;
+pub mod embeddings;
+pub mod saved_searches;
 pub mod intelligence;
 pub mod sessions;
 pub mod citations;
+pub mod export;
+pub mod storage;
+pub mod tenant;
+pub mod usage;
+pub mod analytics;
@@ -1,19 +1,36 @@
 //! Paper management handlers
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::handlers::tenant::{check_embedding_model, IngestionDefaults};
 use crate::AppState;
 use paperforge_common::{
+    audit::AuditEvent,
     auth::AuthContext,
-    db::Repository,
+    chunking::{self, ChunkStrategy, ChunkingConfig, Section},
+    crossref::normalize_doi,
+    db::{models::Paper, CitationRelation, PaperUpdate, RelatedPaper, Repository, SimilarPaper, SimilarityBasis},
+    embeddings::HashEmbedder,
     errors::{AppError, Result},
+    fetcher::check_url,
+    outbox::{OutboxPayload, INGESTION_QUEUE},
+    queue::{IngestionJobMessage, IngestionJobOptions, ReprocessPaperMessage, UrlImportMessage},
+    references::reference_section,
+    request_context::RequestContext,
+};
+use paperforge_ingestion::{
+    errors::IngestionError,
+    pdf::{extract_pdf_bytes, PdfLimits},
 };
 
 /// Request to create a new paper
@@ -44,6 +61,10 @@ pub struct PaperInput {
     
     pub external_id: Option<String>,
     
+    /// DOI (bare, `doi:` or doi.org URL); canonical metadata is looked up
+    /// from Crossref during ingestion
+    pub doi: Option<String>,
+    
     pub published_at: Option<chrono::DateTime<chrono::Utc>>,
     
     #[serde(default)]
@@ -58,6 +79,92 @@ pub struct IngestionOptions {
     pub chunk_overlap: Option<usize>,
 }
 
+/// Request to import a paper from a URL
+#[derive(Debug, Deserialize, Validate)]
+pub struct ImportUrlRequest {
+    /// Client-provided idempotency key
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    
+    /// PDF or HTML page to download
+    #[validate(length(min = 1, max = 2048))]
+    pub url: String,
+    
+    /// Title to use instead of the one found in the document
+    #[validate(length(min = 1, max = 1000))]
+    pub title: Option<String>,
+    
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+    
+    /// Ingestion options
+    #[serde(default)]
+    pub options: IngestionOptions,
+}
+
+/// Request to preview how a paper would be chunked
+///
+/// Exactly one of `text` and `source_key` is given. A PDF can also be
+/// posted as the body itself, with the options in the query string.
+#[derive(Debug, Deserialize, Validate)]
+pub struct PreviewPaperRequest {
+    /// Paper text, as extracted
+    #[serde(default)]
+    #[validate(length(min = 1))]
+    pub text: Option<String>,
+    
+    /// Storage key of one of the tenant's stored PDFs, e.g. a paper's
+    /// `source_key`; extracted and cleaned as ingestion does
+    #[serde(default)]
+    pub source_key: Option<String>,
+    
+    /// Chunking overrides; unset fields use the tenant's defaults
+    #[serde(default)]
+    #[validate(nested)]
+    pub options: PreviewOptions,
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct PreviewOptions {
+    pub chunk_strategy: Option<String>,
+    
+    #[validate(range(min = 100, max = 10000))]
+    pub chunk_size: Option<usize>,
+    
+    #[validate(range(max = 5000))]
+    pub chunk_overlap: Option<usize>,
+    
+    #[validate(range(max = 10000))]
+    pub min_chunk_size: Option<usize>,
+    
+    #[validate(range(min = 0.0, max = 100.0))]
+    pub breakpoint_percentile: Option<f64>,
+}
+
+/// Chunks and sections the given text would produce
+#[derive(Serialize)]
+pub struct PreviewPaperResponse {
+    pub strategy: ChunkStrategy,
+    pub chunk_size: usize,
+    pub chunk_overlap: usize,
+    pub min_chunk_size: usize,
+    pub total_chunks: usize,
+    pub total_tokens: i64,
+    pub sections: Vec<Section>,
+    pub chunks: Vec<PreviewChunk>,
+}
+
+#[derive(Serialize)]
+pub struct PreviewChunk {
+    pub index: i32,
+    pub content: String,
+    pub token_count: i32,
+    pub start_pos: usize,
+    pub end_pos: usize,
+    /// Title of the section the chunk starts in
+    pub section: Option<String>,
+}
+
 /// Response after creating a paper
 #[derive(Serialize)]
 pub struct CreatePaperResponse {
@@ -79,20 +186,126 @@ pub struct PaperResponse {
     pub published_at: Option<String>,
     pub metadata: serde_json::Value,
     pub chunk_count: i64,
+    /// Citation PageRank, about 1 for an average paper
+    pub pagerank: Option<f64>,
+    /// When `pagerank` was computed
+    pub pagerank_updated_at: Option<String>,
     pub created_at: String,
 }
 
+impl PaperResponse {
+    fn from_model(paper: Paper, chunk_count: i64) -> Self {
+        Self {
+            id: paper.id,
+            title: paper.title,
+            abstract_text: paper.abstract_text,
+            source: paper.source,
+            external_id: paper.external_id,
+            published_at: paper.published_at.map(|dt| dt.to_rfc3339()),
+            metadata: paper.metadata,
+            chunk_count,
+            pagerank: paper.pagerank,
+            pagerank_updated_at: paper.pagerank_updated_at.map(|dt| dt.to_rfc3339()),
+            created_at: paper.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Time-limited links to a paper's stored documents
+#[derive(Serialize)]
+pub struct PaperSourceResponse {
+    pub paper_id: Uuid,
+    /// Original document
+    pub url: String,
+    /// Extracted text, when stored
+    pub text_url: Option<String>,
+    pub expires_at: String,
+}
+
+/// Request to edit paper metadata (absent fields are left unchanged)
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdatePaperRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub title: Option<String>,
+    
+    #[validate(length(min = 1, max = 50000))]
+    #[serde(rename = "abstract")]
+    pub abstract_text: Option<String>,
+    
+    pub source: Option<String>,
+    
+    pub external_id: Option<String>,
+    
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+    
+    /// Merged into existing metadata; `null` values remove keys
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Options for finding similar papers
+#[derive(Debug, Deserialize)]
+pub struct SimilarPapersQuery {
+    #[serde(default)]
+    pub basis: SimilarityBasis,
+    
+    #[serde(default = "default_similar_limit")]
+    pub limit: u64,
+    
+    /// Share of the score from citation-graph relatedness (0.0 - 1.0)
+    #[serde(default)]
+    pub citation_weight: f64,
+}
+
+fn default_similar_limit() -> u64 { 10 }
+
+/// A similar paper
+#[derive(Debug, Serialize)]
+pub struct SimilarPaperLink {
+    pub paper_id: Uuid,
+    pub paper_title: String,
+    /// Blend of `similarity` and `citation_score`
+    pub score: f64,
+    /// Embedding similarity; absent for papers found only through citations
+    pub similarity: Option<f64>,
+    /// Best co-citation or bibliographic coupling score, when weighted in
+    pub citation_score: Option<f64>,
+}
+
+/// Papers similar to a paper
+#[derive(Serialize)]
+pub struct SimilarPapersResponse {
+    pub paper_id: Uuid,
+    pub basis: SimilarityBasis,
+    pub similar: Vec<SimilarPaperLink>,
+}
+
+/// Request to re-chunk a paper
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct ReprocessPaperRequest {
+    #[validate(range(min = 100, max = 10000))]
+    pub chunk_size: Option<usize>,
+    
+    #[validate(range(max = 5000))]
+    pub chunk_overlap: Option<usize>,
+}
+
 /// Create a new paper and start async ingestion
 pub async fn create_paper(
     State(state): State<AppState>,
     auth: AuthContext,
+    context: RequestContext,
     Json(request): Json<CreatePaperRequest>,
 ) -> Result<(StatusCode, Json<CreatePaperResponse>)> {
+    auth.require_scope("write")?;
     // Validate request
-    request.paper.validate().map_err(|e| AppError::Validation {
-        message: e.to_string(),
-        field: None,
-    })?;
+    request.paper.validate()?;
+    let doi = request.paper.doi
+        .as_deref()
+        .map(|doi| normalize_doi(doi).ok_or_else(|| AppError::Validation {
+            message: format!("Not a valid DOI: {}", doi),
+            field: Some("paper.doi".to_string()),
+        }))
+        .transpose()?;
     
     let repo = Repository::new(state.db.clone());
     
@@ -109,16 +322,52 @@ pub async fn create_paper(
         }
     }
     
-    // Create the ingestion job
-    let job = repo.create_job(auth.tenant_id, request.idempotency_key.clone()).await?;
+    // Create the ingestion job and its queue message atomically; options
+    // the request leaves out come from the tenant's settings
+    if let Some(model) = &request.options.embedding_model {
+        check_embedding_model(model, &state.config.load(), "options.embedding_model")?;
+    }
+    let defaults = IngestionDefaults::load(&state, auth.tenant_id).await?;
+    let paper = request.paper;
+    let job = repo.create_job_with_outbox(
+        auth.tenant_id,
+        None,
+        request.idempotency_key.clone(),
+        INGESTION_QUEUE,
+        |job| OutboxPayload::new(&IngestionJobMessage {
+            job_id: job.id,
+            tenant_id: auth.tenant_id,
+            paper_title: paper.title.clone(),
+            paper_abstract: paper.abstract_text.clone(),
+            paper_source: paper.source.clone(),
+            external_id: paper.external_id.clone(),
+            doi: doi.clone(),
+            published_at: paper.published_at,
+            metadata: paper.metadata.clone(),
+            idempotency_key: request.idempotency_key.clone(),
+            options: IngestionJobOptions {
+                embedding_model: request.options.embedding_model.clone()
+                    .unwrap_or_else(|| defaults.embedding_model.clone()),
+                chunk_strategy: request.options.chunk_strategy.clone()
+                    .unwrap_or_else(|| defaults.chunk_strategy.clone()),
+                chunk_size: request.options.chunk_size.unwrap_or(defaults.chunk_size),
+                chunk_overlap: request.options.chunk_overlap.unwrap_or(defaults.chunk_overlap),
+            },
+        })
+        .with_traceparent(context.traceparent.as_deref()),
+    ).await?;
     
-    // TODO: Send to ingestion queue for async processing
-    // For now, we'll process synchronously (Phase 1 limitation)
+    state.audit.record(
+        AuditEvent::new("paper.create", "ingestion_job")
+            .by(&auth)
+            .resource_id(job.id)
+            .after(&job),
+    ).await;
     
     tracing::info!(
         job_id = %job.id,
         tenant_id = %auth.tenant_id,
-        title = %request.paper.title,
+        title = %paper.title,
         "Paper ingestion job created"
     );
     
@@ -130,12 +379,238 @@ pub async fn create_paper(
     })))
 }
 
+/// Import a paper from a URL and start async ingestion
+///
+/// The URL is checked against the fetch policy here so a refused one fails
+/// the request; the download itself happens in the ingestion service,
+/// which checks the addresses the host resolves to before connecting.
+pub async fn import_paper_url(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    context: RequestContext,
+    Json(request): Json<ImportUrlRequest>,
+) -> Result<(StatusCode, Json<CreatePaperResponse>)> {
+    auth.require_scope("write")?;
+    request.validate()?;
+    let url = check_url(request.url.trim(), &state.config.load().fetcher)?;
+    
+    let repo = Repository::new(state.db.clone());
+    
+    if let Some(ref key) = request.idempotency_key {
+        if let Some(existing_job) = repo.find_job_by_idempotency_key(auth.tenant_id, key).await? {
+            return Ok((StatusCode::OK, Json(CreatePaperResponse {
+                job_id: existing_job.id,
+                status: existing_job.status.clone(),
+                estimated_completion_ms: 0,
+                poll_url: format!("/v2/jobs/{}", existing_job.id),
+            })));
+        }
+    }
+    
+    if let Some(model) = &request.options.embedding_model {
+        check_embedding_model(model, &state.config.load(), "options.embedding_model")?;
+    }
+    let defaults = IngestionDefaults::load(&state, auth.tenant_id).await?;
+    let job = repo.create_job_with_outbox(
+        auth.tenant_id,
+        None,
+        request.idempotency_key.clone(),
+        INGESTION_QUEUE,
+        |job| OutboxPayload::new(&UrlImportMessage {
+            job_id: job.id,
+            tenant_id: auth.tenant_id,
+            url: url.to_string(),
+            title: request.title.clone(),
+            metadata: request.metadata.clone(),
+            idempotency_key: request.idempotency_key.clone(),
+            options: IngestionJobOptions {
+                embedding_model: request.options.embedding_model.clone()
+                    .unwrap_or_else(|| defaults.embedding_model.clone()),
+                chunk_strategy: request.options.chunk_strategy.clone()
+                    .unwrap_or_else(|| defaults.chunk_strategy.clone()),
+                chunk_size: request.options.chunk_size.unwrap_or(defaults.chunk_size),
+                chunk_overlap: request.options.chunk_overlap.unwrap_or(defaults.chunk_overlap),
+            },
+        })
+        .with_traceparent(context.traceparent.as_deref()),
+    ).await?;
+    
+    state.audit.record(
+        AuditEvent::new("paper.import_url", "ingestion_job")
+            .by(&auth)
+            .resource_id(job.id)
+            .after(&job),
+    ).await;
+    
+    tracing::info!(
+        job_id = %job.id,
+        tenant_id = %auth.tenant_id,
+        url = %url,
+        "URL import job created"
+    );
+    
+    Ok((StatusCode::ACCEPTED, Json(CreatePaperResponse {
+        job_id: job.id,
+        status: "pending".to_string(),
+        estimated_completion_ms: 10000,
+        poll_url: format!("/v2/jobs/{}", job.id),
+    })))
+}
+
+/// Preview chunking of a paper
+///
+/// Runs the same chunking as ingestion, with the tenant's defaults, but
+/// stores and embeds nothing, so chunk settings can be tuned before
+/// ingesting a corpus. The paper is given as text, as a stored PDF, or as
+/// an `application/pdf` body; PDFs go through ingestion's extraction and
+/// cleanup first. Semantic boundaries always use the local hashing model
+/// here, so they can differ from ingestion when that is configured with
+/// another provider.
+pub async fn preview_paper(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Query(query_options): Query<PreviewOptions>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<PreviewPaperResponse>> {
+    auth.require_scope("read")?;
+    let config = state.config.load_full();
+    let defaults = &config.ingestion;
+    let limits = PdfLimits {
+        max_file_bytes: defaults.max_file_bytes,
+        max_pages: defaults.max_pages,
+        max_extracted_chars: defaults.max_extracted_chars,
+    };
+    
+    let is_pdf = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/pdf"));
+    let (text, options) = if is_pdf {
+        query_options.validate()?;
+        (extract_preview_pdf(body.to_vec(), "upload".to_string(), limits).await?, query_options)
+    } else {
+        let request: PreviewPaperRequest = serde_json::from_slice(&body).map_err(|e| AppError::Validation {
+            message: format!("Invalid request body: {}", e),
+            field: None,
+        })?;
+        request.validate()?;
+        let text = match (request.text, request.source_key) {
+            (Some(text), None) => text,
+            (None, Some(key)) => {
+                // Keys are laid out as `{tenant_id}/{job_id}/{object}`
+                if !key.starts_with(&format!("{}/", auth.tenant_id)) {
+                    return Err(AppError::TenantMismatch);
+                }
+                let storage = state.storage.clone().ok_or_else(|| AppError::ServiceUnavailable {
+                    message: "Previewing a stored PDF requires document storage".to_string(),
+                })?;
+                let pdf = storage.get(&key).await?.ok_or_else(|| AppError::NotFound {
+                    resource_type: "document".to_string(),
+                    id: key.clone(),
+                })?;
+                extract_preview_pdf(pdf, key, limits).await?
+            }
+            _ => {
+                return Err(AppError::Validation {
+                    message: "Give either text or source_key".to_string(),
+                    field: Some("text".to_string()),
+                })
+            }
+        };
+        (text, request.options)
+    };
+    
+    let size = text.chars().count();
+    if size > defaults.max_extracted_chars {
+        return Err(AppError::PayloadTooLarge {
+            size,
+            limit: defaults.max_extracted_chars,
+        });
+    }
+    
+    let tenant_defaults = IngestionDefaults::load(&state, auth.tenant_id).await?;
+    let strategy = match options.chunk_strategy {
+        Some(strategy) => strategy.parse::<ChunkStrategy>().map_err(|message| AppError::Validation {
+            message,
+            field: Some("options.chunk_strategy".to_string()),
+        })?,
+        None => tenant_defaults.chunk_strategy.parse().unwrap_or_default(),
+    };
+    let chunking_config = ChunkingConfig {
+        strategy,
+        chunk_size: options.chunk_size.unwrap_or(tenant_defaults.chunk_size),
+        chunk_overlap: options.chunk_overlap.unwrap_or(tenant_defaults.chunk_overlap),
+        min_chunk_size: options.min_chunk_size.unwrap_or(defaults.min_chunk_size),
+        breakpoint_percentile: options.breakpoint_percentile.unwrap_or(defaults.breakpoint_percentile),
+    };
+    
+    let sections = chunking::detect_sections(&text);
+    let embedder = HashEmbedder::new(256);
+    let chunks = match reference_section(&sections).filter(|_| tenant_defaults.exclude_references) {
+        Some(section) => {
+            let skip = section.start_pos..section.end_pos;
+            chunking::chunk_around(&text, skip, &chunking_config, &embedder).await?
+        }
+        None => chunking::chunk(&text, &chunking_config, &embedder).await?,
+    };
+    
+    tracing::debug!(
+        tenant_id = %auth.tenant_id,
+        chunk_count = chunks.len(),
+        section_count = sections.len(),
+        "Chunking previewed"
+    );
+    
+    let chunks: Vec<PreviewChunk> = chunks
+        .into_iter()
+        .map(|chunk| PreviewChunk {
+            section: Section::at(&sections, chunk.start_pos).map(|s| s.title.clone()),
+            index: chunk.index,
+            content: chunk.content,
+            token_count: chunk.token_count,
+            start_pos: chunk.start_pos,
+            end_pos: chunk.end_pos,
+        })
+        .collect();
+    
+    Ok(Json(PreviewPaperResponse {
+        strategy: chunking_config.strategy,
+        chunk_size: chunking_config.chunk_size,
+        chunk_overlap: chunking_config.chunk_overlap,
+        min_chunk_size: chunking_config.min_chunk_size,
+        total_chunks: chunks.len(),
+        total_tokens: chunks.iter().map(|c| c.token_count as i64).sum(),
+        sections,
+        chunks,
+    }))
+}
+
+/// Extract a PDF's text with ingestion's extraction and cleanup
+async fn extract_preview_pdf(body: Vec<u8>, name: String, limits: PdfLimits) -> Result<String> {
+    let extracted = tokio::task::spawn_blocking(move || extract_pdf_bytes(&body, &name, &limits))
+        .await
+        .map_err(|e| AppError::Internal {
+            message: format!("PDF extraction failed: {}", e),
+        })?;
+    match extracted {
+        Ok(pdf) => Ok(pdf.text),
+        Err(IngestionError::App(e)) => Err(e),
+        Err(e @ IngestionError::PdfParseError { .. }) => Err(AppError::Validation {
+            message: e.to_string(),
+            field: None,
+        }),
+        Err(e) => Err(AppError::Internal { message: e.to_string() }),
+    }
+}
+
 /// Get a paper by ID
 pub async fn get_paper(
     State(state): State<AppState>,
     auth: AuthContext,
     Path(paper_id): Path<Uuid>,
 ) -> Result<Json<PaperResponse>> {
+    auth.require_scope("read")?;
     let repo = Repository::new(state.db.clone());
     
     let paper = repo.find_paper_by_id(paper_id)
@@ -149,28 +624,155 @@ pub async fn get_paper(
         return Err(AppError::TenantMismatch);
     }
     
+    // Soft-deleted papers are only reachable through restore
+    if paper.is_deleted() {
+        return Err(AppError::PaperNotFound { 
+            id: paper_id.to_string() 
+        });
+    }
+    
     // Get chunk count
     let chunks = repo.get_chunks_by_paper(paper_id).await?;
     
-    Ok(Json(PaperResponse {
-        id: paper.id,
-        title: paper.title,
-        abstract_text: paper.abstract_text,
-        source: paper.source,
-        external_id: paper.external_id,
-        published_at: paper.published_at.map(|dt| dt.to_rfc3339()),
-        metadata: paper.metadata,
-        chunk_count: chunks.len() as i64,
-        created_at: paper.created_at.to_rfc3339(),
+    Ok(Json(PaperResponse::from_model(paper, chunks.len() as i64)))
+}
+
+/// Rank similar papers by `(1 - w) * similarity + w * citation score`
+///
+/// A paper's citation score is its best score across `related`; papers
+/// missing from either list score 0 on that side.
+fn blend_similar(
+    similar: Vec<SimilarPaper>,
+    related: Vec<RelatedPaper>,
+    citation_weight: f64,
+    limit: usize,
+) -> Vec<SimilarPaperLink> {
+    let mut links: HashMap<Uuid, SimilarPaperLink> = HashMap::new();
+    for paper in similar {
+        links.insert(paper.paper_id, SimilarPaperLink {
+            paper_id: paper.paper_id,
+            paper_title: paper.title,
+            score: 0.0,
+            similarity: Some(paper.similarity),
+            citation_score: None,
+        });
+    }
+    for paper in related {
+        let link = links.entry(paper.paper_id).or_insert_with(|| SimilarPaperLink {
+            paper_id: paper.paper_id,
+            paper_title: paper.title,
+            score: 0.0,
+            similarity: None,
+            citation_score: None,
+        });
+        link.citation_score = Some(link.citation_score.unwrap_or(0.0).max(paper.score));
+    }
+    
+    let mut links: Vec<SimilarPaperLink> = links.into_values()
+        .map(|mut link| {
+            link.score = (1.0 - citation_weight) * link.similarity.unwrap_or(0.0)
+                + citation_weight * link.citation_score.unwrap_or(0.0);
+            link
+        })
+        .collect();
+    links.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.paper_id.cmp(&b.paper_id)));
+    links.truncate(limit);
+    links
+}
+
+/// Papers similar to a paper in embedding space
+///
+/// `basis=paper` (default) compares against the mean of the paper's chunk
+/// embeddings, `basis=abstract` against its title and abstract chunk. A
+/// `citation_weight` above 0 blends in co-citation and bibliographic
+/// coupling scores.
+pub async fn similar_papers(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(paper_id): Path<Uuid>,
+    Query(query): Query<SimilarPapersQuery>,
+) -> Result<Json<SimilarPapersResponse>> {
+    auth.require_scope("read")?;
+    if !(0.0..=1.0).contains(&query.citation_weight) {
+        return Err(AppError::Validation {
+            message: "citation_weight must be between 0 and 1".to_string(),
+            field: Some("citation_weight".to_string()),
+        });
+    }
+    let limit = query.limit.clamp(1, 100);
+    
+    let repo = Repository::new(state.db.clone());
+    let paper = repo.find_paper_by_id(paper_id)
+        .await?
+        .filter(|paper| paper.tenant_id == auth.tenant_id && !paper.is_deleted())
+        .ok_or_else(|| AppError::PaperNotFound {
+            id: paper_id.to_string(),
+        })?;
+    
+    let similar = repo.similar_papers(auth.tenant_id, paper.id, query.basis, limit).await?;
+    let mut related = Vec::new();
+    if query.citation_weight > 0.0 {
+        for relation in [CitationRelation::Cocitation, CitationRelation::Coupling] {
+            related.extend(repo.related_papers(auth.tenant_id, paper.id, relation, limit).await?);
+        }
+    }
+    
+    Ok(Json(SimilarPapersResponse {
+        paper_id: paper.id,
+        basis: query.basis,
+        similar: blend_similar(similar, related, query.citation_weight, limit as usize),
+    }))
+}
+
+/// Get links to a paper's original document and extracted text
+pub async fn get_paper_source(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(paper_id): Path<Uuid>,
+) -> Result<Json<PaperSourceResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    
+    let paper = repo.find_paper_by_id(paper_id)
+        .await?
+        .filter(|paper| !paper.is_deleted())
+        .ok_or_else(|| AppError::PaperNotFound { 
+            id: paper_id.to_string() 
+        })?;
+    
+    if paper.tenant_id != auth.tenant_id {
+        return Err(AppError::TenantMismatch);
+    }
+    
+    let (Some(storage), Some(source_key)) = (&state.storage, &paper.source_key) else {
+        return Err(AppError::NotFound {
+            resource_type: "paper source".to_string(),
+            id: paper_id.to_string(),
+        });
+    };
+    
+    let ttl = Duration::from_secs(state.config.load().storage.presign_ttl_secs);
+    let url = storage.presign(source_key, ttl).await?;
+    let text_url = match &paper.text_key {
+        Some(key) => Some(storage.presign(key, ttl).await?),
+        None => None,
+    };
+    
+    Ok(Json(PaperSourceResponse {
+        paper_id,
+        url,
+        text_url,
+        expires_at: (chrono::Utc::now() + ttl).to_rfc3339(),
     }))
 }
 
-/// Delete a paper
+/// Delete a paper (soft delete; restorable until purged)
 pub async fn delete_paper(
     State(state): State<AppState>,
     auth: AuthContext,
     Path(paper_id): Path<Uuid>,
 ) -> Result<StatusCode> {
+    auth.require_scope("write")?;
     let repo = Repository::new(state.db.clone());
     
     // Verify paper exists and belongs to tenant
@@ -184,7 +786,19 @@ pub async fn delete_paper(
         return Err(AppError::TenantMismatch);
     }
     
-    repo.delete_paper(paper_id).await?;
+    if !repo.delete_paper(paper_id).await? {
+        return Err(AppError::PaperNotFound { 
+            id: paper_id.to_string() 
+        });
+    }
+    
+    state.audit.record(
+        AuditEvent::new("paper.delete", "paper")
+            .by(&auth)
+            .resource_id(paper_id)
+            .before(&paper),
+    ).await;
+    invalidate_search(&state, auth.tenant_id).await;
     
     tracing::info!(
         paper_id = %paper_id,
@@ -194,3 +808,221 @@ pub async fn delete_paper(
     
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Restore a soft-deleted paper
+pub async fn restore_paper(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(paper_id): Path<Uuid>,
+) -> Result<Json<PaperResponse>> {
+    auth.require_scope("write")?;
+    let repo = Repository::new(state.db.clone());
+    
+    let paper = repo.find_paper_by_id(paper_id)
+        .await?
+        .ok_or_else(|| AppError::PaperNotFound { 
+            id: paper_id.to_string() 
+        })?;
+    
+    if paper.tenant_id != auth.tenant_id {
+        return Err(AppError::TenantMismatch);
+    }
+    
+    // Restoring a live paper is a no-op
+    if paper.is_deleted() {
+        repo.restore_paper(paper_id).await?;
+        
+        state.audit.record(
+            AuditEvent::new("paper.restore", "paper")
+                .by(&auth)
+                .resource_id(paper_id)
+                .before(&paper),
+        ).await;
+        invalidate_search(&state, auth.tenant_id).await;
+        
+        tracing::info!(
+            paper_id = %paper_id,
+            tenant_id = %auth.tenant_id,
+            "Paper restored"
+        );
+    }
+    
+    let chunks = repo.get_chunks_by_paper(paper_id).await?;
+    
+    Ok(Json(PaperResponse::from_model(paper, chunks.len() as i64)))
+}
+
+/// Drop the tenant's cached search results after its papers or
+/// collections change
+///
+/// Best effort: on failure, cached results expire with their TTL.
+pub(crate) async fn invalidate_search(state: &AppState, tenant_id: Uuid) {
+    if let Some(cache) = &state.cache {
+        if let Err(e) = cache.invalidate_search(tenant_id).await {
+            tracing::warn!(error = %e, tenant_id = %tenant_id, "Failed to invalidate search cache");
+        }
+    }
+}
+
+/// Edit paper metadata
+pub async fn update_paper(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(paper_id): Path<Uuid>,
+    Json(request): Json<UpdatePaperRequest>,
+) -> Result<Json<PaperResponse>> {
+    auth.require_scope("write")?;
+    request.validate()?;
+    
+    let repo = Repository::new(state.db.clone());
+    
+    let paper = repo.find_paper_by_id(paper_id)
+        .await?
+        .filter(|p| !p.is_deleted())
+        .ok_or_else(|| AppError::PaperNotFound { 
+            id: paper_id.to_string() 
+        })?;
+    
+    if paper.tenant_id != auth.tenant_id {
+        return Err(AppError::TenantMismatch);
+    }
+    
+    // Shallow merge of metadata keys
+    let metadata = request.metadata.map(|patch| {
+        let mut merged = match paper.metadata.clone() {
+            serde_json::Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        for (key, value) in patch {
+            if value.is_null() {
+                merged.remove(&key);
+            } else {
+                merged.insert(key, value);
+            }
+        }
+        serde_json::Value::Object(merged)
+    });
+    
+    let update = PaperUpdate {
+        title: request.title,
+        abstract_text: request.abstract_text,
+        source: request.source,
+        external_id: request.external_id,
+        published_at: request.published_at,
+        metadata,
+    };
+    
+    let before = paper.clone();
+    let paper = repo.update_paper(paper, update).await?;
+    
+    state.audit.record(
+        AuditEvent::new("paper.update", "paper")
+            .by(&auth)
+            .resource_id(paper_id)
+            .before(&before)
+            .after(&paper),
+    ).await;
+    // Results carry the title and metadata, and filters match on them
+    invalidate_search(&state, auth.tenant_id).await;
+    
+    let chunks = repo.get_chunks_by_paper(paper_id).await?;
+    
+    tracing::info!(
+        paper_id = %paper_id,
+        tenant_id = %auth.tenant_id,
+        "Paper updated"
+    );
+    
+    Ok(Json(PaperResponse::from_model(paper, chunks.len() as i64)))
+}
+
+/// Re-chunk a paper and re-run embedding as a new job
+pub async fn reprocess_paper(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    context: RequestContext,
+    Path(paper_id): Path<Uuid>,
+    request: Option<Json<ReprocessPaperRequest>>,
+) -> Result<(StatusCode, Json<CreatePaperResponse>)> {
+    auth.require_scope("write")?;
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    request.validate()?;
+    
+    let repo = Repository::new(state.db.clone());
+    
+    let paper = repo.find_paper_by_id(paper_id)
+        .await?
+        .filter(|p| !p.is_deleted())
+        .ok_or_else(|| AppError::PaperNotFound { 
+            id: paper_id.to_string() 
+        })?;
+    
+    if paper.tenant_id != auth.tenant_id {
+        return Err(AppError::TenantMismatch);
+    }
+    
+    let job = repo.create_job_with_outbox(
+        auth.tenant_id,
+        Some(paper_id),
+        None,
+        INGESTION_QUEUE,
+        |job| OutboxPayload::new(&ReprocessPaperMessage {
+            job_id: job.id,
+            tenant_id: auth.tenant_id,
+            paper_id,
+            chunk_size: request.chunk_size,
+            chunk_overlap: request.chunk_overlap,
+        })
+        .with_traceparent(context.traceparent.as_deref()),
+    ).await?;
+    
+    state.audit.record(
+        AuditEvent::new("paper.reprocess", "paper")
+            .by(&auth)
+            .resource_id(paper_id)
+            .after(&job),
+    ).await;
+    
+    tracing::info!(
+        job_id = %job.id,
+        paper_id = %paper_id,
+        tenant_id = %auth.tenant_id,
+        "Paper reprocess job created"
+    );
+    
+    Ok((StatusCode::ACCEPTED, Json(CreatePaperResponse {
+        job_id: job.id,
+        status: "pending".to_string(),
+        estimated_completion_ms: 5000,
+        poll_url: format!("/v2/jobs/{}", job.id),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_blend_similar() {
+        let similar = vec![
+            SimilarPaper { paper_id: Uuid::from_u128(1), title: "A".to_string(), similarity: 0.9 },
+            SimilarPaper { paper_id: Uuid::from_u128(2), title: "B".to_string(), similarity: 0.8 },
+        ];
+        let related = vec![
+            RelatedPaper { paper_id: Uuid::from_u128(2), title: "B".to_string(), shared: 3, score: 0.6 },
+            RelatedPaper { paper_id: Uuid::from_u128(3), title: "C".to_string(), shared: 1, score: 0.2 },
+            RelatedPaper { paper_id: Uuid::from_u128(2), title: "B".to_string(), shared: 2, score: 0.4 },
+        ];
+        
+        let ranked = blend_similar(similar.clone(), Vec::new(), 0.0, 10);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].paper_id, Uuid::from_u128(1));
+        
+        let ranked = blend_similar(similar, related, 0.5, 2);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].paper_id, Uuid::from_u128(2));
+        assert!((ranked[0].score - 0.7).abs() < 1e-9);
+        assert_eq!(ranked[0].citation_score, Some(0.6));
+        assert_eq!(ranked[1].paper_id, Uuid::from_u128(1));
+    }
+}
@@ -0,0 +1,169 @@
+//! Saved search handlers
+//!
+//! Saved searches with a webhook or email address are run by the ingestion
+//! worker against newly indexed papers (see `paperforge_common::alerts`).
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use paperforge_common::{
+    auth::AuthContext,
+    db::{models::SavedSearch, NewSavedSearch, Repository},
+    errors::{AppError, Result},
+    fetcher::check_url,
+};
+
+/// Save search request
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateSavedSearchRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub name: String,
+    
+    #[validate(length(min = 1, max = 1000))]
+    pub query: String,
+    
+    /// Calibrated score a new paper must reach to alert
+    #[serde(default = "default_min_score")]
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub min_score: f64,
+    
+    #[validate(url)]
+    pub webhook_url: Option<String>,
+    
+    /// Key for the `X-PaperForge-Signature` HMAC of webhook bodies
+    #[validate(length(min = 16, max = 256))]
+    pub webhook_secret: Option<String>,
+    
+    #[validate(email)]
+    pub email: Option<String>,
+}
+
+fn default_min_score() -> f64 { 0.5 }
+
+/// A saved search
+#[derive(Serialize)]
+pub struct SavedSearchResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub query: String,
+    pub min_score: f64,
+    pub webhook_url: Option<String>,
+    pub email: Option<String>,
+    /// Papers indexed after this are new to the search
+    pub last_run_at: String,
+    pub created_at: String,
+}
+
+impl From<SavedSearch> for SavedSearchResponse {
+    fn from(search: SavedSearch) -> Self {
+        Self {
+            id: search.id,
+            name: search.name,
+            query: search.query,
+            min_score: search.min_score,
+            webhook_url: search.webhook_url,
+            email: search.email,
+            last_run_at: search.last_run_at.to_rfc3339(),
+            created_at: search.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Saved searches list
+#[derive(Serialize)]
+pub struct SavedSearchListResponse {
+    pub searches: Vec<SavedSearchResponse>,
+}
+
+/// Save a search
+///
+/// Only papers indexed after the search is saved trigger alerts.
+pub async fn create_saved_search(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<CreateSavedSearchRequest>,
+) -> Result<(StatusCode, Json<SavedSearchResponse>)> {
+    auth.require_scope("write")?;
+    request.validate()?;
+    if request.webhook_secret.is_some() && request.webhook_url.is_none() {
+        return Err(AppError::Validation {
+            message: "webhook_secret requires webhook_url".to_string(),
+            field: Some("webhook_secret".to_string()),
+        });
+    }
+    // Alerts are posted from inside the network; hold the URL to the fetch policy
+    let webhook_url = request
+        .webhook_url
+        .as_deref()
+        .map(|url| {
+            check_url(url.trim(), &state.config.load().fetcher).map_err(|e| match e {
+                AppError::Validation { message, .. } => AppError::Validation {
+                    message,
+                    field: Some("webhook_url".to_string()),
+                },
+                e => e,
+            })
+        })
+        .transpose()?;
+    
+    let search = Repository::new(state.db.clone())
+        .create_saved_search(auth.tenant_id, NewSavedSearch {
+            name: request.name,
+            query: request.query,
+            min_score: request.min_score,
+            webhook_url: webhook_url.map(String::from),
+            webhook_secret: request.webhook_secret,
+            email: request.email,
+        })
+        .await?;
+    
+    tracing::info!(
+        saved_search_id = %search.id,
+        tenant_id = %auth.tenant_id,
+        "Search saved"
+    );
+    
+    Ok((StatusCode::CREATED, Json(search.into())))
+}
+
+/// List the tenant's saved searches
+pub async fn list_saved_searches(
+    State(state): State<AppState>,
+    auth: AuthContext,
+) -> Result<Json<SavedSearchListResponse>> {
+    auth.require_scope("read")?;
+    let searches = Repository::new(state.db.clone())
+        .list_saved_searches(auth.tenant_id)
+        .await?;
+    
+    Ok(Json(SavedSearchListResponse {
+        searches: searches.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Delete a saved search
+pub async fn delete_saved_search(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode> {
+    auth.require_scope("write")?;
+    let deleted = Repository::new(state.db.clone())
+        .delete_saved_search(auth.tenant_id, id)
+        .await?;
+    if !deleted {
+        return Err(AppError::NotFound {
+            resource_type: "saved search".to_string(),
+            id: id.to_string(),
+        });
+    }
+    
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,17 +1,27 @@
 //! Search handlers
 
-use axum::{extract::State, Json};
+use axum::{extract::State, http::StatusCode, Json};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Instant;
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::AppState;
+use crate::{AppState, SearchClient};
 use paperforge_common::{
+    analytics::SearchRecord,
     auth::AuthContext,
-    db::{ChunkResult, Repository},
+    authors::split_author_filter,
+    cache::keys,
+    db::{models::{Chunk, ChunkType, Provenance}, ChunkResult, Repository, SearchScope},
+    embeddings::InputKind,
     errors::{AppError, Result},
     metrics,
+    proto::search::{
+        SearchFilters as ProtoSearchFilters, SearchMode, SearchOptions as ProtoSearchOptions,
+        SearchRequest as ProtoSearchRequest,
+    },
+    request_context::RequestContext,
 };
 
 /// Search request
@@ -20,11 +30,15 @@ pub struct SearchRequest {
     #[validate(length(min = 1, max = 1000))]
     pub query: String,
     
+    /// Session the search belongs to, for query analytics
+    #[serde(default)]
+    pub session_id: Option<Uuid>,
+    
     #[serde(default)]
     pub options: SearchOptions,
 }
 
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchOptions {
     /// Search mode: vector, bm25, hybrid (default)
     #[serde(default = "default_mode")]
@@ -42,25 +56,58 @@ pub struct SearchOptions {
     #[serde(default)]
     pub rerank: bool,
     
-    /// Minimum score threshold
+    /// Minimum score threshold, on the calibrated 0-1 scale
     #[serde(default)]
     pub min_score: Option<f64>,
     
+    /// Neighbouring chunks to return on each side of every hit
+    #[serde(default)]
+    pub expand_context: usize,
+    
+    /// Aggregate chunk hits into one result per paper
+    #[serde(default)]
+    pub group_by: Option<GroupBy>,
+    
     /// Filters
     #[serde(default)]
     pub filters: SearchFilters,
 }
 
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchFilters {
     pub source: Option<Vec<String>>,
     pub published_after: Option<String>,
     pub published_before: Option<String>,
+    /// Only papers in this collection
+    pub collection_id: Option<Uuid>,
 }
 
 fn default_mode() -> String { "hybrid".to_string() }
 fn default_limit() -> usize { 20 }
 
+/// Unit that search results are returned in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Paper,
+}
+
+/// Most neighbouring chunks returned on each side of a hit
+const MAX_EXPAND_CONTEXT: usize = 5;
+
+/// Chunk hits fetched per paper result when grouping by paper
+const CHUNKS_PER_PAPER: usize = 5;
+
+/// Most chunk hits fetched for one grouped search
+const MAX_GROUPED_CHUNKS: usize = 500;
+
+/// Seconds a query embedding stays cached; a model embeds a text the same way
+/// every time
+const QUERY_EMBEDDING_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Seconds a batch search result stays cached
+const BATCH_RESULT_TTL_SECS: u64 = 60;
+
 /// Search response
 #[derive(Serialize)]
 pub struct SearchResponse {
@@ -68,17 +115,75 @@ pub struct SearchResponse {
     pub mode: String,
     pub total_results: usize,
     pub results: Vec<SearchResultItem>,
+    /// Paper results, in place of `results`, when grouping by paper
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub papers: Option<Vec<PaperResultItem>>,
     pub processing_time_ms: u64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct SearchResultItem {
     pub chunk_id: Uuid,
     pub paper_id: Uuid,
     pub paper_title: String,
     pub content: String,
     pub chunk_index: i32,
+    /// `text`, `table`, `figure_caption` or `equation`
+    pub chunk_type: String,
+    /// Page and character offsets in the source PDF, for deep links into
+    /// a viewer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Relevance estimate between 0 and 1, comparable across modes
     pub score: f64,
+    /// Score as ranked by the retrieval mode
+    pub raw_score: f64,
+    /// Text chunks preceding the hit in its paper, when `expand_context` is set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_before: Vec<ContextChunk>,
+    /// Text chunks following the hit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_after: Vec<ContextChunk>,
+}
+
+impl From<ChunkResult> for SearchResultItem {
+    fn from(r: ChunkResult) -> Self {
+        Self {
+            chunk_id: r.chunk_id,
+            paper_id: r.paper_id,
+            paper_title: r.paper_title,
+            content: r.content,
+            chunk_index: r.chunk_index,
+            chunk_type: r.chunk_type,
+            provenance: r.provenance,
+            score: r.score,
+            raw_score: r.score,
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+        }
+    }
+}
+
+/// A paper and its matching chunks
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaperResultItem {
+    pub paper_id: Uuid,
+    pub paper_title: String,
+    /// Score of the best matching chunk, which ranks the paper
+    pub score: f64,
+    /// Mean score of the matching chunks
+    pub mean_score: f64,
+    pub matched_chunks: usize,
+    /// Best matching chunk
+    pub snippet: ContextChunk,
+}
+
+/// A chunk next to a search hit
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContextChunk {
+    pub chunk_id: Uuid,
+    pub chunk_index: i32,
+    pub content: String,
 }
 
 /// Batch search request
@@ -103,51 +208,136 @@ pub struct BatchSearchResponse {
     pub processing_time_ms: u64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct BatchSearchResult {
     pub query: String,
     pub results: Vec<SearchResultItem>,
+    /// Paper results, in place of `results`, when grouping by paper
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub papers: Option<Vec<PaperResultItem>>,
+}
+
+/// Relevance feedback on one search result
+#[derive(Debug, Deserialize, Validate)]
+pub struct FeedbackRequest {
+    /// Query the result was returned for
+    #[validate(length(min = 1, max = 1000))]
+    pub query: String,
+    
+    pub chunk_id: Uuid,
+    
+    #[serde(default)]
+    pub session_id: Option<Uuid>,
+    
+    pub rating: FeedbackRating,
+}
+
+/// Thumbs up or down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackRating {
+    Up,
+    Down,
+}
+
+impl FeedbackRating {
+    fn as_i16(self) -> i16 {
+        match self {
+            FeedbackRating::Up => 1,
+            FeedbackRating::Down => -1,
+        }
+    }
+}
+
+/// Recorded feedback
+#[derive(Serialize)]
+pub struct FeedbackResponse {
+    pub id: Uuid,
+    pub chunk_id: Uuid,
+    pub rating: FeedbackRating,
+    pub created_at: String,
 }
 
 /// Perform a search
 pub async fn search(
     State(state): State<AppState>,
     auth: AuthContext,
+    context: RequestContext,
     Json(request): Json<SearchRequest>,
 ) -> Result<Json<SearchResponse>> {
+    auth.require_scope("read")?;
     let start = Instant::now();
     
-    request.validate().map_err(|e| AppError::Validation {
-        message: e.to_string(),
-        field: None,
-    })?;
-    
-    let repo = Repository::new(state.db.clone());
-    
-    // Get embedding for the query (TODO: use actual embedder)
-    // For now, using mock embedding
-    let mock_embedding: Vec<f32> = (0..768).map(|i| (i as f32).sin()).collect();
-    
-    let results = match request.options.mode.as_str() {
-        "vector" => {
-            repo.vector_search(&mock_embedding, request.options.limit, Some(auth.tenant_id)).await?
+    request.validate()?;
+    validate_expand_context(request.options.expand_context)?;
+    let (query, author) = split_query(&request.query)?;
+    let scope = search_scope(&state, &auth, &request.options).await?.by_author(author);
+    if let Some(session_id) = request.session_id {
+        if state.sessions.find_active(auth.tenant_id, session_id).await?.is_none() {
+            return Err(AppError::SessionNotFound {
+                id: session_id.to_string(),
+            });
         }
-        "bm25" => {
-            repo.bm25_search(&request.query, request.options.limit, Some(auth.tenant_id)).await?
+    }
+    let fetch_limit = chunk_fetch_limit(&request.options, request.options.limit);
+    
+    let results = match state.search.clone() {
+        Some(client) => {
+            let options = SearchOptions {
+                limit: fetch_limit,
+                offset: if request.options.group_by.is_some() { 0 } else { request.options.offset },
+                ..request.options.clone()
+            };
+            search_remote(client, &context, &request.query, &options).await?
         }
-        "hybrid" | _ => {
-            repo.hybrid_search(&request.query, &mock_embedding, request.options.limit, Some(auth.tenant_id)).await?
+        None => {
+            let repo = Repository::new(state.db.clone());
+            
+            // Get embedding for the query (TODO: use actual embedder)
+            // For now, using mock embedding
+            let mock_embedding: Vec<f32> = (0..768).map(|i| (i as f32).sin()).collect();
+            
+            match request.options.mode.as_str() {
+                "vector" => {
+                    repo.vector_search(&mock_embedding, fetch_limit, scope).await?
+                }
+                "bm25" => {
+                    repo.bm25_search(&query, fetch_limit, scope).await?
+                }
+                "hybrid" | _ => {
+                    let weights = state.config.load().search.clone();
+                    repo.hybrid_search_weighted(
+                        &query,
+                        &mock_embedding,
+                        fetch_limit,
+                        scope,
+                        weights.vector_weight,
+                        weights.bm25_weight,
+                    ).await?
+                }
+            }
         }
     };
     
+    let mut results: Vec<SearchResultItem> = results.into_iter().map(Into::into).collect();
+    calibrate_scores(&state, &request.options.mode, &mut results);
+    
     // Apply min_score filter if specified
-    let results: Vec<_> = if let Some(min_score) = request.options.min_score {
-        results.into_iter()
-            .filter(|r| r.score >= min_score)
-            .collect()
-    } else {
-        results
+    if let Some(min_score) = request.options.min_score {
+        results.retain(|r| r.score >= min_score);
+    }
+    
+    let (mut results, papers, total_results) = match request.options.group_by {
+        Some(GroupBy::Paper) => {
+            let (papers, total) = group_by_paper(results, request.options.offset, request.options.limit);
+            (Vec::new(), Some(papers), total)
+        }
+        None => {
+            let total = results.len();
+            (results, None, total)
+        }
     };
+    let returned = papers.as_ref().map_or(results.len(), Vec::len);
     
     let processing_time_ms = start.elapsed().as_millis() as u64;
     
@@ -155,40 +345,293 @@ pub async fn search(
     metrics::record_search(
         processing_time_ms as f64 / 1000.0,
         &request.options.mode,
-        results.len(),
+        returned,
+        auth.tenant_id,
     );
     
     tracing::info!(
         query = %request.query,
         mode = %request.options.mode,
-        results = results.len(),
+        results = returned,
         latency_ms = processing_time_ms,
         tenant_id = %auth.tenant_id,
         "Search completed"
     );
     
+    state.analytics.record_search(SearchRecord {
+        tenant_id: auth.tenant_id,
+        session_id: request.session_id,
+        query: request.query.clone(),
+        mode: request.options.mode.clone(),
+        result_count: returned,
+        latency_ms: processing_time_ms,
+    });
+    
+    let repo = Repository::new(state.db.clone());
+    expand_context(&repo, &mut results, request.options.expand_context).await?;
+    
     Ok(Json(SearchResponse {
         query: request.query,
         mode: request.options.mode,
-        total_results: results.len(),
-        results: results.into_iter().map(|r| SearchResultItem {
-            chunk_id: r.chunk_id,
-            paper_id: r.paper_id,
-            paper_title: r.paper_title,
-            content: r.content,
-            chunk_index: r.chunk_index,
-            score: r.score,
-        }).collect(),
+        total_results,
+        results,
+        papers,
         processing_time_ms,
     }))
 }
 
+/// Papers a search covers: the caller's tenant, narrowed to a collection
+/// when the filters name one
+async fn search_scope(state: &AppState, auth: &AuthContext, options: &SearchOptions) -> Result<SearchScope> {
+    let collection_id = options.filters.collection_id;
+    if let Some(id) = collection_id {
+        let found = Repository::new(state.db.clone())
+            .find_collection(auth.tenant_id, id)
+            .await?;
+        if found.is_none() {
+            return Err(AppError::NotFound {
+                resource_type: "collection".to_string(),
+                id: id.to_string(),
+            });
+        }
+    }
+    Ok(SearchScope::tenant(auth.tenant_id).in_collection(collection_id))
+}
+
+/// Split an `author:` filter off a query, which must still have terms to
+/// search for
+fn split_query(query: &str) -> Result<(String, Option<String>)> {
+    let (text, author) = split_author_filter(query);
+    if text.is_empty() {
+        return Err(AppError::Validation {
+            message: "Query needs search terms besides the author: filter".to_string(),
+            field: Some("query".to_string()),
+        });
+    }
+    Ok((text, author))
+}
+
+/// Chunk hits to fetch for a page of `limit` results
+///
+/// Grouping by paper needs several chunks per paper, and enough to rank
+/// every paper up to the end of the requested page.
+fn chunk_fetch_limit(options: &SearchOptions, limit: usize) -> usize {
+    match options.group_by {
+        Some(GroupBy::Paper) => ((options.offset + limit) * CHUNKS_PER_PAPER).clamp(limit, MAX_GROUPED_CHUNKS),
+        None => limit,
+    }
+}
+
+/// Aggregate ranked chunk hits into a page of paper results
+///
+/// Papers rank by their best chunk's score; ties keep the order the papers
+/// first appeared in. Returns the page and the number of papers found.
+fn group_by_paper(items: Vec<SearchResultItem>, offset: usize, limit: usize) -> (Vec<PaperResultItem>, usize) {
+    let mut papers: Vec<PaperResultItem> = Vec::new();
+    let mut positions: HashMap<Uuid, usize> = HashMap::new();
+    
+    for item in items {
+        match positions.get(&item.paper_id) {
+            Some(&i) => {
+                let paper = &mut papers[i];
+                paper.mean_score += item.score;
+                paper.matched_chunks += 1;
+                if item.score > paper.score {
+                    paper.score = item.score;
+                    paper.snippet = ContextChunk {
+                        chunk_id: item.chunk_id,
+                        chunk_index: item.chunk_index,
+                        content: item.content,
+                    };
+                }
+            }
+            None => {
+                positions.insert(item.paper_id, papers.len());
+                papers.push(PaperResultItem {
+                    paper_id: item.paper_id,
+                    paper_title: item.paper_title,
+                    score: item.score,
+                    mean_score: item.score,
+                    matched_chunks: 1,
+                    snippet: ContextChunk {
+                        chunk_id: item.chunk_id,
+                        chunk_index: item.chunk_index,
+                        content: item.content,
+                    },
+                });
+            }
+        }
+    }
+    
+    for paper in &mut papers {
+        paper.mean_score /= paper.matched_chunks as f64;
+    }
+    papers.sort_by(|a, b| b.score.total_cmp(&a.score));
+    
+    let total = papers.len();
+    (papers.into_iter().skip(offset).take(limit).collect(), total)
+}
+
+/// Replace raw scores with calibrated ones and sample them for calibration
+///
+/// With calibration disabled, `score` stays the mode's raw score.
+fn calibrate_scores(state: &AppState, mode: &str, items: &mut [SearchResultItem]) {
+    if !state.config.load().search.calibrate_scores || items.is_empty() {
+        return;
+    }
+    
+    let raw: Vec<f64> = items.iter().map(|item| item.raw_score).collect();
+    state.calibrator.observe(mode, &raw);
+    for item in items.iter_mut() {
+        item.score = state.calibrator.calibrate(mode, item.raw_score);
+    }
+}
+
+fn validate_expand_context(n: usize) -> Result<()> {
+    if n > MAX_EXPAND_CONTEXT {
+        return Err(AppError::Validation {
+            message: format!("expand_context must be at most {}", MAX_EXPAND_CONTEXT),
+            field: Some("options.expand_context".to_string()),
+        });
+    }
+    Ok(())
+}
+
+/// Attach the `n` text chunks before and after each text hit
+///
+/// Neighbours are fetched with one ranged query per paper, covering all of
+/// that paper's hits. Table and caption hits have no running text around
+/// them and are left as they are.
+async fn expand_context(repo: &Repository, items: &mut [SearchResultItem], n: usize) -> Result<()> {
+    if n == 0 {
+        return Ok(());
+    }
+    let n = n as i32;
+    let text = String::from(ChunkType::Text);
+    
+    let mut ranges: HashMap<Uuid, (i32, i32)> = HashMap::new();
+    for item in items.iter().filter(|item| item.chunk_type == text) {
+        let range = ranges.entry(item.paper_id).or_insert((item.chunk_index, item.chunk_index));
+        range.0 = range.0.min(item.chunk_index);
+        range.1 = range.1.max(item.chunk_index);
+    }
+    
+    for (paper_id, (first, last)) in ranges {
+        let chunks = repo
+            .get_chunks_by_paper_range(paper_id, (first - n).max(0), last + n)
+            .await?;
+        for item in items.iter_mut().filter(|item| item.paper_id == paper_id && item.chunk_type == text) {
+            let (before, after) = neighbours(&chunks, item.chunk_index, n);
+            item.context_before = before;
+            item.context_after = after;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Text chunks within `n` positions before and after `index`
+fn neighbours(chunks: &[Chunk], index: i32, n: i32) -> (Vec<ContextChunk>, Vec<ContextChunk>) {
+    let text = String::from(ChunkType::Text);
+    let window = |range: std::ops::RangeInclusive<i32>| {
+        chunks
+            .iter()
+            .filter(|c| c.chunk_type == text && range.contains(&c.chunk_index))
+            .map(|c| ContextChunk {
+                chunk_id: c.id,
+                chunk_index: c.chunk_index,
+                content: c.content.clone(),
+            })
+            .collect()
+    };
+    (window(index - n..=index - 1), window(index + 1..=index + n))
+}
+
+/// Run a search on the search service on behalf of the caller
+///
+/// The call carries the request's context: tenant, request ID, locale,
+/// trace context, and the time left before its deadline.
+pub(crate) async fn search_remote(
+    mut client: SearchClient,
+    context: &RequestContext,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<Vec<ChunkResult>> {
+    let mode = match options.mode.as_str() {
+        "vector" => SearchMode::Vector,
+        "bm25" => SearchMode::Bm25,
+        _ => SearchMode::Hybrid,
+    };
+    
+    let mut grpc_request = tonic::Request::new(ProtoSearchRequest {
+        query: query.to_string(),
+        tenant_id: context.tenant_id.map(|id| id.to_string()).unwrap_or_default(),
+        query_embedding: Vec::new(),
+        options: Some(ProtoSearchOptions {
+            mode: mode as i32,
+            limit: options.limit as i32,
+            offset: options.offset as i32,
+            // min_score applies to calibrated scores, filtered by the caller
+            min_score: 0.0,
+            rerank: options.rerank,
+            filters: options.filters.collection_id.map(|id| ProtoSearchFilters {
+                collection_id: id.to_string(),
+                ..Default::default()
+            }),
+        }),
+    });
+    context.propagate(&mut grpc_request);
+    let timeout_ms = context.remaining().map_or(0, |r| r.as_millis() as u64);
+    
+    let response = client.search(grpc_request).await.map_err(|status| match status.code() {
+        tonic::Code::DeadlineExceeded => AppError::RequestTimeout { timeout_ms },
+        tonic::Code::PermissionDenied => AppError::Forbidden {
+            message: status.message().to_string(),
+        },
+        tonic::Code::InvalidArgument => AppError::Validation {
+            message: status.message().to_string(),
+            field: None,
+        },
+        _ => AppError::ServiceUnavailable {
+            message: format!("Search service error: {}", status.message()),
+        },
+    })?;
+    
+    Ok(response
+        .into_inner()
+        .results
+        .into_iter()
+        .filter_map(|r| {
+            Some(ChunkResult {
+                chunk_id: Uuid::parse_str(&r.chunk_id).ok()?,
+                paper_id: Uuid::parse_str(&r.paper_id).ok()?,
+                paper_title: r.paper_title,
+                content: r.content,
+                chunk_index: r.chunk_index,
+                score: r.score as f64,
+                embedding_model: String::new(),
+                chunk_type: if r.chunk_type.is_empty() {
+                    String::from(ChunkType::Text)
+                } else {
+                    r.chunk_type
+                },
+                provenance: r.provenance.map(Into::into),
+            })
+        })
+        .collect())
+}
+
 /// Batch search for multiple queries
+///
+/// Cached results, and cached embeddings for the remaining queries, are each
+/// looked up in one round trip; embeddings that aren't cached are computed in
+/// one batch.
 pub async fn batch_search(
     State(state): State<AppState>,
     auth: AuthContext,
     Json(request): Json<BatchSearchRequest>,
 ) -> Result<Json<BatchSearchResponse>> {
+    auth.require_scope("read")?;
     let start = Instant::now();
     
     if request.queries.len() > 10 {
@@ -197,43 +640,331 @@ pub async fn batch_search(
             field: Some("queries".to_string()),
         });
     }
+    validate_expand_context(request.options.expand_context)?;
+    let scope = search_scope(&state, &auth, &request.options).await?;
     
-    let repo = Repository::new(state.db.clone());
-    let mut batch_results = Vec::with_capacity(request.queries.len());
+    let generation = search_generation(&state, auth.tenant_id).await;
+    let result_keys: Vec<String> = request
+        .queries
+        .iter()
+        .map(|single| batch_result_key(auth.tenant_id, generation, single, &request.options))
+        .collect();
+    let mut batch_results = cached(&state, &result_keys).await;
+    let uncached: Vec<usize> = (0..batch_results.len()).filter(|&i| batch_results[i].is_none()).collect();
+    
+    let embeddings = if request.options.mode == "bm25" {
+        Vec::new()
+    } else {
+        let queries: Vec<&str> = uncached.iter().map(|&i| request.queries[i].query.as_str()).collect();
+        query_embeddings(&state, &queries).await?
+    };
     
-    for single in request.queries {
-        // Mock embedding for each query
-        let mock_embedding: Vec<f32> = (0..768).map(|i| (i as f32).sin()).collect();
+    let repo = Repository::new(state.db.clone());
+    let mut fresh = Vec::with_capacity(uncached.len());
+    for (n, &i) in uncached.iter().enumerate() {
+        let single = &request.queries[i];
+        let fetch_limit = chunk_fetch_limit(&request.options, single.limit);
+        let (query, author) = split_query(&single.query)?;
+        let scope = scope.clone().by_author(author);
         
-        let results = match request.options.mode.as_str() {
-            "vector" => {
-                repo.vector_search(&mock_embedding, single.limit, Some(auth.tenant_id)).await?
+        let results = match (request.options.mode.as_str(), embeddings.get(n)) {
+            ("bm25", _) | (_, None) => {
+                repo.bm25_search(&query, fetch_limit, scope).await?
             }
-            "bm25" => {
-                repo.bm25_search(&single.query, single.limit, Some(auth.tenant_id)).await?
+            ("vector", Some((embedding, model))) => {
+                repo.vector_search(embedding, fetch_limit, scope.with_model(model.clone())).await?
             }
-            "hybrid" | _ => {
-                repo.hybrid_search(&single.query, &mock_embedding, single.limit, Some(auth.tenant_id)).await?
+            (_, Some((embedding, model))) => {
+                let weights = state.config.load().search.clone();
+                repo.hybrid_search_weighted(
+                    &query,
+                    embedding,
+                    fetch_limit,
+                    scope.with_model(model.clone()),
+                    weights.vector_weight,
+                    weights.bm25_weight,
+                ).await?
             }
         };
         
-        batch_results.push(BatchSearchResult {
-            query: single.query,
-            results: results.into_iter().map(|r| SearchResultItem {
-                chunk_id: r.chunk_id,
-                paper_id: r.paper_id,
-                paper_title: r.paper_title,
-                content: r.content,
-                chunk_index: r.chunk_index,
-                score: r.score,
-            }).collect(),
-        });
+        let mut results: Vec<SearchResultItem> = results.into_iter().map(Into::into).collect();
+        calibrate_scores(&state, &request.options.mode, &mut results);
+        if let Some(min_score) = request.options.min_score {
+            results.retain(|r| r.score >= min_score);
+        }
+        
+        let result = if request.options.group_by == Some(GroupBy::Paper) {
+            let (papers, _) = group_by_paper(results, request.options.offset, single.limit);
+            BatchSearchResult {
+                query: single.query.clone(),
+                results: Vec::new(),
+                papers: Some(papers),
+            }
+        } else {
+            expand_context(&repo, &mut results, request.options.expand_context).await?;
+            BatchSearchResult {
+                query: single.query.clone(),
+                results,
+                papers: None,
+            }
+        };
+        fresh.push((result_keys[i].clone(), result));
+    }
+    
+    if let Some(cache) = &state.cache {
+        if let Err(e) = cache.set_many(&fresh, BATCH_RESULT_TTL_SECS).await {
+            tracing::warn!(error = %e, "Failed to cache batch search results");
+        }
+    }
+    for ((_, result), i) in fresh.into_iter().zip(uncached) {
+        batch_results[i] = Some(result);
     }
     
     let processing_time_ms = start.elapsed().as_millis() as u64;
     
     Ok(Json(BatchSearchResponse {
-        results: batch_results,
+        results: batch_results.into_iter().flatten().collect(),
         processing_time_ms,
     }))
 }
+
+/// The tenant's search result generation, 0 without a cache
+///
+/// A failed read also gives 0; results cached under it may be stale for up
+/// to `BATCH_RESULT_TTL_SECS`.
+async fn search_generation(state: &AppState, tenant_id: Uuid) -> u64 {
+    let Some(cache) = &state.cache else {
+        return 0;
+    };
+    cache.search_generation(tenant_id).await.unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Failed to read search generation");
+        0
+    })
+}
+
+/// Cache key of one query's results in a batch search
+fn batch_result_key(tenant_id: Uuid, generation: u64, single: &SingleQuery, options: &SearchOptions) -> String {
+    let request = serde_json::json!({
+        "query": single.query,
+        "limit": single.limit,
+        "options": options,
+    });
+    keys::search_query(tenant_id, generation, &keys::digest(&request.to_string()), &options.mode)
+}
+
+/// Values cached under `keys`, all misses without a cache
+///
+/// A cache error is logged and treated as all misses.
+async fn cached<T: serde::de::DeserializeOwned>(state: &AppState, keys: &[String]) -> Vec<Option<T>> {
+    let cached = match &state.cache {
+        Some(cache) => cache.get_many(keys).await.map_err(|e| {
+            tracing::warn!(error = %e, "Failed to read cache, continuing without it");
+        }),
+        None => Err(()),
+    };
+    cached.unwrap_or_else(|()| keys.iter().map(|_| None).collect())
+}
+
+/// Embed queries, reusing cached embeddings
+///
+/// Returns each query's embedding and the model that produced it.
+async fn query_embeddings(state: &AppState, queries: &[&str]) -> Result<Vec<(Vec<f32>, String)>> {
+    if queries.is_empty() {
+        return Ok(Vec::new());
+    }
+    
+    let model = state.embedder.model_name().to_string();
+    let prepared: Vec<String> = queries
+        .iter()
+        .map(|query| state.preprocessor.apply(query, InputKind::Query))
+        .collect();
+    let embedding_keys: Vec<String> = prepared
+        .iter()
+        .map(|text| keys::embedding(&keys::digest(text), &model))
+        .collect();
+    
+    let mut embeddings: Vec<Option<(Vec<f32>, String)>> = cached::<Vec<f32>>(state, &embedding_keys)
+        .await
+        .into_iter()
+        .map(|embedding| embedding.map(|e| (e, model.clone())))
+        .collect();
+    
+    let missing: Vec<usize> = (0..embeddings.len()).filter(|&i| embeddings[i].is_none()).collect();
+    if !missing.is_empty() {
+        let texts: Vec<String> = missing.iter().map(|&i| prepared[i].clone()).collect();
+        let tagged = state.embedder.embed_batch_tagged(&texts).await?;
+        
+        // Only the primary model's embeddings are cached under its name
+        if tagged.model == model {
+            if let Some(cache) = &state.cache {
+                let entries: Vec<(String, &Vec<f32>)> = missing
+                    .iter()
+                    .zip(&tagged.embeddings)
+                    .map(|(&i, embedding)| (embedding_keys[i].clone(), embedding))
+                    .collect();
+                if let Err(e) = cache.set_many(&entries, QUERY_EMBEDDING_TTL_SECS).await {
+                    tracing::warn!(error = %e, "Failed to cache query embeddings");
+                }
+            }
+        }
+        for (&i, embedding) in missing.iter().zip(tagged.embeddings) {
+            embeddings[i] = Some((embedding, tagged.model.clone()));
+        }
+    }
+    
+    embeddings
+        .into_iter()
+        .map(|embedding| embedding.ok_or_else(|| AppError::EmbeddingError {
+            message: "Empty response".to_string(),
+        }))
+        .collect()
+}
+
+/// Record a thumbs up or down on a search result
+///
+/// Feedback nudges the chunk's hybrid score for later searches by the
+/// tenant, most strongly for the same query.
+pub async fn submit_feedback(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<FeedbackRequest>,
+) -> Result<(StatusCode, Json<FeedbackResponse>)> {
+    auth.require_scope("write")?;
+    request.validate()?;
+    
+    let repo = Repository::new(state.db.clone());
+    if !repo.chunk_belongs_to_tenant(request.chunk_id, auth.tenant_id).await? {
+        return Err(AppError::NotFound {
+            resource_type: "chunk".to_string(),
+            id: request.chunk_id.to_string(),
+        });
+    }
+    
+    if let Some(session_id) = request.session_id {
+        if state.sessions.find_active(auth.tenant_id, session_id).await?.is_none() {
+            return Err(AppError::SessionNotFound {
+                id: session_id.to_string(),
+            });
+        }
+    }
+    
+    let feedback = repo.record_feedback(
+        auth.tenant_id,
+        request.session_id,
+        &request.query,
+        request.chunk_id,
+        request.rating.as_i16(),
+    ).await?;
+    
+    tracing::info!(
+        chunk_id = %feedback.chunk_id,
+        rating = feedback.rating,
+        tenant_id = %auth.tenant_id,
+        "Search feedback recorded"
+    );
+    
+    Ok((StatusCode::CREATED, Json(FeedbackResponse {
+        id: feedback.id,
+        chunk_id: feedback.chunk_id,
+        rating: request.rating,
+        created_at: feedback.created_at.to_rfc3339(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn chunk(index: i32, chunk_type: ChunkType) -> Chunk {
+        Chunk {
+            id: Uuid::new_v4(),
+            paper_id: Uuid::nil(),
+            chunk_index: index,
+            content: format!("chunk {}", index),
+            embedding: None,
+            embedding_model: String::new(),
+            embedding_version: 1,
+            token_count: 2,
+            char_offset_start: None,
+            char_offset_end: None,
+            chunk_type: chunk_type.into(),
+            metadata: serde_json::json!({}),
+            created_at: chrono::Utc::now().into(),
+        }
+    }
+    
+    #[test]
+    fn test_neighbours() {
+        let mut chunks: Vec<Chunk> = (0..6).map(|i| chunk(i, ChunkType::Text)).collect();
+        chunks.push(chunk(6, ChunkType::Table));
+        
+        let (before, after) = neighbours(&chunks, 1, 2);
+        assert_eq!(before.iter().map(|c| c.chunk_index).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(after.iter().map(|c| c.chunk_index).collect::<Vec<_>>(), vec![2, 3]);
+        
+        // The table after the last text chunk isn't running text
+        let (_, after) = neighbours(&chunks, 5, 2);
+        assert!(after.is_empty());
+    }
+    
+    #[test]
+    fn test_group_by_paper() {
+        let item = |paper: u128, index: i32, score: f64| SearchResultItem {
+            chunk_id: Uuid::new_v4(),
+            paper_id: Uuid::from_u128(paper),
+            paper_title: format!("Paper {}", paper),
+            content: format!("chunk {}", index),
+            chunk_index: index,
+            chunk_type: String::from(ChunkType::Text),
+            provenance: None,
+            score,
+            raw_score: score,
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+        };
+        let items = vec![item(1, 0, 0.9), item(2, 4, 0.8), item(1, 7, 0.5), item(3, 2, 0.95), item(2, 5, 0.6)];
+        
+        let (papers, total) = group_by_paper(items, 0, 2);
+        assert_eq!(total, 3);
+        assert_eq!(papers.len(), 2);
+        assert_eq!(papers[0].paper_id, Uuid::from_u128(3));
+        assert_eq!(papers[1].paper_id, Uuid::from_u128(1));
+        assert_eq!(papers[1].matched_chunks, 2);
+        assert!((papers[1].mean_score - 0.7).abs() < 1e-9);
+        assert_eq!(papers[1].snippet.chunk_index, 0);
+        
+        let options = SearchOptions { group_by: Some(GroupBy::Paper), offset: 20, ..Default::default() };
+        assert_eq!(chunk_fetch_limit(&options, 20), 200);
+        assert_eq!(chunk_fetch_limit(&SearchOptions::default(), 20), 20);
+    }
+    
+    #[test]
+    fn test_batch_result_key() {
+        let tenant_id = Uuid::new_v4();
+        let single = |query: &str, limit| SingleQuery { query: query.to_string(), limit };
+        let options = SearchOptions::default();
+        
+        let key = batch_result_key(tenant_id, 0, &single("attention", 10), &options);
+        assert_eq!(key, batch_result_key(tenant_id, 0, &single("attention", 10), &options));
+        assert_ne!(key, batch_result_key(tenant_id, 0, &single("attention", 20), &options));
+        assert_ne!(key, batch_result_key(tenant_id, 0, &single("attention", 10), &SearchOptions {
+            min_score: Some(0.5),
+            ..Default::default()
+        }));
+        assert_ne!(key, batch_result_key(Uuid::new_v4(), 0, &single("attention", 10), &options));
+        assert_ne!(key, batch_result_key(tenant_id, 1, &single("attention", 10), &options));
+    }
+    
+    #[test]
+    fn test_feedback_rating() {
+        let request: FeedbackRequest = serde_json::from_str(&format!(
+            r#"{{"query": "graph neural networks", "chunk_id": "{}", "rating": "down"}}"#,
+            Uuid::new_v4()
+        )).unwrap();
+        assert_eq!(request.rating, FeedbackRating::Down);
+        assert_eq!(request.rating.as_i16(), -1);
+        assert_eq!(request.session_id, None);
+        
+        assert!(serde_json::from_str::<FeedbackRating>(r#""sideways""#).is_err());
+    }
+}
@@ -7,11 +7,11 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::AppState;
 use paperforge_common::{
     auth::AuthContext,
-    db::Repository,
     errors::{AppError, Result},
 };
 
@@ -40,8 +40,10 @@ pub struct SessionResponse {
 }
 
 /// Track event request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct TrackEventRequest {
+    /// Event type, such as `click`, `view_paper` or `query`
+    #[validate(length(min = 1, max = 64))]
     pub event: String,
     pub data: serde_json::Value,
 }
@@ -52,7 +54,7 @@ pub async fn create_session(
     auth: AuthContext,
     Json(request): Json<CreateSessionRequest>,
 ) -> Result<(StatusCode, Json<CreateSessionResponse>)> {
-    let repo = Repository::new(state.db.clone());
+    auth.require_scope("write")?;
     let session_id = Uuid::new_v4();
     
     let initial_state = serde_json::json!({
@@ -63,12 +65,7 @@ pub async fn create_session(
         "metadata": request.metadata,
     });
     
-    let session = repo.upsert_session(
-        auth.tenant_id,
-        session_id,
-        initial_state,
-        30, // 30 minute TTL
-    ).await?;
+    let session = state.sessions.save(auth.tenant_id, session_id, initial_state).await?;
     
     tracing::info!(
         session_id = %session_id,
@@ -88,9 +85,8 @@ pub async fn get_session(
     auth: AuthContext,
     Path(session_id): Path<Uuid>,
 ) -> Result<Json<SessionResponse>> {
-    let repo = Repository::new(state.db.clone());
-    
-    let session = repo.find_session(session_id)
+    auth.require_scope("read")?;
+    let session = state.sessions.find(session_id)
         .await?
         .ok_or_else(|| AppError::SessionNotFound { 
             id: session_id.to_string() 
@@ -124,9 +120,10 @@ pub async fn track_event(
     Path(session_id): Path<Uuid>,
     Json(request): Json<TrackEventRequest>,
 ) -> Result<StatusCode> {
-    let repo = Repository::new(state.db.clone());
+    auth.require_scope("write")?;
+    request.validate()?;
     
-    let session = repo.find_session(session_id)
+    let session = state.sessions.find(session_id)
         .await?
         .ok_or_else(|| AppError::SessionNotFound { 
             id: session_id.to_string() 
@@ -137,26 +134,33 @@ pub async fn track_event(
         return Err(AppError::TenantMismatch);
     }
     
+    // Expired sessions aren't revived; they're swept
+    if session.is_expired() {
+        return Err(AppError::SessionNotFound { 
+            id: session_id.to_string() 
+        });
+    }
+    
     // Update session state with event
-    let mut state = session.state.clone();
+    let mut session_state = session.state;
     
     match request.event.as_str() {
         "click" => {
-            if let Some(clicked) = state.get_mut("clicked_results") {
+            if let Some(clicked) = session_state.get_mut("clicked_results") {
                 if let Some(arr) = clicked.as_array_mut() {
                     arr.push(request.data.clone());
                 }
             }
         }
         "view_paper" => {
-            if let Some(viewed) = state.get_mut("viewed_papers") {
+            if let Some(viewed) = session_state.get_mut("viewed_papers") {
                 if let Some(arr) = viewed.as_array_mut() {
                     arr.push(request.data.clone());
                 }
             }
         }
         "query" => {
-            if let Some(queries) = state.get_mut("queries") {
+            if let Some(queries) = session_state.get_mut("queries") {
                 if let Some(arr) = queries.as_array_mut() {
                     arr.push(serde_json::json!({
                         "query": request.data.get("query"),
@@ -171,7 +175,8 @@ pub async fn track_event(
     }
     
     // Update session
-    repo.upsert_session(auth.tenant_id, session_id, state, 30).await?;
+    state.sessions.save(auth.tenant_id, session_id, session_state).await?;
+    state.analytics.record_event(auth.tenant_id, Some(session_id), &request.event, request.data);
     
     tracing::debug!(
         session_id = %session_id,
@@ -0,0 +1,56 @@
+//! Signed document links for the local storage backend
+//!
+//! The S3 backend hands out presigned S3 URLs; the local backend's links
+//! point here instead. Requests carry no credentials, only the link's
+//! expiry and signature.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+
+use crate::AppState;
+use paperforge_common::errors::{AppError, Result};
+
+/// Signature of a document link
+#[derive(Debug, Deserialize)]
+pub struct LinkQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+/// Serve a stored document through a signed link
+pub async fn get_object(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    Query(link): Query<LinkQuery>,
+) -> Result<impl IntoResponse> {
+    let storage = state.storage.as_ref().ok_or_else(|| AppError::NotFound {
+        resource_type: "document".to_string(),
+        id: key.clone(),
+    })?;
+    
+    if !storage.verify_link(&key, link.expires, &link.signature) {
+        return Err(AppError::Forbidden {
+            message: "Invalid or expired document link".to_string(),
+        });
+    }
+    
+    let body = storage.get(&key).await?.ok_or_else(|| AppError::NotFound {
+        resource_type: "document".to_string(),
+        id: key.clone(),
+    })?;
+    
+    Ok(([(header::CONTENT_TYPE, content_type(&key))], body))
+}
+
+/// Content type from the object name
+fn content_type(key: &str) -> &'static str {
+    match key.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("pdf") => "application/pdf",
+        Some("txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,315 @@
+//! Tenant settings handlers
+
+use axum::{extract::State, Json};
+use chrono::Utc;
+use serde::{Deserialize, Deserializer, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use paperforge_common::{
+    audit::AuditEvent,
+    auth::AuthContext,
+    chunking::ChunkStrategy,
+    config::AppConfig,
+    db::{models::TenantSettings, Repository},
+    errors::{AppError, Result},
+};
+
+/// Ingestion options applied when a request leaves them out
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IngestionDefaults {
+    pub chunk_strategy: String,
+    pub chunk_size: usize,
+    pub chunk_overlap: usize,
+    pub embedding_model: String,
+    /// Whether the reference list is left out of chunks
+    pub exclude_references: bool,
+}
+
+impl IngestionDefaults {
+    /// Tenant settings, falling back to the service configuration
+    pub fn resolve(settings: Option<&TenantSettings>, config: &AppConfig) -> Self {
+        let ingestion = &config.ingestion;
+        Self {
+            chunk_strategy: settings
+                .and_then(|s| s.chunk_strategy.clone())
+                .unwrap_or_else(|| ingestion.chunk_strategy.clone()),
+            chunk_size: settings
+                .and_then(|s| s.chunk_size)
+                .map_or(ingestion.chunk_size, |size| size as usize),
+            chunk_overlap: settings
+                .and_then(|s| s.chunk_overlap)
+                .map_or(ingestion.chunk_overlap, |overlap| overlap as usize),
+            embedding_model: settings
+                .and_then(|s| s.embedding_model.clone())
+                .unwrap_or_else(|| config.embedding.model.clone()),
+            exclude_references: settings
+                .and_then(|s| s.exclude_references)
+                .unwrap_or(ingestion.exclude_references),
+        }
+    }
+    
+    /// Defaults for `tenant_id`
+    pub async fn load(state: &AppState, tenant_id: Uuid) -> Result<Self> {
+        let settings = Repository::new(state.db.clone())
+            .find_tenant_settings(tenant_id)
+            .await?;
+        Ok(Self::resolve(settings.as_ref(), &state.config.load()))
+    }
+}
+
+/// Stored settings and the defaults they produce
+#[derive(Serialize)]
+pub struct TenantSettingsResponse {
+    /// Stored values; `null` means the service default applies
+    pub chunk_strategy: Option<String>,
+    pub chunk_size: Option<i32>,
+    pub chunk_overlap: Option<i32>,
+    pub embedding_model: Option<String>,
+    /// Monthly LLM and embedding budget; `null` means unlimited
+    pub monthly_budget_usd: Option<f64>,
+    pub exclude_references: Option<bool>,
+    pub updated_at: Option<String>,
+    /// Values applied to new ingestion jobs
+    pub effective: IngestionDefaults,
+}
+
+impl TenantSettingsResponse {
+    fn new(settings: Option<TenantSettings>, config: &AppConfig) -> Self {
+        let effective = IngestionDefaults::resolve(settings.as_ref(), config);
+        match settings {
+            Some(settings) => Self {
+                chunk_strategy: settings.chunk_strategy,
+                chunk_size: settings.chunk_size,
+                chunk_overlap: settings.chunk_overlap,
+                embedding_model: settings.embedding_model,
+                monthly_budget_usd: settings.monthly_budget_micros.map(|micros| micros as f64 / 1_000_000.0),
+                exclude_references: settings.exclude_references,
+                updated_at: Some(settings.updated_at.to_rfc3339()),
+                effective,
+            },
+            None => Self {
+                chunk_strategy: None,
+                chunk_size: None,
+                chunk_overlap: None,
+                embedding_model: None,
+                monthly_budget_usd: None,
+                exclude_references: None,
+                updated_at: None,
+                effective,
+            },
+        }
+    }
+}
+
+/// Reject an embedding model the deployment doesn't serve
+///
+/// Jobs are embedded by the worker's configured providers whatever model
+/// they ask for, so any other model would be silently ignored.
+pub fn check_embedding_model(model: &str, config: &AppConfig, field: &str) -> Result<()> {
+    let served = config.embedding.served_models();
+    if served.contains(&model) {
+        return Ok(());
+    }
+    Err(AppError::Validation {
+        message: format!(
+            "Embedding model {} is not served by this deployment; use one of: {}",
+            model,
+            served.join(", ")
+        ),
+        field: Some(field.to_string()),
+    })
+}
+
+/// Request to change tenant settings
+///
+/// Absent fields are left unchanged; `null` resets a field to the service
+/// default.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct UpdateTenantSettingsRequest {
+    #[serde(default, deserialize_with = "nullable")]
+    pub chunk_strategy: Option<Option<String>>,
+    
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(range(min = 100, max = 10000))]
+    pub chunk_size: Option<Option<usize>>,
+    
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(range(max = 5000))]
+    pub chunk_overlap: Option<Option<usize>>,
+    
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(length(min = 1, max = 200))]
+    pub embedding_model: Option<Option<String>>,
+    
+    #[serde(default, deserialize_with = "nullable")]
+    #[validate(range(min = 0.0))]
+    pub monthly_budget_usd: Option<Option<f64>>,
+    
+    #[serde(default, deserialize_with = "nullable")]
+    pub exclude_references: Option<Option<bool>>,
+}
+
+/// Tell an explicit `null` (`Some(None)`) from an absent field (`None`)
+pub(crate) fn nullable<'de, T, D>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Get the caller's tenant settings
+pub async fn get_settings(
+    State(state): State<AppState>,
+    auth: AuthContext,
+) -> Result<Json<TenantSettingsResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    let settings = repo.find_tenant_settings(auth.tenant_id).await?;
+    
+    Ok(Json(TenantSettingsResponse::new(settings, &state.config.load())))
+}
+
+/// Update the caller's tenant settings
+pub async fn update_settings(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Json(request): Json<UpdateTenantSettingsRequest>,
+) -> Result<Json<TenantSettingsResponse>> {
+    auth.require_scope("write")?;
+    request.validate()?;
+    if let Some(Some(strategy)) = &request.chunk_strategy {
+        strategy.parse::<ChunkStrategy>().map_err(|message| AppError::Validation {
+            message,
+            field: Some("chunk_strategy".to_string()),
+        })?;
+    }
+    
+    let config = state.config.load_full();
+    if let Some(Some(model)) = &request.embedding_model {
+        check_embedding_model(model, &config, "embedding_model")?;
+    }
+    
+    let repo = Repository::new(state.db.clone());
+    let before = repo.find_tenant_settings(auth.tenant_id).await?;
+    let mut settings = before.clone().unwrap_or_else(|| TenantSettings {
+        tenant_id: auth.tenant_id,
+        chunk_strategy: None,
+        chunk_size: None,
+        chunk_overlap: None,
+        embedding_model: None,
+        monthly_budget_micros: None,
+        exclude_references: None,
+        updated_at: Utc::now().into(),
+    });
+    
+    if let Some(strategy) = request.chunk_strategy {
+        settings.chunk_strategy = strategy;
+    }
+    if let Some(size) = request.chunk_size {
+        settings.chunk_size = size.map(|size| size as i32);
+    }
+    if let Some(overlap) = request.chunk_overlap {
+        settings.chunk_overlap = overlap.map(|overlap| overlap as i32);
+    }
+    if let Some(model) = request.embedding_model {
+        settings.embedding_model = model;
+    }
+    if let Some(budget) = request.monthly_budget_usd {
+        settings.monthly_budget_micros = budget.map(|usd| (usd * 1_000_000.0).round() as i64);
+    }
+    if let Some(exclude) = request.exclude_references {
+        settings.exclude_references = exclude;
+    }
+    
+    let effective = IngestionDefaults::resolve(Some(&settings), &config);
+    if effective.chunk_overlap >= effective.chunk_size {
+        return Err(AppError::Validation {
+            message: format!(
+                "Chunk overlap ({}) must be smaller than chunk size ({})",
+                effective.chunk_overlap, effective.chunk_size
+            ),
+            field: Some("chunk_overlap".to_string()),
+        });
+    }
+    
+    let settings = repo.save_tenant_settings(settings).await?;
+    state.usage.invalidate(auth.tenant_id);
+    
+    state.audit.record(
+        AuditEvent::new("tenant.settings.update", "tenant_settings")
+            .by(&auth)
+            .resource_id(auth.tenant_id)
+            .before(&before)
+            .after(&settings),
+    ).await;
+    
+    Ok(Json(TenantSettingsResponse::new(Some(settings), &config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paperforge_common::config::EmbeddingProviderConfig;
+    
+    #[test]
+    fn test_settings_fall_back_to_config() {
+        let config = AppConfig::default();
+        let settings = TenantSettings {
+            tenant_id: Uuid::new_v4(),
+            chunk_strategy: Some("semantic".to_string()),
+            chunk_size: Some(600),
+            chunk_overlap: None,
+            embedding_model: None,
+            monthly_budget_micros: None,
+            exclude_references: Some(false),
+            updated_at: Utc::now().into(),
+        };
+        
+        let defaults = IngestionDefaults::resolve(Some(&settings), &config);
+        assert_eq!(defaults.chunk_strategy, "semantic");
+        assert_eq!(defaults.chunk_size, 600);
+        assert_eq!(defaults.chunk_overlap, config.ingestion.chunk_overlap);
+        assert_eq!(defaults.embedding_model, config.embedding.model);
+        assert!(!defaults.exclude_references);
+        
+        assert_eq!(
+            IngestionDefaults::resolve(None, &config).chunk_size,
+            config.ingestion.chunk_size
+        );
+        assert!(IngestionDefaults::resolve(None, &config).exclude_references);
+    }
+    
+    #[test]
+    fn test_only_served_embedding_models_are_accepted() {
+        let mut config = AppConfig::default();
+        config.embedding.model = "text-embedding-3-small".to_string();
+        config.embedding.fallbacks = vec![EmbeddingProviderConfig {
+            provider: "bedrock".to_string(),
+            api_key: None,
+            api_base: None,
+            model: "amazon.titan-embed-text-v2:0".to_string(),
+            dimensions: None,
+        }];
+        
+        assert!(check_embedding_model("text-embedding-3-small", &config, "embedding_model").is_ok());
+        assert!(check_embedding_model("amazon.titan-embed-text-v2:0", &config, "embedding_model").is_ok());
+        let err = check_embedding_model("nomic-embed-text", &config, "embedding_model").unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::Validation { ref message, field: Some(ref field) }
+                if field == "embedding_model" && message.contains("text-embedding-3-small")
+        ));
+    }
+    
+    #[test]
+    fn test_update_request_tells_null_from_absent() {
+        let request: UpdateTenantSettingsRequest =
+            serde_json::from_str(r#"{"chunk_size": null, "embedding_model": "nomic-embed-text"}"#).unwrap();
+        assert_eq!(request.chunk_size, Some(None));
+        assert_eq!(request.chunk_overlap, None);
+        assert_eq!(request.embedding_model, Some(Some("nomic-embed-text".to_string())));
+    }
+}
@@ -0,0 +1,97 @@
+//! Usage and cost handlers
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+use paperforge_common::{
+    auth::AuthContext,
+    db::{Repository, UsageTotals},
+    errors::Result,
+    usage::month_start,
+};
+
+/// Query parameters for usage
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// Start of the period; defaults to the start of the current month (UTC)
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// Usage of one model
+#[derive(Debug, Serialize)]
+pub struct ModelUsage {
+    pub kind: String,
+    pub model: String,
+    pub calls: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub cost_usd: f64,
+}
+
+impl From<UsageTotals> for ModelUsage {
+    fn from(totals: UsageTotals) -> Self {
+        Self {
+            kind: totals.kind,
+            model: totals.model,
+            calls: totals.calls,
+            prompt_tokens: totals.prompt_tokens,
+            completion_tokens: totals.completion_tokens,
+            cost_usd: micros_to_usd(totals.cost_micros),
+        }
+    }
+}
+
+/// The caller's usage over a period
+#[derive(Debug, Serialize)]
+pub struct UsageResponse {
+    pub period_start: String,
+    pub total_cost_usd: f64,
+    /// Monthly budget; `null` means unlimited
+    pub monthly_budget_usd: Option<f64>,
+    /// Budget left this month
+    pub budget_remaining_usd: Option<f64>,
+    pub by_model: Vec<ModelUsage>,
+}
+
+fn micros_to_usd(micros: i64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+/// Get the caller's LLM and embedding usage
+pub async fn get_usage(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageResponse>> {
+    auth.require_scope("read")?;
+    let repo = Repository::new(state.db.clone());
+    let period_start = query.since.unwrap_or_else(|| month_start(Utc::now()));
+    
+    let totals = repo.usage_by_model(auth.tenant_id, period_start).await?;
+    let total_cost_micros: i64 = totals.iter().map(|t| t.cost_micros).sum();
+    
+    let budget_micros = repo
+        .find_tenant_settings(auth.tenant_id)
+        .await?
+        .and_then(|settings| settings.monthly_budget_micros);
+    let budget_remaining_usd = match budget_micros {
+        Some(budget) => {
+            let spent = repo.tenant_spend_since(auth.tenant_id, month_start(Utc::now())).await?;
+            Some(micros_to_usd((budget - spent).max(0)))
+        }
+        None => None,
+    };
+    
+    Ok(Json(UsageResponse {
+        period_start: period_start.to_rfc3339(),
+        total_cost_usd: micros_to_usd(total_cost_micros),
+        monthly_budget_usd: budget_micros.map(micros_to_usd),
+        budget_remaining_usd,
+        by_model: totals.into_iter().map(ModelUsage::from).collect(),
+    }))
+}
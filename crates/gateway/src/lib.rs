@@ -0,0 +1,432 @@
+//! PaperForge API Gateway
+//!
+//! Handlers, middleware and router behind the `gateway` binary. Integration
+//! tests build the same [`AppState`] and router in-process.
+
+pub mod archive;
+pub mod handlers;
+pub mod middleware;
+
+use axum::{
+    error_handling::HandleErrorLayer,
+    extract::{DefaultBodyLimit, FromRef},
+    routing::{delete, get, patch, post},
+    BoxError, Router,
+};
+use paperforge_common::{
+    analytics::Analytics,
+    audit::AuditLogger,
+    auth::{signature_middleware, AuthState, ServiceTokenInterceptor},
+    cache::{Cache, CacheConfig},
+    calibration::ScoreCalibrator,
+    config::{AppConfig, SharedConfig},
+    context::{LLMConfig, LlmClient, QueryDictionaries, QueryParser, QueryParserConfig},
+    db::{DbPool, Repository},
+    errors::AppError,
+    embeddings::{create_embedder_chain, Embedder, HashEmbedder, MockEmbedder, Preprocessor},
+    proto::search::search_service_client::SearchServiceClient,
+    queue::{Queue, QueueConfig},
+    sessions::SessionStore,
+    slo::SloTracker,
+    storage::{create_store, ObjectStore},
+    usage::{MeteredEmbedder, UsageMeter},
+};
+use std::sync::Arc;
+use tonic::{service::interceptor::InterceptedService, transport::Channel};
+use tonic_health::pb::health_client::HealthClient;
+use tower::{limit::GlobalConcurrencyLimitLayer, ServiceBuilder};
+use tower_http::{
+    compression::{
+        predicate::{NotForContentType, SizeAbove},
+        CompressionLayer, DefaultPredicate, Predicate,
+    },
+    cors::{Any, CorsLayer},
+    limit::RequestBodyLimitLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
+use tracing::info;
+
+use crate::middleware::consistency::read_your_writes_middleware;
+use crate::middleware::deadline::deadline_middleware;
+use crate::middleware::error_response::error_response_middleware;
+use crate::middleware::overload::overload_error;
+use crate::middleware::rate_limit::{rate_limit_middleware, ReloadableRateLimiter, TenantRateLimiter};
+use crate::middleware::request_context::request_context_middleware;
+use crate::middleware::slo::slo_middleware;
+use crate::middleware::usage::usage_scope_middleware;
+
+/// Application state shared across handlers
+#[derive(Clone)]
+pub struct AppState {
+    /// Current configuration; reload-safe settings change at runtime
+    pub config: SharedConfig,
+    pub db: DbPool,
+    pub auth: AuthState,
+    pub audit: AuditLogger,
+    /// Logs searches and session events for query analytics
+    pub analytics: Analytics,
+    /// Ingestion queue, for DLQ administration; `None` when not configured
+    pub queue: Option<Arc<Queue>>,
+    pub rate_limiter: Arc<ReloadableRateLimiter>,
+    /// Per-tenant limit on chunk exports
+    pub export_limiter: Arc<TenantRateLimiter>,
+    /// Redis cache; `None` when Redis is unreachable
+    pub cache: Option<Arc<Cache>>,
+    /// Search service client; searches run in-process when not configured
+    pub search: Option<SearchClient>,
+    /// Search service's gRPC health endpoint, for readiness
+    pub search_health: Option<HealthClient<Channel>>,
+    /// Original document storage; `None` when disabled
+    pub storage: Option<Arc<dyn ObjectStore>>,
+    /// Query understanding for intelligent search
+    pub query_parser: Arc<QueryParser>,
+    /// LLM for the context engine; `None` when no API key is configured
+    pub llm: Option<Arc<LlmClient>>,
+    /// Embeds queries for intelligent search
+    pub embedder: Arc<dyn Embedder>,
+    /// Prepares text for `embedder` the way the embedding worker does
+    pub preprocessor: Arc<Preprocessor>,
+    /// Meters LLM and embedding usage per tenant
+    pub usage: Arc<UsageMeter>,
+    /// Maps raw search scores to 0-1 relevance estimates
+    pub calibrator: Arc<ScoreCalibrator>,
+    /// Context engine sessions
+    pub sessions: SessionStore,
+    /// Rolling error rate and latency against the SLOs
+    pub slo: Arc<SloTracker>,
+}
+
+/// gRPC client for the search service, authenticated with the service token
+pub type SearchClient = SearchServiceClient<InterceptedService<Channel, ServiceTokenInterceptor>>;
+
+impl FromRef<AppState> for AuthState {
+    fn from_ref(state: &AppState) -> Self {
+        state.auth.clone()
+    }
+}
+
+impl AppState {
+    /// Connect the gateway's dependencies as `config` describes
+    ///
+    /// Background work only a running server needs (outbox relay, queue
+    /// depth and SLO publishing, session sweeping) is left to the caller.
+    pub async fn from_config(
+        config: &AppConfig,
+        shared: SharedConfig,
+        rate_limiter: Arc<ReloadableRateLimiter>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        // Initialize database connection
+        info!("Connecting to database...");
+        let db = DbPool::new(&config.database)
+            .await?
+            .with_vector_storage(config.embedding.vector_storage())
+            .with_embedding_dimension(config.embedding.dimension);
+        
+        // Ingestion queue for the outbox relay (optional - may not be available locally)
+        let queue = match config.queue.ingestion_queue_url.clone() {
+            Some(url) => {
+                let queue_config = QueueConfig {
+                    url,
+                    dlq_url: config.queue.dlq_url.clone(),
+                    ..Default::default()
+                };
+                Some(Arc::new(Queue::new(queue_config).await?))
+            }
+            None => {
+                tracing::warn!("Ingestion queue URL not configured, outbox messages will not be published");
+                None
+            }
+        };
+        // Search service client (optional - search runs in-process without it)
+        let (search, search_health) = match config.gateway.search_grpc_url.clone() {
+            Some(url) => {
+                // The search service rejects unauthenticated calls, so without
+                // a token every search would fail at request time
+                let service_token = config.auth.service_token.as_deref().ok_or_else(|| AppError::Configuration {
+                    message: "auth.service_token must be set when gateway.search_grpc_url is".to_string(),
+                })?;
+                info!(url = %url, "Using search service");
+                let channel = Channel::from_shared(url)?.connect_lazy();
+                let interceptor = ServiceTokenInterceptor::new(Some(service_token))?;
+                (
+                    Some(SearchServiceClient::with_interceptor(channel.clone(), interceptor)),
+                    Some(HealthClient::new(channel)),
+                )
+            }
+            None => (None, None),
+        };
+        
+        if config.auth.jwt_secret.is_none() {
+            tracing::warn!("JWT secret not configured, only API key authentication is available");
+        }
+        
+        let storage = create_store(&config.storage).await?;
+        
+        // Query understanding, cached in Redis when it's reachable
+        let dictionaries = match &config.context.dictionary_path {
+            Some(path) => {
+                info!(path = %path, "Loading query dictionaries");
+                Arc::new(QueryDictionaries::from_file(path)?)
+            }
+            None => QueryDictionaries::builtin(),
+        };
+        let parser_config = QueryParserConfig {
+            llm_fallback_threshold: config.context.intent_fallback_threshold,
+            ..Default::default()
+        };
+        let mut query_parser = QueryParser::with_dictionaries(parser_config, dictionaries);
+        let usage = Arc::new(UsageMeter::new(Repository::new(db.clone())));
+        let llm = match config.context.llm_api_key.clone() {
+            _ if config.context.offline => {
+                tracing::warn!("Context engine is offline, LLM and query embeddings are mocked");
+                Some(Arc::new(LlmClient::offline(&config.context.llm_model)))
+            }
+            Some(api_key) => Some(Arc::new(LlmClient::new(LLMConfig {
+                endpoint: config.context.llm_endpoint.clone(),
+                api_key,
+                model: config.context.llm_model.clone(),
+                timeout_secs: config.context.llm_timeout_secs,
+                context_window: config.context.llm_context_window,
+                offline: false,
+            })?.with_meter(usage.clone()))),
+            None => None,
+        };
+        if let Some(llm) = llm.clone().filter(|llm| llm.is_configured()) {
+            query_parser = query_parser.with_llm(llm);
+        }
+        let cache = match Cache::new(CacheConfig::from_redis(&config.redis)).await {
+            Ok(cache) => Some(Arc::new(cache)),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to connect to Redis, query understanding and sessions will not be cached and request nonces are only tracked per instance");
+                None
+            }
+        };
+        if let Some(cache) = &cache {
+            query_parser = query_parser.with_cache(cache.clone(), config.context.understanding_cache_ttl_secs);
+        }
+        
+        // Sessions live in Postgres, optionally cached in Redis
+        let sessions = SessionStore::new(Repository::new(db.clone()), &config.sessions);
+        let sessions = match cache.clone().filter(|_| config.sessions.redis_cache) {
+            Some(cache) => sessions.with_cache(cache),
+            None => sessions,
+        };
+        
+        // Signed request nonces are shared between gateways through Redis
+        let auth = AuthState::new(&config.auth, db.clone());
+        let auth = match cache.clone() {
+            Some(cache) => auth.with_cache(cache),
+            None => auth,
+        };
+        
+        // Query embeddings; without an API key the local hashing model stands in
+        let embedder: Arc<dyn Embedder> = match (config.embedding.provider.as_str(), &config.embedding.api_key) {
+            _ if config.context.offline => Arc::new(MockEmbedder::new(config.embedding.dimension)),
+            ("openai", None) => {
+                tracing::warn!("Embedding API key not configured, query embeddings use the hashing model");
+                Arc::new(HashEmbedder::new(config.embedding.dimension))
+            }
+            _ => create_embedder_chain(&config.embedding),
+        };
+        let embedder: Arc<dyn Embedder> = Arc::new(MeteredEmbedder::new(embedder, usage.clone()));
+        // Query vectors are compared against stored ones, so they must match the column
+        db.verify_embedding_dimension(embedder.model_name(), embedder.dimension()).await?;
+        
+        Ok(AppState {
+            config: shared,
+            auth,
+            audit: AuditLogger::new(Repository::new(db.clone())),
+            analytics: Analytics::new(Repository::new(db.clone())),
+            queue,
+            rate_limiter,
+            export_limiter: TenantRateLimiter::new(
+                config.rate_limit.export_requests_per_minute,
+                config.rate_limit.enabled,
+            ),
+            db,
+            cache,
+            search,
+            search_health,
+            storage,
+            query_parser: Arc::new(query_parser),
+            llm,
+            embedder,
+            preprocessor: Arc::new(Preprocessor::new(&config.embedding.preprocess, &config.embedding.model)),
+            usage,
+            calibrator: Arc::new(ScoreCalibrator::new(config.search.calibration_sample_size)),
+            sessions,
+            slo: Arc::new(SloTracker::new()),
+        })
+    }
+}
+
+/// Create the main application router
+pub fn create_router(state: AppState) -> Router {
+    // CORS configuration
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any);
+    
+    // Request ID propagation
+    let request_id = SetRequestIdLayer::x_request_id(MakeRequestUuid);
+    let propagate_id = PropagateRequestIdLayer::x_request_id();
+    
+    // Body limits, compression, timeout and concurrency are read once at startup
+    let server = state.config.load().server.clone();
+    let request_timeout = state.config.load().request_timeout();
+    
+    // Paper submission carries full texts, so it gets its own body limit
+    let paper_ingest_routes = Router::new()
+        .route("/papers", post(handlers::papers::create_paper))
+        .route("/papers/preview", post(handlers::papers::preview_paper))
+        .layer(RequestBodyLimitLayer::new(server.max_paper_body_bytes));
+    
+    // Archives of PDFs are larger still
+    let archive_upload_routes = Router::new()
+        .route("/papers/archive", post(handlers::batches::upload_archive))
+        .layer(RequestBodyLimitLayer::new(server.max_archive_body_bytes));
+    
+    // API routes
+    let api_routes = Router::new()
+        // Health endpoints (no auth)
+        .route("/health", get(handlers::health::health))
+        .route("/ready", get(handlers::health::ready))
+        
+        // Token endpoints (authenticated by the request body)
+        .route("/auth/token", post(handlers::auth::issue_token))
+        .route("/auth/refresh", post(handlers::auth::refresh_token))
+        
+        // API key management
+        .route("/keys/current", get(handlers::keys::get_current_key))
+        .route("/keys/rotate", post(handlers::keys::rotate_key))
+        .route("/admin/keys/stale", get(handlers::keys::list_stale_keys))
+        
+        // Tenant settings
+        .route("/tenant/settings", get(handlers::tenant::get_settings))
+        .route("/tenant/settings", patch(handlers::tenant::update_settings))
+        
+        // Usage and cost
+        .route("/usage", get(handlers::usage::get_usage))
+        
+        // Query analytics
+        .route("/analytics/queries/top", get(handlers::analytics::top_queries))
+        .route("/analytics/queries/zero-results", get(handlers::analytics::zero_result_queries))
+        
+        // Admin endpoints
+        .route("/admin/audit", get(handlers::admin::list_audit_logs))
+        .route("/admin/dlq/redrive", post(handlers::admin::redrive_dlq))
+        .route("/admin/reindex", post(handlers::admin::start_reindex))
+        .route("/admin/reindex/{id}", get(handlers::admin::get_reindex))
+        
+        // Paper endpoints (submission, preview and archive upload are in the routers above)
+        .route("/papers/import/url", post(handlers::papers::import_paper_url))
+        .route("/papers/{id}", get(handlers::papers::get_paper))
+        .route("/papers/{id}", patch(handlers::papers::update_paper))
+        .route("/papers/{id}", delete(handlers::papers::delete_paper))
+        .route("/papers/{id}/restore", post(handlers::papers::restore_paper))
+        .route("/papers/{id}/reprocess", post(handlers::papers::reprocess_paper))
+        .route("/papers/{id}/source", get(handlers::papers::get_paper_source))
+        .route("/papers/{id}/similar", get(handlers::papers::similar_papers))
+        
+        // Collection endpoints
+        .route("/collections", post(handlers::collections::create_collection))
+        .route("/collections", get(handlers::collections::list_collections))
+        .route("/collections/{id}", get(handlers::collections::get_collection))
+        .route("/collections/{id}", patch(handlers::collections::update_collection))
+        .route("/collections/{id}", delete(handlers::collections::delete_collection))
+        .route("/collections/{id}/papers", get(handlers::collections::list_collection_papers))
+        .route("/collections/{id}/papers", post(handlers::collections::add_collection_papers))
+        .route("/collections/{id}/papers/{paper_id}", delete(handlers::collections::remove_collection_paper))
+        
+        // Author endpoints
+        .route("/authors/{id}/papers", get(handlers::authors::list_author_papers))
+        
+        // Document links from the local storage backend (authorized by signature)
+        .route("/storage/{*key}", get(handlers::storage::get_object))
+        
+        // Chunks with their provenance, for deep links into a PDF viewer
+        .route("/chunks/{id}", get(handlers::chunks::get_chunk))
+        
+        // Job endpoints
+        .route("/jobs/{id}", get(handlers::jobs::get_job))
+        .route("/batches/{id}", get(handlers::batches::get_batch))
+        
+        // Search endpoints
+        .route("/search", post(handlers::search::search))
+        .route("/search/batch", post(handlers::search::batch_search))
+        .route("/search/feedback", post(handlers::search::submit_feedback))
+        
+        // Embeddings of caller-supplied text
+        .route("/embeddings", post(handlers::embeddings::create_embeddings))
+        
+        // Saved searches and new-paper alerts
+        .route("/searches", post(handlers::saved_searches::create_saved_search))
+        .route("/searches", get(handlers::saved_searches::list_saved_searches))
+        .route("/searches/{id}", delete(handlers::saved_searches::delete_saved_search))
+        
+        // Intelligence endpoints (Context Engine)
+        .route("/intelligence/search", post(handlers::intelligence::intelligent_search))
+        .route("/intelligence/review", post(handlers::intelligence::create_review))
+        .route("/intelligence/review/{id}", get(handlers::intelligence::get_review))
+        
+        // Session endpoints
+        .route("/sessions", post(handlers::sessions::create_session))
+        .route("/sessions/{id}", get(handlers::sessions::get_session))
+        .route("/sessions/{id}/events", post(handlers::sessions::track_event))
+        
+        // Citation endpoints
+        .route("/papers/{id}/citations", get(handlers::citations::get_citations))
+        .route("/papers/{id}/related", get(handlers::citations::related_papers))
+        .route("/citations/traverse", post(handlers::citations::traverse_citations))
+        .route("/citations/graph", get(handlers::citations::export_graph))
+        
+        // Export
+        .route("/export/chunks", get(handlers::export::export_chunks))
+        .layer(RequestBodyLimitLayer::new(server.max_request_body_bytes))
+        .merge(paper_ingest_routes)
+        .merge(archive_upload_routes)
+        // The tower-http limits above replace axum's built-in 2 MB extractor limit
+        .layer(DefaultBodyLimit::disable());
+    
+    // Search responses with full chunk content run to several hundred KB; PDFs
+    // are already compressed
+    let compression = CompressionLayer::new()
+        .gzip(server.compress_responses)
+        .br(server.compress_responses)
+        .compress_when(
+            DefaultPredicate::new()
+                .and(SizeAbove::new(server.compression_min_bytes))
+                .and(NotForContentType::const_new("application/pdf")),
+        );
+    
+    // Bound the time and number of in-flight requests, shedding the excess
+    // with 503 instead of queueing it
+    let overload = ServiceBuilder::new()
+        .layer(HandleErrorLayer::new(move |err: BoxError| async move {
+            overload_error(err, request_timeout)
+        }))
+        .load_shed()
+        .layer(GlobalConcurrencyLimitLayer::new(server.max_concurrent_requests))
+        .timeout(request_timeout);
+    
+    // Compose the app
+    Router::new()
+        .nest("/v2", api_routes)
+        .layer(axum::middleware::from_fn(read_your_writes_middleware))
+        .layer(axum::middleware::from_fn(request_context_middleware))
+        .layer(axum::middleware::from_fn_with_state(request_timeout, deadline_middleware))
+        .layer(axum::middleware::from_fn_with_state(state.auth.clone(), usage_scope_middleware))
+        .layer(axum::middleware::from_fn_with_state(state.auth.clone(), signature_middleware))
+        .layer(overload)
+        .layer(axum::middleware::from_fn_with_state(state.rate_limiter.clone(), rate_limit_middleware))
+        .layer(axum::middleware::from_fn_with_state(state.slo.clone(), slo_middleware))
+        .layer(compression)
+        .layer(TraceLayer::new_for_http())
+        .layer(cors)
+        .layer(axum::middleware::from_fn_with_state(state.config.clone(), error_response_middleware))
+        .layer(request_id)
+        .layer(propagate_id)
+        .with_state(state)
+}
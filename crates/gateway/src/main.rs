@@ -7,70 +7,121 @@
 //! - Request routing
 //! - Observability (logging, metrics, tracing)
 
-mod handlers;
-mod middleware;
-
-use axum::{
-    routing::{delete, get, post},
-    Router,
-};
 use paperforge_common::{
-    config::AppConfig,
-    db::DbPool,
-    errors::AppError,
+    config::{AppConfig, ConfigWatcher, Service},
+    db::Repository,
     metrics,
+    outbox::{OutboxRelay, OutboxRelayConfig, INGESTION_QUEUE},
+    queue::QueueMonitor,
+    reindex::{ReindexWorker, ReindexWorkerConfig},
 };
+use paperforge_gateway::{create_router, middleware::rate_limit::ReloadableRateLimiter, AppState};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::signal;
-use tower_http::{
-    cors::{Any, CorsLayer},
-    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
-    trace::TraceLayer,
-};
-use tracing::{info, Level};
+use tracing::info;
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry};
 
-/// Application state shared across handlers
-#[derive(Clone)]
-pub struct AppState {
-    pub config: Arc<AppConfig>,
-    pub db: DbPool,
-}
+/// Seconds between publishing SLO gauges
+const SLO_PUBLISH_INTERVAL_SECS: u64 = 15;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables
     dotenvy::dotenv().ok();
     
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_max_level(Level::INFO)
-        .with_target(true)
-        .json()
+    // Initialize tracing (the filter follows observability.log_level once loaded)
+    let (log_filter, log_level) = reload::Layer::new(EnvFilter::new("info"));
+    tracing_subscriber::registry()
+        .with(log_filter)
+        .with(tracing_subscriber::fmt::layer().with_target(true).json())
         .init();
     
     info!("Starting PaperForge API Gateway v{}", paperforge_common::VERSION);
     
     // Load configuration
-    let config = AppConfig::load().map_err(|e| {
+    let config = AppConfig::load_for(Service::Gateway).await.map_err(|e| {
         tracing::error!(error = %e, "Failed to load configuration");
         e
     })?;
     
     let config = Arc::new(config);
+    apply_log_level(&log_level, &config.observability.log_level);
+    
+    // Hot-reload tunables on config file changes and SIGHUP
+    let watcher = Arc::new(ConfigWatcher::new(config.as_ref().clone()).for_service(Service::Gateway));
+    let rate_limiter = ReloadableRateLimiter::new(&config.rate_limit);
+    let _config_watcher = watcher
+        .clone()
+        .spawn(Duration::from_secs(config.gateway.config_poll_secs));
     
-    // Initialize metrics
+    // Initialize metrics, served for Prometheus on the metrics port
+    if config.observability.metrics_port > 0 {
+        PrometheusBuilder::new()
+            .with_http_listener(SocketAddr::from(([0, 0, 0, 0], config.observability.metrics_port)))
+            .set_buckets_for_metric(
+                Matcher::Suffix("duration_seconds".to_string()),
+                metrics::LATENCY_BUCKETS,
+            )?
+            .install()?;
+    }
     metrics::register_metrics();
+    metrics::configure_tenant_labels(&config.observability);
     
-    // Initialize database connection
-    info!("Connecting to database...");
-    let db = DbPool::new(&config.database).await?;
+    // Connect the database, queue, cache, search client and models
+    let state = AppState::from_config(&config, watcher.shared(), rate_limiter).await?;
+    state.db.spawn_metrics_collector(Duration::from_secs(config.database.pool_metrics_interval_secs));
     
-    // Create app state
-    let state = AppState {
-        config: config.clone(),
-        db,
-    };
+    // Apply reloaded tunables to the components that copied them at startup
+    let mut config_changes = watcher.subscribe();
+    let reload_limiter = state.rate_limiter.clone();
+    let reload_cache = state.cache.clone();
+    tokio::spawn(async move {
+        let mut current = config_changes.borrow().clone();
+        while config_changes.changed().await.is_ok() {
+            let next = config_changes.borrow_and_update().clone();
+            if next.observability.log_level != current.observability.log_level {
+                apply_log_level(&log_level, &next.observability.log_level);
+            }
+            if next.rate_limit != current.rate_limit {
+                reload_limiter.update(&next.rate_limit);
+                info!(rate_limit = ?next.rate_limit, "Rate limits updated");
+            }
+            if let Some(cache) = &reload_cache {
+                if next.redis.default_ttl_secs != current.redis.default_ttl_secs {
+                    cache.set_default_ttl(next.redis.default_ttl_secs);
+                    info!(ttl_secs = next.redis.default_ttl_secs, "Cache TTL updated");
+                }
+            }
+            current = next;
+        }
+    });
+    
+    // Relay queue messages written to the outbox
+    let _relay = state.queue.clone().map(|queue| {
+        OutboxRelay::new(Repository::new(state.db.clone()), OutboxRelayConfig::default())
+            .with_queue(INGESTION_QUEUE, queue)
+            .spawn()
+    });
+    
+    // Run admin re-indexing jobs, failing any a previous instance abandoned
+    let _reindex_worker = ReindexWorker::new(Repository::new(state.db.clone()), ReindexWorkerConfig::default()).spawn();
+    
+    // Publish queue depths for the ingestion, embedding and dead letter queues
+    if config.queue.depth_poll_interval_secs > 0 {
+        if let Some(monitor) = QueueMonitor::from_config(&config.queue).await {
+            monitor.spawn(Duration::from_secs(config.queue.depth_poll_interval_secs));
+        }
+    }
+    
+    // Track requests against the SLOs, publishing burn rates for alerting
+    state.slo.spawn_publisher(Duration::from_secs(SLO_PUBLISH_INTERVAL_SECS));
+    
+    if config.sessions.sweep_interval_secs > 0 {
+        state.sessions.spawn_sweeper(Duration::from_secs(config.sessions.sweep_interval_secs));
+    }
     
     // Build the router
     let app = create_router(state);
@@ -82,73 +133,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let listener = tokio::net::TcpListener::bind(addr).await?;
     
     axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(shutdown_signal(config.clone()))
         .await?;
     
     info!("Server shutdown complete");
     Ok(())
 }
 
-/// Create the main application router
-fn create_router(state: AppState) -> Router {
-    // CORS configuration
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-    
-    // Request ID propagation
-    let request_id = SetRequestIdLayer::x_request_id(MakeRequestUuid);
-    let propagate_id = PropagateRequestIdLayer::x_request_id();
-    
-    // API routes
-    let api_routes = Router::new()
-        // Health endpoints (no auth)
-        .route("/health", get(handlers::health::health))
-        .route("/ready", get(handlers::health::ready))
-        
-        // Paper endpoints
-        .route("/papers", post(handlers::papers::create_paper))
-        .route("/papers/:id", get(handlers::papers::get_paper))
-        .route("/papers/:id", delete(handlers::papers::delete_paper))
-        
-        // Job endpoints
-        .route("/jobs/:id", get(handlers::jobs::get_job))
-        
-        // Search endpoints
-        .route("/search", post(handlers::search::search))
-        .route("/search/batch", post(handlers::search::batch_search))
-        
-        // Intelligence endpoints (Context Engine)
-        .route("/intelligence/search", post(handlers::intelligence::intelligent_search))
-        
-        // Session endpoints
-        .route("/sessions", post(handlers::sessions::create_session))
-        .route("/sessions/:id", get(handlers::sessions::get_session))
-        .route("/sessions/:id/events", post(handlers::sessions::track_event))
-        
-        // Citation endpoints
-        .route("/papers/:id/citations", get(handlers::citations::get_citations))
-        .route("/citations/traverse", post(handlers::citations::traverse_citations));
-    
-    // Compose the app
-    Router::new()
-        .nest("/v2", api_routes)
-        .layer(TraceLayer::new_for_http())
-        .layer(cors)
-        .layer(request_id)
-        .layer(propagate_id)
-        .with_state(state)
+/// Switch the log filter (e.g. `info`, `debug`, `paperforge=debug,tower_http=warn`)
+fn apply_log_level(handle: &reload::Handle<EnvFilter, Registry>, level: &str) {
+    match EnvFilter::try_new(level) {
+        Ok(filter) => {
+            if let Err(e) = handle.reload(filter) {
+                tracing::warn!(error = %e, "Failed to update log level");
+            }
+        }
+        Err(e) => tracing::warn!(level, error = %e, "Invalid log level, keeping current filter"),
+    }
 }
 
-/// Graceful shutdown signal handler
-async fn shutdown_signal() {
+/// Graceful shutdown signal handler, also triggered by rotated secrets
+async fn shutdown_signal(config: Arc<AppConfig>) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
             .expect("failed to install Ctrl+C handler");
     };
-
+    
     #[cfg(unix)]
     let terminate = async {
         signal::unix::signal(signal::unix::SignalKind::terminate())
@@ -156,12 +167,13 @@ async fn shutdown_signal() {
             .recv()
             .await;
     };
-
+    
     #[cfg(not(unix))]
     let terminate = std::future::pending::<()>();
-
+    
     tokio::select! {
         _ = ctrl_c => info!("Received Ctrl+C, starting shutdown..."),
         _ = terminate => info!("Received SIGTERM, starting shutdown..."),
+        _ = config.secrets_rotated(Some(Service::Gateway)) => {}
     }
 }
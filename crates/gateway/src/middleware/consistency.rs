@@ -0,0 +1,12 @@
+//! Read-your-writes middleware
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use paperforge_common::db;
+
+/// Keep a request's reads on the primary once it has written
+///
+/// Without this, a read right after a write could hit a replica that
+/// hasn't replayed it yet.
+pub async fn read_your_writes_middleware(request: Request, next: Next) -> Response {
+    db::consistent(next.run(request)).await
+}
@@ -0,0 +1,21 @@
+//! Request deadline middleware
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use paperforge_common::deadline;
+use std::time::Duration;
+
+/// Give a request a deadline at the gateway's request timeout
+///
+/// Searches forwarded over gRPC and statement timeouts pick it up, so work
+/// for a request that has already timed out is cancelled downstream too.
+pub async fn deadline_middleware(
+    State(timeout): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    deadline::with_timeout(Some(timeout), next.run(request)).await
+}
@@ -0,0 +1,125 @@
+//! Error response enrichment
+//!
+//! Handlers and extractors render [`AppError`](paperforge_common::errors::AppError)
+//! without access to the request, so the error body leaves `request_id`
+//! empty. This layer fills it in from the request ID extension and, when
+//! configured or requested via `Accept`, re-renders the body as RFC 7807
+//! problem details.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use paperforge_common::{
+    config::{ErrorFormat, SharedConfig},
+    errors::{ErrorResponse, ProblemDetails, PROBLEM_JSON},
+};
+use tower_http::request_id::RequestId;
+
+/// Add the request ID to error bodies and pick their format
+pub async fn error_response_middleware(
+    State(config): State<SharedConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .map(str::to_string);
+    let problem = config.load().gateway.error_format == ErrorFormat::Problem
+        || accepts_problem_json(request.headers());
+    
+    let mut response = next.run(request).await;
+    let Some(mut body) = response.extensions_mut().remove::<ErrorResponse>() else {
+        return response;
+    };
+    if request_id.is_none() && !problem {
+        return response;
+    }
+    
+    body.error.request_id = request_id;
+    let (mut parts, _) = response.into_parts();
+    let rendered = if problem {
+        ProblemDetails::new(parts.status, &body.error).into_response()
+    } else {
+        Json(body).into_response()
+    };
+    
+    let (rendered_parts, rendered_body) = rendered.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    if let Some(content_type) = rendered_parts.headers.get(header::CONTENT_TYPE) {
+        parts.headers.insert(header::CONTENT_TYPE, content_type.clone());
+    }
+    Response::from_parts(parts, rendered_body)
+}
+
+/// Whether the client lists `application/problem+json` in `Accept`
+fn accepts_problem_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media| media.split(';').next().unwrap_or("").trim().eq_ignore_ascii_case(PROBLEM_JSON))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arc_swap::ArcSwap;
+    use axum::{body::Body, http::StatusCode, routing::get, Router};
+    use paperforge_common::{config::AppConfig, errors::AppError};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use tower_http::request_id::{MakeRequestUuid, SetRequestIdLayer};
+    
+    fn app(config: AppConfig) -> Router {
+        let config: SharedConfig = Arc::new(ArcSwap::from_pointee(config));
+        Router::new()
+            .route("/", get(|| async { Err::<(), _>(AppError::InvalidApiKey) }))
+            .layer(axum::middleware::from_fn_with_state(config, error_response_middleware))
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+    }
+    
+    async fn send(app: Router, accept: &str) -> (StatusCode, HeaderMap, serde_json::Value) {
+        let request = Request::builder()
+            .uri("/")
+            .header("x-request-id", "req-123")
+            .header(header::ACCEPT, accept)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        (parts.status, parts.headers, serde_json::from_slice(&bytes).unwrap())
+    }
+    
+    #[tokio::test]
+    async fn test_request_id_added_to_errors() {
+        let (status, _, body) = send(app(AppConfig::default()), "application/json").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        
+        let body: ErrorResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(body.error.request_id.as_deref(), Some("req-123"));
+    }
+    
+    #[tokio::test]
+    async fn test_problem_json() {
+        let accept = "application/problem+json;q=0.9, application/json;q=0.5";
+        let (status, headers, body) = send(app(AppConfig::default()), accept).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(headers[header::CONTENT_TYPE], PROBLEM_JSON);
+        assert_eq!(body["type"], "https://paperforge.dev/problems/invalid-api-key");
+        assert_eq!(body["status"], 401);
+        assert_eq!(body["instance"], "req-123");
+        
+        let mut config = AppConfig::default();
+        config.gateway.error_format = ErrorFormat::Problem;
+        let (_, headers, _) = send(app(config), "*/*").await;
+        assert_eq!(headers[header::CONTENT_TYPE], PROBLEM_JSON);
+    }
+}
@@ -2,7 +2,20 @@
 //!
 //! Provides:
 //! - Rate limiting
+//! - Read-your-writes database routing
+//! - Usage attribution
+//! - Timeouts and load shedding
+//! - Request deadlines
+//! - Request context (tenant, request ID, deadline, locale)
+//! - SLO tracking
 //! - Request logging
 //! - Error handling
 
+pub mod consistency;
+pub mod deadline;
+pub mod error_response;
+pub mod overload;
 pub mod rate_limit;
+pub mod request_context;
+pub mod slo;
+pub mod usage;
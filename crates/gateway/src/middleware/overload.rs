@@ -0,0 +1,93 @@
+//! Timeout and load shedding
+//!
+//! The gateway admits at most `server.max_concurrent_requests` requests at
+//! once, across all routes, and sheds the rest immediately instead of
+//! queueing them. Each admitted request gets `server.request_timeout_secs`
+//! to produce a response. Both failures surface from the tower stack as errors, which
+//! [`overload_error`] turns into structured responses.
+
+use axum::BoxError;
+use paperforge_common::errors::AppError;
+use std::time::Duration;
+use tower::{load_shed::error::Overloaded, timeout::error::Elapsed};
+
+/// Map an error from the timeout/concurrency stack to a response
+///
+/// Shed requests get 503 with `Retry-After`; requests that ran out of time
+/// get 504.
+pub fn overload_error(err: BoxError, timeout: Duration) -> AppError {
+    if err.is::<Overloaded>() {
+        tracing::warn!("Shedding request, concurrency limit reached");
+        AppError::ServiceUnavailable {
+            message: "Server is at capacity, retry shortly".to_string(),
+        }
+    } else if err.is::<Elapsed>() {
+        tracing::warn!(timeout_ms = timeout.as_millis() as u64, "Request timed out");
+        AppError::RequestTimeout {
+            timeout_ms: timeout.as_millis() as u64,
+        }
+    } else {
+        AppError::Internal {
+            message: format!("Unhandled middleware error: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        error_handling::HandleErrorLayer,
+        http::{Request, StatusCode},
+        routing::get,
+        Router,
+    };
+    use std::sync::Arc;
+    use tokio::sync::Notify;
+    use tower::{limit::GlobalConcurrencyLimitLayer, ServiceBuilder, ServiceExt};
+    
+    fn app(timeout: Duration, release: Arc<Notify>) -> Router {
+        Router::new()
+            .route("/slow", get(move || {
+                let release = release.clone();
+                async move { release.notified().await }
+            }))
+            .layer(
+                ServiceBuilder::new()
+                    .layer(HandleErrorLayer::new(move |err: BoxError| async move {
+                        overload_error(err, timeout)
+                    }))
+                    .load_shed()
+                    .layer(GlobalConcurrencyLimitLayer::new(1))
+                    .timeout(timeout),
+            )
+    }
+    
+    fn request() -> Request<Body> {
+        Request::get("/slow").body(Body::empty()).unwrap()
+    }
+    
+    #[tokio::test]
+    async fn test_times_out_slow_requests() {
+        let app = app(Duration::from_millis(20), Arc::new(Notify::new()));
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+    
+    #[tokio::test]
+    async fn test_sheds_requests_over_the_limit() {
+        let release = Arc::new(Notify::new());
+        let app = app(Duration::from_secs(5), release.clone());
+        
+        let first = tokio::spawn(app.clone().oneshot(request()));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        
+        let shed = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(shed.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(shed.headers().contains_key("retry-after"));
+        
+        release.notify_one();
+        assert_eq!(first.await.unwrap().unwrap().status(), StatusCode::OK);
+    }
+}
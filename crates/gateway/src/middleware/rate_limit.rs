@@ -1,18 +1,21 @@
 //! Rate limiting middleware using token bucket algorithm
 
+use arc_swap::ArcSwapOption;
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{Request, State},
     middleware::Next,
     response::Response,
 };
 use governor::{
     clock::QuantaClock,
-    state::{InMemoryState, NotKeyed},
+    state::{keyed::DefaultKeyedStateStore, InMemoryState, NotKeyed},
     Quota, RateLimiter,
 };
+use paperforge_common::{config::RateLimitConfig, errors::AppError};
 use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use uuid::Uuid;
 
 /// Rate limiter using governor crate
 pub type GlobalRateLimiter = RateLimiter<NotKeyed, InMemoryState, QuantaClock>;
@@ -25,18 +28,82 @@ pub fn create_rate_limiter(requests_per_second: u32, burst: u32) -> Arc<GlobalRa
     Arc::new(RateLimiter::direct(quota))
 }
 
+/// Rate limiter that is rebuilt when limits are reloaded
+#[derive(Default)]
+pub struct ReloadableRateLimiter {
+    limiter: ArcSwapOption<GlobalRateLimiter>,
+    requests_per_second: AtomicU32,
+}
+
+impl ReloadableRateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Arc<Self> {
+        let limiter = Arc::new(Self::default());
+        limiter.update(config);
+        limiter
+    }
+    
+    /// Apply new limits (resets the token bucket)
+    pub fn update(&self, config: &RateLimitConfig) {
+        let limiter = config.enabled.then(|| {
+            create_rate_limiter(config.requests_per_second.max(1), config.burst.max(1))
+        });
+        self.limiter.store(limiter);
+        self.requests_per_second.store(config.requests_per_second, Ordering::Relaxed);
+    }
+    
+    /// Take a token; always succeeds when rate limiting is disabled
+    pub fn check(&self) -> bool {
+        self.limiter
+            .load()
+            .as_ref()
+            .is_none_or(|limiter| limiter.check().is_ok())
+    }
+}
+
+/// Per-tenant limiter for expensive endpoints such as exports
+pub struct TenantRateLimiter {
+    limiter: Option<RateLimiter<Uuid, DefaultKeyedStateStore<Uuid>, QuantaClock>>,
+    per_minute: u32,
+}
+
+impl TenantRateLimiter {
+    /// Allow each tenant `per_minute` requests a minute, with no burst
+    /// beyond that; `enabled: false` lets everything through
+    pub fn new(per_minute: u32, enabled: bool) -> Arc<Self> {
+        let quota = Quota::per_minute(NonZeroU32::new(per_minute.max(1)).unwrap());
+        Arc::new(Self {
+            limiter: enabled.then(|| RateLimiter::keyed(quota)),
+            per_minute,
+        })
+    }
+    
+    /// Take a token for `tenant_id`
+    pub fn check(&self, tenant_id: &Uuid) -> Result<(), AppError> {
+        match &self.limiter {
+            Some(limiter) if limiter.check_key(tenant_id).is_err() => {
+                tracing::warn!(tenant_id = %tenant_id, "Tenant rate limit exceeded");
+                Err(AppError::RateLimited { limit: self.per_minute })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 /// Rate limiting middleware
+///
+/// Rejected requests get a 429 with a `Retry-After` header.
 pub async fn rate_limit_middleware(
+    State(limiter): State<Arc<ReloadableRateLimiter>>,
     request: Request,
     next: Next,
-    limiter: Arc<GlobalRateLimiter>,
-) -> Result<Response, StatusCode> {
-    match limiter.check() {
-        Ok(_) => Ok(next.run(request).await),
-        Err(_) => {
-            tracing::warn!("Rate limit exceeded");
-            Err(StatusCode::TOO_MANY_REQUESTS)
-        }
+) -> Result<Response, AppError> {
+    if limiter.check() {
+        Ok(next.run(request).await)
+    } else {
+        tracing::warn!("Rate limit exceeded");
+        Err(AppError::RateLimited {
+            limit: limiter.requests_per_second.load(Ordering::Relaxed),
+        })
     }
 }
 
@@ -49,4 +116,35 @@ mod tests {
         let limiter = create_rate_limiter(100, 200);
         assert!(limiter.check().is_ok());
     }
+    
+    #[test]
+    fn test_tenant_rate_limiter_is_per_tenant() {
+        let limiter = TenantRateLimiter::new(1, true);
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert!(limiter.check(&a).is_ok());
+        assert!(limiter.check(&a).is_err());
+        assert!(limiter.check(&b).is_ok());
+        
+        let disabled = TenantRateLimiter::new(1, false);
+        assert!(disabled.check(&a).is_ok());
+        assert!(disabled.check(&a).is_ok());
+    }
+    
+    #[test]
+    fn test_reloadable_rate_limiter() {
+        let mut config = RateLimitConfig {
+            requests_per_second: 1,
+            burst: 1,
+            enabled: true,
+            export_requests_per_minute: 1,
+        };
+        let limiter = ReloadableRateLimiter::new(&config);
+        assert!(limiter.check());
+        assert!(!limiter.check());
+        
+        config.enabled = false;
+        limiter.update(&config);
+        assert!(limiter.check());
+        assert!(limiter.check());
+    }
 }
@@ -0,0 +1,20 @@
+//! Request context middleware
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use paperforge_common::{auth::AuthContext, deadline, request_context::RequestContext};
+
+/// Build the request's [`RequestContext`] for handlers to extract
+///
+/// Runs inside the deadline middleware, whose deadline it records. Signed
+/// requests are already authenticated here; others get their tenant when
+/// a handler authenticates them.
+pub async fn request_context_middleware(mut request: Request, next: Next) -> Response {
+    let mut context = RequestContext::from_headers(request.headers());
+    context.deadline = deadline::current();
+    if let Some(auth) = request.extensions().get::<AuthContext>() {
+        context = context.with_auth(auth);
+    }
+    
+    request.extensions_mut().insert(context);
+    next.run(request).await
+}
@@ -0,0 +1,29 @@
+//! SLO tracking middleware
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use paperforge_common::slo::SloTracker;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Count each request's latency and outcome towards the SLOs
+///
+/// Health probes are left out, so they don't dilute the error rate.
+pub async fn slo_middleware(
+    State(tracker): State<Arc<SloTracker>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if path.ends_with("/health") || path.ends_with("/ready") {
+        return next.run(request).await;
+    }
+    
+    let start = Instant::now();
+    let response = next.run(request).await;
+    tracker.record(start.elapsed(), response.status().is_server_error());
+    response
+}
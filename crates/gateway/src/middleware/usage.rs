@@ -0,0 +1,37 @@
+//! Usage attribution middleware
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use paperforge_common::{
+    auth::{AuthContext, AuthState},
+    usage,
+};
+
+/// Attribute a request's LLM and embedding usage to the caller's tenant
+///
+/// Authenticates requests that carry credentials and stores the
+/// [`AuthContext`] in the request extensions, where the extractor picks it
+/// up. Requests that don't authenticate pass through unattributed and are
+/// rejected by the handlers that need a caller.
+pub async fn usage_scope_middleware(
+    State(auth): State<AuthState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let context = match request.extensions().get::<AuthContext>() {
+        Some(context) => Some(context.clone()),
+        None => auth.authenticate_headers(request.headers(), false).await.ok(),
+    };
+    
+    match context {
+        Some(context) => {
+            let tenant_id = context.tenant_id;
+            request.extensions_mut().insert(context);
+            usage::attribute_to(tenant_id, next.run(request)).await
+        }
+        None => next.run(request).await,
+    }
+}
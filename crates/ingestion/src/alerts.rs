@@ -0,0 +1,69 @@
+//! Background saved search alerts
+//!
+//! Runs tenants' saved searches against newly indexed chunks on an
+//! interval and alerts their webhooks and email addresses. With Redis, a
+//! distributed lock keeps instances from running the same searches at once
+//! and alerting twice.
+
+use paperforge_common::{alerts::AlertRunner, cache::Cache, config::{AlertsConfig, FetcherConfig}, db::{DbPool, Repository}};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+/// Name of the lock held while saved searches run
+const ALERT_LOCK: &str = "alerts";
+
+/// Spawn the alert loop on the current runtime
+///
+/// Without a cache every instance runs the searches, which suits a single
+/// instance.
+pub fn spawn_alert_task(
+    db: DbPool,
+    config: AlertsConfig,
+    fetcher: FetcherConfig,
+    cache: Option<Arc<Cache>>,
+) -> paperforge_common::Result<JoinHandle<()>> {
+    let runner = AlertRunner::new(Repository::new(db), &config, &fetcher)?;
+    let lock_ttl = Duration::from_secs(config.interval_secs);
+
+    Ok(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs));
+
+        info!(
+            interval_secs = config.interval_secs,
+            email = config.smtp_url.is_some(),
+            "Starting saved search alert task"
+        );
+
+        loop {
+            interval.tick().await;
+
+            let result = match &cache {
+                Some(cache) => match cache.with_lock(ALERT_LOCK, lock_ttl, runner.run_once()).await {
+                    Ok(Some(result)) => result,
+                    Ok(None) => {
+                        debug!("Saved searches are running on another instance");
+                        continue;
+                    }
+                    // Running unlocked could alert twice; wait for the next tick
+                    Err(e) => {
+                        error!(error = %e, "Failed to take the saved search lock");
+                        continue;
+                    }
+                },
+                None => runner.run_once().await,
+            };
+
+            match result {
+                Ok(0) => {}
+                Ok(sent) => {
+                    info!(sent = sent, "Sent saved search alerts");
+                }
+                Err(e) => {
+                    error!(error = %e, "Failed to run saved searches");
+                }
+            }
+        }
+    }))
+}
@@ -0,0 +1,43 @@
+//! Background sync of the per-tenant BM25 indexes
+//!
+//! When `bm25_index.enabled` is set, this task periodically brings every
+//! tenant's tantivy index up to date with its chunks, so the search service
+//! can serve BM25 queries without Postgres full-text search.
+
+use paperforge_common::{
+    bm25_index::Bm25Index,
+    db::{DbPool, Repository},
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Spawn the index sync loop on the current runtime
+pub fn spawn_index_task(db: DbPool, index: Arc<Bm25Index>, interval_secs: u64) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let repo = Repository::new(db);
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+        info!(interval_secs = interval_secs, "Starting BM25 index sync task");
+
+        loop {
+            interval.tick().await;
+
+            let tenants = match repo.tenants_with_papers().await {
+                Ok(tenants) => tenants,
+                Err(e) => {
+                    error!(error = %e, "Failed to list tenants for BM25 indexing");
+                    continue;
+                }
+            };
+
+            // One tenant's failure doesn't hold up the others
+            for tenant_id in tenants {
+                if let Err(e) = index.sync_tenant(&repo, tenant_id).await {
+                    error!(tenant_id = %tenant_id, error = %e, "Failed to sync BM25 index");
+                }
+            }
+        }
+    })
+}
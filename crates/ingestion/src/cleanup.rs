@@ -0,0 +1,183 @@
+//! Cleanup of text extracted from PDFs
+//!
+//! Runs over the lines of every page before they are joined and chunked:
+//! - Unicode is normalized to NFC, ligatures such as `ﬁ` are split into
+//!   their letters, odd spaces become plain ones and invisible characters
+//!   (soft hyphens, zero-width spaces, invisible math operators) are dropped
+//! - inline math set in mathematical alphanumerics (`𝑥`, `𝜃`, `𝐖`) is
+//!   spelled with plain letters, so it reads and matches like text
+//! - running headers and footers, lines that recur at the top or bottom of
+//!   most pages with at most their numbers changing, are removed
+//! - words hyphenated across a line break ("trans- former") are joined
+
+use std::collections::{HashMap, HashSet};
+use unicode_normalization::UnicodeNormalization;
+
+/// Lines at each end of a page that may be a header or footer
+const EDGE_LINES: usize = 2;
+
+/// Fewest pages a document needs before lines are judged by repetition
+const MIN_PAGES_FOR_REPETITION: usize = 3;
+
+/// Share of pages a line must recur on to be a header or footer
+const RUNNING_LINE_SHARE: f64 = 0.5;
+
+/// Words that after a hyphen mark a suspended hyphen ("pre- and
+/// post-training"), not a word split over two lines
+const SUSPENDED_HYPHEN_FOLLOWERS: &[&str] = &["and", "or", "nor", "to", "vs"];
+
+/// Clean the text of every page of a document
+///
+/// Takes each page's text with its lines separated by `\n`; returns them
+/// the same way, one entry per page.
+pub fn clean_pages(pages: &[String]) -> Vec<String> {
+    let mut pages: Vec<Vec<String>> = pages
+        .iter()
+        .map(|page| {
+            page.lines()
+                .map(normalize)
+                .filter(|line| !line.trim().is_empty())
+                .collect()
+        })
+        .collect();
+    
+    remove_running_lines(&mut pages);
+    
+    pages
+        .iter()
+        .map(|lines| dehyphenate(&lines.join("\n")))
+        .collect()
+}
+
+/// NFC-normalize a line, splitting ligatures, spelling out mathematical
+/// letters and dropping characters that only affect layout
+///
+/// Full compatibility normalization (NFKC) would also flatten superscripts
+/// and other symbols formulas need, so it is only applied to ligatures and
+/// mathematical alphanumerics.
+fn normalize(line: &str) -> String {
+    let mut normalized = String::with_capacity(line.len());
+    for c in line.chars() {
+        match c {
+            '\u{00AD}' | '\u{200B}'..='\u{200D}' | '\u{2060}'..='\u{2064}' | '\u{FEFF}' => {}
+            '\u{FB00}'..='\u{FB06}' | '\u{1D400}'..='\u{1D7FF}' => normalized.extend(c.nfkc()),
+            c if c.is_whitespace() => normalized.push(' '),
+            c => normalized.push(c),
+        }
+    }
+    normalized.nfc().collect()
+}
+
+/// Remove headers and footers: lines at the edges of a page that recur at
+/// the edges of most pages
+fn remove_running_lines(pages: &mut [Vec<String>]) {
+    if pages.len() < MIN_PAGES_FOR_REPETITION {
+        return;
+    }
+    
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for lines in pages.iter() {
+        let keys: HashSet<String> = edge_lines(lines.len())
+            .map(|i| running_key(&lines[i]))
+            .collect();
+        for key in keys {
+            *counts.entry(key).or_default() += 1;
+        }
+    }
+    
+    let threshold = (pages.len() as f64 * RUNNING_LINE_SHARE).ceil() as usize;
+    for lines in pages.iter_mut() {
+        let running: Vec<usize> = edge_lines(lines.len())
+            .filter(|&i| counts[&running_key(&lines[i])] >= threshold)
+            .collect();
+        for i in running.into_iter().rev() {
+            lines.remove(i);
+        }
+    }
+}
+
+/// Indexes of the first and last [`EDGE_LINES`] of `len` lines, each once
+fn edge_lines(len: usize) -> impl Iterator<Item = usize> {
+    let head = 0..len.min(EDGE_LINES);
+    let tail = len.saturating_sub(EDGE_LINES).max(head.end)..len;
+    head.chain(tail)
+}
+
+/// What the occurrences of a running line have in common: its letters and
+/// punctuation, lowercased, without page numbers or spacing
+fn running_key(line: &str) -> String {
+    line.chars()
+        .filter(|c| !c.is_numeric() && !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Join words hyphenated across a line break
+///
+/// A hyphen after a letter, followed by whitespace and a lowercase word,
+/// is taken out with the whitespace, unless the word shows the hyphen is
+/// a suspended one.
+fn dehyphenate(text: &str) -> String {
+    let mut joined = String::with_capacity(text.len());
+    let mut rest = text;
+    
+    while let Some(i) = rest.find('-') {
+        joined.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let next = after.trim_start();
+        let next_word = next.split(|c: char| !c.is_alphabetic()).next().unwrap_or_default();
+        
+        let split_word = next.len() < after.len()
+            && joined.chars().last().is_some_and(char::is_alphabetic)
+            && next_word.chars().next().is_some_and(char::is_lowercase)
+            && !SUSPENDED_HYPHEN_FOLLOWERS.contains(&next_word);
+        if split_word {
+            rest = next;
+        } else {
+            joined.push('-');
+            rest = after;
+        }
+    }
+    joined.push_str(rest);
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_dehyphenate() {
+        assert_eq!(dehyphenate("the trans-\nformer model"), "the transformer model");
+        assert_eq!(dehyphenate("the trans- former model"), "the transformer model");
+        assert_eq!(dehyphenate("pre- and post-training"), "pre- and post-training");
+        assert_eq!(dehyphenate("state-of-the-art"), "state-of-the-art");
+        assert_eq!(dehyphenate("x - y and GPT-\n4"), "x - y and GPT-\n4");
+    }
+    
+    #[test]
+    fn test_normalize_splits_ligatures_and_keeps_symbols() {
+        assert_eq!(normalize("e\u{FB03}cient \u{FB01}ne-tuning"), "efficient fine-tuning");
+        assert_eq!(normalize("co\u{00AD}operate\u{00A0}x\u{00B2}"), "cooperate x\u{00B2}");
+        assert_eq!(normalize("cafe\u{0301}"), "caf\u{00E9}");
+        assert_eq!(normalize("\u{1D453}\u{2061}(\u{1D465}) = \u{1D416}\u{1D465} + \u{1D703}"), "f(x) = Wx + \u{03B8}");
+    }
+    
+    #[test]
+    fn test_running_headers_and_footers_removed() {
+        let body = ["Attention is all", "you need, we find.", "Results follow", "in the next section."];
+        let pages: Vec<String> = body
+            .iter()
+            .enumerate()
+            .map(|(i, text)| format!("Journal of Examples, Vol. 3\n{}\n- {} -", text, i + 1))
+            .collect();
+        
+        let cleaned = clean_pages(&pages);
+        assert_eq!(cleaned[0], "Attention is all");
+        assert_eq!(cleaned[3], "in the next section.");
+        
+        // Too few pages to tell running lines from content
+        let short = clean_pages(&pages[..2]);
+        assert!(short[0].starts_with("Journal of Examples"));
+    }
+}
@@ -1,5 +1,7 @@
 //! Ingestion service error types
 
+use paperforge_common::errors::{AppError, Retryable};
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,6 +12,9 @@ pub enum IngestionError {
     #[error("Chunking error: {0}")]
     ChunkingError(String),
 
+    #[error("Embedding error: {0}")]
+    EmbeddingError(String),
+
     #[error("Queue error: {0}")]
     QueueError(String),
 
@@ -24,10 +29,41 @@ pub enum IngestionError {
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    App(#[from] AppError),
 }
 
-impl From<paperforge_common::errors::AppError> for IngestionError {
-    fn from(e: paperforge_common::errors::AppError) -> Self {
-        IngestionError::DatabaseError(e.to_string())
+impl IngestionError {
+    /// Failure reason recorded on the job, prefixed with the error code
+    /// when there is one (e.g. `PAYLOAD_TOO_LARGE: ...`)
+    pub fn failure_reason(&self) -> String {
+        match self {
+            IngestionError::App(e) => match serde_json::to_value(e.code()) {
+                Ok(serde_json::Value::String(code)) => format!("{}: {}", code, e),
+                _ => e.to_string(),
+            },
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl Retryable for IngestionError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            IngestionError::PdfParseError { .. }
+            | IngestionError::ChunkingError(_)
+            | IngestionError::ConfigError(_)
+            | IngestionError::FileNotFound(_) => false,
+            IngestionError::App(e) => e.is_retryable(),
+            _ => true,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            IngestionError::App(e) => e.retry_after(),
+            _ => None,
+        }
     }
 }
@@ -0,0 +1,226 @@
+//! Text extraction from HTML pages
+//!
+//! Papers imported by URL are often landing pages rather than PDFs. Scripts,
+//! styles and navigation are dropped, block elements become line breaks and
+//! character references are decoded, giving text the chunker can find
+//! sections in. Scholarly pages carry Highwire `citation_*` meta tags; the
+//! title, DOI and full-text PDF link are taken from them when present.
+
+use regex_lite::Regex;
+use std::sync::OnceLock;
+
+/// Elements whose content is never text of the document
+const HIDDEN_ELEMENTS: &[&str] = &["head", "script", "style", "noscript", "svg", "template", "nav"];
+
+/// Elements that start a new line
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "br", "dd", "div", "dl", "dt", "figcaption",
+    "figure", "footer", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "li", "main", "ol",
+    "p", "pre", "section", "table", "td", "th", "tr", "ul",
+];
+
+/// Text and metadata of an HTML page
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractedHtml {
+    pub title: Option<String>,
+    pub doi: Option<String>,
+    /// Link to the full text as a PDF (`citation_pdf_url`)
+    pub pdf_url: Option<String>,
+    /// Body text, one block per line
+    pub text: String,
+}
+
+fn hidden_elements() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        HIDDEN_ELEMENTS
+            .iter()
+            .map(|name| Regex::new(&format!(r"(?is)<{0}\b[^>]*>.*?</{0}\s*>", name)).unwrap())
+            .collect()
+    })
+}
+
+fn comment() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?s)<!--.*?-->").unwrap())
+}
+
+fn tag() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?s)</?([A-Za-z][A-Za-z0-9]*)\b[^>]*>|<![^>]*>").unwrap())
+}
+
+fn title_element() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title\s*>").unwrap())
+}
+
+fn meta_element() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?is)<meta\b[^>]*>").unwrap())
+}
+
+fn attribute() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r#"(?s)([A-Za-z_:][-A-Za-z0-9_:.]*)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap())
+}
+
+fn character_reference() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"&(#[0-9]+|#[xX][0-9A-Fa-f]+|[A-Za-z]+);").unwrap())
+}
+
+/// Extract the text and citation metadata of an HTML page
+pub fn extract_html(html: &str) -> ExtractedHtml {
+    let meta = |name: &str| {
+        meta_element().find_iter(html).find_map(|element| {
+            let attributes = attributes(element.as_str());
+            let key = attributes.iter().find(|(k, _)| k == "name" || k == "property")?;
+            if !key.1.eq_ignore_ascii_case(name) {
+                return None;
+            }
+            let (_, content) = attributes.into_iter().find(|(k, _)| k == "content")?;
+            Some(collapse(&decode_references(&content))).filter(|content| !content.is_empty())
+        })
+    };
+
+    let title = meta("citation_title")
+        .or_else(|| meta("og:title"))
+        .or_else(|| {
+            title_element()
+                .captures(html)
+                .map(|c| collapse(&decode_references(&c[1])))
+                .filter(|title| !title.is_empty())
+        });
+
+    let mut body = comment().replace_all(html, "").into_owned();
+    for pattern in hidden_elements() {
+        body = pattern.replace_all(&body, "").into_owned();
+    }
+    // Line breaks in the source are plain spaces; only block elements break lines
+    let body = collapse(&body);
+    let body = tag().replace_all(&body, |c: &regex_lite::Captures| {
+        let block = c.get(1).is_some_and(|name| BLOCK_ELEMENTS.contains(&name.as_str().to_ascii_lowercase().as_str()));
+        if block { "\n" } else { "" }
+    });
+    let text = decode_references(&body)
+        .lines()
+        .map(collapse)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ExtractedHtml {
+        title,
+        doi: meta("citation_doi").or_else(|| meta("dc.identifier")).filter(|doi| doi.contains("10.")),
+        pdf_url: meta("citation_pdf_url"),
+        text,
+    }
+}
+
+/// Attributes of a tag, names lowercased
+fn attributes(element: &str) -> Vec<(String, String)> {
+    attribute()
+        .captures_iter(element)
+        .map(|c| {
+            let value = c.get(2).or_else(|| c.get(3)).map_or("", |m| m.as_str());
+            (c[1].to_ascii_lowercase(), value.to_string())
+        })
+        .collect()
+}
+
+/// Collapse runs of whitespace, non-breaking spaces included
+fn collapse(text: &str) -> String {
+    text.split(|c: char| c.is_whitespace()).filter(|word| !word.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// Decode numeric and common named character references
+fn decode_references(text: &str) -> String {
+    character_reference()
+        .replace_all(text, |c: &regex_lite::Captures| {
+            let reference = &c[1];
+            let decoded = match reference.strip_prefix('#') {
+                Some(number) => {
+                    let code = match number.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => number.parse().ok(),
+                    };
+                    code.and_then(char::from_u32)
+                }
+                None => named_reference(reference),
+            };
+            decoded.map_or_else(|| c[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+fn named_reference(name: &str) -> Option<char> {
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{00A0}',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "times" => '×',
+        "minus" => '−',
+        "le" => '≤',
+        "ge" => '≥',
+        "middot" => '·',
+        "copy" => '©',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <title>Attention Is All You Need | arXiv</title>
+  <meta name="citation_title" content="Attention Is All You Need">
+  <meta name="citation_doi" content="10.48550/arXiv.1706.03762">
+  <meta name="citation_pdf_url" content="https://arxiv.org/pdf/1706.03762">
+  <style>body { color: black; }</style>
+</head>
+<body>
+  <nav><a href="/">Home</a></nav>
+  <!-- banner -->
+  <h1>Attention Is All You Need</h1>
+  <div class="abstract"><p>The dominant sequence transduction models are based on
+  <em>complex</em> recurrent networks.</p><p>We propose the Transformer &amp; show it&#8217;s&nbsp;better.</p></div>
+  <script>var x = "<p>not text</p>";</script>
+</body>
+</html>"#;
+
+    #[test]
+    fn test_extract_html() {
+        let page = extract_html(PAGE);
+        assert_eq!(page.title.as_deref(), Some("Attention Is All You Need"));
+        assert_eq!(page.doi.as_deref(), Some("10.48550/arXiv.1706.03762"));
+        assert_eq!(page.pdf_url.as_deref(), Some("https://arxiv.org/pdf/1706.03762"));
+        assert_eq!(
+            page.text,
+            "Attention Is All You Need\n\
+             The dominant sequence transduction models are based on complex recurrent networks.\n\
+             We propose the Transformer & show it’s better."
+        );
+    }
+
+    #[test]
+    fn test_title_falls_back_to_title_element() {
+        let page = extract_html("<html><head><title> A  Page &ndash; Blog </title></head><body><p>Text</p></body></html>");
+        assert_eq!(page.title.as_deref(), Some("A Page – Blog"));
+        assert_eq!(page.doi, None);
+        assert_eq!(page.text, "Text");
+    }
+}
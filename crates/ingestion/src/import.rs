@@ -0,0 +1,250 @@
+//! Bulk import of already-parsed papers
+//!
+//! Loads a JSONL corpus, one paper per line:
+//!
+//! ```json
+//! {"title": "...", "abstract": "...", "full_text": "...", "metadata": {}, "references": ["..."]}
+//! ```
+//!
+//! PDF extraction is skipped: the full text (or the abstract, when there is
+//! none) is chunked as the paper body. Papers are loaded in batches, each
+//! batch's papers and jobs inserted in one transaction and its embedding
+//! messages written to the outbox in another.
+//!
+//! Records with an `external_id` are keyed on it, so re-running an import
+//! skips papers already loaded. Lines that fail to parse or validate are
+//! logged and counted, and don't stop the import.
+
+use crate::errors::IngestionError;
+use crate::processor::IngestionProcessor;
+use paperforge_common::crossref::normalize_doi;
+use serde::Deserialize;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// One paper in an import file
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportRecord {
+    pub title: String,
+    #[serde(default, rename = "abstract")]
+    pub abstract_text: Option<String>,
+    #[serde(default)]
+    pub full_text: Option<String>,
+    #[serde(default)]
+    pub external_id: Option<String>,
+    #[serde(default)]
+    pub doi: Option<String>,
+    #[serde(default)]
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default = "empty_metadata")]
+    pub metadata: serde_json::Value,
+    /// Reference list; cited papers already in the corpus get citation
+    /// edges. When empty, references are parsed from the full text instead.
+    #[serde(default)]
+    pub references: Vec<ImportReference>,
+}
+
+fn empty_metadata() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+/// A reference, as its raw text or with a DOI alongside
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ImportReference {
+    Text(String),
+    Entry {
+        text: String,
+        #[serde(default)]
+        doi: Option<String>,
+    },
+}
+
+impl ImportReference {
+    /// `(normalized DOI, text)`, as references are resolved
+    pub fn to_pair(&self) -> (Option<String>, String) {
+        match self {
+            ImportReference::Text(text) => (None, text.clone()),
+            ImportReference::Entry { text, doi } => {
+                (doi.as_deref().and_then(normalize_doi), text.clone())
+            }
+        }
+    }
+}
+
+impl ImportRecord {
+    /// Parse and validate one line of an import file
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut record: ImportRecord = serde_json::from_str(line).map_err(|e| e.to_string())?;
+
+        if record.title.trim().is_empty() {
+            return Err("title is empty".to_string());
+        }
+        if record.text().trim().is_empty() {
+            return Err("record has neither full_text nor abstract".to_string());
+        }
+        if !record.metadata.is_object() {
+            return Err("metadata must be an object".to_string());
+        }
+
+        // The DOI is kept in the metadata, where search and Crossref look for it
+        if let Some(doi) = record.doi.take() {
+            let doi = normalize_doi(&doi).ok_or_else(|| format!("not a valid DOI: {}", doi))?;
+            if let Some(metadata) = record.metadata.as_object_mut() {
+                metadata.entry("doi").or_insert(doi.clone().into());
+            }
+            record.doi = Some(doi);
+        }
+
+        Ok(record)
+    }
+
+    /// Text chunked as the paper body
+    pub fn text(&self) -> &str {
+        self.full_text
+            .as_deref()
+            .filter(|text| !text.trim().is_empty())
+            .or(self.abstract_text.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Abstract stored on the paper; the first 500 characters of the text
+    /// when none is given
+    pub fn abstract_or_excerpt(&self) -> String {
+        match &self.abstract_text {
+            Some(text) if !text.trim().is_empty() => text.clone(),
+            _ => self.text().chars().take(500).collect(),
+        }
+    }
+
+    /// Idempotency key for the paper and its job, when the record has an
+    /// external ID
+    pub fn idempotency_key(&self) -> Option<String> {
+        self.external_id.as_ref().map(|id| format!("import:{}", id))
+    }
+}
+
+/// Counts for a finished import
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub chunks: usize,
+    /// Records loaded by an earlier import
+    pub skipped: usize,
+    /// Lines that failed to parse or validate
+    pub invalid: usize,
+}
+
+/// Import a JSONL file into a tenant's corpus, `batch_size` papers at a time
+pub async fn run(
+    processor: &IngestionProcessor,
+    path: &Path,
+    tenant_id: Uuid,
+    batch_size: usize,
+) -> Result<ImportSummary, IngestionError> {
+    let file = File::open(path).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => IngestionError::FileNotFound(path.display().to_string()),
+        _ => e.into(),
+    })?;
+    let mut lines = BufReader::new(file).lines();
+    let batch_size = batch_size.max(1);
+
+    let mut summary = ImportSummary::default();
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut line_number = 0;
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match ImportRecord::parse(&line) {
+            Ok(record) => batch.push(record),
+            Err(e) => {
+                warn!(line = line_number, error = %e, "Skipping invalid import record");
+                summary.invalid += 1;
+            }
+        }
+
+        if batch.len() >= batch_size {
+            import_batch(processor, tenant_id, std::mem::take(&mut batch), &mut summary).await?;
+        }
+    }
+    if !batch.is_empty() {
+        import_batch(processor, tenant_id, batch, &mut summary).await?;
+    }
+
+    Ok(summary)
+}
+
+async fn import_batch(
+    processor: &IngestionProcessor,
+    tenant_id: Uuid,
+    batch: Vec<ImportRecord>,
+    summary: &mut ImportSummary,
+) -> Result<(), IngestionError> {
+    let records = batch.len();
+    let imported = processor.import_batch(tenant_id, batch).await?;
+
+    summary.imported += imported.len();
+    summary.skipped += records - imported.len();
+    summary.chunks += imported.iter().map(|(_, _, chunks)| chunks).sum::<usize>();
+    info!(
+        imported = summary.imported,
+        skipped = summary.skipped,
+        invalid = summary.invalid,
+        "Import batch loaded"
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_record() {
+        let record = ImportRecord::parse(
+            r#"{"title": "Attention", "abstract": "We propose", "full_text": "Body text",
+                "doi": "https://doi.org/10.1000/XYZ", "external_id": "arxiv:1706.03762",
+                "references": ["Vaswani et al. 2017", {"text": "BERT", "doi": "10.18653/v1/N19-1423"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(record.text(), "Body text");
+        assert_eq!(record.abstract_or_excerpt(), "We propose");
+        assert_eq!(record.idempotency_key().as_deref(), Some("import:arxiv:1706.03762"));
+        assert_eq!(record.doi.as_deref(), Some("10.1000/xyz"));
+        assert_eq!(record.metadata["doi"], "10.1000/xyz");
+
+        let references: Vec<_> = record.references.iter().map(ImportReference::to_pair).collect();
+        assert_eq!(references[0], (None, "Vaswani et al. 2017".to_string()));
+        assert_eq!(references[1].0.as_deref(), Some("10.18653/v1/n19-1423"));
+    }
+
+    #[test]
+    fn test_abstract_only_record() {
+        let record = ImportRecord::parse(r#"{"title": "T", "abstract": "Only an abstract"}"#).unwrap();
+        assert_eq!(record.text(), "Only an abstract");
+        assert!(record.metadata.is_object());
+        assert!(record.idempotency_key().is_none());
+    }
+
+    #[test]
+    fn test_invalid_records_rejected() {
+        for line in [
+            "not json",
+            r#"{"abstract": "no title"}"#,
+            r#"{"title": " ", "abstract": "blank title"}"#,
+            r#"{"title": "No text"}"#,
+            r#"{"title": "T", "abstract": "a", "doi": "not-a-doi"}"#,
+            r#"{"title": "T", "abstract": "a", "metadata": [1]}"#,
+        ] {
+            assert!(ImportRecord::parse(line).is_err(), "{line} should be rejected");
+        }
+    }
+}
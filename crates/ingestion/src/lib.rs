@@ -0,0 +1,89 @@
+//! PaperForge Ingestion Service
+//!
+//! PDF extraction, chunking and job processing behind the `ingestion`
+//! binary. Integration tests drive the same processor in-process.
+
+pub mod alerts;
+#[cfg(feature = "bm25-index")]
+pub mod bm25_sync;
+pub mod cleanup;
+#[cfg(feature = "corpus-gen")]
+pub mod corpus_gen;
+pub mod errors;
+pub mod html;
+pub mod import;
+pub mod math;
+pub mod pdf;
+pub mod processor;
+pub mod purge;
+pub mod retry;
+
+use crate::pdf::PdfLimits;
+use crate::processor::IngestionProcessor;
+use paperforge_common::{
+    chunking::{ChunkStrategy, ChunkingConfig},
+    config::{AppConfig, EmbeddingProviderConfig},
+    crossref::CrossrefClient,
+    fetcher::UrlFetcher,
+    db::{DbPool, Repository},
+    embeddings::create_embedder,
+    queue::Queue,
+    storage::ObjectStore,
+    usage::{MeteredEmbedder, UsageMeter},
+};
+use std::sync::Arc;
+
+/// Build the ingestion processor as `config` describes
+///
+/// Chunks are announced on `embedding_queue` when given; `storage` keeps
+/// original documents.
+pub fn build_processor(
+    config: &AppConfig,
+    db: DbPool,
+    embedding_queue: Option<Arc<Queue>>,
+    storage: Option<Arc<dyn ObjectStore>>,
+) -> Result<IngestionProcessor, Box<dyn std::error::Error>> {
+    let processor = IngestionProcessor::new(
+        db.clone(),
+        embedding_queue,
+        ChunkingConfig {
+            strategy: config.ingestion.chunk_strategy.parse::<ChunkStrategy>()?,
+            chunk_size: config.ingestion.chunk_size,
+            chunk_overlap: config.ingestion.chunk_overlap,
+            min_chunk_size: config.ingestion.min_chunk_size,
+            breakpoint_percentile: config.ingestion.breakpoint_percentile,
+        },
+        config.embedding.model.clone(),
+    )
+    .with_pdf_limits(PdfLimits {
+        max_file_bytes: config.ingestion.max_file_bytes,
+        max_pages: config.ingestion.max_pages,
+        max_extracted_chars: config.ingestion.max_extracted_chars,
+    })
+    .with_exclude_references(config.ingestion.exclude_references)
+    .with_embed_equations(config.ingestion.embed_equations);
+    // Semantic boundaries use the local hashing model unless another provider is set
+    let processor = match config.ingestion.semantic_provider.as_str() {
+        "hash" => processor,
+        provider => processor.with_boundary_embedder(Arc::new(MeteredEmbedder::new(
+            create_embedder(
+                &config.embedding,
+                &EmbeddingProviderConfig {
+                    provider: provider.to_string(),
+                    ..config.embedding.primary()
+                },
+            ),
+            Arc::new(UsageMeter::new(Repository::new(db))),
+        ))),
+    };
+    let processor = if config.crossref.enabled {
+        processor.with_crossref(Arc::new(CrossrefClient::new(&config.crossref)?))
+    } else {
+        processor
+    };
+    let processor = processor.with_fetcher(Arc::new(UrlFetcher::new(&config.fetcher)));
+    Ok(match storage {
+        Some(storage) => processor.with_storage(storage),
+        None => processor,
+    })
+}
@@ -7,19 +7,26 @@
 //! 4. Sends chunks to embedding queue
 //! 5. Updates job status
 
-mod chunker;
-mod errors;
-mod pdf;
-mod processor;
-
-use crate::chunker::ChunkingConfig;
-use crate::processor::{IngestionJobMessage, IngestionProcessor};
 use paperforge_common::{
-    config::AppConfig,
-    db::DbPool,
-    queue::{Queue, QueueConfig},
+    cache::{Cache, CacheConfig},
+    config::{AppConfig, Service},
+    db::{DbPool, Repository},
+    errors::Retryable,
+    metrics,
+    outbox::{OutboxRelay, OutboxRelayConfig, EMBEDDING_QUEUE},
+    queue::{Heartbeat, Queue, QueueConfig, Received, RetryBackoff},
+    storage::create_store,
     VERSION,
 };
+#[cfg(feature = "bm25-index")]
+use paperforge_ingestion::bm25_sync;
+#[cfg(feature = "corpus-gen")]
+use paperforge_ingestion::corpus_gen;
+use paperforge_ingestion::{
+    alerts, build_processor, errors::IngestionError, import,
+    processor::{IngestionProcessor, IngestionQueueMessage},
+    purge, retry,
+};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::{error, info, warn, Level};
@@ -40,24 +47,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting PaperForge Ingestion Service v{}", VERSION);
 
     // Load configuration
-    let config = AppConfig::load().map_err(|e| {
+    let config = AppConfig::load_for(Service::Ingestion).await.map_err(|e| {
         tracing::error!(error = %e, "Failed to load configuration");
         e
     })?;
 
     let config = Arc::new(config);
+    metrics::configure_tenant_labels(&config.observability);
 
     // Initialize database connection
     info!("Connecting to database...");
     let db = DbPool::new(&config.database).await?;
+    db.spawn_metrics_collector(std::time::Duration::from_secs(config.database.pool_metrics_interval_secs));
 
     // Initialize embedding queue (optional - may not be available locally)
-    let embedding_queue = match std::env::var("EMBEDDING_QUEUE_URL") {
-        Ok(url) => {
+    let embedding_queue = match config.queue.embedding_queue_url.clone() {
+        Some(url) => {
             info!(url = %url, "Connecting to embedding queue...");
             let queue_config = QueueConfig {
                 url,
-                dlq_url: std::env::var("DLQ_URL").ok(),
+                dlq_url: config.queue.dlq_url.clone(),
                 ..Default::default()
             };
             match Queue::new(queue_config).await {
@@ -68,19 +77,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
-        Err(_) => {
-            warn!("EMBEDDING_QUEUE_URL not set, running in standalone mode");
+        None => {
+            warn!("Embedding queue URL not configured, running in standalone mode");
             None
         }
     };
 
     // Initialize processor
-    let processor = IngestionProcessor::new(
-        db.clone(),
-        embedding_queue.clone(),
-        ChunkingConfig::default(),
-        config.embedding.model.clone(),
-    );
+    let storage = create_store(&config.storage).await?;
+    let processor = build_processor(&config, db.clone(), embedding_queue.clone(), storage.clone())?;
+
+    // Relay publishes embedding messages written to the outbox
+    let relay = embedding_queue.clone().map(|queue| {
+        OutboxRelay::new(Repository::new(db.clone()), OutboxRelayConfig::default())
+            .with_queue(EMBEDDING_QUEUE, queue)
+    });
 
     // Check for command line arguments for local testing
     let args: Vec<String> = std::env::args().collect();
@@ -100,7 +111,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                 info!(path = %path.display(), "Processing single PDF file");
 
-                match processor.process_local_pdf(&path, tenant_id, None).await {
+                match processor.process_local_pdf(&path, tenant_id, None, None).await {
                     Ok((job_id, paper_id, chunks)) => {
                         info!(
                             job_id = %job_id,
@@ -147,39 +158,157 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
             }
+            "import" => {
+                if args.len() < 4 {
+                    eprintln!("Usage: ingestion import <path-to-jsonl> <tenant-id>");
+                    std::process::exit(1);
+                }
+                let path = PathBuf::from(&args[2]);
+                let tenant_id = Uuid::parse_str(&args[3])?;
+
+                info!(path = %path.display(), tenant_id = %tenant_id, "Importing JSONL corpus");
+
+                match import::run(&processor, &path, tenant_id, config.ingestion.import_batch_size).await {
+                    Ok(summary) => {
+                        println!("Imported {} papers ({} chunks)", summary.imported, summary.chunks);
+                        println!("  Already imported: {}", summary.skipped);
+                        println!("  Invalid lines:    {}", summary.invalid);
+                    }
+                    Err(e) => {
+                        error!(error = %e, "Failed to import corpus");
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            #[cfg(feature = "corpus-gen")]
+            "corpus-gen" => {
+                if args.len() < 3 {
+                    eprintln!("Usage: ingestion corpus-gen <papers> [topics] [seed]");
+                    std::process::exit(1);
+                }
+                let defaults = corpus_gen::CorpusGenConfig::default();
+                let gen_config = corpus_gen::CorpusGenConfig {
+                    papers: args[2].parse()?,
+                    topics: args.get(3).map(|s| s.parse()).transpose()?.unwrap_or(defaults.topics),
+                    seed: args.get(4).map(|s| s.parse()).transpose()?.unwrap_or(defaults.seed),
+                    ..defaults
+                };
+                // Synthetic papers land in the development tenant unless overridden
+                let tenant_id = match std::env::var("CORPUS_TENANT_ID") {
+                    Ok(id) => Uuid::parse_str(&id)?,
+                    Err(_) => Uuid::from_u128(1),
+                };
+
+                info!(
+                    papers = gen_config.papers,
+                    topics = gen_config.topics,
+                    seed = gen_config.seed,
+                    "Generating synthetic corpus"
+                );
+
+                let corpus = corpus_gen::generate(&gen_config);
+                match corpus_gen::load(&processor, &corpus, tenant_id, gen_config.seed).await {
+                    Ok(paper_ids) => {
+                        println!("Generated {} papers, {} citations", paper_ids.len(), corpus.citations.len());
+                    }
+                    Err(e) => {
+                        error!(error = %e, "Failed to load synthetic corpus");
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
             _ => {
                 eprintln!("Unknown command: {}", command);
                 eprintln!("Available commands:");
                 eprintln!("  process-file <path>  - Process a single PDF file");
                 eprintln!("  process-dir <path>   - Process all PDFs in a directory");
+                eprintln!("  import <path> <tenant-id> - Import a JSONL corpus of parsed papers");
+                #[cfg(feature = "corpus-gen")]
+                eprintln!("  corpus-gen <papers> [topics] [seed] - Generate a synthetic corpus");
                 std::process::exit(1);
             }
         }
 
+        // Flush anything the command wrote to the outbox
+        if let Some(relay) = &relay {
+            while relay.run_once().await? > 0 {}
+        }
+
         return Ok(());
     }
 
     // Service mode: poll SQS queue
     info!("Ingestion service ready, starting queue polling...");
 
+    let relay_task = relay.map(OutboxRelay::spawn);
+
+    // Periodically purge soft-deleted papers past retention
+    let purge_task = purge::spawn_purge_task(db.clone(), storage.clone(), config.retention.clone());
+
+    // Re-send failed jobs once their retry is due
+    let retry_task = config
+        .job_retry
+        .enabled
+        .then(|| retry::spawn_retry_task(db.clone(), config.job_retry.clone()));
+
+    // Alert saved searches about newly indexed papers
+    let alert_task = if config.alerts.enabled {
+        let cache = match Cache::new(CacheConfig::from_redis(&config.redis)).await {
+            Ok(cache) => Some(Arc::new(cache)),
+            Err(e) => {
+                warn!(error = %e, "Failed to connect to Redis, saved searches may run on several instances at once");
+                None
+            }
+        };
+        Some(alerts::spawn_alert_task(db.clone(), config.alerts.clone(), config.fetcher.clone(), cache)?)
+    } else {
+        None
+    };
+
+    // Keep the BM25 indexes up to date
+    #[cfg(feature = "bm25-index")]
+    let index_task = if config.bm25_index.enabled {
+        let index = paperforge_common::bm25_index::Bm25Index::new(config.bm25_index.clone(), storage.clone())?;
+        Some(bm25_sync::spawn_index_task(db.clone(), Arc::new(index), config.bm25_index.sync_interval_secs))
+    } else {
+        None
+    };
+    #[cfg(not(feature = "bm25-index"))]
+    if config.bm25_index.enabled {
+        warn!("bm25_index.enabled is set but the ingestion service was built without the bm25-index feature");
+    }
+
     // Initialize ingestion queue
-    let ingestion_queue = match std::env::var("INGESTION_QUEUE_URL") {
-        Ok(url) => {
+    let ingestion_queue = match config.queue.ingestion_queue_url.clone() {
+        Some(url) => {
             let queue_config = QueueConfig {
                 url,
-                dlq_url: std::env::var("DLQ_URL").ok(),
+                dlq_url: config.queue.dlq_url.clone(),
+                max_receive_count: config.queue.max_attempts,
+                retry_backoff: RetryBackoff {
+                    base_delay: std::time::Duration::from_secs(config.queue.retry_base_delay_secs),
+                    max_delay: std::time::Duration::from_secs(config.queue.retry_max_delay_secs),
+                },
                 ..Default::default()
             };
-            Queue::new(queue_config).await?
+            Arc::new(Queue::new(queue_config).await?)
         }
-        Err(_) => {
-            warn!("INGESTION_QUEUE_URL not set, waiting for shutdown signal...");
+        None => {
+            warn!("Ingestion queue URL not configured, waiting for shutdown signal...");
             tokio::signal::ctrl_c().await?;
             info!("Ingestion service shutting down");
             return Ok(());
         }
     };
 
+    let max_processing = std::time::Duration::from_secs(config.queue.max_processing_secs);
+
+    // Shut down for a restart once secrets are rotated
+    let secrets_rotated = config.secrets_rotated(Some(Service::Ingestion));
+    tokio::pin!(secrets_rotated);
+
     // Start polling loop
     loop {
         tokio::select! {
@@ -187,29 +316,73 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 info!("Shutdown signal received");
                 break;
             }
-            result = ingestion_queue.receive::<IngestionJobMessage>() => {
+            _ = &mut secrets_rotated => break,
+            result = ingestion_queue.receive::<IngestionQueueMessage>() => {
                 match result {
                     Ok(messages) => {
-                        for (message, receipt_handle) in messages {
-                            info!(job_id = %message.job_id, "Received ingestion job");
+                        let mut processed = Vec::with_capacity(messages.len());
+                        // Messages stay hidden while earlier ones in the batch are processed,
+                        // until they are deleted below
+                        let heartbeats: Vec<_> = messages
+                            .iter()
+                            .map(|received| ingestion_queue.heartbeat(&received.receipt_handle, max_processing))
+                            .collect();
+
+                        for (received, heartbeat) in messages.into_iter().zip(&heartbeats) {
+                            let job_id = received.message.job_id();
+                            info!(
+                                job_id = %job_id,
+                                message_type = received.envelope.message_type.as_deref(),
+                                traceparent = received.envelope.traceparent.as_deref(),
+                                "Received ingestion job"
+                            );
 
-                            match processor.process_job(message.clone()).await {
+                            match processor.handle_message(received.message.clone()).await {
                                 Ok(()) => {
-                                    // Delete message on success
-                                    if let Err(e) = ingestion_queue.delete(&receipt_handle).await {
-                                        error!(error = %e, "Failed to delete message");
-                                    }
+                                    processed.push(received.receipt_handle);
                                 }
                                 Err(e) => {
                                     error!(
-                                        job_id = %message.job_id,
+                                        job_id = %job_id,
                                         error = %e,
+                                        retryable = e.is_retryable(),
                                         "Failed to process ingestion job"
                                     );
-                                    // Message will be re-delivered or moved to DLQ
+                                    // Retries are scheduled on the job when enabled
+                                    if config.job_retry.enabled {
+                                        settle_on_job(
+                                            &processor,
+                                            &ingestion_queue,
+                                            &config,
+                                            &received,
+                                            heartbeat,
+                                            &e,
+                                            &mut processed,
+                                        )
+                                        .await;
+                                        continue;
+                                    }
+                                    // Fatal failures go straight to the DLQ; retryable ones are re-sent with backoff
+                                    heartbeat.stop();
+                                    match ingestion_queue.handle_failure(&received, &e).await {
+                                        Ok(true) => processed.push(received.receipt_handle),
+                                        Ok(false) => {}
+                                        Err(e) => error!(error = %e, "Failed to settle failed message"),
+                                    }
                                 }
                             }
                         }
+
+                        // Delete processed messages in one round trip
+                        match ingestion_queue.delete_batch(&processed).await {
+                            Ok(result) => {
+                                for failure in result.failed {
+                                    error!(code = %failure.code, "Failed to delete message");
+                                }
+                            }
+                            Err(e) => error!(error = %e, "Failed to delete messages"),
+                        }
+                        drop(heartbeats);
                     }
                     Err(e) => {
                         error!(error = %e, "Failed to receive messages from queue");
@@ -220,6 +393,64 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    purge_task.abort();
+    if let Some(task) = retry_task {
+        task.abort();
+    }
+    if let Some(task) = alert_task {
+        task.abort();
+    }
+    #[cfg(feature = "bm25-index")]
+    if let Some(task) = index_task {
+        task.abort();
+    }
+    if let Some(task) = relay_task {
+        task.abort();
+    }
     info!("Ingestion service shutting down");
     Ok(())
 }
+
+/// Settle a failed message on its job
+///
+/// The message is deleted once the job has a retry scheduled, since the
+/// retry task re-sends it; a dead job's message goes to the DLQ when there
+/// is one. If the job can't be updated, the message's heartbeat is stopped
+/// and the queue settles it as it would with job retries off.
+async fn settle_on_job(
+    processor: &IngestionProcessor,
+    queue: &Queue,
+    config: &AppConfig,
+    message: &Received<IngestionQueueMessage>,
+    heartbeat: &Heartbeat,
+    error: &IngestionError,
+    processed: &mut Vec<String>,
+) {
+    let job_id = message.message.job_id();
+    let receipt_handle = message.receipt_handle.clone();
+    match retry::record_failure(processor.repository(), &config.job_retry, job_id, error).await {
+        Ok(Some(retry_at)) => {
+            info!(job_id = %job_id, retry_at = %retry_at, "Ingestion job retry scheduled");
+            processed.push(receipt_handle);
+        }
+        Ok(None) => {
+            warn!(job_id = %job_id, "Ingestion job is dead");
+            if config.queue.dlq_url.is_some() {
+                if let Err(e) = queue.move_to_dlq(message, &error.failure_reason()).await {
+                    error!(error = %e, "Failed to move dead job's message to DLQ");
+                    return;
+                }
+            }
+            processed.push(receipt_handle);
+        }
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to record job failure, settling message on the queue");
+            heartbeat.stop();
+            match queue.handle_failure(message, error).await {
+                Ok(true) => processed.push(receipt_handle),
+                Ok(false) => {}
+                Err(e) => error!(error = %e, "Failed to settle failed message"),
+            }
+        }
+    }
+}
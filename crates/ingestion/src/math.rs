@@ -0,0 +1,104 @@
+//! Math in text extracted from PDFs
+//!
+//! Inline math stays in the body text, made readable by
+//! [`cleanup`](crate::cleanup). Display equations, lines of their own that
+//! are mostly symbols, are found here so they can be kept as chunks of
+//! their own: embedded on their own they give vectors of little meaning,
+//! and mixed into body chunks they dilute them.
+
+use regex_lite::Regex;
+use std::sync::OnceLock;
+
+/// Longest line taken as a display equation, in characters
+const MAX_EQUATION_CHARS: usize = 200;
+
+/// Share of a numbered line's characters that must be math symbols
+const MIN_NUMBERED_MATH_SHARE: f64 = 0.15;
+
+/// Share of an unnumbered line's characters that must be math symbols
+const MIN_MATH_SHARE: f64 = 0.3;
+
+/// Most prose words a display equation may have
+const MAX_PROSE_WORDS: usize = 2;
+
+/// Function and operator names that read as words but belong to formulas
+const OPERATOR_NAMES: &[&str] = &[
+    "arg", "argmax", "argmin", "cos", "det", "exp", "inf", "lim", "log", "max", "min", "sgn",
+    "sin", "softmax", "sup", "tan", "tanh", "trace",
+];
+
+fn equation_number() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\s*\(\d+(?:\.\d+)?[a-z]?\)$").unwrap())
+}
+
+/// Whether `c` is typical of formulas: operators, relations, brackets,
+/// Greek letters and the like
+fn is_math_char(c: char) -> bool {
+    matches!(c, '=' | '+' | '<' | '>' | '^' | '_' | '|' | '/' | '*' | '(' | ')' | '[' | ']' | '{' | '}')
+        || matches!(c, '\u{0391}'..='\u{03C9}')
+        || matches!(c, '\u{2200}'..='\u{22FF}')
+        || matches!(c, '\u{27C0}'..='\u{27EF}' | '\u{2980}'..='\u{2AFF}')
+        || matches!(c, '\u{2032}'..='\u{2034}' | '\u{00B1}' | '\u{00D7}' | '\u{00F7}' | '\u{00B7}')
+}
+
+/// Whether `c` relates two sides of a formula
+fn is_relation(c: char) -> bool {
+    matches!(
+        c,
+        '=' | '<' | '>' | '≤' | '≥' | '≈' | '≠' | '≡' | '∝' | '∼' | '≃' | '∈' | '⊂' | '⊆' | '→' | '↦'
+    )
+}
+
+/// Whether a whitespace-separated token reads as a prose word
+fn is_prose_word(token: &str) -> bool {
+    let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+    word.len() >= 3
+        && word.len() == token.trim_end_matches(['.', ',', ';', ':']).len()
+        && word.chars().all(|c| c.is_ascii_alphabetic())
+        && !OPERATOR_NAMES.contains(&word.to_ascii_lowercase().as_str())
+}
+
+/// Whether a line reads as a display equation
+///
+/// It has to relate two sides, have next to no prose words and be largely
+/// symbols, with a lower bar for lines ending in an equation number such
+/// as `(3)`.
+pub fn is_display_equation(line: &str) -> bool {
+    let line = line.trim();
+    let numbered = equation_number().find(line).filter(|m| m.start() > 0);
+    let formula = numbered.map(|m| &line[..m.start()]).unwrap_or(line);
+    
+    let chars: Vec<char> = formula.chars().filter(|c| !c.is_whitespace()).collect();
+    if chars.is_empty() || formula.chars().count() > MAX_EQUATION_CHARS {
+        return false;
+    }
+    if !chars.iter().any(|c| is_relation(*c)) {
+        return false;
+    }
+    if formula.split_whitespace().filter(|token| is_prose_word(token)).count() > MAX_PROSE_WORDS {
+        return false;
+    }
+    
+    let math = chars.iter().filter(|c| is_math_char(**c)).count() as f64;
+    let share = math / chars.len() as f64;
+    share >= if numbered.is_some() { MIN_NUMBERED_MATH_SHARE } else { MIN_MATH_SHARE }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_display_equations() {
+        assert!(is_display_equation("Attention(Q, K, V) = softmax(QK^T / √d_k) V (1)"));
+        assert!(is_display_equation("L = −∑ y_i log ŷ_i"));
+        assert!(is_display_equation("∂L/∂θ = ∇_θ L (3.2)"));
+        
+        assert!(!is_display_equation("We set the learning rate to 0.001 in all experiments."));
+        assert!(!is_display_equation("The loss is minimized when the model predicts y = 1 for positive samples (2)."));
+        assert!(!is_display_equation("Results are shown in Table 2 (3)"));
+        assert!(!is_display_equation("and y = 3."));
+        assert!(!is_display_equation("(1)"));
+    }
+}
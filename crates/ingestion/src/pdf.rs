@@ -1,28 +1,223 @@
 //! PDF text extraction module
 //!
-//! Extracts text content from PDF files using lopdf.
+//! Extracts text content from PDF files using lopdf, along with table
+//! regions, figure captions and display equations located from text
+//! positions on each page,
+//! and where each page's text is, so chunks can be traced back to pages.
+//! Page text goes through [`cleanup`](crate::cleanup) before it is joined.
 
+use crate::cleanup::clean_pages;
 use crate::errors::IngestionError;
+use crate::math::is_display_equation;
+use paperforge_common::chunking::TextChunk;
+use paperforge_common::db::models::{ChunkType, Provenance};
+use paperforge_common::errors::AppError;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use tracing::{debug, warn};
 
-/// Extract text content from a PDF file
-pub fn extract_text_from_pdf(path: &Path) -> Result<String, IngestionError> {
+/// Runs closer than this many font sizes horizontally belong to one cell
+const CELL_GAP_EMS: f32 = 1.0;
+
+/// Runs within this many points vertically share a line
+const LINE_TOLERANCE: f32 = 2.0;
+
+/// Longest caption, in lines
+const MAX_CAPTION_LINES: usize = 4;
+
+/// A table, figure caption or display equation found on a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PdfElement {
+    /// `Table`, `FigureCaption` or `Equation`
+    pub kind: ChunkType,
+    /// 1-based page number
+    pub page: u32,
+    /// Approximate bounding box `[x0, y0, x1, y1]` in PDF points
+    pub bbox: [f32; 4],
+    /// Caption, followed by table rows with cells separated by ` | `
+    pub text: String,
+}
+
+/// Where one page's text is in the extracted text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageSpan {
+    /// 1-based page number
+    pub page: u32,
+    /// Byte offset of the page's first character
+    pub start: usize,
+    /// Byte offset just past the page's text
+    pub end: usize,
+}
+
+/// Located elements and page spans of a PDF's extracted text
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PdfLayout {
+    pub elements: Vec<PdfElement>,
+    /// Pages with text, in order; empty for text extracted before pages
+    /// were recorded
+    #[serde(default)]
+    pub pages: Vec<PageSpan>,
+}
+
+/// Text and layout extracted from a PDF
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedPdf {
+    pub text: String,
+    #[serde(flatten)]
+    pub layout: PdfLayout,
+}
+
+/// Turn tables, figure captions and equations into chunks, indexed from
+/// `first_index`
+///
+/// Each element becomes one chunk regardless of size, so a table is never
+/// split across chunks. Positions refer to the element text itself.
+pub fn element_chunks(elements: &[PdfElement], first_index: i32) -> Vec<TextChunk> {
+    elements
+        .iter()
+        .enumerate()
+        .map(|(i, element)| TextChunk {
+            content: element.text.clone(),
+            index: first_index + i as i32,
+            token_count: (element.text.len() / 4) as i32,
+            start_pos: 0,
+            end_pos: element.text.len(),
+            chunk_type: element.kind,
+            metadata: Some(serde_json::json!({
+                "page": element.page,
+                "bbox": element.bbox,
+            })),
+        })
+        .collect()
+}
+
+/// Record in each chunk's metadata the pages it spans and its character
+/// offsets on them
+///
+/// `chunks` hold byte positions in the `text` that `pages` describe.
+/// Chunks keep the metadata they have; ones outside every page get none.
+pub fn annotate_pages(chunks: &mut [TextChunk], text: &str, pages: &[PageSpan]) {
+    for chunk in chunks.iter_mut().filter(|c| c.metadata.is_none()) {
+        if let Some(provenance) = page_provenance(text, pages, chunk.start_pos, chunk.end_pos) {
+            chunk.metadata = serde_json::to_value(provenance).ok();
+        }
+    }
+}
+
+/// Provenance of the text between byte offsets `start` and `end`
+fn page_provenance(text: &str, pages: &[PageSpan], start: usize, end: usize) -> Option<Provenance> {
+    let first = pages.iter().find(|p| start < p.end)?;
+    let last = pages.iter().rev().find(|p| p.start < end)?;
+    if last.page < first.page {
+        return None;
+    }
+    let chars = |from: usize, to: usize| Some(text.get(from..to)?.chars().count() as u32);
+    
+    Some(Provenance {
+        page: first.page,
+        page_end: (last.page != first.page).then_some(last.page),
+        char_start: chars(first.start, start.max(first.start)),
+        char_end: chars(last.start, end.min(last.end)),
+        bbox: None,
+    })
+}
+
+/// Size limits applied while extracting a PDF
+#[derive(Debug, Clone)]
+pub struct PdfLimits {
+    /// Largest file accepted, checked before the file is loaded
+    pub max_file_bytes: u64,
+    /// Most pages accepted
+    pub max_pages: usize,
+    /// Most characters of text extracted before giving up
+    pub max_extracted_chars: usize,
+}
+
+impl Default for PdfLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 100 * 1024 * 1024,
+            max_pages: 2000,
+            max_extracted_chars: 5_000_000,
+        }
+    }
+}
+
+/// Extract text, tables and figure captions from a PDF file
+///
+/// Fails with [`AppError::PayloadTooLarge`] when the file is over the size
+/// limit, and with [`AppError::Validation`] when it has too many pages or
+/// too much text, before memory use grows with the document.
+pub fn extract_pdf(path: &Path, limits: &PdfLimits) -> Result<ExtractedPdf, IngestionError> {
+    let size = std::fs::metadata(path)?.len();
+    if size > limits.max_file_bytes {
+        return Err(AppError::PayloadTooLarge {
+            size: size as usize,
+            limit: limits.max_file_bytes as usize,
+        }
+        .into());
+    }
+
     let doc = lopdf::Document::load(path).map_err(|e| IngestionError::PdfParseError {
         path: path.display().to_string(),
         message: format!("Failed to load PDF: {}", e),
     })?;
+    extract_document(&doc, &path.display().to_string(), limits)
+}
 
-    let mut text = String::new();
+/// Extract a PDF held in memory, as [`extract_pdf`] does a file
+///
+/// `name` identifies the document in errors.
+pub fn extract_pdf_bytes(body: &[u8], name: &str, limits: &PdfLimits) -> Result<ExtractedPdf, IngestionError> {
+    if body.len() as u64 > limits.max_file_bytes {
+        return Err(AppError::PayloadTooLarge {
+            size: body.len(),
+            limit: limits.max_file_bytes as usize,
+        }
+        .into());
+    }
+
+    let doc = lopdf::Document::load_mem(body).map_err(|e| IngestionError::PdfParseError {
+        path: name.to_string(),
+        message: format!("Failed to load PDF: {}", e),
+    })?;
+    extract_document(&doc, name, limits)
+}
+
+/// Extract a loaded document; `name` identifies it in errors
+fn extract_document(doc: &lopdf::Document, name: &str, limits: &PdfLimits) -> Result<ExtractedPdf, IngestionError> {
+    let mut page_texts = Vec::new();
+    let mut extracted_chars = 0;
+    let mut elements = Vec::new();
     let pages = doc.get_pages();
     
     debug!(page_count = pages.len(), "Extracting text from PDF");
 
+    if pages.len() > limits.max_pages {
+        return Err(AppError::Validation {
+            message: format!("PDF has {} pages, limit is {}", pages.len(), limits.max_pages),
+            field: Some("pages".to_string()),
+        }
+        .into());
+    }
+
     for (page_num, _) in pages.iter() {
-        match extract_page_text(&doc, *page_num) {
-            Ok(page_text) => {
-                text.push_str(&page_text);
-                text.push('\n');
+        match page_content(doc, *page_num) {
+            Ok(content) => {
+                let page_text = extract_text_from_content(&content);
+                extracted_chars += page_text.chars().count();
+                if extracted_chars > limits.max_extracted_chars {
+                    return Err(AppError::Validation {
+                        message: format!(
+                            "PDF text exceeds {} characters (stopped at page {})",
+                            limits.max_extracted_chars, page_num
+                        ),
+                        field: Some("text".to_string()),
+                    }
+                    .into());
+                }
+                page_texts.push((*page_num, page_text));
+                elements.extend(detect_elements(*page_num, &extract_runs_from_content(&content)));
             }
             Err(e) => {
                 warn!(page = page_num, error = %e, "Failed to extract text from page, skipping");
@@ -30,38 +225,292 @@ pub fn extract_text_from_pdf(path: &Path) -> Result<String, IngestionError> {
         }
     }
 
+    // Running headers are found by comparing pages, so all are cleaned
+    // together; each is then joined on its own so its span stays known
+    let (page_numbers, page_texts): (Vec<u32>, Vec<String>) = page_texts.into_iter().unzip();
+    let mut text = String::new();
+    let mut page_spans = Vec::new();
+    for (page, page_text) in page_numbers.into_iter().zip(clean_pages(&page_texts)) {
+        let cleaned = clean_text(&page_text);
+        if cleaned.is_empty() {
+            continue;
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        let start = text.len();
+        text.push_str(&cleaned);
+        page_spans.push(PageSpan {
+            page,
+            start,
+            end: text.len(),
+        });
+    }
+
     if text.trim().is_empty() {
         return Err(IngestionError::PdfParseError {
-            path: path.display().to_string(),
+            path: name.to_string(),
             message: "No text content extracted from PDF".to_string(),
         });
     }
 
-    // Clean up the extracted text
-    let cleaned = clean_text(&text);
-    
     debug!(
-        original_len = text.len(),
-        cleaned_len = cleaned.len(),
+        text_len = text.len(),
+        pages_with_text = page_spans.len(),
+        elements = elements.len(),
         "Text extraction complete"
     );
 
-    Ok(cleaned)
+    Ok(ExtractedPdf {
+        text,
+        layout: PdfLayout {
+            elements,
+            pages: page_spans,
+        },
+    })
 }
 
-/// Extract text from a single page
-fn extract_page_text(doc: &lopdf::Document, page_num: u32) -> Result<String, String> {
+/// Raw content stream of a single page
+fn page_content(doc: &lopdf::Document, page_num: u32) -> Result<Vec<u8>, String> {
     let page_id = doc
         .page_iter()
         .nth((page_num - 1) as usize)
         .ok_or_else(|| format!("Page {} not found", page_num))?;
 
-    let content = doc.get_page_content(page_id).map_err(|e| e.to_string())?;
-    
-    // Parse content stream and extract text
-    let text = extract_text_from_content(&content);
-    
-    Ok(text)
+    doc.get_page_content(page_id).map_err(|e| e.to_string())
+}
+
+/// A piece of text shown at a position on the page
+#[derive(Debug, Clone, PartialEq)]
+struct TextRun {
+    x: f32,
+    y: f32,
+    font_size: f32,
+    text: String,
+}
+
+impl TextRun {
+    /// Estimated right edge (average glyph width of half an em)
+    fn end_x(&self) -> f32 {
+        self.x + self.text.chars().count() as f32 * self.font_size * 0.5
+    }
+}
+
+/// Extract positioned text runs from a PDF content stream
+///
+/// Tracks the text line matrix through `Td`, `TD`, `Tm`, `T*` and `TL`,
+/// and the font size through `Tf`. Scaling and rotation are ignored.
+fn extract_runs_from_content(content: &[u8]) -> Vec<TextRun> {
+    let content_str = String::from_utf8_lossy(content);
+    let mut runs = Vec::new();
+    let (mut x, mut y) = (0.0_f32, 0.0_f32);
+    let mut font_size = 10.0_f32;
+    let mut leading = 0.0_f32;
+
+    for line in content_str.lines() {
+        let trimmed = line.trim();
+        let operands: Vec<f32> = trimmed
+            .split_whitespace()
+            .filter_map(|token| token.parse().ok())
+            .collect();
+
+        if trimmed == "BT" {
+            x = 0.0;
+            y = 0.0;
+        } else if trimmed.ends_with("Tf") {
+            if let Some(size) = operands.last() {
+                font_size = size.abs().max(1.0);
+            }
+        } else if trimmed.ends_with("TL") {
+            if let Some(value) = operands.last() {
+                leading = *value;
+            }
+        } else if trimmed.ends_with("TD") || trimmed.ends_with("Td") {
+            if let [tx, ty] = operands[..] {
+                x += tx;
+                y += ty;
+                if trimmed.ends_with("TD") {
+                    leading = -ty;
+                }
+            }
+        } else if trimmed.ends_with("Tm") {
+            if let [_, _, _, _, e, f] = operands[..] {
+                x = e;
+                y = f;
+            }
+        } else if trimmed == "T*" {
+            y -= leading;
+        } else if let Some(text) = extract_text_from_operator(trimmed) {
+            // ' and " move to the next line before showing text
+            if trimmed.ends_with('\'') || trimmed.ends_with('"') {
+                y -= leading;
+            }
+            if !text.trim().is_empty() {
+                runs.push(TextRun { x, y, font_size, text });
+            }
+        }
+    }
+
+    runs
+}
+
+/// Runs grouped into visual lines, top of the page first
+fn group_lines(runs: &[TextRun]) -> Vec<Vec<TextRun>> {
+    let mut sorted = runs.to_vec();
+    sorted.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+    let mut lines: Vec<Vec<TextRun>> = Vec::new();
+    for run in sorted {
+        match lines.last_mut() {
+            Some(line) if (line[0].y - run.y).abs() <= LINE_TOLERANCE => line.push(run),
+            _ => lines.push(vec![run]),
+        }
+    }
+    for line in &mut lines {
+        line.sort_by(|a, b| a.x.total_cmp(&b.x));
+    }
+    lines
+}
+
+/// Merge a line's runs into cells separated by wide horizontal gaps
+fn cells(line: &[TextRun]) -> Vec<String> {
+    let mut cells: Vec<String> = Vec::new();
+    let mut previous: Option<&TextRun> = None;
+    for run in line {
+        let joined = previous
+            .map(|prev| run.x - prev.end_x() < prev.font_size * CELL_GAP_EMS)
+            .unwrap_or(false);
+        match cells.last_mut() {
+            Some(cell) if joined => {
+                cell.push(' ');
+                cell.push_str(run.text.trim());
+            }
+            _ => cells.push(run.text.trim().to_string()),
+        }
+        previous = Some(run);
+    }
+    cells
+}
+
+fn line_text(line: &[TextRun]) -> String {
+    clean_text(&cells(line).join(" "))
+}
+
+/// Whether `text` starts a caption labelled with one of `labels`, e.g. "Table 2:"
+fn is_caption(text: &str, labels: &[&str]) -> bool {
+    labels.iter().any(|label| {
+        text.strip_prefix(label)
+            .map(|rest| rest.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+            .unwrap_or(false)
+    })
+}
+
+fn bounding_box(lines: &[Vec<TextRun>]) -> [f32; 4] {
+    let runs = lines.iter().flatten();
+    let mut bbox = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+    for run in runs {
+        bbox[0] = bbox[0].min(run.x);
+        bbox[1] = bbox[1].min(run.y);
+        bbox[2] = bbox[2].max(run.end_x());
+        bbox[3] = bbox[3].max(run.y + run.font_size);
+    }
+    bbox
+}
+
+/// Find figure captions, tables and display equations among a page's text
+/// runs
+///
+/// Captions start with "Figure N" / "Fig. N" and run on while lines stay
+/// tightly spaced. Equations are consecutive lines that read as formulas
+/// (see [`is_display_equation`]). Tables are consecutive lines with two or
+/// more cells; a "Table N" caption directly above or below is attached to
+/// them. Uncaptioned tables need at least three such rows.
+fn detect_elements(page: u32, runs: &[TextRun]) -> Vec<PdfElement> {
+    let lines = group_lines(runs);
+    let mut elements = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let text = line_text(&lines[i]);
+
+        if is_caption(&text, &["Figure", "Fig."]) {
+            let end = caption_end(&lines, i);
+            elements.push(PdfElement {
+                kind: ChunkType::FigureCaption,
+                page,
+                bbox: bounding_box(&lines[i..end]),
+                text: lines[i..end].iter().map(|l| line_text(l)).collect::<Vec<_>>().join(" "),
+            });
+            i = end;
+            continue;
+        }
+
+        if is_display_equation(&text) {
+            let mut end = i + 1;
+            while end < lines.len() && is_display_equation(&line_text(&lines[end])) {
+                end += 1;
+            }
+            elements.push(PdfElement {
+                kind: ChunkType::Equation,
+                page,
+                bbox: bounding_box(&lines[i..end]),
+                text: lines[i..end].iter().map(|l| line_text(l)).collect::<Vec<_>>().join("\n"),
+            });
+            i = end;
+            continue;
+        }
+
+        let caption = is_caption(&text, &["Table"]).then(|| (i, caption_end(&lines, i)));
+        let rows_start = caption.map(|(_, end)| end).unwrap_or(i);
+        let mut rows_end = rows_start;
+        while rows_end < lines.len() && cells(&lines[rows_end]).len() >= 2 {
+            rows_end += 1;
+        }
+
+        let rows = rows_end - rows_start;
+        if rows >= 3 || (caption.is_some() && rows >= 2) {
+            let caption_text = caption.map(|(start, end)| {
+                lines[start..end].iter().map(|l| line_text(l)).collect::<Vec<_>>().join(" ")
+            });
+            let body = lines[rows_start..rows_end]
+                .iter()
+                .map(|l| cells(l).join(" | "))
+                .collect::<Vec<_>>()
+                .join("\n");
+            elements.push(PdfElement {
+                kind: ChunkType::Table,
+                page,
+                bbox: bounding_box(&lines[i..rows_end]),
+                text: match caption_text {
+                    Some(caption) => format!("{}\n{}", caption, body),
+                    None => body,
+                },
+            });
+            i = rows_end;
+            continue;
+        }
+
+        i += 1;
+    }
+
+    elements
+}
+
+/// End (exclusive) of the caption starting at line `start`
+///
+/// A caption continues over single-cell lines spaced no wider than its first
+/// line's font allows.
+fn caption_end(lines: &[Vec<TextRun>], start: usize) -> usize {
+    let line_height = lines[start][0].font_size * 1.6;
+    let mut end = start + 1;
+    while end < lines.len()
+        && end - start < MAX_CAPTION_LINES
+        && cells(&lines[end]).len() == 1
+        && lines[end - 1][0].y - lines[end][0].y <= line_height
+    {
+        end += 1;
+    }
+    end
 }
 
 /// Extract text from PDF content stream
@@ -84,13 +533,18 @@ fn extract_text_from_content(content: &[u8]) -> String {
             in_text_block = false;
             if !current_text.is_empty() {
                 text.push_str(&current_text);
-                text.push(' ');
+                text.push('\n');
                 current_text.clear();
             }
             continue;
         }
         
         if in_text_block {
+            // Keep line breaks for cleanup, which works line by line
+            if starts_new_line(trimmed) && !current_text.is_empty() && !current_text.ends_with('\n') {
+                current_text.push('\n');
+            }
+            
             // Look for text showing operators: Tj, TJ, ', "
             if let Some(text_content) = extract_text_from_operator(trimmed) {
                 current_text.push_str(&text_content);
@@ -101,6 +555,20 @@ fn extract_text_from_content(content: &[u8]) -> String {
     text
 }
 
+/// Whether a content stream operator moves text to a new line
+fn starts_new_line(operator: &str) -> bool {
+    if operator.ends_with("Td") || operator.ends_with("TD") {
+        let operands: Vec<f32> = operator
+            .split_whitespace()
+            .filter_map(|token| token.parse().ok())
+            .collect();
+        matches!(operands[..], [_, ty] if ty != 0.0)
+    } else {
+        // ' and " move to the next line before showing text
+        operator == "T*" || operator.ends_with("Tm") || operator.ends_with('\'') || operator.ends_with('"')
+    }
+}
+
 /// Extract text from a PDF text operator
 fn extract_text_from_operator(line: &str) -> Option<String> {
     // Handle (text) Tj operator
@@ -170,12 +638,17 @@ fn decode_pdf_string(s: &str) -> String {
 }
 
 /// Clean extracted text
+///
+/// Line breaks are kept, so headings stay on lines of their own for
+/// section detection; blank lines are dropped.
 fn clean_text(text: &str) -> String {
     text
+        .lines()
         // Replace multiple whitespace with single space
-        .split_whitespace()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
         .collect::<Vec<_>>()
-        .join(" ")
+        .join("\n")
         // Remove common PDF artifacts
         .replace("", "") // Remove BOM
         .replace("\u{FEFF}", "")
@@ -194,7 +667,205 @@ mod tests {
     fn test_clean_text() {
         let input = "Hello   World\n\nTest";
         let cleaned = clean_text(input);
-        assert_eq!(cleaned, "Hello World Test");
+        assert_eq!(cleaned, "Hello World\nTest");
+    }
+
+    #[test]
+    fn test_detect_table_and_caption() {
+        let content = b"BT
+/F1 10 Tf
+72 700 Td
+(Table 2: Ablation results) Tj
+0 -14 Td
+(Model) Tj
+200 0 Td
+(F1) Tj
+-200 -14 Td
+(Baseline) Tj
+200 0 Td
+(71.2) Tj
+-200 -14 Td
+(Ours) Tj
+200 0 Td
+(78.9) Tj
+ET
+BT
+72 400 Td
+(Figure 3: Attention weights) Tj
+0 -14 Td
+(over the input tokens.) Tj
+0 -40 Td
+(Unrelated body text follows here.) Tj
+ET";
+        let elements = detect_elements(4, &extract_runs_from_content(content));
+        assert_eq!(elements.len(), 2);
+
+        let table = &elements[0];
+        assert_eq!(table.kind, ChunkType::Table);
+        assert_eq!(table.page, 4);
+        assert_eq!(
+            table.text,
+            "Table 2: Ablation results\nModel | F1\nBaseline | 71.2\nOurs | 78.9"
+        );
+        assert_eq!(table.bbox[0], 72.0);
+        assert_eq!(table.bbox[3], 710.0);
+
+        let caption = &elements[1];
+        assert_eq!(caption.kind, ChunkType::FigureCaption);
+        assert_eq!(caption.text, "Figure 3: Attention weights over the input tokens.");
+    }
+
+    #[test]
+    fn test_detect_equation() {
+        let content = b"BT
+/F1 10 Tf
+72 700 Td
+(We minimize the squared error) Tj
+0 -20 Td
+(L = \\(y - f\\(x\\)\\)^2 \\(1\\)) Tj
+0 -20 Td
+(over all training pairs.) Tj
+ET";
+        let elements = detect_elements(2, &extract_runs_from_content(content));
+        assert_eq!(elements.len(), 1);
+        assert_eq!(elements[0].kind, ChunkType::Equation);
+        assert_eq!(elements[0].text, "L = (y - f(x))^2 (1)");
+    }
+
+    #[test]
+    fn test_element_chunks() {
+        let elements = vec![PdfElement {
+            kind: ChunkType::Table,
+            page: 3,
+            bbox: [72.0, 500.0, 300.0, 640.0],
+            text: "Table 1: Results\nModel | F1".to_string(),
+        }];
+
+        let chunks = element_chunks(&elements, 5);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].index, 5);
+        assert_eq!(chunks[0].chunk_type, ChunkType::Table);
+
+        let metadata = chunks[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata["page"], 3);
+        assert_eq!(metadata["bbox"][2], 300.0);
+    }
+
+    #[test]
+    fn test_annotate_pages() {
+        let text = "First page text. Second page starts here and ends. Third";
+        let pages = [
+            PageSpan { page: 1, start: 0, end: 16 },
+            PageSpan { page: 2, start: 17, end: 50 },
+            PageSpan { page: 4, start: 51, end: 56 },
+        ];
+        let chunk = |start_pos: usize, end_pos: usize| TextChunk {
+            content: text[start_pos..end_pos].to_string(),
+            index: 0,
+            token_count: 0,
+            start_pos,
+            end_pos,
+            chunk_type: ChunkType::Text,
+            metadata: None,
+        };
+        let mut chunks = vec![chunk(6, 16), chunk(11, 40), chunk(45, 56)];
+        annotate_pages(&mut chunks, text, &pages);
+        
+        let provenance = |i: usize| Provenance::from_metadata(chunks[i].metadata.as_ref().unwrap()).unwrap();
+        assert_eq!(provenance(0).page, 1);
+        assert_eq!(provenance(0).page_end, None);
+        assert_eq!((provenance(0).char_start, provenance(0).char_end), (Some(6), Some(16)));
+        
+        assert_eq!((provenance(1).page, provenance(1).page_end), (1, Some(2)));
+        assert_eq!((provenance(1).char_start, provenance(1).char_end), (Some(11), Some(23)));
+        
+        assert_eq!((provenance(2).page, provenance(2).page_end), (2, Some(4)));
+        assert_eq!(provenance(2).char_end, Some(5));
+    }
+
+    #[test]
+    fn test_file_size_limit() {
+        let path = std::env::temp_dir().join(format!("paperforge-limit-{}.pdf", std::process::id()));
+        std::fs::write(&path, vec![0u8; 2048]).unwrap();
+        let limits = PdfLimits {
+            max_file_bytes: 1024,
+            ..Default::default()
+        };
+
+        let err = extract_pdf(&path, &limits).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, IngestionError::App(AppError::PayloadTooLarge { size: 2048, limit: 1024 })));
+        assert!(err.failure_reason().starts_with("PAYLOAD_TOO_LARGE: "));
+    }
+
+    /// A one-page PDF showing `content`
+    fn pdf(content: &[u8]) -> Vec<u8> {
+        use lopdf::{dictionary, Object, Stream};
+
+        let mut doc = lopdf::Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.to_vec()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }));
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut body = Vec::new();
+        doc.save_to(&mut body).unwrap();
+        body
+    }
+
+    #[test]
+    fn test_extract_pdf_bytes() {
+        let body = pdf(b"BT /F1 10 Tf 72 700 Td (Attention is all you need.) Tj ET");
+        let extracted = extract_pdf_bytes(&body, "upload", &PdfLimits::default()).unwrap();
+        assert_eq!(extracted.text, "Attention is all you need.");
+        assert_eq!(extracted.layout.pages.len(), 1);
+
+        let limits = PdfLimits {
+            max_file_bytes: 16,
+            ..Default::default()
+        };
+        let err = extract_pdf_bytes(&body, "upload", &limits).unwrap_err();
+        assert!(matches!(err, IngestionError::App(AppError::PayloadTooLarge { limit: 16, .. })));
+
+        let err = extract_pdf_bytes(b"%PDF-1.7 truncated", "upload", &PdfLimits::default()).unwrap_err();
+        assert!(matches!(err, IngestionError::PdfParseError { .. }));
+    }
+
+    #[test]
+    fn test_text_keeps_line_breaks() {
+        let content = b"BT
+/F1 10 Tf
+72 700 Td
+(Self-attention relates posi-) Tj
+0 -12 Td
+(tions of a sequence) Tj
+40 0 Td
+(.) Tj
+ET";
+        assert_eq!(
+            extract_text_from_content(content),
+            "Self-attention relates posi-\ntions of a sequence.\n"
+        );
     }
 
     #[test]
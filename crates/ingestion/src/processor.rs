@@ -2,14 +2,32 @@
 //!
 //! Core logic for processing papers: PDF extraction, chunking, and queue dispatch.
 
-use crate::chunker::{chunk_text, ChunkingConfig, TextChunk};
 use crate::errors::IngestionError;
-use crate::pdf::extract_text_from_pdf;
-use paperforge_common::db::{DbPool, Repository};
-use paperforge_common::queue::Queue;
-use serde::{Deserialize, Serialize};
+use crate::html::extract_html;
+use crate::import::{ImportRecord, ImportReference};
+use crate::pdf::{annotate_pages, element_chunks, extract_pdf, ExtractedPdf, PdfElement, PdfLayout, PdfLimits};
+use paperforge_common::authors::metadata_authors;
+use paperforge_common::chunking::{self, detect_sections, ChunkingConfig, TextChunk};
+use paperforge_common::crossref::{find_doi, normalize_doi, CrossrefClient};
+use paperforge_common::db::{self, DbPool, PaperUpdate, Repository};
+use paperforge_common::db::models::{CheckpointStage, ChunkType, IngestionJob, JobStatus, Paper};
+use paperforge_common::embeddings::{Embedder, HashEmbedder};
+use paperforge_common::errors::{AppError, Retryable};
+use paperforge_common::fetcher::{DocumentKind, FetchedDocument, UrlFetcher};
+use paperforge_common::{metrics, usage};
+use paperforge_common::outbox::{OutboxPayload, EMBEDDING_QUEUE};
+use paperforge_common::queue::{
+    Consumable, Envelope, IngestionJobMessage as SubmittedPaperMessage, IngestionJobOptions, Queue,
+    ReprocessPaperMessage, StoredDocumentMessage, UrlImportMessage, Versioned,
+};
+use paperforge_common::references::{extract_citations, reference_section};
+use paperforge_common::storage::{document_key, ObjectStore, SOURCE_OBJECT, TEXT_OBJECT};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, error, info, instrument, warn};
 use uuid::Uuid;
 
@@ -22,12 +40,100 @@ pub struct EmbeddingJob {
     pub embedding_model: String,
 }
 
+impl Versioned for EmbeddingJob {
+    const MESSAGE_TYPE: &'static str = "embedding.job";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
 /// Chunk data for embedding
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChunkData {
     pub index: i32,
     pub content: String,
     pub token_count: i32,
+    #[serde(default)]
+    pub chunk_type: ChunkType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    /// Byte range of a text chunk in the paper's text, kept so the text can
+    /// be rebuilt for reprocessing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub char_range: Option<Range<usize>>,
+}
+
+/// Any message accepted on the ingestion queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IngestionQueueMessage {
+    Ingest(IngestionJobMessage),
+    Reprocess(ReprocessPaperMessage),
+    Submit(SubmittedPaperMessage),
+    Document(StoredDocumentMessage),
+    Url(UrlImportMessage),
+}
+
+impl IngestionQueueMessage {
+    pub fn job_id(&self) -> Uuid {
+        match self {
+            Self::Ingest(m) => m.job_id,
+            Self::Reprocess(m) => m.job_id,
+            Self::Submit(m) => m.job_id,
+            Self::Document(m) => m.job_id,
+            Self::Url(m) => m.job_id,
+        }
+    }
+
+    pub fn tenant_id(&self) -> Uuid {
+        match self {
+            Self::Ingest(m) => m.tenant_id,
+            Self::Reprocess(m) => m.tenant_id,
+            Self::Submit(m) => m.tenant_id,
+            Self::Document(m) => m.tenant_id,
+            Self::Url(m) => m.tenant_id,
+        }
+    }
+}
+
+impl Consumable for IngestionQueueMessage {
+    const ACCEPTS: &'static [(&'static str, u32)] = &[
+        (IngestionJobMessage::MESSAGE_TYPE, IngestionJobMessage::SCHEMA_VERSION),
+        (ReprocessPaperMessage::MESSAGE_TYPE, ReprocessPaperMessage::SCHEMA_VERSION),
+        (SubmittedPaperMessage::MESSAGE_TYPE, SubmittedPaperMessage::SCHEMA_VERSION),
+        (StoredDocumentMessage::MESSAGE_TYPE, StoredDocumentMessage::SCHEMA_VERSION),
+        (UrlImportMessage::MESSAGE_TYPE, UrlImportMessage::SCHEMA_VERSION),
+    ];
+
+    /// Decode by the envelope's type; legacy messages are told apart by
+    /// their fields
+    fn decode(envelope: &Envelope, body: &str) -> serde_json::Result<Self> {
+        match envelope.message_type.as_deref() {
+            Some(IngestionJobMessage::MESSAGE_TYPE) => serde_json::from_str(body).map(Self::Ingest),
+            Some(ReprocessPaperMessage::MESSAGE_TYPE) => serde_json::from_str(body).map(Self::Reprocess),
+            Some(SubmittedPaperMessage::MESSAGE_TYPE) => serde_json::from_str(body).map(Self::Submit),
+            Some(StoredDocumentMessage::MESSAGE_TYPE) => serde_json::from_str(body).map(Self::Document),
+            Some(UrlImportMessage::MESSAGE_TYPE) => serde_json::from_str(body).map(Self::Url),
+            _ => serde_json::from_str(body),
+        }
+    }
+}
+
+/// Fields for a paper created by the pipeline
+#[derive(Debug, Clone, Default)]
+pub struct NewPaper {
+    pub title: String,
+    /// Defaults to the first 500 characters of the text
+    pub abstract_text: Option<String>,
+    pub source: Option<String>,
+    pub external_id: Option<String>,
+    /// Normalized DOI; when absent one is looked for in the metadata,
+    /// external ID and text
+    pub doi: Option<String>,
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub metadata: serde_json::Value,
+    pub idempotency_key: Option<String>,
+    /// Storage keys of the original document and extracted text
+    pub source_key: Option<String>,
+    pub text_key: Option<String>,
 }
 
 /// Ingestion job message (received from SQS)
@@ -41,6 +147,15 @@ pub struct IngestionJobMessage {
     pub metadata: serde_json::Value,
 }
 
+impl Versioned for IngestionJobMessage {
+    const MESSAGE_TYPE: &'static str = "paper.ingest";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn tenant_id(&self) -> Option<Uuid> {
+        Some(self.tenant_id)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SourceType {
@@ -55,6 +170,18 @@ pub struct IngestionProcessor {
     embedding_queue: Option<Arc<Queue>>,
     chunking_config: ChunkingConfig,
     embedding_model: String,
+    crossref: Option<Arc<CrossrefClient>>,
+    pdf_limits: PdfLimits,
+    /// Embeds sentences to find semantic chunk boundaries
+    boundary_embedder: Arc<dyn Embedder>,
+    /// Keeps original documents; `None` when storage is disabled
+    storage: Option<Arc<dyn ObjectStore>>,
+    /// Leave reference lists out of chunks for tenants that don't say
+    exclude_references: bool,
+    /// Keep display equations as chunks of their own
+    embed_equations: bool,
+    /// Downloads documents imported by URL; `None` when not configured
+    fetcher: Option<Arc<UrlFetcher>>,
 }
 
 impl IngestionProcessor {
@@ -69,116 +196,926 @@ impl IngestionProcessor {
             embedding_queue,
             chunking_config,
             embedding_model,
+            crossref: None,
+            pdf_limits: PdfLimits::default(),
+            boundary_embedder: Arc::new(HashEmbedder::new(256)),
+            storage: None,
+            exclude_references: true,
+            embed_equations: true,
+            fetcher: None,
         }
     }
 
-    /// Process a local PDF file directly (for testing without SQS)
+    /// Embedder used for semantic chunk boundaries, instead of the local
+    /// hashing model
+    pub fn with_boundary_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.boundary_embedder = embedder;
+        self
+    }
+
+    /// Override the default PDF size limits
+    pub fn with_pdf_limits(mut self, limits: PdfLimits) -> Self {
+        self.pdf_limits = limits;
+        self
+    }
+
+    /// Keep original PDFs and their extracted text in document storage
+    pub fn with_storage(mut self, storage: Arc<dyn ObjectStore>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Whether reference lists are left out of chunks for tenants whose
+    /// settings don't say
+    pub fn with_exclude_references(mut self, exclude: bool) -> Self {
+        self.exclude_references = exclude;
+        self
+    }
+
+    /// Whether display equations are embedded as chunks of their own, or
+    /// only left in the body text
+    pub fn with_embed_equations(mut self, embed: bool) -> Self {
+        self.embed_equations = embed;
+        self
+    }
+
+    /// Resolve DOIs against Crossref to backfill paper metadata
+    pub fn with_crossref(mut self, client: Arc<CrossrefClient>) -> Self {
+        self.crossref = Some(client);
+        self
+    }
+
+    /// Download documents imported by URL with `fetcher`
+    pub fn with_fetcher(mut self, fetcher: Arc<UrlFetcher>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    /// Process a local PDF file
+    ///
+    /// A new job is created unless `job_id` is given. Extracted text is
+    /// checkpointed, so a retried job doesn't parse the PDF again. A PDF
+    /// over the size limits fails the job without being retried. With
+    /// document storage configured, the PDF and its text are stored before
+    /// the checkpoint is saved.
     #[instrument(skip(self), fields(path = %path.display()))]
     pub async fn process_local_pdf(
         &self,
         path: &Path,
         tenant_id: Uuid,
+        job_id: Option<Uuid>,
         title: Option<String>,
     ) -> Result<(Uuid, Uuid, Vec<TextChunk>), IngestionError> {
         info!("Processing local PDF");
 
-        // Create job
-        let job = self
-            .repository
-            .create_job(tenant_id, None)
-            .await
-            .map_err(|e| IngestionError::DatabaseError(e.to_string()))?;
-
-        let job_id = job.id;
-
-        // Extract text from PDF
-        info!("Extracting text from PDF...");
-        let text = extract_text_from_pdf(path)?;
+        let job_id = match job_id {
+            Some(id) => id,
+            None => self.repository.create_job(tenant_id, None).await?.id,
+        };
 
         // Get title from metadata or filename
-        let paper_title = title.unwrap_or_else(|| {
-            path.file_stem()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_else(|| "Untitled".to_string())
-        });
+        let paper_title = title.unwrap_or_else(|| file_title(path));
 
-        // Create paper record
-        let paper = self
-            .repository
-            .create_paper(
-                tenant_id,
-                paper_title,
-                text.chars().take(500).collect(), // First 500 chars as abstract
-                Some(path.display().to_string()),
-                None,
-                serde_json::json!({
+        self.process_pdf(
+            path,
+            tenant_id,
+            job_id,
+            NewPaper {
+                title: paper_title,
+                source: Some(path.display().to_string()),
+                metadata: serde_json::json!({
                     "source": "local_file",
                     "file_path": path.display().to_string(),
                 }),
-                None,
-            )
+                ..Default::default()
+            },
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Process a PDF uploaded to document storage, e.g. an entry of an
+    /// uploaded archive
+    ///
+    /// The PDF is fetched to a temporary file and goes through the same
+    /// pipeline as a local one; the stored object is kept as the paper's
+    /// source.
+    #[instrument(skip(self, message), fields(job_id = %message.job_id, file_name = %message.file_name))]
+    pub async fn process_stored_document(&self, message: StoredDocumentMessage) -> Result<(), IngestionError> {
+        info!("Processing stored document");
+
+        let storage = self.storage.as_ref().ok_or_else(|| {
+            IngestionError::ConfigError("Document storage is not configured".to_string())
+        })?;
+        let source = storage
+            .get(&message.source_key)
+            .await?
+            .ok_or_else(|| IngestionError::FileNotFound(message.source_key.clone()))?;
+
+        let chunking = self.chunking_for(&message.options);
+        self.process_pdf_bytes(
+            source,
+            message.tenant_id,
+            message.job_id,
+            NewPaper {
+                title: file_title(Path::new(&message.file_name)),
+                source: Some(message.file_name.clone()),
+                metadata: serde_json::json!({
+                    "source": "archive",
+                    "file_name": message.file_name,
+                    "batch_id": message.batch_id,
+                }),
+                ..Default::default()
+            },
+            Some(message.source_key.clone()),
+            Some(&chunking),
+        )
+        .await
+    }
+
+    /// Download a document from a user-supplied URL and ingest it
+    ///
+    /// PDFs go through the same pipeline as local ones. HTML pages are
+    /// ingested as text, unless they link their full text as a PDF
+    /// (`citation_pdf_url`), which is fetched in their place. A URL the
+    /// fetch policy refuses fails the job without being retried.
+    #[instrument(skip(self, message), fields(job_id = %message.job_id, url = %message.url))]
+    pub async fn import_url(&self, message: UrlImportMessage) -> Result<(), IngestionError> {
+        info!("Importing paper from URL");
+
+        let fetcher = self.fetcher.as_ref().ok_or_else(|| {
+            IngestionError::ConfigError("URL fetching is not configured".to_string())
+        })?;
+        let document = match fetcher.fetch(&message.url).await {
+            Ok(document) => document,
+            Err(e) => {
+                let e = IngestionError::from(e);
+                if !e.is_retryable() {
+                    self.fail_job(message.job_id, &e).await;
+                }
+                return Err(e);
+            }
+        };
+
+        let chunking = self.chunking_for(&message.options);
+        let mut paper = NewPaper {
+            title: message.title.clone().unwrap_or_default(),
+            source: Some(message.url.clone()),
+            metadata: url_metadata(&message, &document),
+            idempotency_key: message.idempotency_key.clone(),
+            ..Default::default()
+        };
+
+        if document.kind == DocumentKind::Html {
+            let page = extract_html(&String::from_utf8_lossy(&document.body));
+            if paper.title.is_empty() {
+                paper.title = page.title.clone().unwrap_or_default();
+            }
+            paper.doi = page.doi.as_deref().and_then(normalize_doi);
+
+            let linked_pdf = match page.pdf_url.as_deref().map(|link| document.url.join(link)) {
+                Some(Ok(pdf_url)) => match fetcher.fetch(pdf_url.as_str()).await {
+                    Ok(pdf) if pdf.kind == DocumentKind::Pdf => Some(pdf),
+                    Ok(_) => {
+                        warn!(pdf_url = %pdf_url, "Linked full text is not a PDF, using the page text");
+                        None
+                    }
+                    Err(e) => {
+                        warn!(pdf_url = %pdf_url, error = %e, "Failed to fetch linked full text, using the page text");
+                        None
+                    }
+                },
+                Some(Err(e)) => {
+                    warn!(error = %e, "Invalid full text link, using the page text");
+                    None
+                }
+                None => None,
+            };
+
+            let Some(pdf) = linked_pdf else {
+                if page.text.is_empty() {
+                    let e = IngestionError::App(AppError::Validation {
+                        message: "Page has no text".to_string(),
+                        field: Some("url".to_string()),
+                    });
+                    self.fail_job(message.job_id, &e).await;
+                    return Err(e);
+                }
+                if paper.title.is_empty() {
+                    paper.title = message.url.clone();
+                }
+                self.process_text(
+                    message.tenant_id,
+                    Some(message.job_id),
+                    paper,
+                    &page.text,
+                    &PdfLayout::default(),
+                    Some(&chunking),
+                )
+                .await?;
+                return Ok(());
+            };
+            paper.metadata["pdf_url"] = pdf.url.as_str().into();
+            return self.import_fetched_pdf(pdf, &message, paper, &chunking).await;
+        }
+
+        self.import_fetched_pdf(document, &message, paper, &chunking).await
+    }
+
+    async fn import_fetched_pdf(
+        &self,
+        pdf: FetchedDocument,
+        message: &UrlImportMessage,
+        mut paper: NewPaper,
+        chunking: &ChunkingConfig,
+    ) -> Result<(), IngestionError> {
+        if paper.title.is_empty() {
+            // The last path segment, e.g. `1706.03762` for an arXiv PDF
+            paper.title = pdf
+                .url
+                .path_segments()
+                .and_then(|mut segments| segments.rfind(|segment| !segment.is_empty()))
+                .map(|segment| segment.strip_suffix(".pdf").unwrap_or(segment).to_string())
+                .unwrap_or_else(|| pdf.url.to_string());
+        }
+        self.process_pdf_bytes(pdf.body, message.tenant_id, message.job_id, paper, None, Some(chunking))
             .await
-            .map_err(|e| IngestionError::DatabaseError(e.to_string()))?;
+    }
 
-        let paper_id = paper.id;
+    /// Run a PDF held in memory through [`process_pdf`](Self::process_pdf)
+    /// by way of a temporary file
+    async fn process_pdf_bytes(
+        &self,
+        body: Vec<u8>,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        paper: NewPaper,
+        stored_source: Option<String>,
+        chunking: Option<&ChunkingConfig>,
+    ) -> Result<(), IngestionError> {
+        let path = std::env::temp_dir().join(format!("paperforge-{}.pdf", job_id));
+        tokio::fs::write(&path, body).await?;
 
-        // Update job with paper ID
-        self.repository
-            .update_job_status(
-                job_id,
-                paperforge_common::db::models::JobStatus::Chunking,
-                Some(paper_id),
-                None,
-                None,
+        let result = self.process_pdf(&path, tenant_id, job_id, paper, stored_source, chunking).await;
+
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            warn!(error = %e, path = %path.display(), "Failed to remove temporary PDF");
+        }
+        result.map(|_| ())
+    }
+
+    /// Extract a PDF and run its text through the pipeline
+    ///
+    /// With document storage configured, the PDF is stored unless it already
+    /// is (`stored_source`), and its text is stored next to it.
+    async fn process_pdf(
+        &self,
+        path: &Path,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        paper: NewPaper,
+        stored_source: Option<String>,
+        chunking: Option<&ChunkingConfig>,
+    ) -> Result<(Uuid, Uuid, Vec<TextChunk>), IngestionError> {
+        let keys = self.storage.as_ref().map(|_| {
+            (
+                stored_source.clone().unwrap_or_else(|| document_key(tenant_id, job_id, SOURCE_OBJECT)),
+                document_key(tenant_id, job_id, TEXT_OBJECT),
             )
+        });
+
+        let pdf = match self.load_checkpoint::<ExtractedPdf>(job_id, CheckpointStage::Extracted).await? {
+            Some(pdf) => {
+                info!("Resuming from extracted text checkpoint");
+                pdf
+            }
+            None => {
+                info!("Extracting text from PDF...");
+                let pdf = match extract_pdf(path, &self.pdf_limits) {
+                    Ok(pdf) => pdf,
+                    Err(e) => {
+                        if !e.is_retryable() {
+                            self.fail_job(job_id, &e).await;
+                        }
+                        return Err(e);
+                    }
+                };
+                if let (Some(storage), Some((source_key, text_key))) = (&self.storage, &keys) {
+                    if stored_source.is_none() {
+                        let source = tokio::fs::read(path).await?;
+                        storage.put(source_key, source, "application/pdf").await?;
+                    }
+                    storage.put(text_key, pdf.text.clone().into_bytes(), "text/plain; charset=utf-8").await?;
+                }
+                self.save_checkpoint(job_id, CheckpointStage::Extracted, &pdf).await?;
+                pdf
+            }
+        };
+
+        self.process_text(
+            tenant_id,
+            Some(job_id),
+            NewPaper {
+                source_key: keys.as_ref().map(|(source, _)| source.clone()),
+                text_key: keys.map(|(_, text)| text),
+                ..paper
+            },
+            &pdf.text,
+            &pdf.layout,
+            chunking,
+        )
+        .await
+    }
+
+    /// Run already-extracted text through the pipeline: create the paper,
+    /// chunk, and dispatch chunks for embedding
+    ///
+    /// A new job is created unless `job_id` refers to one created upstream
+    /// (e.g. by the gateway). Text chunks are annotated with the pages of
+    /// `layout` they span, and its tables, figure captions and equations
+    /// appended as chunks of their own. `chunking` overrides the processor's
+    /// chunking config. The reference list is left out of chunks unless the
+    /// tenant turns that off; its citations are linked either way.
+    ///
+    /// An existing job resumes where its last attempt stopped: the paper
+    /// row is reused once created, and saved chunks are re-dispatched
+    /// without chunking again. Jobs already handed to embedding are left
+    /// alone.
+    #[instrument(skip(self, paper, text, layout), fields(title = %paper.title))]
+    pub async fn process_text(
+        &self,
+        tenant_id: Uuid,
+        job_id: Option<Uuid>,
+        paper: NewPaper,
+        text: &str,
+        layout: &PdfLayout,
+        chunking: Option<&ChunkingConfig>,
+    ) -> Result<(Uuid, Uuid, Vec<TextChunk>), IngestionError> {
+        let start = Instant::now();
+
+        // Create job, or pick up an existing one
+        let job = match job_id {
+            Some(id) => self
+                .repository
+                .find_job_by_id(id)
+                .await?
+                .ok_or_else(|| AppError::JobNotFound { id: id.to_string() })?,
+            None => self.repository.create_job(tenant_id, None).await?,
+        };
+        let job_id = job.id;
+
+        if let Some(paper_id) = job.paper_id.filter(|_| Self::ingestion_done(&job)) {
+            info!(stage = %job.stage, "Job already past ingestion, nothing to resume");
+            let chunks = self
+                .load_checkpoint(job_id, CheckpointStage::Chunked)
+                .await?
+                .unwrap_or_default();
+            return Ok((job_id, paper_id, chunks));
+        }
+
+        let paper_id = match job.paper_id {
+            Some(paper_id) => {
+                info!(paper_id = %paper_id, "Resuming with paper created by an earlier attempt");
+                paper_id
+            }
+            None => self.create_paper(tenant_id, job_id, paper, text).await?,
+        };
+
+        self.link_citations(tenant_id, paper_id, text).await;
+
+        let chunks = match self.load_checkpoint::<Vec<TextChunk>>(job_id, CheckpointStage::Chunked).await? {
+            Some(chunks) => {
+                info!(chunk_count = chunks.len(), "Resuming from chunk checkpoint");
+                chunks
+            }
+            None => {
+                info!("Chunking text...");
+                let exclude_references = self.excludes_references(tenant_id).await;
+                let mut chunks = self
+                    .chunk(text, chunking.unwrap_or(&self.chunking_config), exclude_references)
+                    .await?;
+                annotate_pages(&mut chunks, text, &layout.pages);
+                chunks.extend(self.element_chunks(&layout.elements, chunks.len() as i32));
+
+                info!(
+                    chunk_count = chunks.len(),
+                    element_count = layout.elements.len(),
+                    "Text chunked successfully"
+                );
+
+                // Persist before dispatching so a retry doesn't chunk again
+                self.save_checkpoint(job_id, CheckpointStage::Chunked, &chunks).await?;
+                chunks
+            }
+        };
+
+        self.dispatch_embedding(job_id, paper_id, &chunks).await?;
+        metrics::record_ingestion(start.elapsed().as_secs_f64(), chunks.len(), tenant_id);
+
+        Ok((job_id, paper_id, chunks))
+    }
+
+    /// Split text with the configured strategy
+    ///
+    /// With `exclude_references`, the text's reference list is left out;
+    /// citations are still parsed from the full text.
+    async fn chunk(
+        &self,
+        text: &str,
+        config: &ChunkingConfig,
+        exclude_references: bool,
+    ) -> Result<Vec<TextChunk>, IngestionError> {
+        let embedder = self.boundary_embedder.as_ref();
+        if exclude_references {
+            let sections = detect_sections(text);
+            if let Some(section) = reference_section(&sections) {
+                debug!(bytes = section.end_pos - section.start_pos, "Leaving the reference list out of chunks");
+                let skip = section.start_pos..section.end_pos;
+                return Ok(chunking::chunk_around(text, skip, config, embedder).await?);
+            }
+        }
+        Ok(chunking::chunk(text, config, embedder).await?)
+    }
+
+    /// Chunks of a PDF's tables, captions and equations, indexed from
+    /// `first_index`; equations are left out unless they are embedded
+    fn element_chunks(&self, elements: &[PdfElement], first_index: i32) -> Vec<TextChunk> {
+        let elements: Vec<PdfElement> = elements
+            .iter()
+            .filter(|e| self.embed_equations || e.kind != ChunkType::Equation)
+            .cloned()
+            .collect();
+        element_chunks(&elements, first_index)
+    }
+
+    /// Whether the tenant's reference lists are left out of chunks, per its
+    /// settings or else the processor default
+    async fn excludes_references(&self, tenant_id: Uuid) -> bool {
+        match self.repository.find_tenant_settings(tenant_id).await {
+            Ok(settings) => settings
+                .and_then(|s| s.exclude_references)
+                .unwrap_or(self.exclude_references),
+            Err(e) => {
+                warn!(error = %e, "Failed to load tenant settings, using the default for reference lists");
+                self.exclude_references
+            }
+        }
+    }
+
+    /// Whether the job has already been handed to embedding
+    fn ingestion_done(job: &IngestionJob) -> bool {
+        matches!(
+            JobStatus::from(job.stage.clone()),
+            JobStatus::Embedding | JobStatus::Indexing
+        ) || job.job_status() == JobStatus::Completed
+    }
+
+    /// Create the paper row for a job and move the job to `Chunking`
+    async fn create_paper(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        paper: NewPaper,
+        text: &str,
+    ) -> Result<Uuid, IngestionError> {
+        let paper = self.resolve_metadata(tenant_id, paper, text).await;
+        let authors = metadata_authors(&paper.metadata);
+
+        // First 500 chars as abstract when none is provided
+        let abstract_text = paper
+            .abstract_text
+            .clone()
+            .unwrap_or_else(|| text.chars().take(500).collect());
+
+        // The paper row and the job's move to `Chunking` commit together
+        let paper_id = self
+            .repository
+            .transaction(|uow| {
+                let (paper, abstract_text) = (paper.clone(), abstract_text.clone());
+                Box::pin(async move {
+                    let created = uow
+                        .create_paper(
+                            tenant_id,
+                            paper.title,
+                            abstract_text,
+                            paper.source,
+                            paper.external_id,
+                            paper.metadata,
+                            paper.idempotency_key,
+                        )
+                        .await?;
+                    let paper_id = created.id;
+
+                    if paper.source_key.is_some() || paper.text_key.is_some() {
+                        uow.set_paper_storage_keys(paper_id, paper.source_key, paper.text_key)
+                            .await?;
+                    }
+
+                    if let Some(published_at) = paper.published_at {
+                        let update = PaperUpdate {
+                            published_at: Some(published_at),
+                            ..Default::default()
+                        };
+                        uow.update_paper(created, update).await?;
+                    }
+
+                    uow.update_job_status(job_id, JobStatus::Chunking, Some(paper_id), None, None)
+                        .await?;
+                    Ok(paper_id)
+                })
+            })
             .await
             .map_err(|e| IngestionError::DatabaseError(e.to_string()))?;
 
-        // Chunk the text
-        info!("Chunking text...");
-        let chunks = chunk_text(&text, &self.chunking_config);
+        // Authors are an index over the metadata; the paper stands without them
+        if !authors.is_empty() {
+            if let Err(e) = self.repository.link_paper_authors(tenant_id, paper_id, &authors).await {
+                warn!(error = %e, "Failed to link paper authors");
+            }
+        }
 
-        info!(chunk_count = chunks.len(), "Text chunked successfully");
+        Ok(paper_id)
+    }
 
-        // Update job with chunk count
-        self.repository
-            .update_job_status(
-                job_id,
-                paperforge_common::db::models::JobStatus::Embedding,
-                None,
-                Some(chunks.len() as i32),
-                None,
-            )
+    /// Parse the paper's reference list and record citations of papers
+    /// already in the tenant's corpus, with the sentences citing them
+    ///
+    /// Citing a paper that isn't ingested yet creates no edge. Failures are
+    /// logged; they don't fail the job.
+    async fn link_citations(&self, tenant_id: Uuid, paper_id: Uuid, text: &str) {
+        let cited = extract_citations(text);
+        let references: Vec<(Option<String>, String)> = cited
+            .iter()
+            .map(|c| (c.reference.doi.clone(), c.reference.text.clone()))
+            .collect();
+        let contexts = cited.iter().map(|c| c.context()).collect();
+        self.link_references(tenant_id, paper_id, &references, contexts).await;
+    }
+
+    /// Record citations of the `(DOI, text)` references that resolve to
+    /// papers in the tenant's corpus, each with its citing context
+    async fn link_references(
+        &self,
+        tenant_id: Uuid,
+        paper_id: Uuid,
+        references: &[(Option<String>, String)],
+        contexts: Vec<Option<String>>,
+    ) {
+        if references.is_empty() {
+            return;
+        }
+
+        let resolved = match self.repository.resolve_references(tenant_id, paper_id, references).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                warn!(error = %e, "Failed to resolve references");
+                return;
+            }
+        };
+
+        // Entries resolving to the same paper keep the first one's context
+        let mut seen = HashSet::new();
+        let citations: Vec<(Uuid, Option<String>, i32)> = contexts
+            .into_iter()
+            .zip(resolved)
+            .enumerate()
+            .filter_map(|(position, (context, cited_id))| {
+                let cited_id = cited_id.filter(|id| seen.insert(*id))?;
+                Some((cited_id, context, position as i32 + 1))
+            })
+            .collect();
+        if citations.is_empty() {
+            debug!(references = references.len(), "No references resolved to ingested papers");
+            return;
+        }
+
+        match self.repository.upsert_citations(paper_id, &citations).await {
+            Ok(_) => info!(
+                references = references.len(),
+                citations = citations.len(),
+                "Citations linked"
+            ),
+            Err(e) => warn!(error = %e, "Failed to record citations"),
+        }
+    }
+
+    /// Mark a job dead with the error's failure reason; for errors retrying
+    /// can't fix
+    async fn fail_job(&self, job_id: Uuid, error: &IngestionError) {
+        let reason = error.failure_reason();
+        if let Err(e) = self
+            .repository
+            .fail_job_attempt(job_id, &reason, None)
             .await
-            .map_err(|e| IngestionError::DatabaseError(e.to_string()))?;
+        {
+            warn!(error = %e, "Failed to record job failure");
+        }
+    }
 
-        // Send to embedding queue if available
-        if let Some(ref queue) = self.embedding_queue {
-            let embedding_job = EmbeddingJob {
-                job_id,
-                paper_id,
-                chunks: chunks
-                    .iter()
-                    .map(|c| ChunkData {
-                        index: c.index,
-                        content: c.content.clone(),
-                        token_count: c.token_count,
-                    })
-                    .collect(),
-                embedding_model: self.embedding_model.clone(),
-            };
+    /// Load and decode a stage checkpoint, if one was saved
+    async fn load_checkpoint<T: DeserializeOwned>(
+        &self,
+        job_id: Uuid,
+        stage: CheckpointStage,
+    ) -> Result<Option<T>, IngestionError> {
+        let Some(checkpoint) = self.repository.find_checkpoint(job_id, stage).await? else {
+            return Ok(None);
+        };
+        match serde_json::from_value(checkpoint.data) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) => {
+                // An unreadable checkpoint just means redoing the stage
+                warn!(stage = stage.as_str(), error = %e, "Ignoring unreadable checkpoint");
+                Ok(None)
+            }
+        }
+    }
+
+    async fn save_checkpoint<T: Serialize>(
+        &self,
+        job_id: Uuid,
+        stage: CheckpointStage,
+        data: &T,
+    ) -> Result<(), IngestionError> {
+        let data = serde_json::to_value(data).map_err(AppError::from)?;
+        self.repository.save_checkpoint(job_id, stage, data).await?;
+        Ok(())
+    }
+
+    /// Backfill canonical metadata from Crossref when the paper has a DOI
+    ///
+    /// Crossref's title and publication date replace the submitted ones;
+    /// authors and venue only fill keys missing from the metadata. Lookup
+    /// failures are logged and the paper keeps its metadata as submitted.
+    async fn resolve_metadata(&self, tenant_id: Uuid, mut paper: NewPaper, text: &str) -> NewPaper {
+        let Some(crossref) = &self.crossref else {
+            return paper;
+        };
+
+        let doi = paper
+            .doi
+            .clone()
+            .or_else(|| paper.metadata.get("doi").and_then(|v| v.as_str()).and_then(normalize_doi))
+            .or_else(|| paper.external_id.as_deref().and_then(normalize_doi))
+            .or_else(|| find_doi(text));
+        let Some(doi) = doi else {
+            return paper;
+        };
+
+        match self.repository.find_tenant_by_id(tenant_id).await {
+            Ok(Some(tenant)) if !tenant.resolve_metadata => {
+                debug!(doi = %doi, "Metadata resolution disabled for tenant");
+                return paper;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                warn!(error = %e, "Failed to load tenant settings, skipping metadata resolution");
+                return paper;
+            }
+        }
+
+        match crossref.resolve(&doi).await {
+            Ok(Some(work)) => {
+                info!(doi = %doi, "Resolved paper metadata from Crossref");
+                if let Some(title) = &work.title {
+                    paper.title = title.clone();
+                }
+                paper.published_at = work.published_at().or(paper.published_at);
+                work.merge_into(&mut paper.metadata);
+            }
+            Ok(None) => debug!(doi = %doi, "DOI not registered with Crossref"),
+            Err(e) => warn!(doi = %doi, error = %e, "Crossref lookup failed"),
+        }
+
+        paper.doi = Some(doi);
+        paper
+    }
+
+    /// Move the job to `Embedding` and, if an embedding queue is configured,
+    /// write the embedding message to the outbox in the same transaction
+    async fn dispatch_embedding(
+        &self,
+        job_id: Uuid,
+        paper_id: Uuid,
+        chunks: &[TextChunk],
+    ) -> Result<(), IngestionError> {
+        let chunks_total = Some(chunks.len() as i32);
+
+        if self.embedding_queue.is_some() {
+            let message = self.embedding_message(job_id, paper_id, chunks)?;
 
-            queue
-                .send(&embedding_job)
+            self.repository
+                .update_job_status_with_outbox(
+                    job_id,
+                    JobStatus::Embedding,
+                    chunks_total,
+                    EMBEDDING_QUEUE,
+                    message,
+                )
                 .await
-                .map_err(|e| IngestionError::QueueError(e.to_string()))?;
+                .map_err(|e| IngestionError::DatabaseError(e.to_string()))?;
 
-            info!("Embedding job sent to queue");
+            info!("Embedding job written to outbox");
         } else {
+            self.repository
+                .update_job_status(job_id, JobStatus::Embedding, None, chunks_total, None)
+                .await
+                .map_err(|e| IngestionError::DatabaseError(e.to_string()))?;
+
             warn!("No embedding queue configured, chunks not sent for embedding");
         }
 
-        Ok((job_id, paper_id, chunks))
+        Ok(())
+    }
+
+    /// Embedding queue message for a job's chunks
+    fn embedding_message(
+        &self,
+        job_id: Uuid,
+        paper_id: Uuid,
+        chunks: &[TextChunk],
+    ) -> Result<OutboxPayload, IngestionError> {
+        let embedding_job = EmbeddingJob {
+            job_id,
+            paper_id,
+            chunks: chunks
+                .iter()
+                .map(|c| ChunkData {
+                    index: c.index,
+                    content: c.content.clone(),
+                    token_count: c.token_count,
+                    chunk_type: c.chunk_type,
+                    metadata: c.metadata.clone(),
+                    char_range: c.text_range(),
+                })
+                .collect(),
+            embedding_model: self.embedding_model.clone(),
+        };
+        let payload =
+            serde_json::to_value(&embedding_job).map_err(|e| IngestionError::QueueError(e.to_string()))?;
+        Ok(OutboxPayload {
+            payload,
+            envelope: Envelope::for_message(&embedding_job),
+        })
+    }
+
+    /// Load a batch of already-parsed papers, skipping PDF extraction
+    ///
+    /// Texts are chunked up front. The batch's papers and jobs are then
+    /// inserted in one transaction, and the jobs moved to `Embedding` with
+    /// their embedding messages written to the outbox in a second one. Records imported before
+    /// (by idempotency key) are skipped. Crossref lookups are skipped too:
+    /// imported metadata is taken as given. Returns the job ID, paper ID and
+    /// chunk count of each paper created.
+    #[instrument(skip(self, records), fields(records = records.len()))]
+    pub async fn import_batch(
+        &self,
+        tenant_id: Uuid,
+        records: Vec<ImportRecord>,
+    ) -> Result<Vec<(Uuid, Uuid, usize)>, IngestionError> {
+        let mut fresh = Vec::with_capacity(records.len());
+        for record in records {
+            if let Some(key) = record.idempotency_key() {
+                if self.repository.find_job_by_idempotency_key(tenant_id, &key).await?.is_some() {
+                    debug!(key = %key, "Skipping record imported before");
+                    continue;
+                }
+            }
+            fresh.push(record);
+        }
+        if fresh.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Chunk first, so a chunking failure leaves nothing half-imported
+        let exclude_references = self.excludes_references(tenant_id).await;
+        let mut chunked = Vec::with_capacity(fresh.len());
+        for record in &fresh {
+            chunked.push(self.chunk(record.text(), &self.chunking_config, exclude_references).await?);
+        }
+
+        let fresh = Arc::new(fresh);
+        let ids: Vec<(Uuid, Uuid)> = self
+            .repository
+            .transaction(|uow| {
+                let records = fresh.clone();
+                Box::pin(async move {
+                    let mut ids = Vec::with_capacity(records.len());
+                    for record in records.iter() {
+                        let key = record.idempotency_key();
+                        let job = uow.create_job(tenant_id, None, key.clone()).await?;
+                        let paper = uow
+                            .create_paper(
+                                tenant_id,
+                                record.title.clone(),
+                                record.abstract_or_excerpt(),
+                                Some("import".to_string()),
+                                record.external_id.clone(),
+                                record.metadata.clone(),
+                                key,
+                            )
+                            .await?;
+                        let paper_id = paper.id;
+
+                        if let Some(published_at) = record.published_at {
+                            let update = PaperUpdate {
+                                published_at: Some(published_at),
+                                ..Default::default()
+                            };
+                            uow.update_paper(paper, update).await?;
+                        }
+
+                        uow.update_job_status(job.id, JobStatus::Chunking, Some(paper_id), None, None)
+                            .await?;
+                        ids.push((job.id, paper_id));
+                    }
+                    Ok(ids)
+                })
+            })
+            .await
+            .map_err(|e| IngestionError::DatabaseError(e.to_string()))?;
+
+        let mut dispatches = Vec::with_capacity(ids.len());
+        for ((record, chunks), (job_id, paper_id)) in fresh.iter().zip(chunked).zip(ids) {
+            let authors = metadata_authors(&record.metadata);
+            if !authors.is_empty() {
+                if let Err(e) = self.repository.link_paper_authors(tenant_id, paper_id, &authors).await {
+                    warn!(paper_id = %paper_id, error = %e, "Failed to link paper authors");
+                }
+            }
+
+            if record.references.is_empty() {
+                self.link_citations(tenant_id, paper_id, record.text()).await;
+            } else {
+                let references: Vec<_> = record.references.iter().map(ImportReference::to_pair).collect();
+                let contexts = vec![None; references.len()];
+                self.link_references(tenant_id, paper_id, &references, contexts).await;
+            }
+
+            let message = match self.embedding_queue {
+                Some(_) => Some(self.embedding_message(job_id, paper_id, &chunks)?),
+                None => None,
+            };
+            dispatches.push((job_id, paper_id, chunks.len(), message));
+        }
+
+        let dispatches = Arc::new(dispatches);
+        self.repository
+            .transaction(|uow| {
+                let dispatches = dispatches.clone();
+                Box::pin(async move {
+                    for (job_id, _, chunk_count, message) in dispatches.iter() {
+                        uow.update_job_status(*job_id, JobStatus::Embedding, None, Some(*chunk_count as i32), None)
+                            .await?;
+                        if let Some(message) = message {
+                            uow.write_outbox(EMBEDDING_QUEUE, message.clone()).await?;
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|e| IngestionError::DatabaseError(e.to_string()))?;
+
+        if self.embedding_queue.is_none() {
+            warn!("No embedding queue configured, imported chunks not sent for embedding");
+        }
+
+        Ok(dispatches
+            .iter()
+            .map(|(job_id, paper_id, chunk_count, _)| (*job_id, *paper_id, *chunk_count))
+            .collect())
+    }
+
+    /// Whether chunks are dispatched to an embedding queue
+    pub fn has_embedding_queue(&self) -> bool {
+        self.embedding_queue.is_some()
+    }
+
+    /// Repository used by the processor
+    pub fn repository(&self) -> &Repository {
+        &self.repository
+    }
+
+    /// Process any message received on the ingestion queue
+    ///
+    /// Embedding usage is attributed to the job's tenant, and reads after
+    /// the job's writes stay on the primary. The attempt is counted on the
+    /// job before anything else, so failures at any stage count towards
+    /// its retry limit.
+    pub async fn handle_message(&self, message: IngestionQueueMessage) -> Result<(), IngestionError> {
+        usage::attribute_to(message.tenant_id(), db::consistent(async {
+            self.repository.record_job_attempt(message.job_id()).await?;
+            match message {
+                IngestionQueueMessage::Ingest(m) => self.process_job(m).await,
+                IngestionQueueMessage::Reprocess(m) => self.reprocess_paper(m).await,
+                IngestionQueueMessage::Submit(m) => self.process_submission(m).await,
+                IngestionQueueMessage::Document(m) => self.process_stored_document(m).await,
+                IngestionQueueMessage::Url(m) => self.import_url(m).await,
+            }
+        }))
+        .await
     }
 
     /// Process an ingestion job from SQS
@@ -192,7 +1129,7 @@ impl IngestionProcessor {
                 if !path.exists() {
                     return Err(IngestionError::FileNotFound(message.source_path));
                 }
-                self.process_local_pdf(path, message.tenant_id, None)
+                self.process_local_pdf(path, message.tenant_id, Some(message.job_id), None)
                     .await?;
             }
             SourceType::S3 => {
@@ -214,6 +1151,201 @@ impl IngestionProcessor {
         Ok(())
     }
 
+    /// Chunking config for a job submitted with its own options
+    fn chunking_for(&self, options: &IngestionJobOptions) -> ChunkingConfig {
+        ChunkingConfig {
+            strategy: options.chunk_strategy.parse().unwrap_or_else(|e| {
+                warn!(error = %e, "Falling back to the default chunk strategy");
+                self.chunking_config.strategy
+            }),
+            chunk_size: options.chunk_size,
+            chunk_overlap: options.chunk_overlap,
+            ..self.chunking_config.clone()
+        }
+    }
+
+    /// Ingest a paper submitted through the API
+    ///
+    /// The job row already exists (created by the gateway); the abstract is
+    /// the only text available, so it is chunked as the paper body, using
+    /// the job's chunking options.
+    #[instrument(skip(self, message), fields(job_id = %message.job_id))]
+    pub async fn process_submission(&self, message: SubmittedPaperMessage) -> Result<(), IngestionError> {
+        info!("Processing submitted paper");
+
+        let text = message.paper_abstract.clone();
+        let chunking = self.chunking_for(&message.options);
+
+        self.process_text(
+            message.tenant_id,
+            Some(message.job_id),
+            NewPaper {
+                title: message.paper_title,
+                abstract_text: Some(message.paper_abstract),
+                source: message.paper_source,
+                external_id: message.external_id,
+                doi: message.doi,
+                published_at: message.published_at,
+                metadata: message.metadata,
+                idempotency_key: message.idempotency_key,
+                ..Default::default()
+            },
+            &text,
+            &PdfLayout::default(),
+            Some(&chunking),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Re-chunk an existing paper and re-enqueue embedding
+    ///
+    /// Text is re-extracted from the original file when it is still
+    /// available, otherwise read from the stored text object, otherwise
+    /// reassembled from the current text chunks at their recorded offsets;
+    /// without a file, table and caption chunks are carried over as they
+    /// are. Reassembled text is stored as the paper's text object, so the
+    /// next reprocess starts from the same text. Existing chunks are deleted
+    /// before the new ones are dispatched.
+    #[instrument(skip(self, message), fields(job_id = %message.job_id, paper_id = %message.paper_id))]
+    pub async fn reprocess_paper(&self, message: ReprocessPaperMessage) -> Result<(), IngestionError> {
+        info!("Reprocessing paper");
+
+        let paper = self
+            .repository
+            .find_paper_by_id(message.paper_id)
+            .await?
+            .ok_or_else(|| IngestionError::DatabaseError(format!(
+                "Paper {} not found",
+                message.paper_id
+            )))?;
+
+        let existing = self.repository.get_chunks_by_paper(paper.id).await?;
+
+        let source_file = paper
+            .metadata
+            .get("file_path")
+            .and_then(|v| v.as_str())
+            .map(Path::new)
+            .filter(|p| p.exists());
+
+        let (text, pages, mut carried) = match source_file {
+            Some(path) => {
+                let pdf = match extract_pdf(path, &self.pdf_limits) {
+                    Ok(pdf) => pdf,
+                    Err(e) => {
+                        self.fail_job(message.job_id, &e).await;
+                        return Err(e);
+                    }
+                };
+                let carried = self.element_chunks(&pdf.layout.elements, 0);
+                (pdf.text, pdf.layout.pages, carried)
+            }
+            None => {
+                let (text_chunks, other): (Vec<_>, Vec<_>) = existing
+                    .iter()
+                    .partition(|c| c.chunk_type() == ChunkType::Text);
+                let text = match self.stored_text(&paper).await {
+                    Some(text) => text,
+                    None => {
+                        let ranges: Vec<_> = text_chunks
+                            .iter()
+                            .map(|c| (c.content.as_str(), c.char_range()))
+                            .collect();
+                        let text = chunking::reassemble(&ranges);
+                        self.store_text(&paper, message.job_id, &text).await;
+                        text
+                    }
+                };
+                let carried = other
+                    .into_iter()
+                    .filter(|c| self.embed_equations || c.chunk_type() != ChunkType::Equation)
+                    .map(|c| TextChunk {
+                        content: c.content.clone(),
+                        index: 0,
+                        token_count: c.token_count,
+                        start_pos: 0,
+                        end_pos: c.content.len(),
+                        chunk_type: c.chunk_type(),
+                        metadata: Some(c.metadata.clone()),
+                    })
+                    .collect();
+                (text, Vec::new(), carried)
+            }
+        };
+
+        if text.trim().is_empty() {
+            let err = "No source text available for reprocessing".to_string();
+            self.repository.fail_job_attempt(message.job_id, &err, None).await?;
+            return Err(IngestionError::ChunkingError(err));
+        }
+
+        self.repository
+            .update_job_status(message.job_id, JobStatus::Chunking, Some(paper.id), None, None)
+            .await?;
+
+        self.link_citations(paper.tenant_id, paper.id, &text).await;
+
+        let deleted = self.repository.delete_chunks_by_paper(paper.id).await?;
+        debug!(deleted = deleted, "Existing chunks deleted");
+
+        let config = ChunkingConfig {
+            chunk_size: message.chunk_size.unwrap_or(self.chunking_config.chunk_size),
+            chunk_overlap: message.chunk_overlap.unwrap_or(self.chunking_config.chunk_overlap),
+            ..self.chunking_config.clone()
+        };
+        let exclude_references = self.excludes_references(paper.tenant_id).await;
+        let mut chunks = self.chunk(&text, &config, exclude_references).await?;
+        annotate_pages(&mut chunks, &text, &pages);
+        for (i, chunk) in carried.iter_mut().enumerate() {
+            chunk.index = (chunks.len() + i) as i32;
+        }
+        chunks.append(&mut carried);
+
+        info!(chunk_count = chunks.len(), "Paper re-chunked");
+
+        self.dispatch_embedding(message.job_id, paper.id, &chunks).await
+    }
+
+    /// The paper's text from document storage, if it was kept
+    async fn stored_text(&self, paper: &Paper) -> Option<String> {
+        let (storage, key) = (self.storage.as_ref()?, paper.text_key.as_ref()?);
+        match storage.get(key).await {
+            Ok(Some(body)) => String::from_utf8(body).ok(),
+            Ok(None) => {
+                warn!(key = %key, "Stored text is missing, reassembling it from chunks");
+                None
+            }
+            Err(e) => {
+                warn!(key = %key, error = %e, "Failed to read stored text, reassembling it from chunks");
+                None
+            }
+        }
+    }
+
+    /// Keep reassembled text as the paper's text object; best effort
+    async fn store_text(&self, paper: &Paper, job_id: Uuid, text: &str) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+        if text.trim().is_empty() {
+            return;
+        }
+        let key = document_key(paper.tenant_id, job_id, TEXT_OBJECT);
+        let stored = match storage.put(&key, text.as_bytes().to_vec(), "text/plain; charset=utf-8").await {
+            Ok(()) => {
+                self.repository
+                    .set_paper_storage_keys(paper.id, paper.source_key.clone(), Some(key))
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = stored {
+            warn!(error = %e, "Failed to store reassembled text");
+        }
+    }
+
     /// Batch process all PDFs in a directory (for testing)
     #[instrument(skip(self), fields(dir = %dir.display()))]
     pub async fn process_directory(
@@ -230,7 +1362,7 @@ impl IngestionProcessor {
             let path = entry.path();
 
             if path.extension().map(|e| e == "pdf").unwrap_or(false) {
-                match self.process_local_pdf(&path, tenant_id, None).await {
+                match self.process_local_pdf(&path, tenant_id, None, None).await {
                     Ok((job_id, paper_id, chunks)) => {
                         info!(
                             job_id = %job_id,
@@ -259,3 +1391,25 @@ impl IngestionProcessor {
         Ok(results)
     }
 }
+
+/// Paper title taken from a file name, for PDFs without one of their own
+fn file_title(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Untitled".to_string())
+}
+
+/// Paper metadata for a document imported by URL: the caller's, plus where
+/// the document came from
+fn url_metadata(message: &UrlImportMessage, document: &FetchedDocument) -> serde_json::Value {
+    let mut metadata = match &message.metadata {
+        serde_json::Value::Object(map) => map.clone(),
+        _ => serde_json::Map::new(),
+    };
+    metadata.insert("source".to_string(), "url".into());
+    metadata.insert("url".to_string(), message.url.clone().into());
+    if document.url.as_str() != message.url {
+        metadata.insert("fetched_url".to_string(), document.url.as_str().into());
+    }
+    serde_json::Value::Object(metadata)
+}
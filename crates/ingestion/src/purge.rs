@@ -0,0 +1,51 @@
+//! Background purge of soft-deleted papers
+//!
+//! Papers deleted through the API are only marked with `deleted_at`.
+//! This task periodically removes rows (and, via cascade, their chunks
+//! and vectors) and their stored documents once the retention period has
+//! elapsed.
+
+use paperforge_common::{
+    config::RetentionConfig,
+    db::{DbPool, Repository},
+    storage::ObjectStore,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Spawn the purge loop on the current runtime
+pub fn spawn_purge_task(
+    db: DbPool,
+    storage: Option<Arc<dyn ObjectStore>>,
+    config: RetentionConfig,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let repo = Repository::new(db);
+        let mut interval = tokio::time::interval(Duration::from_secs(config.purge_interval_secs));
+
+        info!(
+            retention_days = config.paper_retention_days,
+            interval_secs = config.purge_interval_secs,
+            "Starting deleted paper purge task"
+        );
+
+        loop {
+            interval.tick().await;
+
+            let cutoff = chrono::Utc::now()
+                - chrono::Duration::days(config.paper_retention_days as i64);
+
+            match repo.purge_deleted_papers(cutoff, storage.as_deref()).await {
+                Ok(0) => {}
+                Ok(purged) => {
+                    info!(purged = purged, cutoff = %cutoff, "Purged soft-deleted papers");
+                }
+                Err(e) => {
+                    error!(error = %e, "Failed to purge soft-deleted papers");
+                }
+            }
+        }
+    })
+}
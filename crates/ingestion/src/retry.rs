@@ -0,0 +1,127 @@
+//! Ingestion job retries
+//!
+//! A message whose processing failed is settled on its job instead of being
+//! left for SQS to redeliver: a retryable failure gets a `next_retry_at`
+//! that backs off exponentially with the job's attempts, and a sweeper
+//! re-sends jobs whose retry is due through the outbox, where the gateway's
+//! relay publishes them like new jobs. Jobs whose error can't be retried,
+//! or that used up `max_attempts`, are marked `dead`.
+
+use crate::errors::IngestionError;
+use chrono::{DateTime, Utc};
+use paperforge_common::{
+    config::JobRetryConfig,
+    db::{DbPool, Repository},
+    errors::{AppError, Retryable},
+    outbox::INGESTION_QUEUE,
+};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// When to retry a job that failed on its `attempts`th attempt
+///
+/// `None` when it shouldn't be: the error isn't retryable or the attempts
+/// ran out. A delay the error asks for is honored if longer than the
+/// backoff.
+pub fn retry_at<E: Retryable>(
+    config: &JobRetryConfig,
+    attempts: u32,
+    error: &E,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if !error.is_retryable() || attempts >= config.max_attempts {
+        return None;
+    }
+    let delay = config.backoff(attempts).max(error.retry_after().unwrap_or_default());
+    Some(now + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX))
+}
+
+/// Record a failed attempt of `job_id`; returns when it will be retried,
+/// `None` when the job is now dead
+pub async fn record_failure(
+    repo: &Repository,
+    config: &JobRetryConfig,
+    job_id: Uuid,
+    error: &IngestionError,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+    let job = repo
+        .find_job_by_id(job_id)
+        .await?
+        .ok_or_else(|| AppError::JobNotFound { id: job_id.to_string() })?;
+
+    let attempts = job.attempt_count.max(1) as u32;
+    let retry_at = retry_at(config, attempts, error, Utc::now());
+    repo.fail_job_attempt(job_id, &error.failure_reason(), retry_at.map(Into::into))
+        .await?;
+    Ok(retry_at)
+}
+
+/// Spawn the loop that re-sends jobs whose retry is due
+pub fn spawn_retry_task(db: DbPool, config: JobRetryConfig) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let repo = Repository::new(db);
+        let mut interval = tokio::time::interval(Duration::from_secs(config.sweep_interval_secs));
+
+        info!(
+            interval_secs = config.sweep_interval_secs,
+            max_attempts = config.max_attempts,
+            "Starting job retry task"
+        );
+
+        loop {
+            interval.tick().await;
+
+            match repo.requeue_due_jobs(config.sweep_batch_size, INGESTION_QUEUE).await {
+                Ok(jobs) if jobs.is_empty() => {}
+                Ok(jobs) => {
+                    for job in &jobs {
+                        info!(job_id = %job.id, attempt_count = job.attempt_count, "Retrying ingestion job");
+                    }
+                    info!(requeued = jobs.len(), "Requeued failed ingestion jobs");
+                }
+                Err(e) => {
+                    error!(error = %e, "Failed to requeue failed ingestion jobs");
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> JobRetryConfig {
+        JobRetryConfig {
+            max_attempts: 3,
+            base_delay_secs: 10,
+            max_delay_secs: 60,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_retry_backs_off_until_attempts_run_out() {
+        let now = Utc::now();
+        let error = IngestionError::QueueError("unavailable".to_string());
+
+        assert_eq!(retry_at(&config(), 1, &error, now), Some(now + chrono::Duration::seconds(10)));
+        assert_eq!(retry_at(&config(), 2, &error, now), Some(now + chrono::Duration::seconds(20)));
+        assert_eq!(retry_at(&config(), 3, &error, now), None);
+    }
+
+    #[test]
+    fn test_fatal_errors_are_not_retried() {
+        let error = IngestionError::FileNotFound("paper.pdf".to_string());
+        assert_eq!(retry_at(&config(), 1, &error, Utc::now()), None);
+    }
+
+    #[test]
+    fn test_requested_delay_wins_over_shorter_backoff() {
+        let now = Utc::now();
+        let error = IngestionError::App(AppError::EmbeddingThrottled { retry_after_secs: Some(45) });
+        assert_eq!(retry_at(&config(), 1, &error, now), Some(now + chrono::Duration::seconds(45)));
+    }
+}
@@ -2,11 +2,8 @@
 //!
 //! Provides in-memory citation graph for scoring
 
-use paperforge_common::errors::{AppError, Result};
-use paperforge_common::db::DbPool;
-use sea_orm::{ConnectionTrait, Statement, DbBackend};
+use paperforge_common::db::TenantCitations;
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
 use uuid::Uuid;
 
 /// Edge in the citation graph
@@ -48,71 +45,17 @@ impl CitationGraph {
         }
     }
     
-    /// Load citation graph from database for a tenant
-    pub async fn load_from_db(db: &DbPool, tenant_id: Uuid) -> Result<Self> {
+    /// Build a tenant's graph from its stored papers and citations
+    pub fn from_citations(citations: TenantCitations) -> Self {
         let mut graph = Self::new();
-        
-        // Load papers
-        let papers_sql = r#"
-            SELECT id, title
-            FROM papers
-            WHERE tenant_id = $1 AND status = 'processed'
-        "#;
-        
-        let conn = db.read_connection().await;
-        let paper_rows = conn
-            .query_all(Statement::from_sql_and_values(
-                DbBackend::Postgres,
-                papers_sql,
-                vec![tenant_id.into()],
-            ))
-            .await
-            .map_err(|e| AppError::DatabaseError { 
-                message: format!("Failed to load papers: {}", e) 
-            })?;
-        
-        for row in paper_rows {
-            use sea_orm::TryGetable;
-            if let (Ok(id), Ok(title)) = (
-                row.try_get::<Uuid, _>("", "id"),
-                row.try_get::<String, _>("", "title"),
-            ) {
-                graph.nodes.insert(id);
-                graph.titles.insert(id, title);
-            }
+        for (id, title) in citations.papers {
+            graph.nodes.insert(id);
+            graph.titles.insert(id, title);
         }
-        
-        // Load citations
-        let citations_sql = r#"
-            SELECT c.citing_paper_id, c.cited_paper_id
-            FROM citations c
-            INNER JOIN papers p1 ON c.citing_paper_id = p1.id
-            INNER JOIN papers p2 ON c.cited_paper_id = p2.id
-            WHERE p1.tenant_id = $1
-        "#;
-        
-        let citation_rows = conn
-            .query_all(Statement::from_sql_and_values(
-                DbBackend::Postgres,
-                citations_sql,
-                vec![tenant_id.into()],
-            ))
-            .await
-            .map_err(|e| AppError::DatabaseError { 
-                message: format!("Failed to load citations: {}", e) 
-            })?;
-        
-        for row in citation_rows {
-            use sea_orm::TryGetable;
-            if let (Ok(citing), Ok(cited)) = (
-                row.try_get::<Uuid, _>("", "citing_paper_id"),
-                row.try_get::<Uuid, _>("", "cited_paper_id"),
-            ) {
-                graph.add_edge(citing, cited);
-            }
+        for (citing, cited) in citations.edges {
+            graph.add_edge(citing, cited);
         }
-        
-        Ok(graph)
+        graph
     }
     
     /// Add an edge to the graph
@@ -159,6 +102,20 @@ impl CitationGraph {
         self.outgoing.get(&paper_id).map(|v| v.len()).unwrap_or(0)
     }
     
+    /// Papers reachable from `start` by following references, `start` included
+    pub fn reachable(&self, start: impl IntoIterator<Item = Uuid>) -> HashSet<Uuid> {
+        let mut reached = HashSet::new();
+        let mut stack: Vec<Uuid> = start.into_iter().collect();
+        
+        while let Some(paper_id) = stack.pop() {
+            if reached.insert(paper_id) {
+                stack.extend_from_slice(self.get_references(paper_id));
+            }
+        }
+        
+        reached
+    }
+    
     /// Traverse citations up to a depth
     pub fn traverse(
         &self,
@@ -4,9 +4,13 @@
 
 mod graph;
 mod pagerank;
+mod related;
+mod updater;
 
 pub use graph::{CitationGraph, CitationEdge};
 pub use pagerank::{PageRankScorer, PageRankConfig};
+pub use related::{related_papers, RelatedMethod, RelatedPaper};
+pub use updater::PageRankUpdater;
 
 use paperforge_common::errors::Result;
 use serde::{Deserialize, Serialize};
@@ -40,41 +40,77 @@ impl PageRankScorer {
         Self { config }
     }
     
-    /// Compute PageRank scores for all papers
+    /// Compute PageRank scores for all papers, normalized to 0-1
     pub fn compute(&self, graph: &CitationGraph) -> HashMap<Uuid, f32> {
-        let n = graph.node_count();
-        if n == 0 {
-            return HashMap::new();
-        }
+        let mut scores = self.compute_raw(graph);
         
-        let n_f32 = n as f32;
-        let initial_score = 1.0 / n_f32;
-        let damping = self.config.damping;
-        let teleport = (1.0 - damping) / n_f32;
+        // Normalize to 0-1 range
+        let max_score = scores.values().cloned().fold(0.0f32, f32::max);
+        if max_score > 0.0 {
+            for score in scores.values_mut() {
+                *score /= max_score;
+            }
+        }
         
-        // Initialize scores
+        scores
+    }
+    
+    /// Compute raw PageRank scores for all papers
+    ///
+    /// Raw scores aren't divided by the paper count: a paper nobody cites
+    /// scores `1 - damping` and the average is about 1. Adding papers thus
+    /// leaves the scores of unrelated papers as they are, which is what lets
+    /// [`update`](Self::update) rescore part of the graph.
+    pub fn compute_raw(&self, graph: &CitationGraph) -> HashMap<Uuid, f32> {
         let nodes: Vec<Uuid> = graph.nodes().cloned().collect();
-        let mut scores: HashMap<Uuid, f32> = nodes.iter()
-            .map(|&id| (id, initial_score))
+        let scores = nodes.iter().map(|&id| (id, 1.0)).collect();
+        self.iterate(graph, scores, &nodes)
+    }
+    
+    /// Rescore after `sources` gained references
+    ///
+    /// Rank flows from citing to cited papers, so only the papers reachable
+    /// from `sources` can change. Just those are iterated, starting from the
+    /// `previous` raw scores; their new raw scores are returned.
+    pub fn update(
+        &self,
+        graph: &CitationGraph,
+        previous: &HashMap<Uuid, f32>,
+        sources: &[Uuid],
+    ) -> HashMap<Uuid, f32> {
+        let affected: Vec<Uuid> = graph.reachable(sources.iter().copied()).into_iter().collect();
+        let scores = graph.nodes()
+            .map(|&id| (id, previous.get(&id).copied().unwrap_or(1.0)))
             .collect();
         
-        // Precompute outgoing counts
-        let out_counts: HashMap<Uuid, usize> = nodes.iter()
-            .map(|&id| (id, graph.reference_count(id)))
-            .collect();
+        let scores = self.iterate(graph, scores, &affected);
+        affected.into_iter()
+            .map(|id| (id, scores.get(&id).copied().unwrap_or(0.0)))
+            .collect()
+    }
+    
+    /// Power iteration over `nodes`; other papers keep their scores
+    fn iterate(
+        &self,
+        graph: &CitationGraph,
+        mut scores: HashMap<Uuid, f32>,
+        nodes: &[Uuid],
+    ) -> HashMap<Uuid, f32> {
+        let damping = self.config.damping;
+        let teleport = 1.0 - damping;
+        // Raw scores are the paper count times the classic ones
+        let epsilon = self.config.epsilon * graph.node_count().max(1) as f32;
         
         // Iterative computation
         for _ in 0..self.config.max_iterations {
-            let mut new_scores: HashMap<Uuid, f32> = HashMap::with_capacity(n);
             let mut max_diff: f32 = 0.0;
             
-            for &node in &nodes {
+            let new_scores: Vec<(Uuid, f32)> = nodes.iter().map(|&node| {
                 // Sum contributions from papers citing this one
-                let citations = graph.get_citations(node);
-                let citation_sum: f32 = citations.iter()
+                let citation_sum: f32 = graph.get_citations(node).iter()
                     .map(|&citing| {
                         let citing_score = scores.get(&citing).copied().unwrap_or(0.0);
-                        let citing_out = *out_counts.get(&citing).unwrap_or(&1) as f32;
+                        let citing_out = graph.reference_count(citing).max(1) as f32;
                         citing_score / citing_out
                     })
                     .sum();
@@ -84,25 +120,17 @@ impl PageRankScorer {
                 let old_score = scores.get(&node).copied().unwrap_or(0.0);
                 max_diff = max_diff.max((new_score - old_score).abs());
                 
-                new_scores.insert(node, new_score);
-            }
+                (node, new_score)
+            }).collect();
             
-            scores = new_scores;
+            scores.extend(new_scores);
             
             // Check convergence
-            if max_diff < self.config.epsilon {
+            if max_diff < epsilon {
                 break;
             }
         }
         
-        // Normalize to 0-1 range
-        let max_score = scores.values().cloned().fold(0.0f32, f32::max);
-        if max_score > 0.0 {
-            for score in scores.values_mut() {
-                *score /= max_score;
-            }
-        }
-        
         scores
     }
     
@@ -174,4 +202,34 @@ mod tests {
         
         assert!(scores.is_empty());
     }
+    
+    #[test]
+    fn test_pagerank_update_matches_full() {
+        let mut graph = CitationGraph::new();
+        
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let c = Uuid::from_u128(3);
+        let d = Uuid::from_u128(4);
+        let e = Uuid::from_u128(5);
+        
+        graph.add_edge(a, b);
+        graph.add_edge(b, c);
+        graph.add_edge(d, e);
+        
+        let scorer = PageRankScorer::new(PageRankConfig::default());
+        let previous = scorer.compute_raw(&graph);
+        
+        // A now also cites E; D is upstream of nothing new
+        graph.add_edge(a, e);
+        let updated = scorer.update(&graph, &previous, &[a]);
+        let full = scorer.compute_raw(&graph);
+        
+        assert!(!updated.contains_key(&d));
+        for (id, score) in &updated {
+            assert!((score - full[id]).abs() < 1e-4, "{} differs from a full recompute", id);
+        }
+        assert!(updated[&e] > previous[&e]);
+        assert!(updated[&b] < previous[&b]);
+    }
 }
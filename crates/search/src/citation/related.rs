@@ -0,0 +1,117 @@
+//! Citation-based related papers
+//!
+//! Complements embedding similarity with two classic citation measures:
+//! - co-citation: papers cited together by the same papers
+//! - bibliographic coupling: papers citing the same papers
+//!
+//! Both are normalized with Salton's cosine, `shared / sqrt(n_a * n_b)`,
+//! where `n` counts citations (co-citation) or references (coupling).
+
+use super::CitationGraph;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Neighbors of a paper in one direction of the graph
+type Links = fn(&CitationGraph, Uuid) -> &[Uuid];
+
+/// How papers are related through the citation graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelatedMethod {
+    /// Cited together by the same papers
+    Cocitation,
+    /// Citing the same papers
+    Coupling,
+}
+
+/// A related paper
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedPaper {
+    /// Paper ID
+    pub paper_id: Uuid,
+    
+    /// Papers citing both (co-citation) or cited by both (coupling)
+    pub shared: usize,
+    
+    /// Salton's cosine of the two papers (0.0 - 1.0)
+    pub score: f32,
+}
+
+/// Papers most related to `paper_id`, best first
+pub fn related_papers(
+    graph: &CitationGraph,
+    paper_id: Uuid,
+    method: RelatedMethod,
+    limit: usize,
+) -> Vec<RelatedPaper> {
+    // Co-cited papers share citing papers; coupled papers share references
+    let (links, back): (Links, Links) = match method {
+        RelatedMethod::Cocitation => (CitationGraph::get_citations, CitationGraph::get_references),
+        RelatedMethod::Coupling => (CitationGraph::get_references, CitationGraph::get_citations),
+    };
+    
+    let mut shared: HashMap<Uuid, usize> = HashMap::new();
+    for &link in links(graph, paper_id) {
+        for &other in back(graph, link) {
+            if other != paper_id {
+                *shared.entry(other).or_default() += 1;
+            }
+        }
+    }
+    
+    let own = links(graph, paper_id).len() as f32;
+    let mut related: Vec<RelatedPaper> = shared.into_iter()
+        .map(|(other, count)| RelatedPaper {
+            paper_id: other,
+            shared: count,
+            score: count as f32 / (own * links(graph, other).len() as f32).sqrt(),
+        })
+        .collect();
+    
+    related.sort_by(|a, b| {
+        b.score.total_cmp(&a.score)
+            .then(b.shared.cmp(&a.shared))
+            .then(a.paper_id.cmp(&b.paper_id))
+    });
+    related.truncate(limit);
+    related
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_cocitation_and_coupling() {
+        let mut graph = CitationGraph::new();
+        
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let c = Uuid::from_u128(3);
+        let d = Uuid::from_u128(4);
+        let x = Uuid::from_u128(10);
+        let y = Uuid::from_u128(11);
+        
+        // X and Y both cite A and B; only Y cites C
+        graph.add_edge(x, a);
+        graph.add_edge(x, b);
+        graph.add_edge(y, a);
+        graph.add_edge(y, b);
+        graph.add_edge(y, c);
+        graph.add_edge(d, c);
+        
+        let cocited = related_papers(&graph, a, RelatedMethod::Cocitation, 10);
+        assert_eq!(cocited[0].paper_id, b);
+        assert_eq!(cocited[0].shared, 2);
+        assert!((cocited[0].score - 1.0).abs() < 1e-6);
+        assert_eq!(cocited[1].paper_id, c);
+        assert_eq!(cocited[1].shared, 1);
+        
+        // X and Y share two references; D shares only C with Y
+        let coupled = related_papers(&graph, y, RelatedMethod::Coupling, 10);
+        assert_eq!(coupled[0].paper_id, x);
+        assert_eq!(coupled[0].shared, 2);
+        assert_eq!(coupled.len(), 2);
+    }
+}
@@ -0,0 +1,201 @@
+//! Background PageRank updates
+//!
+//! New citations only change the scores of the papers downstream of their
+//! citing papers, so every `interval_secs` just those are rescored, starting
+//! from their persisted scores. Every `full_recompute_secs` each tenant is
+//! rescored from scratch, which also accounts for deleted papers and
+//! citations. With a cache, a distributed lock keeps search instances from
+//! doing the same updates at once.
+
+use super::{CitationGraph, PageRankScorer};
+use chrono::Utc;
+use paperforge_common::cache::Cache;
+use paperforge_common::config::PageRankUpdateConfig;
+use paperforge_common::db::PageRankRepository;
+use paperforge_common::errors::Result;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+/// Name of the lock held while PageRank is updated
+const PAGERANK_LOCK: &str = "pagerank";
+
+/// Keeps the papers' persisted PageRank up to date
+pub struct PageRankUpdater {
+    repo: Arc<dyn PageRankRepository>,
+    scorer: PageRankScorer,
+    cache: Option<Arc<Cache>>,
+}
+
+impl PageRankUpdater {
+    /// Create an updater
+    pub fn new(repo: Arc<dyn PageRankRepository>, scorer: PageRankScorer) -> Self {
+        Self {
+            repo,
+            scorer,
+            cache: None,
+        }
+    }
+    
+    /// Run updates under a distributed lock in `cache`
+    pub fn with_lock(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+    
+    /// Run `update` unless another instance is updating
+    ///
+    /// Returns `Ok(None)` when it was skipped.
+    async fn locked<F>(&self, ttl: Duration, update: F) -> Result<Option<usize>>
+    where
+        F: std::future::Future<Output = Result<usize>>,
+    {
+        match &self.cache {
+            Some(cache) => match cache.with_lock(PAGERANK_LOCK, ttl, update).await? {
+                Some(result) => result.map(Some),
+                None => {
+                    debug!("PageRank is being updated on another instance");
+                    Ok(None)
+                }
+            },
+            None => update.await.map(Some),
+        }
+    }
+    
+    /// Rescore the papers downstream of new citations; returns papers rescored
+    pub async fn update_pending(&self) -> Result<usize> {
+        let mut updated = 0;
+        // One tenant's failure doesn't hold up the others
+        for (tenant_id, sources) in self.repo.pagerank_pending_papers().await? {
+            match self.update_tenant(tenant_id, &sources).await {
+                Ok(count) => updated += count,
+                Err(e) => error!(tenant_id = %tenant_id, error = %e, "Failed to update PageRank"),
+            }
+        }
+        Ok(updated)
+    }
+    
+    /// Rescore every tenant from scratch; returns papers rescored
+    pub async fn recompute_all(&self) -> Result<usize> {
+        let mut updated = 0;
+        for tenant_id in self.repo.tenants_with_papers().await? {
+            match self.recompute_tenant(tenant_id).await {
+                Ok(count) => updated += count,
+                Err(e) => error!(tenant_id = %tenant_id, error = %e, "Failed to recompute PageRank"),
+            }
+        }
+        Ok(updated)
+    }
+    
+    async fn update_tenant(&self, tenant_id: Uuid, sources: &[Uuid]) -> Result<usize> {
+        // Citations added from here on are newer than the scores and are
+        // picked up by the next run
+        let computed_at = Utc::now().fixed_offset();
+        let graph = CitationGraph::from_citations(self.repo.citation_graph(tenant_id).await?);
+        let previous = self.repo.paper_pageranks(tenant_id)
+            .await?
+            .into_iter()
+            .map(|(id, score)| (id, score as f32))
+            .collect();
+        
+        let scores = self.scorer.update(&graph, &previous, sources);
+        self.save(scores, computed_at).await
+    }
+    
+    async fn recompute_tenant(&self, tenant_id: Uuid) -> Result<usize> {
+        let computed_at = Utc::now().fixed_offset();
+        let graph = CitationGraph::from_citations(self.repo.citation_graph(tenant_id).await?);
+        
+        let scores = self.scorer.compute_raw(&graph);
+        self.save(scores, computed_at).await
+    }
+    
+    async fn save(&self, scores: HashMap<Uuid, f32>, computed_at: DateTimeWithTimeZone) -> Result<usize> {
+        let scores: Vec<(Uuid, f64)> = scores.into_iter()
+            .map(|(id, score)| (id, score as f64))
+            .collect();
+        self.repo.save_pageranks(&scores, computed_at).await?;
+        Ok(scores.len())
+    }
+    
+    /// Spawn the update loop on the current runtime
+    ///
+    /// Papers with citations start out pending, so the first runs score them
+    /// without waiting for a full recompute.
+    pub fn spawn(self, config: &PageRankUpdateConfig) -> JoinHandle<()> {
+        let interval_secs = config.interval_secs;
+        let full_recompute = Duration::from_secs(config.full_recompute_secs);
+        
+        tokio::spawn(async move {
+            let mut pending = tokio::time::interval(Duration::from_secs(interval_secs));
+            let mut full = tokio::time::interval_at(tokio::time::Instant::now() + full_recompute, full_recompute);
+            
+            info!(interval_secs = interval_secs, "Starting PageRank update task");
+            
+            loop {
+                tokio::select! {
+                    _ = pending.tick() => match self.locked(pending.period(), self.update_pending()).await {
+                        Ok(None | Some(0)) => {}
+                        Ok(Some(papers)) => info!(papers = papers, "PageRank updated for new citations"),
+                        Err(e) => error!(error = %e, "Failed to update PageRank"),
+                    },
+                    _ = full.tick() => match self.locked(full_recompute, self.recompute_all()).await {
+                        Ok(None) => {}
+                        Ok(Some(papers)) => info!(papers = papers, "PageRank recomputed"),
+                        Err(e) => error!(error = %e, "Failed to recompute PageRank"),
+                    },
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::citation::PageRankConfig;
+    use paperforge_common::db::memory::MemoryRepository;
+
+    fn updater(repo: Arc<MemoryRepository>) -> PageRankUpdater {
+        PageRankUpdater::new(repo, PageRankScorer::new(PageRankConfig::default()))
+    }
+
+    #[tokio::test]
+    async fn test_update_pending_scores_new_citations_once() {
+        let repo = Arc::new(MemoryRepository::new());
+        let tenant_id = Uuid::new_v4();
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        for (id, title) in [(a, "A"), (b, "B"), (c, "C")] {
+            repo.insert_paper(tenant_id, id, title);
+        }
+        repo.insert_citation(a, c);
+        repo.insert_citation(b, c);
+
+        let updater = updater(repo.clone());
+        assert!(updater.update_pending().await.unwrap() > 0);
+        assert!(repo.pagerank(c).unwrap() > repo.pagerank(a).unwrap());
+
+        // Nothing new since the last run
+        assert_eq!(updater.update_pending().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_recompute_all_is_tenant_scoped() {
+        let repo = Arc::new(MemoryRepository::new());
+        let (tenant_a, tenant_b) = (Uuid::new_v4(), Uuid::new_v4());
+        let (a1, a2, b1) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        repo.insert_paper(tenant_a, a1, "A1");
+        repo.insert_paper(tenant_a, a2, "A2");
+        repo.insert_paper(tenant_b, b1, "B1");
+        repo.insert_citation(a1, a2);
+
+        assert_eq!(updater(repo.clone()).recompute_all().await.unwrap(), 3);
+        let scores = repo.paper_pageranks(tenant_a).await.unwrap();
+        assert_eq!(scores.len(), 2);
+        assert!(!scores.contains_key(&b1));
+    }
+}
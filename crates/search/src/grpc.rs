@@ -1,21 +1,37 @@
 //! gRPC service implementation for search
 
-use crate::retrieval::{HybridRetriever, BM25Retriever, VectorRetriever, Retriever, SearchRequest, RetrievalMode};
+use crate::retrieval::{HybridRetriever, BM25Retriever, VectorRetriever, Retriever, RetrievedChunk, SearchRequest, RetrievalMode};
 use crate::citation::{CitationGraph, PageRankScorer, PageRankConfig};
+use paperforge_common::authors::split_author_filter;
+use paperforge_common::auth::grpc_auth_context;
+use paperforge_common::config::SharedConfig;
 use paperforge_common::db::DbPool;
-use paperforge_common::cache::{Cache, CacheConfig};
+use paperforge_common::request_context::grpc_request_context;
+use paperforge_common::errors::AppError;
+use paperforge_common::cache::{keys, Cache, CacheConfig};
+use paperforge_common::metrics;
 use paperforge_common::proto::search::{
     search_service_server::{SearchService, SearchServiceServer},
     SearchRequest as ProtoSearchRequest,
     SearchResponse as ProtoSearchResponse,
     SearchResult as ProtoSearchResult,
-    SearchMode,
+    SearchStreamResponse as ProtoSearchStreamResponse,
+    search_stream_response::Event,
+    SearchMode, SearchResultsUpdate, SearchStreamSummary,
 };
+use futures::{channel::mpsc, SinkExt};
 use std::sync::Arc;
+use std::time::Instant;
 use tonic::{Request, Response, Status};
 use uuid::Uuid;
 
+/// Messages buffered per streaming search before it waits on the client
+const STREAM_BUFFER: usize = 4;
+
+type StreamSender = mpsc::Sender<Result<ProtoSearchStreamResponse, Status>>;
+
 /// Search gRPC service
+#[derive(Clone)]
 pub struct SearchGrpcService {
     db: Arc<DbPool>,
     cache: Option<Arc<Cache>>,
@@ -36,6 +52,19 @@ impl SearchGrpcService {
         }
     }
     
+    /// Take hybrid search weights from `config`, following reloads
+    pub fn with_config(mut self, config: SharedConfig) -> Self {
+        self.hybrid = self.hybrid.with_config(config);
+        self
+    }
+    
+    /// Serve BM25 (and the BM25 half of hybrid search) from `bm25`
+    pub fn with_bm25(mut self, bm25: BM25Retriever) -> Self {
+        self.hybrid = self.hybrid.with_bm25(bm25.clone());
+        self.bm25 = bm25;
+        self
+    }
+    
     /// Create the gRPC server
     pub fn into_server(self) -> SearchServiceServer<Self> {
         SearchServiceServer::new(self)
@@ -50,87 +79,287 @@ impl SearchGrpcService {
         }
     }
     
-    /// Generate cache key for search
-    fn cache_key(&self, req: &ProtoSearchRequest) -> String {
+    /// Cache key of a search's response, `None` when it mustn't be cached
+    ///
+    /// The key covers the whole request and the tenant's search generation,
+    /// so ingesting or deleting papers retires it. A search whose generation
+    /// can't be read bypasses the cache rather than risk stale results.
+    async fn cache_key(&self, req: &ProtoSearchRequest) -> Option<String> {
+        use prost::Message;
         use sha2::{Sha256, Digest};
-        let mut hasher = Sha256::new();
-        hasher.update(&req.query);
-        hasher.update(req.mode.to_le_bytes());
-        hasher.update(req.limit.to_le_bytes());
-        let hash = hex::encode(hasher.finalize());
-        format!("search:{}:{}:{}", req.tenant_id, req.mode, &hash[..16])
+        let cache = self.cache.as_ref()?;
+        let tenant_id = Uuid::parse_str(&req.tenant_id).ok()?;
+        let generation = match cache.search_generation(tenant_id).await {
+            Ok(generation) => generation,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to read search generation, bypassing cache");
+                return None;
+            }
+        };
+        let hash = hex::encode(Sha256::digest(req.encode_to_vec()));
+        let mode = req.options.as_ref().map(|o| o.mode).unwrap_or_default();
+        Some(keys::search_query(tenant_id, generation, &hash, &mode.to_string()))
     }
-}
-
-#[tonic::async_trait]
-impl SearchService for SearchGrpcService {
-    async fn search(
-        &self,
-        request: Request<ProtoSearchRequest>,
-    ) -> Result<Response<ProtoSearchResponse>, Status> {
-        let req = request.into_inner();
-        let start = std::time::Instant::now();
-        
-        // Parse tenant ID
-        let tenant_id = Uuid::parse_str(&req.tenant_id)
-            .map_err(|_| Status::invalid_argument("Invalid tenant_id"))?;
+    
+    /// The cached response under `cache_key`, recording the hit or miss
+    async fn cached(&self, cache_key: Option<&str>) -> Option<ProtoSearchResponse> {
+        let (cache, cache_key) = (self.cache.as_ref()?, cache_key?);
+        let cached = cache.get::<ProtoSearchResponse>(cache_key).await.ok().flatten();
+        metrics::record_cache(cached.is_some(), "search");
+        if cached.is_some() {
+            tracing::debug!(cache_key = %cache_key, "Cache hit");
+        }
+        cached
+    }
+    
+    /// Cache `response` under `cache_key`
+    async fn cache(&self, cache_key: Option<&str>, response: &ProtoSearchResponse) {
+        if let (Some(cache), Some(cache_key)) = (&self.cache, cache_key) {
+            if let Err(e) = cache.set(cache_key, response).await {
+                tracing::warn!(error = %e, "Failed to cache search response");
+            }
+        }
+    }
+    
+    /// Authenticate a search and build the retrieval request
+    ///
+    /// The returned proto request carries the authenticated tenant.
+    fn prepare(request: Request<ProtoSearchRequest>) -> Result<(ProtoSearchRequest, SearchRequest), Status> {
+        let auth = grpc_auth_context(&request)?;
+        let mut req = request.into_inner();
         
-        // Check cache first
-        let cache_key = self.cache_key(&req);
-        if let Some(cache) = &self.cache {
-            if let Ok(Some(cached)) = cache.get::<ProtoSearchResponse>(&cache_key).await {
-                tracing::debug!(cache_key = %cache_key, "Cache hit");
-                return Ok(Response::new(cached));
+        // The authenticated tenant wins; an explicit tenant_id must agree
+        if !req.tenant_id.is_empty() {
+            let requested = Uuid::parse_str(&req.tenant_id)
+                .map_err(|_| Status::invalid_argument("Invalid tenant_id"))?;
+            if requested != auth.tenant_id {
+                return Err(AppError::TenantMismatch.into());
             }
         }
+        let tenant_id = auth.tenant_id;
+        req.tenant_id = tenant_id.to_string();
+        
+        // Scope to a collection; the SQL only matches the tenant's own papers
+        let collection_id = req.options.as_ref()
+            .and_then(|o| o.filters.as_ref())
+            .map(|f| f.collection_id.as_str())
+            .filter(|id| !id.is_empty())
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Status::invalid_argument("Invalid collection_id"))?;
+        
+        // `author:` narrows the search and isn't itself searched for
+        let (query, author) = split_author_filter(&req.query);
+        if query.is_empty() {
+            return Err(Status::invalid_argument("Query needs search terms besides the author: filter"));
+        }
         
         // Build search request
-        let mode = Self::convert_mode(req.mode);
         let search_req = SearchRequest {
             tenant_id,
-            query: req.query.clone(),
+            query,
             query_embedding: if req.query_embedding.is_empty() {
                 None
             } else {
                 Some(req.query_embedding.clone())
             },
-            mode,
+            mode: Self::convert_mode(req.mode),
             limit: req.limit as usize,
             min_score: if req.min_score > 0.0 { Some(req.min_score) } else { None },
             paper_ids: None,
+            collection_id,
+            author,
         };
         
-        // Execute search
-        let chunks = match mode {
-            RetrievalMode::Vector => self.vector.retrieve(&search_req).await,
-            RetrievalMode::BM25 => self.bm25.retrieve(&search_req).await,
-            RetrievalMode::Hybrid => self.hybrid.retrieve(&search_req).await,
-        }.map_err(|e| Status::internal(format!("Search failed: {}", e)))?;
-        
-        // Convert to proto
-        let results: Vec<ProtoSearchResult> = chunks.iter().map(|c| {
-            ProtoSearchResult {
-                chunk_id: c.chunk_id.to_string(),
-                paper_id: c.paper_id.to_string(),
-                paper_title: c.paper_title.clone(),
-                content: c.content.clone(),
-                chunk_index: c.chunk_index,
-                score: c.score,
-            }
-        }).collect();
-        
-        let response = ProtoSearchResponse {
-            results,
+        Ok((req, search_req))
+    }
+    
+    /// Run a search with the retriever for its mode
+    async fn retrieve(&self, search_req: &SearchRequest) -> Result<Vec<RetrievedChunk>, Status> {
+        match search_req.mode {
+            RetrievalMode::Vector => self.vector.retrieve(search_req).await,
+            RetrievalMode::BM25 => self.bm25.retrieve(search_req).await,
+            RetrievalMode::Hybrid => self.hybrid.retrieve(search_req).await,
+        }.map_err(|e| match e {
+            // A query cut short by the caller's deadline
+            AppError::QueryTimeout { .. } => e.into(),
+            e => Status::internal(format!("Search failed: {}", e)),
+        })
+    }
+    
+    /// Convert a retrieved chunk to proto
+    fn to_proto(c: &RetrievedChunk) -> ProtoSearchResult {
+        ProtoSearchResult {
+            chunk_id: c.chunk_id.to_string(),
+            paper_id: c.paper_id.to_string(),
+            paper_title: c.paper_title.clone(),
+            content: c.content.clone(),
+            chunk_index: c.chunk_index,
+            score: c.score,
+            provenance: c.provenance.clone().map(Into::into),
+            ..Default::default()
+        }
+    }
+    
+    /// Build the (cacheable) response for a search's final results
+    fn response(req: &ProtoSearchRequest, chunks: &[RetrievedChunk], start: Instant) -> ProtoSearchResponse {
+        ProtoSearchResponse {
+            results: chunks.iter().map(Self::to_proto).collect(),
             total_count: chunks.len() as u32,
             query_time_ms: start.elapsed().as_millis() as u64,
             mode: req.mode,
+        }
+    }
+    
+    /// Run a streaming search, sending ranking updates and then the summary
+    ///
+    /// Hybrid searches send the vector hits as soon as they arrive, then the
+    /// fused ranking. Results are cached like unary searches and served from
+    /// the cache as a single final update.
+    async fn stream_search(
+        &self,
+        req: ProtoSearchRequest,
+        search_req: SearchRequest,
+        start: Instant,
+        mut tx: StreamSender,
+    ) -> Result<(), Status> {
+        let cache_key = self.cache_key(&req).await;
+        if let Some(cached) = self.cached(cache_key.as_deref()).await {
+            let total_results = cached.results.len() as i32;
+            send_update(&mut tx, req.mode, true, cached.results).await?;
+            return send_summary(&mut tx, SearchStreamSummary {
+                total_results,
+                processing_time_ms: start.elapsed().as_millis() as i64,
+                cache_hit: true,
+                ..Default::default()
+            }).await;
+        }
+        
+        let mut summary = SearchStreamSummary::default();
+        let chunks = match search_req.mode {
+            RetrievalMode::Hybrid => {
+                // Preview the vector hits while BM25 is still running
+                let vector_stage = async {
+                    let started = Instant::now();
+                    let results = self.hybrid.retrieve_vector(&search_req).await.unwrap_or_default();
+                    let elapsed = started.elapsed().as_millis() as i64;
+                    let preview = results.iter().take(search_req.limit).map(Self::to_proto).collect();
+                    let sent = send_update(&mut tx, SearchMode::Vector as i32, false, preview).await;
+                    (results, elapsed, sent)
+                };
+                let bm25_stage = async {
+                    let started = Instant::now();
+                    let results = self.hybrid.retrieve_bm25(&search_req).await.unwrap_or_default();
+                    (results, started.elapsed().as_millis() as i64)
+                };
+                let ((vector_results, vector_ms, sent), (bm25_results, bm25_ms)) =
+                    tokio::join!(vector_stage, bm25_stage);
+                sent?;
+                summary.vector_time_ms = vector_ms;
+                summary.bm25_time_ms = bm25_ms;
+                
+                let started = Instant::now();
+                let chunks = self.hybrid.fuse(&search_req, vector_results, bm25_results);
+                summary.fusion_time_ms = started.elapsed().as_millis() as i64;
+                chunks
+            }
+            mode => {
+                let started = Instant::now();
+                let chunks = self.retrieve(&search_req).await?;
+                let elapsed = started.elapsed().as_millis() as i64;
+                if mode == RetrievalMode::Vector {
+                    summary.vector_time_ms = elapsed;
+                } else {
+                    summary.bm25_time_ms = elapsed;
+                }
+                chunks
+            }
         };
         
+        let response = Self::response(&req, &chunks, start);
+        send_update(&mut tx, req.mode, true, response.results.clone()).await?;
+        
         // Cache the result
-        if let Some(cache) = &self.cache {
-            let _ = cache.set_with_ttl(&cache_key, &response, 300).await;
-        }
+        self.cache(cache_key.as_deref(), &response).await;
+        
+        summary.total_results = chunks.len() as i32;
+        summary.processing_time_ms = start.elapsed().as_millis() as i64;
+        send_summary(&mut tx, summary).await
+    }
+}
+
+/// Send a ranking update; fails once the client has gone away
+async fn send_update(
+    tx: &mut StreamSender,
+    mode: i32,
+    is_final: bool,
+    results: Vec<ProtoSearchResult>,
+) -> Result<(), Status> {
+    send_event(tx, Event::Results(SearchResultsUpdate { mode, is_final, results })).await
+}
+
+async fn send_summary(tx: &mut StreamSender, summary: SearchStreamSummary) -> Result<(), Status> {
+    send_event(tx, Event::Summary(summary)).await
+}
+
+async fn send_event(tx: &mut StreamSender, event: Event) -> Result<(), Status> {
+    tx.send(Ok(ProtoSearchStreamResponse { event: Some(event) }))
+        .await
+        .map_err(|_| Status::cancelled("Client closed the stream"))
+}
+
+#[tonic::async_trait]
+impl SearchService for SearchGrpcService {
+    async fn search(
+        &self,
+        request: Request<ProtoSearchRequest>,
+    ) -> Result<Response<ProtoSearchResponse>, Status> {
+        let start = Instant::now();
+        let context = grpc_request_context(&request);
+        let (req, search_req) = Self::prepare(request)?;
+        
+        context.scope(async {
+            // Check cache first
+            let cache_key = self.cache_key(&req).await;
+            if let Some(cached) = self.cached(cache_key.as_deref()).await {
+                return Ok(Response::new(cached));
+            }
+            
+            // Execute search
+            let chunks = self.retrieve(&search_req).await?;
+            let response = Self::response(&req, &chunks, start);
+            
+            // Cache the result
+            self.cache(cache_key.as_deref(), &response).await;
+            
+            Ok(Response::new(response))
+        })
+        .await
+    }
+    
+    type SearchStreamStream = mpsc::Receiver<Result<ProtoSearchStreamResponse, Status>>;
+    
+    async fn search_stream(
+        &self,
+        request: Request<ProtoSearchRequest>,
+    ) -> Result<Response<Self::SearchStreamStream>, Status> {
+        let start = Instant::now();
+        let context = grpc_request_context(&request);
+        let (req, search_req) = Self::prepare(request)?;
+        
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let service = self.clone();
+        // The spawned task doesn't inherit the handler's deadline
+        tokio::spawn(async move {
+            context.scope(async move {
+                let mut errors = tx.clone();
+                if let Err(status) = service.stream_search(req, search_req, start, tx).await {
+                    tracing::debug!(error = %status, "Streaming search ended early");
+                    let _ = errors.send(Err(status)).await;
+                }
+            }).await;
+        });
         
-        Ok(Response::new(response))
+        Ok(Response::new(rx))
     }
 }
@@ -2,7 +2,7 @@
 //!
 //! Dedicated search microservice providing:
 //! - Vector similarity search (pgvector)
-//! - BM25 text search (PostgreSQL full-text)
+//! - BM25 text search (PostgreSQL full-text, or tantivy indexes)
 //! - Hybrid search with RRF fusion
 //! - Citation graph traversal & PageRank scoring
 //! - Query caching via Redis
@@ -11,9 +11,18 @@ mod retrieval;
 mod citation;
 mod grpc;
 
-use paperforge_common::{config::AppConfig, db::DbPool, cache::{Cache, CacheConfig}, VERSION};
+use paperforge_common::{
+    auth::{AuthState, GrpcAuthLayer},
+    cache::{Cache, CacheConfig},
+    config::{AppConfig, ConfigWatcher, Service},
+    db::{DbPool, Repository},
+    metrics::{self, GrpcMetricsLayer},
+    VERSION,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tonic::transport::Server;
 use tracing::{info, warn, Level};
 
@@ -32,69 +41,132 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting PaperForge Search Service v{}", VERSION);
     
     // Load configuration
-    let config = AppConfig::load().map_err(|e| {
+    let config = AppConfig::load_for(Service::Search).await.map_err(|e| {
         tracing::error!(error = %e, "Failed to load configuration");
         e
     })?;
     
     let config = Arc::new(config);
     
+    // Initialize metrics, served for Prometheus on the metrics port
+    if config.observability.metrics_port > 0 {
+        PrometheusBuilder::new()
+            .with_http_listener(SocketAddr::from(([0, 0, 0, 0], config.observability.metrics_port)))
+            .set_buckets_for_metric(
+                Matcher::Suffix("duration_seconds".to_string()),
+                metrics::LATENCY_BUCKETS,
+            )?
+            .install()?;
+    }
+    metrics::register_metrics();
+    metrics::configure_tenant_labels(&config.observability);
+    
     // Initialize database connection
     info!("Connecting to database...");
     let db = Arc::new(DbPool::new(&config.database).await?);
+    db.spawn_metrics_collector(Duration::from_secs(config.database.pool_metrics_interval_secs));
     
-    // Initialize Redis cache (optional)
-    let cache = match std::env::var("REDIS_URL") {
-        Ok(url) => {
-            info!("Connecting to Redis at {}", url);
-            let cache_config = CacheConfig {
-                url,
-                default_ttl_secs: 300,
-                pool_size: 10,
-                key_prefix: "paperforge:search".to_string(),
-            };
-            match Cache::new(cache_config).await {
-                Ok(cache) => {
-                    info!("Redis cache connected");
-                    Some(Arc::new(cache))
-                }
-                Err(e) => {
-                    warn!("Failed to connect to Redis, caching disabled: {}", e);
-                    None
-                }
-            }
+    // Initialize Redis cache (optional; searches run uncached without it)
+    info!("Connecting to Redis at {}", config.redis.url);
+    let cache_config = CacheConfig {
+        key_prefix: config.search.cache_key_prefix.clone(),
+        ..CacheConfig::from_redis(&config.redis)
+    };
+    let cache = match Cache::new(cache_config).await {
+        Ok(cache) => {
+            info!("Redis cache connected");
+            Some(Arc::new(cache))
         }
-        Err(_) => {
-            warn!("REDIS_URL not set, caching disabled");
+        Err(e) => {
+            warn!("Failed to connect to Redis, caching disabled: {}", e);
             None
         }
     };
     
+    // Hot-reload search weights and the cache TTL on config file changes and SIGHUP
+    let watcher = Arc::new(ConfigWatcher::new(config.as_ref().clone()).for_service(Service::Search));
+    let _config_watcher = watcher
+        .clone()
+        .spawn(Duration::from_secs(config.search.config_poll_secs));
+    if let Some(cache) = cache.clone() {
+        let mut config_changes = watcher.subscribe();
+        tokio::spawn(async move {
+            while config_changes.changed().await.is_ok() {
+                let ttl_secs = config_changes.borrow_and_update().redis.default_ttl_secs;
+                if ttl_secs != cache.default_ttl() {
+                    cache.set_default_ttl(ttl_secs);
+                    info!(ttl_secs, "Search cache TTL updated");
+                }
+            }
+        });
+    }
+    
+    // Authenticate every call (API key, JWT or internal service token)
+    if config.auth.service_token.is_none() {
+        warn!("Service token not configured, internal callers must use API keys or JWTs");
+    }
+    let auth = GrpcAuthLayer::new(AuthState::new(&config.auth, db.as_ref().clone()));
+    
     // Create gRPC service
-    let search_service = grpc::SearchGrpcService::new(db, cache);
+    let search_service = grpc::SearchGrpcService::new(db.clone(), cache.clone())
+        .with_config(watcher.shared());
     
-    // Get gRPC port
-    let grpc_port = std::env::var("GRPC_PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(50051);
+    // Keep the papers' citation PageRank up to date
+    let pagerank_task = config.pagerank.enabled.then(|| {
+        let scorer = citation::PageRankScorer::new(citation::PageRankConfig::default());
+        let updater = citation::PageRankUpdater::new(Arc::new(Repository::new(db.as_ref().clone())), scorer);
+        let updater = match cache.clone() {
+            Some(cache) => updater.with_lock(cache),
+            None => updater,
+        };
+        updater.spawn(&config.pagerank)
+    });
     
+    // Serve BM25 from the tenants' tantivy indexes when enabled
+    #[cfg(feature = "bm25-index")]
+    let search_service = if config.bm25_index.enabled {
+        let storage = paperforge_common::storage::create_store(&config.storage).await?;
+        let index = paperforge_common::bm25_index::Bm25Index::new(config.bm25_index.clone(), storage)?;
+        info!(dir = %config.bm25_index.dir, "Serving BM25 from tantivy indexes");
+        search_service.with_bm25(retrieval::BM25Retriever::with_index(db, Arc::new(index)))
+    } else {
+        search_service
+    };
+    #[cfg(not(feature = "bm25-index"))]
+    if config.bm25_index.enabled {
+        warn!("bm25_index.enabled is set but the search service was built without the bm25-index feature");
+    }
+    
+    let grpc_port = config.search.grpc_port;
     let addr: SocketAddr = ([0, 0, 0, 0], grpc_port).into();
     
     info!("Search service listening on gRPC port {}", grpc_port);
     
+    // Standard gRPC health service, for the gateway's readiness probe
+    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter
+        .set_serving::<paperforge_common::proto::search::search_service_server::SearchServiceServer<grpc::SearchGrpcService>>()
+        .await;
+    
     // Start gRPC server
     Server::builder()
+        .layer(GrpcMetricsLayer::new())
+        .layer(auth)
+        .add_service(health_service)
         .add_service(search_service.into_server())
-        .serve_with_shutdown(addr, shutdown_signal())
+        .serve_with_shutdown(addr, shutdown_signal(config.clone()))
         .await?;
     
+    if let Some(task) = pagerank_task {
+        task.abort();
+    }
+    
     info!("Search service shutdown complete");
     Ok(())
 }
 
-/// Graceful shutdown signal handler
-async fn shutdown_signal() {
+/// Graceful shutdown signal handler, also triggered by rotated secrets
+async fn shutdown_signal(config: Arc<AppConfig>) {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
             .await
@@ -115,5 +187,6 @@ async fn shutdown_signal() {
     tokio::select! {
         _ = ctrl_c => info!("Received Ctrl+C, starting shutdown..."),
         _ = terminate => info!("Received SIGTERM, starting shutdown..."),
+        _ = config.secrets_rotated(Some(Service::Search)) => {}
     }
 }
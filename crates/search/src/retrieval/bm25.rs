@@ -1,23 +1,51 @@
-//! BM25 lexical search using PostgreSQL full-text search
+//! BM25 lexical search
 //!
-//! Provides keyword-based search with ranking
+//! Provides keyword-based search with ranking, from PostgreSQL full-text
+//! search or, with the `bm25-index` feature, per-tenant tantivy indexes
 
 use super::{RetrievalMode, RetrievedChunk, Retriever, SearchRequest};
-use paperforge_common::errors::{AppError, Result};
-use paperforge_common::db::DbPool;
-use sea_orm::{ConnectionTrait, Statement, DbBackend};
+use paperforge_common::errors::Result;
+use paperforge_common::db::{models::Provenance, DbPool};
+#[cfg(feature = "bm25-index")]
+use paperforge_common::{bm25_index::Bm25Index, db::Repository};
+use sea_orm::{Statement, DbBackend};
+#[cfg(feature = "bm25-index")]
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
-/// BM25 retriever using PostgreSQL full-text search
+/// Where BM25 scores come from
+#[derive(Clone)]
+enum Backend {
+    /// PostgreSQL full-text search
+    Postgres,
+    /// Per-tenant tantivy indexes; hits are loaded from Postgres
+    #[cfg(feature = "bm25-index")]
+    Tantivy(Arc<Bm25Index>),
+}
+
+/// BM25 retriever
+#[derive(Clone)]
 pub struct BM25Retriever {
     db: Arc<DbPool>,
+    backend: Backend,
 }
 
 impl BM25Retriever {
-    /// Create a new BM25 retriever
+    /// Create a new BM25 retriever using PostgreSQL full-text search
     pub fn new(db: Arc<DbPool>) -> Self {
-        Self { db }
+        Self { db, backend: Backend::Postgres }
+    }
+    
+    /// Create a BM25 retriever scoring with the tenants' tantivy indexes
+    #[cfg(feature = "bm25-index")]
+    pub fn with_index(db: Arc<DbPool>, index: Arc<Bm25Index>) -> Self {
+        Self { db, backend: Backend::Tantivy(index) }
+    }
+    
+    /// Normalize a BM25 score to the 0-1 range
+    fn normalize_score(score: f64) -> f32 {
+        (score / (score + 1.0)) as f32
     }
     
     /// Prepare query for full-text search
@@ -37,11 +65,46 @@ impl BM25Retriever {
             .collect::<Vec<_>>()
             .join(" & ")
     }
-}
-
-#[async_trait::async_trait]
-impl Retriever for BM25Retriever {
-    async fn retrieve(&self, request: &SearchRequest) -> Result<Vec<RetrievedChunk>> {
+    
+    /// Score with a tenant's tantivy index
+    #[cfg(feature = "bm25-index")]
+    async fn retrieve_indexed(&self, index: &Bm25Index, request: &SearchRequest) -> Result<Vec<RetrievedChunk>> {
+        let hits = index.search(request.tenant_id, &request.query, request.limit).await?;
+        let ids: Vec<Uuid> = hits.iter().map(|(id, _)| *id).collect();
+        
+        let mut found: HashMap<Uuid, _> = Repository::new(self.db.as_ref().clone())
+            .chunk_results_by_ids(request.tenant_id, &ids)
+            .await?
+            .into_iter()
+            .map(|chunk| (chunk.chunk_id, chunk))
+            .collect();
+        
+        let min_score = request.min_score.unwrap_or(0.0);
+        
+        // Chunks of papers deleted since the last index sync aren't found
+        Ok(hits.into_iter().filter_map(|(id, score)| {
+            let chunk = found.remove(&id)?;
+            let normalized_score = Self::normalize_score(score as f64);
+            
+            if normalized_score < min_score {
+                return None;
+            }
+            
+            Some(RetrievedChunk {
+                chunk_id: chunk.chunk_id,
+                paper_id: chunk.paper_id,
+                paper_title: chunk.paper_title,
+                content: chunk.content,
+                chunk_index: chunk.chunk_index,
+                score: normalized_score,
+                retrieval_mode: RetrievalMode::BM25,
+                provenance: chunk.provenance,
+            })
+        }).collect())
+    }
+    
+    /// Score with PostgreSQL full-text search
+    async fn retrieve_postgres(&self, request: &SearchRequest) -> Result<Vec<RetrievedChunk>> {
         let ts_query = self.prepare_query(&request.query);
         
         if ts_query.is_empty() {
@@ -58,6 +121,7 @@ impl Retriever for BM25Retriever {
                 p.title as paper_title,
                 c.content,
                 c.chunk_index,
+                c.metadata,
                 ts_rank_cd(
                     to_tsvector('english', c.content),
                     plainto_tsquery('english', $2),
@@ -66,33 +130,39 @@ impl Retriever for BM25Retriever {
             FROM chunks c
             INNER JOIN papers p ON c.paper_id = p.id
             WHERE p.tenant_id = $1
+              AND p.deleted_at IS NULL
               AND to_tsvector('english', c.content) @@ plainto_tsquery('english', $2)
+              AND ($4::uuid IS NULL OR c.paper_id IN (
+                  SELECT paper_id FROM collection_papers WHERE collection_id = $4
+              ))
+              AND ($5::text IS NULL OR c.paper_id IN (
+                  SELECT pa.paper_id FROM paper_authors pa
+                  JOIN authors a ON a.id = pa.author_id
+                  WHERE a.orcid = $5 OR strpos(a.normalized_name, $5) > 0
+              ))
             ORDER BY score DESC
             LIMIT $3
         "#;
         
-        let conn = self.db.read_connection().await;
-        let rows = conn
-            .query_all(Statement::from_sql_and_values(
-                DbBackend::Postgres,
-                sql,
-                vec![
-                    request.tenant_id.into(),
-                    request.query.clone().into(),
-                    (request.limit as i64).into(),
-                ],
-            ))
-            .await
-            .map_err(|e| AppError::DatabaseError { 
-                message: format!("BM25 search failed: {}", e) 
-            })?;
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            vec![
+                request.tenant_id.into(),
+                request.query.clone().into(),
+                (request.limit as i64).into(),
+                request.collection_id.into(),
+                request.author.clone().into(),
+            ],
+        );
+        let rows = self.db.search_query("bm25_search", stmt).await?;
         
         let chunks: Vec<RetrievedChunk> = rows.iter().filter_map(|row| {
             use sea_orm::TryGetable;
             let score: f64 = row.try_get("", "score").ok()?;
             
             // Normalize score to 0-1 range (ts_rank_cd can exceed 1)
-            let normalized_score = (score / (score + 1.0)) as f32;
+            let normalized_score = Self::normalize_score(score);
             
             if normalized_score < min_score {
                 return None;
@@ -106,11 +176,31 @@ impl Retriever for BM25Retriever {
                 chunk_index: row.try_get("", "chunk_index").ok()?,
                 score: normalized_score,
                 retrieval_mode: RetrievalMode::BM25,
+                provenance: row
+                    .try_get::<serde_json::Value>("", "metadata")
+                    .ok()
+                    .and_then(|metadata| Provenance::from_metadata(&metadata)),
             })
         }).collect();
         
         Ok(chunks)
     }
+}
+
+#[async_trait::async_trait]
+impl Retriever for BM25Retriever {
+    async fn retrieve(&self, request: &SearchRequest) -> Result<Vec<RetrievedChunk>> {
+        match &self.backend {
+            Backend::Postgres => self.retrieve_postgres(request).await,
+            // The tantivy indexes don't know collections or authors
+            #[cfg(feature = "bm25-index")]
+            Backend::Tantivy(_) if request.collection_id.is_some() || request.author.is_some() => {
+                self.retrieve_postgres(request).await
+            }
+            #[cfg(feature = "bm25-index")]
+            Backend::Tantivy(index) => self.retrieve_indexed(index, request).await,
+        }
+    }
     
     fn mode(&self) -> RetrievalMode {
         RetrievalMode::BM25
@@ -124,7 +214,7 @@ mod tests {
     #[test]
     fn test_query_preparation() {
         let db = Arc::new(DbPool::default_for_test());
-        let retriever = BM25Retriever { db };
+        let retriever = BM25Retriever::new(db);
         
         // Note: This test will fail because DbPool::default_for_test doesn't exist
         // It's here to show the expected behavior
@@ -6,6 +6,7 @@
 //! - Is robust to outliers
 
 use super::{RetrievedChunk, RetrievalMode};
+use paperforge_common::config::SearchConfig;
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -51,6 +52,11 @@ impl RRFusion {
         }
     }
     
+    /// Create with the weights of the search configuration
+    pub fn from_config(search: &SearchConfig) -> Self {
+        Self::with_weights(search.vector_weight as f32, search.bm25_weight as f32)
+    }
+    
     /// Fuse vector and BM25 results using RRF
     pub fn fuse(
         &self,
@@ -140,6 +146,7 @@ mod tests {
             chunk_index: 0,
             score,
             retrieval_mode: RetrievalMode::Vector,
+            provenance: None,
         }
     }
     
@@ -176,4 +183,18 @@ mod tests {
         // A should be second (appears in both)
         assert_eq!(results[1].chunk.chunk_id, Uuid::from_u128(1));
     }
+    
+    #[test]
+    fn test_weights_from_config() {
+        let mut bm25_b = make_chunk(2, 0.9);
+        bm25_b.retrieval_mode = RetrievalMode::BM25;
+        let (vector, bm25) = (vec![make_chunk(1, 0.9)], vec![bm25_b]);
+        
+        let results = RRFusion::from_config(&SearchConfig::default()).fuse(vector.clone(), bm25.clone(), 10);
+        assert_eq!(results[0].chunk.chunk_id, Uuid::from_u128(1));
+        
+        let search = SearchConfig { vector_weight: 0.2, bm25_weight: 0.8, ..Default::default() };
+        let results = RRFusion::from_config(&search).fuse(vector, bm25, 10);
+        assert_eq!(results[0].chunk.chunk_id, Uuid::from_u128(2));
+    }
 }
@@ -9,14 +9,18 @@ use super::{
     RetrievalMode, RetrievedChunk, Retriever, SearchRequest,
 };
 use paperforge_common::errors::Result;
+use paperforge_common::config::SharedConfig;
 use paperforge_common::db::DbPool;
 use std::sync::Arc;
 
 /// Hybrid retriever combining vector and BM25
+#[derive(Clone)]
 pub struct HybridRetriever {
     vector: VectorRetriever,
     bm25: BM25Retriever,
     fusion: RRFusion,
+    /// Configuration whose current search weights override `fusion`'s
+    config: Option<SharedConfig>,
 }
 
 impl HybridRetriever {
@@ -26,6 +30,7 @@ impl HybridRetriever {
             vector: VectorRetriever::new(db.clone()),
             bm25: BM25Retriever::new(db),
             fusion: RRFusion::default(),
+            config: None,
         }
     }
     
@@ -35,45 +40,78 @@ impl HybridRetriever {
             vector: VectorRetriever::new(db.clone()),
             bm25: BM25Retriever::new(db),
             fusion: RRFusion::with_weights(vector_weight, bm25_weight),
+            config: None,
         }
     }
+    
+    /// Use another BM25 retriever, e.g. one backed by tantivy indexes
+    pub fn with_bm25(mut self, bm25: BM25Retriever) -> Self {
+        self.bm25 = bm25;
+        self
+    }
+    
+    /// Fuse with the search weights of `config`, following reloads
+    pub fn with_config(mut self, config: SharedConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+    
+    /// Request for either half of the search
+    fn expanded(request: &SearchRequest) -> SearchRequest {
+        // Fetch more results from each retriever for better fusion
+        let mut expanded = request.clone();
+        expanded.limit = request.limit * 2;
+        expanded.min_score = None; // We'll filter after fusion
+        expanded
+    }
+    
+    /// Run the vector half of a hybrid search
+    pub async fn retrieve_vector(&self, request: &SearchRequest) -> Result<Vec<RetrievedChunk>> {
+        self.vector.retrieve(&Self::expanded(request)).await
+    }
+    
+    /// Run the BM25 half of a hybrid search
+    pub async fn retrieve_bm25(&self, request: &SearchRequest) -> Result<Vec<RetrievedChunk>> {
+        self.bm25.retrieve(&Self::expanded(request)).await
+    }
+    
+    /// Fuse the two halves into the final ranking
+    pub fn fuse(
+        &self,
+        request: &SearchRequest,
+        vector_results: Vec<RetrievedChunk>,
+        bm25_results: Vec<RetrievedChunk>,
+    ) -> Vec<RetrievedChunk> {
+        // Fuse results using RRF
+        let fused = match &self.config {
+            Some(config) => RRFusion::from_config(&config.load().search),
+            None => self.fusion.clone(),
+        }
+        .fuse(vector_results, bm25_results, request.limit);
+        
+        // Apply min_score filter if specified
+        let min_score = request.min_score.unwrap_or(0.0);
+        fused
+            .into_iter()
+            .filter(|r| r.chunk.score >= min_score)
+            .map(|r| r.chunk)
+            .collect()
+    }
 }
 
 #[async_trait::async_trait]
 impl Retriever for HybridRetriever {
     async fn retrieve(&self, request: &SearchRequest) -> Result<Vec<RetrievedChunk>> {
-        // Fetch more results from each retriever for better fusion
-        let expanded_limit = request.limit * 2;
-        
-        let mut vector_request = request.clone();
-        vector_request.limit = expanded_limit;
-        vector_request.min_score = None; // We'll filter after fusion
-        
-        let mut bm25_request = request.clone();
-        bm25_request.limit = expanded_limit;
-        bm25_request.min_score = None;
-        
         // Execute both searches in parallel
         let (vector_results, bm25_results) = tokio::join!(
-            self.vector.retrieve(&vector_request),
-            self.bm25.retrieve(&bm25_request)
+            self.retrieve_vector(request),
+            self.retrieve_bm25(request)
         );
         
         let vector_results = vector_results.unwrap_or_default();
         let bm25_results = bm25_results.unwrap_or_default();
         
-        // Fuse results using RRF
-        let fused = self.fusion.fuse(vector_results, bm25_results, request.limit);
-        
-        // Apply min_score filter if specified
-        let min_score = request.min_score.unwrap_or(0.0);
-        let chunks: Vec<RetrievedChunk> = fused
-            .into_iter()
-            .filter(|r| r.chunk.score >= min_score)
-            .map(|r| r.chunk)
-            .collect();
-        
-        Ok(chunks)
+        Ok(self.fuse(request, vector_results, bm25_results))
     }
     
     fn mode(&self) -> RetrievalMode {
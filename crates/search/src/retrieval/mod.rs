@@ -15,6 +15,7 @@ pub use bm25::BM25Retriever;
 pub use hybrid::HybridRetriever;
 pub use fusion::{RRFusion, FusionResult};
 
+use paperforge_common::db::models::Provenance;
 use paperforge_common::errors::Result;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -42,6 +43,10 @@ pub struct RetrievedChunk {
     
     /// Retrieval mode used
     pub retrieval_mode: RetrievalMode,
+    
+    /// Where the chunk is in its source PDF
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 }
 
 /// Retrieval mode
@@ -79,6 +84,13 @@ pub struct SearchRequest {
     
     /// Filter by paper IDs (optional)
     pub paper_ids: Option<Vec<Uuid>>,
+    
+    /// Only papers in this collection (optional)
+    pub collection_id: Option<Uuid>,
+    
+    /// Only papers by an author with this ORCID iD or normalized name
+    /// fragment (optional)
+    pub author: Option<String>,
 }
 
 impl Default for SearchRequest {
@@ -91,6 +103,8 @@ impl Default for SearchRequest {
             limit: 10,
             min_score: Some(0.3),
             paper_ids: None,
+            collection_id: None,
+            author: None,
         }
     }
 }
@@ -4,12 +4,13 @@
 
 use super::{RetrievalMode, RetrievedChunk, Retriever, SearchRequest};
 use paperforge_common::errors::{AppError, Result};
-use paperforge_common::db::DbPool;
-use sea_orm::{ConnectionTrait, Statement, FromQueryResult, DbBackend};
+use paperforge_common::db::{models::Provenance, DbPool};
+use sea_orm::{Statement, FromQueryResult, DbBackend};
 use std::sync::Arc;
 use uuid::Uuid;
 
 /// Vector retriever using pgvector
+#[derive(Clone)]
 pub struct VectorRetriever {
     db: Arc<DbPool>,
 }
@@ -49,6 +50,7 @@ impl VectorRetriever {
             FROM chunks c
             INNER JOIN papers p ON c.paper_id = p.id
             WHERE p.tenant_id = $1
+              AND p.deleted_at IS NULL
               AND 1 - (c.embedding <=> '{embedding}'::vector) >= $2
             "#,
             embedding = embedding_str
@@ -109,32 +111,39 @@ impl Retriever for VectorRetriever {
                 p.title as paper_title,
                 c.content,
                 c.chunk_index,
+                c.metadata,
                 1 - (c.embedding <=> '{embedding}'::vector) as score
             FROM chunks c
             INNER JOIN papers p ON c.paper_id = p.id
             WHERE p.tenant_id = $1
+              AND p.deleted_at IS NULL
               AND 1 - (c.embedding <=> '{embedding}'::vector) >= $2
+              AND ($4::uuid IS NULL OR c.paper_id IN (
+                  SELECT paper_id FROM collection_papers WHERE collection_id = $4
+              ))
+              AND ($5::text IS NULL OR c.paper_id IN (
+                  SELECT pa.paper_id FROM paper_authors pa
+                  JOIN authors a ON a.id = pa.author_id
+                  WHERE a.orcid = $5 OR strpos(a.normalized_name, $5) > 0
+              ))
             ORDER BY c.embedding <=> '{embedding}'::vector
             LIMIT $3
             "#,
             embedding = embedding_str
         );
         
-        let conn = self.db.read_connection().await;
-        let rows = conn
-            .query_all(Statement::from_sql_and_values(
-                DbBackend::Postgres,
-                &sql,
-                vec![
-                    request.tenant_id.into(),
-                    min_score.into(),
-                    (request.limit as i64).into(),
-                ],
-            ))
-            .await
-            .map_err(|e| AppError::DatabaseError { 
-                message: format!("Vector search failed: {}", e) 
-            })?;
+        let stmt = Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            &sql,
+            vec![
+                request.tenant_id.into(),
+                min_score.into(),
+                (request.limit as i64).into(),
+                request.collection_id.into(),
+                request.author.clone().into(),
+            ],
+        );
+        let rows = self.db.search_query("vector_search", stmt).await?;
         
         let chunks = rows.iter().map(|row| {
             use sea_orm::TryGetable;
@@ -146,6 +155,10 @@ impl Retriever for VectorRetriever {
                 chunk_index: row.try_get("", "chunk_index").unwrap_or_default(),
                 score: row.try_get::<f64, _>("", "score").unwrap_or_default() as f32,
                 retrieval_mode: RetrievalMode::Vector,
+                provenance: row
+                    .try_get::<serde_json::Value>("", "metadata")
+                    .ok()
+                    .and_then(|metadata| Provenance::from_metadata(&metadata)),
             }
         }).collect();
         
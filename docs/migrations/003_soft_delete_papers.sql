@@ -0,0 +1,12 @@
+-- =========================================================================================
+-- Soft Delete for 'papers'
+-- Adds deleted_at so DELETE /v2/papers/:id can be undone until the purge job runs
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE papers ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_papers_deleted ON papers(deleted_at) WHERE deleted_at IS NOT NULL;
+
+COMMIT;
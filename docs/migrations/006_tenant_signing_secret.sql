@@ -0,0 +1,10 @@
+-- =========================================================================================
+-- HMAC Request Signing
+-- Per-tenant shared secret for machine-to-machine callers that sign requests
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS signing_secret TEXT;
+
+COMMIT;
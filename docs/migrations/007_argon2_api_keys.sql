@@ -0,0 +1,13 @@
+-- =========================================================================================
+-- argon2id API Key Hashes
+-- Keys are found by prefix and verified with argon2id. Existing SHA-256 hashes keep
+-- working and are rehashed by the gateway the next time each key is used.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS api_key_prefix TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tenants_api_key_prefix ON tenants(api_key_prefix) WHERE is_active = true;
+
+COMMIT;
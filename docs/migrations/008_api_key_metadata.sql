@@ -0,0 +1,16 @@
+-- =========================================================================================
+-- API Key Scopes, Expiry and Usage
+-- Keys carry their own scopes and an optional expiry. last_used_at lets admins find
+-- stale keys to rotate. Existing keys keep read/write access and never expire.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS api_key_scopes TEXT NOT NULL DEFAULT 'read write';
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS api_key_created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS api_key_expires_at TIMESTAMPTZ;
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS api_key_last_used_at TIMESTAMPTZ;
+
+UPDATE tenants SET api_key_created_at = created_at;
+
+COMMIT;
@@ -0,0 +1,20 @@
+-- =========================================================================================
+-- Chunk Types
+-- Distinguishes body text from table and figure caption chunks extracted from PDFs,
+-- with page coordinates kept in chunk metadata
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE chunks ADD COLUMN IF NOT EXISTS chunk_type TEXT NOT NULL DEFAULT 'text';
+ALTER TABLE chunks ADD COLUMN IF NOT EXISTS metadata JSONB NOT NULL DEFAULT '{}';
+
+ALTER TABLE chunks DROP CONSTRAINT IF EXISTS chunks_type_check;
+ALTER TABLE chunks ADD CONSTRAINT chunks_type_check
+    CHECK (chunk_type IN ('text', 'table', 'figure_caption'));
+
+-- Tables and captions are rare; index only those
+CREATE INDEX IF NOT EXISTS idx_chunks_paper_type ON chunks(paper_id, chunk_type)
+    WHERE chunk_type <> 'text';
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- =========================================================================================
+-- Crossref Metadata Resolution
+-- Tenants can opt out of DOI lookups during ingestion (e.g. for unpublished
+-- manuscripts whose DOIs shouldn't leave the deployment). On by default.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS resolve_metadata BOOLEAN NOT NULL DEFAULT true;
+
+COMMIT;
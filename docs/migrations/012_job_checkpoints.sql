@@ -0,0 +1,22 @@
+-- =========================================================================================
+-- Resumable Ingestion Jobs
+-- Each pipeline stage saves its output as a checkpoint so a retried job picks up at the
+-- stage that failed instead of re-extracting and re-chunking. `stage` keeps the last
+-- stage a job reached, including after it fails.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE ingestion_jobs ADD COLUMN IF NOT EXISTS stage TEXT NOT NULL DEFAULT 'pending';
+
+UPDATE ingestion_jobs SET stage = status WHERE status NOT IN ('completed', 'failed');
+
+CREATE TABLE IF NOT EXISTS job_checkpoints (
+    job_id UUID NOT NULL REFERENCES ingestion_jobs(id) ON DELETE CASCADE,
+    stage TEXT NOT NULL CHECK (stage IN ('extracted', 'chunked')),
+    data JSONB NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (job_id, stage)
+);
+
+COMMIT;
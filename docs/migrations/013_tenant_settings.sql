@@ -0,0 +1,18 @@
+-- =========================================================================================
+-- Per-Tenant Ingestion Settings
+-- Default chunking and embedding options the gateway applies to a tenant's ingestion jobs
+-- when a request leaves them out. NULL columns fall back to the service configuration.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS tenant_settings (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    chunk_strategy TEXT CHECK (chunk_strategy IN ('recursive', 'semantic')),
+    chunk_size INT CHECK (chunk_size BETWEEN 100 AND 10000),
+    chunk_overlap INT CHECK (chunk_overlap BETWEEN 0 AND 5000),
+    embedding_model TEXT,
+    updated_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+COMMIT;
@@ -0,0 +1,12 @@
+-- =========================================================================================
+-- Stored Source Documents
+-- Object keys of the original document and its extracted text in document storage
+-- (S3 or a local directory). NULL when storage was disabled at ingestion time.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE papers ADD COLUMN IF NOT EXISTS source_key TEXT;
+ALTER TABLE papers ADD COLUMN IF NOT EXISTS text_key TEXT;
+
+COMMIT;
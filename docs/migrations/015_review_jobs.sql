@@ -0,0 +1,27 @@
+-- =========================================================================================
+-- Literature Review Jobs
+-- A review is generated in the background: broad retrieval, clustering into sub-topics and
+-- one LLM call per section. The job row tracks progress, and the finished review is stored
+-- as JSONB.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS review_jobs (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    topic TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'retrieving', 'clustering', 'writing', 'completed', 'failed')),
+    sections_total INT NOT NULL DEFAULT 0,
+    sections_written INT NOT NULL DEFAULT 0,
+    review JSONB,
+    error_message TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_review_jobs_tenant ON review_jobs(tenant_id, created_at DESC);
+
+COMMIT;
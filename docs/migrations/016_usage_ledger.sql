@@ -0,0 +1,26 @@
+-- =========================================================================================
+-- LLM and Embedding Usage Ledger
+-- Every LLM completion and embedding call is recorded with its token counts and estimated
+-- cost, attributed to the tenant it ran for. Costs are in micro-dollars (1e-6 USD).
+-- Tenants can cap their monthly spend; calls over the cap are refused.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS usage_ledger (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('llm', 'embedding')),
+    model TEXT NOT NULL,
+    prompt_tokens INT NOT NULL DEFAULT 0,
+    completion_tokens INT NOT NULL DEFAULT 0,
+    cost_micros BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_ledger_tenant_time ON usage_ledger(tenant_id, created_at);
+
+ALTER TABLE tenant_settings
+    ADD COLUMN IF NOT EXISTS monthly_budget_micros BIGINT CHECK (monthly_budget_micros >= 0);
+
+COMMIT;
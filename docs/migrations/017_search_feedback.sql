@@ -0,0 +1,24 @@
+-- =========================================================================================
+-- Search Relevance Feedback
+-- Thumbs up/down on individual search results, tied to the query, the chunk and, when the
+-- client tracks one, the session. Hybrid search nudges chunks by their net votes; the raw
+-- events are kept as training data for learning-to-rank.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS search_feedback (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    session_id UUID REFERENCES sessions(id) ON DELETE SET NULL,
+    query TEXT NOT NULL,
+    chunk_id UUID NOT NULL,
+    rating SMALLINT NOT NULL CHECK (rating IN (-1, 1)),
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+-- chunks is partitioned, so chunk_id can't reference it; votes on deleted chunks are
+-- simply never looked up again
+CREATE INDEX IF NOT EXISTS idx_search_feedback_tenant_chunk ON search_feedback(tenant_id, chunk_id);
+
+COMMIT;
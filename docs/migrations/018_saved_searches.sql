@@ -0,0 +1,26 @@
+-- =========================================================================================
+-- Saved Searches
+-- Queries a tenant wants to follow. The ingestion worker periodically runs each one against
+-- chunks indexed since its last run and alerts the webhook and/or email address when new
+-- papers score at least min_score.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS saved_searches (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    -- Calibrated 0-1 score a new paper's best chunk must reach to alert
+    min_score DOUBLE PRECISION NOT NULL DEFAULT 0.5,
+    webhook_url TEXT,
+    webhook_secret TEXT,
+    email TEXT,
+    last_run_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_searches_tenant ON saved_searches(tenant_id);
+
+COMMIT;
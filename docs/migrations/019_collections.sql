@@ -0,0 +1,29 @@
+-- =========================================================================================
+-- Collections
+-- Named groupings of a tenant's papers ("My RL reading list"). A paper can belong to any
+-- number of collections, and searches can be scoped to one collection.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS collections (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    UNIQUE (tenant_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS collection_papers (
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    paper_id UUID NOT NULL REFERENCES papers(id) ON DELETE CASCADE,
+    added_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (collection_id, paper_id)
+);
+
+-- Collections a paper belongs to
+CREATE INDEX IF NOT EXISTS idx_collection_papers_paper ON collection_papers(paper_id);
+
+COMMIT;
@@ -0,0 +1,15 @@
+-- =========================================================================================
+-- Paper PageRank
+-- Citation PageRank scores are persisted on papers by the search service. New citations are
+-- picked up incrementally: a citation newer than its citing paper's pagerank_updated_at
+-- marks the papers downstream of it as stale until the next update.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE papers ADD COLUMN IF NOT EXISTS pagerank DOUBLE PRECISION;
+ALTER TABLE papers ADD COLUMN IF NOT EXISTS pagerank_updated_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_citations_created ON citations(created_at);
+
+COMMIT;
@@ -0,0 +1,37 @@
+-- =========================================================================================
+-- Authors
+-- Author entities linked to their papers, populated at ingestion from the paper's author
+-- metadata (submitted, or backfilled from Crossref). Authors with an ORCID iD are one
+-- entity per ORCID; authors without one are told apart by normalized name only.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS authors (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- Display name as first seen
+    name TEXT NOT NULL,
+    -- Lowercased, punctuation stripped; used for matching and search filters
+    normalized_name TEXT NOT NULL,
+    orcid TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_authors_orcid
+    ON authors(tenant_id, orcid) WHERE orcid IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_authors_name
+    ON authors(tenant_id, normalized_name) WHERE orcid IS NULL;
+
+CREATE TABLE IF NOT EXISTS paper_authors (
+    paper_id UUID NOT NULL REFERENCES papers(id) ON DELETE CASCADE,
+    author_id UUID NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+    -- Order in the paper's author list, from 1
+    position INT NOT NULL,
+    PRIMARY KEY (paper_id, author_id)
+);
+
+-- Papers of an author
+CREATE INDEX IF NOT EXISTS idx_paper_authors_author ON paper_authors(author_id);
+
+COMMIT;
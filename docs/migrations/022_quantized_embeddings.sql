@@ -0,0 +1,20 @@
+-- =========================================================================================
+-- Quantized Embedding Indexes
+-- Expression indexes for searching embeddings at reduced precision (pgvector 0.7+): halfvec
+-- (16-bit floats) and binary (one bit per dimension). Chunks keep their full-precision
+-- embedding, which search uses to rescore quantized candidates. Which index is scanned is
+-- chosen per embedding model by `embedding.storage`; indexes for unused modes, including the
+-- full-precision idx_chunks_embedding_hnsw, can be dropped to reclaim their space.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE INDEX IF NOT EXISTS idx_chunks_embedding_halfvec_hnsw ON chunks
+USING hnsw ((embedding::halfvec(768)) halfvec_cosine_ops)
+WITH (m = 16, ef_construction = 64);
+
+CREATE INDEX IF NOT EXISTS idx_chunks_embedding_binary_hnsw ON chunks
+USING hnsw ((binary_quantize(embedding)::bit(768)) bit_hamming_ops)
+WITH (m = 16, ef_construction = 64);
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- =========================================================================================
+-- Chunk Export Cursor
+-- Keyset index for GET /v2/export/chunks, which pages through a tenant's chunks in
+-- (created_at, id) order and resumes from the last pair a client received.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE INDEX IF NOT EXISTS idx_chunks_created_id ON chunks(created_at, id);
+
+COMMIT;
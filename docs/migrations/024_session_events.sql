@@ -0,0 +1,22 @@
+-- =========================================================================================
+-- Session Analytics Events
+-- What users do besides searching: result clicks, paper views, synthesis requests and any
+-- other event sent to POST /v2/sessions/:id/events. Searches themselves go to query_logs.
+-- Events outlive their session, which is swept once expired.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS session_events (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    session_id UUID REFERENCES sessions(id) ON DELETE SET NULL,
+    event_type TEXT NOT NULL,
+    data JSONB DEFAULT '{}' NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_events_tenant_type ON session_events(tenant_id, event_type, created_at);
+CREATE INDEX IF NOT EXISTS idx_session_events_session ON session_events(session_id);
+
+COMMIT;
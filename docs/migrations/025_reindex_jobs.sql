@@ -0,0 +1,31 @@
+-- =========================================================================================
+-- Re-indexing Jobs
+-- Started from POST /v2/admin/reindex after changing text-search configuration or chunking.
+-- A job rewrites chunks in batches so their generated text_search_vector is recomputed
+-- (for one tenant, or all when tenant_id is NULL), then rebuilds the vector indexes on
+-- chunks with REINDEX CONCURRENTLY. Vector indexes span all tenants.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS reindex_jobs (
+    id UUID PRIMARY KEY,
+    tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+    text_search BOOLEAN NOT NULL,
+    vector_indexes BOOLEAN NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'text_search', 'vector_indexes', 'completed', 'failed')),
+    chunks_total BIGINT NOT NULL DEFAULT 0,
+    chunks_done BIGINT NOT NULL DEFAULT 0,
+    indexes_total INT NOT NULL DEFAULT 0,
+    indexes_done INT NOT NULL DEFAULT 0,
+    requested_by TEXT NOT NULL,
+    error_message TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_reindex_jobs_created ON reindex_jobs(created_at DESC);
+
+COMMIT;
@@ -0,0 +1,12 @@
+-- =========================================================================================
+-- Failed Chunks
+-- Indexes of the chunks the embedding worker could not embed. The job's other chunks are
+-- stored and searchable; the failed ones are retried on their own a limited number of
+-- times and stay listed here if they never succeed.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE ingestion_jobs ADD COLUMN IF NOT EXISTS failed_chunks JSONB NOT NULL DEFAULT '[]';
+
+COMMIT;
@@ -0,0 +1,30 @@
+-- =========================================================================================
+-- Job Retries
+-- A failed ingestion job is re-sent once its next_retry_at passes, backing off with each
+-- attempt; jobs whose error can't be retried or whose attempts ran out become 'dead'. The
+-- job keeps the queue message that started it so the retry can send it again.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE ingestion_jobs ADD COLUMN IF NOT EXISTS queue_message JSONB;
+
+ALTER TABLE ingestion_jobs DROP CONSTRAINT IF EXISTS ingestion_jobs_status_check;
+ALTER TABLE ingestion_jobs ADD CONSTRAINT ingestion_jobs_status_check
+    CHECK (status IN ('pending', 'chunking', 'embedding', 'indexing', 'completed', 'failed', 'dead'));
+
+-- Failed jobs with a retry scheduled aren't finished yet
+CREATE OR REPLACE FUNCTION cleanup_old_jobs(retention_hours INT DEFAULT 24) 
+RETURNS INTEGER AS $$
+DECLARE
+    deleted_count INTEGER;
+BEGIN
+    DELETE FROM ingestion_jobs 
+    WHERE (status IN ('completed', 'dead') OR (status = 'failed' AND next_retry_at IS NULL))
+      AND completed_at < NOW() - (retention_hours || ' hours')::INTERVAL;
+    GET DIAGNOSTICS deleted_count = ROW_COUNT;
+    RETURN deleted_count;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMIT;
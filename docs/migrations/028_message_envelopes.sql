@@ -0,0 +1,14 @@
+-- =========================================================================================
+-- Message Envelopes
+-- Queue messages are sent with an envelope as SQS message attributes: type, schema version,
+-- tenant and trace context. Outbox rows keep the envelope to publish with, and ingestion
+-- jobs keep it next to their queue message so a retry is sent with it too.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS attributes JSONB;
+
+ALTER TABLE ingestion_jobs ADD COLUMN IF NOT EXISTS queue_attributes JSONB;
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- =========================================================================================
+-- Reference List Exclusion
+-- A paper's References/Bibliography section is left out of its chunks and only parsed for
+-- citations. Tenants can override the service default; NULL keeps it.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE tenant_settings ADD COLUMN IF NOT EXISTS exclude_references BOOLEAN;
+
+COMMIT;
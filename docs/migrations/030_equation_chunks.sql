@@ -0,0 +1,12 @@
+-- =========================================================================================
+-- Equation Chunks
+-- Display equations extracted from PDFs are kept as chunks of their own
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE chunks DROP CONSTRAINT IF EXISTS chunks_type_check;
+ALTER TABLE chunks ADD CONSTRAINT chunks_type_check
+    CHECK (chunk_type IN ('text', 'table', 'figure_caption', 'equation'));
+
+COMMIT;
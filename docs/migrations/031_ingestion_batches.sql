@@ -0,0 +1,25 @@
+-- =========================================================================================
+-- Ingestion Batches
+-- An uploaded zip/tar archive of PDFs becomes a batch with one ingestion job per document;
+-- entries left out (not a PDF, too large) are recorded on the batch.
+-- =========================================================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS ingestion_batches (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    archive_name TEXT,
+    document_count INT NOT NULL DEFAULT 0,
+    skipped JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_batches_tenant ON ingestion_batches(tenant_id, created_at DESC);
+
+ALTER TABLE ingestion_jobs
+    ADD COLUMN IF NOT EXISTS batch_id UUID REFERENCES ingestion_batches(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_batch ON ingestion_jobs(batch_id) WHERE batch_id IS NOT NULL;
+
+COMMIT;
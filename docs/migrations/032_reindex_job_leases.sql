@@ -0,0 +1,28 @@
+-- =========================================================================================
+-- Re-indexing Job Leases
+-- Jobs are run by a gateway worker that renews heartbeat_at while it works; an active job
+-- whose heartbeat lapses lost its worker and is marked failed. At most one job is active,
+-- enforced by a unique index over the active rows so concurrent requests can't both start.
+-- =========================================================================================
+
+BEGIN;
+
+ALTER TABLE reindex_jobs ADD COLUMN IF NOT EXISTS heartbeat_at TIMESTAMPTZ;
+
+-- Jobs left active by earlier releases were run by tasks that may be gone; keep only the newest
+UPDATE reindex_jobs SET
+    status = 'failed',
+    error_message = 'Superseded by a newer re-indexing job',
+    completed_at = NOW()
+WHERE status NOT IN ('completed', 'failed')
+AND id <> (
+    SELECT id FROM reindex_jobs
+    WHERE status NOT IN ('completed', 'failed')
+    ORDER BY created_at DESC
+    LIMIT 1
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reindex_jobs_one_active ON reindex_jobs ((true))
+    WHERE status NOT IN ('completed', 'failed');
+
+COMMIT;
@@ -18,14 +18,44 @@ CREATE EXTENSION IF NOT EXISTS "pg_trgm";
 CREATE TABLE IF NOT EXISTS tenants (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     name TEXT NOT NULL UNIQUE,
+    -- argon2id PHC string (legacy rows hold unsalted SHA-256 hex until the
+    -- key is next used)
     api_key_hash TEXT NOT NULL,
+    -- Leading characters of the key, used to find candidate rows
+    api_key_prefix TEXT,
+    -- Space-separated scopes granted to the API key
+    api_key_scopes TEXT NOT NULL DEFAULT 'read write',
+    api_key_created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    -- NULL means the key never expires
+    api_key_expires_at TIMESTAMPTZ,
+    -- Updated asynchronously, at most about once a minute per key
+    api_key_last_used_at TIMESTAMPTZ,
+    -- Shared secret for HMAC request signing (NULL disables signed requests)
+    signing_secret TEXT,
     rate_limit_rps INT DEFAULT 100,
+    -- Backfill paper metadata from Crossref when a DOI is found
+    resolve_metadata BOOLEAN NOT NULL DEFAULT true,
     is_active BOOLEAN DEFAULT true,
     created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
     updated_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
 );
 
 CREATE INDEX IF NOT EXISTS idx_tenants_api_key ON tenants(api_key_hash) WHERE is_active = true;
+CREATE INDEX IF NOT EXISTS idx_tenants_api_key_prefix ON tenants(api_key_prefix) WHERE is_active = true;
+
+-- Default ingestion options per tenant; NULL falls back to the service config
+CREATE TABLE IF NOT EXISTS tenant_settings (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    chunk_strategy TEXT CHECK (chunk_strategy IN ('recursive', 'semantic')),
+    chunk_size INT CHECK (chunk_size BETWEEN 100 AND 10000),
+    chunk_overlap INT CHECK (chunk_overlap BETWEEN 0 AND 5000),
+    embedding_model TEXT,
+    -- Monthly LLM and embedding spend cap in micro-dollars; NULL is unlimited
+    monthly_budget_micros BIGINT CHECK (monthly_budget_micros >= 0),
+    -- Leave the reference list out of chunks; NULL uses the service default
+    exclude_references BOOLEAN,
+    updated_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
 
 -- =========================================================================
 -- EMBEDDING MODELS REGISTRY
@@ -67,6 +97,17 @@ CREATE TABLE IF NOT EXISTS papers (
     created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
     updated_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
     
+    -- Soft delete marker (NULL = live); purged after retention period
+    deleted_at TIMESTAMPTZ,
+    
+    -- Document storage keys of the original document and extracted text
+    source_key TEXT,
+    text_key TEXT,
+    
+    -- Citation PageRank (about 1 for an average paper) and when it was computed
+    pagerank DOUBLE PRECISION,
+    pagerank_updated_at TIMESTAMPTZ,
+    
     CONSTRAINT papers_tenant_external_unique UNIQUE(tenant_id, external_id),
     CONSTRAINT papers_tenant_idempotency_unique UNIQUE(tenant_id, idempotency_key)
 );
@@ -78,6 +119,7 @@ CREATE INDEX IF NOT EXISTS idx_papers_published ON papers(published_at);
 CREATE INDEX IF NOT EXISTS idx_papers_created ON papers(created_at);
 CREATE INDEX IF NOT EXISTS idx_papers_source ON papers(source);
 CREATE INDEX IF NOT EXISTS idx_papers_metadata ON papers USING GIN(metadata);
+CREATE INDEX IF NOT EXISTS idx_papers_deleted ON papers(deleted_at) WHERE deleted_at IS NOT NULL;
 
 -- Full-text search on title
 CREATE INDEX IF NOT EXISTS idx_papers_title_fts ON papers USING GIN(to_tsvector('english', title));
@@ -105,18 +147,28 @@ CREATE TABLE IF NOT EXISTS chunks (
     char_offset_start INT,
     char_offset_end INT,
     
+    -- Chunk kind: text, table, figure_caption or equation
+    chunk_type TEXT NOT NULL DEFAULT 'text',
+    
+    -- Source location (page, bounding box) for table and caption chunks
+    metadata JSONB NOT NULL DEFAULT '{}',
+    
     -- Generated full-text search vector
     text_search_vector tsvector GENERATED ALWAYS AS (to_tsvector('english', content)) STORED,
     
     created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
     
-    CONSTRAINT chunks_paper_index_unique UNIQUE(paper_id, chunk_index)
+    CONSTRAINT chunks_paper_index_unique UNIQUE(paper_id, chunk_index),
+    CONSTRAINT chunks_type_check CHECK (chunk_type IN ('text', 'table', 'figure_caption', 'equation'))
 );
 
 -- Indexes for chunks
 CREATE INDEX IF NOT EXISTS idx_chunks_paper ON chunks(paper_id);
 CREATE INDEX IF NOT EXISTS idx_chunks_model_version ON chunks(embedding_model, embedding_version);
 CREATE INDEX IF NOT EXISTS idx_chunks_created ON chunks(created_at);
+CREATE INDEX IF NOT EXISTS idx_chunks_created_id ON chunks(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_chunks_paper_type ON chunks(paper_id, chunk_type)
+    WHERE chunk_type <> 'text';
 
 -- Vector similarity search index (HNSW for better performance)
 -- m = number of bidirectional links (higher = better recall, more memory)
@@ -125,6 +177,16 @@ CREATE INDEX IF NOT EXISTS idx_chunks_embedding_hnsw ON chunks
 USING hnsw (embedding vector_cosine_ops) 
 WITH (m = 16, ef_construction = 64);
 
+-- Quantized indexes for embedding.storage = halfvec / binary; search rescores
+-- their candidates on the full-precision embedding
+CREATE INDEX IF NOT EXISTS idx_chunks_embedding_halfvec_hnsw ON chunks
+USING hnsw ((embedding::halfvec(768)) halfvec_cosine_ops)
+WITH (m = 16, ef_construction = 64);
+
+CREATE INDEX IF NOT EXISTS idx_chunks_embedding_binary_hnsw ON chunks
+USING hnsw ((binary_quantize(embedding)::bit(768)) bit_hamming_ops)
+WITH (m = 16, ef_construction = 64);
+
 -- Full-text search index
 CREATE INDEX IF NOT EXISTS idx_chunks_content_fts ON chunks USING GIN(text_search_vector);
 
@@ -149,27 +211,51 @@ CREATE TABLE IF NOT EXISTS citations (
 -- Indexes for citations (bidirectional traversal)
 CREATE INDEX IF NOT EXISTS idx_citations_citing ON citations(citing_paper_id);
 CREATE INDEX IF NOT EXISTS idx_citations_cited ON citations(cited_paper_id);
+CREATE INDEX IF NOT EXISTS idx_citations_created ON citations(created_at);
 
 -- =========================================================================
 -- INGESTION JOBS TABLE (Async tracking)
 -- =========================================================================
+CREATE TABLE IF NOT EXISTS ingestion_batches (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- File name of the uploaded archive, when given
+    archive_name TEXT,
+    document_count INT NOT NULL DEFAULT 0,
+    -- Archive entries not ingested, with the reason: [{"name", "reason"}]
+    skipped JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_batches_tenant ON ingestion_batches(tenant_id, created_at DESC);
+
 CREATE TABLE IF NOT EXISTS ingestion_jobs (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
     paper_id UUID REFERENCES papers(id) ON DELETE SET NULL,
+    -- Upload batch the job's document came in with
+    batch_id UUID REFERENCES ingestion_batches(id) ON DELETE SET NULL,
     
-    status TEXT NOT NULL CHECK (status IN ('pending', 'chunking', 'embedding', 'indexing', 'completed', 'failed')),
+    status TEXT NOT NULL CHECK (status IN ('pending', 'chunking', 'embedding', 'indexing', 'completed', 'failed', 'dead')),
+    -- Last non-terminal status reached; kept when the job fails
+    stage TEXT NOT NULL DEFAULT 'pending',
     
     chunks_total INT DEFAULT 0,
     chunks_processed INT DEFAULT 0,
     error_message TEXT,
     
+    -- Indexes of chunks that could not be embedded
+    failed_chunks JSONB NOT NULL DEFAULT '[]',
+    
     -- Idempotency key (unique per tenant)
     idempotency_key TEXT,
     
-    -- Retry tracking
+    -- Retry tracking: a failed job is re-sent at next_retry_at; 'dead' jobs are not
     attempt_count INT DEFAULT 0,
     next_retry_at TIMESTAMPTZ,
+    -- Queue message that started the job, re-sent on retry, and its envelope
+    queue_message JSONB,
+    queue_attributes JSONB,
     
     created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
     started_at TIMESTAMPTZ,
@@ -182,9 +268,89 @@ CREATE TABLE IF NOT EXISTS ingestion_jobs (
 CREATE INDEX IF NOT EXISTS idx_jobs_tenant_status ON ingestion_jobs(tenant_id, status);
 CREATE INDEX IF NOT EXISTS idx_jobs_status ON ingestion_jobs(status);
 CREATE INDEX IF NOT EXISTS idx_jobs_paper ON ingestion_jobs(paper_id);
+CREATE INDEX IF NOT EXISTS idx_jobs_batch ON ingestion_jobs(batch_id) WHERE batch_id IS NOT NULL;
 CREATE INDEX IF NOT EXISTS idx_jobs_pending ON ingestion_jobs(status, next_retry_at) 
     WHERE status IN ('pending', 'failed');
 
+-- Stage outputs a retried job resumes from
+CREATE TABLE IF NOT EXISTS job_checkpoints (
+    job_id UUID NOT NULL REFERENCES ingestion_jobs(id) ON DELETE CASCADE,
+    stage TEXT NOT NULL CHECK (stage IN ('extracted', 'chunked')),
+    data JSONB NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (job_id, stage)
+);
+
+-- =========================================================================
+-- OUTBOX TABLE (Transactional queue publishing)
+-- =========================================================================
+CREATE TABLE IF NOT EXISTS outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    
+    -- Logical queue name ('ingestion', 'embedding')
+    queue TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    -- Envelope (type, schema_version, tenant_id, traceparent) sent as message attributes
+    attributes JSONB,
+    
+    -- Relay bookkeeping
+    attempts INT DEFAULT 0 NOT NULL,
+    last_error TEXT,
+    locked_until TIMESTAMPTZ,
+    
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    published_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_pending ON outbox(created_at) WHERE published_at IS NULL;
+
+-- =========================================================================
+-- REFRESH TOKENS TABLE (JWT issuance)
+-- =========================================================================
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    
+    -- Tokens issued by rotation share a family; reuse of a rotated token
+    -- revokes the whole family
+    family_id UUID NOT NULL,
+    
+    -- SHA-256 of the token; the token itself is never stored
+    token_hash TEXT NOT NULL UNIQUE,
+    
+    -- Space-separated scopes granted to access tokens minted from this token
+    scopes TEXT DEFAULT '' NOT NULL,
+    
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    replaced_by UUID,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_family ON refresh_tokens(family_id);
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_expires ON refresh_tokens(expires_at);
+
+-- =========================================================================
+-- AUDIT LOGS TABLE (mutating operations)
+-- =========================================================================
+CREATE TABLE IF NOT EXISTS audit_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- No foreign key: audit history outlives the tenant
+    tenant_id UUID,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    resource_type TEXT NOT NULL,
+    resource_id TEXT,
+    request_id TEXT,
+    before JSONB,
+    after JSONB,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_logs_tenant ON audit_logs(tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_resource ON audit_logs(resource_type, resource_id);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_action ON audit_logs(action, created_at DESC);
+
 -- =========================================================================
 -- SESSIONS TABLE (Context Engine)
 -- =========================================================================
@@ -204,6 +370,50 @@ CREATE TABLE IF NOT EXISTS sessions (
 CREATE INDEX IF NOT EXISTS idx_sessions_tenant ON sessions(tenant_id);
 CREATE INDEX IF NOT EXISTS idx_sessions_expires ON sessions(expires_at);
 
+-- Literature reviews generated in the background
+CREATE TABLE IF NOT EXISTS review_jobs (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    topic TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'retrieving', 'clustering', 'writing', 'completed', 'failed')),
+    sections_total INT NOT NULL DEFAULT 0,
+    sections_written INT NOT NULL DEFAULT 0,
+    review JSONB,
+    error_message TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_review_jobs_tenant ON review_jobs(tenant_id, created_at DESC);
+
+-- Text-search and vector index rebuilds started by an admin; a NULL tenant_id
+-- covers all tenants
+CREATE TABLE IF NOT EXISTS reindex_jobs (
+    id UUID PRIMARY KEY,
+    tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+    text_search BOOLEAN NOT NULL,
+    vector_indexes BOOLEAN NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'text_search', 'vector_indexes', 'completed', 'failed')),
+    chunks_total BIGINT NOT NULL DEFAULT 0,
+    chunks_done BIGINT NOT NULL DEFAULT 0,
+    indexes_total INT NOT NULL DEFAULT 0,
+    indexes_done INT NOT NULL DEFAULT 0,
+    requested_by TEXT NOT NULL,
+    error_message TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    heartbeat_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_reindex_jobs_created ON reindex_jobs(created_at DESC);
+-- At most one job is pending or running
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reindex_jobs_one_active ON reindex_jobs ((true))
+    WHERE status NOT IN ('completed', 'failed');
+
 -- =========================================================================
 -- QUERY LOG TABLE (Analytics)
 -- =========================================================================
@@ -228,6 +438,111 @@ CREATE TABLE IF NOT EXISTS query_logs (
 CREATE INDEX IF NOT EXISTS idx_query_logs_tenant ON query_logs(tenant_id, created_at);
 CREATE INDEX IF NOT EXISTS idx_query_logs_hash ON query_logs(query_hash);
 
+-- Session events other than searches: result clicks, paper views, synthesis
+-- requests
+CREATE TABLE IF NOT EXISTS session_events (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    session_id UUID REFERENCES sessions(id) ON DELETE SET NULL,
+    event_type TEXT NOT NULL,
+    data JSONB DEFAULT '{}' NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_events_tenant_type ON session_events(tenant_id, event_type, created_at);
+CREATE INDEX IF NOT EXISTS idx_session_events_session ON session_events(session_id);
+
+-- Thumbs up/down on search results; chunks is partitioned, so chunk_id has
+-- no foreign key
+CREATE TABLE IF NOT EXISTS search_feedback (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    session_id UUID REFERENCES sessions(id) ON DELETE SET NULL,
+    query TEXT NOT NULL,
+    chunk_id UUID NOT NULL,
+    rating SMALLINT NOT NULL CHECK (rating IN (-1, 1)),
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_feedback_tenant_chunk ON search_feedback(tenant_id, chunk_id);
+
+-- Followed queries; new papers matching them trigger webhook/email alerts
+CREATE TABLE IF NOT EXISTS saved_searches (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    min_score DOUBLE PRECISION NOT NULL DEFAULT 0.5,
+    webhook_url TEXT,
+    webhook_secret TEXT,
+    email TEXT,
+    last_run_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_searches_tenant ON saved_searches(tenant_id);
+
+-- Named paper groupings; searches can be scoped to one collection
+CREATE TABLE IF NOT EXISTS collections (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    UNIQUE (tenant_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS collection_papers (
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    paper_id UUID NOT NULL REFERENCES papers(id) ON DELETE CASCADE,
+    added_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (collection_id, paper_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_papers_paper ON collection_papers(paper_id);
+
+-- Authors; one per ORCID iD, otherwise one per normalized name
+CREATE TABLE IF NOT EXISTS authors (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    normalized_name TEXT NOT NULL,
+    orcid TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_authors_orcid
+    ON authors(tenant_id, orcid) WHERE orcid IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_authors_name
+    ON authors(tenant_id, normalized_name) WHERE orcid IS NULL;
+
+CREATE TABLE IF NOT EXISTS paper_authors (
+    paper_id UUID NOT NULL REFERENCES papers(id) ON DELETE CASCADE,
+    author_id UUID NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    PRIMARY KEY (paper_id, author_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_paper_authors_author ON paper_authors(author_id);
+
+-- =========================================================================
+-- USAGE LEDGER (LLM and embedding cost attribution)
+-- =========================================================================
+CREATE TABLE IF NOT EXISTS usage_ledger (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('llm', 'embedding')),
+    model TEXT NOT NULL,
+    prompt_tokens INT NOT NULL DEFAULT 0,
+    completion_tokens INT NOT NULL DEFAULT 0,
+    -- Estimated cost in micro-dollars (1e-6 USD)
+    cost_micros BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_ledger_tenant_time ON usage_ledger(tenant_id, created_at);
+
 -- =========================================================================
 -- USEFUL VIEWS
 -- =========================================================================
@@ -245,6 +560,7 @@ SELECT
     COALESCE(SUM(c.token_count), 0) AS total_tokens
 FROM papers p
 LEFT JOIN chunks c ON p.id = c.paper_id
+WHERE p.deleted_at IS NULL
 GROUP BY p.id;
 
 -- Chunks needing re-embedding (model version change)
@@ -252,8 +568,9 @@ CREATE OR REPLACE VIEW chunks_needing_reembed AS
 SELECT c.*, p.title AS paper_title, p.tenant_id
 FROM chunks c
 JOIN papers p ON c.paper_id = p.id
-WHERE c.embedding_model != (SELECT name FROM embedding_models WHERE is_default = true LIMIT 1)
-   OR c.embedding IS NULL;
+WHERE p.deleted_at IS NULL
+  AND (c.embedding_model != (SELECT name FROM embedding_models WHERE is_default = true LIMIT 1)
+   OR c.embedding IS NULL);
 
 -- Citation stats per paper
 CREATE OR REPLACE VIEW citation_stats AS
@@ -286,13 +603,41 @@ DECLARE
     deleted_count INTEGER;
 BEGIN
     DELETE FROM ingestion_jobs 
-    WHERE status IN ('completed', 'failed') 
+    WHERE (status IN ('completed', 'dead') OR (status = 'failed' AND next_retry_at IS NULL))
       AND completed_at < NOW() - (retention_hours || ' hours')::INTERVAL;
     GET DIAGNOSTICS deleted_count = ROW_COUNT;
     RETURN deleted_count;
 END;
 $$ LANGUAGE plpgsql;
 
+-- Purge soft-deleted papers (chunks and citations cascade)
+CREATE OR REPLACE FUNCTION purge_deleted_papers(retention_days INT DEFAULT 30) 
+RETURNS INTEGER AS $$
+DECLARE
+    deleted_count INTEGER;
+BEGIN
+    DELETE FROM papers 
+    WHERE deleted_at IS NOT NULL 
+      AND deleted_at < NOW() - (retention_days || ' days')::INTERVAL;
+    GET DIAGNOSTICS deleted_count = ROW_COUNT;
+    RETURN deleted_count;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Cleanup published outbox rows
+CREATE OR REPLACE FUNCTION cleanup_published_outbox(retention_hours INT DEFAULT 24) 
+RETURNS INTEGER AS $$
+DECLARE
+    deleted_count INTEGER;
+BEGIN
+    DELETE FROM outbox 
+    WHERE published_at IS NOT NULL 
+      AND published_at < NOW() - (retention_hours || ' hours')::INTERVAL;
+    GET DIAGNOSTICS deleted_count = ROW_COUNT;
+    RETURN deleted_count;
+END;
+$$ LANGUAGE plpgsql;
+
 -- Cleanup expired sessions
 CREATE OR REPLACE FUNCTION cleanup_expired_sessions() 
 RETURNS INTEGER AS $$
@@ -364,9 +709,11 @@ VALUES (
 ) ON CONFLICT (name) DO NOTHING;
 
 COMMENT ON TABLE tenants IS 'Multi-tenant organization accounts';
+COMMENT ON TABLE tenant_settings IS 'Per-tenant default ingestion options';
 COMMENT ON TABLE papers IS 'Research papers with metadata';
 COMMENT ON TABLE chunks IS 'Text chunks with embeddings for vector search';
 COMMENT ON TABLE citations IS 'Citation graph between papers';
 COMMENT ON TABLE ingestion_jobs IS 'Async ingestion job tracking';
+COMMENT ON TABLE ingestion_batches IS 'Archive uploads, one ingestion job per document';
 COMMENT ON TABLE sessions IS 'User session state for context engine';
 COMMENT ON TABLE query_logs IS 'Query analytics and feedback tracking';